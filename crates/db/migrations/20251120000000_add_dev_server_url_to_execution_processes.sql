@@ -0,0 +1,7 @@
+-- Dev server processes record the first listen URL found in their output
+-- and whether the output reported a port conflict (EADDRINUSE)
+ALTER TABLE execution_processes
+    ADD COLUMN dev_server_url TEXT;
+
+ALTER TABLE execution_processes
+    ADD COLUMN dev_server_port_conflict BOOLEAN NOT NULL DEFAULT 0;
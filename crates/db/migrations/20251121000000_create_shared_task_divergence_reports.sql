@@ -0,0 +1,12 @@
+PRAGMA foreign_keys = ON;
+
+-- Latest consistency check per linked remote project. The id lists keep the
+-- last divergence that was found and repaired, checked_at advances on every run.
+CREATE TABLE IF NOT EXISTS shared_task_divergence_reports (
+    remote_project_id   BLOB PRIMARY KEY,
+    mismatched_task_ids TEXT NOT NULL DEFAULT '[]',
+    missing_task_ids    TEXT NOT NULL DEFAULT '[]',
+    stray_task_ids      TEXT NOT NULL DEFAULT '[]',
+    detected_at         TEXT,
+    checked_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
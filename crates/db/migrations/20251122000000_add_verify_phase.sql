@@ -0,0 +1,38 @@
+-- Optional verify command that runs between the coding agent and cleanup
+ALTER TABLE projects ADD COLUMN verify_script TEXT;
+ALTER TABLE projects ADD COLUMN verify_timeout_secs INTEGER;
+
+-- Outcome of the latest verify run for an attempt, and the tail of its
+-- output when it did not pass
+ALTER TABLE task_attempts
+  ADD COLUMN verify_status TEXT
+    CHECK (verify_status IN ('running', 'passed', 'failed', 'timed_out'));
+ALTER TABLE task_attempts ADD COLUMN verify_output TEXT;
+
+-- Widen the run_reason CHECK to allow verify script processes
+-- 1. Add the replacement column with the wider CHECK
+ALTER TABLE execution_processes
+  ADD COLUMN run_reason_new TEXT NOT NULL DEFAULT 'setupscript'
+    CHECK (run_reason_new IN ('setupscript',
+                              'cleanupscript',
+                              'codingagent',
+                              'devserver',
+                              'verifyscript'));
+
+-- 2. Copy existing values across
+UPDATE execution_processes
+  SET run_reason_new = run_reason;
+
+-- 3. Drop any indexes that mention the old column
+DROP INDEX IF EXISTS idx_execution_processes_type;
+
+-- 4. Remove the old column
+ALTER TABLE execution_processes DROP COLUMN run_reason;
+
+-- 5. Rename the new column back to the canonical name
+ALTER TABLE execution_processes
+  RENAME COLUMN run_reason_new TO run_reason;
+
+-- 6. Re-create the index
+CREATE INDEX idx_execution_processes_type
+        ON execution_processes(run_reason);
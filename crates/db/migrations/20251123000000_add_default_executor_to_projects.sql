@@ -0,0 +1,2 @@
+-- Executor profile (EXECUTOR[:VARIANT]) used for attempts started without one
+ALTER TABLE projects ADD COLUMN default_executor TEXT;
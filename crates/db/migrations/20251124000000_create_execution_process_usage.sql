@@ -0,0 +1,14 @@
+PRAGMA foreign_keys = ON;
+
+-- Token totals reported by a coding agent run. Processes whose agent does not
+-- report usage have no row.
+CREATE TABLE IF NOT EXISTS execution_process_usage (
+    execution_process_id        BLOB PRIMARY KEY,
+    input_tokens                INTEGER NOT NULL DEFAULT 0,
+    output_tokens               INTEGER NOT NULL DEFAULT 0,
+    cache_read_input_tokens     INTEGER NOT NULL DEFAULT 0,
+    cache_creation_input_tokens INTEGER NOT NULL DEFAULT 0,
+    cost_usd                    REAL,
+    created_at                  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
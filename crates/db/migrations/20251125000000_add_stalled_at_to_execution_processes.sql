@@ -0,0 +1,4 @@
+-- Set while a running process has produced no output for longer than the
+-- stall timeout, cleared again when output resumes
+ALTER TABLE execution_processes
+    ADD COLUMN stalled_at TEXT;
@@ -0,0 +1,17 @@
+PRAGMA foreign_keys = ON;
+
+-- Per-project cap on coding agent runs; NULL leaves only the global limit
+ALTER TABLE projects
+    ADD COLUMN max_concurrent_executions INTEGER;
+
+-- Attempts waiting for a free execution slot, started in position order
+CREATE TABLE IF NOT EXISTS queued_attempts (
+    task_attempt_id     BLOB PRIMARY KEY,
+    executor_profile_id TEXT NOT NULL, -- JSON ExecutorProfileId
+    position            INTEGER NOT NULL,
+    created_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_queued_attempts_position
+    ON queued_attempts (position);
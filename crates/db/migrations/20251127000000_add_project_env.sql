@@ -0,0 +1,4 @@
+-- Extra environment variables for executor processes, as a JSON object of
+-- name to value. Values may reference the server's environment as ${VAR}.
+ALTER TABLE projects
+    ADD COLUMN env TEXT;
@@ -0,0 +1,21 @@
+-- Reusable follow-up prompts with {placeholders}. A NULL project_id makes the
+-- template available in every project.
+CREATE TABLE prompt_templates (
+    id          BLOB PRIMARY KEY,
+    project_id  BLOB,
+    name        TEXT NOT NULL CHECK(name != ''),
+    body        TEXT NOT NULL CHECK(body != ''),
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_prompt_templates_project_id ON prompt_templates(project_id);
+
+CREATE UNIQUE INDEX idx_prompt_templates_unique_name_project
+    ON prompt_templates(project_id, name)
+    WHERE project_id IS NOT NULL;
+
+CREATE UNIQUE INDEX idx_prompt_templates_unique_name_global
+    ON prompt_templates(name)
+    WHERE project_id IS NULL;
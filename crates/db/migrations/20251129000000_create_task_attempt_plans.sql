@@ -0,0 +1,12 @@
+-- The latest plan presented by a plan-mode run of an attempt, split into
+-- steps. `steps` is a JSON array of {number, text}.
+CREATE TABLE task_attempt_plans (
+    task_attempt_id      BLOB PRIMARY KEY,
+    execution_process_id BLOB NOT NULL,
+    plan                 TEXT NOT NULL,
+    steps                TEXT NOT NULL,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
@@ -0,0 +1,14 @@
+-- Shared task changes that could not be sent to the remote service, replayed
+-- in order once it is reachable again
+CREATE TABLE shared_task_outbox (
+    id              BLOB PRIMARY KEY,
+    op_type         TEXT NOT NULL CHECK (op_type IN ('update', 'delete')),
+    shared_task_id  BLOB NOT NULL,
+    payload         TEXT NOT NULL,
+    attempt_count   INTEGER NOT NULL DEFAULT 0,
+    last_error      TEXT,
+    created_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_shared_task_outbox_created_at ON shared_task_outbox(created_at);
+CREATE INDEX idx_shared_task_outbox_shared_task_id ON shared_task_outbox(shared_task_id);
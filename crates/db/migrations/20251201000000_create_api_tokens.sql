@@ -0,0 +1,13 @@
+-- Bearer tokens for scripting against the local API. Only a salted SHA-256 of
+-- the secret is kept; the full value is shown once when the token is created.
+CREATE TABLE api_tokens (
+    id            BLOB PRIMARY KEY,
+    name          TEXT NOT NULL CHECK(name != ''),
+    token_hash    TEXT NOT NULL,
+    salt          TEXT NOT NULL,
+    scope         TEXT NOT NULL CHECK (scope IN ('read_only', 'task_execution')),
+    expires_at    TEXT,
+    revoked_at    TEXT,
+    last_used_at  TEXT,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
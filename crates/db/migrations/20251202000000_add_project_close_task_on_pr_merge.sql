@@ -0,0 +1,5 @@
+PRAGMA foreign_keys = ON;
+
+-- Whether the PR monitor moves a task to done when its attempt's PR merges
+ALTER TABLE projects
+    ADD COLUMN close_task_on_pr_merge INTEGER NOT NULL DEFAULT 1;
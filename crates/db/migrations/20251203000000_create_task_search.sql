@@ -0,0 +1,136 @@
+-- Full-text index for searching tasks across projects. Local tasks are keyed
+-- by tasks.rowid and shared tasks by the negated shared_tasks.rowid, so the
+-- two never collide. `summary` holds the latest assistant summary of the
+-- task's most recent attempt that has one.
+CREATE VIRTUAL TABLE IF NOT EXISTS task_search USING fts5(
+    title,
+    description,
+    summary,
+    tokenize = 'porter unicode61 remove_diacritics 2'
+);
+
+CREATE TRIGGER IF NOT EXISTS trg_task_search_tasks_insert
+AFTER INSERT ON tasks
+BEGIN
+    INSERT INTO task_search (rowid, title, description, summary)
+    VALUES (NEW.rowid, NEW.title, NEW.description, NULL);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_task_search_tasks_update
+AFTER UPDATE OF title, description ON tasks
+BEGIN
+    UPDATE task_search
+    SET title = NEW.title, description = NEW.description
+    WHERE rowid = NEW.rowid;
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_task_search_tasks_delete
+AFTER DELETE ON tasks
+BEGIN
+    DELETE FROM task_search WHERE rowid = OLD.rowid;
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_task_search_shared_tasks_insert
+AFTER INSERT ON shared_tasks
+BEGIN
+    INSERT INTO task_search (rowid, title, description, summary)
+    VALUES (-NEW.rowid, NEW.title, NEW.description, NULL);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_task_search_shared_tasks_update
+AFTER UPDATE OF title, description ON shared_tasks
+BEGIN
+    UPDATE task_search
+    SET title = NEW.title, description = NEW.description
+    WHERE rowid = -NEW.rowid;
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_task_search_shared_tasks_delete
+AFTER DELETE ON shared_tasks
+BEGIN
+    DELETE FROM task_search WHERE rowid = -OLD.rowid;
+END;
+
+-- Summaries are written after the session row is created, but attempts and
+-- their sessions can also be deleted, so every change recomputes the summary
+CREATE TRIGGER IF NOT EXISTS trg_task_search_summary_insert
+AFTER INSERT ON executor_sessions
+WHEN NEW.summary IS NOT NULL
+BEGIN
+    UPDATE task_search
+    SET summary = (
+        SELECT es.summary
+        FROM executor_sessions es
+        JOIN task_attempts ta ON ta.id = es.task_attempt_id
+        WHERE ta.task_id = (SELECT task_id FROM task_attempts WHERE id = NEW.task_attempt_id)
+          AND es.summary IS NOT NULL
+        ORDER BY ta.created_at DESC, es.created_at DESC
+        LIMIT 1
+    )
+    WHERE rowid = (
+        SELECT t.rowid FROM tasks t
+        JOIN task_attempts ta ON ta.task_id = t.id
+        WHERE ta.id = NEW.task_attempt_id
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_task_search_summary_update
+AFTER UPDATE OF summary ON executor_sessions
+BEGIN
+    UPDATE task_search
+    SET summary = (
+        SELECT es.summary
+        FROM executor_sessions es
+        JOIN task_attempts ta ON ta.id = es.task_attempt_id
+        WHERE ta.task_id = (SELECT task_id FROM task_attempts WHERE id = NEW.task_attempt_id)
+          AND es.summary IS NOT NULL
+        ORDER BY ta.created_at DESC, es.created_at DESC
+        LIMIT 1
+    )
+    WHERE rowid = (
+        SELECT t.rowid FROM tasks t
+        JOIN task_attempts ta ON ta.task_id = t.id
+        WHERE ta.id = NEW.task_attempt_id
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_task_search_summary_delete
+AFTER DELETE ON executor_sessions
+WHEN OLD.summary IS NOT NULL
+BEGIN
+    UPDATE task_search
+    SET summary = (
+        SELECT es.summary
+        FROM executor_sessions es
+        JOIN task_attempts ta ON ta.id = es.task_attempt_id
+        WHERE ta.task_id = (SELECT task_id FROM task_attempts WHERE id = OLD.task_attempt_id)
+          AND es.summary IS NOT NULL
+        ORDER BY ta.created_at DESC, es.created_at DESC
+        LIMIT 1
+    )
+    WHERE rowid = (
+        SELECT t.rowid FROM tasks t
+        JOIN task_attempts ta ON ta.task_id = t.id
+        WHERE ta.id = OLD.task_attempt_id
+    );
+END;
+
+-- Backfill existing rows
+INSERT INTO task_search (rowid, title, description, summary)
+SELECT
+    t.rowid,
+    t.title,
+    t.description,
+    (
+        SELECT es.summary
+        FROM executor_sessions es
+        JOIN task_attempts ta ON ta.id = es.task_attempt_id
+        WHERE ta.task_id = t.id AND es.summary IS NOT NULL
+        ORDER BY ta.created_at DESC, es.created_at DESC
+        LIMIT 1
+    )
+FROM tasks t;
+
+INSERT INTO task_search (rowid, title, description, summary)
+SELECT -st.rowid, st.title, st.description, NULL
+FROM shared_tasks st;
@@ -0,0 +1,20 @@
+PRAGMA foreign_keys = ON;
+
+-- A task should not start until every task it depends on is done
+CREATE TABLE IF NOT EXISTS task_dependencies (
+    task_id            BLOB NOT NULL,
+    depends_on_task_id BLOB NOT NULL,
+    created_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (task_id, depends_on_task_id),
+    CHECK (task_id != depends_on_task_id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (depends_on_task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_dependencies_depends_on_task_id
+    ON task_dependencies (depends_on_task_id);
+
+-- Attempts started with `override` keep ignoring dependencies while they
+-- wait for an execution slot
+ALTER TABLE queued_attempts
+    ADD COLUMN ignore_dependencies BOOLEAN NOT NULL DEFAULT FALSE;
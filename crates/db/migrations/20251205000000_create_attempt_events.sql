@@ -0,0 +1,13 @@
+-- Lifecycle history of an attempt beyond what execution processes record,
+-- such as its worktree being created or its PR being opened
+CREATE TABLE IF NOT EXISTS attempt_events (
+    id              BLOB PRIMARY KEY,
+    task_attempt_id BLOB NOT NULL,
+    event_type      TEXT NOT NULL,
+    payload         TEXT NOT NULL DEFAULT '{}', -- JSON object, depends on event_type
+    created_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_attempt_events_task_attempt_id_created_at
+    ON attempt_events (task_attempt_id, created_at);
@@ -0,0 +1,3 @@
+-- Archived projects are hidden from the project list, take no new tasks or
+-- attempts, and are left out of shared task sync
+ALTER TABLE projects ADD COLUMN archived_at TEXT;
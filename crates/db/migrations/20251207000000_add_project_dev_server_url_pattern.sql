@@ -0,0 +1,3 @@
+-- Regex tried before the built-in patterns when looking for the URL a dev
+-- server listens on
+ALTER TABLE projects ADD COLUMN dev_server_url_pattern TEXT;
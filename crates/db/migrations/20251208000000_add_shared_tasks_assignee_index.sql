@@ -0,0 +1,3 @@
+-- Serves the "assigned to me" list, newest first
+CREATE INDEX IF NOT EXISTS idx_shared_tasks_assignee_updated
+    ON shared_tasks (assignee_user_id, updated_at DESC);
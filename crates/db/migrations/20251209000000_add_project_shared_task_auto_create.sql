@@ -0,0 +1,6 @@
+PRAGMA foreign_keys = ON;
+
+-- Which synced shared tasks get a local task: 'assigned_to_me', 'all' or 'none'
+ALTER TABLE projects
+    ADD COLUMN shared_task_auto_create TEXT NOT NULL DEFAULT 'assigned_to_me'
+        CHECK (shared_task_auto_create IN ('assigned_to_me', 'all', 'none'));
@@ -0,0 +1,13 @@
+PRAGMA foreign_keys = ON;
+
+-- Activity events already applied to shared_tasks, so a replayed event is
+-- skipped instead of being applied twice
+CREATE TABLE IF NOT EXISTS applied_activity_events (
+    event_id          BLOB PRIMARY KEY,
+    remote_project_id BLOB NOT NULL,
+    seq               INTEGER NOT NULL,
+    applied_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_applied_activity_events_applied_at
+    ON applied_activity_events (applied_at);
@@ -0,0 +1,22 @@
+-- Normalized conversation entries of coding agent runs, written as the
+-- output is normalized so the conversation survives log truncation.
+-- `ordinal` is the entry's index in the run's conversation. `kind` and
+-- `action` are the `entry_type` and tool `action_type` tags, kept as
+-- columns for filtering.
+CREATE TABLE executor_session_entries (
+    execution_process_id BLOB NOT NULL,
+    ordinal              INTEGER NOT NULL,
+    kind                 TEXT NOT NULL,
+    action               TEXT,
+    entry_type           TEXT NOT NULL,
+    content              TEXT NOT NULL,
+    metadata             TEXT,
+    timestamp            TEXT,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (execution_process_id, ordinal),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_executor_session_entries_kind
+    ON executor_session_entries (kind, action);
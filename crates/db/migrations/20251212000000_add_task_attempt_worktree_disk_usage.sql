@@ -0,0 +1,6 @@
+-- Disk usage of each attempt's worktree, as of the last background scan.
+-- `worktree_disk_truncated` is set when the scan ran out of time, so the
+-- size is a lower bound.
+ALTER TABLE task_attempts ADD COLUMN worktree_disk_bytes INTEGER;
+ALTER TABLE task_attempts ADD COLUMN worktree_disk_truncated BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE task_attempts ADD COLUMN worktree_disk_computed_at TEXT;
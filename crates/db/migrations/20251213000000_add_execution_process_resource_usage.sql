@@ -0,0 +1,4 @@
+-- CPU time and peak resident memory of the process itself, sampled while it
+-- ran. NULL when the platform could not be queried.
+ALTER TABLE execution_processes ADD COLUMN cpu_time_ms INTEGER;
+ALTER TABLE execution_processes ADD COLUMN peak_rss_bytes INTEGER;
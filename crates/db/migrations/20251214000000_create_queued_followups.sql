@@ -0,0 +1,18 @@
+PRAGMA foreign_keys = ON;
+
+-- Follow-up prompts typed while an attempt is running, sent in order as its
+-- runs finish. `blocked_reason` says why the next one could not be sent;
+-- `dispatched_at` is set once it has been.
+CREATE TABLE IF NOT EXISTS queued_followups (
+    id              BLOB PRIMARY KEY,
+    task_attempt_id BLOB NOT NULL,
+    prompt          TEXT NOT NULL,
+    blocked_reason  TEXT,
+    created_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    dispatched_at   TEXT,
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_queued_followups_pending
+    ON queued_followups (task_attempt_id, created_at)
+    WHERE dispatched_at IS NULL;
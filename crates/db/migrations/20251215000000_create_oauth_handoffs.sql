@@ -0,0 +1,18 @@
+PRAGMA foreign_keys = ON;
+
+-- Sign-in handoffs started by this app, kept so a restart mid-login does not
+-- lose the verifier. The verifier is cleared once the handoff is redeemed;
+-- the row stays until it expires so a second callback is reported as
+-- already used rather than unknown.
+CREATE TABLE IF NOT EXISTS oauth_handoffs (
+    handoff_id   BLOB PRIMARY KEY,
+    provider     TEXT NOT NULL,
+    app_verifier TEXT,
+    status       TEXT NOT NULL DEFAULT 'pending'
+                 CHECK (status IN ('pending', 'redeeming', 'completed', 'failed')),
+    error        TEXT,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_oauth_handoffs_created_at
+    ON oauth_handoffs (created_at);
@@ -0,0 +1,4 @@
+-- The attempt a retry re-ran from scratch, so attempts can be shown as a
+-- lineage. The original is left untouched.
+ALTER TABLE task_attempts
+    ADD COLUMN retried_from_attempt_id BLOB REFERENCES task_attempts(id) ON DELETE SET NULL;
@@ -0,0 +1,7 @@
+-- Deleted tasks stay in the trash until restored or purged. A task linked to
+-- a shared task is detached when deleted; the shared task id is kept so a
+-- restore can link it again.
+ALTER TABLE tasks ADD COLUMN deleted_at TEXT;
+ALTER TABLE tasks ADD COLUMN detached_shared_task_id BLOB;
+
+CREATE INDEX idx_tasks_deleted_at ON tasks(deleted_at) WHERE deleted_at IS NOT NULL;
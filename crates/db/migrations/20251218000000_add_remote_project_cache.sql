@@ -0,0 +1,5 @@
+-- The linked remote project's name and metadata as last fetched, so remote
+-- renames reach the local UI, and when the remote project was found deleted
+ALTER TABLE projects ADD COLUMN remote_project_name TEXT;
+ALTER TABLE projects ADD COLUMN remote_project_metadata TEXT;
+ALTER TABLE projects ADD COLUMN remote_link_broken_at TEXT;
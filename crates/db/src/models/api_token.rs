@@ -0,0 +1,132 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ApiTokenScope {
+    /// Safe requests only (GET, HEAD, OPTIONS)
+    ReadOnly,
+    /// Creating and running tasks: the task, attempt and execution process
+    /// routes only
+    TaskExecution,
+}
+
+/// A bearer token for the local API. The secret itself is never stored.
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct ApiToken {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(skip)]
+    #[ts(skip)]
+    pub token_hash: String,
+    #[serde(skip)]
+    #[ts(skip)]
+    pub salt: String,
+    pub scope: ApiTokenScope,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// Updated at most once a minute
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateApiToken {
+    pub name: String,
+    pub scope: ApiTokenScope,
+    /// Never expires when omitted
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl ApiToken {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ApiToken,
+            r#"SELECT id as "id!: Uuid", name, token_hash, salt, scope as "scope!: ApiTokenScope",
+                      expires_at as "expires_at?: DateTime<Utc>", revoked_at as "revoked_at?: DateTime<Utc>",
+                      last_used_at as "last_used_at?: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM api_tokens
+               ORDER BY created_at DESC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ApiToken,
+            r#"SELECT id as "id!: Uuid", name, token_hash, salt, scope as "scope!: ApiTokenScope",
+                      expires_at as "expires_at?: DateTime<Utc>", revoked_at as "revoked_at?: DateTime<Utc>",
+                      last_used_at as "last_used_at?: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM api_tokens
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// `token_hash` and `salt` come from the caller, which also generates
+    /// the secret they protect.
+    pub async fn create(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &CreateApiToken,
+        token_hash: &str,
+        salt: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            ApiToken,
+            r#"INSERT INTO api_tokens (id, name, token_hash, salt, scope, expires_at)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id as "id!: Uuid", name, token_hash, salt, scope as "scope!: ApiTokenScope",
+                         expires_at as "expires_at?: DateTime<Utc>", revoked_at as "revoked_at?: DateTime<Utc>",
+                         last_used_at as "last_used_at?: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.name,
+            token_hash,
+            salt,
+            data.scope,
+            data.expires_at
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Returns the number of tokens revoked; revoking twice is a no-op.
+    pub async fn revoke(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let now = Utc::now();
+        let result = sqlx::query!(
+            "UPDATE api_tokens SET revoked_at = $2 WHERE id = $1 AND revoked_at IS NULL",
+            id,
+            now
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Record a use at `now`, skipped if the last one was under a minute ago.
+    pub async fn touch_last_used(
+        pool: &SqlitePool,
+        id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE api_tokens
+               SET last_used_at = $2
+               WHERE id = $1
+                 AND (last_used_at IS NULL
+                      OR julianday($2) - julianday(last_used_at) >= 60.0 / 86400.0)"#,
+            id,
+            now
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
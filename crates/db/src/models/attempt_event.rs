@@ -0,0 +1,130 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{SqlitePool, Type, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// What happened to an attempt. The shape of [`AttemptEvent::payload`]
+/// depends on the type.
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum AttemptEventType {
+    /// `{ branch, path }`
+    WorktreeCreated,
+    /// Waiting for a free execution slot
+    Queued,
+    /// `{ execution_process_id, run_reason }`
+    ProcessStarted,
+    /// `{ execution_process_id, run_reason, exit_code }`
+    ProcessCompleted,
+    /// `{ execution_process_id, run_reason, exit_code }`
+    ProcessFailed,
+    /// `{ execution_process_id, run_reason }`
+    ProcessKilled,
+    /// `{ execution_process_id }`
+    FollowUpRequested,
+    /// `{ number, url }`
+    PrOpened,
+    /// `{ number, url }`
+    PrMerged,
+    /// `{ commit }`
+    BranchMerged,
+    /// `{ base_branch }`
+    Rebased,
+    /// `{ path }`
+    WorktreeRemoved,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct AttemptEvent {
+    pub id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub event_type: AttemptEventType,
+    pub payload: Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AttemptEvent {
+    pub async fn create(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        event_type: AttemptEventType,
+        payload: Value,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let payload = Json(payload);
+        let row = sqlx::query!(
+            r#"INSERT INTO attempt_events (id, task_attempt_id, event_type, payload)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id AS "id!: Uuid",
+                         task_attempt_id AS "task_attempt_id!: Uuid",
+                         event_type AS "event_type!: AttemptEventType",
+                         payload AS "payload!: Json<Value>",
+                         created_at AS "created_at!: DateTime<Utc>""#,
+            id,
+            task_attempt_id,
+            event_type,
+            payload
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(Self {
+            id: row.id,
+            task_attempt_id: row.task_attempt_id,
+            event_type: row.event_type,
+            payload: row.payload.0,
+            created_at: row.created_at,
+        })
+    }
+
+    /// Record an event without letting a failed insert affect the caller;
+    /// the history is informational, the operation it describes is not.
+    pub async fn record(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        event_type: AttemptEventType,
+        payload: Value,
+    ) {
+        if let Err(e) = Self::create(pool, task_attempt_id, event_type, payload).await {
+            tracing::warn!(
+                "Failed to record {:?} event for task attempt {}: {}",
+                event_type,
+                task_attempt_id,
+                e
+            );
+        }
+    }
+
+    /// Events of an attempt, oldest first.
+    pub async fn find_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"SELECT id AS "id!: Uuid",
+                      task_attempt_id AS "task_attempt_id!: Uuid",
+                      event_type AS "event_type!: AttemptEventType",
+                      payload AS "payload!: Json<Value>",
+                      created_at AS "created_at!: DateTime<Utc>"
+               FROM attempt_events
+               WHERE task_attempt_id = $1
+               ORDER BY created_at ASC, rowid ASC"#,
+            task_attempt_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| Self {
+                id: row.id,
+                task_attempt_id: row.task_attempt_id,
+                event_type: row.event_type,
+                payload: row.payload.0,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+}
@@ -5,7 +5,7 @@ use executors::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use sqlx::{FromRow, SqlitePool, Type};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type};
 use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
@@ -47,6 +47,7 @@ pub enum ExecutionProcessRunReason {
     CleanupScript,
     CodingAgent,
     DevServer,
+    VerifyScript,
 }
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
@@ -66,12 +67,44 @@ pub struct ExecutionProcess {
     /// history view (due to restore/trimming). Hidden from logs/timeline;
     /// still listed in the Processes tab.
     pub dropped: bool,
+    /// URL the dev server reported it is listening on, detected from its output
+    pub dev_server_url: Option<String>,
+    /// true if the dev server output reported its port was already in use
+    pub dev_server_port_conflict: bool,
+    /// When the process went quiet for longer than the stall timeout; cleared
+    /// if its output resumes
+    pub stalled_at: Option<DateTime<Utc>>,
+    /// CPU time the process used, sampled while it ran; `None` if it could
+    /// not be measured
+    pub cpu_time_ms: Option<i64>,
+    /// Largest resident memory seen while sampling
+    pub peak_rss_bytes: Option<i64>,
     pub started_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// CPU time and peak memory of one of an attempt's processes
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProcessResourceUsage {
+    pub execution_process_id: Uuid,
+    pub run_reason: ExecutionProcessRunReason,
+    pub cpu_time_ms: Option<i64>,
+    pub peak_rss_bytes: Option<i64>,
+}
+
+impl From<&ExecutionProcess> for ProcessResourceUsage {
+    fn from(process: &ExecutionProcess) -> Self {
+        Self {
+            execution_process_id: process.id,
+            run_reason: process.run_reason.clone(),
+            cpu_time_ms: process.cpu_time_ms,
+            peak_rss_bytes: process.peak_rss_bytes,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, TS)]
 pub struct CreateExecutionProcess {
     pub task_attempt_id: Uuid,
@@ -87,6 +120,20 @@ pub struct UpdateExecutionProcess {
     pub completed_at: Option<DateTime<Utc>>,
 }
 
+/// A finished execution process restored from an exported board
+#[derive(Debug)]
+pub struct ImportExecutionProcess {
+    pub task_attempt_id: Uuid,
+    pub run_reason: ExecutionProcessRunReason,
+    pub executor_action: Value,
+    pub before_head_commit: Option<String>,
+    pub after_head_commit: Option<String>,
+    pub status: ExecutionProcessStatus,
+    pub exit_code: Option<i64>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug)]
 pub struct ExecutionContext {
     pub execution_process: ExecutionProcess,
@@ -116,7 +163,7 @@ impl ExecutionProcess {
         sqlx::query_as!(
             ExecutionProcess,
             r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
-                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
+                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, dev_server_url, dev_server_port_conflict, stalled_at as "stalled_at?: DateTime<Utc>", cpu_time_ms, peak_rss_bytes, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes WHERE id = ?"#,
             id
@@ -192,7 +239,7 @@ impl ExecutionProcess {
         sqlx::query_as!(
             ExecutionProcess,
             r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
-                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
+                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, dev_server_url, dev_server_port_conflict, stalled_at as "stalled_at?: DateTime<Utc>", cpu_time_ms, peak_rss_bytes, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes WHERE rowid = ?"#,
             rowid
@@ -218,6 +265,11 @@ impl ExecutionProcess {
                       status          as "status!: ExecutionProcessStatus",
                       exit_code,
                       dropped,
+                      dev_server_url,
+                      dev_server_port_conflict,
+                      stalled_at      as "stalled_at?: DateTime<Utc>",
+                      cpu_time_ms,
+                      peak_rss_bytes,
                       started_at      as "started_at!: DateTime<Utc>",
                       completed_at    as "completed_at?: DateTime<Utc>",
                       created_at      as "created_at!: DateTime<Utc>",
@@ -238,7 +290,7 @@ impl ExecutionProcess {
         sqlx::query_as!(
             ExecutionProcess,
             r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
-                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
+                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, dev_server_url, dev_server_port_conflict, stalled_at as "stalled_at?: DateTime<Utc>", cpu_time_ms, peak_rss_bytes, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes WHERE status = 'running' ORDER BY created_at ASC"#,
         )
@@ -255,7 +307,7 @@ impl ExecutionProcess {
             ExecutionProcess,
             r#"SELECT ep.id as "id!: Uuid", ep.task_attempt_id as "task_attempt_id!: Uuid", ep.run_reason as "run_reason!: ExecutionProcessRunReason", ep.executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
                       ep.before_head_commit, ep.after_head_commit, ep.status as "status!: ExecutionProcessStatus", ep.exit_code,
-                      ep.dropped, ep.started_at as "started_at!: DateTime<Utc>", ep.completed_at as "completed_at?: DateTime<Utc>", ep.created_at as "created_at!: DateTime<Utc>", ep.updated_at as "updated_at!: DateTime<Utc>"
+                      ep.dropped, ep.dev_server_url, ep.dev_server_port_conflict, ep.stalled_at as "stalled_at?: DateTime<Utc>", ep.cpu_time_ms, ep.peak_rss_bytes, ep.started_at as "started_at!: DateTime<Utc>", ep.completed_at as "completed_at?: DateTime<Utc>", ep.created_at as "created_at!: DateTime<Utc>", ep.updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes ep
                JOIN task_attempts ta ON ep.task_attempt_id = ta.id
                JOIN tasks t ON ta.task_id = t.id
@@ -285,6 +337,11 @@ impl ExecutionProcess {
             status as "status!: ExecutionProcessStatus",
             exit_code,
             dropped,
+            dev_server_url,
+            dev_server_port_conflict,
+            stalled_at as "stalled_at?: DateTime<Utc>",
+            cpu_time_ms,
+            peak_rss_bytes,
             started_at as "started_at!: DateTime<Utc>",
             completed_at as "completed_at?: DateTime<Utc>",
             created_at as "created_at!: DateTime<Utc>",
@@ -339,7 +396,7 @@ impl ExecutionProcess {
         sqlx::query_as!(
             ExecutionProcess,
             r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
-                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
+                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, dev_server_url, dev_server_port_conflict, stalled_at as "stalled_at?: DateTime<Utc>", cpu_time_ms, peak_rss_bytes, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes
                WHERE task_attempt_id = ? AND run_reason = ? AND dropped = FALSE
@@ -368,7 +425,7 @@ impl ExecutionProcess {
                     after_head_commit, status, exit_code, started_at, completed_at, created_at, updated_at
                 ) VALUES (?, ?, ?, ?, ?, NULL, ?, ?, ?, ?, ?, ?) RETURNING
                     id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
-                    after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+                    after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, dev_server_url, dev_server_port_conflict, stalled_at as "stalled_at?: DateTime<Utc>", cpu_time_ms, peak_rss_bytes, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             process_id,
             data.task_attempt_id,
             data.run_reason,
@@ -385,6 +442,37 @@ impl ExecutionProcess {
         .await
     }
 
+    pub async fn insert_imported<'e, E>(
+        executor: E,
+        data: &ImportExecutionProcess,
+        process_id: Uuid,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let executor_action = sqlx::types::Json(&data.executor_action);
+        sqlx::query!(
+            r#"INSERT INTO execution_processes (
+                    id, task_attempt_id, run_reason, executor_action, before_head_commit,
+                    after_head_commit, status, exit_code, started_at, completed_at,
+                    created_at, updated_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $9, $10)"#,
+            process_id,
+            data.task_attempt_id,
+            data.run_reason,
+            executor_action,
+            data.before_head_commit,
+            data.after_head_commit,
+            data.status,
+            data.exit_code,
+            data.started_at,
+            data.completed_at,
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
     pub async fn was_stopped(pool: &SqlitePool, id: Uuid) -> bool {
         if let Ok(exp_process) = Self::find_by_id(pool, id).await
             && exp_process.is_some_and(|ep| {
@@ -461,6 +549,103 @@ impl ExecutionProcess {
         Ok(())
     }
 
+    /// Record the URL a dev server process is listening on
+    pub async fn update_dev_server_url(
+        pool: &SqlitePool,
+        id: Uuid,
+        dev_server_url: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET dev_server_url = $1
+               WHERE id = $2"#,
+            dev_server_url,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Flag a dev server process whose port was already in use
+    pub async fn set_dev_server_port_conflict(
+        pool: &SqlitePool,
+        id: Uuid,
+        port_conflict: bool,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET dev_server_port_conflict = $1
+               WHERE id = $2"#,
+            port_conflict,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Attempts currently running a setup script or coding agent, across all
+    /// projects or within one. Setup scripts count because the agent run
+    /// follows them.
+    pub async fn count_active_attempts(
+        pool: &SqlitePool,
+        project_id: Option<Uuid>,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(DISTINCT ep.task_attempt_id) AS "count!: i64"
+               FROM execution_processes ep
+               JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+               JOIN tasks t ON t.id = ta.task_id
+               WHERE ep.status = 'running'
+                 AND ep.run_reason IN ('setupscript', 'codingagent')
+                 AND ($1 IS NULL OR t.project_id = $1)"#,
+            project_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Flag a running process as stalled, or clear the flag once it produces
+    /// output again
+    pub async fn set_stalled(
+        pool: &SqlitePool,
+        id: Uuid,
+        stalled: bool,
+    ) -> Result<(), sqlx::Error> {
+        let stalled_at = stalled.then(Utc::now);
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET stalled_at = $1
+               WHERE id = $2"#,
+            stalled_at,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Store the CPU time and peak memory sampled while the process ran
+    pub async fn record_resource_usage(
+        pool: &SqlitePool,
+        id: Uuid,
+        cpu_time_ms: Option<i64>,
+        peak_rss_bytes: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET cpu_time_ms = $1, peak_rss_bytes = $2
+               WHERE id = $3"#,
+            cpu_time_ms,
+            peak_rss_bytes,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     pub async fn delete_by_task_attempt_id(
         pool: &SqlitePool,
         task_attempt_id: Uuid,
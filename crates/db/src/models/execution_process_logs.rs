@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
 use ts_rs::TS;
 use utils::log_msg::LogMsg;
 use uuid::Uuid;
@@ -35,6 +35,39 @@ impl ExecutionProcessLogs {
         .await
     }
 
+    /// The most recent log records of an execution process holding at least
+    /// `max_bytes` of output (or all of it, if there is less), oldest first.
+    /// Records are returned whole, so the result can overshoot the budget by
+    /// up to one record.
+    pub async fn find_tail_by_execution_id(
+        pool: &SqlitePool,
+        execution_id: Uuid,
+        max_bytes: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessLogs,
+            r#"SELECT
+                execution_id as "execution_id!: Uuid",
+                logs as "logs!",
+                byte_size as "byte_size!",
+                inserted_at as "inserted_at!: DateTime<Utc>"
+               FROM (
+                   SELECT execution_id, logs, byte_size, inserted_at, rowid AS seq,
+                          SUM(byte_size) OVER (
+                              ORDER BY inserted_at DESC, rowid DESC
+                          ) AS newer_bytes
+                   FROM execution_process_logs
+                   WHERE execution_id = $1
+               )
+               WHERE newer_bytes - byte_size < $2
+               ORDER BY inserted_at ASC, seq ASC"#,
+            execution_id,
+            max_bytes
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Parse JSONL logs back into Vec<LogMsg>
     pub fn parse_logs(records: &[Self]) -> Result<Vec<LogMsg>, serde_json::Error> {
         let mut messages = Vec::new();
@@ -77,4 +110,28 @@ impl ExecutionProcessLogs {
 
         Ok(())
     }
+
+    /// Store the whole output of an imported execution process as one record
+    pub async fn insert_imported<'e, E>(
+        executor: E,
+        execution_id: Uuid,
+        logs: &str,
+        inserted_at: Option<DateTime<Utc>>,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let byte_size = logs.len() as i64;
+        sqlx::query!(
+            r#"INSERT INTO execution_process_logs (execution_id, logs, byte_size, inserted_at)
+               VALUES ($1, $2, $3, $4)"#,
+            execution_id,
+            logs,
+            byte_size,
+            inserted_at,
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
 }
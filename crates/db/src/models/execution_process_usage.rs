@@ -0,0 +1,121 @@
+use executors::logs::usage::UsageSummary;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Token totals for one task, across all of its attempts.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TaskUsage {
+    pub task_id: Uuid,
+    pub task_title: String,
+    pub usage: UsageSummary,
+}
+
+/// Usage recorded for coding agent runs, one row per execution process.
+pub struct ExecutionProcessUsage;
+
+impl ExecutionProcessUsage {
+    /// Store the totals for a run, replacing any earlier record.
+    pub async fn record(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        usage: &UsageSummary,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO execution_process_usage (
+                   execution_process_id, input_tokens, output_tokens,
+                   cache_read_input_tokens, cache_creation_input_tokens, cost_usd
+               )
+               VALUES ($1, $2, $3, $4, $5, $6)
+               ON CONFLICT(execution_process_id) DO UPDATE SET
+                   input_tokens = excluded.input_tokens,
+                   output_tokens = excluded.output_tokens,
+                   cache_read_input_tokens = excluded.cache_read_input_tokens,
+                   cache_creation_input_tokens = excluded.cache_creation_input_tokens,
+                   cost_usd = excluded.cost_usd"#,
+            execution_process_id,
+            usage.input_tokens,
+            usage.output_tokens,
+            usage.cache_read_input_tokens,
+            usage.cache_creation_input_tokens,
+            usage.cost_usd
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Totals across an attempt's runs, or `None` if none of them reported
+    /// usage.
+    pub async fn total_for_task_attempt(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<UsageSummary>, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"SELECT COUNT(*) AS "runs!: i64",
+                      COALESCE(SUM(u.input_tokens), 0) AS "input_tokens!: i64",
+                      COALESCE(SUM(u.output_tokens), 0) AS "output_tokens!: i64",
+                      COALESCE(SUM(u.cache_read_input_tokens), 0) AS "cache_read_input_tokens!: i64",
+                      COALESCE(SUM(u.cache_creation_input_tokens), 0) AS "cache_creation_input_tokens!: i64",
+                      CASE WHEN COUNT(u.cost_usd) = COUNT(*) THEN SUM(u.cost_usd) END AS "cost_usd: f64"
+               FROM execution_process_usage u
+               JOIN execution_processes ep ON ep.id = u.execution_process_id
+               WHERE ep.task_attempt_id = $1"#,
+            task_attempt_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok((row.runs > 0).then_some(UsageSummary {
+            input_tokens: row.input_tokens,
+            output_tokens: row.output_tokens,
+            cache_read_input_tokens: row.cache_read_input_tokens,
+            cache_creation_input_tokens: row.cache_creation_input_tokens,
+            cost_usd: row.cost_usd,
+        }))
+    }
+
+    /// Totals per task in a project, heaviest first. Tasks without recorded
+    /// usage are left out.
+    pub async fn totals_by_task(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<TaskUsage>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"SELECT t.id AS "task_id!: Uuid",
+                      t.title AS "task_title!",
+                      SUM(u.input_tokens) AS "input_tokens!: i64",
+                      SUM(u.output_tokens) AS "output_tokens!: i64",
+                      SUM(u.cache_read_input_tokens) AS "cache_read_input_tokens!: i64",
+                      SUM(u.cache_creation_input_tokens) AS "cache_creation_input_tokens!: i64",
+                      CASE WHEN COUNT(u.cost_usd) = COUNT(*) THEN SUM(u.cost_usd) END AS "cost_usd: f64"
+               FROM execution_process_usage u
+               JOIN execution_processes ep ON ep.id = u.execution_process_id
+               JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+               JOIN tasks t ON t.id = ta.task_id
+               WHERE t.project_id = $1
+               GROUP BY t.id
+               ORDER BY SUM(u.input_tokens + u.output_tokens + u.cache_read_input_tokens
+                            + u.cache_creation_input_tokens) DESC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| TaskUsage {
+                task_id: row.task_id,
+                task_title: row.task_title,
+                usage: UsageSummary {
+                    input_tokens: row.input_tokens,
+                    output_tokens: row.output_tokens,
+                    cache_read_input_tokens: row.cache_read_input_tokens,
+                    cache_creation_input_tokens: row.cache_creation_input_tokens,
+                    cost_usd: row.cost_usd,
+                },
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,222 @@
+use executors::logs::{NormalizedEntry, NormalizedEntryType};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// One entry of a coding agent run's normalized conversation, as persisted
+/// while the run's output was normalized.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ExecutorSessionEntry {
+    pub execution_process_id: Uuid,
+    /// Position in the run's conversation
+    pub ordinal: i64,
+    pub entry: NormalizedEntry,
+}
+
+#[derive(FromRow)]
+struct EntryRow {
+    execution_process_id: Uuid,
+    ordinal: i64,
+    entry_type: Json<NormalizedEntryType>,
+    content: String,
+    metadata: Option<Json<Value>>,
+    timestamp: Option<String>,
+}
+
+impl From<EntryRow> for ExecutorSessionEntry {
+    fn from(row: EntryRow) -> Self {
+        Self {
+            execution_process_id: row.execution_process_id,
+            ordinal: row.ordinal,
+            entry: NormalizedEntry {
+                timestamp: row.timestamp,
+                entry_type: row.entry_type.0,
+                content: row.content,
+                metadata: row.metadata.map(|metadata| metadata.0),
+            },
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct UnrecordedRun {
+    pub rowid: i64,
+    pub id: Uuid,
+}
+
+/// The `entry_type` tag and, for tool use, the `action_type` tag, e.g.
+/// `("tool_use", Some("file_edit"))`.
+pub fn entry_kind(entry_type: &NormalizedEntryType) -> (String, Option<String>) {
+    let value = serde_json::to_value(entry_type).unwrap_or_default();
+    let kind = value["type"].as_str().unwrap_or_default().to_string();
+    let action = value["action_type"]["action"].as_str().map(str::to_string);
+    (kind, action)
+}
+
+impl ExecutorSessionEntry {
+    pub async fn upsert<'e, E>(
+        executor: E,
+        execution_process_id: Uuid,
+        ordinal: i64,
+        entry: &NormalizedEntry,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let (kind, action) = entry_kind(&entry.entry_type);
+        let entry_type = Json(&entry.entry_type);
+        let metadata = entry.metadata.as_ref().map(Json);
+        sqlx::query!(
+            r#"INSERT INTO executor_session_entries (
+                   execution_process_id, ordinal, kind, action, entry_type, content, metadata, timestamp
+               ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+               ON CONFLICT(execution_process_id, ordinal) DO UPDATE SET
+                   kind = excluded.kind,
+                   action = excluded.action,
+                   entry_type = excluded.entry_type,
+                   content = excluded.content,
+                   metadata = excluded.metadata,
+                   timestamp = excluded.timestamp,
+                   updated_at = datetime('now', 'subsec')"#,
+            execution_process_id,
+            ordinal,
+            kind,
+            action,
+            entry_type,
+            entry.content,
+            metadata,
+            entry.timestamp
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete<'e, E>(
+        executor: E,
+        execution_process_id: Uuid,
+        ordinal: i64,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            "DELETE FROM executor_session_entries WHERE execution_process_id = $1 AND ordinal = $2",
+            execution_process_id,
+            ordinal
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// The run's entries in conversation order; empty if none were persisted.
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            EntryRow,
+            r#"SELECT execution_process_id as "execution_process_id!: Uuid",
+                      ordinal as "ordinal!: i64",
+                      entry_type as "entry_type!: Json<NormalizedEntryType>",
+                      content,
+                      metadata as "metadata: Json<Value>",
+                      timestamp
+               FROM executor_session_entries
+               WHERE execution_process_id = $1
+               ORDER BY ordinal ASC"#,
+            execution_process_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.into_iter().map(Self::from).collect())
+    }
+
+    /// Entries across a project's runs, newest runs first, optionally only
+    /// those of one `kind` (e.g. `tool_use`) and tool `action` (e.g.
+    /// `file_edit`).
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        kind: Option<&str>,
+        action: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            EntryRow,
+            r#"SELECT e.execution_process_id as "execution_process_id!: Uuid",
+                      e.ordinal as "ordinal!: i64",
+                      e.entry_type as "entry_type!: Json<NormalizedEntryType>",
+                      e.content,
+                      e.metadata as "metadata: Json<Value>",
+                      e.timestamp
+               FROM executor_session_entries e
+               JOIN execution_processes ep ON ep.id = e.execution_process_id
+               JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+               JOIN tasks t ON t.id = ta.task_id
+               WHERE t.project_id = $1
+                 AND ($2 IS NULL OR e.kind = $2)
+                 AND ($3 IS NULL OR e.action = $3)
+               ORDER BY ep.created_at DESC, e.ordinal ASC
+               LIMIT $4 OFFSET $5"#,
+            project_id,
+            kind,
+            action,
+            limit,
+            offset
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.into_iter().map(Self::from).collect())
+    }
+
+    /// Replace everything persisted for a run with `entries`, numbered in
+    /// order.
+    pub async fn replace_for_execution_process(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        entries: &[NormalizedEntry],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            "DELETE FROM executor_session_entries WHERE execution_process_id = $1",
+            execution_process_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        for (ordinal, entry) in entries.iter().enumerate() {
+            Self::upsert(&mut *tx, execution_process_id, ordinal as i64, entry).await?;
+        }
+        tx.commit().await
+    }
+
+    /// Finished coding agent runs with no persisted entries, after the
+    /// `after_rowid` cursor, oldest first.
+    pub async fn find_unrecorded_runs(
+        pool: &SqlitePool,
+        after_rowid: i64,
+        limit: i64,
+    ) -> Result<Vec<UnrecordedRun>, sqlx::Error> {
+        sqlx::query_as!(
+            UnrecordedRun,
+            r#"SELECT ep.rowid as "rowid!: i64", ep.id as "id!: Uuid"
+               FROM execution_processes ep
+               WHERE ep.rowid > $1
+                 AND ep.run_reason = 'codingagent'
+                 AND ep.status != 'running'
+                 AND NOT EXISTS (
+                     SELECT 1 FROM executor_session_entries e WHERE e.execution_process_id = ep.id
+                 )
+               ORDER BY ep.rowid ASC
+               LIMIT $2"#,
+            after_rowid,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
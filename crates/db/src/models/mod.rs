@@ -1,11 +1,23 @@
+pub mod api_token;
+pub mod attempt_event;
 pub mod draft;
 pub mod execution_process;
 pub mod execution_process_logs;
+pub mod execution_process_usage;
 pub mod executor_session;
+pub mod executor_session_entry;
 pub mod image;
 pub mod merge;
+pub mod oauth_handoff;
 pub mod project;
+pub mod prompt_template;
+pub mod queued_attempt;
+pub mod queued_followup;
 pub mod shared_task;
+pub mod shared_task_outbox;
 pub mod tag;
 pub mod task;
 pub mod task_attempt;
+pub mod task_attempt_plan;
+pub mod task_dependency;
+pub mod task_search;
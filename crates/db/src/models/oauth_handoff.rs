@@ -0,0 +1,195 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// How long a handoff can be redeemed after it was started
+pub const OAUTH_HANDOFF_TTL: Duration = Duration::minutes(10);
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq)]
+#[sqlx(type_name = "oauth_handoff_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum OAuthHandoffStatus {
+    Pending,
+    /// The callback arrived and the verifier has been used
+    Redeeming,
+    Completed,
+    Failed,
+}
+
+/// A sign-in handoff started by this app. The verifier is only read by
+/// [`OAuthHandoff::take`].
+#[derive(Debug, Clone)]
+pub struct OAuthHandoff {
+    pub handoff_id: Uuid,
+    pub provider: String,
+    pub status: OAuthHandoffStatus,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Outcome of redeeming a handoff's verifier
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandoffRedemption {
+    Ready {
+        provider: String,
+        app_verifier: String,
+    },
+    Expired,
+    AlreadyUsed,
+    Unknown,
+}
+
+/// Progress of a handoff as reported to the frontend
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum HandoffProgress {
+    Pending,
+    Redeeming,
+    Completed { provider: String },
+    Failed { error: String },
+    Expired,
+    Unknown,
+}
+
+impl OAuthHandoff {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now - self.created_at >= OAUTH_HANDOFF_TTL
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        handoff_id: Uuid,
+        provider: &str,
+        app_verifier: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO oauth_handoffs (handoff_id, provider, app_verifier)
+             VALUES ($1, $2, $3)",
+            handoff_id,
+            provider,
+            app_verifier
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn find(pool: &SqlitePool, handoff_id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            OAuthHandoff,
+            r#"SELECT handoff_id AS "handoff_id!: Uuid",
+                      provider,
+                      status     AS "status!: OAuthHandoffStatus",
+                      error,
+                      created_at AS "created_at!: DateTime<Utc>"
+               FROM oauth_handoffs
+               WHERE handoff_id = $1"#,
+            handoff_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Hand out the verifier once. The row is kept, without the verifier,
+    /// so a repeated callback can be told apart from an unknown one.
+    pub async fn take(
+        pool: &SqlitePool,
+        handoff_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<HandoffRedemption, sqlx::Error> {
+        let Some(handoff) = Self::find(pool, handoff_id).await? else {
+            return Ok(HandoffRedemption::Unknown);
+        };
+        if handoff.status != OAuthHandoffStatus::Pending {
+            return Ok(HandoffRedemption::AlreadyUsed);
+        }
+        if handoff.is_expired(now) {
+            return Ok(HandoffRedemption::Expired);
+        }
+
+        let app_verifier = sqlx::query_scalar!(
+            "SELECT app_verifier FROM oauth_handoffs WHERE handoff_id = $1",
+            handoff_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .flatten();
+        let claimed = sqlx::query!(
+            "UPDATE oauth_handoffs
+             SET status = 'redeeming', app_verifier = NULL
+             WHERE handoff_id = $1 AND status = 'pending'",
+            handoff_id
+        )
+        .execute(pool)
+        .await?;
+
+        // A concurrent callback claimed it between the two queries
+        match app_verifier {
+            Some(app_verifier) if claimed.rows_affected() > 0 => Ok(HandoffRedemption::Ready {
+                provider: handoff.provider,
+                app_verifier,
+            }),
+            _ => Ok(HandoffRedemption::AlreadyUsed),
+        }
+    }
+
+    /// Record how a redeemed handoff ended.
+    pub async fn finish(
+        pool: &SqlitePool,
+        handoff_id: Uuid,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        let status = if error.is_some() {
+            OAuthHandoffStatus::Failed
+        } else {
+            OAuthHandoffStatus::Completed
+        };
+        sqlx::query!(
+            "UPDATE oauth_handoffs
+             SET status = $2, error = $3, app_verifier = NULL
+             WHERE handoff_id = $1",
+            handoff_id,
+            status,
+            error
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn progress(
+        pool: &SqlitePool,
+        handoff_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<HandoffProgress, sqlx::Error> {
+        let Some(handoff) = Self::find(pool, handoff_id).await? else {
+            return Ok(HandoffProgress::Unknown);
+        };
+        Ok(match handoff.status {
+            OAuthHandoffStatus::Pending if handoff.is_expired(now) => HandoffProgress::Expired,
+            OAuthHandoffStatus::Pending => HandoffProgress::Pending,
+            OAuthHandoffStatus::Redeeming => HandoffProgress::Redeeming,
+            OAuthHandoffStatus::Completed => HandoffProgress::Completed {
+                provider: handoff.provider,
+            },
+            OAuthHandoffStatus::Failed => HandoffProgress::Failed {
+                error: handoff.error.unwrap_or_default(),
+            },
+        })
+    }
+
+    /// Delete handoffs started more than the TTL before `now`, finished or
+    /// not. Returns how many were removed.
+    pub async fn purge_expired(pool: &SqlitePool, now: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let cutoff = now - OAUTH_HANDOFF_TTL;
+        let result = sqlx::query!(
+            "DELETE FROM oauth_handoffs WHERE julianday(created_at) <= julianday($1)",
+            cutoff
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
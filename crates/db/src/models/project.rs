@@ -1,8 +1,10 @@
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf, str::FromStr};
 
 use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
 use serde::{Deserialize, Serialize};
-use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
+use serde_json::Value;
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type, types::Json};
 use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
@@ -19,6 +21,8 @@ pub enum ProjectError {
     GitRepoCheckFailed(String),
     #[error("Failed to create project: {0}")]
     CreateFailed(String),
+    #[error("Project is archived; unarchive it to create tasks or start attempts")]
+    Archived,
 }
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
@@ -28,15 +32,57 @@ pub struct Project {
     pub git_repo_path: PathBuf,
     pub setup_script: Option<String>,
     pub dev_script: Option<String>,
+    /// Regex for the URL in the dev server's output, tried before the
+    /// built-in patterns. A named `url` group selects part of the match.
+    pub dev_server_url_pattern: Option<String>,
     pub cleanup_script: Option<String>,
     pub copy_files: Option<String>,
+    /// Command run after the coding agent to check the attempt, e.g. `cargo test`
+    pub verify_script: Option<String>,
+    pub verify_timeout_secs: Option<i64>,
+    /// Executor profile for attempts started without one, as `EXECUTOR[:VARIANT]`
+    pub default_executor: Option<String>,
+    /// Coding agent runs allowed at once in this project; further attempts
+    /// wait in the queue. `None` leaves only the global limit.
+    pub max_concurrent_executions: Option<i64>,
+    /// Move tasks to done when the PR opened from one of their attempts is
+    /// merged
+    pub close_task_on_pr_merge: bool,
+    /// Which shared tasks synced from the linked remote project get a local
+    /// task
+    pub shared_task_auto_create: SharedTaskAutoCreate,
     pub remote_project_id: Option<Uuid>,
+    /// Name of the linked remote project as last fetched from the remote
+    pub remote_project_name: Option<String>,
+    /// Metadata of the linked remote project as last fetched
+    #[ts(type = "Record<string, unknown> | null")]
+    pub remote_project_metadata: Option<Json<Value>>,
+    /// Set once the linked remote project was found to be deleted. The
+    /// project stops syncing until it is unlinked.
+    #[ts(type = "Date | null")]
+    pub remote_link_broken_at: Option<DateTime<Utc>>,
+    /// Set while the project is archived
+    #[ts(type = "Date | null")]
+    pub archived_at: Option<DateTime<Utc>>,
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
     #[ts(type = "Date")]
     pub updated_at: DateTime<Utc>,
 }
 
+/// When syncing a shared task creates a local task for it in the linked
+/// project. Tasks that already have a local task are updated regardless.
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS, Default)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SharedTaskAutoCreate {
+    /// Only tasks assigned to the signed-in user
+    #[default]
+    AssignedToMe,
+    All,
+    None,
+}
+
 #[derive(Debug, Deserialize, TS)]
 pub struct CreateProject {
     pub name: String,
@@ -44,8 +90,35 @@ pub struct CreateProject {
     pub use_existing_repo: bool,
     pub setup_script: Option<String>,
     pub dev_script: Option<String>,
+    pub dev_server_url_pattern: Option<String>,
     pub cleanup_script: Option<String>,
     pub copy_files: Option<String>,
+    pub verify_script: Option<String>,
+    pub verify_timeout_secs: Option<i64>,
+    pub default_executor: Option<String>,
+    pub max_concurrent_executions: Option<i64>,
+    /// Defaults to true
+    pub close_task_on_pr_merge: Option<bool>,
+}
+
+/// A project restored from an exported board, with the settings it was
+/// exported with
+#[derive(Debug)]
+pub struct ImportProject {
+    pub name: String,
+    pub git_repo_path: String,
+    pub setup_script: Option<String>,
+    pub dev_script: Option<String>,
+    pub dev_server_url_pattern: Option<String>,
+    pub cleanup_script: Option<String>,
+    pub copy_files: Option<String>,
+    pub verify_script: Option<String>,
+    pub verify_timeout_secs: Option<i64>,
+    pub default_executor: Option<String>,
+    pub max_concurrent_executions: Option<i64>,
+    pub close_task_on_pr_merge: bool,
+    pub shared_task_auto_create: SharedTaskAutoCreate,
+    pub remote_project_id: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -54,8 +127,17 @@ pub struct UpdateProject {
     pub git_repo_path: Option<String>,
     pub setup_script: Option<String>,
     pub dev_script: Option<String>,
+    pub dev_server_url_pattern: Option<String>,
     pub cleanup_script: Option<String>,
     pub copy_files: Option<String>,
+    pub verify_script: Option<String>,
+    pub verify_timeout_secs: Option<i64>,
+    pub default_executor: Option<String>,
+    pub max_concurrent_executions: Option<i64>,
+    /// Leaves the current setting when omitted
+    pub close_task_on_pr_merge: Option<bool>,
+    /// Leaves the current setting when omitted
+    pub shared_task_auto_create: Option<SharedTaskAutoCreate>,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -73,6 +155,28 @@ pub enum SearchMatchType {
 }
 
 impl Project {
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+
+    /// The project's executor override. A stored value that no longer parses,
+    /// e.g. because the executor was removed, is ignored with a warning.
+    pub fn default_executor_profile(&self) -> Option<ExecutorProfileId> {
+        let raw = self.default_executor.as_deref()?;
+        match ExecutorProfileId::from_str(raw) {
+            Ok(profile) => Some(profile),
+            Err(err) => {
+                tracing::warn!(
+                    "Ignoring default executor '{}' for project {}: {}",
+                    raw,
+                    self.id,
+                    err
+                );
+                None
+            }
+        }
+    }
+
     pub async fn count(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(r#"SELECT COUNT(*) as "count!: i64" FROM projects"#)
             .fetch_one(pool)
@@ -87,9 +191,20 @@ impl Project {
                       git_repo_path,
                       setup_script,
                       dev_script,
+                      dev_server_url_pattern,
                       cleanup_script,
                       copy_files,
+                      verify_script,
+                      verify_timeout_secs,
+                      default_executor,
+                      max_concurrent_executions,
+                      close_task_on_pr_merge as "close_task_on_pr_merge!: bool",
+                      shared_task_auto_create as "shared_task_auto_create!: SharedTaskAutoCreate",
                       remote_project_id as "remote_project_id: Uuid",
+                      remote_project_name,
+                      remote_project_metadata as "remote_project_metadata: Json<Value>",
+                      remote_link_broken_at as "remote_link_broken_at: DateTime<Utc>",
+                      archived_at as "archived_at: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
@@ -104,11 +219,18 @@ impl Project {
         sqlx::query_as!(
             Project,
             r#"
-            SELECT p.id as "id!: Uuid", p.name, p.git_repo_path, p.setup_script, p.dev_script, p.cleanup_script, p.copy_files, 
+            SELECT p.id as "id!: Uuid", p.name, p.git_repo_path, p.setup_script, p.dev_script, p.dev_server_url_pattern, p.cleanup_script, p.copy_files,
+                   p.verify_script, p.verify_timeout_secs, p.default_executor, p.max_concurrent_executions,
+                   p.close_task_on_pr_merge as "close_task_on_pr_merge!: bool",
+                   p.shared_task_auto_create as "shared_task_auto_create!: SharedTaskAutoCreate",
                    p.remote_project_id as "remote_project_id: Uuid",
+                   p.remote_project_name,
+                   p.remote_project_metadata as "remote_project_metadata: Json<Value>",
+                   p.remote_link_broken_at as "remote_link_broken_at: DateTime<Utc>",
+                   p.archived_at as "archived_at: DateTime<Utc>",
                    p.created_at as "created_at!: DateTime<Utc>", p.updated_at as "updated_at!: DateTime<Utc>"
             FROM projects p
-            WHERE p.id IN (
+            WHERE p.archived_at IS NULL AND p.id IN (
                 SELECT DISTINCT t.project_id
                 FROM tasks t
                 INNER JOIN task_attempts ta ON ta.task_id = t.id
@@ -130,9 +252,20 @@ impl Project {
                       git_repo_path,
                       setup_script,
                       dev_script,
+                      dev_server_url_pattern,
                       cleanup_script,
                       copy_files,
+                      verify_script,
+                      verify_timeout_secs,
+                      default_executor,
+                      max_concurrent_executions,
+                      close_task_on_pr_merge as "close_task_on_pr_merge!: bool",
+                      shared_task_auto_create as "shared_task_auto_create!: SharedTaskAutoCreate",
                       remote_project_id as "remote_project_id: Uuid",
+                      remote_project_name,
+                      remote_project_metadata as "remote_project_metadata: Json<Value>",
+                      remote_link_broken_at as "remote_link_broken_at: DateTime<Utc>",
+                      archived_at as "archived_at: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
@@ -143,6 +276,38 @@ impl Project {
         .await
     }
 
+    pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Project,
+            r#"SELECT id as "id!: Uuid",
+                      name,
+                      git_repo_path,
+                      setup_script,
+                      dev_script,
+                      dev_server_url_pattern,
+                      cleanup_script,
+                      copy_files,
+                      verify_script,
+                      verify_timeout_secs,
+                      default_executor,
+                      max_concurrent_executions,
+                      close_task_on_pr_merge as "close_task_on_pr_merge!: bool",
+                      shared_task_auto_create as "shared_task_auto_create!: SharedTaskAutoCreate",
+                      remote_project_id as "remote_project_id: Uuid",
+                      remote_project_name,
+                      remote_project_metadata as "remote_project_metadata: Json<Value>",
+                      remote_link_broken_at as "remote_link_broken_at: DateTime<Utc>",
+                      archived_at as "archived_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM projects
+               WHERE rowid = $1"#,
+            rowid
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
     pub async fn find_by_remote_project_id(
         pool: &SqlitePool,
         remote_project_id: Uuid,
@@ -154,9 +319,20 @@ impl Project {
                       git_repo_path,
                       setup_script,
                       dev_script,
+                      dev_server_url_pattern,
                       cleanup_script,
                       copy_files,
+                      verify_script,
+                      verify_timeout_secs,
+                      default_executor,
+                      max_concurrent_executions,
+                      close_task_on_pr_merge as "close_task_on_pr_merge!: bool",
+                      shared_task_auto_create as "shared_task_auto_create!: SharedTaskAutoCreate",
                       remote_project_id as "remote_project_id: Uuid",
+                      remote_project_name,
+                      remote_project_metadata as "remote_project_metadata: Json<Value>",
+                      remote_link_broken_at as "remote_link_broken_at: DateTime<Utc>",
+                      archived_at as "archived_at: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
@@ -179,9 +355,20 @@ impl Project {
                       git_repo_path,
                       setup_script,
                       dev_script,
+                      dev_server_url_pattern,
                       cleanup_script,
                       copy_files,
+                      verify_script,
+                      verify_timeout_secs,
+                      default_executor,
+                      max_concurrent_executions,
+                      close_task_on_pr_merge as "close_task_on_pr_merge!: bool",
+                      shared_task_auto_create as "shared_task_auto_create!: SharedTaskAutoCreate",
                       remote_project_id as "remote_project_id: Uuid",
+                      remote_project_name,
+                      remote_project_metadata as "remote_project_metadata: Json<Value>",
+                      remote_link_broken_at as "remote_link_broken_at: DateTime<Utc>",
+                      archived_at as "archived_at: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
@@ -204,9 +391,20 @@ impl Project {
                       git_repo_path,
                       setup_script,
                       dev_script,
+                      dev_server_url_pattern,
                       cleanup_script,
                       copy_files,
+                      verify_script,
+                      verify_timeout_secs,
+                      default_executor,
+                      max_concurrent_executions,
+                      close_task_on_pr_merge as "close_task_on_pr_merge!: bool",
+                      shared_task_auto_create as "shared_task_auto_create!: SharedTaskAutoCreate",
                       remote_project_id as "remote_project_id: Uuid",
+                      remote_project_name,
+                      remote_project_metadata as "remote_project_metadata: Json<Value>",
+                      remote_link_broken_at as "remote_link_broken_at: DateTime<Utc>",
+                      archived_at as "archived_at: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
@@ -223,6 +421,7 @@ impl Project {
         data: &CreateProject,
         project_id: Uuid,
     ) -> Result<Self, sqlx::Error> {
+        let close_task_on_pr_merge = data.close_task_on_pr_merge.unwrap_or(true);
         sqlx::query_as!(
             Project,
             r#"INSERT INTO projects (
@@ -231,19 +430,36 @@ impl Project {
                     git_repo_path,
                     setup_script,
                     dev_script,
+                    dev_server_url_pattern,
                     cleanup_script,
-                    copy_files
+                    copy_files,
+                    verify_script,
+                    verify_timeout_secs,
+                    default_executor,
+                    max_concurrent_executions,
+                    close_task_on_pr_merge
                 ) VALUES (
-                    $1, $2, $3, $4, $5, $6, $7
+                    $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13
                 )
                 RETURNING id as "id!: Uuid",
                           name,
                           git_repo_path,
                           setup_script,
                           dev_script,
+                          dev_server_url_pattern,
                           cleanup_script,
                           copy_files,
+                          verify_script,
+                          verify_timeout_secs,
+                          default_executor,
+                          max_concurrent_executions,
+                          close_task_on_pr_merge as "close_task_on_pr_merge!: bool",
+                          shared_task_auto_create as "shared_task_auto_create!: SharedTaskAutoCreate",
                           remote_project_id as "remote_project_id: Uuid",
+                          remote_project_name,
+                          remote_project_metadata as "remote_project_metadata: Json<Value>",
+                          remote_link_broken_at as "remote_link_broken_at: DateTime<Utc>",
+                          archived_at as "archived_at: DateTime<Utc>",
                           created_at as "created_at!: DateTime<Utc>",
                           updated_at as "updated_at!: DateTime<Utc>""#,
             project_id,
@@ -251,13 +467,55 @@ impl Project {
             data.git_repo_path,
             data.setup_script,
             data.dev_script,
+            data.dev_server_url_pattern,
             data.cleanup_script,
             data.copy_files,
+            data.verify_script,
+            data.verify_timeout_secs,
+            data.default_executor,
+            data.max_concurrent_executions,
+            close_task_on_pr_merge,
         )
         .fetch_one(pool)
         .await
     }
 
+    pub async fn insert_imported<'e, E>(
+        executor: E,
+        data: &ImportProject,
+        project_id: Uuid,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"INSERT INTO projects (
+                    id, name, git_repo_path, setup_script, dev_script, dev_server_url_pattern,
+                    cleanup_script, copy_files, verify_script, verify_timeout_secs, default_executor,
+                    max_concurrent_executions, close_task_on_pr_merge, shared_task_auto_create,
+                    remote_project_id
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)"#,
+            project_id,
+            data.name,
+            data.git_repo_path,
+            data.setup_script,
+            data.dev_script,
+            data.dev_server_url_pattern,
+            data.cleanup_script,
+            data.copy_files,
+            data.verify_script,
+            data.verify_timeout_secs,
+            data.default_executor,
+            data.max_concurrent_executions,
+            data.close_task_on_pr_merge,
+            data.shared_task_auto_create,
+            data.remote_project_id,
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn update(
         pool: &SqlitePool,
@@ -266,8 +524,15 @@ impl Project {
         git_repo_path: String,
         setup_script: Option<String>,
         dev_script: Option<String>,
+        dev_server_url_pattern: Option<String>,
         cleanup_script: Option<String>,
         copy_files: Option<String>,
+        verify_script: Option<String>,
+        verify_timeout_secs: Option<i64>,
+        default_executor: Option<String>,
+        max_concurrent_executions: Option<i64>,
+        close_task_on_pr_merge: bool,
+        shared_task_auto_create: SharedTaskAutoCreate,
     ) -> Result<Self, sqlx::Error> {
         sqlx::query_as!(
             Project,
@@ -276,17 +541,35 @@ impl Project {
                    git_repo_path = $3,
                    setup_script = $4,
                    dev_script = $5,
-                   cleanup_script = $6,
-                   copy_files = $7
+                   dev_server_url_pattern = $6,
+                   cleanup_script = $7,
+                   copy_files = $8,
+                   verify_script = $9,
+                   verify_timeout_secs = $10,
+                   default_executor = $11,
+                   max_concurrent_executions = $12,
+                   close_task_on_pr_merge = $13,
+                   shared_task_auto_create = $14
                WHERE id = $1
                RETURNING id as "id!: Uuid",
                          name,
                          git_repo_path,
                          setup_script,
                          dev_script,
+                         dev_server_url_pattern,
                          cleanup_script,
                          copy_files,
+                         verify_script,
+                         verify_timeout_secs,
+                         default_executor,
+                         max_concurrent_executions,
+                         close_task_on_pr_merge as "close_task_on_pr_merge!: bool",
+                         shared_task_auto_create as "shared_task_auto_create!: SharedTaskAutoCreate",
                          remote_project_id as "remote_project_id: Uuid",
+                         remote_project_name,
+                         remote_project_metadata as "remote_project_metadata: Json<Value>",
+                         remote_link_broken_at as "remote_link_broken_at: DateTime<Utc>",
+                         archived_at as "archived_at: DateTime<Utc>",
                          created_at as "created_at!: DateTime<Utc>",
                          updated_at as "updated_at!: DateTime<Utc>""#,
             id,
@@ -294,13 +577,22 @@ impl Project {
             git_repo_path,
             setup_script,
             dev_script,
+            dev_server_url_pattern,
             cleanup_script,
             copy_files,
+            verify_script,
+            verify_timeout_secs,
+            default_executor,
+            max_concurrent_executions,
+            close_task_on_pr_merge,
+            shared_task_auto_create,
         )
         .fetch_one(pool)
         .await
     }
 
+    /// Link the project to a remote project, or unlink it with `None`. The
+    /// cached remote name and metadata are cleared either way.
     pub async fn set_remote_project_id(
         pool: &SqlitePool,
         id: Uuid,
@@ -308,7 +600,10 @@ impl Project {
     ) -> Result<(), sqlx::Error> {
         sqlx::query!(
             r#"UPDATE projects
-               SET remote_project_id = $2
+               SET remote_project_id = $2,
+                   remote_project_name = NULL,
+                   remote_project_metadata = NULL,
+                   remote_link_broken_at = NULL
                WHERE id = $1"#,
             id,
             remote_project_id
@@ -319,6 +614,71 @@ impl Project {
         Ok(())
     }
 
+    /// Store the name and metadata fetched for a linked remote project.
+    /// Returns false if they were already up to date.
+    pub async fn update_remote_project_cache(
+        pool: &SqlitePool,
+        remote_project_id: Uuid,
+        name: &str,
+        metadata: &Value,
+    ) -> Result<bool, sqlx::Error> {
+        let metadata = Json(metadata);
+        let result = sqlx::query!(
+            r#"UPDATE projects
+               SET remote_project_name = $2,
+                   remote_project_metadata = $3
+               WHERE remote_project_id = $1
+                 AND (remote_project_name IS NOT $2 OR remote_project_metadata IS NOT $3)"#,
+            remote_project_id,
+            name,
+            metadata
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record that the linked remote project no longer exists. Marking a
+    /// broken link again keeps the original timestamp.
+    pub async fn mark_remote_link_broken(
+        pool: &SqlitePool,
+        remote_project_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE projects
+               SET remote_link_broken_at = COALESCE(remote_link_broken_at, datetime('now', 'subsec'))
+               WHERE remote_project_id = $1"#,
+            remote_project_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Archive the project, or unarchive it with `archived: false`.
+    /// Archiving an archived project keeps the original timestamp.
+    pub async fn set_archived(
+        pool: &SqlitePool,
+        id: Uuid,
+        archived: bool,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE projects
+               SET archived_at = CASE
+                       WHEN $2 THEN COALESCE(archived_at, datetime('now', 'subsec'))
+                       ELSE NULL
+                   END
+               WHERE id = $1"#,
+            id,
+            archived
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Transaction-compatible version of set_remote_project_id
     pub async fn set_remote_project_id_tx<'e, E>(
         executor: E,
@@ -330,7 +690,10 @@ impl Project {
     {
         sqlx::query!(
             r#"UPDATE projects
-               SET remote_project_id = $2
+               SET remote_project_id = $2,
+                   remote_project_name = NULL,
+                   remote_project_metadata = NULL,
+                   remote_link_broken_at = NULL
                WHERE id = $1"#,
             id,
             remote_project_id
@@ -341,6 +704,42 @@ impl Project {
         Ok(())
     }
 
+    /// Extra environment variables for the project's executor processes, kept
+    /// out of [`Project`] so they are not sent with every project listing.
+    pub async fn find_env(
+        pool: &SqlitePool,
+        id: Uuid,
+    ) -> Result<BTreeMap<String, String>, sqlx::Error> {
+        let env = sqlx::query_scalar!(
+            r#"SELECT env AS "env: Json<BTreeMap<String, String>>"
+               FROM projects
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?
+        .flatten();
+        Ok(env.map(|env| env.0).unwrap_or_default())
+    }
+
+    pub async fn set_env(
+        pool: &SqlitePool,
+        id: Uuid,
+        env: &BTreeMap<String, String>,
+    ) -> Result<(), sqlx::Error> {
+        let env = Json(env);
+        sqlx::query!(
+            r#"UPDATE projects
+               SET env = $2
+               WHERE id = $1"#,
+            id,
+            env
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
         let result = sqlx::query!("DELETE FROM projects WHERE id = $1", id)
             .execute(pool)
@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TemplateRenderError {
+    #[error("Missing values for template variables: {}", .0.join(", "))]
+    MissingVariables(Vec<String>),
+}
+
+/// A reusable follow-up prompt. `{name}` placeholders in `body` are filled in
+/// when the template is sent.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct PromptTemplate {
+    pub id: Uuid,
+    /// `None` for global templates, available in every project
+    pub project_id: Option<Uuid>,
+    pub name: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreatePromptTemplate {
+    pub project_id: Option<Uuid>,
+    pub name: String,
+    pub body: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdatePromptTemplate {
+    pub name: Option<String>,
+    pub body: Option<String>,
+}
+
+impl PromptTemplate {
+    /// Global templates plus, if `project_id` is given, that project's own.
+    /// Without a project every template is returned.
+    pub async fn find_all(
+        pool: &SqlitePool,
+        project_id: Option<Uuid>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PromptTemplate,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id?: Uuid", name, body, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM prompt_templates
+               WHERE $1 IS NULL OR project_id IS NULL OR project_id = $1
+               ORDER BY name ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PromptTemplate,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id?: Uuid", name, body, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM prompt_templates
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreatePromptTemplate,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            PromptTemplate,
+            r#"INSERT INTO prompt_templates (id, project_id, name, body)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid", project_id as "project_id?: Uuid", name, body, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.project_id,
+            data.name,
+            data.body
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdatePromptTemplate,
+    ) -> Result<Self, sqlx::Error> {
+        let existing = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let name = data.name.as_ref().unwrap_or(&existing.name);
+        let body = data.body.as_ref().unwrap_or(&existing.body);
+
+        sqlx::query_as!(
+            PromptTemplate,
+            r#"UPDATE prompt_templates
+               SET name = $2, body = $3, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", project_id as "project_id?: Uuid", name, body, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            name,
+            body
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM prompt_templates WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Whether the template may be used in `project_id`.
+    pub fn is_available_in(&self, project_id: Uuid) -> bool {
+        self.project_id.is_none_or(|id| id == project_id)
+    }
+
+    pub fn render(
+        &self,
+        variables: &HashMap<String, String>,
+    ) -> Result<String, TemplateRenderError> {
+        render_template(&self.body, variables)
+    }
+}
+
+/// Replace each `{name}` in `body` with its value. Only identifier-like names
+/// are placeholders, so braces in code snippets (`{}`, `{ x: 1 }`) are kept.
+pub fn render_template(
+    body: &str,
+    variables: &HashMap<String, String>,
+) -> Result<String, TemplateRenderError> {
+    let mut out = String::with_capacity(body.len());
+    let mut missing: Vec<String> = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let placeholder = after
+            .find('}')
+            .map(|end| &after[..end])
+            .filter(|name| is_placeholder_name(name));
+        match placeholder {
+            Some(name) => {
+                match variables.get(name) {
+                    Some(value) => out.push_str(value),
+                    None if !missing.iter().any(|m| m == name) => missing.push(name.to_string()),
+                    None => {}
+                }
+                rest = &after[name.len() + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+
+    if missing.is_empty() {
+        Ok(out)
+    } else {
+        Err(TemplateRenderError::MissingVariables(missing))
+    }
+}
+
+fn is_placeholder_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substitutes_placeholders_and_reports_missing_ones() {
+        let variables = HashMap::from([("n".to_string(), "42".to_string())]);
+        assert_eq!(
+            render_template("Address review comments on PR #{n}", &variables),
+            Ok("Address review comments on PR #42".to_string())
+        );
+        assert_eq!(
+            render_template("Keep `fn f() {}` and { x: 1 } for #{n}", &variables),
+            Ok("Keep `fn f() {}` and { x: 1 } for #42".to_string())
+        );
+        assert_eq!(
+            render_template("{branch} into {base} and {branch}", &variables),
+            Err(TemplateRenderError::MissingVariables(vec![
+                "branch".to_string(),
+                "base".to_string()
+            ]))
+        );
+    }
+}
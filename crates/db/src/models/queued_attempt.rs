@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Sqlite, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A task attempt waiting for a free execution slot.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct QueuedAttempt {
+    pub task_attempt_id: Uuid,
+    pub task_id: Uuid,
+    pub task_title: String,
+    pub project_id: Uuid,
+    pub executor_profile_id: ExecutorProfileId,
+    /// Started with `override`, so unfinished dependencies do not hold it
+    /// back
+    pub ignore_dependencies: bool,
+    /// Zero-based place in the queue
+    pub position: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl QueuedAttempt {
+    /// Queued attempts in the order they will be started.
+    pub async fn list(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"SELECT q.task_attempt_id AS "task_attempt_id!: Uuid",
+                      t.id AS "task_id!: Uuid",
+                      t.title AS "task_title!",
+                      t.project_id AS "project_id!: Uuid",
+                      q.executor_profile_id AS "executor_profile_id!: Json<ExecutorProfileId>",
+                      q.ignore_dependencies AS "ignore_dependencies!: bool",
+                      q.created_at AS "created_at!: DateTime<Utc>"
+               FROM queued_attempts q
+               JOIN task_attempts ta ON ta.id = q.task_attempt_id
+               JOIN tasks t ON t.id = ta.task_id
+               ORDER BY q.position ASC, q.created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .enumerate()
+            .map(|(position, row)| QueuedAttempt {
+                task_attempt_id: row.task_attempt_id,
+                task_id: row.task_id,
+                task_title: row.task_title,
+                project_id: row.project_id,
+                executor_profile_id: row.executor_profile_id.0,
+                ignore_dependencies: row.ignore_dependencies,
+                position: position as i64,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+
+    pub async fn find_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        Ok(Self::list(pool)
+            .await?
+            .into_iter()
+            .find(|queued| queued.task_attempt_id == task_attempt_id))
+    }
+
+    /// Add an attempt to the back of the queue.
+    pub async fn enqueue(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        executor_profile_id: &ExecutorProfileId,
+        ignore_dependencies: bool,
+    ) -> Result<(), sqlx::Error> {
+        let executor_profile_id = Json(executor_profile_id);
+        sqlx::query!(
+            r#"INSERT INTO queued_attempts (task_attempt_id, executor_profile_id, ignore_dependencies, position)
+               VALUES ($1, $2, $3, (SELECT COALESCE(MAX(position) + 1, 0) FROM queued_attempts))"#,
+            task_attempt_id,
+            executor_profile_id,
+            ignore_dependencies
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Take an attempt out of the queue. Returns false if it was not queued.
+    pub async fn remove(pool: &SqlitePool, task_attempt_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM queued_attempts WHERE task_attempt_id = $1",
+            task_attempt_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Take every attempt of a task out of the queue, returning how many
+    /// were queued.
+    pub async fn remove_for_task<'e, E>(executor: E, task_id: Uuid) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            r#"DELETE FROM queued_attempts
+               WHERE task_attempt_id IN (SELECT id FROM task_attempts WHERE task_id = $1)"#,
+            task_id
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Move a queued attempt to `position`, shifting the others along.
+    /// Positions past the end move it to the back. Returns false if the
+    /// attempt is not queued.
+    pub async fn move_to(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        position: usize,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let mut order = sqlx::query_scalar!(
+            r#"SELECT task_attempt_id AS "task_attempt_id!: Uuid"
+               FROM queued_attempts
+               ORDER BY position ASC, created_at ASC"#
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let Some(current) = order.iter().position(|id| *id == task_attempt_id) else {
+            return Ok(false);
+        };
+        let id = order.remove(current);
+        order.insert(position.min(order.len()), id);
+
+        for (index, id) in order.iter().enumerate() {
+            let index = index as i64;
+            sqlx::query!(
+                "UPDATE queued_attempts SET position = $1 WHERE task_attempt_id = $2",
+                index,
+                id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+}
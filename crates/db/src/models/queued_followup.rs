@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A follow-up prompt waiting for the attempt's current run to finish.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct QueuedFollowUp {
+    pub id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub prompt: String,
+    /// Why it could not be sent when the last run finished; it is tried
+    /// again after the next one
+    pub blocked_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub dispatched_at: Option<DateTime<Utc>>,
+}
+
+impl QueuedFollowUp {
+    /// Follow-ups not yet sent, oldest first.
+    pub async fn find_pending_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            QueuedFollowUp,
+            r#"SELECT id              AS "id!: Uuid",
+                      task_attempt_id AS "task_attempt_id!: Uuid",
+                      prompt,
+                      blocked_reason,
+                      created_at      AS "created_at!: DateTime<Utc>",
+                      dispatched_at   AS "dispatched_at: DateTime<Utc>"
+               FROM queued_followups
+               WHERE task_attempt_id = $1 AND dispatched_at IS NULL
+               ORDER BY created_at ASC, rowid ASC"#,
+            task_attempt_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn next_pending(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        Ok(Self::find_pending_by_task_attempt_id(pool, task_attempt_id)
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        prompt: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            QueuedFollowUp,
+            r#"INSERT INTO queued_followups (id, task_attempt_id, prompt)
+               VALUES ($1, $2, $3)
+               RETURNING id              AS "id!: Uuid",
+                         task_attempt_id AS "task_attempt_id!: Uuid",
+                         prompt,
+                         blocked_reason,
+                         created_at      AS "created_at!: DateTime<Utc>",
+                         dispatched_at   AS "dispatched_at: DateTime<Utc>""#,
+            id,
+            task_attempt_id,
+            prompt
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Remove a follow-up that has not been sent. Returns false if there was
+    /// none.
+    pub async fn delete_pending(
+        pool: &SqlitePool,
+        id: Uuid,
+        task_attempt_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM queued_followups
+             WHERE id = $1 AND task_attempt_id = $2 AND dispatched_at IS NULL",
+            id,
+            task_attempt_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Mark a follow-up as sent. Returns false if another caller got there
+    /// first.
+    pub async fn claim(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE queued_followups
+             SET dispatched_at = datetime('now', 'subsec'), blocked_reason = NULL
+             WHERE id = $1 AND dispatched_at IS NULL",
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Put a follow-up back in the queue, blocked for `reason`.
+    pub async fn block(pool: &SqlitePool, id: Uuid, reason: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE queued_followups
+             SET dispatched_at = NULL, blocked_reason = $2
+             WHERE id = $1",
+            id,
+            reason
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{Executor, FromRow, QueryBuilder, Sqlite, SqlitePool};
+use sqlx::{Executor, FromRow, QueryBuilder, Sqlite, SqlitePool, types::Json};
 use ts_rs::TS;
 use uuid::Uuid;
 
@@ -42,11 +42,22 @@ pub struct SharedTaskInput {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Result of [`SharedTask::upsert_if_newer`].
+#[derive(Debug)]
+pub enum UpsertOutcome {
+    Applied(SharedTask),
+    /// The stored row is at a later version; nothing was written
+    IgnoredStale,
+}
+
 impl SharedTask {
-    pub async fn list_by_remote_project_id(
-        pool: &SqlitePool,
+    pub async fn list_by_remote_project_id<'e, E>(
+        executor: E,
         remote_project_id: Uuid,
-    ) -> Result<Vec<Self>, sqlx::Error> {
+    ) -> Result<Vec<Self>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         sqlx::query_as!(
             SharedTask,
             r#"
@@ -70,10 +81,55 @@ impl SharedTask {
             "#,
             remote_project_id
         )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Tasks assigned to `assignee_user_id` across all projects, most
+    /// recently updated first.
+    pub async fn list_by_assignee(
+        pool: &SqlitePool,
+        assignee_user_id: Uuid,
+        status: Option<TaskStatus>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let limit = i64::from(limit);
+        let offset = i64::from(offset);
+        sqlx::query_as!(
+            SharedTask,
+            r#"
+            SELECT
+                id                         AS "id!: Uuid",
+                remote_project_id          AS "remote_project_id!: Uuid",
+                title                      AS title,
+                description                AS description,
+                status                     AS "status!: TaskStatus",
+                assignee_user_id           AS "assignee_user_id: Uuid",
+                assignee_first_name        AS "assignee_first_name: String",
+                assignee_last_name         AS "assignee_last_name: String",
+                assignee_username          AS "assignee_username: String",
+                version                    AS "version!: i64",
+                last_event_seq             AS "last_event_seq: i64",
+                created_at                 AS "created_at!: DateTime<Utc>",
+                updated_at                 AS "updated_at!: DateTime<Utc>"
+            FROM shared_tasks
+            WHERE assignee_user_id = $1
+              AND ($2 IS NULL OR status = $2)
+            ORDER BY updated_at DESC, id ASC
+            LIMIT $3 OFFSET $4
+            "#,
+            assignee_user_id,
+            status,
+            limit,
+            offset
+        )
         .fetch_all(pool)
         .await
     }
 
+    /// Insert or overwrite the row, whatever version is stored. For
+    /// authoritative sources such as bulk syncs and consistency repairs.
     pub async fn upsert<'e, E>(executor: E, data: SharedTaskInput) -> Result<Self, sqlx::Error>
     where
         E: Executor<'e, Database = Sqlite>,
@@ -146,6 +202,93 @@ impl SharedTask {
         .await
     }
 
+    /// Like [`Self::upsert`], but keeps the stored row if it is at a later
+    /// version, or at the same version and updated later. Events can arrive
+    /// out of order after a reconnect; only authoritative snapshots should
+    /// overwrite unconditionally.
+    pub async fn upsert_if_newer<'e, E>(
+        executor: E,
+        data: SharedTaskInput,
+    ) -> Result<UpsertOutcome, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let status = data.status.clone();
+        let row = sqlx::query_as!(
+            SharedTask,
+            r#"
+            INSERT INTO shared_tasks (
+                id,
+                remote_project_id,
+                title,
+                description,
+                status,
+                assignee_user_id,
+                assignee_first_name,
+                assignee_last_name,
+                assignee_username,
+                version,
+                last_event_seq,
+                created_at,
+                updated_at
+            )
+            VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13
+            )
+            ON CONFLICT(id) DO UPDATE SET
+                remote_project_id   = excluded.remote_project_id,
+                title               = excluded.title,
+                description         = excluded.description,
+                status              = excluded.status,
+                assignee_user_id    = excluded.assignee_user_id,
+                assignee_first_name = excluded.assignee_first_name,
+                assignee_last_name  = excluded.assignee_last_name,
+                assignee_username   = excluded.assignee_username,
+                version             = excluded.version,
+                last_event_seq      = excluded.last_event_seq,
+                created_at          = excluded.created_at,
+                updated_at          = excluded.updated_at
+            WHERE excluded.version > shared_tasks.version
+               OR (excluded.version = shared_tasks.version
+                   AND julianday(excluded.updated_at) >= julianday(shared_tasks.updated_at))
+            RETURNING
+                id                         AS "id!: Uuid",
+                remote_project_id          AS "remote_project_id!: Uuid",
+                title                      AS title,
+                description                AS description,
+                status                     AS "status!: TaskStatus",
+                assignee_user_id           AS "assignee_user_id: Uuid",
+                assignee_first_name        AS "assignee_first_name: String",
+                assignee_last_name         AS "assignee_last_name: String",
+                assignee_username          AS "assignee_username: String",
+                version                    AS "version!: i64",
+                last_event_seq             AS "last_event_seq: i64",
+                created_at                 AS "created_at!: DateTime<Utc>",
+                updated_at                 AS "updated_at!: DateTime<Utc>"
+            "#,
+            data.id,
+            data.remote_project_id,
+            data.title,
+            data.description,
+            status,
+            data.assignee_user_id,
+            data.assignee_first_name,
+            data.assignee_last_name,
+            data.assignee_username,
+            data.version,
+            data.last_event_seq,
+            data.created_at,
+            data.updated_at
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(match row {
+            Some(shared_task) => UpsertOutcome::Applied(shared_task),
+            None => UpsertOutcome::IgnoredStale,
+        })
+    }
+
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             SharedTask,
@@ -295,3 +438,177 @@ impl SharedActivityCursor {
         .await
     }
 }
+
+/// Ledger of activity events applied locally. Checked in the same
+/// transaction that applies an event, so an event delivered twice (e.g. once
+/// over the websocket and again while catching up) is only applied once.
+pub struct AppliedActivityEvent;
+
+impl AppliedActivityEvent {
+    /// Record `event_id` as applied. Returns false if it already was.
+    pub async fn record<'e, E>(
+        executor: E,
+        event_id: Uuid,
+        remote_project_id: Uuid,
+        seq: i64,
+    ) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO applied_activity_events (event_id, remote_project_id, seq)
+            VALUES ($1, $2, $3)
+            ON CONFLICT(event_id) DO NOTHING
+            "#,
+            event_id,
+            remote_project_id,
+            seq
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Forget events applied more than `retention_secs` ago.
+    pub async fn prune(pool: &SqlitePool, retention_secs: i64) -> Result<u64, sqlx::Error> {
+        let modifier = format!("-{retention_secs} seconds");
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM applied_activity_events
+            WHERE applied_at < datetime('now', 'subsec', $1)
+            "#,
+            modifier
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+/// Latest result of the periodic local/remote consistency check for a project.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct SharedTaskDivergenceReport {
+    pub remote_project_id: Uuid,
+    /// Local rows that disagreed with the remote and were re-fetched
+    #[ts(type = "string[]")]
+    pub mismatched_task_ids: Json<Vec<Uuid>>,
+    /// Remote tasks that had no local row
+    #[ts(type = "string[]")]
+    pub missing_task_ids: Json<Vec<Uuid>>,
+    /// Local rows for tasks that are gone remotely and were removed
+    #[ts(type = "string[]")]
+    pub stray_task_ids: Json<Vec<Uuid>>,
+    /// When the divergence above was found, `None` if no check found any
+    #[ts(type = "Date | null")]
+    pub detected_at: Option<DateTime<Utc>>,
+    #[ts(type = "Date")]
+    pub checked_at: DateTime<Utc>,
+}
+
+impl SharedTaskDivergenceReport {
+    pub fn total(&self) -> usize {
+        self.mismatched_task_ids.len() + self.missing_task_ids.len() + self.stray_task_ids.len()
+    }
+
+    pub async fn find(
+        pool: &SqlitePool,
+        remote_project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            SharedTaskDivergenceReport,
+            r#"
+            SELECT
+                remote_project_id   AS "remote_project_id!: Uuid",
+                mismatched_task_ids AS "mismatched_task_ids!: Json<Vec<Uuid>>",
+                missing_task_ids    AS "missing_task_ids!: Json<Vec<Uuid>>",
+                stray_task_ids      AS "stray_task_ids!: Json<Vec<Uuid>>",
+                detected_at         AS "detected_at: DateTime<Utc>",
+                checked_at          AS "checked_at!: DateTime<Utc>"
+            FROM shared_task_divergence_reports
+            WHERE remote_project_id = $1
+            "#,
+            remote_project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Record a check that found nothing to repair. A previous divergence
+    /// stays on the report so it can still be inspected.
+    pub async fn record_clean_check<'e, E>(
+        executor: E,
+        remote_project_id: Uuid,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            SharedTaskDivergenceReport,
+            r#"
+            INSERT INTO shared_task_divergence_reports (remote_project_id, checked_at)
+            VALUES ($1, datetime('now', 'subsec'))
+            ON CONFLICT(remote_project_id) DO UPDATE SET
+                checked_at = excluded.checked_at
+            RETURNING
+                remote_project_id   AS "remote_project_id!: Uuid",
+                mismatched_task_ids AS "mismatched_task_ids!: Json<Vec<Uuid>>",
+                missing_task_ids    AS "missing_task_ids!: Json<Vec<Uuid>>",
+                stray_task_ids      AS "stray_task_ids!: Json<Vec<Uuid>>",
+                detected_at         AS "detected_at: DateTime<Utc>",
+                checked_at          AS "checked_at!: DateTime<Utc>"
+            "#,
+            remote_project_id
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    pub async fn record_divergence<'e, E>(
+        executor: E,
+        remote_project_id: Uuid,
+        mismatched_task_ids: &[Uuid],
+        missing_task_ids: &[Uuid],
+        stray_task_ids: &[Uuid],
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let mismatched = Json(mismatched_task_ids.to_vec());
+        let missing = Json(missing_task_ids.to_vec());
+        let stray = Json(stray_task_ids.to_vec());
+        sqlx::query_as!(
+            SharedTaskDivergenceReport,
+            r#"
+            INSERT INTO shared_task_divergence_reports (
+                remote_project_id,
+                mismatched_task_ids,
+                missing_task_ids,
+                stray_task_ids,
+                detected_at,
+                checked_at
+            )
+            VALUES ($1, $2, $3, $4, datetime('now', 'subsec'), datetime('now', 'subsec'))
+            ON CONFLICT(remote_project_id) DO UPDATE SET
+                mismatched_task_ids = excluded.mismatched_task_ids,
+                missing_task_ids    = excluded.missing_task_ids,
+                stray_task_ids      = excluded.stray_task_ids,
+                detected_at         = excluded.detected_at,
+                checked_at          = excluded.checked_at
+            RETURNING
+                remote_project_id   AS "remote_project_id!: Uuid",
+                mismatched_task_ids AS "mismatched_task_ids!: Json<Vec<Uuid>>",
+                missing_task_ids    AS "missing_task_ids!: Json<Vec<Uuid>>",
+                stray_task_ids      AS "stray_task_ids!: Json<Vec<Uuid>>",
+                detected_at         AS "detected_at: DateTime<Utc>",
+                checked_at          AS "checked_at!: DateTime<Utc>"
+            "#,
+            remote_project_id,
+            mismatched,
+            missing,
+            stray
+        )
+        .fetch_one(executor)
+        .await
+    }
+}
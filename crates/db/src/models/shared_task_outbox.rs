@@ -0,0 +1,181 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, SqlitePool, Type, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum SharedTaskOpType {
+    Update,
+    Delete,
+}
+
+/// A shared task change waiting to be sent to the remote service.
+#[derive(Debug, Clone, FromRow)]
+pub struct SharedTaskOutboxOp {
+    pub id: Uuid,
+    pub op_type: SharedTaskOpType,
+    pub shared_task_id: Uuid,
+    /// The request body to replay
+    pub payload: Json<Value>,
+    pub attempt_count: i64,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How many changes are waiting to be synced and why the last replay failed.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct SharedTaskOutboxStatus {
+    pub pending: i64,
+    /// Why replaying the oldest failing change last failed
+    pub last_error: Option<String>,
+    pub oldest_created_at: Option<DateTime<Utc>>,
+}
+
+impl SharedTaskOutboxOp {
+    /// Pending operations in the order they were queued.
+    pub async fn list_pending(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            SharedTaskOutboxOp,
+            r#"SELECT id as "id!: Uuid",
+                      op_type as "op_type!: SharedTaskOpType",
+                      shared_task_id as "shared_task_id!: Uuid",
+                      payload as "payload!: Json<Value>",
+                      attempt_count as "attempt_count!: i64",
+                      last_error,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM shared_task_outbox
+               ORDER BY created_at ASC, rowid ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_pending(
+        pool: &SqlitePool,
+        shared_task_id: Uuid,
+        op_type: SharedTaskOpType,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            SharedTaskOutboxOp,
+            r#"SELECT id as "id!: Uuid",
+                      op_type as "op_type!: SharedTaskOpType",
+                      shared_task_id as "shared_task_id!: Uuid",
+                      payload as "payload!: Json<Value>",
+                      attempt_count as "attempt_count!: i64",
+                      last_error,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM shared_task_outbox
+               WHERE shared_task_id = $1 AND op_type = $2
+               ORDER BY created_at ASC, rowid ASC
+               LIMIT 1"#,
+            shared_task_id,
+            op_type
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn enqueue(
+        pool: &SqlitePool,
+        op_type: SharedTaskOpType,
+        shared_task_id: Uuid,
+        payload: Value,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let payload = Json(payload);
+        sqlx::query_as!(
+            SharedTaskOutboxOp,
+            r#"INSERT INTO shared_task_outbox (id, op_type, shared_task_id, payload)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid",
+                         op_type as "op_type!: SharedTaskOpType",
+                         shared_task_id as "shared_task_id!: Uuid",
+                         payload as "payload!: Json<Value>",
+                         attempt_count as "attempt_count!: i64",
+                         last_error,
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            op_type,
+            shared_task_id,
+            payload
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn replace_payload(
+        pool: &SqlitePool,
+        id: Uuid,
+        payload: Value,
+    ) -> Result<(), sqlx::Error> {
+        let payload = Json(payload);
+        sqlx::query!(
+            "UPDATE shared_task_outbox SET payload = $2 WHERE id = $1",
+            id,
+            payload
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn record_failure(
+        pool: &SqlitePool,
+        id: Uuid,
+        error: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE shared_task_outbox SET attempt_count = attempt_count + 1, last_error = $2 WHERE id = $1",
+            id,
+            error
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!("DELETE FROM shared_task_outbox WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Drop every pending operation for a shared task, e.g. updates made
+    /// moot by a later delete.
+    pub async fn delete_for_shared_task(
+        pool: &SqlitePool,
+        shared_task_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM shared_task_outbox WHERE shared_task_id = $1",
+            shared_task_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn status(pool: &SqlitePool) -> Result<SharedTaskOutboxStatus, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"SELECT COUNT(*) as "pending!: i64",
+                      MIN(created_at) as "oldest_created_at?: DateTime<Utc>",
+                      (SELECT last_error FROM shared_task_outbox
+                       WHERE last_error IS NOT NULL
+                       ORDER BY created_at ASC, rowid ASC
+                       LIMIT 1) as "last_error?: String"
+               FROM shared_task_outbox"#
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(SharedTaskOutboxStatus {
+            pending: row.pending,
+            last_error: row.last_error,
+            oldest_created_at: row.oldest_created_at,
+        })
+    }
+}
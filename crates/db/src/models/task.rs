@@ -5,7 +5,10 @@ use strum_macros::{Display, EnumString};
 use ts_rs::TS;
 use uuid::Uuid;
 
-use super::{project::Project, task_attempt::TaskAttempt};
+use super::{
+    project::Project,
+    task_attempt::{TaskAttempt, VerifyStatus},
+};
 
 #[derive(
     Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS, EnumString, Display, Default,
@@ -43,7 +46,12 @@ pub struct TaskWithAttemptStatus {
     pub has_in_progress_attempt: bool,
     pub has_merged_attempt: bool,
     pub last_attempt_failed: bool,
+    /// An attempt is waiting for a free execution slot
+    pub has_queued_attempt: bool,
+    /// Some task this one depends on is not done yet
+    pub blocked: bool,
     pub executor: String,
+    pub last_verify_status: Option<VerifyStatus>,
 }
 
 impl std::ops::Deref for TaskWithAttemptStatus {
@@ -66,6 +74,17 @@ pub struct TaskRelationships {
     pub children: Vec<Task>,          // Tasks created by this attempt
 }
 
+/// A deleted task waiting in its project's trash
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TrashedTask {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub task: Task,
+    pub deleted_at: DateTime<Utc>,
+    /// The shared task this task was detached from when it was deleted
+    pub detached_shared_task_id: Option<Uuid>,
+}
+
 #[derive(Debug, Serialize, Deserialize, TS)]
 pub struct CreateTask {
     pub project_id: Uuid,
@@ -113,6 +132,18 @@ impl CreateTask {
     }
 }
 
+/// A task restored from an exported board, keeping its original timestamps
+#[derive(Debug)]
+pub struct ImportTask {
+    pub project_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub status: TaskStatus,
+    pub shared_task_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone)]
 pub struct SyncTask {
     pub shared_task_id: Uuid,
@@ -167,9 +198,29 @@ impl Task {
         ON ep.task_attempt_id = ta.id
      WHERE ta.task_id       = t.id
        AND ep.status        = 'running'
-       AND ep.run_reason IN ('setupscript','cleanupscript','codingagent')
+       AND ep.run_reason IN ('setupscript','cleanupscript','codingagent','verifyscript')
      LIMIT 1
   ) THEN 1 ELSE 0 END            AS "has_in_progress_attempt!: i64",
+
+  CASE WHEN EXISTS (
+    SELECT 1
+      FROM task_attempts ta
+      JOIN queued_attempts q
+        ON q.task_attempt_id = ta.id
+     WHERE ta.task_id = t.id
+     LIMIT 1
+  ) THEN 1 ELSE 0 END            AS "has_queued_attempt!: i64",
+
+  CASE WHEN EXISTS (
+    SELECT 1
+      FROM task_dependencies td
+      JOIN tasks dep
+        ON dep.id = td.depends_on_task_id
+     WHERE td.task_id = t.id
+       AND dep.status != 'done'
+       AND dep.deleted_at IS NULL
+     LIMIT 1
+  ) THEN 1 ELSE 0 END            AS "blocked!: i64",
   
   CASE WHEN (
     SELECT ep.status
@@ -188,10 +239,18 @@ impl Task {
       WHERE ta.task_id = t.id
      ORDER BY ta.created_at DESC
       LIMIT 1
-    )                               AS "executor!: String"
+    )                               AS "executor!: String",
+
+  ( SELECT ta.verify_status
+      FROM task_attempts ta
+      WHERE ta.task_id = t.id
+     ORDER BY ta.created_at DESC
+      LIMIT 1
+    )                               AS "last_verify_status: VerifyStatus"
 
 FROM tasks t
 WHERE t.project_id = $1
+  AND t.deleted_at IS NULL
 ORDER BY t.created_at DESC"#,
             project_id
         )
@@ -215,7 +274,10 @@ ORDER BY t.created_at DESC"#,
                 has_in_progress_attempt: rec.has_in_progress_attempt != 0,
                 has_merged_attempt: false, // TODO use merges table
                 last_attempt_failed: rec.last_attempt_failed != 0,
+                has_queued_attempt: rec.has_queued_attempt != 0,
+                blocked: rec.blocked != 0,
                 executor: rec.executor,
+                last_verify_status: rec.last_verify_status,
             })
             .collect();
 
@@ -227,13 +289,14 @@ ORDER BY t.created_at DESC"#,
             Task,
             r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks 
-               WHERE id = $1"#,
+               WHERE id = $1 AND deleted_at IS NULL"#,
             id
         )
         .fetch_optional(pool)
         .await
     }
 
+    /// Unlike the other lookups, this also finds tasks in the trash.
     pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
@@ -255,7 +318,7 @@ ORDER BY t.created_at DESC"#,
             Task,
             r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks 
-               WHERE id = $1 AND project_id = $2"#,
+               WHERE id = $1 AND project_id = $2 AND deleted_at IS NULL"#,
             id,
             project_id
         )
@@ -305,6 +368,31 @@ ORDER BY t.created_at DESC"#,
         .await
     }
 
+    pub async fn insert_imported<'e, E>(
+        executor: E,
+        data: &ImportTask,
+        task_id: Uuid,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"INSERT INTO tasks (id, project_id, title, description, status, shared_task_id, created_at, updated_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+            task_id,
+            data.project_id,
+            data.title,
+            data.description,
+            data.status,
+            data.shared_task_id,
+            data.created_at,
+            data.updated_at,
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
     pub async fn update(
         pool: &SqlitePool,
         id: Uuid,
@@ -358,9 +446,12 @@ ORDER BY t.created_at DESC"#,
                 $4,
                 $5,
                 $6
-            WHERE $7
+            WHERE ($7
                OR EXISTS (
                     SELECT 1 FROM tasks WHERE shared_task_id = $6
+               ))
+              AND NOT EXISTS (
+                    SELECT 1 FROM tasks WHERE detached_shared_task_id = $6
                )
             ON CONFLICT(shared_task_id) WHERE shared_task_id IS NOT NULL DO UPDATE SET
                 project_id = excluded.project_id,
@@ -383,17 +474,20 @@ ORDER BY t.created_at DESC"#,
         Ok(result.rows_affected() > 0)
     }
 
-    pub async fn update_status(
-        pool: &SqlitePool,
+    pub async fn update_status<'e, E>(
+        executor: E,
         id: Uuid,
         status: TaskStatus,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         sqlx::query!(
             "UPDATE tasks SET status = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
             id,
             status
         )
-        .execute(pool)
+        .execute(executor)
         .await?;
         Ok(())
     }
@@ -438,6 +532,50 @@ ORDER BY t.created_at DESC"#,
         Ok(result.rows_affected())
     }
 
+    /// Local tasks in the project linked to a shared task that no longer
+    /// exists.
+    pub async fn find_with_missing_shared_task<'e, E>(
+        executor: E,
+        project_id: Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_scalar!(
+            r#"SELECT id as "id!: Uuid"
+               FROM tasks
+               WHERE project_id = $1
+                 AND shared_task_id IS NOT NULL
+                 AND shared_task_id NOT IN (SELECT id FROM shared_tasks)"#,
+            project_id
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Unlink the tasks [`Task::find_with_missing_shared_task`] returns,
+    /// returning their ids.
+    pub async fn clear_missing_shared_task_ids<'e, E>(
+        executor: E,
+        project_id: Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_scalar!(
+            r#"UPDATE tasks
+               SET shared_task_id = NULL,
+                   updated_at = CURRENT_TIMESTAMP
+               WHERE project_id = $1
+                 AND shared_task_id IS NOT NULL
+                 AND shared_task_id NOT IN (SELECT id FROM shared_tasks)
+               RETURNING id as "id!: Uuid""#,
+            project_id
+        )
+        .fetch_all(executor)
+        .await
+    }
+
     pub async fn delete<'e, E>(executor: E, id: Uuid) -> Result<u64, sqlx::Error>
     where
         E: Executor<'e, Database = Sqlite>,
@@ -448,6 +586,106 @@ ORDER BY t.created_at DESC"#,
         Ok(result.rows_affected())
     }
 
+    /// Move a task to the trash. A link to a shared task is cleared, leaving
+    /// the shared task itself alone, and remembered for [`Task::restore`].
+    pub async fn soft_delete<'e, E>(executor: E, id: Uuid) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            r#"UPDATE tasks
+               SET deleted_at = datetime('now', 'subsec'),
+                   detached_shared_task_id = shared_task_id,
+                   shared_task_id = NULL,
+                   updated_at = CURRENT_TIMESTAMP
+               WHERE id = $1 AND deleted_at IS NULL"#,
+            id
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Take a task out of the trash. It is linked to its shared task again
+    /// if that still exists and no other local task has taken its place.
+    /// Returns `None` if the task is not in the trash.
+    pub async fn restore(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"UPDATE tasks
+               SET deleted_at = NULL,
+                   shared_task_id = CASE
+                       WHEN detached_shared_task_id IN (SELECT id FROM shared_tasks)
+                        AND NOT EXISTS (
+                            SELECT 1 FROM tasks other
+                            WHERE other.shared_task_id = tasks.detached_shared_task_id
+                        )
+                       THEN detached_shared_task_id
+                   END,
+                   detached_shared_task_id = NULL,
+                   updated_at = CURRENT_TIMESTAMP
+               WHERE id = $1 AND deleted_at IS NOT NULL
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Tasks in a project's trash, most recently deleted first.
+    pub async fn find_deleted_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<TrashedTask>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>",
+                      deleted_at as "deleted_at!: DateTime<Utc>",
+                      detached_shared_task_id as "detached_shared_task_id: Uuid"
+               FROM tasks
+               WHERE project_id = $1 AND deleted_at IS NOT NULL
+               ORDER BY deleted_at DESC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|rec| TrashedTask {
+                task: Task {
+                    id: rec.id,
+                    project_id: rec.project_id,
+                    title: rec.title,
+                    description: rec.description,
+                    status: rec.status,
+                    parent_task_attempt: rec.parent_task_attempt,
+                    shared_task_id: rec.shared_task_id,
+                    created_at: rec.created_at,
+                    updated_at: rec.updated_at,
+                },
+                deleted_at: rec.deleted_at,
+                detached_shared_task_id: rec.detached_shared_task_id,
+            })
+            .collect())
+    }
+
+    /// Tasks, in any project, that were moved to the trash before `cutoff`.
+    pub async fn find_deleted_before(
+        pool: &SqlitePool,
+        cutoff: DateTime<Utc>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE deleted_at IS NOT NULL
+                 AND julianday(deleted_at) <= julianday($1)"#,
+            cutoff
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     pub async fn set_shared_task_id<'e, E>(
         executor: E,
         id: Uuid,
@@ -466,13 +704,31 @@ ORDER BY t.created_at DESC"#,
         Ok(())
     }
 
+    pub async fn set_parent_task_attempt<'e, E>(
+        executor: E,
+        id: Uuid,
+        parent_task_attempt: Uuid,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            "UPDATE tasks SET parent_task_attempt = $1 WHERE id = $2",
+            parent_task_attempt,
+            id
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
     pub async fn exists(
         pool: &SqlitePool,
         id: Uuid,
         project_id: Uuid,
     ) -> Result<bool, sqlx::Error> {
         let result = sqlx::query!(
-            "SELECT id as \"id!: Uuid\" FROM tasks WHERE id = $1 AND project_id = $2",
+            "SELECT id as \"id!: Uuid\" FROM tasks WHERE id = $1 AND project_id = $2 AND deleted_at IS NULL",
             id,
             project_id
         )
@@ -490,7 +746,7 @@ ORDER BY t.created_at DESC"#,
             Task,
             r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks 
-               WHERE parent_task_attempt = $1
+               WHERE parent_task_attempt = $1 AND deleted_at IS NULL
                ORDER BY created_at DESC"#,
             attempt_id,
         )
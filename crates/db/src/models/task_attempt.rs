@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use executors::executors::BaseCodingAgent;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool, Type};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type};
 use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
@@ -34,6 +34,17 @@ pub enum TaskAttemptStatus {
     ExecutorFailed,
 }
 
+/// Outcome of the latest run of the project's verify command for an attempt
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "verify_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyStatus {
+    Running,
+    Passed,
+    Failed,
+    TimedOut,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct TaskAttempt {
     pub id: Uuid,
@@ -45,10 +56,46 @@ pub struct TaskAttempt {
     // "GEMINI", etc.)
     pub worktree_deleted: bool, // Flag indicating if worktree has been cleaned up
     pub setup_completed_at: Option<DateTime<Utc>>, // When setup script was last completed
+    pub verify_status: Option<VerifyStatus>,
+    pub verify_output: Option<String>, // Tail of the verify output when it did not pass
+    pub retried_from_attempt_id: Option<Uuid>, // Attempt this one re-ran from scratch
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// An attempt's worktree and branch as seen by worktree cleanup
+#[derive(Debug, Clone)]
+pub struct WorktreeCleanupCandidate {
+    pub attempt_id: Uuid,
+    pub container_ref: Option<String>,
+    pub branch: String,
+    pub worktree_deleted: bool,
+    /// A process is running or the attempt is waiting in the execution queue
+    pub is_active: bool,
+    /// Merged directly or through a merged PR
+    pub is_merged: bool,
+    pub last_activity_at: DateTime<Utc>,
+}
+
+/// An attempt's worktree as seen by the disk usage scan
+#[derive(Debug, Clone)]
+pub struct WorktreeDiskScanCandidate {
+    pub attempt_id: Uuid,
+    pub container_ref: String,
+    /// A process is running in the worktree
+    pub is_active: bool,
+}
+
+/// Size of an attempt's worktree as of the last scan
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct WorktreeDiskUsage {
+    pub task_attempt_id: Uuid,
+    pub bytes: i64,
+    /// The scan ran out of time, so `bytes` is a lower bound
+    pub truncated: bool,
+    pub computed_at: DateTime<Utc>,
+}
+
 /// GitHub PR creation parameters
 pub struct CreatePrParams<'a> {
     pub attempt_id: Uuid,
@@ -79,11 +126,27 @@ pub struct TaskAttemptContext {
     pub project: Project,
 }
 
+/// An attempt restored from an exported board. Its worktree only existed on
+/// the exporting machine, so it is recorded as deleted.
+#[derive(Debug)]
+pub struct ImportTaskAttempt {
+    pub task_id: Uuid,
+    pub branch: String,
+    pub target_branch: String,
+    pub executor: String,
+    pub verify_status: Option<VerifyStatus>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Deserialize, TS)]
 pub struct CreateTaskAttempt {
     pub executor: BaseCodingAgent,
     pub base_branch: String,
     pub branch: String,
+    /// Set when the attempt re-runs an earlier one from scratch
+    #[serde(default)]
+    pub retried_from_attempt_id: Option<Uuid>,
 }
 
 impl TaskAttempt {
@@ -107,6 +170,9 @@ impl TaskAttempt {
                               executor AS "executor!",
                               worktree_deleted AS "worktree_deleted!: bool",
                               setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                              verify_status AS "verify_status: VerifyStatus",
+                              verify_output,
+                              retried_from_attempt_id AS "retried_from_attempt_id: Uuid",
                               created_at AS "created_at!: DateTime<Utc>",
                               updated_at AS "updated_at!: DateTime<Utc>"
                        FROM task_attempts
@@ -127,6 +193,9 @@ impl TaskAttempt {
                               executor AS "executor!",
                               worktree_deleted AS "worktree_deleted!: bool",
                               setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                              verify_status AS "verify_status: VerifyStatus",
+                              verify_output,
+                              retried_from_attempt_id AS "retried_from_attempt_id: Uuid",
                               created_at AS "created_at!: DateTime<Utc>",
                               updated_at AS "updated_at!: DateTime<Utc>"
                        FROM task_attempts
@@ -158,6 +227,9 @@ impl TaskAttempt {
                        ta.executor AS "executor!",
                        ta.worktree_deleted  AS "worktree_deleted!: bool",
                        ta.setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       ta.verify_status AS "verify_status: VerifyStatus",
+                       ta.verify_output,
+                       ta.retried_from_attempt_id AS "retried_from_attempt_id: Uuid",
                        ta.created_at        AS "created_at!: DateTime<Utc>",
                        ta.updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    task_attempts ta
@@ -220,6 +292,24 @@ impl TaskAttempt {
         Ok(())
     }
 
+    /// Record the verify outcome; `output` is cleared when it passed
+    pub async fn update_verify_status(
+        pool: &SqlitePool,
+        attempt_id: Uuid,
+        status: VerifyStatus,
+        output: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE task_attempts SET verify_status = $1, verify_output = $2, updated_at = datetime('now') WHERE id = $3",
+            status,
+            output,
+            attempt_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             TaskAttempt,
@@ -231,6 +321,9 @@ impl TaskAttempt {
                        executor AS "executor!",
                        worktree_deleted  AS "worktree_deleted!: bool",
                        setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       verify_status AS "verify_status: VerifyStatus",
+                       verify_output,
+                       retried_from_attempt_id AS "retried_from_attempt_id: Uuid",
                        created_at        AS "created_at!: DateTime<Utc>",
                        updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    task_attempts
@@ -252,6 +345,9 @@ impl TaskAttempt {
                        executor AS "executor!",
                        worktree_deleted  AS "worktree_deleted!: bool",
                        setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       verify_status AS "verify_status: VerifyStatus",
+                       verify_output,
+                       retried_from_attempt_id AS "retried_from_attempt_id: Uuid",
                        created_at        AS "created_at!: DateTime<Utc>",
                        updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    task_attempts
@@ -313,53 +409,120 @@ impl TaskAttempt {
         Ok(result.exists)
     }
 
-    /// Find task attempts that are expired (72+ hours since last activity) and eligible for worktree cleanup
-    /// Activity includes: execution completion, task attempt updates (including worktree recreation),
-    /// and any attempts that are currently in progress
-    pub async fn find_expired_for_cleanup(
+    pub async fn branch_exists(pool: &SqlitePool, branch: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"SELECT EXISTS(SELECT 1 FROM task_attempts WHERE branch = ?) as "exists!: bool""#,
+            branch
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(result.exists)
+    }
+
+    /// Every attempt of a project with what worktree cleanup needs to decide
+    /// whether its worktree and branch can go. Last activity is the later of
+    /// the attempt's own update and its latest finished process.
+    pub async fn find_cleanup_candidates(
         pool: &SqlitePool,
-    ) -> Result<Vec<(Uuid, String, String)>, sqlx::Error> {
-        let records = sqlx::query!(
-            r#"
-            SELECT ta.id as "attempt_id!: Uuid", ta.container_ref, p.git_repo_path as "git_repo_path!"
-            FROM task_attempts ta
-            LEFT JOIN execution_processes ep ON ta.id = ep.task_attempt_id AND ep.completed_at IS NOT NULL
-            JOIN tasks t ON ta.task_id = t.id
-            JOIN projects p ON t.project_id = p.id
-            WHERE ta.worktree_deleted = FALSE
-                -- Exclude attempts with any running processes (in progress)
-                AND ta.id NOT IN (
-                    SELECT DISTINCT ep2.task_attempt_id
-                    FROM execution_processes ep2
-                    WHERE ep2.completed_at IS NULL
-                )
-            GROUP BY ta.id, ta.container_ref, p.git_repo_path, ta.updated_at
-            HAVING datetime('now', '-72 hours') > datetime(
-                MAX(
-                    CASE
-                        WHEN ep.completed_at IS NOT NULL THEN ep.completed_at
-                        ELSE ta.updated_at
-                    END
-                )
-            )
-            ORDER BY MAX(
-                CASE
-                    WHEN ep.completed_at IS NOT NULL THEN ep.completed_at
-                    ELSE ta.updated_at
-                END
-            ) ASC
-            "#
+        project_id: Uuid,
+    ) -> Result<Vec<WorktreeCleanupCandidate>, sqlx::Error> {
+        sqlx::query_as!(
+            WorktreeCleanupCandidate,
+            r#"SELECT ta.id AS "attempt_id!: Uuid",
+                      ta.container_ref,
+                      ta.branch,
+                      ta.worktree_deleted AS "worktree_deleted!: bool",
+                      (EXISTS(SELECT 1 FROM execution_processes ep
+                              WHERE ep.task_attempt_id = ta.id AND ep.completed_at IS NULL)
+                       OR EXISTS(SELECT 1 FROM queued_attempts q
+                                 WHERE q.task_attempt_id = ta.id)) AS "is_active!: bool",
+                      EXISTS(SELECT 1 FROM merges m
+                             WHERE m.task_attempt_id = ta.id
+                               AND (m.merge_type = 'direct' OR m.pr_status = 'merged')) AS "is_merged!: bool",
+                      MAX(ta.updated_at, COALESCE(
+                          (SELECT MAX(ep.completed_at) FROM execution_processes ep
+                           WHERE ep.task_attempt_id = ta.id),
+                          ta.updated_at)) AS "last_activity_at!: DateTime<Utc>"
+               FROM task_attempts ta
+               JOIN tasks t ON ta.task_id = t.id
+               WHERE t.project_id = $1
+               ORDER BY ta.created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Attempts of the project whose worktree still exists.
+    pub async fn find_disk_scan_candidates(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<WorktreeDiskScanCandidate>, sqlx::Error> {
+        sqlx::query_as!(
+            WorktreeDiskScanCandidate,
+            r#"SELECT ta.id AS "attempt_id!: Uuid",
+                      ta.container_ref AS "container_ref!",
+                      EXISTS(SELECT 1 FROM execution_processes ep
+                             WHERE ep.task_attempt_id = ta.id AND ep.completed_at IS NULL) AS "is_active!: bool"
+               FROM task_attempts ta
+               JOIN tasks t ON ta.task_id = t.id
+               WHERE t.project_id = $1
+                 AND ta.container_ref IS NOT NULL
+                 AND ta.worktree_deleted = FALSE
+               ORDER BY ta.created_at ASC"#,
+            project_id
         )
         .fetch_all(pool)
+        .await
+    }
+
+    /// Store a scan result. Leaves `updated_at` alone, since a scan is not
+    /// activity on the attempt.
+    pub async fn record_worktree_disk_usage(
+        pool: &SqlitePool,
+        attempt_id: Uuid,
+        bytes: i64,
+        truncated: bool,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE task_attempts
+               SET worktree_disk_bytes = $1,
+                   worktree_disk_truncated = $2,
+                   worktree_disk_computed_at = datetime('now', 'subsec')
+               WHERE id = $3"#,
+            bytes,
+            truncated,
+            attempt_id
+        )
+        .execute(pool)
         .await?;
+        Ok(())
+    }
 
-        Ok(records
-            .into_iter()
-            .filter_map(|r| {
-                r.container_ref
-                    .map(|path| (r.attempt_id, path, r.git_repo_path))
-            })
-            .collect())
+    /// Last scanned size of each of the project's remaining worktrees,
+    /// largest first.
+    pub async fn worktree_disk_usage_by_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<WorktreeDiskUsage>, sqlx::Error> {
+        sqlx::query_as!(
+            WorktreeDiskUsage,
+            r#"SELECT ta.id AS "task_attempt_id!: Uuid",
+                      ta.worktree_disk_bytes AS "bytes!: i64",
+                      ta.worktree_disk_truncated AS "truncated!: bool",
+                      ta.worktree_disk_computed_at AS "computed_at!: DateTime<Utc>"
+               FROM task_attempts ta
+               JOIN tasks t ON ta.task_id = t.id
+               WHERE t.project_id = $1
+                 AND ta.worktree_deleted = FALSE
+                 AND ta.worktree_disk_bytes IS NOT NULL
+                 AND ta.worktree_disk_computed_at IS NOT NULL
+               ORDER BY ta.worktree_disk_bytes DESC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
     }
 
     pub async fn create(
@@ -372,9 +535,9 @@ impl TaskAttempt {
         // Insert the record into the database
         Ok(sqlx::query_as!(
             TaskAttempt,
-            r#"INSERT INTO task_attempts (id, task_id, container_ref, branch, target_branch, executor, worktree_deleted, setup_completed_at)
-               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", container_ref, branch, target_branch, executor as "executor!",  worktree_deleted as "worktree_deleted!: bool", setup_completed_at as "setup_completed_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO task_attempts (id, task_id, container_ref, branch, target_branch, executor, worktree_deleted, setup_completed_at, retried_from_attempt_id)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", container_ref, branch, target_branch, executor as "executor!",  worktree_deleted as "worktree_deleted!: bool", setup_completed_at as "setup_completed_at: DateTime<Utc>", verify_status as "verify_status: VerifyStatus", verify_output, retried_from_attempt_id as "retried_from_attempt_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             task_id,
             Option::<String>::None, // Container isn't known yet
@@ -382,12 +545,38 @@ impl TaskAttempt {
             data.base_branch, // Target branch is same as base branch during creation
             data.executor,
             false, // worktree_deleted is false during creation
-            Option::<DateTime<Utc>>::None // setup_completed_at is None during creation
+            Option::<DateTime<Utc>>::None, // setup_completed_at is None during creation
+            data.retried_from_attempt_id
         )
         .fetch_one(pool)
         .await?)
     }
 
+    pub async fn insert_imported<'e, E>(
+        executor: E,
+        data: &ImportTaskAttempt,
+        attempt_id: Uuid,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"INSERT INTO task_attempts (id, task_id, container_ref, branch, target_branch, executor, worktree_deleted, verify_status, created_at, updated_at)
+               VALUES ($1, $2, NULL, $3, $4, $5, TRUE, $6, $7, $8)"#,
+            attempt_id,
+            data.task_id,
+            data.branch,
+            data.target_branch,
+            data.executor,
+            data.verify_status,
+            data.created_at,
+            data.updated_at,
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
     pub async fn update_target_branch(
         pool: &SqlitePool,
         attempt_id: Uuid,
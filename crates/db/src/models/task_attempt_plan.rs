@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use executors::logs::plan::{PlanStep, parse_plan_steps};
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// The latest plan a plan-mode run presented for an attempt.
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct TaskAttemptPlan {
+    pub task_attempt_id: Uuid,
+    /// The run that presented the plan
+    pub execution_process_id: Uuid,
+    /// The plan as the agent wrote it
+    pub plan: String,
+    #[ts(type = "Array<PlanStep>")]
+    pub steps: sqlx::types::Json<Vec<PlanStep>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TaskAttemptPlan {
+    pub async fn find_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAttemptPlan,
+            r#"SELECT task_attempt_id as "task_attempt_id!: Uuid",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      plan,
+                      steps as "steps!: sqlx::types::Json<Vec<PlanStep>>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_attempt_plans
+               WHERE task_attempt_id = $1"#,
+            task_attempt_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Store `plan` for the attempt, split into steps, replacing any plan an
+    /// earlier run presented.
+    pub async fn record(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        execution_process_id: Uuid,
+        plan: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let steps = sqlx::types::Json(parse_plan_steps(plan));
+        sqlx::query_as!(
+            TaskAttemptPlan,
+            r#"INSERT INTO task_attempt_plans (task_attempt_id, execution_process_id, plan, steps)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT(task_attempt_id) DO UPDATE SET
+                   execution_process_id = excluded.execution_process_id,
+                   plan = excluded.plan,
+                   steps = excluded.steps,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING task_attempt_id as "task_attempt_id!: Uuid",
+                         execution_process_id as "execution_process_id!: Uuid",
+                         plan,
+                         steps as "steps!: sqlx::types::Json<Vec<PlanStep>>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            task_attempt_id,
+            execution_process_id,
+            plan,
+            steps
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Sqlite, SqlitePool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use super::task::{Task, TaskStatus};
+
+#[derive(Debug, Error)]
+pub enum TaskDependencyError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Task not found")]
+    TaskNotFound,
+    #[error("A task cannot depend on itself")]
+    SelfDependency,
+    #[error("Tasks can only depend on tasks in the same project")]
+    DifferentProject,
+    /// Task titles along the cycle, starting and ending with the same task
+    #[error("Dependency would create a cycle: {}", .0.join(" → "))]
+    Cycle(Vec<String>),
+}
+
+/// Edges of the dependency graph: a task waits for the tasks it depends on
+/// to be done before an attempt may start.
+pub struct TaskDependency;
+
+impl TaskDependency {
+    /// Tasks that `task_id` depends on. Tasks in the trash are left out, so
+    /// they do not block anything.
+    pub async fn find_dependencies(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Task>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.shared_task_id as "shared_task_id: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_dependencies td
+               JOIN tasks t ON t.id = td.depends_on_task_id
+               WHERE td.task_id = $1 AND t.deleted_at IS NULL
+               ORDER BY t.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Dependencies of `task_id` that are not done yet.
+    pub async fn find_blocking(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<Task>, sqlx::Error> {
+        Ok(Self::find_dependencies(pool, task_id)
+            .await?
+            .into_iter()
+            .filter(|task| task.status != TaskStatus::Done)
+            .collect())
+    }
+
+    /// Tasks that depend on `task_id`.
+    pub async fn find_dependent_ids(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT task_id AS "task_id!: Uuid"
+               FROM task_dependencies
+               WHERE depends_on_task_id = $1"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Record a dependency without checking it, for boards whose dependency
+    /// graph was validated as a whole before import.
+    pub async fn insert_imported<'e, E>(
+        executor: E,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"INSERT INTO task_dependencies (task_id, depends_on_task_id)
+               VALUES ($1, $2)
+               ON CONFLICT DO NOTHING"#,
+            task_id,
+            depends_on_task_id
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Make `task_id` wait for `depends_on_task_id`. Adding an existing
+    /// dependency again is a no-op.
+    pub async fn add(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<(), TaskDependencyError> {
+        if task_id == depends_on_task_id {
+            return Err(TaskDependencyError::SelfDependency);
+        }
+        let task = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(TaskDependencyError::TaskNotFound)?;
+        let depends_on = Task::find_by_id(pool, depends_on_task_id)
+            .await?
+            .ok_or(TaskDependencyError::TaskNotFound)?;
+        if task.project_id != depends_on.project_id {
+            return Err(TaskDependencyError::DifferentProject);
+        }
+
+        let mut tx = pool.begin().await?;
+        let edges = sqlx::query!(
+            r#"SELECT td.task_id AS "task_id!: Uuid",
+                      td.depends_on_task_id AS "depends_on_task_id!: Uuid"
+               FROM task_dependencies td
+               JOIN tasks t ON t.id = td.task_id
+               WHERE t.project_id = $1"#,
+            task.project_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut graph: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for edge in edges {
+            graph
+                .entry(edge.task_id)
+                .or_default()
+                .push(edge.depends_on_task_id);
+        }
+
+        if let Some(path) = find_path(&graph, depends_on_task_id, task_id) {
+            let mut chain = vec![task.title.clone()];
+            for id in path {
+                let title = sqlx::query_scalar!("SELECT title FROM tasks WHERE id = $1", id)
+                    .fetch_one(&mut *tx)
+                    .await?;
+                chain.push(title);
+            }
+            return Err(TaskDependencyError::Cycle(chain));
+        }
+
+        sqlx::query!(
+            r#"INSERT INTO task_dependencies (task_id, depends_on_task_id)
+               VALUES ($1, $2)
+               ON CONFLICT DO NOTHING"#,
+            task_id,
+            depends_on_task_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Returns false if there was no such dependency.
+    pub async fn remove(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM task_dependencies WHERE task_id = $1 AND depends_on_task_id = $2",
+            task_id,
+            depends_on_task_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Shortest path from `from` to `to` following dependency edges, both ends
+/// included.
+fn find_path(graph: &HashMap<Uuid, Vec<Uuid>>, from: Uuid, to: Uuid) -> Option<Vec<Uuid>> {
+    let mut previous: HashMap<Uuid, Uuid> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+    while let Some(node) = queue.pop_front() {
+        if node == to {
+            let mut path = vec![to];
+            let mut current = to;
+            while let Some(prev) = previous.get(&current) {
+                path.push(*prev);
+                current = *prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for next in graph.get(&node).into_iter().flatten() {
+            if *next != from && !previous.contains_key(next) {
+                previous.insert(*next, node);
+                queue.push_back(*next);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_follows_dependency_edges() {
+        let [a, b, c, d] = [(); 4].map(|_| Uuid::new_v4());
+        let graph = HashMap::from([(a, vec![b]), (b, vec![c, d]), (d, vec![a])]);
+        assert_eq!(find_path(&graph, b, a), Some(vec![b, d, a]));
+        assert_eq!(find_path(&graph, c, a), None);
+    }
+}
@@ -0,0 +1,185 @@
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+/// Marks matched terms in FTS5 snippets; stripped before returning them
+const MATCH_START: char = '\u{2}';
+const MATCH_END: char = '\u{3}';
+
+/// A matched term within [`TaskSearchHit::snippet`], as `[start, end)` in
+/// UTF-16 code units so it can be applied to JavaScript strings directly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+pub struct SearchHighlight {
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TaskSearchHit {
+    /// `None` for shared tasks that only exist on the remote server
+    pub task_id: Option<Uuid>,
+    pub shared_task_id: Option<Uuid>,
+    /// `None` if a remote-only task's project is not linked locally
+    pub project_id: Option<Uuid>,
+    pub title: String,
+    pub status: TaskStatus,
+    /// There is no local task to open; only the shared task exists
+    pub remote_only: bool,
+    /// Excerpt around the best match in the title, description or summary
+    pub snippet: String,
+    pub highlights: Vec<SearchHighlight>,
+}
+
+#[derive(Debug, FromRow)]
+struct TaskSearchRow {
+    task_id: Option<Uuid>,
+    shared_task_id: Option<Uuid>,
+    project_id: Option<Uuid>,
+    title: String,
+    status: TaskStatus,
+    remote_only: bool,
+    snippet: String,
+}
+
+pub struct TaskSearch;
+
+impl TaskSearch {
+    /// Tasks matching `query` across all projects, best matches first.
+    /// Shared tasks that already have a local task are only returned once,
+    /// as the local task.
+    pub async fn search(
+        pool: &SqlitePool,
+        query: &str,
+        project_id: Option<Uuid>,
+        status: Option<TaskStatus>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<TaskSearchHit>, sqlx::Error> {
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        // Runtime-checked: the compile-time macros cannot see into FTS5 tables
+        let rows = sqlx::query_as::<_, TaskSearchRow>(
+            r#"SELECT
+                   t.id AS task_id,
+                   COALESCE(t.shared_task_id, st.id) AS shared_task_id,
+                   COALESCE(t.project_id, p.id) AS project_id,
+                   COALESCE(t.title, st.title) AS title,
+                   COALESCE(t.status, st.status) AS status,
+                   t.id IS NULL AS remote_only,
+                   snippet(task_search, -1, char(2), char(3), '…', 24) AS snippet
+               FROM task_search
+               LEFT JOIN tasks t
+                   ON task_search.rowid > 0 AND t.rowid = task_search.rowid
+               LEFT JOIN shared_tasks st
+                   ON task_search.rowid < 0 AND st.rowid = -task_search.rowid
+               LEFT JOIN projects p
+                   ON st.id IS NOT NULL AND p.remote_project_id = st.remote_project_id
+               WHERE task_search MATCH $1
+                 AND ((t.id IS NOT NULL AND t.deleted_at IS NULL) OR (
+                     st.id IS NOT NULL
+                     AND NOT EXISTS (SELECT 1 FROM tasks lt WHERE lt.shared_task_id = st.id)
+                 ))
+                 AND ($2 IS NULL OR COALESCE(t.project_id, p.id) = $2)
+                 AND ($3 IS NULL OR COALESCE(t.status, st.status) = $3)
+               ORDER BY bm25(task_search, 10.0, 4.0, 1.0)
+               LIMIT $4 OFFSET $5"#,
+        )
+        .bind(fts_query)
+        .bind(project_id)
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let (snippet, highlights) = split_highlights(&row.snippet);
+                TaskSearchHit {
+                    task_id: row.task_id,
+                    shared_task_id: row.shared_task_id,
+                    project_id: row.project_id,
+                    title: row.title,
+                    status: row.status,
+                    remote_only: row.remote_only,
+                    snippet,
+                    highlights,
+                }
+            })
+            .collect())
+    }
+}
+
+/// Turn free text into an FTS5 query: every word must match, and the last
+/// one may be a prefix so results appear while typing. Words are quoted, so
+/// FTS5 operators in the input are searched for literally.
+fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    let (last, rest) = terms.split_last()?;
+    let mut query = rest.join(" ");
+    if !query.is_empty() {
+        query.push(' ');
+    }
+    query.push_str(last);
+    query.push('*');
+    Some(query)
+}
+
+fn split_highlights(marked: &str) -> (String, Vec<SearchHighlight>) {
+    let mut text = String::with_capacity(marked.len());
+    let mut highlights = Vec::new();
+    let mut offset = 0u32;
+    let mut start = None;
+    for c in marked.chars() {
+        match c {
+            MATCH_START => start = Some(offset),
+            MATCH_END => {
+                if let Some(start) = start.take() {
+                    highlights.push(SearchHighlight { start, end: offset });
+                }
+            }
+            _ => {
+                text.push(c);
+                offset += c.len_utf16() as u32;
+            }
+        }
+    }
+    (text, highlights)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn free_text_becomes_a_quoted_prefix_query() {
+        assert_eq!(fts_query("  "), None);
+        assert_eq!(fts_query("webh").as_deref(), Some("\"webh\"*"));
+        assert_eq!(
+            fts_query("payment \"webhook OR").as_deref(),
+            Some("\"payment\" \"\"\"webhook\" \"OR\"*")
+        );
+    }
+
+    #[test]
+    fn highlight_offsets_are_in_utf16_units() {
+        let (text, highlights) = split_highlights("fix 💳 \u{2}payment\u{3} \u{2}webhook\u{3}…");
+        assert_eq!(text, "fix 💳 payment webhook…");
+        assert_eq!(
+            highlights,
+            [
+                SearchHighlight { start: 7, end: 14 },
+                SearchHighlight { start: 15, end: 22 },
+            ]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use anyhow::Error as AnyhowError;
 use async_trait::async_trait;
@@ -18,7 +18,7 @@ use services::services::{
     analytics::{AnalyticsContext, AnalyticsService},
     approvals::Approvals,
     auth::AuthContext,
-    config::{Config, ConfigError},
+    config::{Config, ConfigError, reload::ConfigReloader},
     container::{ContainerError, ContainerService},
     drafts::DraftsService,
     events::{EventError, EventService},
@@ -27,14 +27,17 @@ use services::services::{
     filesystem_watcher::FilesystemWatcherError,
     git::{GitService, GitServiceError},
     image::{ImageError, ImageService},
+    mcp_config_watcher::{MCP_CONFIG_CHANGED_EVENT, McpConfigWatcher},
     pr_monitor::PrMonitorService,
+    remote_client::RemoteClient,
     share::{RemoteSync, RemoteSyncHandle, ShareConfig, SharePublisher},
+    shutdown::ShutdownSummary,
     worktree_manager::WorktreeError,
 };
 use sqlx::{Error as SqlxError, types::Uuid};
 use thiserror::Error;
-use tokio::sync::{Mutex, RwLock};
-use utils::sentry as sentry_utils;
+use tokio::sync::{Mutex, RwLock, broadcast};
+use utils::{network::NetworkConfigError, sentry as sentry_utils};
 
 #[derive(Debug, Clone, Copy, Error)]
 #[error("Remote client not configured")]
@@ -68,6 +71,8 @@ pub enum DeploymentError {
     Event(#[from] EventError),
     #[error(transparent)]
     Config(#[from] ConfigError),
+    #[error(transparent)]
+    Network(#[from] NetworkConfigError),
     #[error("Remote client not configured")]
     RemoteClientNotConfigured,
     #[error(transparent)]
@@ -82,6 +87,10 @@ pub trait Deployment: Clone + Send + Sync + 'static {
 
     fn config(&self) -> &Arc<RwLock<Config>>;
 
+    fn config_reloader(&self) -> &ConfigReloader;
+
+    fn mcp_config_watcher(&self) -> &McpConfigWatcher;
+
     fn db(&self) -> &DBService;
 
     fn analytics(&self) -> &Option<AnalyticsService>;
@@ -108,7 +117,7 @@ pub trait Deployment: Clone + Send + Sync + 'static {
 
     fn share_sync_handle(&self) -> &Arc<Mutex<Option<RemoteSyncHandle>>>;
 
-    fn spawn_remote_sync(&self, config: ShareConfig) {
+    fn spawn_remote_sync(&self, config: ShareConfig, remote_client: RemoteClient) {
         let deployment = self.clone();
         let handle_slot = self.share_sync_handle().clone();
         tokio::spawn(async move {
@@ -117,7 +126,9 @@ pub trait Deployment: Clone + Send + Sync + 'static {
             let remote_sync_handle = RemoteSync::spawn(
                 deployment.db().clone(),
                 config,
+                remote_client,
                 deployment.auth_context().clone(),
+                deployment.config().clone(),
             );
             {
                 let mut guard = handle_slot.lock().await;
@@ -126,6 +137,26 @@ pub trait Deployment: Clone + Send + Sync + 'static {
         });
     }
 
+    /// Stop the executors and wait for their output to be written, then stop
+    /// the shared task sync and close the database. Each step gets up to
+    /// `timeout`.
+    async fn shutdown(&self, timeout: Duration) -> ShutdownSummary {
+        let mut summary = self.container().shutdown(timeout).await;
+
+        let remote_sync = self.share_sync_handle().lock().await.take();
+        if let Some(handle) = remote_sync {
+            summary.remote_sync_stopped = tokio::time::timeout(timeout, handle.shutdown())
+                .await
+                .is_ok();
+            if !summary.remote_sync_stopped {
+                tracing::warn!("Shared task sync did not stop within {:?}", timeout);
+            }
+        }
+
+        self.db().pool.close().await;
+        summary
+    }
+
     async fn update_sentry_scope(&self) -> Result<(), DeploymentError> {
         let user_id = self.user_id();
         let config = self.config().read().await;
@@ -136,7 +167,7 @@ pub trait Deployment: Clone + Send + Sync + 'static {
         Ok(())
     }
 
-    async fn spawn_pr_monitor_service(&self) -> tokio::task::JoinHandle<()> {
+    fn pr_monitor(&self) -> PrMonitorService {
         let db = self.db().clone();
         let analytics = self
             .analytics()
@@ -146,7 +177,49 @@ pub trait Deployment: Clone + Send + Sync + 'static {
                 analytics_service: analytics_service.clone(),
             });
         let publisher = self.share_publisher().ok();
-        PrMonitorService::spawn(db, analytics, publisher).await
+        PrMonitorService::new(db, self.config().clone(), analytics, publisher)
+    }
+
+    async fn spawn_pr_monitor_service(&self) -> tokio::task::JoinHandle<()> {
+        let (completed_tx, mut completed_rx) = tokio::sync::mpsc::unbounded_channel();
+        let deployment = self.clone();
+        tokio::spawn(async move {
+            // Tasks closed by a merged PR may be what their dependents wait on
+            while let Some(task_id) = completed_rx.recv().await {
+                deployment
+                    .container()
+                    .start_unblocked_attempts(task_id)
+                    .await;
+            }
+        });
+        self.pr_monitor()
+            .notify_completed_tasks(completed_tx)
+            .spawn()
+    }
+
+    /// Apply edits to the config file made while the server is running
+    fn spawn_config_reloader(&self) -> Result<(), DeploymentError> {
+        let reloader = self.config_reloader().clone();
+        let mut changes = reloader.subscribe();
+        reloader.spawn()?;
+
+        let deployment = self.clone();
+        tokio::spawn(async move {
+            while let Ok(change) = changes.recv().await {
+                if change.changed.iter().any(|key| key == "github") {
+                    let _ = deployment.update_sentry_scope().await;
+                }
+                if change.changed.iter().any(|key| key == "executor_profile") {
+                    deployment.mcp_config_watcher().refresh();
+                }
+            }
+        });
+        Ok(())
+    }
+
+    fn spawn_mcp_config_watcher(&self) -> Result<(), DeploymentError> {
+        self.mcp_config_watcher().clone().spawn()?;
+        Ok(())
     }
 
     async fn track_if_analytics_allowed(&self, event_name: &str, properties: Value) {
@@ -184,8 +257,14 @@ pub trait Deployment: Clone + Send + Sync + 'static {
                         use_existing_repo: true,
                         setup_script: None,
                         dev_script: None,
+                        dev_server_url_pattern: None,
                         cleanup_script: None,
                         copy_files: None,
+                        verify_script: None,
+                        verify_timeout_secs: None,
+                        default_executor: None,
+                        max_concurrent_executions: None,
+                        close_task_on_pr_merge: None,
                     };
                     // Ensure existing repo has a main branch if it's empty
                     if let Err(e) = self.git().ensure_main_branch_exists(&repo.path) {
@@ -233,10 +312,30 @@ pub trait Deployment: Clone + Send + Sync + 'static {
     async fn stream_events(
         &self,
     ) -> futures::stream::BoxStream<'static, Result<Event, std::io::Error>> {
-        self.events()
-            .msg_store()
-            .history_plus_stream()
-            .map_ok(|m| m.to_sse_event())
-            .boxed()
+        let mcp_changes = futures::stream::unfold(
+            self.mcp_config_watcher().subscribe(),
+            |mut changes| async move {
+                loop {
+                    match changes.recv().await {
+                        Ok(executor) => {
+                            let event = Event::default()
+                                .event(MCP_CONFIG_CHANGED_EVENT)
+                                .data(executor.to_string());
+                            return Some((Ok(event), changes));
+                        }
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            },
+        );
+        futures::stream::select(
+            self.events()
+                .msg_store()
+                .history_plus_stream()
+                .map_ok(|m| m.to_sse_event()),
+            mcp_changes,
+        )
+        .boxed()
     }
 }
@@ -7,6 +7,7 @@ use ts_rs::TS;
 use crate::{
     actions::Executable,
     approvals::ExecutorApprovalService,
+    env::ExecutionEnv,
     executors::{BaseCodingAgent, ExecutorError, SpawnedChild, StandardCodingAgentExecutor},
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
@@ -15,6 +16,10 @@ use crate::{
 pub struct CodingAgentFollowUpRequest {
     pub prompt: String,
     pub session_id: String,
+    /// The task's own prompt. Used to brief a fresh session when `session_id`
+    /// can no longer be resumed.
+    #[serde(default)]
+    pub task_context: Option<String>,
     /// Executor profile specification
     #[serde(alias = "profile_variant_label")]
     // Backwards compatability with ProfileVariantIds, esp stored in DB under ExecutorAction
@@ -30,6 +35,41 @@ impl CodingAgentFollowUpRequest {
     pub fn base_executor(&self) -> BaseCodingAgent {
         self.executor_profile_id.executor
     }
+
+    /// Prompt for a fresh session that replaces one that could not be resumed.
+    pub fn fresh_session_prompt(&self) -> String {
+        match &self.task_context {
+            Some(task_context) => format!(
+                "{task_context}\n\nThe previous session for this task could not be resumed; \
+                 earlier work is in the working directory. Follow-up request:\n\n{}",
+                self.prompt
+            ),
+            None => self.prompt.clone(),
+        }
+    }
+}
+
+/// Run `resume`, and if the executor reports its session is gone, `fresh`
+/// instead. The session error is kept if the fresh spawn fails too.
+async fn spawn_with_session_fallback<R, F, Fut>(
+    resume: R,
+    fresh: F,
+) -> Result<SpawnedChild, ExecutorError>
+where
+    R: Future<Output = Result<SpawnedChild, ExecutorError>>,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<SpawnedChild, ExecutorError>>,
+{
+    match resume.await {
+        Err(ExecutorError::InvalidSessionId(session_id)) => {
+            tracing::warn!("Session {session_id} is gone; starting a fresh session instead");
+            fresh().await.map_err(|e| {
+                tracing::error!("Fresh session after {session_id} also failed: {e}");
+                ExecutorError::InvalidSessionId(session_id)
+            })
+        }
+        result => result,
+    }
 }
 
 #[async_trait]
@@ -38,6 +78,7 @@ impl Executable for CodingAgentFollowUpRequest {
         &self,
         current_dir: &Path,
         approvals: Arc<dyn ExecutorApprovalService>,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
         let executor_profile_id = self.get_executor_profile_id();
         let mut agent = ExecutorConfigs::get_cached()
@@ -48,8 +89,74 @@ impl Executable for CodingAgentFollowUpRequest {
 
         agent.use_approvals(approvals.clone());
 
-        agent
-            .spawn_follow_up(current_dir, &self.prompt, &self.session_id)
-            .await
+        let fresh_prompt = self.fresh_session_prompt();
+        spawn_with_session_fallback(
+            agent.spawn_follow_up(current_dir, &self.prompt, &self.session_id, env),
+            || agent.spawn(current_dir, &fresh_prompt, env),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use command_group::AsyncCommandGroup;
+
+    use super::*;
+
+    #[cfg(unix)]
+    fn spawn_true() -> Result<SpawnedChild, ExecutorError> {
+        Ok(tokio::process::Command::new("true").group_spawn()?.into())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stale_session_falls_back_to_fresh_spawn() {
+        let fresh_called = AtomicBool::new(false);
+        let spawned = spawn_with_session_fallback(
+            async { Err::<SpawnedChild, _>(ExecutorError::InvalidSessionId("abc".to_string())) },
+            || async {
+                fresh_called.store(true, Ordering::SeqCst);
+                spawn_true()
+            },
+        )
+        .await;
+        assert!(spawned.is_ok());
+        assert!(fresh_called.load(Ordering::SeqCst));
+
+        let failed = spawn_with_session_fallback(
+            async { Err::<SpawnedChild, _>(ExecutorError::InvalidSessionId("abc".to_string())) },
+            || async { Err(ExecutorError::SetupHelperNotSupported) },
+        )
+        .await;
+        assert!(matches!(failed, Err(ExecutorError::InvalidSessionId(id)) if id == "abc"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn other_results_skip_the_fallback() {
+        let fresh_called = AtomicBool::new(false);
+        let spawned = spawn_with_session_fallback(async { spawn_true() }, || async {
+            fresh_called.store(true, Ordering::SeqCst);
+            spawn_true()
+        })
+        .await;
+        assert!(spawned.is_ok());
+        assert!(!fresh_called.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn fresh_prompt_leads_with_task_context() {
+        let request = CodingAgentFollowUpRequest {
+            prompt: "Also update the docs".to_string(),
+            session_id: "abc".to_string(),
+            task_context: Some("Add a --verbose flag".to_string()),
+            executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+        };
+        let prompt = request.fresh_session_prompt();
+        assert!(prompt.starts_with("Add a --verbose flag\n\n"));
+        assert!(prompt.ends_with("Also update the docs"));
     }
 }
@@ -11,11 +11,13 @@ use crate::{
         coding_agent_initial::CodingAgentInitialRequest, script::ScriptRequest,
     },
     approvals::ExecutorApprovalService,
+    env::ExecutionEnv,
     executors::{BaseCodingAgent, ExecutorError, SpawnedChild},
 };
 pub mod coding_agent_follow_up;
 pub mod coding_agent_initial;
 pub mod script;
+pub mod setup_steps;
 
 #[enum_dispatch]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
@@ -71,6 +73,7 @@ pub trait Executable {
         &self,
         current_dir: &Path,
         approvals: Arc<dyn ExecutorApprovalService>,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError>;
 }
 
@@ -80,7 +83,8 @@ impl Executable for ExecutorAction {
         &self,
         current_dir: &Path,
         approvals: Arc<dyn ExecutorApprovalService>,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
-        self.typ.spawn(current_dir, approvals).await
+        self.typ.spawn(current_dir, approvals, env).await
     }
 }
@@ -1,4 +1,4 @@
-use std::{path::Path, sync::Arc};
+use std::{path::Path, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use command_group::AsyncCommandGroup;
@@ -8,9 +8,13 @@ use ts_rs::TS;
 use workspace_utils::shell::get_shell_command;
 
 use crate::{
-    actions::Executable,
+    actions::{
+        Executable,
+        setup_steps::{SetupStep, parse_setup_steps, steps_to_shell_script},
+    },
     approvals::ExecutorApprovalService,
-    executors::{ExecutorError, SpawnedChild},
+    env::ExecutionEnv,
+    executors::{ExecutorError, ExecutorExitResult, SpawnedChild},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
@@ -24,6 +28,7 @@ pub enum ScriptContext {
     CleanupScript,
     DevServer,
     ToolInstallScript,
+    VerifyScript,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
@@ -31,6 +36,24 @@ pub struct ScriptRequest {
     pub script: String,
     pub language: ScriptRequestLanguage,
     pub context: ScriptContext,
+    /// Kill the script and report [`ExecutorExitResult::TimedOut`] after this
+    /// many seconds
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Seconds without output before the run is reported as stalled,
+    /// overriding the user's setting. `Some(0)` turns stall detection off.
+    #[serde(default)]
+    pub stall_timeout_secs: Option<u64>,
+}
+
+impl ScriptRequest {
+    /// The named steps of a setup script written as a JSON array of steps.
+    pub fn setup_steps(&self) -> Option<Vec<SetupStep>> {
+        match self.context {
+            ScriptContext::SetupScript => parse_setup_steps(&self.script),
+            _ => None,
+        }
+    }
 }
 
 #[async_trait]
@@ -39,7 +62,12 @@ impl Executable for ScriptRequest {
         &self,
         current_dir: &Path,
         _approvals: Arc<dyn ExecutorApprovalService>,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
+        let script = match self.setup_steps() {
+            Some(steps) => steps_to_shell_script(&steps),
+            None => self.script.clone(),
+        };
         let (shell_cmd, shell_arg) = get_shell_command();
         let mut command = Command::new(shell_cmd);
         command
@@ -48,11 +76,29 @@ impl Executable for ScriptRequest {
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
             .arg(shell_arg)
-            .arg(&self.script)
+            .arg(&script)
             .current_dir(current_dir);
+        env.apply_to_command(&mut command);
 
         let child = command.group_spawn()?;
 
-        Ok(child.into())
+        let Some(timeout_secs) = self.timeout_secs else {
+            return Ok(child.into());
+        };
+        let (mut exit_tx, exit_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(timeout_secs)) => {
+                    let _ = exit_tx.send(ExecutorExitResult::TimedOut);
+                }
+                // The process exited on its own and the monitor stopped listening
+                _ = exit_tx.closed() => {}
+            }
+        });
+
+        Ok(SpawnedChild {
+            child,
+            exit_signal: Some(exit_rx),
+        })
     }
 }
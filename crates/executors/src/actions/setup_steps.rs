@@ -0,0 +1,296 @@
+//! Setup scripts written as a list of named steps instead of one shell blob.
+//!
+//! A project's setup script that is a JSON array of [`SetupStep`]s runs the
+//! steps in order, each in its own subshell, and prints a marker line around
+//! every step. [`SetupStepTracker`] reads those markers back out of stdout so
+//! each step can be shown with its own status and exit code. Any other setup
+//! script runs unchanged. Step scripts need a POSIX shell.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use ts_rs::TS;
+use workspace_utils::msg_store::MsgStore;
+
+use crate::logs::{
+    ActionType, CommandExitStatus, CommandRunResult, NormalizedEntry, NormalizedEntryType,
+    ToolStatus,
+    utils::{ConversationPatch, EntryIndexProvider},
+};
+
+const STEP_MARKER: &str = "__VK_SETUP_STEP__";
+const TOOL_NAME: &str = "Setup Step";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+pub struct SetupStep {
+    pub name: String,
+    pub command: String,
+    /// Run the following steps even if this one fails
+    #[serde(default)]
+    pub continue_on_error: bool,
+}
+
+/// The steps of a setup script given as a JSON array, or `None` for a plain
+/// shell script.
+pub fn parse_setup_steps(script: &str) -> Option<Vec<SetupStep>> {
+    if !script.trim_start().starts_with('[') {
+        return None;
+    }
+    serde_json::from_str::<Vec<SetupStep>>(script)
+        .ok()
+        .filter(|steps| !steps.is_empty())
+}
+
+/// A shell script running `steps` in order. A failing step ends the script
+/// with its exit code unless it allows errors.
+pub fn steps_to_shell_script(steps: &[SetupStep]) -> String {
+    let mut script = String::new();
+    for (index, step) in steps.iter().enumerate() {
+        script.push_str(&format!("echo '{STEP_MARKER} start {index}'\n"));
+        script.push_str(&format!("(\n{}\n)\n", step.command));
+        script.push_str("__vk_status=$?\n");
+        // Leading newline so the marker starts a line even after partial output
+        script.push_str(&format!(
+            "printf '\\n{STEP_MARKER} end {index} %s\\n' \"$__vk_status\"\n"
+        ));
+        if !step.continue_on_error {
+            script.push_str("[ \"$__vk_status\" -eq 0 ] || exit \"$__vk_status\"\n");
+        }
+    }
+    script.push_str("exit 0\n");
+    script
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum StepState {
+    Pending,
+    Running,
+    Finished(i32),
+}
+
+/// Per-step status and output, built from the script's stdout.
+pub struct SetupStepTracker {
+    steps: Vec<SetupStep>,
+    states: Vec<StepState>,
+    outputs: Vec<String>,
+    current: Option<usize>,
+}
+
+impl SetupStepTracker {
+    pub fn new(steps: Vec<SetupStep>) -> Self {
+        let len = steps.len();
+        Self {
+            steps,
+            states: vec![StepState::Pending; len],
+            outputs: vec![String::new(); len],
+            current: None,
+        }
+    }
+
+    /// Feed one line of stdout. Returns the step whose state changed.
+    pub fn push_line(&mut self, line: &str) -> Option<usize> {
+        let Some(at) = line.find(STEP_MARKER) else {
+            self.push_output(line);
+            return None;
+        };
+        if at > 0 {
+            self.push_output(&line[..at]);
+        }
+        let mut parts = line[at + STEP_MARKER.len()..].split_whitespace();
+        let (kind, index) = (parts.next()?, parts.next()?.parse::<usize>().ok()?);
+        if index >= self.steps.len() {
+            return None;
+        }
+        match kind {
+            "start" => {
+                self.states[index] = StepState::Running;
+                self.current = Some(index);
+            }
+            "end" => {
+                let code = parts.next().and_then(|c| c.parse().ok()).unwrap_or(-1);
+                self.states[index] = StepState::Finished(code);
+                self.current = None;
+            }
+            _ => return None,
+        }
+        Some(index)
+    }
+
+    fn push_output(&mut self, line: &str) {
+        if let Some(index) = self.current {
+            let output = &mut self.outputs[index];
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            output.push_str(line);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// The entry for step `index`. Its metadata carries the step's position,
+    /// `state` (`pending`, `running` or `finished`) and `exit_code`.
+    pub fn entry(&self, index: usize) -> NormalizedEntry {
+        let step = &self.steps[index];
+        let state = &self.states[index];
+        let (status, state_name, exit_code) = match state {
+            StepState::Pending => (ToolStatus::Created, "pending", None),
+            StepState::Running => (ToolStatus::Created, "running", None),
+            StepState::Finished(0) => (ToolStatus::Success, "finished", Some(0)),
+            StepState::Finished(code) => (ToolStatus::Failed, "finished", Some(*code)),
+        };
+        let output = self.outputs[index].trim_end();
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: TOOL_NAME.to_string(),
+                action_type: ActionType::CommandRun {
+                    command: step.command.clone(),
+                    result: (*state != StepState::Pending).then(|| CommandRunResult {
+                        exit_status: exit_code.map(|code| CommandExitStatus::ExitCode { code }),
+                        output: (!output.is_empty()).then(|| output.to_string()),
+                    }),
+                },
+                status,
+            },
+            content: step.name.clone(),
+            metadata: Some(json!({
+                "setup_step": index,
+                "state": state_name,
+                "exit_code": exit_code,
+                "continue_on_error": step.continue_on_error,
+            })),
+        }
+    }
+
+    pub fn entries(&self) -> Vec<NormalizedEntry> {
+        (0..self.len()).map(|index| self.entry(index)).collect()
+    }
+}
+
+/// Emit one entry per step up front and update it as the step starts and
+/// finishes.
+pub fn normalize_setup_step_logs(
+    msg_store: Arc<MsgStore>,
+    steps: Vec<SetupStep>,
+    entry_index_provider: EntryIndexProvider,
+) {
+    tokio::spawn(async move {
+        let mut tracker = SetupStepTracker::new(steps);
+        let indices: Vec<usize> = tracker
+            .entries()
+            .into_iter()
+            .map(|entry| {
+                let index = entry_index_provider.next();
+                msg_store.push_patch(ConversationPatch::add_normalized_entry(index, entry));
+                index
+            })
+            .collect();
+
+        let mut lines = msg_store.stdout_lines_stream();
+        while let Some(Ok(line)) = lines.next().await {
+            if let Some(index) = tracker.push_line(&line) {
+                msg_store.push_patch(ConversationPatch::replace(
+                    indices[index],
+                    tracker.entry(index),
+                ));
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(name: &str, command: &str) -> SetupStep {
+        SetupStep {
+            name: name.to_string(),
+            command: command.to_string(),
+            continue_on_error: false,
+        }
+    }
+
+    #[test]
+    fn plain_scripts_are_not_steps() {
+        assert_eq!(parse_setup_steps("npm install\nnpm run build"), None);
+        assert_eq!(
+            parse_setup_steps("[ -f .env ] || cp .env.example .env"),
+            None
+        );
+        assert_eq!(
+            parse_setup_steps(r#"[{"name": "Install", "command": "npm install"}]"#),
+            Some(vec![step("Install", "npm install")])
+        );
+    }
+
+    #[test]
+    fn tracks_step_status_and_output_from_markers() {
+        let steps = vec![
+            step("Install", "npm install"),
+            step("Build", "npm run build"),
+        ];
+        let mut tracker = SetupStepTracker::new(steps);
+        let changed: Vec<_> = [
+            "__VK_SETUP_STEP__ start 0",
+            "added 12 packages",
+            "",
+            "__VK_SETUP_STEP__ end 0 0",
+            "__VK_SETUP_STEP__ start 1",
+            "error: no such script__VK_SETUP_STEP__ end 1 1",
+        ]
+        .into_iter()
+        .filter_map(|line| tracker.push_line(line))
+        .collect();
+        assert_eq!(changed, [0, 0, 1, 1]);
+
+        let entries = tracker.entries();
+        let metadata = |i: usize| entries[i].metadata.clone().unwrap();
+        assert_eq!(metadata(0)["exit_code"], 0);
+        assert_eq!(metadata(1)["exit_code"], 1);
+        match &entries[1].entry_type {
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::CommandRun { result, .. },
+                status,
+                ..
+            } => {
+                assert!(matches!(status, ToolStatus::Failed));
+                assert_eq!(
+                    result.as_ref().unwrap().output.as_deref(),
+                    Some("error: no such script")
+                );
+            }
+            other => panic!("unexpected entry {other:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_step_stops_unless_errors_are_allowed() {
+        let mut steps = vec![step("Fails", "exit 3"), step("Never", "echo unreachable")];
+        let output = |steps: &[SetupStep]| {
+            std::process::Command::new("sh")
+                .arg("-c")
+                .arg(steps_to_shell_script(steps))
+                .output()
+                .unwrap()
+        };
+
+        let stopped = output(&steps);
+        assert_eq!(stopped.status.code(), Some(3));
+        assert!(!String::from_utf8_lossy(&stopped.stdout).contains("unreachable"));
+
+        steps[0].continue_on_error = true;
+        let continued = output(&steps);
+        assert!(continued.status.success());
+        assert!(String::from_utf8_lossy(&continued.stdout).contains("unreachable"));
+    }
+}
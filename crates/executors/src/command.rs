@@ -1,8 +1,14 @@
-use std::path::PathBuf;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
 
+use regex::{Captures, Regex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tokio::process::Command;
 use ts_rs::TS;
 use workspace_utils::shell::resolve_executable_path;
 
@@ -16,9 +22,11 @@ pub enum CommandBuildError {
     EmptyCommand,
     #[error("failed to quote command: {0}")]
     QuoteError(#[from] shlex::QuoteError),
+    #[error("argument has unbalanced quotes: {0}")]
+    UnbalancedQuotes(String),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CommandParts {
     program: String,
     args: Vec<String>,
@@ -29,6 +37,10 @@ impl CommandParts {
         Self { program, args }
     }
 
+    pub fn into_parts(self) -> (String, Vec<String>) {
+        (self.program, self.args)
+    }
+
     pub async fn into_resolved(self) -> Result<(PathBuf, Vec<String>), ExecutorError> {
         let CommandParts { program, args } = self;
         let executable = resolve_executable_path(&program)
@@ -38,6 +50,232 @@ impl CommandParts {
     }
 }
 
+/// The program, arguments and extra environment an executor launches with.
+/// Spawning and previewing both go through it, so a preview shows exactly
+/// what would run.
+#[derive(Debug, Clone)]
+pub struct LaunchCommand {
+    parts: CommandParts,
+    env: Vec<(String, String)>,
+}
+
+impl LaunchCommand {
+    pub fn new(parts: CommandParts) -> Self {
+        Self {
+            parts,
+            env: Vec::new(),
+        }
+    }
+
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// A command ready for the caller to set up stdio and the execution
+    /// environment on, then spawn.
+    pub async fn into_command(self, current_dir: &Path) -> Result<Command, ExecutorError> {
+        let (program_path, args) = self.parts.into_resolved().await?;
+        let mut command = Command::new(program_path);
+        command
+            .kill_on_drop(true)
+            .current_dir(current_dir)
+            .args(args)
+            .envs(self.env);
+        Ok(command)
+    }
+
+    pub async fn preview(self, current_dir: &Path) -> CommandPreview {
+        self.env.into_iter().fold(
+            CommandPreview::new(self.parts, current_dir).await,
+            |preview, (key, value)| preview.env(key, value),
+        )
+    }
+}
+
+pub(crate) const REDACTED: &str = "<redacted>";
+
+/// Name segments that mark an environment variable or flag as secret. A
+/// plural segment (`KEYS`) counts too.
+const SECRET_MARKERS: &[&str] = &[
+    "KEY",
+    "APIKEY",
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "CREDENTIAL",
+];
+
+/// What an executor would run for a new session, without spawning it. Values
+/// that look like secrets are redacted so the preview can be shared.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct CommandPreview {
+    pub program: String,
+    /// Where `program` resolves on the server's PATH, if it does
+    pub resolved_program: Option<String>,
+    pub args: Vec<String>,
+    /// Variables the executor sets on top of the server's environment
+    pub env: BTreeMap<String, String>,
+    /// Secret-looking variables the process would inherit from the server.
+    /// Only names are listed.
+    pub inherited_secrets: Vec<String>,
+    pub working_dir: String,
+    /// Shell line that reproduces the invocation
+    pub command_line: String,
+}
+
+impl CommandPreview {
+    pub async fn new(parts: CommandParts, working_dir: &Path) -> Self {
+        let resolved_program = resolve_executable_path(&parts.program)
+            .await
+            .map(|path| path.to_string_lossy().into_owned());
+        let mut inherited_secrets: Vec<String> = std::env::vars_os()
+            .filter_map(|(key, _)| key.into_string().ok())
+            .filter(|key| is_secret_name(key))
+            .collect();
+        inherited_secrets.sort();
+
+        let mut preview = Self {
+            program: parts.program,
+            resolved_program,
+            args: redact_args(parts.args),
+            env: BTreeMap::new(),
+            inherited_secrets,
+            working_dir: working_dir.to_string_lossy().into_owned(),
+            command_line: String::new(),
+        };
+        preview.command_line = preview.render_command_line();
+        preview
+    }
+
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let key = key.into();
+        let value = if is_secret_name(&key) {
+            REDACTED.to_string()
+        } else {
+            value.into()
+        };
+        self.env.insert(key, value);
+        self.command_line = self.render_command_line();
+        self
+    }
+
+    fn render_command_line(&self) -> String {
+        let quote = |s: &str| {
+            shlex::try_quote(s)
+                .map(|quoted| quoted.into_owned())
+                .unwrap_or_else(|_| s.to_string())
+        };
+        let mut words = vec!["cd".to_string(), quote(&self.working_dir), "&&".to_string()];
+        words.extend(
+            self.env
+                .iter()
+                .map(|(key, value)| format!("{key}={}", quote(value))),
+        );
+        words.push(quote(&self.program));
+        words.extend(self.args.iter().map(|arg| quote(arg)));
+        words.join(" ")
+    }
+}
+
+/// Whether a variable or flag name has a secret marker as one of its
+/// segments, so `API_KEY`, `--api-key` and `apiKey` match but `KEYBOARD` and
+/// `MONKEY_MODE` do not.
+fn is_secret_name(name: &str) -> bool {
+    name_segments(name).iter().any(|segment| {
+        let segment = segment.strip_suffix('S').unwrap_or(segment);
+        SECRET_MARKERS.contains(&segment)
+    })
+}
+
+/// Split a name on separators and camelCase boundaries, uppercased.
+fn name_segments(name: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            segments.push(std::mem::take(&mut current));
+            prev_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && prev_lower {
+            segments.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        current.push(c.to_ascii_uppercase());
+    }
+    segments.push(current);
+    segments.retain(|segment| !segment.is_empty());
+    segments
+}
+
+/// Redact `--api-key=value` and the argument after `--api-key`.
+fn redact_args(args: Vec<String>) -> Vec<String> {
+    let mut redact_next = false;
+    args.into_iter()
+        .map(|arg| {
+            if std::mem::take(&mut redact_next) {
+                return REDACTED.to_string();
+            }
+            if !arg.starts_with('-') {
+                return arg;
+            }
+            match arg.split_once('=') {
+                Some((flag, _)) if is_secret_name(flag) => format!("{flag}={REDACTED}"),
+                Some(_) => arg,
+                None => {
+                    redact_next = is_secret_name(&arg);
+                    arg
+                }
+            }
+        })
+        .collect()
+}
+
+/// Shorter secret values are not scrubbed from free text, where they would
+/// match ordinary words.
+const MIN_SCRUBBED_SECRET_LEN: usize = 8;
+
+static SECRET_ASSIGNMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?P<name>[A-Za-z_][A-Za-z0-9_-]*)=(?P<value>[^\s"'&;,]+)"#).unwrap()
+});
+
+static SECRET_FLAG_VALUE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?P<flag>(?:^|\s)--?[A-Za-z][A-Za-z0-9_-]*)(?P<sep>[ \t]+)(?P<value>[^\s-]\S*)")
+        .unwrap()
+});
+
+/// Redact secrets from free text such as logs or diffs, by the same rules
+/// as [`CommandPreview`]: values of secret-looking variables in the server's
+/// environment, `NAME=value` assignments and `--flag value` pairs whose name
+/// looks secret.
+pub fn redact_secrets(text: &str) -> String {
+    let mut text = text.to_string();
+    for (key, value) in std::env::vars() {
+        if value.len() >= MIN_SCRUBBED_SECRET_LEN && is_secret_name(&key) {
+            text = text.replace(&value, REDACTED);
+        }
+    }
+    let text = SECRET_ASSIGNMENT.replace_all(&text, |caps: &Captures| {
+        if is_secret_name(&caps["name"]) {
+            format!("{}={REDACTED}", &caps["name"])
+        } else {
+            caps[0].to_string()
+        }
+    });
+    SECRET_FLAG_VALUE
+        .replace_all(&text, |caps: &Captures| {
+            if is_secret_name(&caps["flag"]) {
+                format!("{}{}{REDACTED}", &caps["flag"], &caps["sep"])
+            } else {
+                caps[0].to_string()
+            }
+        })
+        .into_owned()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema, Default)]
 pub struct CmdOverrides {
     #[schemars(
@@ -142,6 +380,16 @@ fn split_command_line(input: &str) -> Result<Vec<String>, CommandBuildError> {
     }
 }
 
+/// Check extra arguments before they are saved. They are joined into the
+/// command line and split again, so one with an unbalanced quote would break
+/// every command it is added to.
+pub fn validate_extra_args(args: &[String]) -> Result<(), CommandBuildError> {
+    match args.iter().find(|arg| shlex::split(arg).is_none()) {
+        Some(arg) => Err(CommandBuildError::UnbalancedQuotes(arg.clone())),
+        None => Ok(()),
+    }
+}
+
 pub fn apply_overrides(builder: CommandBuilder, overrides: &CmdOverrides) -> CommandBuilder {
     let builder = if let Some(ref base) = overrides.base_command_override {
         builder.override_base(base.clone())
@@ -154,3 +402,95 @@ pub fn apply_overrides(builder: CommandBuilder, overrides: &CmdOverrides) -> Com
         builder
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extra_args_with_unbalanced_quotes_are_rejected() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(validate_extra_args(&args(&["--model", "claude-opus-4", "--name 'a b'"])).is_ok());
+        assert!(matches!(
+            validate_extra_args(&args(&["--model", "\"claude"])),
+            Err(CommandBuildError::UnbalancedQuotes(arg)) if arg == "\"claude"
+        ));
+    }
+
+    #[test]
+    fn secrets_are_redacted_from_text() {
+        let text = "$ GITHUB_TOKEN=ghp_abc123 deploy --api-key sk-live --model fast\nPORT=3000";
+        assert_eq!(
+            redact_secrets(text),
+            "$ GITHUB_TOKEN=<redacted> deploy --api-key <redacted> --model fast\nPORT=3000"
+        );
+    }
+
+    #[test]
+    fn secret_names_match_whole_segments() {
+        for name in [
+            "ANTHROPIC_API_KEY",
+            "--api-key",
+            "apiKey",
+            "OPENAI_APIKEY",
+            "GITHUB_TOKEN",
+            "AWS_SECRET_ACCESS_KEY",
+            "--credentials",
+        ] {
+            assert!(is_secret_name(name), "{name} should be secret");
+        }
+        for name in ["--keyboard", "MONKEY_MODE", "TOKENIZER", "--model", "PORT"] {
+            assert!(!is_secret_name(name), "{name} should not be secret");
+        }
+    }
+
+    #[tokio::test]
+    async fn preview_redacts_secrets() {
+        let parts = CommandParts::new(
+            "npx".to_string(),
+            vec![
+                "my-agent".to_string(),
+                "--api-key=sk-live".to_string(),
+                "--token".to_string(),
+                "abc123".to_string(),
+                "--model".to_string(),
+                "fast model".to_string(),
+            ],
+        );
+        let preview = CommandPreview::new(parts, Path::new("/work/tree"))
+            .await
+            .env("NO_COLOR", "1")
+            .env("ANTHROPIC_API_KEY", "sk-ant");
+
+        assert_eq!(
+            preview.args,
+            [
+                "my-agent",
+                "--api-key=<redacted>",
+                "--token",
+                "<redacted>",
+                "--model",
+                "fast model"
+            ]
+        );
+        assert_eq!(preview.env["ANTHROPIC_API_KEY"], "<redacted>");
+        assert!(!preview.command_line.contains("sk-"));
+        assert_eq!(
+            shlex::split(&preview.command_line).unwrap(),
+            [
+                "cd",
+                "/work/tree",
+                "&&",
+                "ANTHROPIC_API_KEY=<redacted>",
+                "NO_COLOR=1",
+                "npx",
+                "my-agent",
+                "--api-key=<redacted>",
+                "--token",
+                "<redacted>",
+                "--model",
+                "fast model"
+            ]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Extra environment variables for executor processes, configured per
+//! project.
+
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+use tokio::process::Command;
+
+use crate::command::REDACTED;
+
+/// Variables the process cannot run correctly without. Overriding them needs
+/// an explicit opt-in.
+const DANGEROUS_VARS: &[&str] = &[
+    "PATH",
+    "HOME",
+    "SHELL",
+    "LD_PRELOAD",
+    "LD_LIBRARY_PATH",
+    "DYLD_LIBRARY_PATH",
+    "DYLD_INSERT_LIBRARIES",
+];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum EnvVarError {
+    #[error("`{0}` is not a valid environment variable name")]
+    InvalidName(String),
+    #[error("overriding `{0}` can break executors; set allow_dangerous to do it anyway")]
+    Dangerous(String),
+}
+
+/// Check project variables before they are saved.
+pub fn validate_env_vars(
+    vars: &BTreeMap<String, String>,
+    allow_dangerous: bool,
+) -> Result<(), EnvVarError> {
+    for name in vars.keys() {
+        let mut chars = name.chars();
+        let valid = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(EnvVarError::InvalidName(name.clone()));
+        }
+        if !allow_dangerous && DANGEROUS_VARS.contains(&name.as_str()) {
+            return Err(EnvVarError::Dangerous(name.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Replace `${VAR}` with `VAR` from the server's environment. Unset variables
+/// expand to an empty string; a `$` not followed by `{NAME}` is kept as is.
+pub fn interpolate(value: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) => {
+                out.push_str(&lookup(&after[..end]).unwrap_or_default());
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Resolved variables set on top of the server's environment when an
+/// executor process is spawned.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionEnv {
+    vars: BTreeMap<String, String>,
+}
+
+impl ExecutionEnv {
+    /// Resolve a project's configured variables against the current
+    /// environment.
+    pub fn from_project_vars(vars: &BTreeMap<String, String>) -> Self {
+        Self {
+            vars: vars
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.clone(),
+                        interpolate(value, |var| std::env::var(var).ok()),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty()
+    }
+
+    pub fn apply_to_command(&self, command: &mut Command) {
+        command.envs(&self.vars);
+    }
+
+    /// Variable names with their values hidden, for logs and previews.
+    pub fn masked(&self) -> BTreeMap<String, String> {
+        self.vars
+            .keys()
+            .map(|name| (name.clone(), REDACTED.to_string()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolates_host_variables() {
+        let lookup = |name: &str| (name == "HOST").then(|| "example.com".to_string());
+        assert_eq!(
+            interpolate("https://${HOST}/api", lookup),
+            "https://example.com/api"
+        );
+        assert_eq!(interpolate("${MISSING}-x", lookup), "-x");
+        assert_eq!(interpolate("$HOST ${HOST", lookup), "$HOST ${HOST");
+    }
+
+    #[test]
+    fn rejects_dangerous_overrides_unless_allowed() {
+        let vars = BTreeMap::from([("PATH".to_string(), "/opt/bin".to_string())]);
+        assert_eq!(
+            validate_env_vars(&vars, false),
+            Err(EnvVarError::Dangerous("PATH".to_string()))
+        );
+        assert_eq!(validate_env_vars(&vars, true), Ok(()));
+
+        let vars = BTreeMap::from([("1BAD".to_string(), String::new())]);
+        assert_eq!(
+            validate_env_vars(&vars, true),
+            Err(EnvVarError::InvalidName("1BAD".to_string()))
+        );
+    }
+}
@@ -8,7 +8,7 @@ use agent_client_protocol as proto;
 use agent_client_protocol::Agent as _;
 use command_group::{AsyncCommandGroup, AsyncGroupChild};
 use futures::StreamExt;
-use tokio::{io::AsyncWriteExt, process::Command, sync::mpsc};
+use tokio::{io::AsyncWriteExt, sync::mpsc};
 use tokio_util::{
     compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt},
     io::ReaderStream,
@@ -18,7 +18,8 @@ use workspace_utils::stream_lines::LinesStreamExt;
 
 use super::{AcpClient, SessionManager};
 use crate::{
-    command::CommandParts,
+    command::{CommandParts, LaunchCommand},
+    env::ExecutionEnv,
     executors::{ExecutorError, ExecutorExitResult, SpawnedChild, acp::AcpEvent},
 };
 
@@ -49,22 +50,26 @@ impl AcpAgentHarness {
         }
     }
 
+    /// What an ACP agent is launched with, for spawning and previews alike
+    pub fn launch_command(command_parts: CommandParts) -> LaunchCommand {
+        LaunchCommand::new(command_parts).env("NODE_NO_WARNINGS", "1")
+    }
+
     pub async fn spawn_with_command(
         &self,
         current_dir: &Path,
         prompt: String,
         command_parts: CommandParts,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
-        let (program_path, args) = command_parts.into_resolved().await?;
-        let mut command = Command::new(program_path);
+        let mut command = Self::launch_command(command_parts)
+            .into_command(current_dir)
+            .await?;
         command
-            .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args)
-            .env("NODE_NO_WARNINGS", "1");
+            .stderr(Stdio::piped());
+        env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
@@ -91,17 +96,16 @@ impl AcpAgentHarness {
         prompt: String,
         session_id: &str,
         command_parts: CommandParts,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
-        let (program_path, args) = command_parts.into_resolved().await?;
-        let mut command = Command::new(program_path);
+        let mut command = Self::launch_command(command_parts)
+            .into_command(current_dir)
+            .await?;
         command
-            .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args)
-            .env("NODE_NO_WARNINGS", "1");
+            .stderr(Stdio::piped());
+        env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
@@ -18,7 +18,7 @@ use crate::logs::{
     ActionType, FileChange, NormalizedEntry, NormalizedEntryError, NormalizedEntryType, ToolResult,
     ToolResultValueType, ToolStatus as LogToolStatus,
     stderr_processor::normalize_stderr_logs,
-    utils::{ConversationPatch, EntryIndexProvider},
+    utils::{ConversationPatch, EntryIndexProvider, json_guard, search::SearchMetadata},
 };
 
 pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
@@ -38,6 +38,12 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
 
         let mut stdout_lines = msg_store.stdout_lines_stream();
         while let Some(Ok(line)) = stdout_lines.next().await {
+            if json_guard::check_line(line.trim()).is_err() {
+                let idx = entry_index.next();
+                let entry = json_guard::fallback_entry(line.trim());
+                msg_store.push_patch(ConversationPatch::add_normalized_entry(idx, entry));
+                continue;
+            }
             if let Some(parsed) = AcpEventParser::parse_line(&line) {
                 debug!("Parsed ACP line: {:?}", parsed);
                 match parsed {
@@ -232,7 +238,11 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                     status: convert_tool_status(&tool_data.status),
                 },
                 content: get_tool_content(tool_data),
-                metadata: None,
+                metadata: search_metadata(tool_data, worktree_path).map(|search| {
+                    let mut metadata = serde_json::json!({});
+                    search.attach(&mut metadata);
+                    metadata
+                }),
             };
             let patch = if is_new {
                 ConversationPatch::add_normalized_entry(tool_data.index, entry)
@@ -408,6 +418,40 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
             }
         }
 
+        fn search_metadata(
+            tc: &PartialToolCallData,
+            worktree_path: &Path,
+        ) -> Option<SearchMetadata> {
+            if tc.kind != agent_client_protocol::ToolKind::Search {
+                return None;
+            }
+            let web = extract_tool_name_from_id(tc.id.0.as_ref())
+                .is_some_and(|name| name.contains("web_search"));
+            let mut search = SearchMetadata {
+                path: tc
+                    .raw_input
+                    .as_ref()
+                    .and_then(|v| serde_json::from_value::<SearchArgs>(v.clone()).ok())
+                    .and_then(|a| a.path)
+                    .map(|path| {
+                        workspace_utils::path::make_path_relative(
+                            &path,
+                            &worktree_path.to_string_lossy(),
+                        )
+                    }),
+                web,
+                result_count: None,
+            };
+            if matches!(tc.status, agent_client_protocol::ToolCallStatus::Completed) {
+                let output = collect_text_content(&tc.content)
+                    .or_else(|| tc.raw_output.as_ref()?.as_str().map(str::to_string));
+                if let Some(output) = output {
+                    search.record_output(&output);
+                }
+            }
+            Some(search)
+        }
+
         fn extract_file_changes(tc: &PartialToolCallData) -> Vec<FileChange> {
             let mut changes = Vec::new();
             for c in &tc.content {
@@ -642,7 +686,10 @@ impl TryFrom<SessionNotification> for AcpEvent {
 
 #[derive(Debug, Clone, Deserialize)]
 struct SearchArgs {
+    #[serde(alias = "pattern")]
     query: String,
+    #[serde(default)]
+    path: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
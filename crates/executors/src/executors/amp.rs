@@ -4,12 +4,13 @@ use async_trait::async_trait;
 use command_group::AsyncCommandGroup;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tokio::{io::AsyncWriteExt, process::Command};
+use tokio::io::AsyncWriteExt;
 use ts_rs::TS;
 use workspace_utils::msg_store::MsgStore;
 
 use crate::{
-    command::{CmdOverrides, CommandBuilder, apply_overrides},
+    command::{CmdOverrides, CommandBuilder, CommandPreview, LaunchCommand, apply_overrides},
+    env::ExecutionEnv,
     executors::{
         AppendPrompt, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
         claude::{ClaudeLogProcessor, HistoryStrategy},
@@ -44,20 +45,23 @@ impl Amp {
 
 #[async_trait]
 impl StandardCodingAgentExecutor for Amp {
-    async fn spawn(&self, current_dir: &Path, prompt: &str) -> Result<SpawnedChild, ExecutorError> {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
         let command_parts = self.build_command_builder().build_initial()?;
-        let (executable_path, args) = command_parts.into_resolved().await?;
-
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        let mut command = Command::new(executable_path);
+        let mut command = LaunchCommand::new(command_parts)
+            .into_command(current_dir)
+            .await?;
         command
-            .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args);
+            .stderr(Stdio::piped());
+        env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
@@ -75,6 +79,7 @@ impl StandardCodingAgentExecutor for Amp {
         current_dir: &Path,
         prompt: &str,
         session_id: &str,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
         // 1) Fork the thread synchronously to obtain new thread id
         let builder = self.build_command_builder();
@@ -83,15 +88,12 @@ impl StandardCodingAgentExecutor for Amp {
             "fork".to_string(),
             session_id.to_string(),
         ])?;
-        let (fork_program, fork_args) = fork_line.into_resolved().await?;
-        let fork_output = Command::new(fork_program)
-            .kill_on_drop(true)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&fork_args)
-            .output()
+        let mut fork_command = LaunchCommand::new(fork_line)
+            .into_command(current_dir)
             .await?;
+        fork_command.stdout(Stdio::piped()).stderr(Stdio::piped());
+        env.apply_to_command(&mut fork_command);
+        let fork_output = fork_command.output().await?;
         let stdout_str = String::from_utf8_lossy(&fork_output.stdout);
         let new_thread_id = stdout_str
             .lines()
@@ -114,18 +116,16 @@ impl StandardCodingAgentExecutor for Amp {
             "continue".to_string(),
             new_thread_id.clone(),
         ])?;
-        let (continue_program, continue_args) = continue_line.into_resolved().await?;
-
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        let mut command = Command::new(continue_program);
+        let mut command = LaunchCommand::new(continue_line)
+            .into_command(current_dir)
+            .await?;
         command
-            .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&continue_args);
+            .stderr(Stdio::piped());
+        env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
@@ -138,6 +138,11 @@ impl StandardCodingAgentExecutor for Amp {
         Ok(child.into())
     }
 
+    async fn preview_command(&self, current_dir: &Path) -> Result<CommandPreview, ExecutorError> {
+        let command_parts = self.build_command_builder().build_initial()?;
+        Ok(LaunchCommand::new(command_parts).preview(current_dir).await)
+    }
+
     fn normalize_logs(&self, msg_store: Arc<MsgStore>, current_dir: &Path) {
         let entry_index_provider = EntryIndexProvider::start_from(&msg_store);
 
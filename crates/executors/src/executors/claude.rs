@@ -3,14 +3,13 @@ pub mod client;
 pub mod protocol;
 pub mod types;
 
-use std::{collections::HashMap, path::Path, process::Stdio, sync::Arc};
+use std::{collections::HashMap, path::Path, process::Stdio, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
-use command_group::AsyncCommandGroup;
+use command_group::{AsyncCommandGroup, AsyncGroupChild};
 use futures::StreamExt;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tokio::process::Command;
 use ts_rs::TS;
 use workspace_utils::{
     approvals::ApprovalStatus, diff::create_unified_diff, log_msg::LogMsg, msg_store::MsgStore,
@@ -20,7 +19,10 @@ use workspace_utils::{
 use self::{client::ClaudeAgentClient, protocol::ProtocolPeer, types::PermissionMode};
 use crate::{
     approvals::ExecutorApprovalService,
-    command::{CmdOverrides, CommandBuilder, CommandParts, apply_overrides},
+    command::{
+        CmdOverrides, CommandBuilder, CommandParts, CommandPreview, LaunchCommand, apply_overrides,
+    },
+    env::ExecutionEnv,
     executors::{
         AppendPrompt, AvailabilityInfo, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
         codex::client::LogWriter,
@@ -29,11 +31,30 @@ use crate::{
         ActionType, FileChange, NormalizedEntry, NormalizedEntryError, NormalizedEntryType,
         TodoItem, ToolStatus,
         stderr_processor::normalize_stderr_logs,
-        utils::{EntryIndexProvider, patch::ConversationPatch},
+        usage::{UsageSummary, stdout_lines, token_count},
+        utils::{
+            EntryIndexProvider, json_guard,
+            mcp::{McpServerStatus, McpStatusMetadata},
+            patch::ConversationPatch,
+            search::SearchMetadata,
+            shell::file_action_from_command,
+        },
     },
-    stdout_dup::create_stdout_pipe_writer,
+    stdout_dup::{create_stdout_pipe_writer, duplicate_stderr},
 };
 
+/// Printed by Claude Code when `--resume` names a session it does not have,
+/// e.g. after `~/.claude` was cleaned or the worktree was recreated.
+const SESSION_NOT_FOUND_MARKER: &str = "No conversation found with session ID";
+
+/// How long a resumed process is watched for [`SESSION_NOT_FOUND_MARKER`].
+/// Claude checks the session on startup, so the message comes early or not at
+/// all.
+const SESSION_PROBE_WINDOW: Duration = Duration::from_secs(3);
+
+/// Only the start of stderr is checked for the marker.
+const SESSION_PROBE_MAX_BYTES: usize = 4096;
+
 fn base_command(claude_code_router: bool) -> &'static str {
     if claude_code_router {
         "npx -y @musistudio/claude-code-router@1.0.66 code"
@@ -151,10 +172,15 @@ impl StandardCodingAgentExecutor for ClaudeCode {
         self.approvals_service = Some(approvals);
     }
 
-    async fn spawn(&self, current_dir: &Path, prompt: &str) -> Result<SpawnedChild, ExecutorError> {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
         let command_builder = self.build_command_builder().await;
         let command_parts = command_builder.build_initial()?;
-        self.spawn_internal(current_dir, prompt, command_parts)
+        self.spawn_internal(current_dir, prompt, command_parts, env)
             .await
     }
 
@@ -163,6 +189,7 @@ impl StandardCodingAgentExecutor for ClaudeCode {
         current_dir: &Path,
         prompt: &str,
         session_id: &str,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
         let command_builder = self.build_command_builder().await;
         let command_parts = command_builder.build_follow_up(&[
@@ -170,8 +197,66 @@ impl StandardCodingAgentExecutor for ClaudeCode {
             "--resume".to_string(),
             session_id.to_string(),
         ])?;
-        self.spawn_internal(current_dir, prompt, command_parts)
-            .await
+        let mut spawned = self
+            .spawn_internal(current_dir, prompt, command_parts, env)
+            .await?;
+
+        if session_not_found(&mut spawned.child).await? {
+            tracing::warn!("Claude Code has no session {session_id}");
+            let _ = spawned.child.kill().await;
+            return Err(ExecutorError::InvalidSessionId(session_id.to_string()));
+        }
+        Ok(spawned)
+    }
+
+    /// Each run ends with a `result` line carrying that run's totals.
+    fn usage_from_logs(&self, history: &[LogMsg]) -> Option<UsageSummary> {
+        let runs: Vec<UsageSummary> = stdout_lines(history)
+            .iter()
+            .filter_map(|line| match serde_json::from_str::<ClaudeJson>(line) {
+                Ok(ClaudeJson::Result {
+                    usage: Some(usage),
+                    total_cost_usd,
+                    ..
+                }) => Some(usage.summary(total_cost_usd)),
+                _ => None,
+            })
+            .collect();
+        (!runs.is_empty()).then(|| runs.into_iter().sum())
+    }
+
+    /// Plans are presented through the `ExitPlanMode` tool.
+    fn plan_from_logs(&self, history: &[LogMsg]) -> Option<String> {
+        if !self.plan.unwrap_or(false) {
+            return None;
+        }
+        stdout_lines(history)
+            .iter()
+            .filter_map(|line| serde_json::from_str::<ClaudeJson>(line).ok())
+            .flat_map(|json| match json {
+                ClaudeJson::Assistant { message, .. } => message
+                    .content
+                    .into_iter()
+                    .filter_map(|item| match item {
+                        ClaudeContentItem::ToolUse {
+                            tool_data: ClaudeToolData::ExitPlanMode { plan },
+                            ..
+                        } => Some(plan),
+                        _ => None,
+                    })
+                    .collect(),
+                ClaudeJson::ToolUse {
+                    tool_data: ClaudeToolData::ExitPlanMode { plan },
+                    ..
+                } => vec![plan],
+                _ => Vec::new(),
+            })
+            .last()
+    }
+
+    async fn preview_command(&self, current_dir: &Path) -> Result<CommandPreview, ExecutorError> {
+        let command_parts = self.build_command_builder().await.build_initial()?;
+        Ok(LaunchCommand::new(command_parts).preview(current_dir).await)
     }
 
     fn normalize_logs(&self, msg_store: Arc<MsgStore>, current_dir: &Path) {
@@ -218,18 +303,18 @@ impl ClaudeCode {
         current_dir: &Path,
         prompt: &str,
         command_parts: CommandParts,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
-        let (program_path, args) = command_parts.into_resolved().await?;
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        let mut command = Command::new(program_path);
+        let mut command = LaunchCommand::new(command_parts)
+            .into_command(current_dir)
+            .await?;
         command
-            .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args);
+            .stderr(Stdio::piped());
+        env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
         let child_stdout = child.inner().stdout.take().ok_or_else(|| {
@@ -298,6 +383,7 @@ pub struct ClaudeLogProcessor {
     strategy: HistoryStrategy,
     streaming_messages: HashMap<String, StreamingMessageState>,
     streaming_message_id: Option<String>,
+    todo_history: TodoHistory,
 }
 
 impl ClaudeLogProcessor {
@@ -313,6 +399,7 @@ impl ClaudeLogProcessor {
             strategy,
             streaming_messages: HashMap::new(),
             streaming_message_id: None,
+            todo_history: TodoHistory::default(),
         }
     }
 
@@ -360,7 +447,7 @@ impl ClaudeLogProcessor {
                         continue;
                     }
 
-                    match serde_json::from_str::<ClaudeJson>(trimmed) {
+                    match json_guard::parse_line::<ClaudeJson>(trimmed) {
                         Ok(claude_json) => {
                             // Extract session ID if present
                             if !session_id_extracted
@@ -380,20 +467,11 @@ impl ClaudeLogProcessor {
                             }
                         }
                         Err(_) => {
-                            // Handle non-JSON output as raw system message
-                            if !trimmed.is_empty() {
-                                let entry = NormalizedEntry {
-                                    timestamp: None,
-                                    entry_type: NormalizedEntryType::SystemMessage,
-                                    content: trimmed.to_string(),
-                                    metadata: None,
-                                };
-
-                                let patch_id = entry_index_provider.next();
-                                let patch =
-                                    ConversationPatch::add_normalized_entry(patch_id, entry);
-                                msg_store.push_patch(patch);
-                            }
+                            // Handle non-JSON or rejected output as raw system message
+                            let entry = json_guard::fallback_entry(trimmed);
+                            let patch_id = entry_index_provider.next();
+                            let patch = ConversationPatch::add_normalized_entry(patch_id, entry);
+                            msg_store.push_patch(patch);
                         }
                     }
                 }
@@ -404,12 +482,7 @@ impl ClaudeLogProcessor {
 
             // Handle any remaining content in buffer
             if !buffer.trim().is_empty() {
-                let entry = NormalizedEntry {
-                    timestamp: None,
-                    entry_type: NormalizedEntryType::SystemMessage,
-                    content: buffer.trim().to_string(),
-                    metadata: None,
-                };
+                let entry = json_guard::fallback_entry(buffer.trim());
 
                 let patch_id = entry_index_provider.next();
                 let patch = ConversationPatch::add_normalized_entry(patch_id, entry);
@@ -452,6 +525,37 @@ impl ClaudeLogProcessor {
         }
     }
 
+    /// Report the MCP servers listed in the init message. Claude carries on
+    /// without the tools of servers that failed to launch, so those are
+    /// shown as an error.
+    fn mcp_status_entry(servers: &Option<Vec<McpServerStatus>>) -> Option<NormalizedEntry> {
+        let servers = servers.as_ref().filter(|servers| !servers.is_empty())?;
+        let mcp = McpStatusMetadata::new(servers.clone());
+        if mcp.degraded {
+            let failed: Vec<_> = servers
+                .iter()
+                .filter(|server| !server.is_connected())
+                .map(|server| server.name.as_str())
+                .collect();
+            tracing::warn!(?failed, "Claude started without some MCP servers");
+        }
+
+        let mut metadata = serde_json::json!({});
+        mcp.attach(&mut metadata);
+        Some(NormalizedEntry {
+            timestamp: None,
+            entry_type: if mcp.degraded {
+                NormalizedEntryType::ErrorMessage {
+                    error_type: NormalizedEntryError::Other,
+                }
+            } else {
+                NormalizedEntryType::SystemMessage
+            },
+            content: mcp.describe(),
+            metadata: Some(metadata),
+        })
+    }
+
     /// Normalize Claude tool_result content to either Markdown string or parsed JSON.
     /// - If content is a string that parses as JSON, return Json with parsed value.
     /// - If content is a string (non-JSON), return Markdown with the raw string.
@@ -490,6 +594,23 @@ impl ClaudeLogProcessor {
         (crate::logs::ToolResultValueType::Json, content.clone())
     }
 
+    /// The text of a tool_result's content, either a string or an array of
+    /// `{ text }` items.
+    fn tool_result_text(content: &serde_json::Value) -> Option<String> {
+        if let Some(s) = content.as_str() {
+            return Some(s.to_string());
+        }
+        serde_json::from_value::<Vec<ClaudeToolResultTextItem>>(content.clone())
+            .ok()
+            .map(|items| {
+                items
+                    .into_iter()
+                    .map(|i| i.text)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+    }
+
     /// Convert Claude content item to normalized entry
     fn content_item_to_normalized_entry(
         content_item: &ClaudeContentItem,
@@ -524,16 +645,7 @@ impl ClaudeLogProcessor {
                 let action_type = Self::extract_action_type(tool_data, worktree_path);
                 let content =
                     Self::generate_concise_content(tool_data, &action_type, worktree_path);
-
-                // Create metadata with tool_call_id for approval matching
-                let mut metadata =
-                    serde_json::to_value(content_item).unwrap_or(serde_json::Value::Null);
-                if let Some(obj) = metadata.as_object_mut() {
-                    obj.insert(
-                        "tool_call_id".to_string(),
-                        serde_json::Value::String(id.clone()),
-                    );
-                }
+                let metadata = Self::tool_use_metadata(id, tool_data, worktree_path, None);
 
                 Some(NormalizedEntry {
                     timestamp: None,
@@ -553,6 +665,54 @@ impl ClaudeLogProcessor {
         }
     }
 
+    /// Metadata of a tool use entry: the tool call itself, its id for
+    /// approval matching, and for searches their scope and, given the
+    /// tool's `output`, the number of results.
+    fn tool_use_metadata(
+        id: &str,
+        tool_data: &ClaudeToolData,
+        worktree_path: &str,
+        output: Option<&str>,
+    ) -> serde_json::Value {
+        let item = ClaudeContentItem::ToolUse {
+            id: id.to_string(),
+            tool_data: tool_data.clone(),
+        };
+        let mut metadata = serde_json::to_value(&item).unwrap_or(serde_json::Value::Null);
+        if let Some(obj) = metadata.as_object_mut() {
+            obj.insert(
+                "tool_call_id".to_string(),
+                serde_json::Value::String(id.to_string()),
+            );
+        }
+        if let Some(mut search) = Self::search_metadata(tool_data, worktree_path) {
+            if let Some(output) = output {
+                search.record_output(output);
+            }
+            search.attach(&mut metadata);
+        }
+        metadata
+    }
+
+    fn search_metadata(tool_data: &ClaudeToolData, worktree_path: &str) -> Option<SearchMetadata> {
+        let path = match tool_data {
+            ClaudeToolData::Grep { path, .. } | ClaudeToolData::Glob { path, .. } => path,
+            ClaudeToolData::WebSearch { .. } => {
+                return Some(SearchMetadata {
+                    web: true,
+                    ..Default::default()
+                });
+            }
+            _ => return None,
+        };
+        Some(SearchMetadata {
+            path: path
+                .as_deref()
+                .map(|path| make_path_relative(path, worktree_path)),
+            ..Default::default()
+        })
+    }
+
     /// Extract action type from structured tool data
     fn extract_action_type(tool_data: &ClaudeToolData, worktree_path: &str) -> ActionType {
         match tool_data {
@@ -608,15 +768,31 @@ impl ClaudeLogProcessor {
                     changes: diffs,
                 }
             }
-            ClaudeToolData::Bash { command, .. } => ActionType::CommandRun {
-                command: command.clone(),
-                result: None,
+            ClaudeToolData::Bash { command, .. } => {
+                file_action_from_command(command, worktree_path).unwrap_or_else(|| {
+                    ActionType::CommandRun {
+                        command: command.clone(),
+                        result: None,
+                    }
+                })
+            }
+            ClaudeToolData::DeleteFile { file_path } => ActionType::FileDelete {
+                path: make_path_relative(file_path, worktree_path),
+            },
+            ClaudeToolData::MoveFile {
+                source_path,
+                destination_path,
+            } => ActionType::FileMove {
+                from: make_path_relative(source_path, worktree_path),
+                to: make_path_relative(destination_path, worktree_path),
             },
             ClaudeToolData::Grep { pattern, .. } => ActionType::Search {
                 query: pattern.clone(),
             },
             ClaudeToolData::WebFetch { url, .. } => ActionType::WebFetch { url: url.clone() },
-            ClaudeToolData::WebSearch { query, .. } => ActionType::WebFetch { url: query.clone() },
+            ClaudeToolData::WebSearch { query, .. } => ActionType::Search {
+                query: query.clone(),
+            },
             ClaudeToolData::Task {
                 description,
                 prompt,
@@ -714,6 +890,7 @@ impl ClaudeLogProcessor {
             ClaudeJson::System {
                 subtype,
                 api_key_source,
+                mcp_servers,
                 ..
             } => {
                 // emit billing warning if required
@@ -722,6 +899,11 @@ impl ClaudeLogProcessor {
                     patches.push(ConversationPatch::add_normalized_entry(idx, warning));
                 }
 
+                if let Some(entry) = Self::mcp_status_entry(mcp_servers) {
+                    let idx = entry_index_provider.next();
+                    patches.push(ConversationPatch::add_normalized_entry(idx, entry));
+                }
+
                 // keep the existing behaviour for the normal system message
                 match subtype.as_deref() {
                     Some("init") => {
@@ -775,20 +957,31 @@ impl ClaudeLogProcessor {
                         ClaudeContentItem::ToolUse { id, tool_data } => {
                             let tool_name = tool_data.get_name().to_string();
                             let action_type = Self::extract_action_type(tool_data, worktree_path);
-                            let content_text = Self::generate_concise_content(
+                            let mut content_text = Self::generate_concise_content(
                                 tool_data,
                                 &action_type,
                                 worktree_path,
                             );
 
-                            // Create metadata with tool_call_id for approval matching
                             let mut metadata =
-                                serde_json::to_value(item).unwrap_or(serde_json::Value::Null);
-                            if let Some(obj) = metadata.as_object_mut() {
-                                obj.insert(
-                                    "tool_call_id".to_string(),
-                                    serde_json::Value::String(id.clone()),
-                                );
+                                Self::tool_use_metadata(id, tool_data, worktree_path, None);
+
+                            if let ActionType::TodoManagement { todos, .. } = &action_type
+                                && matches!(tool_data, ClaudeToolData::TodoWrite { .. })
+                            {
+                                let changes = self.todo_history.record(id, todos);
+                                if let Some(text) = todo_write_content(todos, changes.as_ref()) {
+                                    content_text = text;
+                                }
+                                if let Some(obj) = metadata.as_object_mut() {
+                                    obj.insert(
+                                        "todos".to_string(),
+                                        serde_json::json!({
+                                            "items": todos,
+                                            "changes": changes,
+                                        }),
+                                    );
+                                }
                             }
 
                             let entry = NormalizedEntry {
@@ -854,6 +1047,7 @@ impl ClaudeLogProcessor {
                         }
                         entry_index_provider.reset();
                         self.tool_map.clear();
+                        self.todo_history = TodoHistory::default();
                     }
 
                     for item in &message.content {
@@ -931,20 +1125,58 @@ impl ClaudeLogProcessor {
                                 ToolStatus::Success
                             };
 
+                            // Deletes and moves keep their file action; only the
+                            // status changes
+                            let action_type =
+                                match Self::extract_action_type(&info.tool_data, worktree_path) {
+                                    file_action @ (ActionType::FileDelete { .. }
+                                    | ActionType::FileMove { .. }) => file_action,
+                                    _ => ActionType::CommandRun {
+                                        command: info.content.clone(),
+                                        result,
+                                    },
+                                };
                             let entry = NormalizedEntry {
                                 timestamp: None,
                                 entry_type: NormalizedEntryType::ToolUse {
                                     tool_name: info.tool_name.clone(),
-                                    action_type: ActionType::CommandRun {
-                                        command: info.content.clone(),
-                                        result,
-                                    },
+                                    action_type,
                                     status,
                                 },
                                 content: info.content.clone(),
                                 metadata: None,
                             };
                             patches.push(ConversationPatch::replace(info.entry_index, entry));
+                        } else if Self::search_metadata(&info.tool_data, worktree_path).is_some() {
+                            let failed = is_error.unwrap_or(false);
+                            let output = if failed {
+                                None
+                            } else {
+                                Self::tool_result_text(content)
+                            };
+                            let entry = NormalizedEntry {
+                                timestamp: None,
+                                entry_type: NormalizedEntryType::ToolUse {
+                                    tool_name: info.tool_name.clone(),
+                                    action_type: Self::extract_action_type(
+                                        &info.tool_data,
+                                        worktree_path,
+                                    ),
+                                    status: if failed {
+                                        ToolStatus::Failed
+                                    } else {
+                                        ToolStatus::Success
+                                    },
+                                },
+                                content: info.content.clone(),
+                                metadata: Some(Self::tool_use_metadata(
+                                    tool_use_id,
+                                    &info.tool_data,
+                                    worktree_path,
+                                    output.as_deref(),
+                                )),
+                            };
+                            patches.push(ConversationPatch::replace(info.entry_index, entry));
                         } else if matches!(
                             info.tool_data,
                             ClaudeToolData::Unknown { .. }
@@ -1165,6 +1397,8 @@ impl ClaudeLogProcessor {
         match action_type {
             ActionType::FileRead { path } => format!("`{path}`"),
             ActionType::FileEdit { path, .. } => format!("`{path}`"),
+            ActionType::FileDelete { path } => format!("`{path}`"),
+            ActionType::FileMove { from, to } => format!("`{from}` → `{to}`"),
             ActionType::CommandRun { command, .. } => format!("`{command}`"),
             ActionType::Search { query } => format!("`{query}`"),
             ActionType::WebFetch { url } => format!("`{url}`"),
@@ -1407,6 +1641,29 @@ impl StreamingContentState {
     }
 }
 
+/// Whether a resumed process reports on stderr that its session is gone.
+/// Gives up once the process exits, the probe window passes, or enough
+/// stderr has been seen. The child's stderr is left intact for the logs.
+async fn session_not_found(child: &mut AsyncGroupChild) -> Result<bool, ExecutorError> {
+    let mut stderr = duplicate_stderr(child)?;
+    let probe = async move {
+        let mut seen = String::new();
+        while let Some(Ok(chunk)) = stderr.next().await {
+            seen.push_str(&chunk);
+            if seen.contains(SESSION_NOT_FOUND_MARKER) {
+                return true;
+            }
+            if seen.len() > SESSION_PROBE_MAX_BYTES {
+                break;
+            }
+        }
+        false
+    };
+    Ok(tokio::time::timeout(SESSION_PROBE_WINDOW, probe)
+        .await
+        .unwrap_or(false))
+}
+
 // Data structures for parsing Claude's JSON output format
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(tag = "type")]
@@ -1420,6 +1677,8 @@ pub enum ClaudeJson {
         model: Option<String>,
         #[serde(default, rename = "apiKeySource")]
         api_key_source: Option<String>,
+        #[serde(default)]
+        mcp_servers: Option<Vec<McpServerStatus>>,
     },
     #[serde(rename = "assistant")]
     Assistant {
@@ -1470,6 +1729,10 @@ pub enum ClaudeJson {
         num_turns: Option<u32>,
         #[serde(default, alias = "sessionId")]
         session_id: Option<String>,
+        #[serde(default)]
+        usage: Option<ClaudeUsage>,
+        #[serde(default)]
+        total_cost_usd: Option<f64>,
     },
     #[serde(rename = "approval_response")]
     ApprovalResponse {
@@ -1580,6 +1843,20 @@ pub struct ClaudeUsage {
     pub service_tier: Option<String>,
 }
 
+impl ClaudeUsage {
+    fn summary(&self, cost_usd: Option<f64>) -> UsageSummary {
+        UsageSummary {
+            input_tokens: token_count(self.input_tokens.unwrap_or_default()),
+            output_tokens: token_count(self.output_tokens.unwrap_or_default()),
+            cache_read_input_tokens: token_count(self.cache_read_input_tokens.unwrap_or_default()),
+            cache_creation_input_tokens: token_count(
+                self.cache_creation_input_tokens.unwrap_or_default(),
+            ),
+            cost_usd,
+        }
+    }
+}
+
 /// Structured tool data for Claude tools based on real samples
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(tag = "name", content = "input")]
@@ -1651,6 +1928,18 @@ pub enum ClaudeToolData {
         file_path: String,
         content: String,
     },
+    #[serde(rename = "DeleteFile", alias = "delete_file")]
+    DeleteFile {
+        #[serde(alias = "path")]
+        file_path: String,
+    },
+    #[serde(rename = "MoveFile", alias = "move_file", alias = "rename_file")]
+    MoveFile {
+        #[serde(alias = "source", alias = "from")]
+        source_path: String,
+        #[serde(alias = "destination", alias = "to")]
+        destination_path: String,
+    },
     #[serde(rename = "NotebookEdit", alias = "notebook_edit")]
     NotebookEdit {
         notebook_path: String,
@@ -1755,6 +2044,104 @@ pub struct ClaudeTodoItem {
     pub priority: Option<String>,
 }
 
+/// The TODO list as of the conversation's latest TodoWrite, so each write
+/// can be shown as what changed since the one before it.
+#[derive(Debug, Default)]
+struct TodoHistory {
+    latest: Option<(String, Vec<TodoItem>)>,
+    previous: Option<Vec<TodoItem>>,
+}
+
+impl TodoHistory {
+    /// Record the todos written by tool use `tool_use_id`. Returns the
+    /// changes against the previous write, if there was one. Seeing the same
+    /// tool use again (e.g. once streamed, once complete) diffs against the
+    /// same previous write.
+    fn record(&mut self, tool_use_id: &str, todos: &[TodoItem]) -> Option<TodoChanges> {
+        match self.latest.take() {
+            Some((id, _)) if id == tool_use_id => {}
+            Some((_, latest)) => self.previous = Some(latest),
+            None => self.previous = None,
+        }
+        self.latest = Some((tool_use_id.to_string(), todos.to_vec()));
+        self.previous
+            .as_deref()
+            .map(|previous| TodoChanges::between(previous, todos))
+    }
+}
+
+/// Todos matched by content between two TodoWrites
+#[derive(Debug, Default, Serialize, PartialEq)]
+struct TodoChanges {
+    added: Vec<String>,
+    completed: Vec<String>,
+    removed: Vec<String>,
+}
+
+impl TodoChanges {
+    fn between(previous: &[TodoItem], current: &[TodoItem]) -> Self {
+        fn find<'a>(todos: &'a [TodoItem], content: &str) -> Option<&'a TodoItem> {
+            todos.iter().find(|todo| todo.content == content)
+        }
+        let mut changes = Self::default();
+        for todo in current {
+            match find(previous, &todo.content) {
+                None => changes.added.push(todo.content.clone()),
+                Some(before) if todo.status == "completed" && before.status != "completed" => {
+                    changes.completed.push(todo.content.clone())
+                }
+                Some(_) => {}
+            }
+        }
+        changes.removed = previous
+            .iter()
+            .filter(|todo| find(current, &todo.content).is_none())
+            .map(|todo| todo.content.clone())
+            .collect();
+        changes
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.completed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Content of a TodoWrite entry: what changed since the previous write, or
+/// the whole checklist for the first write and for writes that only move
+/// items between pending and in progress. `None` for an empty first write.
+fn todo_write_content(todos: &[TodoItem], changes: Option<&TodoChanges>) -> Option<String> {
+    if let Some(changes) = changes.filter(|changes| !changes.is_empty()) {
+        let lines = [
+            ("Added", &changes.added),
+            ("Completed", &changes.completed),
+            ("Removed", &changes.removed),
+        ]
+        .into_iter()
+        .filter(|(_, items)| !items.is_empty())
+        .map(|(label, items)| format!("{label}: {}", items.join(", ")))
+        .collect::<Vec<_>>();
+        return Some(lines.join("\n"));
+    }
+    if todos.is_empty() {
+        return None;
+    }
+    let lines = todos
+        .iter()
+        .map(|todo| {
+            let mark = if todo.status == "completed" { "x" } else { " " };
+            let mut line = format!("- [{mark}] {}", todo.content);
+            if todo.status == "in_progress" {
+                line.push_str(" (in progress)");
+            }
+            if let Some(priority) = &todo.priority {
+                line.push_str(&format!(" [{priority}]"));
+            }
+            line
+        })
+        .collect::<Vec<_>>();
+    Some(lines.join("\n"))
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct ClaudeEditItem {
     pub old_string: Option<String>,
@@ -1775,6 +2162,8 @@ impl ClaudeToolData {
             ClaudeToolData::Edit { .. } => "Edit",
             ClaudeToolData::MultiEdit { .. } => "MultiEdit",
             ClaudeToolData::Write { .. } => "Write",
+            ClaudeToolData::DeleteFile { .. } => "DeleteFile",
+            ClaudeToolData::MoveFile { .. } => "MoveFile",
             ClaudeToolData::NotebookEdit { .. } => "NotebookEdit",
             ClaudeToolData::WebFetch { .. } => "WebFetch",
             ClaudeToolData::WebSearch { .. } => "WebSearch",
@@ -1901,6 +2290,91 @@ mod tests {
         assert_eq!(result, "TODO list updated");
     }
 
+    fn todo_write(id: &str, todos: &str) -> ClaudeJson {
+        serde_json::from_str(&format!(
+            r#"{{"type":"assistant","message":{{"role":"assistant","content":[
+                {{"type":"tool_use","id":"{id}","name":"TodoWrite","input":{{"todos":{todos}}}}}
+            ]}}}}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_todo_write_first_write_renders_checklist() {
+        let mut processor = ClaudeLogProcessor::new();
+        let entries = normalize_helper(
+            &mut processor,
+            &todo_write(
+                "t1",
+                r#"[
+                    {"content":"Write parser","status":"in_progress","priority":"high"},
+                    {"content":"Add tests","status":"pending"}
+                ]"#,
+            ),
+            "",
+        );
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].content,
+            "- [ ] Write parser (in progress) [high]\n- [ ] Add tests"
+        );
+        let todos = &entries[0].metadata.as_ref().unwrap()["todos"];
+        assert_eq!(todos["items"][0]["status"], "in_progress");
+        assert_eq!(todos["items"][0]["priority"], "high");
+        assert!(todos["changes"].is_null());
+    }
+
+    #[test]
+    fn test_todo_write_update_renders_changes() {
+        let mut processor = ClaudeLogProcessor::new();
+        normalize_helper(
+            &mut processor,
+            &todo_write(
+                "t1",
+                r#"[
+                    {"content":"Write parser","status":"in_progress"},
+                    {"content":"Add tests","status":"pending"},
+                    {"content":"Update docs","status":"pending"}
+                ]"#,
+            ),
+            "",
+        );
+        let update = todo_write(
+            "t2",
+            r#"[
+                {"content":"Write parser","status":"completed"},
+                {"content":"Add tests","status":"in_progress"},
+                {"content":"Fix lints","status":"pending"}
+            ]"#,
+        );
+        let entries = normalize_helper(&mut processor, &update, "");
+
+        let expected = "Added: Fix lints\nCompleted: Write parser\nRemoved: Update docs";
+        assert_eq!(entries[0].content, expected);
+        let changes = &entries[0].metadata.as_ref().unwrap()["todos"]["changes"];
+        assert_eq!(changes["added"], serde_json::json!(["Fix lints"]));
+        assert_eq!(changes["completed"], serde_json::json!(["Write parser"]));
+        assert_eq!(changes["removed"], serde_json::json!(["Update docs"]));
+
+        // The same tool use seen again still compares against the write before it
+        let entries = normalize_helper(&mut processor, &update, "");
+        assert_eq!(entries[0].content, expected);
+    }
+
+    #[test]
+    fn test_todo_write_malformed_payload_renders_as_generic_tool() {
+        let entries = normalize(&todo_write("t1", r#""not a list""#), "");
+
+        assert_eq!(entries.len(), 1);
+        let NormalizedEntryType::ToolUse { action_type, .. } = &entries[0].entry_type else {
+            panic!("expected a tool use entry");
+        };
+        assert!(matches!(action_type, ActionType::Tool { .. }));
+        assert_eq!(entries[0].content, "TodoWrite");
+        assert!(entries[0].metadata.as_ref().unwrap().get("todos").is_none());
+    }
+
     #[test]
     fn test_glob_tool_content_extraction() {
         // Test Glob with pattern and path
@@ -1939,6 +2413,86 @@ mod tests {
         assert_eq!(result, "`*.js`");
     }
 
+    fn search_of(entry: &NormalizedEntry) -> &serde_json::Value {
+        &entry.metadata.as_ref().unwrap()["search"]
+    }
+
+    #[test]
+    fn test_grep_search_with_zero_results() {
+        let mut processor = ClaudeLogProcessor::new();
+        let tool_use: ClaudeJson = serde_json::from_str(
+            r#"{"type":"assistant","message":{"role":"assistant","content":[
+                {"type":"tool_use","id":"g1","name":"Grep","input":{"pattern":"fn missing","path":"/tmp/work/src"}}
+            ]}}"#,
+        )
+        .unwrap();
+        let entries = normalize_helper(&mut processor, &tool_use, "/tmp/work");
+        let NormalizedEntryType::ToolUse { action_type, .. } = &entries[0].entry_type else {
+            panic!("expected a tool use entry");
+        };
+        assert!(matches!(action_type, ActionType::Search { query } if query == "fn missing"));
+        assert_eq!(
+            search_of(&entries[0]),
+            &serde_json::json!({ "path": "src" })
+        );
+
+        let result: ClaudeJson = serde_json::from_str(
+            r#"{"type":"user","message":{"role":"user","content":[
+                {"type":"tool_result","tool_use_id":"g1","content":"No files found","is_error":false}
+            ]}}"#,
+        )
+        .unwrap();
+        let entries = normalize_helper(&mut processor, &result, "/tmp/work");
+        assert!(matches!(
+            entries[0].entry_type,
+            NormalizedEntryType::ToolUse {
+                status: ToolStatus::Success,
+                ..
+            }
+        ));
+        assert_eq!(search_of(&entries[0])["result_count"], 0);
+        assert_eq!(entries[0].metadata.as_ref().unwrap()["tool_call_id"], "g1");
+    }
+
+    #[test]
+    fn test_amp_glob_search_with_many_results() {
+        let mut processor = ClaudeLogProcessor::new_with_strategy(HistoryStrategy::AmpResume);
+        let tool_use: ClaudeJson = serde_json::from_str(
+            r#"{"type":"assistant","message":{"role":"assistant","content":[
+                {"type":"tool_use","id":"a1","name":"glob","input":{"filePattern":"**/*.rs"}}
+            ]}}"#,
+        )
+        .unwrap();
+        normalize_helper(&mut processor, &tool_use, "/tmp/work");
+
+        let result: ClaudeJson = serde_json::from_str(
+            r#"{"type":"user","message":{"role":"user","content":[
+                {"type":"tool_result","tool_use_id":"a1","content":[
+                    {"type":"text","text":"[\"src/main.rs\",\"src/lib.rs\",\"src/a.rs\",\"src/b.rs\",\"tests/it.rs\"]"}
+                ]}
+            ]}}"#,
+        )
+        .unwrap();
+        let entries = normalize_helper(&mut processor, &result, "/tmp/work");
+        assert_eq!(search_of(&entries[0])["result_count"], 5);
+    }
+
+    #[test]
+    fn test_web_search_is_a_search() {
+        let json: ClaudeJson = serde_json::from_str(
+            r#"{"type":"assistant","message":{"role":"assistant","content":[
+                {"type":"tool_use","id":"w1","name":"WebSearch","input":{"query":"rust let chains"}}
+            ]}}"#,
+        )
+        .unwrap();
+        let entries = normalize(&json, "/tmp/work");
+        let NormalizedEntryType::ToolUse { action_type, .. } = &entries[0].entry_type else {
+            panic!("expected a tool use entry");
+        };
+        assert!(matches!(action_type, ActionType::Search { query } if query == "rust let chains"));
+        assert_eq!(search_of(&entries[0]), &serde_json::json!({ "web": true }));
+    }
+
     #[test]
     fn test_ls_tool_content_extraction() {
         // Test LS with path
@@ -2016,6 +2570,64 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_streaming_rejects_adversarial_lines() {
+        use std::sync::Arc;
+
+        use workspace_utils::msg_store::MsgStore;
+
+        use crate::logs::utils::json_guard::FALLBACK_MAX_CHARS;
+
+        let msg_store = Arc::new(MsgStore::new());
+        let deep = format!("{}{}\n", "[".repeat(100_000), "]".repeat(100_000));
+        msg_store.push_stdout(
+            r#"{"type":"system","subtype":"init","session_id":"test123","model":"claude-sonnet-4"}"#
+                .to_string() + "\n",
+        );
+        msg_store.push_stdout(deep);
+        msg_store.push_stdout(format!("{{\"n\":{}}}\n", "9".repeat(10_000)));
+        msg_store.push_stdout(r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Hello"}]}}"#.to_string() + "\n");
+        msg_store.push_finished();
+
+        ClaudeLogProcessor::process_logs(
+            msg_store.clone(),
+            std::path::Path::new("/tmp/test-worktree"),
+            EntryIndexProvider::test_new(),
+            HistoryStrategy::Default,
+        );
+        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+
+        let patches: Vec<json_patch::Patch> = msg_store
+            .get_history()
+            .into_iter()
+            .filter_map(|msg| match msg {
+                workspace_utils::log_msg::LogMsg::JsonPatch(patch) => Some(patch),
+                _ => None,
+            })
+            .collect();
+        let entries = patches_to_entries(&patches);
+
+        // Both bad lines surface as truncated system messages, and the
+        // legitimate lines around them normalize as usual
+        assert_eq!(entries.len(), 3);
+        assert!(
+            entries[0].content.len() < FALLBACK_MAX_CHARS + 64
+                && entries[0].content.starts_with("[[[")
+        );
+        assert!(matches!(
+            entries[1].entry_type,
+            NormalizedEntryType::SystemMessage
+        ));
+        assert!(matches!(
+            entries[2].entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+        assert_eq!(entries[2].content, "Hello");
+        assert!(msg_store.get_history().iter().any(
+            |msg| matches!(msg, workspace_utils::log_msg::LogMsg::SessionId(id) if id == "test123")
+        ));
+    }
+
     #[test]
     fn test_session_id_extraction() {
         let system_json = r#"{"type":"system","session_id":"test-session-123"}"#;
@@ -2178,6 +2790,44 @@ mod tests {
         assert_eq!(entries[0].content, "Task: `Add header to README`");
     }
 
+    #[test]
+    fn test_file_delete_and_move_actions() {
+        let tool_use = |name: &str, input: &str| {
+            let json = format!(
+                r#"{{"type":"assistant","message":{{"role":"assistant","content":[
+                    {{"type":"tool_use","id":"t1","name":"{name}","input":{input}}}
+                ]}}}}"#
+            );
+            let parsed: ClaudeJson = serde_json::from_str(&json).unwrap();
+            let entries = normalize(&parsed, "/tmp/work");
+            assert_eq!(entries.len(), 1);
+            match &entries[0].entry_type {
+                NormalizedEntryType::ToolUse { action_type, .. } => {
+                    (action_type.clone(), entries[0].content.clone())
+                }
+                other => panic!("Expected ToolUse, got {other:?}"),
+            }
+        };
+
+        let (action, content) = tool_use("delete_file", r#"{"path":"/tmp/work/src/old.rs"}"#);
+        assert!(matches!(action, ActionType::FileDelete { path } if path == "src/old.rs"));
+        assert_eq!(content, "`src/old.rs`");
+
+        let (action, _) = tool_use("Bash", r#"{"command":"rm -f /tmp/work/build.log"}"#);
+        assert!(matches!(action, ActionType::FileDelete { path } if path == "build.log"));
+
+        let (action, content) = tool_use("bash", r#"{"cmd":"git mv src/a.rs src/b.rs"}"#);
+        assert!(matches!(
+            action,
+            ActionType::FileMove { from, to } if from == "src/a.rs" && to == "src/b.rs"
+        ));
+        assert_eq!(content, "`src/a.rs` → `src/b.rs`");
+
+        // More than one path is left as a plain command
+        let (action, _) = tool_use("Bash", r#"{"command":"rm a.rs b.rs"}"#);
+        assert!(matches!(action, ActionType::CommandRun { .. }));
+    }
+
     #[test]
     fn test_task_description_or_prompt_backticks() {
         // When description present, use it
@@ -2285,6 +2935,39 @@ mod tests {
         assert_eq!(entries_no_key.len(), 0); // No warning when field is missing
     }
 
+    #[test]
+    fn test_mcp_server_failures_are_reported() {
+        let init = r#"{"type":"system","subtype":"init","session_id":"test123","mcp_servers":[{"name":"filesystem","status":"connected"},{"name":"playwright","status":"failed"}]}"#;
+        let parsed: ClaudeJson = serde_json::from_str(init).unwrap();
+        let entries = normalize(&parsed, "");
+
+        assert_eq!(entries.len(), 1);
+        assert!(matches!(
+            entries[0].entry_type,
+            NormalizedEntryType::ErrorMessage {
+                error_type: NormalizedEntryError::Other,
+            },
+        ));
+        assert!(entries[0].content.contains("- playwright: failed"));
+        assert!(entries[0].content.contains("- filesystem: connected"));
+        assert!(crate::logs::utils::mcp::mcp_degraded(&entries));
+
+        let healthy = r#"{"type":"system","subtype":"init","session_id":"test123","mcp_servers":[{"name":"filesystem","status":"connected"}]}"#;
+        let parsed: ClaudeJson = serde_json::from_str(healthy).unwrap();
+        let entries = normalize(&parsed, "");
+
+        assert_eq!(entries.len(), 1);
+        assert!(matches!(
+            entries[0].entry_type,
+            NormalizedEntryType::SystemMessage
+        ));
+        assert!(!crate::logs::utils::mcp::mcp_degraded(&entries));
+
+        let none = r#"{"type":"system","subtype":"init","session_id":"test123","mcp_servers":[]}"#;
+        let parsed: ClaudeJson = serde_json::from_str(none).unwrap();
+        assert!(normalize(&parsed, "").is_empty());
+    }
+
     #[test]
     fn test_mixed_content_with_thinking_ignores_tool_result() {
         let complex_assistant_json = r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"thinking","thinking":"I need to read the file first"},{"type":"text","text":"I'll help you with that"},{"type":"tool_result","tool_use_id":"tool_789","content":"Success","is_error":false}]}}"#;
@@ -2310,4 +2993,110 @@ mod tests {
 
         // ToolResult entry is ignored - no third entry
     }
+
+    #[test]
+    fn test_usage_sums_result_lines() {
+        let executor = ClaudeCode {
+            claude_code_router: Some(false),
+            plan: None,
+            approvals: None,
+            model: None,
+            append_prompt: AppendPrompt::default(),
+            dangerously_skip_permissions: None,
+            cmd: crate::command::CmdOverrides {
+                base_command_override: None,
+                additional_params: None,
+            },
+            approvals_service: None,
+        };
+        let first = r#"{"type":"result","subtype":"success","total_cost_usd":0.25,"usage":{"input_tokens":10,"output_tokens":200,"cache_read_input_tokens":3000,"cache_creation_input_tokens":400}}"#;
+        let (head, tail) = first.split_at(40);
+        let history = vec![
+            LogMsg::Stdout(r#"{"type":"system","subtype":"init","session_id":"s1"}"#.to_string() + "\n"),
+            // A line split across two chunks
+            LogMsg::Stdout(head.to_string()),
+            LogMsg::Stderr("warning\n".to_string()),
+            LogMsg::Stdout(format!("{tail}\n")),
+            LogMsg::Stdout(
+                r#"{"type":"result","subtype":"success","total_cost_usd":0.5,"usage":{"input_tokens":5,"output_tokens":100}}"#
+                    .to_string()
+                    + "\n",
+            ),
+        ];
+
+        assert_eq!(
+            executor.usage_from_logs(&history),
+            Some(UsageSummary {
+                input_tokens: 15,
+                output_tokens: 300,
+                cache_read_input_tokens: 3000,
+                cache_creation_input_tokens: 400,
+                cost_usd: Some(0.75),
+            })
+        );
+        assert_eq!(executor.usage_from_logs(&history[..1]), None);
+    }
+
+    #[test]
+    fn test_plan_from_logs_takes_last_exit_plan_mode() {
+        let mut executor = ClaudeCode {
+            claude_code_router: Some(false),
+            plan: Some(true),
+            approvals: None,
+            model: None,
+            append_prompt: AppendPrompt::default(),
+            dangerously_skip_permissions: None,
+            cmd: crate::command::CmdOverrides {
+                base_command_override: None,
+                additional_params: None,
+            },
+            approvals_service: None,
+        };
+        let plan_line = |plan: &str| {
+            serde_json::json!({
+                "type": "assistant",
+                "message": {
+                    "role": "assistant",
+                    "content": [{
+                        "type": "tool_use",
+                        "id": "toolu_1",
+                        "name": "ExitPlanMode",
+                        "input": { "plan": plan }
+                    }]
+                }
+            })
+            .to_string()
+                + "\n"
+        };
+        let history = vec![
+            LogMsg::Stdout(plan_line("1. Draft")),
+            LogMsg::Stdout(plan_line("1. Final")),
+        ];
+
+        assert_eq!(
+            executor.plan_from_logs(&history).as_deref(),
+            Some("1. Final")
+        );
+        executor.plan = None;
+        assert_eq!(executor.plan_from_logs(&history), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_session_not_found_reads_stderr() {
+        async fn probe(script: &str) -> bool {
+            let mut child = tokio::process::Command::new("sh")
+                .args(["-c", script])
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .group_spawn()
+                .unwrap();
+            let found = session_not_found(&mut child).await.unwrap();
+            let _ = child.kill().await;
+            found
+        }
+
+        assert!(probe("echo 'No conversation found with session ID: 1234' >&2; sleep 10").await);
+        assert!(!probe("echo 'Debugger attached' >&2; exit 0").await);
+    }
 }
@@ -19,9 +19,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use strum_macros::AsRefStr;
-use tokio::process::Command;
 use ts_rs::TS;
-use workspace_utils::msg_store::MsgStore;
+use workspace_utils::{log_msg::LogMsg, msg_store::MsgStore};
 
 use self::{
     client::{AppServerClient, LogWriter},
@@ -31,12 +30,16 @@ use self::{
 };
 use crate::{
     approvals::ExecutorApprovalService,
-    command::{CmdOverrides, CommandBuilder, CommandParts, apply_overrides},
+    command::{
+        CmdOverrides, CommandBuilder, CommandParts, CommandPreview, LaunchCommand, apply_overrides,
+    },
+    env::ExecutionEnv,
     executors::{
         AppendPrompt, AvailabilityInfo, ExecutorError, ExecutorExitResult, SpawnedChild,
         StandardCodingAgentExecutor,
         codex::{jsonrpc::ExitSignalSender, normalize_logs::Error},
     },
+    logs::usage::UsageSummary,
     stdout_dup::create_stdout_pipe_writer,
 };
 
@@ -142,9 +145,15 @@ impl StandardCodingAgentExecutor for Codex {
         self.approvals = Some(approvals);
     }
 
-    async fn spawn(&self, current_dir: &Path, prompt: &str) -> Result<SpawnedChild, ExecutorError> {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
         let command_parts = self.build_command_builder().build_initial()?;
-        self.spawn(current_dir, prompt, command_parts, None).await
+        self.spawn(current_dir, prompt, command_parts, None, env)
+            .await
     }
 
     async fn spawn_follow_up(
@@ -152,16 +161,28 @@ impl StandardCodingAgentExecutor for Codex {
         current_dir: &Path,
         prompt: &str,
         session_id: &str,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
         let command_parts = self.build_command_builder().build_follow_up(&[])?;
-        self.spawn(current_dir, prompt, command_parts, Some(session_id))
+        self.spawn(current_dir, prompt, command_parts, Some(session_id), env)
             .await
     }
 
+    async fn preview_command(&self, current_dir: &Path) -> Result<CommandPreview, ExecutorError> {
+        let command_parts = self.build_command_builder().build_initial()?;
+        Ok(Self::launch_command(command_parts)
+            .preview(current_dir)
+            .await)
+    }
+
     fn normalize_logs(&self, msg_store: Arc<MsgStore>, worktree_path: &Path) {
         normalize_logs(msg_store, worktree_path);
     }
 
+    fn usage_from_logs(&self, history: &[LogMsg]) -> Option<UsageSummary> {
+        normalize_logs::usage_from_logs(history)
+    }
+
     fn default_mcp_config_path(&self) -> Option<PathBuf> {
         dirs::home_dir().map(|home| home.join(".codex").join("config.toml"))
     }
@@ -210,6 +231,13 @@ impl Codex {
         apply_overrides(builder, &self.cmd)
     }
 
+    fn launch_command(command_parts: CommandParts) -> LaunchCommand {
+        LaunchCommand::new(command_parts)
+            .env("NODE_NO_WARNINGS", "1")
+            .env("NO_COLOR", "1")
+            .env("RUST_LOG", "error")
+    }
+
     fn build_new_conversation_params(&self, cwd: &Path) -> NewConversationParams {
         let sandbox = match self.sandbox.as_ref() {
             None | Some(SandboxMode::Auto) => Some(CodexSandboxMode::WorkspaceWrite), // match the Auto preset in codex
@@ -282,21 +310,18 @@ impl Codex {
         prompt: &str,
         command_parts: CommandParts,
         resume_session: Option<&str>,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
-        let (program_path, args) = command_parts.into_resolved().await?;
 
-        let mut process = Command::new(program_path);
+        let mut process = Self::launch_command(command_parts)
+            .into_command(current_dir)
+            .await?;
         process
-            .kill_on_drop(true)
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args)
-            .env("NODE_NO_WARNINGS", "1")
-            .env("NO_COLOR", "1")
-            .env("RUST_LOG", "error");
+            .stderr(std::process::Stdio::piped());
+        env.apply_to_command(&mut process);
 
         let mut child = process.group_spawn()?;
 
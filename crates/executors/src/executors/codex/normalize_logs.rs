@@ -29,6 +29,7 @@ use serde_json::Value;
 use workspace_utils::{
     approvals::ApprovalStatus,
     diff::{concatenate_diff_hunks, extract_unified_diff_hunks},
+    log_msg::LogMsg,
     msg_store::MsgStore,
     path::make_path_relative,
 };
@@ -41,8 +42,9 @@ use crate::{
         NormalizedEntryError, NormalizedEntryType, TodoItem, ToolResult, ToolResultValueType,
         ToolStatus,
         stderr_processor::normalize_stderr_logs,
+        usage::{UsageSummary, stdout_lines, token_count},
         utils::{
-            ConversationPatch, EntryIndexProvider,
+            ConversationPatch, EntryIndexProvider, json_guard,
             patch::{add_normalized_entry, replace_normalized_entry, upsert_normalized_entry},
         },
     },
@@ -361,6 +363,13 @@ fn format_todo_status(status: &StepStatus) -> String {
     .to_string()
 }
 
+fn extract_session_id_prefix(line: &str) -> Option<&str> {
+    line.strip_prefix(r#"{"method":"sessionConfigured","params":{"sessionId":""#)
+        .and_then(|suffix| SESSION_ID.captures(suffix))
+        .and_then(|caps| caps.get(1))
+        .map(|m| m.as_str())
+}
+
 pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
     let entry_index = EntryIndexProvider::start_from(&msg_store);
     normalize_stderr_logs(msg_store.clone(), entry_index.clone());
@@ -371,6 +380,20 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
         let mut stdout_lines = msg_store.stdout_lines_stream();
 
         while let Some(Ok(line)) = stdout_lines.next().await {
+            if json_guard::check_line(&line).is_err() {
+                // Oversized session history lines still carry the session id up front
+                if let Some(session_id) = extract_session_id_prefix(&line) {
+                    msg_store.push_session_id(session_id.to_string());
+                } else {
+                    add_normalized_entry(
+                        &msg_store,
+                        &entry_index,
+                        json_guard::fallback_entry(&line),
+                    );
+                }
+                continue;
+            }
+
             if let Ok(error) = serde_json::from_str::<Error>(&line) {
                 add_normalized_entry(&msg_store, &entry_index, error.to_normalized_entry());
                 continue;
@@ -401,13 +424,10 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                     );
                 };
                 continue;
-            } else if let Some(session_id) = line
-                .strip_prefix(r#"{"method":"sessionConfigured","params":{"sessionId":""#)
-                .and_then(|suffix| SESSION_ID.captures(suffix).and_then(|caps| caps.get(1)))
-            {
+            } else if let Some(session_id) = extract_session_id_prefix(&line) {
                 // Best-effort extraction of session ID from logs in case the JSON parsing fails.
                 // This could happen if the line is truncated due to size limits because it includes the full session history.
-                msg_store.push_session_id(session_id.as_str().to_string());
+                msg_store.push_session_id(session_id.to_string());
                 continue;
             }
 
@@ -1145,3 +1165,136 @@ impl ToNormalizedEntryOpt for Approval {
         }
     }
 }
+
+/// Codex repeats the session's running totals in every `token_count` event,
+/// so the last one covers the whole run.
+pub fn usage_from_logs(history: &[LogMsg]) -> Option<UsageSummary> {
+    stdout_lines(history).iter().rev().find_map(|line| {
+        let notification: JSONRPCNotification = serde_json::from_str(line).ok()?;
+        if !notification.method.starts_with("codex/event") {
+            return None;
+        }
+        let params: CodexNotificationParams = serde_json::from_value(notification.params?).ok()?;
+        let EventMsg::TokenCount(payload) = params.msg else {
+            return None;
+        };
+        let total = payload.info?.total_token_usage;
+        // Cached tokens are counted as part of the input
+        let cached = token_count(total.cached_input_tokens);
+        Some(UsageSummary {
+            input_tokens: (token_count(total.input_tokens) - cached).max(0),
+            output_tokens: token_count(total.output_tokens),
+            cache_read_input_tokens: cached,
+            cache_creation_input_tokens: 0,
+            cost_usd: None,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::utils::patch::extract_normalized_entry_from_patch;
+
+    const FIXTURE_SESSION_ID: &str = "0199a1b2-3c4d-7e5f-8a9b-0c1d2e3f4a5b";
+
+    /// Feed JSONL fixture lines through the normalizer and collect the
+    /// session ids and the latest version of each entry.
+    async fn normalize_fixture(lines: &[&str]) -> (Vec<String>, Vec<NormalizedEntry>) {
+        let msg_store = Arc::new(MsgStore::new());
+        for line in lines {
+            msg_store.push_stdout(format!("{line}\n"));
+        }
+        msg_store.push_finished();
+
+        normalize_logs(msg_store.clone(), Path::new("/tmp/codex-worktree"));
+        tokio::time::sleep(tokio::time::Duration::from_millis(150)).await;
+
+        let mut session_ids = Vec::new();
+        let mut entries: Vec<Option<NormalizedEntry>> = Vec::new();
+        for msg in msg_store.get_history() {
+            match msg {
+                LogMsg::SessionId(id) => session_ids.push(id),
+                LogMsg::JsonPatch(patch) => {
+                    if let Some((index, entry)) = extract_normalized_entry_from_patch(&patch) {
+                        if entries.len() <= index {
+                            entries.resize(index + 1, None);
+                        }
+                        entries[index] = Some(entry);
+                    }
+                }
+                _ => {}
+            }
+        }
+        (session_ids, entries.into_iter().flatten().collect())
+    }
+
+    #[tokio::test]
+    async fn normalizes_session_messages_reasoning_and_commands() {
+        let session_line = format!(
+            r#"{{"method":"sessionConfigured","params":{{"sessionId":"{FIXTURE_SESSION_ID}","model":"gpt-5-codex"}}}}"#
+        );
+        let (session_ids, entries) = normalize_fixture(&[
+            &session_line,
+            r#"{"method":"codex/event/agent_reasoning","params":{"id":"0","msg":{"type":"agent_reasoning","text":"Checking the failing test"}}}"#,
+            r#"{"method":"codex/event/agent_message","params":{"id":"0","msg":{"type":"agent_message","message":"The test expects a trailing newline."}}}"#,
+            r#"{"method":"codex/event/exec_approval_request","params":{"id":"0","msg":{"type":"exec_approval_request","call_id":"call-1","command":["cargo","test"],"cwd":"/tmp/codex-worktree","reason":null}}}"#,
+            r#"not json at all"#,
+        ])
+        .await;
+
+        assert_eq!(session_ids, vec![FIXTURE_SESSION_ID.to_string()]);
+
+        let thinking = entries
+            .iter()
+            .find(|e| matches!(e.entry_type, NormalizedEntryType::Thinking))
+            .expect("thinking entry");
+        assert_eq!(thinking.content, "Checking the failing test");
+
+        let assistant = entries
+            .iter()
+            .find(|e| matches!(e.entry_type, NormalizedEntryType::AssistantMessage))
+            .expect("assistant entry");
+        assert_eq!(assistant.content, "The test expects a trailing newline.");
+
+        let command = entries
+            .iter()
+            .find_map(|e| match &e.entry_type {
+                NormalizedEntryType::ToolUse {
+                    tool_name,
+                    action_type: ActionType::CommandRun { command, .. },
+                    ..
+                } => Some((tool_name.as_str(), command.as_str())),
+                _ => None,
+            })
+            .expect("command entry");
+        assert_eq!(command, ("bash", "cargo test"));
+    }
+
+    #[tokio::test]
+    async fn session_id_survives_truncated_session_line() {
+        let truncated = format!(
+            r#"{{"method":"sessionConfigured","params":{{"sessionId":"{FIXTURE_SESSION_ID}","initialMessages":[{{"#
+        );
+        let (session_ids, _) = normalize_fixture(&[&truncated]).await;
+        assert_eq!(session_ids, vec![FIXTURE_SESSION_ID.to_string()]);
+    }
+
+    #[test]
+    fn file_change_paths_are_relative_to_worktree() {
+        let changes = HashMap::from([(
+            PathBuf::from("/tmp/codex-worktree/src/lib.rs"),
+            CodexProtoFileChange::Add {
+                content: "pub fn answer() -> u32 { 42 }\n".to_string(),
+            },
+        )]);
+
+        let normalized = normalize_file_changes("/tmp/codex-worktree", &changes);
+        assert_eq!(normalized.len(), 1);
+        assert_eq!(normalized[0].0, "src/lib.rs");
+        assert!(matches!(
+            normalized[0].1.as_slice(),
+            [FileChange::Write { content }] if content.contains("answer")
+        ));
+    }
+}
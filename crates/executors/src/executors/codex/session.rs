@@ -10,6 +10,8 @@ use regex::Regex;
 use serde_json::{Map, Value};
 use thiserror::Error;
 
+use crate::logs::utils::json_guard;
+
 const FILENAME_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H-%M-%S";
 
 #[derive(Debug, Error)]
@@ -88,7 +90,7 @@ impl SessionHandler {
             )));
         }
 
-        let mut meta: Value = serde_json::from_str(trimmed_header).map_err(|e| {
+        let mut meta: Value = json_guard::parse_line(trimmed_header).map_err(|e| {
             SessionError::Format(format!(
                 "Failed to parse first line JSON in {}: {e}",
                 original.display()
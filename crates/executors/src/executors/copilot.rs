@@ -14,7 +14,6 @@ use serde::{Deserialize, Serialize};
 use tokio::{
     fs,
     io::AsyncWriteExt,
-    process::Command,
     time::{interval, timeout},
 };
 use ts_rs::TS;
@@ -22,7 +21,10 @@ use uuid::Uuid;
 use workspace_utils::{msg_store::MsgStore, path::get_vibe_kanban_temp_dir};
 
 use crate::{
-    command::{CmdOverrides, CommandBuilder, apply_overrides},
+    command::{
+        CmdOverrides, CommandBuilder, CommandParts, CommandPreview, LaunchCommand, apply_overrides,
+    },
+    env::ExecutionEnv,
     executors::{
         AppendPrompt, AvailabilityInfo, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
     },
@@ -93,28 +95,34 @@ impl Copilot {
 
         apply_overrides(builder, &self.cmd)
     }
+
+    fn launch_command(command_parts: CommandParts) -> LaunchCommand {
+        LaunchCommand::new(command_parts).env("NODE_NO_WARNINGS", "1")
+    }
 }
 
 #[async_trait]
 impl StandardCodingAgentExecutor for Copilot {
-    async fn spawn(&self, current_dir: &Path, prompt: &str) -> Result<SpawnedChild, ExecutorError> {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
         let log_dir = Self::create_temp_log_dir(current_dir).await?;
         let command_parts = self
             .build_command_builder(&log_dir.to_string_lossy())
             .build_initial()?;
-        let (program_path, args) = command_parts.into_resolved().await?;
-
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        let mut command = Command::new(program_path);
+        let mut command = Self::launch_command(command_parts)
+            .into_command(current_dir)
+            .await?;
         command
-            .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args)
-            .env("NODE_NO_WARNINGS", "1");
+            .stderr(Stdio::piped());
+        env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
@@ -135,25 +143,22 @@ impl StandardCodingAgentExecutor for Copilot {
         current_dir: &Path,
         prompt: &str,
         session_id: &str,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
         let log_dir = Self::create_temp_log_dir(current_dir).await?;
         let command_parts = self
             .build_command_builder(&log_dir.to_string_lossy())
             .build_follow_up(&["--resume".to_string(), session_id.to_string()])?;
-        let (program_path, args) = command_parts.into_resolved().await?;
-
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        let mut command = Command::new(program_path);
-
+        let mut command = Self::launch_command(command_parts)
+            .into_command(current_dir)
+            .await?;
         command
-            .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args)
-            .env("NODE_NO_WARNINGS", "1");
+            .stderr(Stdio::piped());
+        env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
@@ -172,6 +177,20 @@ impl StandardCodingAgentExecutor for Copilot {
     /// Parses both stderr and stdout logs for Copilot executor using PlainTextLogProcessor.
     ///
     /// Each entry is converted into an `AssistantMessage` or `ErrorMessage` and emitted as patches.
+    async fn preview_command(&self, current_dir: &Path) -> Result<CommandPreview, ExecutorError> {
+        // The real log directory is created per run
+        let log_dir = get_vibe_kanban_temp_dir()
+            .join("copilot_logs")
+            .join(current_dir.file_name().unwrap_or_default())
+            .join("{run-id}");
+        let command_parts = self
+            .build_command_builder(&log_dir.to_string_lossy())
+            .build_initial()?;
+        Ok(Self::launch_command(command_parts)
+            .preview(current_dir)
+            .await)
+    }
+
     fn normalize_logs(&self, msg_store: Arc<MsgStore>, _worktree_path: &Path) {
         let entry_index_counter = EntryIndexProvider::start_from(&msg_store);
         normalize_stderr_logs(msg_store.clone(), entry_index_counter.clone());
@@ -6,7 +6,7 @@ use command_group::AsyncCommandGroup;
 use futures::StreamExt;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tokio::{io::AsyncWriteExt, process::Command};
+use tokio::io::AsyncWriteExt;
 use ts_rs::TS;
 use workspace_utils::{
     diff::{concatenate_diff_hunks, create_unified_diff, extract_unified_diff_hunks},
@@ -16,7 +16,8 @@ use workspace_utils::{
 };
 
 use crate::{
-    command::{CmdOverrides, CommandBuilder, apply_overrides},
+    command::{CmdOverrides, CommandBuilder, CommandPreview, LaunchCommand, apply_overrides},
+    env::ExecutionEnv,
     executors::{
         AppendPrompt, AvailabilityInfo, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
     },
@@ -24,7 +25,7 @@ use crate::{
         ActionType, FileChange, NormalizedEntry, NormalizedEntryError, NormalizedEntryType,
         TodoItem, ToolStatus,
         plain_text_processor::PlainTextLogProcessor,
-        utils::{ConversationPatch, EntryIndexProvider},
+        utils::{ConversationPatch, EntryIndexProvider, json_guard},
     },
 };
 
@@ -68,23 +69,26 @@ impl CursorAgent {
 
 #[async_trait]
 impl StandardCodingAgentExecutor for CursorAgent {
-    async fn spawn(&self, current_dir: &Path, prompt: &str) -> Result<SpawnedChild, ExecutorError> {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
         mcp::ensure_mcp_server_trust(self, current_dir).await;
 
         let command_parts = self.build_command_builder().build_initial()?;
 
-        let (executable_path, args) = command_parts.into_resolved().await?;
-
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        let mut command = Command::new(executable_path);
+        let mut command = LaunchCommand::new(command_parts)
+            .into_command(current_dir)
+            .await?;
         command
-            .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args);
+            .stderr(Stdio::piped());
+        env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
@@ -101,24 +105,23 @@ impl StandardCodingAgentExecutor for CursorAgent {
         current_dir: &Path,
         prompt: &str,
         session_id: &str,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
         mcp::ensure_mcp_server_trust(self, current_dir).await;
 
         let command_parts = self
             .build_command_builder()
             .build_follow_up(&["--resume".to_string(), session_id.to_string()])?;
-        let (executable_path, args) = command_parts.into_resolved().await?;
-
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        let mut command = Command::new(executable_path);
+        let mut command = LaunchCommand::new(command_parts)
+            .into_command(current_dir)
+            .await?;
         command
-            .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args);
+            .stderr(Stdio::piped());
+        env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
@@ -130,6 +133,11 @@ impl StandardCodingAgentExecutor for CursorAgent {
         Ok(child.into())
     }
 
+    async fn preview_command(&self, current_dir: &Path) -> Result<CommandPreview, ExecutorError> {
+        let command_parts = self.build_command_builder().build_initial()?;
+        Ok(LaunchCommand::new(command_parts).preview(current_dir).await)
+    }
+
     fn normalize_logs(&self, msg_store: Arc<MsgStore>, worktree_path: &Path) {
         let entry_index_provider = EntryIndexProvider::start_from(&msg_store);
 
@@ -200,18 +208,12 @@ impl StandardCodingAgentExecutor for CursorAgent {
 
             while let Some(Ok(line)) = lines.next().await {
                 // Parse line as CursorJson
-                let cursor_json: CursorJson = match serde_json::from_str(&line) {
+                let cursor_json: CursorJson = match json_guard::parse_line(&line) {
                     Ok(cursor_json) => cursor_json,
                     Err(_) => {
                         // Handle non-JSON output as raw system message
                         if !line.is_empty() {
-                            let entry = NormalizedEntry {
-                                timestamp: None,
-                                entry_type: NormalizedEntryType::SystemMessage,
-                                content: line.to_string(),
-                                metadata: None,
-                            };
-
+                            let entry = json_guard::fallback_entry(&line);
                             let patch_id = entry_index_provider.next();
                             let patch = ConversationPatch::add_normalized_entry(patch_id, entry);
                             msg_store.push_patch(patch);
@@ -5,12 +5,13 @@ use command_group::AsyncCommandGroup;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use strum_macros::AsRefStr;
-use tokio::{io::AsyncWriteExt, process::Command};
+use tokio::io::AsyncWriteExt;
 use ts_rs::TS;
 use workspace_utils::msg_store::MsgStore;
 
 use crate::{
-    command::CommandParts,
+    command::{CommandParts, CommandPreview, LaunchCommand},
+    env::ExecutionEnv,
     executors::{AppendPrompt, ExecutorError, SpawnedChild, StandardCodingAgentExecutor},
     logs::utils::EntryIndexProvider,
 };
@@ -108,17 +109,16 @@ async fn spawn(
     command_parts: CommandParts,
     prompt: &String,
     current_dir: &Path,
+    env: &ExecutionEnv,
 ) -> Result<SpawnedChild, ExecutorError> {
-    let (program_path, args) = command_parts.into_resolved().await?;
-
-    let mut command = Command::new(program_path);
+    let mut command = LaunchCommand::new(command_parts)
+        .into_command(current_dir)
+        .await?;
     command
-        .kill_on_drop(true)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .current_dir(current_dir)
-        .args(args);
+        .stderr(Stdio::piped());
+    env.apply_to_command(&mut command);
 
     let mut child = command.group_spawn()?;
 
@@ -132,11 +132,16 @@ async fn spawn(
 
 #[async_trait]
 impl StandardCodingAgentExecutor for Droid {
-    async fn spawn(&self, current_dir: &Path, prompt: &str) -> Result<SpawnedChild, ExecutorError> {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
         let droid_command = self.build_command_builder().build_initial()?;
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        spawn(droid_command, &combined_prompt, current_dir).await
+        spawn(droid_command, &combined_prompt, current_dir, env).await
     }
 
     async fn spawn_follow_up(
@@ -144,6 +149,7 @@ impl StandardCodingAgentExecutor for Droid {
         current_dir: &Path,
         prompt: &str,
         session_id: &str,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
         let forked_session_id = fork_session(session_id).map_err(|e| {
             ExecutorError::FollowUpNotSupported(format!(
@@ -155,7 +161,12 @@ impl StandardCodingAgentExecutor for Droid {
             .build_follow_up(&["--session-id".to_string(), forked_session_id.clone()])?;
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        spawn(continue_cmd, &combined_prompt, current_dir).await
+        spawn(continue_cmd, &combined_prompt, current_dir, env).await
+    }
+
+    async fn preview_command(&self, current_dir: &Path) -> Result<CommandPreview, ExecutorError> {
+        let command_parts = self.build_command_builder().build_initial()?;
+        Ok(LaunchCommand::new(command_parts).preview(current_dir).await)
     }
 
     fn normalize_logs(&self, msg_store: Arc<MsgStore>, current_dir: &Path) {
@@ -18,7 +18,7 @@ use crate::logs::{
     NormalizedEntryError, NormalizedEntryType, TodoItem, ToolResult, ToolStatus,
     plain_text_processor::PlainTextLogProcessor,
     utils::{
-        EntryIndexProvider,
+        EntryIndexProvider, json_guard,
         patch::{add_normalized_entry, replace_normalized_entry},
     },
 };
@@ -44,11 +44,12 @@ pub fn normalize_logs(
 
         while let Some(line) = lines_stream.next().await {
             let trimmed = line.trim();
-            let droid_json = match serde_json::from_str::<DroidJson>(trimmed) {
+            let droid_json = match json_guard::parse_line::<DroidJson>(trimmed) {
                 Ok(droid_json) => droid_json,
-                Err(_) => {
-                    if let Ok(DroidErrorLog { error, .. }) =
-                        serde_json::from_str::<DroidErrorLog>(trimmed)
+                Err(err) => {
+                    if !err.is_rejected()
+                        && let Ok(DroidErrorLog { error, .. }) =
+                            serde_json::from_str::<DroidErrorLog>(trimmed)
                     {
                         let entry = NormalizedEntry {
                             timestamp: None,
@@ -63,13 +64,8 @@ pub fn normalize_logs(
                     }
                     // Handle non-JSON output as raw system message
                     if !trimmed.is_empty() {
-                        let entry = NormalizedEntry {
-                            timestamp: None,
-                            entry_type: NormalizedEntryType::SystemMessage,
-                            content: strip_ansi_escapes::strip_str(trimmed).to_string(),
-                            metadata: None,
-                        };
-
+                        let entry =
+                            json_guard::fallback_entry(&strip_ansi_escapes::strip_str(trimmed));
                         add_normalized_entry(&msg_store, &entry_index_provider, entry);
                     }
                     continue;
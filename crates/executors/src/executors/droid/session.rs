@@ -6,6 +6,8 @@ use std::{
 use serde_json::Value;
 use uuid::Uuid;
 
+use crate::logs::utils::json_guard;
+
 pub fn fork_session(session_id: &str) -> io::Result<String> {
     let root = sessions_root()?;
     let source = find_session_file(&root, &format!("{session_id}.jsonl"))?;
@@ -55,7 +57,7 @@ fn sessions_root() -> io::Result<PathBuf> {
 }
 
 fn replace_session_id(line: &str, new_session_id: &str) -> String {
-    if let Ok(mut meta) = serde_json::from_str::<Value>(line)
+    if let Ok(mut meta) = json_guard::parse_line::<Value>(line)
         && meta
             .get("type")
             .and_then(|value| value.as_str())
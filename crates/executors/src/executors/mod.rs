@@ -10,16 +10,19 @@ use sqlx::Type;
 use strum_macros::{Display, EnumDiscriminants, EnumString, VariantNames};
 use thiserror::Error;
 use ts_rs::TS;
-use workspace_utils::msg_store::MsgStore;
+use workspace_utils::{log_msg::LogMsg, msg_store::MsgStore};
 
 use crate::{
     actions::ExecutorAction,
     approvals::ExecutorApprovalService,
-    command::CommandBuildError,
+    command::{CommandBuildError, CommandPreview},
+    env::ExecutionEnv,
     executors::{
         amp::Amp, claude::ClaudeCode, codex::Codex, copilot::Copilot, cursor::CursorAgent,
         droid::Droid, gemini::Gemini, opencode::Opencode, qwen::QwenCode,
     },
+    health::{self, ExecutorHealth},
+    logs::usage::UsageSummary,
     mcp_config::McpConfig,
 };
 
@@ -43,6 +46,57 @@ pub enum BaseAgentCapability {
     SetupHelper,
 }
 
+/// Format of the file an executor reads its MCP servers from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum ConfigFileFormat {
+    Json,
+    Toml,
+}
+
+/// What an executor supports. Every executor declares these once, in
+/// [`BaseCodingAgent::capabilities`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct ExecutorCapabilities {
+    pub supports_mcp: bool,
+    pub supports_followup: bool,
+    pub supports_plan_mode: bool,
+    /// Follow-ups continue a fork of the session rather than the session
+    /// itself
+    pub supports_session_fork: bool,
+    /// Needs a setup script (login, installation) before its first run
+    pub requires_setup_helper: bool,
+    /// Reports a session id that follow-ups can resume
+    pub produces_session_id: bool,
+    /// `None` when the executor has no MCP configuration
+    pub config_file_format: Option<ConfigFileFormat>,
+}
+
+impl ExecutorCapabilities {
+    const DEFAULT: Self = Self {
+        supports_mcp: true,
+        supports_followup: true,
+        supports_plan_mode: false,
+        supports_session_fork: false,
+        requires_setup_helper: false,
+        produces_session_id: true,
+        config_file_format: Some(ConfigFileFormat::Json),
+    };
+
+    /// The legacy capability list sent with the user system info.
+    pub fn as_base_capabilities(&self) -> Vec<BaseAgentCapability> {
+        let mut capabilities = Vec::new();
+        if self.supports_session_fork {
+            capabilities.push(BaseAgentCapability::SessionFork);
+        }
+        if self.requires_setup_helper {
+            capabilities.push(BaseAgentCapability::SetupHelper);
+        }
+        capabilities
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ExecutorError {
     #[error("Follow-up is not supported: {0}")]
@@ -69,6 +123,8 @@ pub enum ExecutorError {
     SetupHelperNotSupported,
     #[error("Auth required: {0}")]
     AuthRequired(String),
+    #[error("Session {0} could not be resumed")]
+    InvalidSessionId(String),
 }
 
 #[enum_dispatch]
@@ -101,8 +157,56 @@ pub enum CodingAgent {
     Droid,
 }
 
+impl BaseCodingAgent {
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::ClaudeCode => "Claude Code",
+            Self::Amp => "Amp",
+            Self::Gemini => "Gemini",
+            Self::Codex => "Codex",
+            Self::Opencode => "OpenCode",
+            Self::CursorAgent => "Cursor Agent",
+            Self::QwenCode => "Qwen Code",
+            Self::Copilot => "GitHub Copilot",
+            Self::Droid => "Droid",
+        }
+    }
+
+    pub fn capabilities(self) -> ExecutorCapabilities {
+        let default = ExecutorCapabilities::DEFAULT;
+        match self {
+            Self::ClaudeCode => ExecutorCapabilities {
+                supports_plan_mode: true,
+                supports_session_fork: true,
+                ..default
+            },
+            Self::Amp | Self::Gemini | Self::QwenCode | Self::Droid => ExecutorCapabilities {
+                supports_session_fork: true,
+                ..default
+            },
+            Self::Codex => ExecutorCapabilities {
+                supports_session_fork: true,
+                requires_setup_helper: true,
+                config_file_format: Some(ConfigFileFormat::Toml),
+                ..default
+            },
+            Self::CursorAgent => ExecutorCapabilities {
+                requires_setup_helper: true,
+                ..default
+            },
+            Self::Opencode | Self::Copilot => default,
+        }
+    }
+}
+
 impl CodingAgent {
+    pub fn executor_capabilities(&self) -> ExecutorCapabilities {
+        BaseCodingAgent::from(self).capabilities()
+    }
+
     pub fn get_mcp_config(&self) -> McpConfig {
+        let is_toml_config =
+            self.executor_capabilities().config_file_format == Some(ConfigFileFormat::Toml);
         match self {
             Self::Codex(_) => McpConfig::new(
                 vec!["mcp_servers".to_string()],
@@ -110,7 +214,7 @@ impl CodingAgent {
                     "mcp_servers": {}
                 }),
                 self.preconfigured_mcp(),
-                true,
+                is_toml_config,
             ),
             Self::Amp(_) => McpConfig::new(
                 vec!["amp.mcpServers".to_string()],
@@ -118,7 +222,7 @@ impl CodingAgent {
                     "amp.mcpServers": {}
                 }),
                 self.preconfigured_mcp(),
-                false,
+                is_toml_config,
             ),
             Self::Opencode(_) => McpConfig::new(
                 vec!["mcp".to_string()],
@@ -127,7 +231,7 @@ impl CodingAgent {
                     "$schema": "https://opencode.ai/config.json"
                 }),
                 self.preconfigured_mcp(),
-                false,
+                is_toml_config,
             ),
             Self::Droid(_) => McpConfig::new(
                 vec!["mcpServers".to_string()],
@@ -135,7 +239,7 @@ impl CodingAgent {
                     "mcpServers": {}
                 }),
                 self.preconfigured_mcp(),
-                false,
+                is_toml_config,
             ),
             _ => McpConfig::new(
                 vec!["mcpServers".to_string()],
@@ -143,29 +247,35 @@ impl CodingAgent {
                     "mcpServers": {}
                 }),
                 self.preconfigured_mcp(),
-                false,
+                is_toml_config,
             ),
         }
     }
 
     pub fn supports_mcp(&self) -> bool {
-        self.default_mcp_config_path().is_some()
+        self.executor_capabilities().supports_mcp
     }
 
     pub fn capabilities(&self) -> Vec<BaseAgentCapability> {
-        match self {
-            Self::ClaudeCode(_)
-            | Self::Amp(_)
-            | Self::Gemini(_)
-            | Self::QwenCode(_)
-            | Self::Droid(_) => vec![BaseAgentCapability::SessionFork],
-            Self::Codex(_) => vec![
-                BaseAgentCapability::SessionFork,
-                BaseAgentCapability::SetupHelper,
-            ],
-            Self::CursorAgent(_) => vec![BaseAgentCapability::SetupHelper],
-            Self::Opencode(_) | Self::Copilot(_) => vec![],
-        }
+        self.executor_capabilities().as_base_capabilities()
+    }
+
+    /// Append `args` after the executor's own additional parameters.
+    pub fn append_args(&mut self, args: &[String]) {
+        let cmd = match self {
+            Self::ClaudeCode(agent) => &mut agent.cmd,
+            Self::Amp(agent) => &mut agent.cmd,
+            Self::Gemini(agent) => &mut agent.cmd,
+            Self::Codex(agent) => &mut agent.cmd,
+            Self::Opencode(agent) => &mut agent.cmd,
+            Self::CursorAgent(agent) => &mut agent.cmd,
+            Self::QwenCode(agent) => &mut agent.cmd,
+            Self::Copilot(agent) => &mut agent.cmd,
+            Self::Droid(agent) => &mut agent.cmd,
+        };
+        cmd.additional_params
+            .get_or_insert_with(Vec::new)
+            .extend(args.iter().cloned());
     }
 }
 
@@ -192,15 +302,53 @@ impl AvailabilityInfo {
 pub trait StandardCodingAgentExecutor {
     fn use_approvals(&mut self, _approvals: Arc<dyn ExecutorApprovalService>) {}
 
-    async fn spawn(&self, current_dir: &Path, prompt: &str) -> Result<SpawnedChild, ExecutorError>;
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError>;
     async fn spawn_follow_up(
         &self,
         current_dir: &Path,
         prompt: &str,
         session_id: &str,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError>;
     fn normalize_logs(&self, _raw_logs_event_store: Arc<MsgStore>, _worktree_path: &Path);
 
+    /// Tokens used by a run, parsed from its log history. `None` when the
+    /// agent does not report usage.
+    fn usage_from_logs(&self, _history: &[LogMsg]) -> Option<UsageSummary> {
+        None
+    }
+
+    /// The last plan a plan-mode run presented, parsed from its log history.
+    /// `None` when the executor was not planning or presented no plan.
+    fn plan_from_logs(&self, _history: &[LogMsg]) -> Option<String> {
+        None
+    }
+
+    /// The command `spawn` would run in `current_dir`, without running it.
+    async fn preview_command(&self, current_dir: &Path) -> Result<CommandPreview, ExecutorError>;
+
+    /// Whether the program `spawn` runs is installed, and which version.
+    /// May run `<program> --version`, so prefer
+    /// [`crate::health::cached_availability`] on request paths.
+    async fn check_availability(&self) -> ExecutorHealth {
+        match self.preview_command(&std::env::temp_dir()).await {
+            Ok(preview) => {
+                health::check_program(
+                    &preview.program,
+                    preview.resolved_program.as_deref(),
+                    &preview.args,
+                )
+                .await
+            }
+            Err(e) => ExecutorHealth::unavailable(String::new(), e.to_string()),
+        }
+    }
+
     // MCP configuration methods
     fn default_mcp_config_path(&self) -> Option<std::path::PathBuf>;
 
@@ -229,6 +377,8 @@ pub enum ExecutorExitResult {
     Success,
     /// Process should be marked as failed (non-zero exit)
     Failure,
+    /// Process ran past its time limit and should be marked as failed
+    TimedOut,
 }
 
 /// Optional exit notification from an executor.
@@ -305,4 +455,53 @@ mod tests {
         assert!(result.is_ok(), "CURSOR should deserialize via serde");
         assert_eq!(result.unwrap(), BaseCodingAgent::CursorAgent);
     }
+
+    #[test]
+    fn legacy_capability_lists_follow_the_capabilities() {
+        assert_eq!(
+            BaseCodingAgent::Codex.capabilities().as_base_capabilities(),
+            vec![
+                BaseAgentCapability::SessionFork,
+                BaseAgentCapability::SetupHelper
+            ]
+        );
+        assert_eq!(
+            BaseCodingAgent::CursorAgent
+                .capabilities()
+                .as_base_capabilities(),
+            vec![BaseAgentCapability::SetupHelper]
+        );
+        assert!(
+            BaseCodingAgent::Opencode
+                .capabilities()
+                .as_base_capabilities()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn only_codex_keeps_its_mcp_servers_in_toml() {
+        for executor in [
+            BaseCodingAgent::ClaudeCode,
+            BaseCodingAgent::Amp,
+            BaseCodingAgent::Gemini,
+            BaseCodingAgent::Codex,
+            BaseCodingAgent::Opencode,
+            BaseCodingAgent::CursorAgent,
+            BaseCodingAgent::QwenCode,
+            BaseCodingAgent::Copilot,
+            BaseCodingAgent::Droid,
+        ] {
+            let expected = if executor == BaseCodingAgent::Codex {
+                ConfigFileFormat::Toml
+            } else {
+                ConfigFileFormat::Json
+            };
+            assert_eq!(
+                executor.capabilities().config_file_format,
+                Some(expected),
+                "{executor}"
+            );
+        }
+    }
 }
@@ -14,19 +14,23 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tokio::{io::AsyncWriteExt, process::Command};
+use tokio::io::AsyncWriteExt;
 use ts_rs::TS;
 use workspace_utils::{msg_store::MsgStore, path::make_path_relative};
 
 use crate::{
-    command::{CmdOverrides, CommandBuilder, apply_overrides},
+    command::{
+        CmdOverrides, CommandBuilder, CommandParts, CommandPreview, LaunchCommand, apply_overrides,
+    },
+    env::ExecutionEnv,
     executors::{
         AppendPrompt, AvailabilityInfo, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
         opencode::share_bridge::Bridge as ShareBridge,
     },
     logs::{
         ActionType, FileChange, NormalizedEntry, NormalizedEntryError, NormalizedEntryType,
-        TodoItem, ToolStatus, utils::EntryIndexProvider,
+        TodoItem, ToolStatus,
+        utils::{EntryIndexProvider, json_guard, shell::file_action_from_command},
     },
     stdout_dup,
 };
@@ -124,29 +128,36 @@ impl Opencode {
 
         apply_overrides(builder, &self.cmd)
     }
+
+    fn launch_command(command_parts: CommandParts, bridge_url: impl Into<String>) -> LaunchCommand {
+        LaunchCommand::new(command_parts)
+            .env("NODE_NO_WARNINGS", "1")
+            .env("OPENCODE_AUTO_SHARE", "1")
+            .env("OPENCODE_API", bridge_url)
+    }
 }
 
 #[async_trait]
 impl StandardCodingAgentExecutor for Opencode {
-    async fn spawn(&self, current_dir: &Path, prompt: &str) -> Result<SpawnedChild, ExecutorError> {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
         // Start a dedicated local share bridge bound to this opencode process
         let bridge = ShareBridge::start().await.map_err(ExecutorError::Io)?;
         let command_parts = self.build_command_builder().build_initial()?;
-        let (program_path, args) = command_parts.into_resolved().await?;
-
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        let mut command = Command::new(program_path);
+        let mut command = Self::launch_command(command_parts, bridge.base_url.clone())
+            .into_command(current_dir)
+            .await?;
         command
-            .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped()) // Keep stdout but we won't use it
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args)
-            .env("NODE_NO_WARNINGS", "1")
-            .env("OPENCODE_AUTO_SHARE", "1")
-            .env("OPENCODE_API", bridge.base_url.clone());
+            .stderr(Stdio::piped());
+        env.apply_to_command(&mut command);
 
         let mut child = match command.group_spawn() {
             Ok(c) => c,
@@ -192,27 +203,28 @@ impl StandardCodingAgentExecutor for Opencode {
         current_dir: &Path,
         prompt: &str,
         session_id: &str,
+        env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
+        // Opencode treats an unknown --session as a new session, so reject
+        // ids it could never have issued instead of silently starting over
+        if !is_valid_session_id(session_id) {
+            return Err(ExecutorError::InvalidSessionId(session_id.to_string()));
+        }
         // Start a dedicated local share bridge bound to this opencode process
         let bridge = ShareBridge::start().await.map_err(ExecutorError::Io)?;
         let command_parts = self
             .build_command_builder()
             .build_follow_up(&["--session".to_string(), session_id.to_string()])?;
-        let (program_path, args) = command_parts.into_resolved().await?;
-
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        let mut command = Command::new(program_path);
+        let mut command = Self::launch_command(command_parts, bridge.base_url.clone())
+            .into_command(current_dir)
+            .await?;
         command
-            .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped()) // Keep stdout but we won't use it
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args)
-            .env("NODE_NO_WARNINGS", "1")
-            .env("OPENCODE_AUTO_SHARE", "1")
-            .env("OPENCODE_API", bridge.base_url.clone());
+            .stderr(Stdio::piped());
+        env.apply_to_command(&mut command);
 
         let mut child = match command.group_spawn() {
             Ok(c) => c,
@@ -257,6 +269,14 @@ impl StandardCodingAgentExecutor for Opencode {
     /// 2. Error log recognition thread: read by line, identify error log lines, store them as error messages.
     /// 3. Main normalizer thread: read stderr by line, filter out log lines, send lines (with '\n' appended) to plain text normalizer,
     ///    then define predicate for split and create appropriate normalized entry (either assistant or tool call).
+    async fn preview_command(&self, current_dir: &Path) -> Result<CommandPreview, ExecutorError> {
+        let command_parts = self.build_command_builder().build_initial()?;
+        // The approval bridge is only started when the agent is spawned
+        Ok(Self::launch_command(command_parts, "{approval-bridge-url}")
+            .preview(current_dir)
+            .await)
+    }
+
     fn normalize_logs(&self, msg_store: Arc<MsgStore>, worktree_path: &Path) {
         let entry_index_counter = EntryIndexProvider::start_from(&msg_store);
 
@@ -328,6 +348,13 @@ impl StandardCodingAgentExecutor for Opencode {
         }
     }
 }
+/// Opencode session ids look like `ses_786439b6dffe4bLqNBS4fGd7mJ`.
+fn is_valid_session_id(session_id: &str) -> bool {
+    session_id
+        .strip_prefix("ses_")
+        .is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
 impl Opencode {
     const SHARE_PREFIX: &'static str = "[oc-share] ";
     async fn process_opencode_log_lines(
@@ -455,11 +482,19 @@ impl Opencode {
         };
 
         while let Some(line) = lines.next().await {
-            let Ok(env) = serde_json::from_str::<ShareSyncEnvelope>(&line) else {
-                continue;
+            let env = match json_guard::parse_line::<ShareSyncEnvelope>(&line) {
+                Ok(env) => env,
+                Err(err) => {
+                    if err.is_rejected() {
+                        let idx = entry_index_counter.next();
+                        let entry = json_guard::fallback_entry(&line);
+                        msg_store.push_patch(ConversationPatch::add_normalized_entry(idx, entry));
+                    }
+                    continue;
+                }
             };
             // Record session id once from stream
-            if !session_id_set {
+            if !session_id_set && is_valid_session_id(&env.session_id) {
                 msg_store.push_session_id(env.session_id.clone());
                 session_id_set = true;
             }
@@ -908,10 +943,12 @@ impl Opencode {
             }
             ActionTool::Bash { input } => {
                 let command = input.command.unwrap_or_default();
-                Some(ActionType::CommandRun {
+                let file_action =
+                    file_action_from_command(&command, &worktree_path.to_string_lossy());
+                Some(file_action.unwrap_or(ActionType::CommandRun {
                     command,
                     result: None,
-                })
+                }))
             }
             ActionTool::Grep { input } => {
                 let query = input.pattern.unwrap_or_default();
@@ -980,3 +1017,120 @@ impl LogUtils {
         line.starts_with("!  ")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use workspace_utils::log_msg::LogMsg;
+
+    use super::*;
+
+    const FIXTURE_SESSION_ID: &str = "ses_786439b6dffe4bLqNBS4fGd7mJ";
+
+    fn share_line(session_id: &str, key: &str, content: serde_json::Value) -> String {
+        let envelope = serde_json::json!({
+            "sessionID": session_id,
+            "secret": "",
+            "key": key,
+            "content": content,
+        });
+        format!("{}{}\n", Opencode::SHARE_PREFIX, envelope)
+    }
+
+    async fn session_ids_from(lines: &[String]) -> Vec<String> {
+        let msg_store = Arc::new(MsgStore::new());
+        for line in lines {
+            msg_store.push_stdout(line.clone());
+        }
+        msg_store.push_finished();
+
+        opencode().normalize_logs(msg_store.clone(), Path::new("/tmp/oc"));
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        msg_store
+            .get_history()
+            .into_iter()
+            .filter_map(|msg| match msg {
+                LogMsg::SessionId(id) => Some(id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn opencode() -> Opencode {
+        Opencode {
+            append_prompt: AppendPrompt::default(),
+            model: None,
+            agent: None,
+            cmd: CmdOverrides::default(),
+        }
+    }
+
+    #[test]
+    fn session_ids_must_look_like_opencode_ids() {
+        assert!(is_valid_session_id(FIXTURE_SESSION_ID));
+        for id in [
+            "",
+            "ses_",
+            "786439b6dffe4b",
+            "ses_../../etc",
+            "ses_abc --continue",
+        ] {
+            assert!(!is_valid_session_id(id), "{id:?} should be rejected");
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_the_session_id_from_share_events() {
+        let ids = session_ids_from(&[
+            share_line(
+                FIXTURE_SESSION_ID,
+                "session/message/ses_786439b6dffe4bLqNBS4fGd7mJ/msg_01",
+                serde_json::json!({ "id": "msg_01", "role": "assistant" }),
+            ),
+            share_line(
+                FIXTURE_SESSION_ID,
+                "session/part/ses_786439b6dffe4bLqNBS4fGd7mJ/msg_01/prt_01",
+                serde_json::json!({
+                    "type": "text",
+                    "id": "prt_01",
+                    "messageID": "msg_01",
+                    "sessionID": FIXTURE_SESSION_ID,
+                    "text": "Looking at the failing test",
+                }),
+            ),
+        ])
+        .await;
+
+        assert_eq!(ids, [FIXTURE_SESSION_ID]);
+    }
+
+    #[tokio::test]
+    async fn skips_malformed_session_ids_in_share_events() {
+        let ids = session_ids_from(&[
+            share_line("not a session", "session/info/x", serde_json::json!({})),
+            share_line(FIXTURE_SESSION_ID, "session/info/y", serde_json::json!({})),
+        ])
+        .await;
+
+        assert_eq!(ids, [FIXTURE_SESSION_ID]);
+    }
+
+    #[tokio::test]
+    async fn follow_up_with_a_malformed_session_id_is_rejected() {
+        let result = opencode()
+            .spawn_follow_up(
+                Path::new("/tmp/oc"),
+                "continue",
+                "../not-a-session",
+                &ExecutionEnv::default(),
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ExecutorError::InvalidSessionId(id)) if id == "../not-a-session"
+        ));
+    }
+}
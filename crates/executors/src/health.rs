@@ -0,0 +1,178 @@
+//! Whether an executor's program can actually be launched on this machine,
+//! checked before an attempt is created rather than discovered from a spawn
+//! error afterwards.
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use lazy_static::lazy_static;
+use serde::Serialize;
+use tokio::process::Command;
+use ts_rs::TS;
+
+use crate::{
+    executors::{BaseCodingAgent, CodingAgent, StandardCodingAgentExecutor},
+    profile::ExecutorProfileId,
+};
+
+/// How long a health result is reused before the program is checked again
+pub const HEALTH_CACHE_TTL: Duration = Duration::from_secs(60);
+
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Launchers that download the real program on first use. Their own
+/// `--version` says nothing about the agent, so the pinned package is
+/// reported instead.
+const PACKAGE_RUNNERS: &[&str] = &["npx", "bunx", "pnpx"];
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ExecutorHealth {
+    pub available: bool,
+    /// Program the executor launches, e.g. `npx` or `droid`
+    pub program: String,
+    /// Where `program` resolves on the server's PATH
+    pub resolved_path: Option<String>,
+    /// Output of `--version`, or the pinned package for package runners
+    pub version: Option<String>,
+    /// Why the executor cannot be started
+    pub error: Option<String>,
+}
+
+impl ExecutorHealth {
+    pub fn unavailable(program: impl Into<String>, error: impl Into<String>) -> Self {
+        Self {
+            available: false,
+            program: program.into(),
+            resolved_path: None,
+            version: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Check a program `spawn` would run. `resolved` is its location on PATH.
+pub async fn check_program(
+    program: &str,
+    resolved: Option<&str>,
+    args: &[String],
+) -> ExecutorHealth {
+    let Some(resolved) = resolved else {
+        return ExecutorHealth::unavailable(program, format!("`{program}` not found in PATH"));
+    };
+    let mut health = ExecutorHealth {
+        available: true,
+        program: program.to_string(),
+        resolved_path: Some(resolved.to_string()),
+        version: None,
+        error: None,
+    };
+
+    let stem = Path::new(program)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(program);
+    if PACKAGE_RUNNERS.contains(&stem) {
+        health.version = args.iter().find(|arg| !arg.starts_with('-')).cloned();
+        return health;
+    }
+
+    let mut command = Command::new(resolved);
+    command.arg("--version").kill_on_drop(true);
+    match tokio::time::timeout(VERSION_TIMEOUT, command.output()).await {
+        Ok(Ok(output)) if output.status.success() => {
+            let text = String::from_utf8_lossy(&output.stdout);
+            health.version = text
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(str::to_string);
+        }
+        // Installed but does not understand `--version`, or is slow to answer
+        Ok(Ok(_)) | Err(_) => {}
+        Ok(Err(e)) => {
+            health.available = false;
+            health.error = Some(format!("`{program}` could not be started: {e}"));
+        }
+    }
+    health
+}
+
+struct CachedHealth {
+    agent: CodingAgent,
+    checked_at: Instant,
+    health: ExecutorHealth,
+}
+
+lazy_static! {
+    static ref HEALTH_CACHE: Mutex<HashMap<String, CachedHealth>> = Mutex::new(HashMap::new());
+}
+
+/// `agent.check_availability()`, reusing a result from the last
+/// [`HEALTH_CACHE_TTL`] as long as the profile's configuration is unchanged.
+pub async fn cached_availability(
+    profile_id: &ExecutorProfileId,
+    agent: &CodingAgent,
+) -> ExecutorHealth {
+    let key = profile_id.cache_key();
+    let cached = HEALTH_CACHE
+        .lock()
+        .unwrap()
+        .get(&key)
+        .filter(|cached| cached.agent == *agent && cached.checked_at.elapsed() < HEALTH_CACHE_TTL)
+        .map(|cached| cached.health.clone());
+    if let Some(health) = cached {
+        return health;
+    }
+
+    let health = agent.check_availability().await;
+    HEALTH_CACHE.lock().unwrap().insert(
+        key,
+        CachedHealth {
+            agent: agent.clone(),
+            checked_at: Instant::now(),
+            health: health.clone(),
+        },
+    );
+    health
+}
+
+/// Drop cached results for every variant of `executor`, e.g. after its
+/// config file changed on disk.
+pub fn invalidate_cached_availability(executor: BaseCodingAgent) {
+    let executor = executor.to_string();
+    let variant_prefix = format!("{executor}:");
+    HEALTH_CACHE
+        .lock()
+        .unwrap()
+        .retain(|key, _| *key != executor && !key.starts_with(&variant_prefix));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_missing_programs_and_pinned_packages() {
+        let missing = check_program("no-such-agent", None, &[]).await;
+        assert!(!missing.available);
+        assert_eq!(
+            missing.error.as_deref(),
+            Some("`no-such-agent` not found in PATH")
+        );
+
+        let args = [
+            "-y".to_string(),
+            "@anthropic-ai/claude-code@2.0.53".to_string(),
+        ];
+        let npx = check_program("npx", Some("/usr/bin/npx"), &args).await;
+        assert!(npx.available);
+        assert_eq!(
+            npx.version.as_deref(),
+            Some("@anthropic-ai/claude-code@2.0.53")
+        );
+    }
+}
@@ -1,8 +1,11 @@
 pub mod actions;
 pub mod approvals;
 pub mod command;
+pub mod env;
 pub mod executors;
+pub mod health;
 pub mod logs;
 pub mod mcp_config;
+pub mod mcp_validation;
 pub mod profile;
 pub mod stdout_dup;
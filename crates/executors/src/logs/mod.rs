@@ -4,7 +4,10 @@ use ts_rs::TS;
 use workspace_utils::approvals::ApprovalStatus;
 
 pub mod plain_text_processor;
+pub mod plan;
+pub mod session_id;
 pub mod stderr_processor;
+pub mod usage;
 pub mod utils;
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -61,6 +64,10 @@ pub struct NormalizedConversation {
     pub executor_type: String,
     pub prompt: Option<String>,
     pub summary: Option<String>,
+    /// Tokens used by the conversation's agent runs, if any reported usage
+    pub usage: Option<usage::UsageSummary>,
+    /// Some run started without MCP servers that failed to launch
+    pub mcp_degraded: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
@@ -181,6 +188,13 @@ pub enum ActionType {
         path: String,
         changes: Vec<FileChange>,
     },
+    FileDelete {
+        path: String,
+    },
+    FileMove {
+        from: String,
+        to: String,
+    },
     CommandRun {
         command: String,
         #[serde(default)]
@@ -0,0 +1,153 @@
+//! Plans presented by agents running in plan mode, split into steps that can
+//! be sent back one at a time.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+pub struct PlanStep {
+    /// 1-based position in the plan
+    pub number: usize,
+    /// The step's own line followed by any nested lines, without its marker
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Marker {
+    Numbered,
+    Bullet,
+}
+
+/// A list item or numbered heading: its indentation, kind and text after the
+/// marker.
+fn list_item(line: &str) -> Option<(usize, Marker, &str)> {
+    let indent = line.len() - line.trim_start().len();
+    let rest = line.trim_start();
+
+    let heading = rest.trim_start_matches('#');
+    if heading.len() < rest.len() && heading.starts_with(' ') {
+        return numbered(heading.trim_start()).map(|text| (0, Marker::Numbered, text));
+    }
+    if let Some(text) = numbered(rest) {
+        return Some((indent, Marker::Numbered, text));
+    }
+    ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| rest.strip_prefix(bullet))
+        .map(|text| (indent, Marker::Bullet, text.trim_start()))
+}
+
+/// `1. text` or `1) text`
+fn numbered(line: &str) -> Option<&str> {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    let rest = line[digits..]
+        .strip_prefix('.')
+        .or_else(|| line[digits..].strip_prefix(')'))?;
+    rest.starts_with(' ').then_some(rest.trim_start())
+}
+
+/// Split a plan into its top-level numbered or bulleted steps. Text before
+/// the first step is left out; nested items and continuation lines stay with
+/// their step. A plan with no recognisable list becomes a single step.
+pub fn parse_plan_steps(plan: &str) -> Vec<PlanStep> {
+    let items: Vec<(usize, Marker)> = plan
+        .lines()
+        .filter_map(list_item)
+        .map(|(indent, marker, _)| (indent, marker))
+        .collect();
+    let Some(top_indent) = items.iter().map(|(indent, _)| *indent).min() else {
+        return single_step(plan);
+    };
+    // Numbered steps win over bullets at the same level, which are usually
+    // notes rather than steps
+    let marker = if items.contains(&(top_indent, Marker::Numbered)) {
+        Marker::Numbered
+    } else {
+        Marker::Bullet
+    };
+
+    let mut steps: Vec<Vec<&str>> = Vec::new();
+    for line in plan.lines() {
+        match list_item(line) {
+            Some((indent, m, text)) if indent == top_indent && m == marker => {
+                steps.push(vec![text]);
+            }
+            _ => {
+                if let Some(step) = steps.last_mut() {
+                    step.push(line);
+                }
+            }
+        }
+    }
+
+    let steps: Vec<PlanStep> = steps
+        .into_iter()
+        .map(|lines| lines.join("\n").trim().to_string())
+        .filter(|text| !text.is_empty())
+        .enumerate()
+        .map(|(i, text)| PlanStep {
+            number: i + 1,
+            text,
+        })
+        .collect();
+    if steps.is_empty() {
+        single_step(plan)
+    } else {
+        steps
+    }
+}
+
+fn single_step(plan: &str) -> Vec<PlanStep> {
+    let text = plan.trim();
+    if text.is_empty() {
+        return Vec::new();
+    }
+    vec![PlanStep {
+        number: 1,
+        text: text.to_string(),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(plan: &str) -> Vec<String> {
+        parse_plan_steps(plan).into_iter().map(|s| s.text).collect()
+    }
+
+    #[test]
+    fn splits_top_level_numbered_steps() {
+        let plan = "## Plan\n\nI'll do this in three steps:\n\n1. Add the migration\n   - new `plans` table\n2) Write the model\n3. Expose the route\n\n- Note: no UI changes";
+        assert_eq!(
+            texts(plan),
+            [
+                "Add the migration\n   - new `plans` table",
+                "Write the model",
+                "Expose the route\n\n- Note: no UI changes",
+            ]
+        );
+    }
+
+    #[test]
+    fn uses_numbered_headings_and_bullets() {
+        assert_eq!(
+            texts("### 1. Backend\nAdd the route.\n### 2. Frontend\nShow it."),
+            ["Backend\nAdd the route.", "Frontend\nShow it."]
+        );
+        assert_eq!(
+            texts("- Rename the module\n- Update imports"),
+            ["Rename the module", "Update imports"]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_a_single_step() {
+        let plan = "Refactor the parser so errors carry spans.";
+        assert_eq!(texts(plan), [plan]);
+        assert!(parse_plan_steps("  ").is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+//! Finds an agent's session id in its raw stdout, for executors whose log
+//! normalizer does not report one.
+
+use serde_json::Value;
+
+use crate::executors::BaseCodingAgent;
+
+/// Where an executor prints its session id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionIdHints {
+    /// Non-empty lines to look at before giving up
+    pub max_lines: usize,
+    /// JSON keys holding the id, at the top level or one object down
+    pub keys: &'static [&'static str],
+    /// Prefix of a plain-text line followed by the id
+    pub line_prefix: Option<&'static str>,
+}
+
+impl SessionIdHints {
+    pub fn for_executor(executor: BaseCodingAgent) -> Self {
+        let hints = |max_lines, keys| Self {
+            max_lines,
+            keys,
+            line_prefix: None,
+        };
+        match executor {
+            BaseCodingAgent::ClaudeCode | BaseCodingAgent::Amp | BaseCodingAgent::CursorAgent => {
+                hints(20, &["session_id"])
+            }
+            BaseCodingAgent::Codex => hints(50, &["session_id", "conversation_id", "thread_id"]),
+            // Gemini and Qwen print a banner before the ACP events start
+            BaseCodingAgent::Gemini | BaseCodingAgent::QwenCode => {
+                hints(100, &["SessionStart", "sessionId"])
+            }
+            // Opencode logs a few info lines before its first event
+            BaseCodingAgent::Opencode => hints(100, &["sessionID", "threadID"]),
+            BaseCodingAgent::Droid => hints(20, &["session_id", "sessionId"]),
+            BaseCodingAgent::Copilot => Self {
+                max_lines: 200,
+                keys: &[],
+                line_prefix: Some("[copilot-session] "),
+            },
+        }
+    }
+}
+
+/// Looks for a session id line by line and stops for good once it finds one
+/// or has seen `max_lines` lines without one.
+#[derive(Debug)]
+pub struct SessionIdScanner {
+    hints: SessionIdHints,
+    lines_seen: usize,
+    partial: String,
+    done: bool,
+}
+
+impl SessionIdScanner {
+    pub fn new(hints: SessionIdHints) -> Self {
+        Self {
+            hints,
+            lines_seen: 0,
+            partial: String::new(),
+            done: false,
+        }
+    }
+
+    pub fn for_executor(executor: BaseCodingAgent) -> Self {
+        Self::new(SessionIdHints::for_executor(executor))
+    }
+
+    /// Whether the scanner has stopped looking.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Stop looking, e.g. because the id was reported another way.
+    pub fn finish(&mut self) {
+        self.done = true;
+        self.partial.clear();
+    }
+
+    /// Feed a chunk of stdout, which may hold partial lines. Returns the id
+    /// the first time one is found.
+    pub fn push_chunk(&mut self, chunk: &str) -> Option<String> {
+        if self.done {
+            return None;
+        }
+        self.partial.push_str(chunk);
+        let Some(end) = self.partial.rfind('\n') else {
+            return None;
+        };
+        let complete: String = self.partial.drain(..=end).collect();
+        complete.lines().find_map(|line| self.push_line(line))
+    }
+
+    /// Feed one complete line. Returns the id the first time one is found.
+    pub fn push_line(&mut self, line: &str) -> Option<String> {
+        if self.done {
+            return None;
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            return None;
+        }
+        self.lines_seen += 1;
+
+        let found = self.find_in_line(line);
+        if found.is_some() || self.lines_seen >= self.hints.max_lines {
+            self.finish();
+        }
+        found
+    }
+
+    fn find_in_line(&self, line: &str) -> Option<String> {
+        if let Some(prefix) = self.hints.line_prefix
+            && let Some(id) = line.strip_prefix(prefix)
+        {
+            let id = id.trim();
+            return (!id.is_empty()).then(|| id.to_string());
+        }
+        if self.hints.keys.is_empty() || !line.starts_with('{') {
+            return None;
+        }
+        let Ok(Value::Object(object)) = serde_json::from_str::<Value>(line) else {
+            return None;
+        };
+        let top_level = std::iter::once(&object);
+        let nested = object.values().filter_map(Value::as_object);
+        top_level.chain(nested).find_map(|object| {
+            self.hints
+                .keys
+                .iter()
+                .find_map(|key| match object.get(*key) {
+                    Some(Value::String(id)) if !id.is_empty() => Some(id.clone()),
+                    _ => None,
+                })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_an_id_printed_after_banner_lines() {
+        let mut scanner = SessionIdScanner::for_executor(BaseCodingAgent::Opencode);
+        let log = "Opencode v0.9\n\
+                   INFO  loading config\n\
+                   INFO  starting server\n\
+                   {\"type\":\"ready\"}\n\
+                   {\"type\":\"message\",\"properties\":{\"sessionID\":\"ses_42\"}}\n\
+                   {\"type\":\"message\",\"properties\":{\"sessionID\":\"ses_43\"}}\n";
+        let (first, second) = log.split_at(70);
+        let found: Vec<String> = [first, second]
+            .into_iter()
+            .filter_map(|chunk| scanner.push_chunk(chunk))
+            .collect();
+        assert_eq!(found, ["ses_42"]);
+        assert!(scanner.is_done());
+    }
+
+    #[test]
+    fn gives_up_after_max_lines_without_an_id() {
+        let mut scanner = SessionIdScanner::new(SessionIdHints {
+            max_lines: 3,
+            keys: &["session_id"],
+            line_prefix: None,
+        });
+        for line in ["warming up", "", "{\"type\":\"system\"}", "still nothing"] {
+            assert_eq!(scanner.push_line(line), None);
+        }
+        assert!(scanner.is_done());
+        assert_eq!(scanner.push_line("{\"session_id\":\"late\"}"), None);
+    }
+}
@@ -0,0 +1,75 @@
+//! Token usage reported by coding agents.
+
+use std::{iter::Sum, ops::Add};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use workspace_utils::log_msg::LogMsg;
+
+/// Token counts for one or more agent runs. Counts are disjoint, so the
+/// tokens billed as input are `input_tokens + cache_read_input_tokens +
+/// cache_creation_input_tokens`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, TS)]
+pub struct UsageSummary {
+    /// Input tokens not read from or written to the prompt cache
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_input_tokens: i64,
+    pub cache_creation_input_tokens: i64,
+    /// Cost reported by the agent. `None` when any of the summed runs did not
+    /// report one.
+    pub cost_usd: Option<f64>,
+}
+
+impl UsageSummary {
+    pub fn total_tokens(&self) -> i64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_read_input_tokens
+            + self.cache_creation_input_tokens
+    }
+}
+
+impl Add for UsageSummary {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            input_tokens: self.input_tokens + other.input_tokens,
+            output_tokens: self.output_tokens + other.output_tokens,
+            cache_read_input_tokens: self.cache_read_input_tokens + other.cache_read_input_tokens,
+            cache_creation_input_tokens: self.cache_creation_input_tokens
+                + other.cache_creation_input_tokens,
+            cost_usd: self.cost_usd.zip(other.cost_usd).map(|(a, b)| a + b),
+        }
+    }
+}
+
+impl Sum for UsageSummary {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(Add::add).unwrap_or_default()
+    }
+}
+
+/// Complete stdout lines from a process's log history. Chunks are joined
+/// first, since a JSON line can be split across several of them.
+pub fn stdout_lines(history: &[LogMsg]) -> Vec<String> {
+    let stdout: String = history
+        .iter()
+        .filter_map(|msg| match msg {
+            LogMsg::Stdout(chunk) => Some(chunk.as_str()),
+            _ => None,
+        })
+        .collect();
+    stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Convert an agent's token count, clamping negative or oversized values.
+pub(crate) fn token_count<T: TryInto<i64>>(value: T) -> i64 {
+    value.try_into().unwrap_or_default().max(0)
+}
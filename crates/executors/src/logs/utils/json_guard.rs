@@ -0,0 +1,262 @@
+//! Guarded JSON parsing for executor output.
+//!
+//! Executors print one JSON document per line and we parse whatever comes
+//! out. A single pathological line (megabytes of nested arrays, a number with
+//! thousands of digits) should not be able to stall the normalizer or balloon
+//! memory, so every line is pre-scanned against [`JsonLineLimits`] before it
+//! reaches `serde_json`.
+
+use std::sync::LazyLock;
+
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+use crate::logs::{NormalizedEntry, NormalizedEntryType};
+
+/// Overrides [`JsonLineLimits::max_line_bytes`] for every normalizer.
+pub const MAX_LINE_BYTES_ENV: &str = "VK_EXECUTOR_MAX_JSON_LINE_BYTES";
+
+/// Longest line content kept when a line is shown as a system message.
+pub const FALLBACK_MAX_CHARS: usize = 4096;
+
+static DEFAULT_LIMITS: LazyLock<JsonLineLimits> = LazyLock::new(JsonLineLimits::from_env);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonLineLimits {
+    pub max_line_bytes: usize,
+    /// Deepest allowed `[`/`{` nesting.
+    pub max_depth: usize,
+    /// Upper bound on the number of values (containers and scalars) in a line.
+    pub max_values: usize,
+    /// Most digits allowed in a number literal. Anything past this cannot be
+    /// represented without losing precision anyway.
+    pub max_number_len: usize,
+}
+
+impl Default for JsonLineLimits {
+    fn default() -> Self {
+        Self {
+            max_line_bytes: 16 * 1024 * 1024,
+            max_depth: 96,
+            max_values: 1_000_000,
+            max_number_len: 64,
+        }
+    }
+}
+
+impl JsonLineLimits {
+    pub fn from_env() -> Self {
+        let mut limits = Self::default();
+        if let Some(max_line_bytes) = std::env::var(MAX_LINE_BYTES_ENV)
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+        {
+            limits.max_line_bytes = max_line_bytes;
+        }
+        limits
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum JsonLineError {
+    #[error("line is {len} bytes, limit is {max}")]
+    TooLong { len: usize, max: usize },
+    #[error("nesting exceeds depth {max}")]
+    TooDeep { max: usize },
+    #[error("line holds more than {max} values")]
+    TooManyValues { max: usize },
+    #[error("number literal has more than {max} digits")]
+    NumberTooLong { max: usize },
+    #[error(transparent)]
+    Parse(#[from] serde_json::Error),
+}
+
+impl JsonLineError {
+    /// True when the line was refused by the limits rather than being
+    /// ordinary non-JSON output.
+    pub fn is_rejected(&self) -> bool {
+        !matches!(self, JsonLineError::Parse(_))
+    }
+}
+
+/// Parse one line of executor output with the process-wide limits.
+pub fn parse_line<T: DeserializeOwned>(line: &str) -> Result<T, JsonLineError> {
+    parse_line_with(line, &DEFAULT_LIMITS)
+}
+
+pub fn parse_line_with<T: DeserializeOwned>(
+    line: &str,
+    limits: &JsonLineLimits,
+) -> Result<T, JsonLineError> {
+    check_line_with(line, limits)?;
+    Ok(serde_json::from_str(line)?)
+}
+
+/// Run only the limit checks, for normalizers that try several target types
+/// against the same line.
+pub fn check_line(line: &str) -> Result<(), JsonLineError> {
+    check_line_with(line, &DEFAULT_LIMITS)
+}
+
+pub fn check_line_with(line: &str, limits: &JsonLineLimits) -> Result<(), JsonLineError> {
+    let result = scan(line, limits);
+    if let Err(err) = &result {
+        tracing::warn!("Rejected executor JSON line: {}", err);
+    }
+    result
+}
+
+fn scan(line: &str, limits: &JsonLineLimits) -> Result<(), JsonLineError> {
+    if line.len() > limits.max_line_bytes {
+        return Err(JsonLineError::TooLong {
+            len: line.len(),
+            max: limits.max_line_bytes,
+        });
+    }
+
+    let mut depth = 0usize;
+    let mut values = 1usize;
+    let mut number_len = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for &b in line.as_bytes() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if b == b'\\' {
+                escaped = true;
+            } else if b == b'"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        if b.is_ascii_digit() {
+            number_len += 1;
+            if number_len > limits.max_number_len {
+                return Err(JsonLineError::NumberTooLong {
+                    max: limits.max_number_len,
+                });
+            }
+            continue;
+        }
+        if matches!(b, b'-' | b'+' | b'.' | b'e' | b'E') {
+            continue;
+        }
+        number_len = 0;
+
+        match b {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > limits.max_depth {
+                    return Err(JsonLineError::TooDeep {
+                        max: limits.max_depth,
+                    });
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            b',' => {
+                values += 1;
+                if values > limits.max_values {
+                    return Err(JsonLineError::TooManyValues {
+                        max: limits.max_values,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// System message shown in place of a line that could not be normalized.
+/// Long lines are cut to [`FALLBACK_MAX_CHARS`] so one bad line cannot flood
+/// the conversation.
+pub fn fallback_entry(line: &str) -> NormalizedEntry {
+    NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::SystemMessage,
+        content: truncate_for_display(line),
+        metadata: None,
+    }
+}
+
+fn truncate_for_display(line: &str) -> String {
+    match line.char_indices().nth(FALLBACK_MAX_CHARS) {
+        Some((cut, _)) => format!("{}… [truncated {} bytes]", &line[..cut], line.len() - cut),
+        None => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    #[test]
+    fn accepts_ordinary_lines() {
+        let value: Value = parse_line(
+            r#"{"type":"assistant","message":{"content":[{"text":"a [{ b"}]},"n":-1.5e3}"#,
+        )
+        .unwrap();
+        assert_eq!(value["message"]["content"][0]["text"], "a [{ b");
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let line = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        let err = parse_line::<Value>(&line).unwrap_err();
+        assert!(matches!(err, JsonLineError::TooDeep { .. }));
+
+        // Brackets inside strings do not count
+        let quoted = format!(r#"{{"text":"{}"}}"#, "[".repeat(1000));
+        assert!(parse_line::<Value>(&quoted).is_ok());
+    }
+
+    #[test]
+    fn rejects_giant_numbers() {
+        let line = format!(r#"{{"n":{}}}"#, "9".repeat(10_000));
+        let err = parse_line::<Value>(&line).unwrap_err();
+        assert!(matches!(err, JsonLineError::NumberTooLong { .. }));
+    }
+
+    #[test]
+    fn rejects_too_many_values() {
+        let limits = JsonLineLimits {
+            max_values: 100,
+            ..Default::default()
+        };
+        let line = format!("[{}0]", "0,".repeat(200));
+        let err = parse_line_with::<Value>(&line, &limits).unwrap_err();
+        assert!(matches!(err, JsonLineError::TooManyValues { .. }));
+    }
+
+    #[test]
+    fn invalid_unicode_escape_is_a_parse_error() {
+        let err = parse_line::<Value>(r#"{"text":"\ud800"}"#).unwrap_err();
+        assert!(!err.is_rejected());
+        let err = parse_line::<Value>(r#"{"text":"\uZZZZ"}"#).unwrap_err();
+        assert!(!err.is_rejected());
+    }
+
+    #[test]
+    fn rejects_oversized_line_and_truncates_fallback() {
+        let line = format!(r#"{{"text":"{}"}}"#, "x".repeat(50 * 1024 * 1024));
+        let err = parse_line::<Value>(&line).unwrap_err();
+        assert!(matches!(err, JsonLineError::TooLong { .. }));
+
+        let entry = fallback_entry(&line);
+        assert!(matches!(
+            entry.entry_type,
+            NormalizedEntryType::SystemMessage
+        ));
+        assert!(entry.content.len() < FALLBACK_MAX_CHARS + 64);
+        assert!(entry.content.ends_with(&format!(
+            "[truncated {} bytes]",
+            line.len() - FALLBACK_MAX_CHARS
+        )));
+    }
+}
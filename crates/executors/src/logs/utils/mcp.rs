@@ -0,0 +1,115 @@
+//! Metadata for the entry reporting which MCP servers an agent started with,
+//! so a run that silently lost its MCP tools can be recognised later.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::logs::NormalizedEntry;
+
+/// Status an agent reports for a server that launched
+pub const MCP_CONNECTED: &str = "connected";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpServerStatus {
+    pub name: String,
+    pub status: String,
+}
+
+impl McpServerStatus {
+    pub fn is_connected(&self) -> bool {
+        self.status == MCP_CONNECTED
+    }
+}
+
+/// Stored under the `mcp` key of the entry's metadata
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpStatusMetadata {
+    pub servers: Vec<McpServerStatus>,
+    /// At least one configured server did not connect
+    pub degraded: bool,
+}
+
+impl McpStatusMetadata {
+    pub fn new(servers: Vec<McpServerStatus>) -> Self {
+        let degraded = servers.iter().any(|server| !server.is_connected());
+        Self { servers, degraded }
+    }
+
+    /// One line per server, failed ones first.
+    pub fn describe(&self) -> String {
+        let mut servers: Vec<_> = self.servers.iter().collect();
+        servers.sort_by_key(|server| server.is_connected());
+        let lines = servers
+            .iter()
+            .map(|server| format!("- {}: {}", server.name, server.status))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if self.degraded {
+            format!("Some MCP servers failed to start; their tools are unavailable.\n{lines}")
+        } else {
+            format!("MCP servers connected.\n{lines}")
+        }
+    }
+
+    pub fn attach(&self, metadata: &mut Value) {
+        if let Some(object) = metadata.as_object_mut()
+            && let Ok(mcp) = serde_json::to_value(self)
+        {
+            object.insert("mcp".to_string(), mcp);
+        }
+    }
+
+    pub fn from_entry(entry: &NormalizedEntry) -> Option<Self> {
+        let mcp = entry.metadata.as_ref()?.get("mcp")?;
+        serde_json::from_value(mcp.clone()).ok()
+    }
+}
+
+/// True if any entry reports an MCP server that failed to start.
+pub fn mcp_degraded<'a>(entries: impl IntoIterator<Item = &'a NormalizedEntry>) -> bool {
+    entries
+        .into_iter()
+        .filter_map(McpStatusMetadata::from_entry)
+        .any(|mcp| mcp.degraded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(name: &str, status: &str) -> McpServerStatus {
+        McpServerStatus {
+            name: name.to_string(),
+            status: status.to_string(),
+        }
+    }
+
+    #[test]
+    fn lists_failed_servers_first() {
+        let mcp = McpStatusMetadata::new(vec![
+            server("filesystem", "connected"),
+            server("playwright", "failed"),
+        ]);
+        assert!(mcp.degraded);
+        assert_eq!(
+            mcp.describe(),
+            "Some MCP servers failed to start; their tools are unavailable.\n- playwright: failed\n- filesystem: connected"
+        );
+    }
+
+    #[test]
+    fn round_trips_through_entry_metadata() {
+        let mcp = McpStatusMetadata::new(vec![server("filesystem", "connected")]);
+        let mut metadata = serde_json::json!({});
+        mcp.attach(&mut metadata);
+        let entry = NormalizedEntry {
+            timestamp: None,
+            entry_type: crate::logs::NormalizedEntryType::SystemMessage,
+            content: mcp.describe(),
+            metadata: Some(metadata),
+        };
+
+        assert_eq!(McpStatusMetadata::from_entry(&entry), Some(mcp));
+        assert!(!mcp_degraded([&entry]));
+    }
+}
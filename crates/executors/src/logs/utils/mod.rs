@@ -1,7 +1,11 @@
 //! Utility modules for executor framework
 
 pub mod entry_index;
+pub mod json_guard;
+pub mod mcp;
 pub mod patch;
+pub mod search;
+pub mod shell;
 
 pub use entry_index::EntryIndexProvider;
 pub use patch::ConversationPatch;
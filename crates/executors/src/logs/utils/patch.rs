@@ -142,6 +142,51 @@ pub fn extract_normalized_entry_from_patch(patch: &Patch) -> Option<(usize, Norm
     })
 }
 
+/// A change to one conversation entry, as carried by a JsonPatch. `Upsert`
+/// covers both added and replaced entries.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum EntryUpdate {
+    Upsert {
+        index: usize,
+        entry: NormalizedEntry,
+    },
+    Remove {
+        index: usize,
+    },
+}
+
+/// Every normalized entry change in a JsonPatch, in order. Raw stdout/stderr
+/// and diff entries are skipped.
+pub fn entry_updates_from_patch(patch: &Patch) -> Vec<EntryUpdate> {
+    let Ok(value) = to_value(patch) else {
+        return Vec::new();
+    };
+    let Some(ops) = value.as_array() else {
+        return Vec::new();
+    };
+    ops.iter()
+        .filter_map(|op| {
+            let index = op
+                .get("path")?
+                .as_str()?
+                .strip_prefix("/entries/")?
+                .parse::<usize>()
+                .ok()?;
+            if op.get("op")?.as_str()? == "remove" {
+                return Some(EntryUpdate::Remove { index });
+            }
+            let value = op.get("value")?;
+            if value.get("type")?.as_str()? != "NORMALIZED_ENTRY" {
+                return None;
+            }
+            let entry = from_value::<NormalizedEntry>(value.get("content")?.clone()).ok()?;
+            Some(EntryUpdate::Upsert { index, entry })
+        })
+        .collect()
+}
+
 pub fn upsert_normalized_entry(
     msg_store: &Arc<MsgStore>,
     index: usize,
@@ -175,3 +220,43 @@ pub fn replace_normalized_entry(
 ) {
     upsert_normalized_entry(msg_store, index, normalized_entry, false);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::NormalizedEntryType;
+
+    fn entry(content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::AssistantMessage,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn entry_updates_skip_raw_output() {
+        let updates =
+            entry_updates_from_patch(&ConversationPatch::add_normalized_entry(2, entry("Hello")));
+        assert!(matches!(
+            updates.as_slice(),
+            [EntryUpdate::Upsert { index: 2, entry }] if entry.content == "Hello"
+        ));
+
+        let updates = entry_updates_from_patch(&ConversationPatch::replace(2, entry("Hello!")));
+        assert!(matches!(
+            updates.as_slice(),
+            [EntryUpdate::Upsert { index: 2, entry }] if entry.content == "Hello!"
+        ));
+
+        assert!(matches!(
+            entry_updates_from_patch(&ConversationPatch::remove(2)).as_slice(),
+            [EntryUpdate::Remove { index: 2 }]
+        ));
+        assert!(
+            entry_updates_from_patch(&ConversationPatch::add_stdout(3, "raw".to_string()))
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Metadata for search tool calls (grep, glob, web search), shared by the
+//! normalizers so every executor describes searches the same way.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Stored under the `search` key of a search entry's metadata
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SearchMetadata {
+    /// Directory or file the search was limited to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// A web search rather than one of the worktree
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub web: bool,
+    /// Matches found, once the tool's output is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_count: Option<usize>,
+}
+
+impl SearchMetadata {
+    /// Fill in `result_count` from the tool's output, when it can be told
+    pub fn record_output(&mut self, output: &str) {
+        self.result_count = if self.web {
+            count_web_results(output)
+        } else {
+            count_matches(output)
+        };
+    }
+
+    pub fn attach(&self, metadata: &mut Value) {
+        if let Some(object) = metadata.as_object_mut()
+            && let Ok(search) = serde_json::to_value(self)
+        {
+            object.insert("search".to_string(), search);
+        }
+    }
+}
+
+/// Understands the usual "No files found" / "Found 3 files" summaries and
+/// JSON arrays of matches, and otherwise counts non-empty lines.
+fn count_matches(output: &str) -> Option<usize> {
+    let output = output.trim();
+    if output.is_empty() {
+        return Some(0);
+    }
+    let first_line = output.lines().next().unwrap_or_default().trim();
+    if first_line.starts_with("No ") && first_line.contains("found") {
+        return Some(0);
+    }
+    if let Some(count) = first_line
+        .strip_prefix("Found ")
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|count| count.parse().ok())
+    {
+        return Some(count);
+    }
+    if let Ok(Value::Array(items)) = serde_json::from_str::<Value>(output) {
+        return Some(items.len());
+    }
+    Some(
+        output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .count(),
+    )
+}
+
+/// Web search output is prose; only a `Links: [...]` list can be counted.
+fn count_web_results(output: &str) -> Option<usize> {
+    let links = output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Links:"))?;
+    match serde_json::from_str::<Value>(links.trim()) {
+        Ok(Value::Array(items)) => Some(items.len()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_common_output_shapes() {
+        assert_eq!(count_matches("No files found"), Some(0));
+        assert_eq!(count_matches("No matches found for \"foo\""), Some(0));
+        assert_eq!(
+            count_matches("Found 3 files\nsrc/a.rs\nsrc/b.rs\nsrc/c.rs"),
+            Some(3)
+        );
+        assert_eq!(count_matches(r#"["a.rs","b.rs"]"#), Some(2));
+        assert_eq!(count_matches("src/a.rs:1:foo\nsrc/b.rs:9:foo\n"), Some(2));
+    }
+
+    #[test]
+    fn web_results_are_counted_only_from_links() {
+        let mut search = SearchMetadata {
+            web: true,
+            ..Default::default()
+        };
+        search.record_output(
+            "Web search results for query: \"rust\"\n\nLinks: [{\"title\":\"Rust\",\"url\":\"https://rust-lang.org\"}]\n\nSummary...",
+        );
+        assert_eq!(search.result_count, Some(1));
+
+        search.record_output("Rust is a systems programming language.\nIt is fast.");
+        assert_eq!(search.result_count, None);
+    }
+
+    #[test]
+    fn attaches_under_the_search_key() {
+        let mut metadata = serde_json::json!({ "tool_call_id": "t1" });
+        SearchMetadata {
+            path: Some("src".to_string()),
+            web: false,
+            result_count: Some(0),
+        }
+        .attach(&mut metadata);
+
+        assert_eq!(
+            metadata,
+            serde_json::json!({
+                "tool_call_id": "t1",
+                "search": { "path": "src", "result_count": 0 }
+            })
+        );
+    }
+}
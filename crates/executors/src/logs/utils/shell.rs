@@ -0,0 +1,114 @@
+//! Recognition of shell commands that do nothing but delete or move a file,
+//! so they can be shown as file actions instead of plain commands.
+
+use workspace_utils::path::make_path_relative;
+
+use crate::logs::ActionType;
+
+/// Anything that could make the command more than one simple invocation on
+/// literal paths: pipes, chaining, redirection, substitution, globs.
+const SHELL_METACHARACTERS: &[char] = &[
+    '|', '&', ';', '<', '>', '`', '$', '(', ')', '*', '?', '[', '{', '~', '\n',
+];
+
+const DELETE_FLAGS: &[&str] = &["-f", "-r", "-R", "-rf", "-fr", "-v", "--force", "--verbose"];
+const GIT_RM_FLAGS: &[&str] = &["-f", "-r", "-q", "--force", "--quiet"];
+const MOVE_FLAGS: &[&str] = &["-f", "-v", "--force", "--verbose"];
+
+/// The file action `command` performs, if it is a single `rm`, `mv`,
+/// `git rm` or `git mv` whose paths can be read off unambiguously.
+pub fn file_action_from_command(command: &str, worktree_path: &str) -> Option<ActionType> {
+    if command.contains(SHELL_METACHARACTERS) {
+        return None;
+    }
+    let words = shlex::split(command)?;
+    let (program, args) = match words.as_slice() {
+        [git, sub, args @ ..] if git == "git" => (format!("git {sub}"), args),
+        [program, args @ ..] => (program.clone(), args),
+        [] => return None,
+    };
+    let flags = match program.as_str() {
+        "rm" => DELETE_FLAGS,
+        "git rm" => GIT_RM_FLAGS,
+        "mv" | "git mv" => MOVE_FLAGS,
+        _ => return None,
+    };
+    let paths = operands(args, flags)?;
+    let relative = |path: &String| make_path_relative(path, worktree_path);
+
+    match (program.as_str(), paths.as_slice()) {
+        ("rm" | "git rm", [path]) => Some(ActionType::FileDelete {
+            path: relative(path),
+        }),
+        // A trailing slash moves into a directory, so the new name is unknown
+        ("mv" | "git mv", [from, to]) if !to.ends_with('/') => Some(ActionType::FileMove {
+            from: relative(from),
+            to: relative(to),
+        }),
+        _ => None,
+    }
+}
+
+/// Non-flag arguments, or `None` if any flag is outside `allowed`.
+fn operands<'a>(args: &'a [String], allowed: &[&str]) -> Option<Vec<&'a String>> {
+    let mut operands = Vec::new();
+    let mut options_ended = false;
+    for arg in args {
+        if options_ended || !arg.starts_with('-') {
+            operands.push(arg);
+        } else if arg == "--" {
+            options_ended = true;
+        } else if !allowed.contains(&arg.as_str()) {
+            return None;
+        }
+    }
+    Some(operands)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    const WORKTREE: &str = "/tmp/worktree";
+
+    fn action(command: &str) -> Value {
+        serde_json::to_value(file_action_from_command(command, WORKTREE)).unwrap()
+    }
+
+    #[test]
+    fn recognizes_simple_deletes_and_moves() {
+        let delete = |path: &str| json!({ "action": "file_delete", "path": path });
+        let moved = |from: &str, to: &str| json!({ "action": "file_move", "from": from, "to": to });
+
+        assert_eq!(action("rm -f src/old.rs"), delete("src/old.rs"));
+        assert_eq!(action("rm /tmp/worktree/src/old.rs"), delete("src/old.rs"));
+        assert_eq!(
+            action("git rm -- 'notes draft.md'"),
+            delete("notes draft.md")
+        );
+        assert_eq!(action("git mv a.rs b.rs"), moved("a.rs", "b.rs"));
+        assert_eq!(
+            action("mv -f /tmp/worktree/a.rs src/a.rs"),
+            moved("a.rs", "src/a.rs")
+        );
+    }
+
+    #[test]
+    fn leaves_ambiguous_commands_alone() {
+        for command in [
+            "rm a.rs b.rs",
+            "rm *.log",
+            "rm -i a.rs",
+            "git rm --cached a.rs",
+            "rm a.rs && cargo build",
+            "mv a.rs src/",
+            "mv a.rs b.rs c/",
+            "rm $FILE",
+            "ls -la",
+        ] {
+            assert_eq!(action(command), Value::Null, "{command}");
+        }
+    }
+}
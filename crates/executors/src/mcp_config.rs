@@ -2,12 +2,19 @@
 //!
 //! These helpers abstract over JSON vs TOML formats used by different agents.
 
-use std::{collections::HashMap, sync::LazyLock};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
 
+use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use tokio::fs;
+use toml_edit::{DocumentMut, Item, Table};
 use ts_rs::TS;
+use workspace_utils::cache_dir;
 
 use crate::executors::{CodingAgent, ExecutorError};
 
@@ -76,15 +83,184 @@ pub async fn write_agent_config(
     if mcp_config.is_toml_config {
         // Convert JSON Value back to TOML
         let toml_value: toml::Value = serde_json::from_str(&serde_json::to_string(config)?)?;
-        let toml_content = toml::to_string_pretty(&toml_value)?;
-        fs::write(config_path, toml_content).await?;
+        let mut toml_content = toml::to_string_pretty(&toml_value)?;
+        // Edit the existing file in place so comments and untouched keys survive
+        if let Ok(existing) = fs::read_to_string(config_path).await
+            && let Some(merged) = merge_toml(&existing, &toml_content)
+        {
+            toml_content = merged;
+        }
+        write_atomically(config_path, toml_content.as_bytes()).await?;
     } else {
         let json_content = serde_json::to_string_pretty(config)?;
-        fs::write(config_path, json_content).await?;
+        write_atomically(config_path, json_content.as_bytes()).await?;
     }
     Ok(())
 }
 
+/// Write through a sibling temp file and rename it into place, so an
+/// interrupted write leaves the old file intact rather than a truncated one.
+async fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".vk-tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    // Replaces any temp file left by an earlier interrupted write
+    fs::write(&tmp, contents).await?;
+    if let Err(e) = fs::rename(&tmp, path).await {
+        let _ = fs::remove_file(&tmp).await;
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Backups kept per executor; older ones are deleted
+const MCP_BACKUPS_KEPT: usize = 5;
+
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3f";
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct McpConfigBackup {
+    /// File name within the executor's backup directory
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub size_bytes: u64,
+}
+
+/// Where copies of an executor's config file are kept before it is rewritten
+pub fn mcp_backup_dir(executor: &str) -> PathBuf {
+    cache_dir().join("mcp-backups").join(executor)
+}
+
+/// Copy the current config file into `backup_dir` and prune old copies.
+/// Returns `None` if there is no file to back up yet.
+pub async fn backup_agent_config(
+    config_path: &Path,
+    backup_dir: &Path,
+) -> Result<Option<McpConfigBackup>, ExecutorError> {
+    let Ok(contents) = fs::read(config_path).await else {
+        return Ok(None);
+    };
+    fs::create_dir_all(backup_dir).await?;
+
+    let created_at = Utc::now();
+    let file_name = config_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "config".to_string());
+    let id = format!("{}-{file_name}", created_at.format(BACKUP_TIMESTAMP_FORMAT));
+    fs::write(backup_dir.join(&id), &contents).await?;
+
+    for stale in list_agent_config_backups(backup_dir)
+        .await?
+        .into_iter()
+        .skip(MCP_BACKUPS_KEPT)
+    {
+        let _ = fs::remove_file(backup_dir.join(stale.id)).await;
+    }
+
+    Ok(Some(McpConfigBackup {
+        id,
+        created_at,
+        size_bytes: contents.len() as u64,
+    }))
+}
+
+/// Backups in `backup_dir`, newest first
+pub async fn list_agent_config_backups(
+    backup_dir: &Path,
+) -> Result<Vec<McpConfigBackup>, ExecutorError> {
+    let mut entries = match fs::read_dir(backup_dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut backups = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let id = entry.file_name().to_string_lossy().into_owned();
+        let Some(created_at) = id
+            .split_once('-')
+            .and_then(|(stamp, _)| {
+                NaiveDateTime::parse_from_str(stamp, BACKUP_TIMESTAMP_FORMAT).ok()
+            })
+            .map(|stamp| stamp.and_utc())
+        else {
+            continue;
+        };
+        backups.push(McpConfigBackup {
+            id,
+            created_at,
+            size_bytes: entry.metadata().await?.len(),
+        });
+    }
+    backups.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(backups)
+}
+
+/// Put backup `id` back in place of the config file, first backing up the
+/// file being replaced so the restore can itself be undone. Returns `false`
+/// if there is no such backup.
+pub async fn restore_agent_config(
+    config_path: &Path,
+    backup_dir: &Path,
+    id: &str,
+) -> Result<bool, ExecutorError> {
+    // Ids are bare file names; anything else could point outside the directory
+    if Path::new(id).file_name().and_then(|name| name.to_str()) != Some(id) {
+        return Ok(false);
+    }
+    let contents = match fs::read(backup_dir.join(id)).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+
+    backup_agent_config(config_path, backup_dir).await?;
+    write_atomically(config_path, &contents).await?;
+    Ok(true)
+}
+
+/// Apply `updated` on top of the `existing` TOML document, keeping the
+/// existing formatting and comments for every key whose value is unchanged.
+/// Returns `None` if either document fails to parse.
+fn merge_toml(existing: &str, updated: &str) -> Option<String> {
+    let mut existing = existing.parse::<DocumentMut>().ok()?;
+    let updated = updated.parse::<DocumentMut>().ok()?;
+    merge_toml_table(existing.as_table_mut(), updated.as_table());
+    Some(existing.to_string())
+}
+
+fn merge_toml_table(existing: &mut Table, updated: &Table) {
+    existing.retain(|key, _| updated.contains_key(key));
+    for (key, new_item) in updated.iter() {
+        match (existing.get_mut(key), new_item) {
+            (Some(Item::Table(old)), Item::Table(new)) => merge_toml_table(old, new),
+            (Some(old), new) if same_toml_value(old, new) => {}
+            _ => {
+                existing.insert(key, new_item.clone());
+            }
+        }
+    }
+}
+
+fn same_toml_value(a: &Item, b: &Item) -> bool {
+    fn normalized(value: &toml_edit::Value) -> String {
+        let mut value = value.clone();
+        match &mut value {
+            toml_edit::Value::Array(array) => array.fmt(),
+            toml_edit::Value::InlineTable(table) => table.fmt(),
+            _ => {}
+        }
+        value.decor_mut().clear();
+        value.to_string()
+    }
+    match (a.as_value(), b.as_value()) {
+        (Some(a), Some(b)) => normalized(a) == normalized(b),
+        _ => false,
+    }
+}
+
 type ServerMap = Map<String, Value>;
 
 fn is_http_server(s: &Map<String, Value>) -> bool {
@@ -305,3 +481,103 @@ impl CodingAgent {
         apply_adapter(adapter, canonical)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_toml_keeps_comments_and_unrelated_keys() {
+        let existing = r#"# Codex settings
+model = "o3" # preferred model
+
+[mcp_servers.old]
+command = "old-server"
+
+[profiles.fast]
+model = "o4-mini"
+"#;
+        let config = serde_json::json!({
+            "model": "o3",
+            "mcp_servers": { "vibe_kanban": { "command": "npx", "args": ["-y", "vibe-kanban"] } },
+            "profiles": { "fast": { "model": "o4-mini" } },
+        });
+        let toml_value: toml::Value = serde_json::from_value(config).unwrap();
+        let updated = toml::to_string_pretty(&toml_value).unwrap();
+
+        let merged = merge_toml(existing, &updated).unwrap();
+        assert!(merged.starts_with("# Codex settings\nmodel = \"o3\" # preferred model\n"));
+        assert!(merged.contains("[profiles.fast]"));
+        assert!(!merged.contains("old-server"));
+
+        let parsed: toml::Value = toml::from_str(&merged).unwrap();
+        assert_eq!(parsed, toml_value);
+    }
+
+    #[tokio::test]
+    async fn failed_rename_leaves_the_original_and_no_temp_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".claude.json");
+        let tmp = dir.path().join(".claude.json.vk-tmp");
+
+        // A temp file left behind by an interrupted write is replaced
+        std::fs::write(&path, r#"{"theme":"dark"}"#).unwrap();
+        std::fs::write(&tmp, r#"{"the"#).unwrap();
+        write_atomically(&path, br#"{"theme":"light"}"#)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            r#"{"theme":"light"}"#
+        );
+        assert!(!tmp.exists());
+
+        // Renaming over a non-empty directory fails after the temp file is written
+        let blocked = dir.path().join("blocked");
+        std::fs::create_dir(&blocked).unwrap();
+        std::fs::write(blocked.join("keep"), "original").unwrap();
+        assert!(write_atomically(&blocked, b"new").await.is_err());
+        assert_eq!(
+            std::fs::read_to_string(blocked.join("keep")).unwrap(),
+            "original"
+        );
+        assert!(!dir.path().join("blocked.vk-tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn backups_are_pruned_and_restorable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".claude.json");
+        let backups = dir.path().join("backups");
+
+        assert!(
+            backup_agent_config(&path, &backups)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let mut first = None;
+        for version in 0..7 {
+            std::fs::write(&path, format!("{{\"version\":{version}}}")).unwrap();
+            let backup = backup_agent_config(&path, &backups).await.unwrap().unwrap();
+            first.get_or_insert(backup.id);
+            tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        }
+        let listed = list_agent_config_backups(&backups).await.unwrap();
+        assert_eq!(listed.len(), MCP_BACKUPS_KEPT);
+        assert!(
+            listed
+                .iter()
+                .all(|backup| Some(&backup.id) != first.as_ref())
+        );
+
+        let oldest = &listed.last().unwrap().id;
+        assert!(restore_agent_config(&path, &backups, oldest).await.unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), r#"{"version":2}"#);
+        assert!(
+            !restore_agent_config(&path, &backups, "../.claude.json")
+                .await
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! Checks on MCP server entries before they are written to an agent's config
+//! file, so mistakes show up in settings instead of as an agent that silently
+//! runs without its servers.
+//!
+//! Entries are in the agent's own format, so both the common
+//! `command`/`args`/`env` shape and OpenCode's `command` array with
+//! `environment` are accepted.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    process::Stdio,
+    time::Duration,
+};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tokio::process::Command;
+use ts_rs::TS;
+use workspace_utils::shell::resolve_executable_path;
+
+/// How long a server gets to answer `--help` in a spawn check
+pub const SPAWN_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Keys that point a server at a remote endpoint instead of a local command
+const URL_KEYS: &[&str] = &["url", "httpUrl", "serverUrl"];
+
+/// Problems with each server entry, keyed by server name. Servers without
+/// problems are left out.
+pub fn validate_mcp_servers(
+    servers: &HashMap<String, Value>,
+    is_toml_config: bool,
+) -> BTreeMap<String, Vec<String>> {
+    servers
+        .iter()
+        .filter_map(|(name, server)| {
+            let problems = validate_server(server, is_toml_config);
+            (!problems.is_empty()).then(|| (name.clone(), problems))
+        })
+        .collect()
+}
+
+fn validate_server(server: &Value, is_toml_config: bool) -> Vec<String> {
+    let Some(server) = server.as_object() else {
+        return vec!["must be an object".to_string()];
+    };
+    let mut problems = Vec::new();
+
+    let url = URL_KEYS
+        .iter()
+        .find_map(|key| server.get(*key).map(|url| (*key, url)));
+    match (server.get("command"), url) {
+        (None, None) => problems.push(format!(
+            "needs a `command`, or a `{}` for remote servers",
+            URL_KEYS[0]
+        )),
+        (Some(Value::String(command)), _) if command.trim().is_empty() => {
+            problems.push("`command` is empty".to_string())
+        }
+        (Some(Value::String(_)), _) => {}
+        (Some(Value::Array(parts)), _) => {
+            if parts.is_empty() {
+                problems.push("`command` is empty".to_string());
+            } else if !parts.iter().all(Value::is_string) {
+                problems.push("`command` must be a list of strings".to_string());
+            }
+        }
+        (Some(_), _) => problems.push("`command` must be a string".to_string()),
+        (None, Some((key, Value::String(url)))) if url.trim().is_empty() => {
+            problems.push(format!("`{key}` is empty"))
+        }
+        (None, Some((_, Value::String(_)))) => {}
+        (None, Some((key, _))) => problems.push(format!("`{key}` must be a string")),
+    }
+
+    if let Some(args) = server.get("args")
+        && !args
+            .as_array()
+            .is_some_and(|args| args.iter().all(Value::is_string))
+    {
+        problems.push("`args` must be a list of strings".to_string());
+    }
+    for key in ["env", "environment", "headers"] {
+        if let Some(map) = server.get(key)
+            && !map
+                .as_object()
+                .is_some_and(|map| map.values().all(Value::is_string))
+        {
+            problems.push(format!("`{key}` must map names to string values"));
+        }
+    }
+    if is_toml_config && contains_null(server) {
+        problems.push("TOML has no null; remove keys set to null".to_string());
+    }
+    problems
+}
+
+fn contains_null(map: &Map<String, Value>) -> bool {
+    fn walk(value: &Value) -> bool {
+        match value {
+            Value::Null => true,
+            Value::Array(items) => items.iter().any(walk),
+            Value::Object(map) => map.values().any(walk),
+            _ => false,
+        }
+    }
+    map.values().any(walk)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(rename_all = "lowercase")]
+pub enum McpSpawnStatus {
+    Passed,
+    Failed,
+    /// Remote servers have nothing to launch
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct McpSpawnCheck {
+    pub status: McpSpawnStatus,
+    pub detail: Option<String>,
+}
+
+impl McpSpawnCheck {
+    fn new(status: McpSpawnStatus, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
+/// Launch each local server with `--help` and report whether it started.
+/// Servers should already have passed [`validate_mcp_servers`].
+pub async fn spawn_check_mcp_servers(
+    servers: &HashMap<String, Value>,
+    timeout: Duration,
+) -> BTreeMap<String, McpSpawnCheck> {
+    let checks = servers.iter().map(|(name, server)| async move {
+        (name.clone(), spawn_check_server(server, timeout).await)
+    });
+    futures::future::join_all(checks)
+        .await
+        .into_iter()
+        .collect()
+}
+
+async fn spawn_check_server(server: &Value, timeout: Duration) -> McpSpawnCheck {
+    let (program, mut args) = match server.get("command") {
+        Some(Value::String(command)) => (command.clone(), string_list(server.get("args"))),
+        Some(Value::Array(_)) => {
+            let mut parts = string_list(server.get("command"));
+            let program = parts.remove(0);
+            (program, parts)
+        }
+        _ => {
+            return McpSpawnCheck::new(McpSpawnStatus::Skipped, "Remote server, nothing to start");
+        }
+    };
+    args.push("--help".to_string());
+
+    let Some(resolved) = resolve_executable_path(&program).await else {
+        return McpSpawnCheck::new(
+            McpSpawnStatus::Failed,
+            format!("`{program}` not found in PATH"),
+        );
+    };
+    let env = server
+        .get("env")
+        .or_else(|| server.get("environment"))
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())));
+
+    let mut command = Command::new(resolved);
+    command
+        .args(&args)
+        .envs(env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    match tokio::time::timeout(timeout, command.output()).await {
+        Ok(Ok(output)) if output.status.success() => McpSpawnCheck {
+            status: McpSpawnStatus::Passed,
+            detail: None,
+        },
+        Ok(Ok(output)) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr
+                .lines()
+                .map(str::trim)
+                .rfind(|line| !line.is_empty())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("exited with {}", output.status));
+            McpSpawnCheck::new(McpSpawnStatus::Failed, reason)
+        }
+        Ok(Err(e)) => McpSpawnCheck::new(
+            McpSpawnStatus::Failed,
+            format!("`{program}` could not be started: {e}"),
+        ),
+        // Servers that ignore `--help` keep running; starting is what matters
+        Err(_) => McpSpawnCheck::new(
+            McpSpawnStatus::Passed,
+            format!("Still running after {}s", timeout.as_secs()),
+        ),
+    }
+}
+
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item.as_str().map(str::to_string))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn reports_problems_per_server() {
+        let servers = HashMap::from([
+            (
+                "ok".to_string(),
+                json!({ "command": "npx", "args": ["-y", "pkg"], "env": { "TOKEN": "x" } }),
+            ),
+            (
+                "remote".to_string(),
+                json!({ "type": "http", "url": "https://example.com/mcp" }),
+            ),
+            (
+                "opencode".to_string(),
+                json!({ "type": "local", "command": ["npx", "pkg"] }),
+            ),
+            (
+                "broken".to_string(),
+                json!({ "args": "-y pkg", "env": { "PORT": 3000 } }),
+            ),
+        ]);
+
+        let problems = validate_mcp_servers(&servers, false);
+        assert_eq!(problems.keys().collect::<Vec<_>>(), ["broken"]);
+        assert_eq!(
+            problems["broken"],
+            [
+                "needs a `command`, or a `url` for remote servers",
+                "`args` must be a list of strings",
+                "`env` must map names to string values",
+            ]
+        );
+    }
+
+    #[test]
+    fn nulls_are_rejected_only_for_toml_configs() {
+        let servers =
+            HashMap::from([("srv".to_string(), json!({ "command": "srv", "cwd": null }))]);
+        assert!(validate_mcp_servers(&servers, false).is_empty());
+        assert_eq!(validate_mcp_servers(&servers, true)["srv"].len(), 1);
+    }
+
+    #[tokio::test]
+    async fn spawn_check_reports_missing_commands_and_skips_remote_servers() {
+        let servers = HashMap::from([
+            (
+                "missing".to_string(),
+                json!({ "command": "no-such-mcp-server" }),
+            ),
+            (
+                "remote".to_string(),
+                json!({ "url": "https://example.com/mcp" }),
+            ),
+        ]);
+        let checks = spawn_check_mcp_servers(&servers, SPAWN_CHECK_TIMEOUT).await;
+        assert_eq!(checks["missing"].status, McpSpawnStatus::Failed);
+        assert_eq!(checks["remote"].status, McpSpawnStatus::Skipped);
+    }
+}
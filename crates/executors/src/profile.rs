@@ -50,6 +50,10 @@ pub enum ProfileError {
 lazy_static! {
     static ref EXECUTOR_PROFILES_CACHE: RwLock<ExecutorConfigs> =
         RwLock::new(ExecutorConfigs::load());
+    /// Arguments from the user's config appended to every command an
+    /// executor builds, whichever variant is used
+    static ref EXTRA_EXECUTOR_ARGS: RwLock<HashMap<BaseCodingAgent, Vec<String>>> =
+        RwLock::new(HashMap::new());
 }
 
 // New format default profiles (v3 - flattened)
@@ -114,6 +118,24 @@ impl std::fmt::Display for ExecutorProfileId {
     }
 }
 
+/// Parses the `EXECUTOR[:VARIANT]` form produced by `Display`.
+impl FromStr for ExecutorProfileId {
+    type Err = ProfileError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (executor, variant) = match s.trim().split_once(':') {
+            Some((executor, variant)) => (executor.trim(), Some(variant.trim())),
+            None => (s.trim(), None),
+        };
+        let executor = BaseCodingAgent::from_str(executor)
+            .map_err(|_| ProfileError::Validation(format!("Unknown executor '{executor}'")))?;
+        Ok(match variant.filter(|variant| !variant.is_empty()) {
+            Some(variant) => Self::with_variant(executor, variant.to_string()),
+            None => Self::new(executor),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub struct ExecutorConfig {
     #[serde(flatten)]
@@ -198,6 +220,11 @@ impl ExecutorConfigs {
         EXECUTOR_PROFILES_CACHE.read().unwrap().clone()
     }
 
+    /// Replace the extra arguments appended to each executor's commands.
+    pub fn set_extra_args(args: HashMap<BaseCodingAgent, Vec<String>>) {
+        *EXTRA_EXECUTOR_ARGS.write().unwrap() = args;
+    }
+
     /// Reload executor profiles cache
     pub fn reload() {
         let mut cache = EXECUTOR_PROFILES_CACHE.write().unwrap();
@@ -397,6 +424,16 @@ impl ExecutorConfigs {
                 )
             })
             .cloned()
+            .map(|mut agent| {
+                if let Some(extra) = EXTRA_EXECUTOR_ARGS
+                    .read()
+                    .unwrap()
+                    .get(&executor_profile_id.executor)
+                {
+                    agent.append_args(extra);
+                }
+                agent
+            })
     }
 
     pub fn get_coding_agent_or_default(
@@ -480,3 +517,25 @@ pub fn to_default_variant(id: &ExecutorProfileId) -> ExecutorProfileId {
         variant: None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn executor_profile_id_round_trips_through_display() {
+        for profile in [
+            ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+            ExecutorProfileId::with_variant(BaseCodingAgent::Codex, "HIGH".to_string()),
+        ] {
+            assert_eq!(
+                ExecutorProfileId::from_str(&profile.to_string()).unwrap(),
+                profile
+            );
+        }
+
+        let trimmed = ExecutorProfileId::from_str(" CLAUDE_CODE : ").unwrap();
+        assert_eq!(trimmed, ExecutorProfileId::new(BaseCodingAgent::ClaudeCode));
+        assert!(ExecutorProfileId::from_str("REMOVED_AGENT").is_err());
+    }
+}
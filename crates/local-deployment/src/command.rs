@@ -4,11 +4,22 @@ use nix::{
     sys::signal::{Signal, killpg},
     unistd::{Pid, getpgid},
 };
-use services::services::container::ContainerError;
-#[cfg(unix)]
+use services::services::container::{ContainerError, DEFAULT_STOP_GRACE};
 use tokio::time::Duration;
 
+#[cfg(unix)]
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), ContainerError> {
+    kill_process_group_with_grace(child, DEFAULT_STOP_GRACE).await
+}
+
+/// Interrupt the group and give it `grace` to wind down before escalating to
+/// SIGTERM and then SIGKILL.
+pub async fn kill_process_group_with_grace(
+    child: &mut AsyncGroupChild,
+    grace: Duration,
+) -> Result<(), ContainerError> {
     // hit the whole process group, not just the leader
     #[cfg(unix)]
     {
@@ -16,7 +27,11 @@ pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), Conta
             let pgid = getpgid(Some(Pid::from_raw(pid as i32)))
                 .map_err(|e| ContainerError::KillFailed(std::io::Error::other(e)))?;
 
-            for sig in [Signal::SIGINT, Signal::SIGTERM, Signal::SIGKILL] {
+            for (sig, wait) in [
+                (Signal::SIGINT, grace),
+                (Signal::SIGTERM, DEFAULT_STOP_GRACE),
+                (Signal::SIGKILL, DEFAULT_STOP_GRACE),
+            ] {
                 if let Err(e) = killpg(pgid, sig) {
                     tracing::warn!(
                         "Failed to send signal {:?} to process group {}: {}",
@@ -25,20 +40,62 @@ pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), Conta
                         e
                     );
                 }
-                tokio::time::sleep(Duration::from_secs(2)).await;
-                if child
-                    .inner()
-                    .try_wait()
-                    .map_err(ContainerError::Io)?
-                    .is_some()
-                {
+                if wait_for_exit(child, wait).await? {
                     break;
                 }
             }
         }
     }
+    #[cfg(not(unix))]
+    let _ = grace;
 
     let _ = child.kill().await;
     let _ = child.wait().await;
     Ok(())
 }
+
+#[cfg(unix)]
+async fn wait_for_exit(
+    child: &mut AsyncGroupChild,
+    timeout: Duration,
+) -> Result<bool, ContainerError> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if child
+            .inner()
+            .try_wait()
+            .map_err(ContainerError::Io)?
+            .is_some()
+        {
+            return Ok(true);
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return Ok(false);
+        }
+        tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::process::ExitStatusExt;
+
+    use command_group::AsyncCommandGroup;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn escalates_when_the_interrupt_is_ignored() {
+        let mut child = tokio::process::Command::new("sh")
+            .args(["-c", "trap '' INT; while :; do :; done"])
+            .group_spawn()
+            .unwrap();
+
+        kill_process_group_with_grace(&mut child, Duration::from_millis(200))
+            .await
+            .unwrap();
+
+        let status = child.inner().try_wait().unwrap().expect("still running");
+        assert_eq!(status.signal(), Some(Signal::SIGTERM as i32));
+    }
+}
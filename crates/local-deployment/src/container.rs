@@ -28,6 +28,7 @@ use deployment::{DeploymentError, RemoteClientNotConfigured};
 use executors::{
     actions::{Executable, ExecutorAction},
     approvals::{ExecutorApprovalService, NoopExecutorApprovalService},
+    env::ExecutionEnv,
     executors::{BaseCodingAgent, ExecutorExitResult, ExecutorExitSignal},
     logs::{
         NormalizedEntryType,
@@ -43,14 +44,23 @@ use services::services::{
     analytics::AnalyticsContext,
     approvals::{Approvals, executor_approvals::ExecutorApprovalBridge},
     config::Config,
-    container::{ContainerError, ContainerRef, ContainerService},
+    container::{ContainerError, ContainerRef, ContainerService, record_process_event},
+    dev_server::{self, DevServerExit, DevServerRegistry},
     diff_stream::{self, DiffStreamHandle},
+    disk_usage::DiskUsageMonitor,
     git::{Commit, DiffTarget, GitService},
     image::ImageService,
+    log_batch::LogBatchConfig,
     share::SharePublisher,
+    shutdown::ShutdownState,
+    stall, task_trash, verify,
+    worktree_cleanup::{self, WorktreeCleanupOptions},
     worktree_manager::{WorktreeCleanup, WorktreeManager},
 };
-use tokio::{sync::RwLock, task::JoinHandle};
+use tokio::{
+    sync::{Mutex, RwLock},
+    task::JoinHandle,
+};
 use tokio_util::io::ReaderStream;
 use utils::{
     log_msg::LogMsg,
@@ -59,13 +69,21 @@ use utils::{
 };
 use uuid::Uuid;
 
-use crate::command;
+use crate::{command, resource_usage::ResourceSampler};
+
+/// How long to wait for a process's pipes to drain after it has exited.
+const OUTPUT_FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Clone)]
 pub struct LocalContainerService {
     db: DBService,
     child_store: Arc<RwLock<HashMap<Uuid, Arc<RwLock<AsyncGroupChild>>>>>,
     msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
+    /// Tasks copying each running process's stdout/stderr into its MsgStore
+    output_forwarders: Arc<RwLock<HashMap<Uuid, JoinHandle<()>>>>,
+    execution_queue_lock: Arc<Mutex<()>>,
+    dev_servers: DevServerRegistry,
+    shutdown: ShutdownState,
     config: Arc<RwLock<Config>>,
     git: GitService,
     image_service: ImageService,
@@ -92,6 +110,10 @@ impl LocalContainerService {
             db,
             child_store,
             msg_stores,
+            output_forwarders: Arc::new(RwLock::new(HashMap::new())),
+            execution_queue_lock: Arc::new(Mutex::new(())),
+            dev_servers: DevServerRegistry::default(),
+            shutdown: ShutdownState::default(),
             config,
             git,
             image_service,
@@ -101,10 +123,25 @@ impl LocalContainerService {
         };
 
         container.spawn_worktree_cleanup().await;
+        container.spawn_worktree_disk_usage_scan();
 
         container
     }
 
+    /// Wait for a finished process's remaining stdout/stderr to reach its
+    /// MsgStore. The pipes close once the whole process group is gone.
+    async fn await_output_flushed(&self, id: &Uuid) {
+        let Some(forwarder) = self.output_forwarders.write().await.remove(id) else {
+            return;
+        };
+        if tokio::time::timeout(OUTPUT_FLUSH_TIMEOUT, forwarder)
+            .await
+            .is_err()
+        {
+            tracing::warn!("Output of execution {} was still streaming after exit", id);
+        }
+    }
+
     pub async fn get_child_from_store(&self, id: &Uuid) -> Option<Arc<RwLock<AsyncGroupChild>>> {
         let map = self.child_store.read().await;
         map.get(id).cloned()
@@ -215,50 +252,10 @@ impl LocalContainerService {
         }
     }
 
-    pub async fn cleanup_expired_attempt(
-        db: &DBService,
-        attempt_id: Uuid,
-        worktree_path: PathBuf,
-        git_repo_path: PathBuf,
-    ) -> Result<(), DeploymentError> {
-        WorktreeManager::cleanup_worktree(&WorktreeCleanup::new(
-            worktree_path,
-            Some(git_repo_path),
-        ))
-        .await?;
-        // Mark worktree as deleted in database after successful cleanup
-        TaskAttempt::mark_worktree_deleted(&db.pool, attempt_id).await?;
-        tracing::info!("Successfully marked worktree as deleted for attempt {attempt_id}",);
-        Ok(())
-    }
-
-    pub async fn cleanup_expired_attempts(db: &DBService) -> Result<(), DeploymentError> {
-        let expired_attempts = TaskAttempt::find_expired_for_cleanup(&db.pool).await?;
-        if expired_attempts.is_empty() {
-            tracing::debug!("No expired worktrees found");
-            return Ok(());
-        }
-        tracing::info!(
-            "Found {} expired worktrees to clean up",
-            expired_attempts.len()
-        );
-        for (attempt_id, worktree_path, git_repo_path) in expired_attempts {
-            Self::cleanup_expired_attempt(
-                db,
-                attempt_id,
-                PathBuf::from(worktree_path),
-                PathBuf::from(git_repo_path),
-            )
-            .await
-            .unwrap_or_else(|e| {
-                tracing::error!("Failed to clean up expired attempt {attempt_id}: {e}",);
-            });
-        }
-        Ok(())
-    }
-
     pub async fn spawn_worktree_cleanup(&self) {
         let db = self.db.clone();
+        let git = self.git.clone();
+        let config = self.config.clone();
         let mut cleanup_interval = tokio::time::interval(tokio::time::Duration::from_secs(1800)); // 30 minutes
         self.cleanup_orphaned_worktrees().await;
         tokio::spawn(async move {
@@ -270,11 +267,71 @@ impl LocalContainerService {
                     .unwrap_or_else(|e| {
                         tracing::error!("Failed to check externally deleted worktrees: {}", e);
                     });
-                Self::cleanup_expired_attempts(&db)
+                let (branch_prefix, retention_hours, trash_retention_days) = {
+                    let config = config.read().await;
+                    (
+                        config.git_branch_prefix.clone(),
+                        config.worktree_retention_hours,
+                        config.task_trash_retention_days,
+                    )
+                };
+                // Purge first, so the sweep below also takes the branches of
+                // the purged attempts
+                if trash_retention_days > 0 {
+                    match task_trash::purge_expired_tasks(
+                        &db.pool,
+                        chrono::Duration::days(i64::from(trash_retention_days)),
+                        chrono::Utc::now(),
+                    )
                     .await
-                    .unwrap_or_else(|e| {
-                        tracing::error!("Failed to clean up expired worktree attempts: {}", e)
-                    });
+                    {
+                        Ok(worktrees) => {
+                            if let Err(e) =
+                                WorktreeManager::batch_cleanup_worktrees(&worktrees).await
+                            {
+                                tracing::error!(
+                                    "Failed to remove worktrees of purged tasks: {}",
+                                    e
+                                );
+                            }
+                        }
+                        Err(e) => tracing::error!("Failed to purge deleted tasks: {}", e),
+                    }
+                }
+                match worktree_cleanup::cleanup_all_projects(
+                    &db,
+                    &git,
+                    &branch_prefix,
+                    WorktreeCleanupOptions::new(retention_hours, false),
+                )
+                .await
+                {
+                    Ok(report) => tracing::debug!(
+                        "Worktree cleanup removed {} and skipped {} items",
+                        report.removed.len(),
+                        report.skipped.len()
+                    ),
+                    Err(e) => tracing::error!("Failed to clean up idle worktrees: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Periodically measure worktree disk usage. Projects are scanned one at
+    /// a time, starting a few minutes after launch.
+    fn spawn_worktree_disk_usage_scan(&self) {
+        let db = self.db.clone();
+        let config = self.config.clone();
+        let period = tokio::time::Duration::from_secs(30 * 60);
+        let start = tokio::time::Instant::now() + tokio::time::Duration::from_secs(5 * 60);
+        tokio::spawn(async move {
+            let mut scan_interval = tokio::time::interval_at(start, period);
+            scan_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut monitor = DiskUsageMonitor::default();
+            loop {
+                scan_interval.tick().await;
+                let config = config.read().await.clone();
+                monitor.scan_all_projects(&db, &config).await;
             }
         });
     }
@@ -285,6 +342,7 @@ impl LocalContainerService {
         &self,
         exec_id: &Uuid,
         exit_signal: Option<ExecutorExitSignal>,
+        sampler: Option<ResourceSampler>,
     ) -> JoinHandle<()> {
         let exec_id = *exec_id;
         let child_store = self.child_store.clone();
@@ -303,6 +361,7 @@ impl LocalContainerService {
                 .unwrap_or_else(|| std::future::pending().boxed()); // no signal, stall forever
 
             let status_result: std::io::Result<std::process::ExitStatus>;
+            let mut timed_out = false;
 
             // Wait for process to exit, or exit signal from executor
             tokio::select! {
@@ -322,6 +381,10 @@ impl LocalContainerService {
                     status_result = match exit_result {
                         Ok(ExecutorExitResult::Success) => Ok(success_exit_status()),
                         Ok(ExecutorExitResult::Failure) => Ok(failure_exit_status()),
+                        Ok(ExecutorExitResult::TimedOut) => {
+                            timed_out = true;
+                            Ok(failure_exit_status())
+                        }
                         Err(_) => Ok(success_exit_status()), // Channel closed, assume success
                     };
                 }
@@ -344,19 +407,79 @@ impl LocalContainerService {
                 Err(_) => (None, ExecutionProcessStatus::Failed),
             };
 
-            if !ExecutionProcess::was_stopped(&db.pool, exec_id).await
-                && let Err(e) =
-                    ExecutionProcess::update_completion(&db.pool, exec_id, status, exit_code).await
+            // Stopped processes had their outcome recorded when they were stopped
+            let stopped = ExecutionProcess::was_stopped(&db.pool, exec_id).await;
+            if !stopped
+                && let Err(e) = ExecutionProcess::update_completion(
+                    &db.pool,
+                    exec_id,
+                    status.clone(),
+                    exit_code,
+                )
+                .await
             {
                 tracing::error!("Failed to update execution process completion: {}", e);
             }
 
-            if let Ok(ctx) = ExecutionProcess::load_context(&db.pool, exec_id).await {
+            if let Some(sampler) = sampler {
+                let usage = sampler.finish().await;
+                if let Err(e) = ExecutionProcess::record_resource_usage(
+                    &db.pool,
+                    exec_id,
+                    usage.cpu_time_ms,
+                    usage.peak_rss_bytes,
+                )
+                .await
+                {
+                    tracing::warn!("Failed to record resource usage for {}: {}", exec_id, e);
+                }
+            }
+
+            if let Ok(mut ctx) = ExecutionProcess::load_context(&db.pool, exec_id).await {
+                if !stopped {
+                    record_process_event(&db.pool, &ctx.execution_process, status, exit_code).await;
+                    if ctx.execution_process.run_reason == ExecutionProcessRunReason::DevServer {
+                        container.supervise_dev_server_exit(&ctx, exit_code);
+                    }
+                }
+                if matches!(
+                    ctx.execution_process.run_reason,
+                    ExecutionProcessRunReason::VerifyScript
+                ) {
+                    let verify_status = verify::verify_status(exit_code, timed_out);
+                    let msg_store = msg_stores.read().await.get(&exec_id).cloned();
+                    if let Err(e) = verify::record_result(
+                        &db.pool,
+                        ctx.task_attempt.id,
+                        verify_status,
+                        msg_store.as_deref(),
+                    )
+                    .await
+                    {
+                        tracing::error!("Failed to record verify result: {}", e);
+                    }
+                    ctx.task_attempt.verify_status = Some(verify_status);
+                }
+
                 // Update executor session summary if available
                 if let Err(e) = container.update_executor_session_summary(&exec_id).await {
                     tracing::warn!("Failed to update executor session summary: {}", e);
                 }
 
+                if let Err(e) = container
+                    .record_execution_usage(&ctx.execution_process)
+                    .await
+                {
+                    tracing::warn!("Failed to record token usage for {}: {}", exec_id, e);
+                }
+
+                if let Err(e) = container
+                    .record_execution_plan(&ctx.execution_process)
+                    .await
+                {
+                    tracing::warn!("Failed to record plan for {}: {}", exec_id, e);
+                }
+
                 let success = matches!(
                     ctx.execution_process.status,
                     ExecutionProcessStatus::Completed
@@ -370,7 +493,14 @@ impl LocalContainerService {
                     ExecutionProcessStatus::Running
                 );
 
-                if success || cleanup_done {
+                // A failing verify script still hands over to cleanup
+                let verify_failed =
+                    matches!(
+                        ctx.execution_process.run_reason,
+                        ExecutionProcessRunReason::VerifyScript
+                    ) && matches!(ctx.execution_process.status, ExecutionProcessStatus::Failed);
+
+                if success || cleanup_done || verify_failed {
                     // Commit changes (if any) and get feedback about whether changes were made
                     let changes_committed = match container.try_commit_changes(&ctx).await {
                         Ok(committed) => committed,
@@ -420,6 +550,14 @@ impl LocalContainerService {
                             e
                         );
                     }
+                    // A queued follow-up draft goes first; this waits for it
+                    if let Err(e) = container.dispatch_queued_followup(&ctx.task_attempt).await {
+                        tracing::error!(
+                            "Failed to dispatch queued follow-up for attempt {}: {}",
+                            ctx.task_attempt.id,
+                            e
+                        );
+                    }
                 }
 
                 // Fire analytics event when CodingAgent execution has finished
@@ -454,6 +592,7 @@ impl LocalContainerService {
             }
 
             // Cleanup msg store
+            container.await_output_flushed(&exec_id).await;
             if let Some(msg_arc) = msg_stores.write().await.remove(&exec_id) {
                 msg_arc.push_finished();
                 tokio::time::sleep(Duration::from_millis(50)).await; // Wait for the finish message to propogate
@@ -469,9 +608,69 @@ impl LocalContainerService {
 
             // Cleanup child handle
             child_store.write().await.remove(&exec_id);
+
+            // The attempt may have given up an execution slot
+            if let Err(e) = container.start_queued_attempts().await {
+                tracing::error!("Failed to start queued task attempts: {}", e);
+            }
         })
     }
 
+    /// Restart a dev server that exited on its own, backing off between
+    /// attempts until the crash loop limit is reached.
+    fn supervise_dev_server_exit(&self, ctx: &ExecutionContext, exit_code: Option<i64>) {
+        let project_id = ctx.task.project_id;
+        let exec_id = ctx.execution_process.id;
+        let ran_for = dev_server::running_time(&ctx.execution_process);
+        let delay = match self
+            .dev_servers
+            .exited(project_id, exec_id, exit_code, ran_for)
+        {
+            DevServerExit::Restart(delay) => delay,
+            DevServerExit::GiveUp => {
+                tracing::warn!(
+                    "Dev server for project {} keeps exiting; not restarting it again",
+                    project_id
+                );
+                return;
+            }
+            DevServerExit::Ignore => return,
+        };
+
+        tracing::info!(
+            "Dev server {} for project {} exited with {:?}; restarting in {:?}",
+            exec_id,
+            project_id,
+            exit_code,
+            delay
+        );
+        let container = self.clone();
+        let task_attempt = ctx.task_attempt.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            if !container.dev_servers.restart_pending(project_id, exec_id) {
+                return;
+            }
+            let restarted = match Project::find_by_id(&container.db.pool, project_id).await {
+                Ok(Some(project)) => container
+                    .start_dev_server(&task_attempt, &project, true)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                Ok(None) => Err("project no longer exists".to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            if let Err(e) = restarted {
+                tracing::error!(
+                    "Failed to restart dev server for project {}: {}",
+                    project_id,
+                    e
+                );
+                container.dev_servers.restart_failed(project_id);
+            }
+        });
+    }
+
     pub fn spawn_os_exit_watcher(
         &self,
         exec_id: Uuid,
@@ -532,7 +731,8 @@ impl LocalContainerService {
 
         // Merge and forward into the store
         let merged = select(out, err); // Stream<Item = Result<LogMsg, io::Error>>
-        store.clone().spawn_forwarder(merged);
+        let forwarder = store.clone().spawn_forwarder(merged);
+        self.output_forwarders.write().await.insert(id, forwarder);
 
         let mut map = self.msg_stores().write().await;
         map.insert(id, store);
@@ -760,12 +960,12 @@ impl LocalContainerService {
             variant: draft.variant.clone(),
         };
 
-        // Prepare cleanup action
-        let cleanup_action = ctx
+        // Prepare verify and cleanup actions
+        let post_agent_action = ctx
             .task
             .parent_project(&self.db.pool)
             .await?
-            .and_then(|project| self.cleanup_action(project.cleanup_script));
+            .and_then(|project| self.post_agent_action(&project));
 
         // Handle images: associate, copy to worktree, canonicalize prompt
         let mut prompt = draft.prompt.clone();
@@ -790,12 +990,13 @@ impl LocalContainerService {
             executors::actions::coding_agent_follow_up::CodingAgentFollowUpRequest {
                 prompt,
                 session_id,
+                task_context: Some(ctx.task.to_prompt()),
                 executor_profile_id,
             };
 
         let follow_up_action = executors::actions::ExecutorAction::new(
             executors::actions::ExecutorActionType::CodingAgentFollowUpRequest(follow_up_request),
-            cleanup_action,
+            post_agent_action,
         );
 
         // Start the execution
@@ -850,9 +1051,34 @@ impl ContainerService for LocalContainerService {
         self.config.read().await.git_branch_prefix.clone()
     }
 
+    async fn log_batch_config(&self) -> LogBatchConfig {
+        self.config.read().await.log_batching
+    }
+
+    async fn max_concurrent_executions(&self) -> u32 {
+        self.config.read().await.max_concurrent_executions
+    }
+
+    fn execution_queue_lock(&self) -> &Mutex<()> {
+        &self.execution_queue_lock
+    }
+
     fn task_attempt_to_current_dir(&self, task_attempt: &TaskAttempt) -> PathBuf {
         PathBuf::from(task_attempt.container_ref.clone().unwrap_or_default())
     }
+
+    fn dev_servers(&self) -> &DevServerRegistry {
+        &self.dev_servers
+    }
+
+    fn shutdown_state(&self) -> &ShutdownState {
+        &self.shutdown
+    }
+
+    async fn execution_pid(&self, execution_process_id: &Uuid) -> Option<u32> {
+        let child = self.get_child_from_store(execution_process_id).await?;
+        child.read().await.id()
+    }
     /// Create a container
     async fn create(&self, task_attempt: &TaskAttempt) -> Result<ContainerRef, ContainerError> {
         let task = task_attempt
@@ -1009,10 +1235,26 @@ impl ContainerService for LocalContainerService {
                 _ => Arc::new(NoopExecutorApprovalService {}),
             };
 
+        // Setup scripts, agents and follow-ups all see the same project env
+        let task = task_attempt
+            .parent_task(&self.db.pool)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+        let env = ExecutionEnv::from_project_vars(
+            &Project::find_env(&self.db.pool, task.project_id).await?,
+        );
+        if !env.is_empty() {
+            tracing::debug!(
+                "Spawning execution {} with project env {:?}",
+                execution_process.id,
+                env.masked()
+            );
+        }
+
         // Create the child and stream, add to execution tracker with timeout
         let mut spawned = tokio::time::timeout(
             Duration::from_secs(30),
-            executor_action.spawn(&current_dir, approvals_service),
+            executor_action.spawn(&current_dir, approvals_service, &env),
         )
         .await
         .map_err(|_| {
@@ -1024,19 +1266,40 @@ impl ContainerService for LocalContainerService {
         self.track_child_msgs_in_store(execution_process.id, &mut spawned.child)
             .await;
 
+        let (stall_minutes, notifications) = {
+            let config = self.config.read().await;
+            (
+                config.executor_stall_timeout_minutes,
+                config.notifications.clone(),
+            )
+        };
+        if let Some(timeout) = stall::stall_timeout(executor_action, stall_minutes)
+            && let Some(store) = self.get_msg_store_by_id(&execution_process.id).await
+        {
+            stall::spawn_stall_watcher(
+                self.db.clone(),
+                execution_process.id,
+                store,
+                timeout,
+                notifications,
+            );
+        }
+
+        let sampler = spawned.child.id().map(ResourceSampler::spawn);
         self.add_child_to_store(execution_process.id, spawned.child)
             .await;
 
         // Spawn unified exit monitor: watches OS exit and optional executor signal
-        let _hn = self.spawn_exit_monitor(&execution_process.id, spawned.exit_signal);
+        let _hn = self.spawn_exit_monitor(&execution_process.id, spawned.exit_signal, sampler);
 
         Ok(())
     }
 
-    async fn stop_execution(
+    async fn stop_execution_with_grace(
         &self,
         execution_process: &ExecutionProcess,
         status: ExecutionProcessStatus,
+        grace: Duration,
     ) -> Result<(), ContainerError> {
         let child = self
             .get_child_from_store(&execution_process.id)
@@ -1050,13 +1313,19 @@ impl ContainerService for LocalContainerService {
             None
         };
 
-        ExecutionProcess::update_completion(&self.db.pool, execution_process.id, status, exit_code)
-            .await?;
+        ExecutionProcess::update_completion(
+            &self.db.pool,
+            execution_process.id,
+            status.clone(),
+            exit_code,
+        )
+        .await?;
+        record_process_event(&self.db.pool, execution_process, status, exit_code).await;
 
         // Kill the child process and remove from the store
         {
             let mut child_guard = child.write().await;
-            if let Err(e) = command::kill_process_group(&mut child_guard).await {
+            if let Err(e) = command::kill_process_group_with_grace(&mut child_guard, grace).await {
                 tracing::error!(
                     "Failed to stop execution process {}: {}",
                     execution_process.id,
@@ -1067,7 +1336,8 @@ impl ContainerService for LocalContainerService {
         }
         self.remove_child_from_store(&execution_process.id).await;
 
-        // Mark the process finished in the MsgStore
+        // Mark the process finished in the MsgStore once its last output is in
+        self.await_output_flushed(&execution_process.id).await;
         if let Some(msg) = self.msg_stores.write().await.remove(&execution_process.id) {
             msg.push_finished();
         }
@@ -1115,6 +1385,10 @@ impl ContainerService for LocalContainerService {
             }
         }
 
+        if let Err(e) = self.start_queued_attempts().await {
+            tracing::error!("Failed to start queued task attempts: {}", e);
+        }
+
         Ok(())
     }
 
@@ -1272,26 +1546,6 @@ impl ContainerService for LocalContainerService {
         }
         Ok(())
     }
-
-    async fn kill_all_running_processes(&self) -> Result<(), ContainerError> {
-        tracing::info!("Killing all running processes");
-        let running_processes = ExecutionProcess::find_running(&self.db.pool).await?;
-
-        for process in running_processes {
-            if let Err(error) = self
-                .stop_execution(&process, ExecutionProcessStatus::Killed)
-                .await
-            {
-                tracing::error!(
-                    "Failed to cleanly kill running execution process {:?}: {:?}",
-                    process,
-                    error
-                );
-            }
-        }
-
-        Ok(())
-    }
 }
 
 fn success_exit_status() -> std::process::ExitStatus {
@@ -1,14 +1,21 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
-use db::DBService;
+use chrono::Utc;
+use db::{
+    DBService,
+    models::oauth_handoff::{HandoffProgress, HandoffRedemption, OAuthHandoff},
+};
 use deployment::{Deployment, DeploymentError, RemoteClientNotConfigured};
 use executors::profile::ExecutorConfigs;
 use services::services::{
     analytics::{AnalyticsConfig, AnalyticsContext, AnalyticsService, generate_user_id},
     approvals::Approvals,
     auth::AuthContext,
-    config::{Config, load_config_from_file, save_config_to_file},
+    config::{
+        Config, load_config_from_file, reload::ConfigReloader, resolve_executor_args,
+        save_config_to_file,
+    },
     container::ContainerService,
     drafts::DraftsService,
     events::EventService,
@@ -16,25 +23,30 @@ use services::services::{
     filesystem::FilesystemService,
     git::GitService,
     image::ImageService,
+    mcp_config_watcher::McpConfigWatcher,
     oauth_credentials::OAuthCredentials,
     remote_client::{RemoteClient, RemoteClientError},
-    share::{RemoteSyncHandle, ShareConfig, SharePublisher},
+    share::{RemoteSyncHandle, ShareConfig, SharePublisher, load_or_create_device_id},
 };
 use tokio::sync::{Mutex, RwLock};
 use utils::{
     api::oauth::LoginStatus,
-    assets::{config_path, credentials_path},
+    assets::{config_path, credentials_path, device_id_path},
     msg_store::MsgStore,
+    network::{NetworkConfig, NetworkConfigError},
 };
 use uuid::Uuid;
 
 use crate::container::LocalContainerService;
 mod command;
 pub mod container;
+mod resource_usage;
 
 #[derive(Clone)]
 pub struct LocalDeployment {
     config: Arc<RwLock<Config>>,
+    config_reloader: ConfigReloader,
+    mcp_config_watcher: McpConfigWatcher,
     user_id: String,
     db: DBService,
     analytics: Option<AnalyticsService>,
@@ -51,19 +63,19 @@ pub struct LocalDeployment {
     share_config: Option<ShareConfig>,
     remote_client: Result<RemoteClient, RemoteClientNotConfigured>,
     auth_context: AuthContext,
-    oauth_handoffs: Arc<RwLock<HashMap<Uuid, PendingHandoff>>>,
 }
 
-#[derive(Debug, Clone)]
-struct PendingHandoff {
-    provider: String,
-    app_verifier: String,
-}
+/// How often expired OAuth handoffs are deleted, besides once at startup
+const OAUTH_HANDOFF_PURGE_INTERVAL: Duration = Duration::from_secs(5 * 60);
 
 #[async_trait]
 impl Deployment for LocalDeployment {
     async fn new() -> Result<Self, DeploymentError> {
         let mut raw_config = load_config_from_file(&config_path()).await;
+        match resolve_executor_args(&raw_config) {
+            Ok(extra_args) => ExecutorConfigs::set_extra_args(extra_args),
+            Err(e) => tracing::warn!("Ignoring executor_args from config: {}", e),
+        }
 
         let profiles = ExecutorConfigs::get_cached();
         if !raw_config.onboarding_acknowledged
@@ -88,6 +100,8 @@ impl Deployment for LocalDeployment {
         save_config_to_file(&raw_config, &config_path()).await?;
 
         let config = Arc::new(RwLock::new(raw_config));
+        let config_reloader = ConfigReloader::new(config.clone(), config_path());
+        let mcp_config_watcher = McpConfigWatcher::new(config.clone());
         let user_id = generate_user_id();
         let analytics = AnalyticsConfig::new().map(AnalyticsService::new);
         let git = GitService::new();
@@ -121,7 +135,18 @@ impl Deployment for LocalDeployment {
 
         let approvals = Approvals::new(msg_stores.clone());
 
-        let share_config = ShareConfig::from_env();
+        // A bad certificate path should stop startup, not surface later as
+        // a TLS failure
+        let network = NetworkConfig::from_env()?;
+        let share_config = ShareConfig::from_env(network.clone()).map(|config| {
+            match load_or_create_device_id(&device_id_path()) {
+                Ok(device_id) => config.with_device_id(device_id),
+                Err(e) => {
+                    tracing::warn!(?e, "failed to load device id; cursor will not be kept");
+                    config
+                }
+            }
+        });
 
         let oauth_credentials = Arc::new(OAuthCredentials::new(credentials_path()));
         if let Err(e) = oauth_credentials.load().await {
@@ -136,11 +161,15 @@ impl Deployment for LocalDeployment {
             .or_else(|| option_env!("VK_SHARED_API_BASE").map(|s| s.to_string()));
 
         let remote_client = match api_base {
-            Some(url) => match RemoteClient::new(&url, auth_context.clone()) {
+            Some(url) => match RemoteClient::with_network(&url, auth_context.clone(), &network) {
                 Ok(client) => {
                     tracing::info!("Remote client initialized with URL: {}", url);
                     Ok(client)
                 }
+                // The HTTP client rejected the proxy or a certificate
+                Err(RemoteClientError::Transport(message)) => {
+                    return Err(NetworkConfigError::Client(message).into());
+                }
                 Err(e) => {
                     tracing::error!(?e, "failed to create remote client");
                     Err(RemoteClientNotConfigured)
@@ -157,14 +186,27 @@ impl Deployment for LocalDeployment {
             .map(|client| SharePublisher::new(db.clone(), client.clone()))
             .map_err(|e| *e);
 
-        let oauth_handoffs = Arc::new(RwLock::new(HashMap::new()));
+        {
+            let pool = db.pool.clone();
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(OAUTH_HANDOFF_PURGE_INTERVAL);
+                loop {
+                    ticker.tick().await;
+                    match OAuthHandoff::purge_expired(&pool, Utc::now()).await {
+                        Ok(0) => {}
+                        Ok(purged) => tracing::debug!(purged, "purged expired OAuth handoffs"),
+                        Err(e) => tracing::warn!(?e, "failed to purge expired OAuth handoffs"),
+                    }
+                }
+            });
+        }
         let share_sync_handle = Arc::new(Mutex::new(None));
 
-        let mut share_sync_config: Option<ShareConfig> = None;
-        if let (Some(sc_ref), Ok(_)) = (share_config.as_ref(), &share_publisher)
+        let mut share_sync_config: Option<(ShareConfig, RemoteClient)> = None;
+        if let (Some(sc_ref), Ok(client)) = (share_config.as_ref(), &remote_client)
             && oauth_credentials.get().await.is_some()
         {
-            share_sync_config = Some(sc_ref.clone());
+            share_sync_config = Some((sc_ref.clone(), client.clone()));
         }
 
         // We need to make analytics accessible to the ContainerService
@@ -192,6 +234,8 @@ impl Deployment for LocalDeployment {
 
         let deployment = Self {
             config,
+            config_reloader,
+            mcp_config_watcher,
             user_id,
             db,
             analytics,
@@ -208,11 +252,10 @@ impl Deployment for LocalDeployment {
             share_config: share_config.clone(),
             remote_client,
             auth_context,
-            oauth_handoffs,
         };
 
-        if let Some(sc) = share_sync_config {
-            deployment.spawn_remote_sync(sc);
+        if let Some((sc, client)) = share_sync_config {
+            deployment.spawn_remote_sync(sc, client);
         }
 
         Ok(deployment)
@@ -226,6 +269,14 @@ impl Deployment for LocalDeployment {
         &self.config
     }
 
+    fn config_reloader(&self) -> &ConfigReloader {
+        &self.config_reloader
+    }
+
+    fn mcp_config_watcher(&self) -> &McpConfigWatcher {
+        &self.mcp_config_watcher
+    }
+
     fn db(&self) -> &DBService {
         &self.db
     }
@@ -317,24 +368,34 @@ impl LocalDeployment {
     pub async fn store_oauth_handoff(
         &self,
         handoff_id: Uuid,
-        provider: String,
-        app_verifier: String,
-    ) {
-        self.oauth_handoffs.write().await.insert(
-            handoff_id,
-            PendingHandoff {
-                provider,
-                app_verifier,
-            },
-        );
+        provider: &str,
+        app_verifier: &str,
+    ) -> Result<(), sqlx::Error> {
+        OAuthHandoff::create(&self.db.pool, handoff_id, provider, app_verifier).await
+    }
+
+    /// Claim a handoff's verifier for its callback. Each handoff can be
+    /// taken once, within ten minutes of being stored.
+    pub async fn take_oauth_handoff(
+        &self,
+        handoff_id: Uuid,
+    ) -> Result<HandoffRedemption, sqlx::Error> {
+        OAuthHandoff::take(&self.db.pool, handoff_id, Utc::now()).await
     }
 
-    pub async fn take_oauth_handoff(&self, handoff_id: &Uuid) -> Option<(String, String)> {
-        self.oauth_handoffs
-            .write()
-            .await
-            .remove(handoff_id)
-            .map(|state| (state.provider, state.app_verifier))
+    pub async fn finish_oauth_handoff(
+        &self,
+        handoff_id: Uuid,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        OAuthHandoff::finish(&self.db.pool, handoff_id, error).await
+    }
+
+    pub async fn oauth_handoff_progress(
+        &self,
+        handoff_id: Uuid,
+    ) -> Result<HandoffProgress, sqlx::Error> {
+        OAuthHandoff::progress(&self.db.pool, handoff_id, Utc::now()).await
     }
 
     pub fn share_config(&self) -> Option<&ShareConfig> {
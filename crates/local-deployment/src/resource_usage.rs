@@ -0,0 +1,210 @@
+//! CPU time and peak memory of running execution processes.
+//!
+//! Only the direct child is measured. Processes it starts in turn, such as
+//! the commands an agent runs or the program a wrapper shell launches, are
+//! not counted. Platforms without a supported query, and failed queries,
+//! leave the values unset rather than failing the run.
+
+use std::time::Duration;
+
+use tokio::{sync::oneshot, task::JoinHandle};
+
+/// How often a process is sampled
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ProcessSample {
+    /// User and system CPU time so far
+    cpu_time_ms: u64,
+    /// Resident memory; on Linux the high-water mark
+    rss_bytes: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceUsage {
+    pub cpu_time_ms: Option<i64>,
+    pub peak_rss_bytes: Option<i64>,
+}
+
+impl ResourceUsage {
+    fn record(&mut self, sample: ProcessSample) {
+        let cpu_time_ms = i64::try_from(sample.cpu_time_ms).unwrap_or(i64::MAX);
+        let rss_bytes = i64::try_from(sample.rss_bytes).unwrap_or(i64::MAX);
+        self.cpu_time_ms = self.cpu_time_ms.max(Some(cpu_time_ms));
+        self.peak_rss_bytes = self.peak_rss_bytes.max(Some(rss_bytes));
+    }
+}
+
+/// Samples one process until it exits or [`ResourceSampler::finish`] is
+/// called.
+pub struct ResourceSampler {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<ResourceUsage>,
+}
+
+impl ResourceSampler {
+    pub fn spawn(pid: u32) -> Self {
+        let (stop, mut stop_rx) = oneshot::channel();
+        let task = tokio::spawn(async move {
+            let mut usage = ResourceUsage::default();
+            let mut ticker = tokio::time::interval(SAMPLE_INTERVAL);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = &mut stop_rx => break,
+                }
+                // Gone, or not measurable on this platform
+                let Some(sample) = sample_process(pid) else {
+                    break;
+                };
+                usage.record(sample);
+            }
+            usage
+        });
+        Self { stop, task }
+    }
+
+    /// Stop sampling and return what was measured.
+    pub async fn finish(self) -> ResourceUsage {
+        let _ = self.stop.send(());
+        self.task.await.unwrap_or_default()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn sample_process(pid: u32) -> Option<ProcessSample> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    let ticks = cpu_ticks_from_stat(&stat)?;
+    // SAFETY: sysconf has no preconditions
+    let ticks_per_sec = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    let ticks_per_sec = u64::try_from(ticks_per_sec).ok().filter(|t| *t > 0)?;
+
+    // Absent for zombies; the CPU time is still worth keeping
+    let rss_bytes = std::fs::read_to_string(format!("/proc/{pid}/status"))
+        .ok()
+        .and_then(|status| rss_kib_from_status(&status))
+        .unwrap_or(0)
+        * 1024;
+    Some(ProcessSample {
+        cpu_time_ms: ticks * 1000 / ticks_per_sec,
+        rss_bytes,
+    })
+}
+
+/// `utime + stime` from `/proc/<pid>/stat`. The command name may contain
+/// spaces and parentheses, so fields are counted from its closing paren.
+#[cfg(any(target_os = "linux", test))]
+fn cpu_ticks_from_stat(stat: &str) -> Option<u64> {
+    let (_, fields) = stat.rsplit_once(')')?;
+    let mut fields = fields.split_whitespace();
+    // Fields 14 and 15, counting from the pid; the state is field 3
+    let utime: u64 = fields.nth(11)?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    Some(utime + stime)
+}
+
+/// Peak resident set (`VmHWM`) from `/proc/<pid>/status`, falling back to
+/// the current one.
+#[cfg(any(target_os = "linux", test))]
+fn rss_kib_from_status(status: &str) -> Option<u64> {
+    let field = |name: &str| {
+        status.lines().find_map(|line| {
+            line.strip_prefix(name)?
+                .trim()
+                .trim_end_matches("kB")
+                .trim()
+                .parse::<u64>()
+                .ok()
+        })
+    };
+    field("VmHWM:").or_else(|| field("VmRSS:"))
+}
+
+#[cfg(target_os = "macos")]
+fn sample_process(pid: u32) -> Option<ProcessSample> {
+    let pid = libc::c_int::try_from(pid).ok()?;
+    // SAFETY: proc_taskinfo is plain data and the buffer size matches it
+    let mut info: libc::proc_taskinfo = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::proc_taskinfo>() as libc::c_int;
+    let written = unsafe {
+        libc::proc_pidinfo(
+            pid,
+            libc::PROC_PIDTASKINFO,
+            0,
+            (&mut info as *mut libc::proc_taskinfo).cast(),
+            size,
+        )
+    };
+    if written != size {
+        return None;
+    }
+
+    // CPU times are in Mach absolute time units
+    let mut timebase = libc::mach_timebase_info { numer: 0, denom: 0 };
+    // SAFETY: only writes to the struct passed in
+    #[allow(deprecated)]
+    let timebase_ok = unsafe { libc::mach_timebase_info(&mut timebase) } == 0;
+    if !timebase_ok || timebase.denom == 0 {
+        return None;
+    }
+    let nanos = u128::from(info.pti_total_user + info.pti_total_system)
+        * u128::from(timebase.numer)
+        / u128::from(timebase.denom);
+    Some(ProcessSample {
+        cpu_time_ms: u64::try_from(nanos / 1_000_000).unwrap_or(u64::MAX),
+        rss_bytes: info.pti_resident_size,
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn sample_process(_pid: u32) -> Option<ProcessSample> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_stat_with_awkward_command_names() {
+        let stat = "4242 (my (odd) cmd) S 1 4242 4242 0 -1 4194560 100 0 0 0 250 75 0 0 20 0 1 0 12345 1000000 200 18446744073709551615";
+        assert_eq!(cpu_ticks_from_stat(stat), Some(325));
+        assert_eq!(cpu_ticks_from_stat("garbage"), None);
+    }
+
+    #[test]
+    fn prefers_the_high_water_mark() {
+        let status = "Name:\tnode\nVmHWM:\t  20480 kB\nVmRSS:\t  10240 kB\n";
+        assert_eq!(rss_kib_from_status(status), Some(20480));
+        assert_eq!(rss_kib_from_status("VmRSS:\t 512 kB\n"), Some(512));
+        assert_eq!(rss_kib_from_status("Name:\tzombie\n"), None);
+    }
+
+    #[test]
+    fn keeps_the_largest_values() {
+        let mut usage = ResourceUsage::default();
+        usage.record(ProcessSample {
+            cpu_time_ms: 100,
+            rss_bytes: 4096,
+        });
+        usage.record(ProcessSample {
+            cpu_time_ms: 300,
+            rss_bytes: 1024,
+        });
+        assert_eq!(
+            usage,
+            ResourceUsage {
+                cpu_time_ms: Some(300),
+                peak_rss_bytes: Some(4096),
+            }
+        );
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn samples_the_current_process() {
+        let sample = sample_process(std::process::id()).expect("sample");
+        assert!(sample.rss_bytes > 0);
+    }
+}
@@ -0,0 +1,3 @@
+-- Keyset pagination for the per-project task digest
+CREATE INDEX IF NOT EXISTS idx_shared_tasks_project_id_id
+    ON shared_tasks (project_id, id);
@@ -0,0 +1,7 @@
+-- Task the event is about, so activity can be filtered per task
+ALTER TABLE activity
+    ADD COLUMN IF NOT EXISTS task_id UUID
+    GENERATED ALWAYS AS ((payload -> 'task' ->> 'id')::uuid) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_activity_project_task_seq
+    ON activity (project_id, task_id, seq);
@@ -0,0 +1,3 @@
+-- Invitee email domains an organization accepts; empty allows any
+ALTER TABLE organizations
+    ADD COLUMN IF NOT EXISTS allowed_email_domains TEXT[] NOT NULL DEFAULT '{}';
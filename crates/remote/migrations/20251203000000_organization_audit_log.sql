@@ -0,0 +1,14 @@
+-- Membership and settings changes per organization. No foreign key on the
+-- organization so the record of its deletion outlives it.
+CREATE TABLE IF NOT EXISTS organization_audit_log (
+    id              UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    organization_id UUID NOT NULL,
+    actor_user_id   UUID REFERENCES users(id) ON DELETE SET NULL,
+    action          TEXT NOT NULL,
+    target_user_id  UUID REFERENCES users(id) ON DELETE SET NULL,
+    details         JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_organization_audit_log_org_created
+    ON organization_audit_log (organization_id, created_at DESC);
@@ -0,0 +1,2 @@
+-- Shown in the session list so users can tell their devices apart
+ALTER TABLE auth_sessions ADD COLUMN user_agent TEXT;
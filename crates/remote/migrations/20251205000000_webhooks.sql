@@ -0,0 +1,39 @@
+-- Endpoints an organization's task activity is POSTed to
+CREATE TABLE IF NOT EXISTS webhooks (
+    id                   UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    organization_id      UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    url                  TEXT NOT NULL,
+    -- HMAC-SHA256 key for the signature header; stored as is since it must
+    -- be used, not just checked
+    secret               TEXT NOT NULL,
+    -- Activity event types delivered; empty delivers every type
+    event_types          TEXT[] NOT NULL DEFAULT '{}',
+    active               BOOLEAN NOT NULL DEFAULT TRUE,
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    created_by           UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at           TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at           TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhooks_organization
+    ON webhooks (organization_id);
+
+-- One row per attempt to deliver an event. The unique key doubles as a
+-- claim, so only one server instance delivers each attempt.
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id              UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    webhook_id      UUID NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    event_id        UUID NOT NULL,
+    event_type      TEXT NOT NULL,
+    attempt         INTEGER NOT NULL,
+    -- NULL while the request is in flight
+    succeeded       BOOLEAN,
+    response_status INTEGER,
+    error           TEXT,
+    latency_ms      INTEGER,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (webhook_id, event_id, attempt)
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook_created
+    ON webhook_deliveries (webhook_id, created_at DESC);
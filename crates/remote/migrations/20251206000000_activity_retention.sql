@@ -0,0 +1,9 @@
+-- Highest activity seq per project whose partition has been dropped. A client
+-- whose cursor is below it has missed events and must bulk sync. Written
+-- from the detached partition before it is dropped, so pruning never scans
+-- or locks the live activity table; lookups use the primary key.
+CREATE TABLE IF NOT EXISTS activity_retention (
+    project_id         UUID PRIMARY KEY REFERENCES projects(id) ON DELETE CASCADE,
+    pruned_through_seq BIGINT NOT NULL,
+    pruned_at          TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,11 @@
+-- Last activity seq each of a user's devices has acknowledged per project.
+-- Devices are identified by an id the local app generates once; the row lets
+-- the server tell a device it is too far behind as soon as it connects.
+CREATE TABLE IF NOT EXISTS device_cursors (
+    user_id    UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    device_id  UUID NOT NULL,
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    last_seq   BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, device_id, project_id)
+);
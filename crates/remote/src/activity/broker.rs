@@ -1,7 +1,10 @@
 use std::{
     hash::{Hash, Hasher},
     pin::Pin,
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 
 use chrono::{DateTime, Utc};
@@ -48,6 +51,8 @@ impl ActivityEvent {
 #[derive(Clone)]
 pub struct ActivityBroker {
     shards: Arc<Vec<broadcast::Sender<ActivityEvent>>>,
+    /// The listener feeding the broker is connected to its channel
+    listening: Arc<AtomicBool>,
 }
 
 pub type ActivityStream =
@@ -67,9 +72,19 @@ impl ActivityBroker {
 
         Self {
             shards: Arc::new(shards),
+            listening: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Without a connected listener no activity reaches subscribers.
+    pub fn is_listening(&self) -> bool {
+        self.listening.load(Ordering::Relaxed)
+    }
+
+    pub fn set_listening(&self, listening: bool) {
+        self.listening.store(listening, Ordering::Relaxed);
+    }
+
     pub fn subscribe(&self, project_id: uuid::Uuid) -> ActivityStream {
         let index = self.shard_index(&project_id);
         let receiver = self.shards[index].subscribe();
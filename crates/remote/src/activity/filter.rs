@@ -0,0 +1,105 @@
+use uuid::Uuid;
+
+use super::ActivityEvent;
+
+/// Narrows an activity feed to one task and/or some event types.
+///
+/// Filtering only hides events: sequence numbers stay project-wide, so a
+/// filtered feed has gaps and a cursor taken from one filter is still valid
+/// with another.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActivityFilter {
+    pub task_id: Option<Uuid>,
+    /// Empty means every event type
+    pub event_types: Vec<String>,
+}
+
+impl ActivityFilter {
+    /// Build a filter from query parameters, `event_types` being a comma
+    /// separated list such as `task.created,task.deleted`.
+    pub fn from_query(task_id: Option<Uuid>, event_types: Option<&str>) -> Self {
+        let event_types = event_types
+            .map(|types| {
+                types
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            task_id,
+            event_types,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.task_id.is_none() && self.event_types.is_empty()
+    }
+
+    pub fn matches(&self, event: &ActivityEvent) -> bool {
+        let type_matches =
+            self.event_types.is_empty() || self.event_types.contains(&event.event_type);
+        type_matches
+            && self
+                .task_id
+                .is_none_or(|id| event_task_id(event) == Some(id))
+    }
+
+    /// The filter as `task_id` and `event_types` query parameters.
+    pub fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = Vec::new();
+        if let Some(task_id) = self.task_id {
+            pairs.push(("task_id", task_id.to_string()));
+        }
+        if !self.event_types.is_empty() {
+            pairs.push(("event_types", self.event_types.join(",")));
+        }
+        pairs
+    }
+}
+
+/// The shared task an event is about, read the same way as the `task_id`
+/// column on the activity table.
+fn event_task_id(event: &ActivityEvent) -> Option<Uuid> {
+    event
+        .payload
+        .as_ref()?
+        .get("task")?
+        .get("id")?
+        .as_str()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde_json::json;
+
+    use super::*;
+
+    fn event(event_type: &str, task_id: Uuid) -> ActivityEvent {
+        ActivityEvent::new(
+            1,
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            event_type.to_string(),
+            Utc::now(),
+            Some(json!({ "task": { "id": task_id }, "user": null })),
+        )
+    }
+
+    #[test]
+    fn filters_by_task_and_event_type() {
+        let task_id = Uuid::new_v4();
+        let filter = ActivityFilter::from_query(Some(task_id), Some("task.created, task.deleted,"));
+        assert_eq!(filter.event_types, ["task.created", "task.deleted"]);
+
+        assert!(filter.matches(&event("task.created", task_id)));
+        assert!(!filter.matches(&event("task.updated", task_id)));
+        assert!(!filter.matches(&event("task.created", Uuid::new_v4())));
+        assert!(ActivityFilter::default().matches(&event("task.updated", Uuid::new_v4())));
+    }
+}
@@ -1,3 +1,5 @@
 mod broker;
+mod filter;
 
 pub use broker::{ActivityBroker, ActivityEvent, ActivityResponse, ActivityStream};
+pub use filter::ActivityFilter;
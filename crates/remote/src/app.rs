@@ -10,9 +10,10 @@ use crate::{
         GitHubOAuthProvider, GoogleOAuthProvider, JwtService, OAuthHandoffService, ProviderRegistry,
     },
     config::RemoteServerConfig,
-    db,
+    db::{self, replicas::ReadPools},
     mail::LoopsMailer,
     routes,
+    webhooks::WebhookDispatcher,
 };
 
 pub struct Server;
@@ -32,14 +33,33 @@ impl Server {
             .await
             .context("failed to run database migrations")?;
 
-        db::maintenance::spawn_activity_partition_maintenance(pool.clone());
+        db::maintenance::spawn_activity_partition_maintenance(
+            pool.clone(),
+            config.activity_retention_days,
+        );
+
+        let replicas = config
+            .read_database_urls
+            .iter()
+            .map(|url| db::create_replica_pool(url))
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to create read replica pool")?;
+        if !replicas.is_empty() {
+            tracing::info!(count = replicas.len(), "read replicas configured");
+        }
+        let read_pools = ReadPools::new(
+            pool.clone(),
+            replicas,
+            config.read_replica_max_lag,
+            config.read_replica_lag_check_interval,
+        );
 
         let broker = ActivityBroker::new(
             config.activity_broadcast_shards,
             config.activity_broadcast_capacity,
         );
         let auth_config = config.auth.clone();
-        let jwt = Arc::new(JwtService::new(auth_config.jwt_secret().clone()));
+        let jwt = Arc::new(JwtService::new(auth_config.jwt_secrets().to_vec()));
 
         let mut registry = ProviderRegistry::new();
 
@@ -82,6 +102,7 @@ impl Server {
 
         let state = AppState::new(
             pool.clone(),
+            read_pools.clone(),
             broker.clone(),
             config.clone(),
             jwt,
@@ -90,8 +111,14 @@ impl Server {
             server_public_base_url,
         );
 
-        let listener =
-            db::ActivityListener::new(pool.clone(), broker, config.activity_channel.clone());
+        let webhooks = WebhookDispatcher::spawn(pool.clone(), &config);
+        let listener = db::ActivityListener::new(
+            pool.clone(),
+            broker,
+            read_pools,
+            webhooks,
+            config.activity_channel.clone(),
+        );
         tokio::spawn(listener.run());
 
         let router = routes::router(state);
@@ -105,7 +132,7 @@ impl Server {
 
         tracing::info!(%addr, "shared sync server listening");
 
-        let make_service = router.into_make_service();
+        let make_service = router.into_make_service_with_connect_info::<SocketAddr>();
 
         axum::serve(tcp_listener, make_service)
             .await
@@ -211,6 +211,7 @@ impl OAuthHandoffService {
         state: Option<&str>,
         code: Option<&str>,
         error: Option<&str>,
+        user_agent: Option<&str>,
     ) -> Result<CallbackResult, HandoffError> {
         let provider = self
             .providers
@@ -265,7 +266,7 @@ impl OAuthHandoffService {
 
         let user = self.upsert_identity(&provider, &user_profile).await?;
         let session_repo = AuthSessionRepository::new(&self.pool);
-        let session_record = session_repo.create(user.id, None).await?;
+        let session_record = session_repo.create(user.id, None, user_agent).await?;
 
         let app_code = generate_app_code();
         let app_code_hash = hash_sha256_hex(&app_code);
@@ -1,9 +1,11 @@
 use std::{collections::HashSet, sync::Arc};
 
 use chrono::{DateTime, Duration as ChronoDuration, Utc};
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode, errors::ErrorKind,
+};
 use secrecy::{ExposeSecret, SecretString};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -17,6 +19,8 @@ const DEFAULT_JWT_LEEWAY_SECONDS: u64 = 60;
 pub enum JwtError {
     #[error("invalid token")]
     InvalidToken,
+    #[error("token signature does not match any configured key")]
+    InvalidSignature,
     #[error("invalid jwt secret")]
     InvalidSecret,
     #[error("token expired")]
@@ -64,9 +68,12 @@ pub struct RefreshTokenDetails {
     pub refresh_token_id: Uuid,
 }
 
+/// Signs with the first secret and verifies with any of them, so a new
+/// secret can be put first while tokens signed with the old one stay valid
+/// until they expire.
 #[derive(Clone)]
 pub struct JwtService {
-    secret: Arc<SecretString>,
+    secrets: Arc<Vec<SecretString>>,
 }
 
 #[derive(Debug, Clone)]
@@ -77,12 +84,23 @@ pub struct TokenPair {
 }
 
 impl JwtService {
-    pub fn new(secret: SecretString) -> Self {
+    pub fn new(secrets: Vec<SecretString>) -> Self {
         Self {
-            secret: Arc::new(secret),
+            secrets: Arc::new(secrets),
         }
     }
 
+    /// Fails if no secret is configured or one is not valid base64.
+    pub fn check_secrets(&self) -> Result<(), JwtError> {
+        if self.secrets.is_empty() {
+            return Err(JwtError::InvalidSecret);
+        }
+        for secret in self.secrets.iter() {
+            DecodingKey::from_base64_secret(secret.expose_secret())?;
+        }
+        Ok(())
+    }
+
     pub fn generate_tokens(
         &self,
         session: &AuthSession,
@@ -112,7 +130,8 @@ impl JwtService {
             aud: "refresh".to_string(),
         };
 
-        let encoding_key = EncodingKey::from_base64_secret(self.secret.expose_secret())?;
+        let signing_secret = self.secrets.first().ok_or(JwtError::InvalidSecret)?;
+        let encoding_key = EncodingKey::from_base64_secret(signing_secret.expose_secret())?;
 
         let access_token = encode(
             &Header::new(Algorithm::HS256),
@@ -154,9 +173,7 @@ impl JwtService {
             HashSet::from(["sub".to_string(), "exp".to_string(), "aud".to_string()]);
         validation.leeway = leeway_seconds;
 
-        let decoding_key = DecodingKey::from_base64_secret(self.secret.expose_secret())?;
-        let data = decode::<AccessTokenClaims>(token, &decoding_key, &validation)?;
-        let claims = data.claims;
+        let claims = self.decode_claims::<AccessTokenClaims>(token, &validation)?;
         let expires_at = DateTime::from_timestamp(claims.exp, 0).ok_or(JwtError::InvalidToken)?;
 
         Ok(AccessTokenDetails {
@@ -183,9 +200,7 @@ impl JwtService {
         ]);
         validation.leeway = DEFAULT_JWT_LEEWAY_SECONDS;
 
-        let decoding_key = DecodingKey::from_base64_secret(self.secret.expose_secret())?;
-        let data = decode::<RefreshTokenClaims>(token, &decoding_key, &validation)?;
-        let claims = data.claims;
+        let claims = self.decode_claims::<RefreshTokenClaims>(token, &validation)?;
 
         Ok(RefreshTokenDetails {
             user_id: claims.sub,
@@ -193,4 +208,111 @@ impl JwtService {
             refresh_token_id: claims.jti,
         })
     }
+
+    /// Verify `token` with each secret in turn. Only a signature mismatch
+    /// moves on to the next secret; any other failure means the token was
+    /// signed with this one but is not acceptable.
+    fn decode_claims<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        validation: &Validation,
+    ) -> Result<T, JwtError> {
+        for secret in self.secrets.iter() {
+            let decoding_key = DecodingKey::from_base64_secret(secret.expose_secret())?;
+            match decode::<T>(token, &decoding_key, validation) {
+                Ok(data) => return Ok(data.claims),
+                Err(error) => match error.kind() {
+                    ErrorKind::InvalidSignature => continue,
+                    ErrorKind::ExpiredSignature => return Err(JwtError::TokenExpired),
+                    _ => return Err(error.into()),
+                },
+            }
+        }
+        Err(JwtError::InvalidSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD_SECRET: &str = "b2xkLXNlY3JldC1vbGQtc2VjcmV0LW9sZC1zZWNyZXQtb2xkLXNlY3JldA==";
+    const NEW_SECRET: &str = "bmV3LXNlY3JldC1uZXctc2VjcmV0LW5ldy1zZWNyZXQtbmV3LXNlY3JldA==";
+
+    fn service(secrets: &[&str]) -> JwtService {
+        JwtService::new(
+            secrets
+                .iter()
+                .map(|secret| SecretString::new((*secret).into()))
+                .collect(),
+        )
+    }
+
+    fn tokens(jwt: &JwtService) -> (TokenPair, AuthSession) {
+        let now = Utc::now();
+        let user = User {
+            id: Uuid::new_v4(),
+            email: "dev@example.com".to_string(),
+            first_name: None,
+            last_name: None,
+            username: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let session = AuthSession {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            created_at: now,
+            last_used_at: None,
+            revoked_at: None,
+            refresh_token_id: None,
+            refresh_token_issued_at: None,
+            user_agent: None,
+        };
+        (jwt.generate_tokens(&session, &user).unwrap(), session)
+    }
+
+    #[test]
+    fn tokens_signed_before_rotation_stay_valid() {
+        let (old_tokens, session) = tokens(&service(&[OLD_SECRET]));
+
+        let rotated = service(&[NEW_SECRET, OLD_SECRET]);
+        let refresh = rotated
+            .decode_refresh_token(&old_tokens.refresh_token)
+            .unwrap();
+        assert_eq!(refresh.session_id, session.id);
+        assert_eq!(refresh.refresh_token_id, old_tokens.refresh_token_id);
+        let access = rotated
+            .decode_access_token(&old_tokens.access_token)
+            .unwrap();
+        assert_eq!(access.session_id, session.id);
+
+        // New tokens are signed with the new secret only
+        let (new_tokens, _) = tokens(&rotated);
+        assert!(
+            service(&[NEW_SECRET])
+                .decode_refresh_token(&new_tokens.refresh_token)
+                .is_ok()
+        );
+        assert!(matches!(
+            service(&[OLD_SECRET]).decode_refresh_token(&new_tokens.refresh_token),
+            Err(JwtError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn retired_secrets_no_longer_verify() {
+        let (old_tokens, _) = tokens(&service(&[OLD_SECRET]));
+        let retired = service(&[NEW_SECRET]);
+
+        assert!(matches!(
+            retired.decode_refresh_token(&old_tokens.refresh_token),
+            Err(JwtError::InvalidSignature)
+        ));
+        // A failure other than the signature is reported as is
+        assert!(matches!(
+            service(&[OLD_SECRET]).decode_access_token(&old_tokens.refresh_token),
+            Err(JwtError::Jwt(_))
+        ));
+    }
 }
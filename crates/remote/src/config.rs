@@ -1,4 +1,4 @@
-use std::env;
+use std::{env, time::Duration};
 
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
 use secrecy::SecretString;
@@ -11,10 +11,43 @@ const DEFAULT_ACTIVITY_MAX_LIMIT: i64 = 500;
 const DEFAULT_ACTIVITY_BROADCAST_SHARDS: usize = 16;
 const DEFAULT_ACTIVITY_BROADCAST_CAPACITY: usize = 512;
 const DEFAULT_ACTIVITY_CATCHUP_BATCH_SIZE: i64 = 100;
+// Days of activity kept before its daily partitions are dropped
+const DEFAULT_ACTIVITY_RETENTION_DAYS: i64 = 2;
+// Activity frames buffered per websocket session before it is forced to bulk sync
+const DEFAULT_WS_OUTBOUND_QUEUE_CAPACITY: usize = 1024;
+// Slowest a single websocket frame may take to send before the client is dropped
+const DEFAULT_WS_SEND_TIMEOUT_MS: u64 = 10_000;
+// How often the server pings websocket clients; two silent intervals close the session
+const DEFAULT_WS_PING_INTERVAL_SECS: u64 = 30;
+// Activity events a read replica may trail the primary by before reads fall back
+const DEFAULT_READ_REPLICA_MAX_LAG: i64 = 50;
+// How long a replica lag measurement is reused
+const DEFAULT_READ_REPLICA_LAG_CHECK_MS: u64 = 2_000;
+// Activity events waiting for webhook delivery before new ones are dropped
+const DEFAULT_WEBHOOK_QUEUE_CAPACITY: usize = 1024;
+// Tries per event and webhook, including the first
+const DEFAULT_WEBHOOK_MAX_ATTEMPTS: u32 = 5;
+// Events in a row a webhook may fail to take before it is deactivated
+const DEFAULT_WEBHOOK_DISABLE_AFTER_FAILURES: i32 = 20;
+// Slowest a webhook endpoint may take to answer
+const DEFAULT_WEBHOOK_TIMEOUT_MS: u64 = 10_000;
+// Length of the window request limits are counted over
+const DEFAULT_RATE_LIMIT_WINDOW_SECS: u64 = 60;
+// Requests per window a client IP may make to the unauthenticated routes
+const DEFAULT_RATE_LIMIT_PUBLIC_REQUESTS: u32 = 30;
+// Requests per window a signed-in user may make to the authenticated routes
+const DEFAULT_RATE_LIMIT_USER_REQUESTS: u32 = 600;
+// Largest request body accepted by the unauthenticated routes
+const DEFAULT_PUBLIC_BODY_LIMIT_BYTES: usize = 16 * 1024;
+// Largest request body accepted by the authenticated routes
+const DEFAULT_BODY_LIMIT_BYTES: usize = 1024 * 1024;
 
 #[derive(Debug, Clone)]
 pub struct RemoteServerConfig {
     pub database_url: String,
+    pub read_database_urls: Vec<String>,
+    pub read_replica_max_lag: i64,
+    pub read_replica_lag_check_interval: Duration,
     pub listen_addr: String,
     pub server_public_base_url: Option<String>,
     pub activity_channel: String,
@@ -23,9 +56,62 @@ pub struct RemoteServerConfig {
     pub activity_broadcast_shards: usize,
     pub activity_broadcast_capacity: usize,
     pub activity_catchup_batch_size: i64,
+    pub activity_retention_days: i64,
+    pub ws_outbound_queue_capacity: usize,
+    pub ws_send_timeout: Duration,
+    pub ws_ping_interval: Duration,
+    pub webhook_queue_capacity: usize,
+    pub webhook_max_attempts: u32,
+    pub webhook_disable_after_failures: i32,
+    pub webhook_timeout: Duration,
+    pub rate_limit: RateLimitConfig,
     pub auth: AuthConfig,
 }
 
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub window: Duration,
+    /// Requests per window per client IP on the public routes
+    pub public_requests: u32,
+    /// Requests per window per user on the authenticated routes
+    pub user_requests: u32,
+    pub public_body_limit: usize,
+    pub body_limit: usize,
+    /// Take the client IP from the right-most `X-Forwarded-For` entry, which
+    /// is only meaningful behind a reverse proxy that sets or appends it.
+    pub trust_forwarded_for: bool,
+}
+
+impl RateLimitConfig {
+    fn from_env() -> Result<Self, ConfigError> {
+        Ok(Self {
+            window: Duration::from_secs(
+                get_numeric_env_var(
+                    "SERVER_RATE_LIMIT_WINDOW_SECS",
+                    DEFAULT_RATE_LIMIT_WINDOW_SECS,
+                )?
+                .max(1),
+            ),
+            public_requests: get_numeric_env_var(
+                "SERVER_RATE_LIMIT_PUBLIC_REQUESTS",
+                DEFAULT_RATE_LIMIT_PUBLIC_REQUESTS,
+            )?
+            .max(1),
+            user_requests: get_numeric_env_var(
+                "SERVER_RATE_LIMIT_USER_REQUESTS",
+                DEFAULT_RATE_LIMIT_USER_REQUESTS,
+            )?
+            .max(1),
+            public_body_limit: get_numeric_env_var(
+                "SERVER_PUBLIC_BODY_LIMIT_BYTES",
+                DEFAULT_PUBLIC_BODY_LIMIT_BYTES,
+            )?,
+            body_limit: get_numeric_env_var("SERVER_BODY_LIMIT_BYTES", DEFAULT_BODY_LIMIT_BYTES)?,
+            trust_forwarded_for: get_bool_env_var("SERVER_TRUST_FORWARDED_FOR", false)?,
+        })
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ConfigError {
     #[error("environment variable `{0}` is not set")]
@@ -42,6 +128,25 @@ impl RemoteServerConfig {
             .or_else(|_| env::var("DATABASE_URL"))
             .map_err(|_| ConfigError::MissingVar("SERVER_DATABASE_URL"))?;
 
+        let read_database_urls = env::var("SERVER_READ_DATABASE_URL")
+            .map(|urls| {
+                urls.split(',')
+                    .map(str::trim)
+                    .filter(|url| !url.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let read_replica_max_lag =
+            get_numeric_env_var("SERVER_READ_REPLICA_MAX_LAG", DEFAULT_READ_REPLICA_MAX_LAG)?
+                .max(0);
+
+        let read_replica_lag_check_interval = Duration::from_millis(get_numeric_env_var(
+            "SERVER_READ_REPLICA_LAG_CHECK_MS",
+            DEFAULT_READ_REPLICA_LAG_CHECK_MS,
+        )?);
+
         let listen_addr =
             env::var("SERVER_LISTEN_ADDR").unwrap_or_else(|_| "0.0.0.0:8081".to_string());
 
@@ -71,10 +176,59 @@ impl RemoteServerConfig {
         )?
         .max(1);
 
+        let activity_retention_days = get_numeric_env_var(
+            "SERVER_ACTIVITY_RETENTION_DAYS",
+            DEFAULT_ACTIVITY_RETENTION_DAYS,
+        )?
+        .max(1);
+
+        let ws_outbound_queue_capacity = get_numeric_env_var(
+            "SERVER_WS_OUTBOUND_QUEUE_CAPACITY",
+            DEFAULT_WS_OUTBOUND_QUEUE_CAPACITY,
+        )?
+        .max(1);
+
+        let ws_send_timeout = Duration::from_millis(
+            get_numeric_env_var("SERVER_WS_SEND_TIMEOUT_MS", DEFAULT_WS_SEND_TIMEOUT_MS)?.max(1),
+        );
+
+        let ws_ping_interval = Duration::from_secs(
+            get_numeric_env_var(
+                "SERVER_WS_PING_INTERVAL_SECS",
+                DEFAULT_WS_PING_INTERVAL_SECS,
+            )?
+            .max(1),
+        );
+
+        let webhook_queue_capacity = get_numeric_env_var(
+            "SERVER_WEBHOOK_QUEUE_CAPACITY",
+            DEFAULT_WEBHOOK_QUEUE_CAPACITY,
+        )?
+        .max(1);
+
+        let webhook_max_attempts =
+            get_numeric_env_var("SERVER_WEBHOOK_MAX_ATTEMPTS", DEFAULT_WEBHOOK_MAX_ATTEMPTS)?
+                .max(1);
+
+        let webhook_disable_after_failures = get_numeric_env_var(
+            "SERVER_WEBHOOK_DISABLE_AFTER_FAILURES",
+            DEFAULT_WEBHOOK_DISABLE_AFTER_FAILURES,
+        )?
+        .max(1);
+
+        let webhook_timeout = Duration::from_millis(
+            get_numeric_env_var("SERVER_WEBHOOK_TIMEOUT_MS", DEFAULT_WEBHOOK_TIMEOUT_MS)?.max(1),
+        );
+
+        let rate_limit = RateLimitConfig::from_env()?;
+
         let auth = AuthConfig::from_env()?;
 
         Ok(Self {
             database_url,
+            read_database_urls,
+            read_replica_max_lag,
+            read_replica_lag_check_interval,
             listen_addr,
             server_public_base_url,
             activity_channel,
@@ -83,6 +237,15 @@ impl RemoteServerConfig {
             activity_broadcast_shards,
             activity_broadcast_capacity,
             activity_catchup_batch_size,
+            activity_retention_days,
+            ws_outbound_queue_capacity,
+            ws_send_timeout,
+            ws_ping_interval,
+            webhook_queue_capacity,
+            webhook_max_attempts,
+            webhook_disable_after_failures,
+            webhook_timeout,
+            rate_limit,
             auth,
         })
     }
@@ -100,6 +263,18 @@ fn get_numeric_env_var<T: std::str::FromStr>(
     }
 }
 
+fn get_bool_env_var(var_name: &'static str, default: bool) -> Result<bool, ConfigError> {
+    match env::var(var_name) {
+        Ok(value) => match value.trim().to_ascii_lowercase().as_str() {
+            "" => Ok(default),
+            "1" | "true" | "yes" | "on" => Ok(true),
+            "0" | "false" | "no" | "off" => Ok(false),
+            _ => Err(ConfigError::InvalidVar(var_name)),
+        },
+        Err(_) => Ok(default),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct OAuthProviderConfig {
     client_id: String,
@@ -127,16 +302,23 @@ impl OAuthProviderConfig {
 pub struct AuthConfig {
     github: Option<OAuthProviderConfig>,
     google: Option<OAuthProviderConfig>,
-    jwt_secret: SecretString,
+    jwt_secrets: Vec<SecretString>,
     public_base_url: String,
 }
 
 impl AuthConfig {
     fn from_env() -> Result<Self, ConfigError> {
-        let jwt_secret = env::var("VIBEKANBAN_REMOTE_JWT_SECRET")
-            .map_err(|_| ConfigError::MissingVar("VIBEKANBAN_REMOTE_JWT_SECRET"))?;
-        validate_jwt_secret(&jwt_secret)?;
-        let jwt_secret = SecretString::new(jwt_secret.into());
+        // Comma-separated; the first signs new tokens, the rest only verify
+        // tokens signed before a rotation
+        let jwt_secrets = env::var("VIBEKANBAN_REMOTE_JWT_SECRET")
+            .map_err(|_| ConfigError::MissingVar("VIBEKANBAN_REMOTE_JWT_SECRET"))?
+            .split(',')
+            .map(str::trim)
+            .map(|secret| {
+                validate_jwt_secret(secret)?;
+                Ok(SecretString::new(secret.into()))
+            })
+            .collect::<Result<Vec<_>, ConfigError>>()?;
 
         let github = match env::var("GITHUB_OAUTH_CLIENT_ID") {
             Ok(client_id) => {
@@ -172,7 +354,7 @@ impl AuthConfig {
         Ok(Self {
             github,
             google,
-            jwt_secret,
+            jwt_secrets,
             public_base_url,
         })
     }
@@ -185,8 +367,9 @@ impl AuthConfig {
         self.google.as_ref()
     }
 
-    pub fn jwt_secret(&self) -> &SecretString {
-        &self.jwt_secret
+    /// Signing secret first, then secrets still accepted for verification
+    pub fn jwt_secrets(&self) -> &[SecretString] {
+        &self.jwt_secrets
     }
 
     pub fn public_base_url(&self) -> &str {
@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::activity::ActivityEvent;
+use crate::activity::{ActivityEvent, ActivityFilter};
 
 pub struct ActivityRepository<'a> {
     pool: &'a PgPool,
@@ -13,11 +13,14 @@ impl<'a> ActivityRepository<'a> {
         Self { pool }
     }
 
+    /// Events after `after_seq` that pass `filter`. Sequence numbers are not
+    /// renumbered, so filtered results have gaps.
     pub async fn fetch_since(
         &self,
         project_id: Uuid,
         after_seq: Option<i64>,
         limit: i64,
+        filter: &ActivityFilter,
     ) -> Result<Vec<ActivityEvent>, sqlx::Error> {
         let rows = sqlx::query_as::<_, ActivityRow>(
             r#"
@@ -30,6 +33,8 @@ impl<'a> ActivityRepository<'a> {
             FROM activity
             WHERE project_id = $1
               AND ($2::bigint IS NULL OR seq > $2)
+              AND ($4::uuid IS NULL OR task_id = $4)
+              AND (cardinality($5::text[]) = 0 OR event_type = ANY($5))
             ORDER BY seq ASC
             LIMIT $3
             "#,
@@ -37,12 +42,43 @@ impl<'a> ActivityRepository<'a> {
         .bind(project_id)
         .bind(after_seq)
         .bind(limit)
+        .bind(filter.task_id)
+        .bind(&filter.event_types)
         .fetch_all(self.pool)
         .await?;
 
         Ok(rows.into_iter().map(ActivityRow::into_event).collect())
     }
 
+    /// Highest seq of the project's activity that has been pruned; a cursor
+    /// below it has missed events that can no longer be fetched.
+    pub async fn pruned_through(&self, project_id: Uuid) -> Result<Option<i64>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT pruned_through_seq
+            FROM activity_retention
+            WHERE project_id = $1
+            "#,
+            project_id
+        )
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Seq of the project's newest activity
+    pub async fn latest_seq(&self, project_id: Uuid) -> Result<Option<i64>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT last_seq
+            FROM project_activity_counters
+            WHERE project_id = $1
+            "#,
+            project_id
+        )
+        .fetch_optional(self.pool)
+        .await
+    }
+
     pub async fn fetch_by_seq(
         &self,
         project_id: Uuid,
@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{Executor, PgPool, Postgres};
+pub use utils::api::organizations::{AuditAction, AuditLogEntry};
+use uuid::Uuid;
+
+/// Append an entry. Pass the transaction making the change so the entry is
+/// written if and only if the change commits.
+pub(crate) async fn record<'a, E>(
+    executor: E,
+    organization_id: Uuid,
+    actor_user_id: Uuid,
+    action: AuditAction,
+    target_user_id: Option<Uuid>,
+    details: Value,
+) -> Result<(), sqlx::Error>
+where
+    E: Executor<'a, Database = Postgres>,
+{
+    // clock_timestamp() rather than the column default keeps entries written
+    // in one transaction distinct for the created_at cursor
+    sqlx::query!(
+        r#"
+        INSERT INTO organization_audit_log (
+            organization_id, actor_user_id, action, target_user_id, details, created_at
+        )
+        VALUES ($1, $2, $3, $4, $5, clock_timestamp())
+        "#,
+        organization_id,
+        actor_user_id,
+        action as AuditAction,
+        target_user_id,
+        details
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+pub struct AuditLogRepository<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> AuditLogRepository<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Newest first, strictly older than `before` when given.
+    pub async fn list(
+        &self,
+        organization_id: Uuid,
+        before: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<AuditLogEntry>, sqlx::Error> {
+        sqlx::query_as!(
+            AuditLogEntry,
+            r#"
+            SELECT
+                id              AS "id!: Uuid",
+                organization_id AS "organization_id!: Uuid",
+                actor_user_id   AS "actor_user_id?: Uuid",
+                action          AS "action!: AuditAction",
+                target_user_id  AS "target_user_id?: Uuid",
+                details         AS "details!: Value",
+                created_at      AS "created_at!"
+            FROM organization_audit_log
+            WHERE organization_id = $1
+              AND ($2::timestamptz IS NULL OR created_at < $2)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#,
+            organization_id,
+            before,
+            limit
+        )
+        .fetch_all(self.pool)
+        .await
+    }
+}
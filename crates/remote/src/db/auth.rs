@@ -29,6 +29,7 @@ pub struct AuthSession {
     pub revoked_at: Option<DateTime<Utc>>,
     pub refresh_token_id: Option<Uuid>,
     pub refresh_token_issued_at: Option<DateTime<Utc>>,
+    pub user_agent: Option<String>,
 }
 
 pub const MAX_SESSION_INACTIVITY_DURATION: Duration = Duration::days(365);
@@ -46,12 +47,13 @@ impl<'a> AuthSessionRepository<'a> {
         &self,
         user_id: Uuid,
         refresh_token_id: Option<Uuid>,
+        user_agent: Option<&str>,
     ) -> Result<AuthSession, AuthSessionError> {
         query_as!(
             AuthSession,
             r#"
-            INSERT INTO auth_sessions (user_id, refresh_token_id)
-            VALUES ($1, $2)
+            INSERT INTO auth_sessions (user_id, refresh_token_id, user_agent)
+            VALUES ($1, $2, $3)
             RETURNING
                 id                          AS "id!",
                 user_id                     AS "user_id!: Uuid",
@@ -59,10 +61,12 @@ impl<'a> AuthSessionRepository<'a> {
                 last_used_at                AS "last_used_at?",
                 revoked_at                  AS "revoked_at?",
                 refresh_token_id           AS "refresh_token_id?",
-                refresh_token_issued_at     AS "refresh_token_issued_at?"
+                refresh_token_issued_at     AS "refresh_token_issued_at?",
+                user_agent                  AS "user_agent?"
             "#,
             user_id,
-            refresh_token_id
+            refresh_token_id,
+            user_agent
         )
         .fetch_one(self.pool)
         .await
@@ -80,7 +84,8 @@ impl<'a> AuthSessionRepository<'a> {
                 last_used_at                AS "last_used_at?",
                 revoked_at                  AS "revoked_at?",
                 refresh_token_id           AS "refresh_token_id?",
-                refresh_token_issued_at     AS "refresh_token_issued_at?"
+                refresh_token_issued_at     AS "refresh_token_issued_at?",
+                user_agent                  AS "user_agent?"
             FROM auth_sessions
             WHERE id = $1
             "#,
@@ -91,6 +96,37 @@ impl<'a> AuthSessionRepository<'a> {
         .ok_or(AuthSessionError::NotFound)
     }
 
+    /// Sessions of `user_id` that are still usable, newest first.
+    pub async fn list_active_for_user(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<AuthSession>, AuthSessionError> {
+        let sessions = query_as!(
+            AuthSession,
+            r#"
+            SELECT
+                id                          AS "id!",
+                user_id                     AS "user_id!: Uuid",
+                created_at                  AS "created_at!",
+                last_used_at                AS "last_used_at?",
+                revoked_at                  AS "revoked_at?",
+                refresh_token_id           AS "refresh_token_id?",
+                refresh_token_issued_at     AS "refresh_token_issued_at?",
+                user_agent                  AS "user_agent?"
+            FROM auth_sessions
+            WHERE user_id = $1
+              AND revoked_at IS NULL
+              AND COALESCE(last_used_at, created_at) > NOW() - make_interval(days => $2)
+            ORDER BY created_at DESC
+            "#,
+            user_id,
+            MAX_SESSION_INACTIVITY_DURATION.num_days() as i32
+        )
+        .fetch_all(self.pool)
+        .await?;
+        Ok(sessions)
+    }
+
     pub async fn touch(&self, session_id: Uuid) -> Result<(), AuthSessionError> {
         sqlx::query!(
             r#"
@@ -229,6 +265,31 @@ impl<'a> AuthSessionRepository<'a> {
         Ok(result.is_revoked.unwrap_or(false))
     }
 
+    /// Revoke a session only if it belongs to `user_id`, so users cannot
+    /// probe or end other users' sessions.
+    pub async fn revoke_for_user(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), AuthSessionError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE auth_sessions
+            SET revoked_at = COALESCE(revoked_at, NOW())
+            WHERE id = $1
+              AND user_id = $2
+            "#,
+            session_id,
+            user_id
+        )
+        .execute(self.pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(AuthSessionError::NotFound);
+        }
+        Ok(())
+    }
+
     pub async fn revoke(&self, session_id: Uuid) -> Result<(), AuthSessionError> {
         sqlx::query!(
             r#"
@@ -0,0 +1,61 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Per-device activity cursors. A device id only means something together
+/// with the user that registered it.
+pub struct DeviceCursorRepository<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> DeviceCursorRepository<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record that the device has applied activity up to `last_seq`. Cursors
+    /// only move forward; the stored value is returned.
+    pub async fn ack(
+        &self,
+        user_id: Uuid,
+        device_id: Uuid,
+        project_id: Uuid,
+        last_seq: i64,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO device_cursors (user_id, device_id, project_id, last_seq)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, device_id, project_id) DO UPDATE
+                SET last_seq = GREATEST(device_cursors.last_seq, EXCLUDED.last_seq),
+                    updated_at = NOW()
+            RETURNING last_seq
+            "#,
+            user_id,
+            device_id,
+            project_id,
+            last_seq
+        )
+        .fetch_one(self.pool)
+        .await
+    }
+
+    pub async fn get(
+        &self,
+        user_id: Uuid,
+        device_id: Uuid,
+        project_id: Uuid,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT last_seq
+            FROM device_cursors
+            WHERE user_id = $1 AND device_id = $2 AND project_id = $3
+            "#,
+            user_id,
+            device_id,
+            project_id
+        )
+        .fetch_optional(self.pool)
+        .await
+    }
+}
@@ -12,6 +12,8 @@ pub enum IdentityError {
     CannotDeleteOrganization(String),
     #[error("organization conflict: {0}")]
     OrganizationConflict(String),
+    #[error("email domain is not allowed in this organization")]
+    EmailDomainNotAllowed,
     #[error(transparent)]
     Database(#[from] sqlx::Error),
 }
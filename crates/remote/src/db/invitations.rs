@@ -1,10 +1,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use sqlx::PgPool;
 pub use utils::api::organizations::InvitationStatus;
 use uuid::Uuid;
 
 use super::{
+    audit_log::{self, AuditAction},
     identity_errors::IdentityError,
     organization_members::{MemberRole, add_member, assert_admin},
     organizations::{Organization, OrganizationRepository},
@@ -54,6 +56,12 @@ impl<'a> InvitationRepository<'a> {
             ));
         }
 
+        OrganizationRepository::new(self.pool)
+            .assert_email_domain_allowed(organization_id, email)
+            .await?;
+
+        let mut tx = self.pool.begin().await?;
+
         let invitation = sqlx::query_as!(
             Invitation,
             r#"
@@ -80,7 +88,7 @@ impl<'a> InvitationRepository<'a> {
             token,
             expires_at
         )
-        .fetch_one(self.pool)
+        .fetch_one(&mut *tx)
         .await
         .map_err(|e| {
             if let Some(db_err) = e.as_database_error()
@@ -93,6 +101,22 @@ impl<'a> InvitationRepository<'a> {
             IdentityError::from(e)
         })?;
 
+        audit_log::record(
+            &mut *tx,
+            organization_id,
+            invited_by_user_id,
+            AuditAction::InvitationCreated,
+            None,
+            json!({
+                "invitation_id": invitation.id,
+                "email": invitation.email,
+                "role": invitation.role,
+            }),
+        )
+        .await?;
+
+        tx.commit().await?;
+
         Ok(invitation)
     }
 
@@ -171,28 +195,47 @@ impl<'a> InvitationRepository<'a> {
     ) -> Result<(), IdentityError> {
         assert_admin(self.pool, organization_id, requesting_user_id).await?;
 
-        let result = sqlx::query!(
+        let mut tx = self.pool.begin().await?;
+
+        let revoked = sqlx::query!(
             r#"
             DELETE FROM organization_invitations
             WHERE id = $1 AND organization_id = $2
+            RETURNING email, role AS "role!: MemberRole"
             "#,
             invitation_id,
             organization_id
         )
-        .execute(self.pool)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(IdentityError::NotFound)?;
+
+        audit_log::record(
+            &mut *tx,
+            organization_id,
+            requesting_user_id,
+            AuditAction::InvitationRevoked,
+            None,
+            json!({
+                "invitation_id": invitation_id,
+                "email": revoked.email,
+                "role": revoked.role,
+            }),
+        )
         .await?;
 
-        if result.rows_affected() == 0 {
-            return Err(IdentityError::NotFound);
-        }
+        tx.commit().await?;
 
         Ok(())
     }
 
+    /// `user_email` is the accepting account's address, which must satisfy
+    /// the organization's allowed domains whatever address was invited.
     pub async fn accept_invitation(
         &self,
         token: &str,
         user_id: Uuid,
+        user_email: &str,
     ) -> Result<(Organization, MemberRole), IdentityError> {
         let mut tx = self.pool.begin().await?;
 
@@ -232,6 +275,14 @@ impl<'a> InvitationRepository<'a> {
             ));
         }
 
+        if let Err(err) = OrganizationRepository::new(self.pool)
+            .assert_email_domain_allowed(invitation.organization_id, user_email)
+            .await
+        {
+            tx.rollback().await?;
+            return Err(err);
+        }
+
         if invitation.expires_at < Utc::now() {
             sqlx::query!(
                 r#"
@@ -276,6 +327,19 @@ impl<'a> InvitationRepository<'a> {
         .execute(&mut *tx)
         .await?;
 
+        audit_log::record(
+            &mut *tx,
+            invitation.organization_id,
+            user_id,
+            AuditAction::InvitationAccepted,
+            Some(user_id),
+            json!({
+                "invitation_id": invitation.id,
+                "role": invitation.role,
+            }),
+        )
+        .await?;
+
         tx.commit().await?;
 
         let organization = OrganizationRepository::new(self.pool)
@@ -7,19 +7,33 @@ use tokio::time::sleep;
 use tracing::instrument;
 use uuid::Uuid;
 
-use crate::{activity::ActivityBroker, db::activity::ActivityRepository};
+use crate::{
+    activity::ActivityBroker,
+    db::{activity::ActivityRepository, replicas::ReadPools},
+    webhooks::WebhookDispatcher,
+};
 
 pub struct ActivityListener {
     pool: PgPool,
     broker: ActivityBroker,
+    read_pools: ReadPools,
+    webhooks: WebhookDispatcher,
     channel: String,
 }
 
 impl ActivityListener {
-    pub fn new(pool: PgPool, broker: ActivityBroker, channel: String) -> Self {
+    pub fn new(
+        pool: PgPool,
+        broker: ActivityBroker,
+        read_pools: ReadPools,
+        webhooks: WebhookDispatcher,
+        channel: String,
+    ) -> Self {
         Self {
             pool,
             broker,
+            read_pools,
+            webhooks,
             channel,
         }
     }
@@ -35,14 +49,17 @@ impl ActivityListener {
 
         let pool = self.pool;
         let broker = self.broker;
+        let read_pools = self.read_pools;
+        let webhooks = self.webhooks;
         let channel = self.channel;
 
         loop {
-            match listen_loop(&pool, &broker, &channel).await {
+            match listen_loop(&pool, &broker, &read_pools, &webhooks, &channel).await {
                 Ok(_) => {
                     backoff = Duration::from_secs(1);
                 }
                 Err(error) => {
+                    broker.set_listening(false);
                     tracing::error!(?error, ?backoff, "activity listener error; retrying");
                     sleep(backoff).await;
                     backoff = (backoff * 2).min(max_backoff);
@@ -54,10 +71,16 @@ impl ActivityListener {
 
 #[instrument(
     name = "activity.listen_loop",
-    skip(pool, broker),
+    skip(pool, broker, read_pools, webhooks),
     fields(channel = %channel)
 )]
-async fn listen_loop(pool: &PgPool, broker: &ActivityBroker, channel: &str) -> anyhow::Result<()> {
+async fn listen_loop(
+    pool: &PgPool,
+    broker: &ActivityBroker,
+    read_pools: &ReadPools,
+    webhooks: &WebhookDispatcher,
+    channel: &str,
+) -> anyhow::Result<()> {
     let mut listener = PgListener::connect_with(pool)
         .await
         .context("failed to create LISTEN connection")?;
@@ -65,6 +88,7 @@ async fn listen_loop(pool: &PgPool, broker: &ActivityBroker, channel: &str) -> a
         .listen(channel)
         .await
         .with_context(|| format!("failed to LISTEN on channel {channel}"))?;
+    broker.set_listening(true);
 
     loop {
         let notification = listener
@@ -97,6 +121,8 @@ async fn listen_loop(pool: &PgPool, broker: &ActivityBroker, channel: &str) -> a
             }
         };
 
+        read_pools.record_published(event.project_id, event.seq);
+        webhooks.enqueue(&event);
         broker.publish(event);
     }
 }
@@ -17,7 +17,9 @@ fn prune_time() -> NaiveTime {
     *PRUNE_TIME.get_or_init(|| NaiveTime::from_hms_opt(1, 30, 0).expect("valid time"))
 }
 
-pub fn spawn_activity_partition_maintenance(pool: PgPool) {
+/// Keeps partitions for the coming days and drops those entirely older than
+/// `retention_days`.
+pub fn spawn_activity_partition_maintenance(pool: PgPool, retention_days: i64) {
     let creation_pool = pool.clone();
     tokio::spawn(async move {
         if let Err(err) = ensure_future_partitions_with_pool(&creation_pool).await {
@@ -33,13 +35,13 @@ pub fn spawn_activity_partition_maintenance(pool: PgPool) {
     });
 
     tokio::spawn(async move {
-        if let Err(err) = prune_old_partitions(&pool).await {
+        if let Err(err) = prune_old_partitions(&pool, retention_days).await {
             error!(error = ?err, "initial activity partition pruning failed");
         }
 
         loop {
             sleep(duration_until(prune_time())).await;
-            if let Err(err) = prune_old_partitions(&pool).await {
+            if let Err(err) = prune_old_partitions(&pool, retention_days).await {
                 error!(error = ?err, "scheduled partition pruning failed");
             }
         }
@@ -62,7 +64,7 @@ fn duration_until(target_time: NaiveTime) -> Duration {
         .unwrap_or_else(|_| Duration::from_secs(0))
 }
 
-async fn prune_old_partitions(pool: &PgPool) -> Result<(), sqlx::Error> {
+async fn prune_old_partitions(pool: &PgPool, retention_days: i64) -> Result<(), sqlx::Error> {
     let mut conn = pool.acquire().await?;
 
     let lock_acquired = sqlx::query_scalar!(
@@ -96,25 +98,53 @@ async fn prune_old_partitions(pool: &PgPool) -> Result<(), sqlx::Error> {
               AND split_part(
                     split_part(pg_get_expr(c.relpartbound, c.oid), ' TO (''', 2),
                     ''')', 1
-                  )::timestamptz <= NOW() - INTERVAL '2 days'
+                  )::timestamptz <= NOW() - make_interval(days => $1::int)
             ORDER BY upper_bound
-            "#
+            "#,
+            retention_days as i32
         )
         .fetch_all(&mut *conn)
         .await?;
 
+        let mut rows_pruned = 0;
+        let mut partitions_dropped = 0;
         for partition in partitions {
             if let Some(name) = partition.qualified_name {
                 let detach = format!("ALTER TABLE activity DETACH PARTITION {name} CONCURRENTLY");
                 sqlx::query(&detach).execute(&mut *conn).await?;
 
+                // Once detached, the partition is only read here, so neither
+                // statement contends with activity writes or reads
+                let count = format!("SELECT COUNT(*) FROM {name}");
+                let rows: i64 = sqlx::query_scalar(&count).fetch_one(&mut *conn).await?;
+                let record = format!(
+                    "INSERT INTO activity_retention (project_id, pruned_through_seq)
+                     SELECT a.project_id, MAX(a.seq)
+                     FROM {name} a
+                     JOIN projects p ON p.id = a.project_id
+                     GROUP BY a.project_id
+                     ON CONFLICT (project_id) DO UPDATE SET
+                         pruned_through_seq = GREATEST(
+                             activity_retention.pruned_through_seq,
+                             EXCLUDED.pruned_through_seq
+                         ),
+                         pruned_at = NOW()"
+                );
+                sqlx::query(&record).execute(&mut *conn).await?;
+
                 let drop = format!("DROP TABLE {name}");
                 sqlx::query(&drop).execute(&mut *conn).await?;
 
-                info!(partition = %name, "dropped activity partition");
+                info!(partition = %name, rows, "dropped activity partition");
+                rows_pruned += rows;
+                partitions_dropped += 1;
             }
         }
 
+        info!(
+            rows_pruned,
+            partitions_dropped, retention_days, "activity retention run finished"
+        );
         Ok(())
     }
     .await;
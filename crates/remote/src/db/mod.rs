@@ -1,5 +1,7 @@
 pub mod activity;
+pub mod audit_log;
 pub mod auth;
+pub mod devices;
 pub mod identity_errors;
 pub mod invitations;
 pub mod listener;
@@ -9,8 +11,12 @@ pub mod oauth_accounts;
 pub mod organization_members;
 pub mod organizations;
 pub mod projects;
+pub mod replicas;
 pub mod tasks;
 pub mod users;
+pub mod webhooks;
+
+use std::time::Duration;
 
 pub use listener::ActivityListener;
 use sqlx::{PgPool, Postgres, Transaction, migrate::MigrateError, postgres::PgPoolOptions};
@@ -27,3 +33,12 @@ pub(crate) async fn create_pool(database_url: &str) -> Result<PgPool, sqlx::Erro
         .connect(database_url)
         .await
 }
+
+/// Replicas connect lazily so an unavailable one does not stop the server;
+/// reads fall back to the primary until it answers lag probes again.
+pub(crate) fn create_replica_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
+    PgPoolOptions::new()
+        .max_connections(10)
+        .acquire_timeout(Duration::from_secs(2))
+        .connect_lazy(database_url)
+}
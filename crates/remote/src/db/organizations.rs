@@ -1,8 +1,10 @@
+use serde_json::json;
 use sqlx::{PgPool, query_as};
 pub use utils::api::organizations::{MemberRole, Organization, OrganizationWithRole};
 use uuid::Uuid;
 
 use super::{
+    audit_log::{self, AuditAction},
     identity_errors::IdentityError,
     organization_members::{
         add_member, assert_admin as check_admin, assert_membership as check_membership,
@@ -39,6 +41,7 @@ impl<'a> OrganizationRepository<'a> {
                 name        AS "name!",
                 slug        AS "slug!",
                 is_personal AS "is_personal!",
+                allowed_email_domains AS "allowed_email_domains!",
                 created_at  AS "created_at!",
                 updated_at  AS "updated_at!"
             FROM organizations
@@ -66,6 +69,72 @@ impl<'a> OrganizationRepository<'a> {
         result.ok_or(IdentityError::NotFound)
     }
 
+    pub async fn allowed_email_domains(
+        &self,
+        organization_id: Uuid,
+    ) -> Result<Vec<String>, IdentityError> {
+        let result = sqlx::query_scalar!(
+            r#"
+            SELECT allowed_email_domains AS "allowed_email_domains!"
+            FROM organizations
+            WHERE id = $1
+            "#,
+            organization_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        result.ok_or(IdentityError::NotFound)
+    }
+
+    /// Replace the allowed invitee domains. Callers normalize them first with
+    /// [`normalize_email_domains`].
+    pub async fn set_allowed_email_domains(
+        &self,
+        org_id: Uuid,
+        user_id: Uuid,
+        domains: &[String],
+    ) -> Result<Organization, IdentityError> {
+        self.assert_admin(org_id, user_id).await?;
+
+        sqlx::query_as!(
+            Organization,
+            r#"
+            UPDATE organizations
+            SET allowed_email_domains = $2
+            WHERE id = $1
+            RETURNING
+                id AS "id!: Uuid",
+                name AS "name!",
+                slug AS "slug!",
+                is_personal AS "is_personal!",
+                allowed_email_domains AS "allowed_email_domains!",
+                created_at AS "created_at!",
+                updated_at AS "updated_at!"
+            "#,
+            org_id,
+            domains
+        )
+        .fetch_optional(self.pool)
+        .await?
+        .ok_or(IdentityError::NotFound)
+    }
+
+    /// Fails with [`IdentityError::EmailDomainNotAllowed`] unless `email` may
+    /// join the organization. Personal organizations accept any address.
+    pub async fn assert_email_domain_allowed(
+        &self,
+        organization_id: Uuid,
+        email: &str,
+    ) -> Result<(), IdentityError> {
+        let org = self.fetch_organization(organization_id).await?;
+        if org.is_personal || email_domain_allowed(email, &org.allowed_email_domains) {
+            Ok(())
+        } else {
+            Err(IdentityError::EmailDomainNotAllowed)
+        }
+    }
+
     pub async fn ensure_personal_org_and_admin_membership(
         &self,
         user_id: Uuid,
@@ -123,6 +192,7 @@ impl<'a> OrganizationRepository<'a> {
                 name AS "name!",
                 slug AS "slug!",
                 is_personal AS "is_personal!",
+                allowed_email_domains AS "allowed_email_domains!",
                 created_at AS "created_at!",
                 updated_at AS "updated_at!"
             "#,
@@ -143,6 +213,15 @@ impl<'a> OrganizationRepository<'a> {
         })?;
 
         add_member(&mut *tx, org.id, creator_user_id, MemberRole::Admin).await?;
+        audit_log::record(
+            &mut *tx,
+            org.id,
+            creator_user_id,
+            AuditAction::MemberAdded,
+            Some(creator_user_id),
+            json!({ "role": MemberRole::Admin }),
+        )
+        .await?;
 
         tx.commit().await?;
 
@@ -193,6 +272,21 @@ impl<'a> OrganizationRepository<'a> {
     ) -> Result<Organization, IdentityError> {
         self.assert_admin(org_id, user_id).await?;
 
+        let mut tx = self.pool.begin().await?;
+
+        let previous_name = sqlx::query_scalar!(
+            r#"
+            SELECT name
+            FROM organizations
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            org_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(IdentityError::NotFound)?;
+
         let org = sqlx::query_as!(
             Organization,
             r#"
@@ -204,15 +298,29 @@ impl<'a> OrganizationRepository<'a> {
                 name AS "name!",
                 slug AS "slug!",
                 is_personal AS "is_personal!",
+                allowed_email_domains AS "allowed_email_domains!",
                 created_at AS "created_at!",
                 updated_at AS "updated_at!"
             "#,
             org_id,
             new_name
         )
-        .fetch_optional(self.pool)
-        .await?
-        .ok_or(IdentityError::NotFound)?;
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if previous_name != org.name {
+            audit_log::record(
+                &mut *tx,
+                org_id,
+                user_id,
+                AuditAction::OrganizationRenamed,
+                None,
+                json!({ "from": previous_name, "to": org.name }),
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
 
         Ok(org)
     }
@@ -232,6 +340,8 @@ impl<'a> OrganizationRepository<'a> {
             ));
         }
 
+        let mut tx = self.pool.begin().await?;
+
         let result = sqlx::query!(
             r#"
             WITH s AS (
@@ -251,10 +361,11 @@ impl<'a> OrganizationRepository<'a> {
             org_id,
             user_id
         )
-        .fetch_optional(self.pool)
+        .fetch_optional(&mut *tx)
         .await?;
 
         if result.is_none() {
+            tx.rollback().await?;
             let role = self.check_user_role(org_id, user_id).await?;
             match role {
                 None | Some(MemberRole::Member) => {
@@ -268,6 +379,18 @@ impl<'a> OrganizationRepository<'a> {
             }
         }
 
+        audit_log::record(
+            &mut *tx,
+            org_id,
+            user_id,
+            AuditAction::OrganizationDeleted,
+            None,
+            json!({ "name": org.name, "slug": org.slug }),
+        )
+        .await?;
+
+        tx.commit().await?;
+
         Ok(())
     }
 }
@@ -284,6 +407,7 @@ async fn find_organization_by_slug(
             name        AS "name!",
             slug        AS "slug!",
             is_personal AS "is_personal!",
+            allowed_email_domains AS "allowed_email_domains!",
             created_at  AS "created_at!",
             updated_at  AS "updated_at!"
         FROM organizations
@@ -310,6 +434,7 @@ async fn create_personal_org(
             name        AS "name!",
             slug        AS "slug!",
             is_personal AS "is_personal!",
+            allowed_email_domains AS "allowed_email_domains!",
             created_at  AS "created_at!",
             updated_at  AS "updated_at!"
         "#,
@@ -320,6 +445,37 @@ async fn create_personal_org(
     .await
 }
 
+/// Lowercase domains without a leading `@`, deduplicated. `None` if any entry
+/// is not a plausible domain.
+pub fn normalize_email_domains(domains: &[String]) -> Option<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::with_capacity(domains.len());
+    for domain in domains {
+        let domain = domain.trim().trim_start_matches('@').to_lowercase();
+        let valid = domain.contains('.')
+            && !domain.starts_with('.')
+            && !domain.ends_with('.')
+            && domain
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+        if !valid {
+            return None;
+        }
+        if !normalized.contains(&domain) {
+            normalized.push(domain);
+        }
+    }
+    Some(normalized)
+}
+
+fn email_domain_allowed(email: &str, allowed: &[String]) -> bool {
+    if allowed.is_empty() {
+        return true;
+    }
+    email
+        .rsplit_once('@')
+        .is_some_and(|(_, domain)| allowed.iter().any(|a| a.eq_ignore_ascii_case(domain)))
+}
+
 fn personal_org_name(hint: Option<&str>, user_id: Uuid) -> String {
     let user_id_str = user_id.to_string();
     let display_name = hint.unwrap_or(&user_id_str);
@@ -330,3 +486,29 @@ fn personal_org_slug(user_id: Uuid) -> String {
     // Use a deterministic slug pattern so we can find personal orgs
     format!("personal-{user_id}")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_normalized_domains_exactly() {
+        let allowed = normalize_email_domains(&[
+            " @Example.com".to_string(),
+            "example.com".to_string(),
+            "corp.example.org".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(allowed, ["example.com", "corp.example.org"]);
+
+        assert!(email_domain_allowed("Jo@EXAMPLE.com", &allowed));
+        assert!(email_domain_allowed("jo@corp.example.org", &allowed));
+        assert!(!email_domain_allowed("jo@mail.example.com", &allowed));
+        assert!(!email_domain_allowed("jo@example.com.evil.io", &allowed));
+        assert!(!email_domain_allowed("not-an-email", &allowed));
+        assert!(email_domain_allowed("anyone@anywhere.io", &[]));
+
+        assert!(normalize_email_domains(&["localhost".to_string()]).is_none());
+        assert!(normalize_email_domains(&["a b.com".to_string()]).is_none());
+    }
+}
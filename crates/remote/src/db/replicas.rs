@@ -0,0 +1,330 @@
+//! Read replica selection for read-only paths.
+//!
+//! Replicas are only used when they are reachable and close enough to the
+//! primary. Lag is measured in activity events: the sum of every project's
+//! `last_seq` counter on the replica against the same sum on the primary,
+//! cached for a short interval so the probe is not paid on every request.
+//!
+//! Cursor-based reads have a stricter rule on top of that: a replica may only
+//! serve a project's activity if it already has every event up to the highest
+//! seq a client could have seen, either from the request cursor or from an
+//! event this server published over the websocket.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Which pool a read was served from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolSource {
+    Primary,
+    Replica(usize),
+}
+
+impl fmt::Display for PoolSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Primary => write!(f, "primary"),
+            Self::Replica(index) => write!(f, "replica-{index}"),
+        }
+    }
+}
+
+/// A pool chosen for one read.
+#[derive(Clone, Copy)]
+pub struct ReadPool<'a> {
+    pool: &'a PgPool,
+    source: PoolSource,
+}
+
+impl<'a> ReadPool<'a> {
+    pub fn pool(&self) -> &'a PgPool {
+        self.pool
+    }
+
+    pub fn source(&self) -> PoolSource {
+        self.source
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Probe {
+    at: Instant,
+    /// `None` when the pool could not be queried
+    total_seq: Option<i64>,
+}
+
+struct Replica {
+    pool: PgPool,
+    probe: Mutex<Option<Probe>>,
+}
+
+struct Inner {
+    primary: PgPool,
+    primary_probe: Mutex<Option<Probe>>,
+    replicas: Vec<Replica>,
+    next: AtomicUsize,
+    max_lag: i64,
+    lag_check_interval: Duration,
+    published: Mutex<HashMap<Uuid, i64>>,
+}
+
+#[derive(Clone)]
+pub struct ReadPools {
+    inner: Arc<Inner>,
+}
+
+impl ReadPools {
+    pub fn new(
+        primary: PgPool,
+        replicas: Vec<PgPool>,
+        max_lag: i64,
+        lag_check_interval: Duration,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                primary,
+                primary_probe: Mutex::new(None),
+                replicas: replicas
+                    .into_iter()
+                    .map(|pool| Replica {
+                        pool,
+                        probe: Mutex::new(None),
+                    })
+                    .collect(),
+                next: AtomicUsize::new(0),
+                max_lag: max_lag.max(0),
+                lag_check_interval,
+                published: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    pub fn primary(&self) -> ReadPool<'_> {
+        ReadPool {
+            pool: &self.inner.primary,
+            source: PoolSource::Primary,
+        }
+    }
+
+    /// A replica within the lag threshold, or the primary if there is none.
+    pub async fn read(&self) -> ReadPool<'_> {
+        let inner = &self.inner;
+        if inner.replicas.is_empty() {
+            return self.primary();
+        }
+
+        let Some(primary_total) = self.total_seq(&inner.primary, &inner.primary_probe).await else {
+            // Without a primary reference the lag is unknown
+            return self.primary();
+        };
+
+        let start = inner.next.fetch_add(1, Ordering::Relaxed);
+        let mut replica_totals = Vec::with_capacity(inner.replicas.len());
+        for replica in &inner.replicas {
+            replica_totals.push(self.total_seq(&replica.pool, &replica.probe).await);
+        }
+
+        match pick_replica(&replica_totals, start, primary_total, inner.max_lag) {
+            Some(index) => ReadPool {
+                pool: &inner.replicas[index].pool,
+                source: PoolSource::Replica(index),
+            },
+            None => self.primary(),
+        }
+    }
+
+    /// Like [`Self::read`], but only returns a replica that has every activity
+    /// event for `project_id` up to `after` and up to the latest event this
+    /// server has published for the project.
+    pub async fn read_activity(&self, project_id: Uuid, after: Option<i64>) -> ReadPool<'_> {
+        let candidate = self.read().await;
+        if candidate.source == PoolSource::Primary {
+            return candidate;
+        }
+
+        let published = self.published_seq(project_id);
+        let replica_max = match max_activity_seq(candidate.pool, project_id).await {
+            Ok(seq) => seq,
+            Err(error) => {
+                tracing::warn!(?error, source = %candidate.source, "replica seq check failed");
+                return self.primary();
+            }
+        };
+
+        if covers_published(replica_max, after, published) {
+            candidate
+        } else {
+            tracing::debug!(
+                %project_id,
+                ?replica_max,
+                ?after,
+                ?published,
+                "replica behind published activity; reading from primary"
+            );
+            self.primary()
+        }
+    }
+
+    /// Record that an activity event has been sent to websocket clients.
+    pub fn record_published(&self, project_id: Uuid, seq: i64) {
+        let mut published = self
+            .inner
+            .published
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let entry = published.entry(project_id).or_insert(seq);
+        *entry = (*entry).max(seq);
+    }
+
+    fn published_seq(&self, project_id: Uuid) -> Option<i64> {
+        self.inner
+            .published
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&project_id)
+            .copied()
+    }
+
+    async fn total_seq(&self, pool: &PgPool, cache: &Mutex<Option<Probe>>) -> Option<i64> {
+        let cached = *cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(probe) = cached
+            && probe.at.elapsed() < self.inner.lag_check_interval
+        {
+            return probe.total_seq;
+        }
+
+        let total_seq = match total_activity_seq(pool).await {
+            Ok(total) => Some(total),
+            Err(error) => {
+                tracing::warn!(?error, "read pool lag probe failed");
+                None
+            }
+        };
+        *cache.lock().unwrap_or_else(|e| e.into_inner()) = Some(Probe {
+            at: Instant::now(),
+            total_seq,
+        });
+        total_seq
+    }
+}
+
+/// First usable replica, starting from `start` so reads rotate across them.
+/// `None` entries are replicas that could not be reached.
+fn pick_replica(
+    replica_totals: &[Option<i64>],
+    start: usize,
+    primary_total: i64,
+    max_lag: i64,
+) -> Option<usize> {
+    let count = replica_totals.len();
+    (0..count)
+        .map(|offset| (start + offset) % count)
+        .find(|&index| replica_totals[index].is_some_and(|total| primary_total - total <= max_lag))
+}
+
+/// Whether a replica whose latest event for a project is `replica_max` can
+/// serve a read without hiding events a client may already know about.
+fn covers_published(replica_max: Option<i64>, after: Option<i64>, published: Option<i64>) -> bool {
+    let Some(floor) = after.max(published) else {
+        return true;
+    };
+    replica_max.is_some_and(|seq| seq >= floor)
+}
+
+async fn total_activity_seq(pool: &PgPool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        r#"SELECT COALESCE(SUM(last_seq), 0)::bigint FROM project_activity_counters"#,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+async fn max_activity_seq(pool: &PgPool, project_id: Uuid) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar::<_, Option<i64>>(r#"SELECT MAX(seq) FROM activity WHERE project_id = $1"#)
+        .bind(project_id)
+        .fetch_one(pool)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_replicas_within_lag_in_rotation() {
+        let totals = [Some(100), Some(98), Some(100)];
+        assert_eq!(pick_replica(&totals, 0, 100, 5), Some(0));
+        assert_eq!(pick_replica(&totals, 1, 100, 5), Some(1));
+        assert_eq!(pick_replica(&totals, 5, 100, 5), Some(2));
+    }
+
+    #[test]
+    fn skips_lagging_and_unreachable_replicas() {
+        let totals = [None, Some(40), Some(95)];
+        assert_eq!(pick_replica(&totals, 0, 100, 5), Some(2));
+        assert_eq!(pick_replica(&totals, 0, 100, 0), None);
+        assert_eq!(pick_replica(&[None, None], 0, 100, 1000), None);
+    }
+
+    #[test]
+    fn replica_must_cover_cursor_and_published_seq() {
+        // Nothing known yet, any replica is fine
+        assert!(covers_published(None, None, None));
+        // The client has seen seq 10 through its cursor
+        assert!(covers_published(Some(10), Some(10), None));
+        assert!(!covers_published(Some(9), Some(10), None));
+        // Seq 12 went out over the websocket, so a replica at 11 would hide it
+        assert!(!covers_published(Some(11), Some(5), Some(12)));
+        assert!(covers_published(Some(12), Some(5), Some(12)));
+        assert!(!covers_published(None, None, Some(1)));
+    }
+
+    fn unreachable_pool(url: &str) -> PgPool {
+        sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(200))
+            .connect_lazy(url)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_primary_when_lag_is_unknown() {
+        let pools = ReadPools::new(
+            unreachable_pool("postgres://primary.invalid/remote"),
+            vec![unreachable_pool("postgres://replica.invalid/remote")],
+            10,
+            Duration::from_secs(60),
+        );
+
+        assert_eq!(pools.read().await.source(), PoolSource::Primary);
+        assert_eq!(
+            pools.read_activity(Uuid::new_v4(), Some(3)).await.source(),
+            PoolSource::Primary
+        );
+    }
+
+    #[tokio::test]
+    async fn published_seq_only_moves_forward() {
+        let pools = ReadPools::new(
+            unreachable_pool("postgres://primary.invalid/remote"),
+            Vec::new(),
+            0,
+            Duration::from_secs(1),
+        );
+        let project_id = Uuid::new_v4();
+
+        pools.record_published(project_id, 7);
+        pools.record_published(project_id, 3);
+        assert_eq!(pools.published_seq(project_id), Some(7));
+        assert_eq!(pools.published_seq(Uuid::new_v4()), None);
+    }
+}
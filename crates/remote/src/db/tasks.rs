@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::PgPool;
 use thiserror::Error;
 use uuid::Uuid;
@@ -18,6 +19,58 @@ pub struct BulkFetchResult {
     pub latest_seq: Option<i64>,
 }
 
+pub struct TaskDigestPage {
+    pub tasks: Vec<TaskDigestEntry>,
+    pub deleted_task_ids: Vec<Uuid>,
+    /// Pass as `after` to fetch the next page; `None` on the last page.
+    pub next_after: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskDigestEntry {
+    pub id: Uuid,
+    pub version: i64,
+    pub hash: String,
+}
+
+/// The synced fields of a shared task, hashed so clients can compare their
+/// local copy without downloading the task itself.
+pub struct TaskDigestFields<'a> {
+    pub version: i64,
+    pub title: &'a str,
+    pub description: Option<&'a str>,
+    pub status: TaskStatus,
+    pub assignee_user_id: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TaskDigestFields<'_> {
+    pub fn hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.version.to_le_bytes());
+        hasher.update(self.updated_at.timestamp_micros().to_le_bytes());
+        hasher.update(self.status.as_str().as_bytes());
+        hasher.update(
+            self.assignee_user_id
+                .map(|id| *id.as_bytes())
+                .unwrap_or_default(),
+        );
+        for text in [Some(self.title), self.description] {
+            match text {
+                Some(text) => {
+                    hasher.update((text.len() as u64 + 1).to_le_bytes());
+                    hasher.update(text.as_bytes());
+                }
+                None => hasher.update(0u64.to_le_bytes()),
+            }
+        }
+        hasher.finalize()[..16]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+}
+
 pub const MAX_SHARED_TASK_TEXT_BYTES: usize = 50 * 1024;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
@@ -31,6 +84,18 @@ pub enum TaskStatus {
     Cancelled,
 }
 
+impl TaskStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Todo => "todo",
+            TaskStatus::InProgress => "in-progress",
+            TaskStatus::InReview => "in-review",
+            TaskStatus::Done => "done",
+            TaskStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SharedTaskWithUser {
     pub task: SharedTask,
@@ -231,6 +296,91 @@ impl<'a> SharedTaskRepository<'a> {
         Ok(SharedTaskWithUser::new(task, user))
     }
 
+    pub async fn find_with_user(
+        &self,
+        task_id: Uuid,
+    ) -> Result<Option<SharedTaskWithUser>, SharedTaskError> {
+        let Some(task) = self.find_by_id(task_id).await? else {
+            return Ok(None);
+        };
+
+        let mut tx = self.pool.begin().await?;
+        let user = match task.assignee_user_id {
+            Some(user_id) => fetch_user(&mut tx, user_id).await?,
+            None => None,
+        };
+        tx.commit().await?;
+
+        Ok(Some(SharedTaskWithUser::new(task, user)))
+    }
+
+    /// Page through a project's tasks, live and deleted, ordered by id.
+    pub async fn digest_page(
+        &self,
+        project_id: Uuid,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> Result<TaskDigestPage, SharedTaskError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                id                      AS "id!: Uuid",
+                assignee_user_id        AS "assignee_user_id?: Uuid",
+                title                   AS "title!",
+                description             AS "description?",
+                status                  AS "status!: TaskStatus",
+                version                 AS "version!",
+                updated_at              AS "updated_at!",
+                deleted_at IS NOT NULL  AS "deleted!"
+            FROM shared_tasks
+            WHERE project_id = $1
+              AND ($2::uuid IS NULL OR id > $2::uuid)
+            ORDER BY id
+            LIMIT $3
+            "#,
+            project_id,
+            after,
+            limit
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        let next_after = if rows.len() as i64 == limit {
+            rows.last().map(|row| row.id)
+        } else {
+            None
+        };
+
+        let mut tasks = Vec::with_capacity(rows.len());
+        let mut deleted_task_ids = Vec::new();
+        for row in rows {
+            if row.deleted {
+                deleted_task_ids.push(row.id);
+                continue;
+            }
+            let hash = TaskDigestFields {
+                version: row.version,
+                title: &row.title,
+                description: row.description.as_deref(),
+                status: row.status,
+                assignee_user_id: row.assignee_user_id,
+                updated_at: row.updated_at,
+            }
+            .hash();
+            tasks.push(TaskDigestEntry {
+                id: row.id,
+                version: row.version,
+                hash,
+            });
+        }
+
+        Ok(TaskDigestPage {
+            tasks,
+            deleted_task_ids,
+            next_after,
+        })
+    }
+
     pub async fn bulk_fetch(&self, project_id: Uuid) -> Result<BulkFetchResult, SharedTaskError> {
         let mut tx = self.pool.begin().await?;
         sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ")
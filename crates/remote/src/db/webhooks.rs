@@ -0,0 +1,340 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// An organization's endpoint. The secret is left out when serialized; it
+/// is only shown once, when the webhook is created.
+#[derive(Debug, Clone, Serialize)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// Empty delivers every event type
+    pub event_types: Vec<String>,
+    pub active: bool,
+    pub consecutive_failures: i32,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    pub fn wants(&self, event_type: &str) -> bool {
+        self.event_types.is_empty() || self.event_types.iter().any(|t| t == event_type)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event_id: Uuid,
+    pub event_type: String,
+    pub attempt: i32,
+    /// `None` while the request is in flight
+    pub succeeded: Option<bool>,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub latency_ms: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Outcome of one delivery attempt.
+#[derive(Debug)]
+pub struct AttemptResult {
+    pub succeeded: bool,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub latency_ms: i32,
+}
+
+pub struct WebhookRepository<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> WebhookRepository<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        organization_id: Uuid,
+        url: &str,
+        secret: &str,
+        event_types: &[String],
+        created_by: Uuid,
+    ) -> Result<Webhook, sqlx::Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"
+            INSERT INTO webhooks (organization_id, url, secret, event_types, created_by)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING
+                id                   AS "id!: Uuid",
+                organization_id      AS "organization_id!: Uuid",
+                url                  AS "url!",
+                secret               AS "secret!",
+                event_types          AS "event_types!: Vec<String>",
+                active               AS "active!",
+                consecutive_failures AS "consecutive_failures!",
+                created_by           AS "created_by?: Uuid",
+                created_at           AS "created_at!",
+                updated_at           AS "updated_at!"
+            "#,
+            organization_id,
+            url,
+            secret,
+            event_types,
+            created_by
+        )
+        .fetch_one(self.pool)
+        .await
+    }
+
+    pub async fn list(&self, organization_id: Uuid) -> Result<Vec<Webhook>, sqlx::Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"
+            SELECT
+                id                   AS "id!: Uuid",
+                organization_id      AS "organization_id!: Uuid",
+                url                  AS "url!",
+                secret               AS "secret!",
+                event_types          AS "event_types!: Vec<String>",
+                active               AS "active!",
+                consecutive_failures AS "consecutive_failures!",
+                created_by           AS "created_by?: Uuid",
+                created_at           AS "created_at!",
+                updated_at           AS "updated_at!"
+            FROM webhooks
+            WHERE organization_id = $1
+            ORDER BY created_at ASC
+            "#,
+            organization_id
+        )
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Active webhooks of the organization owning `project_id`.
+    pub async fn active_for_project(&self, project_id: Uuid) -> Result<Vec<Webhook>, sqlx::Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"
+            SELECT
+                w.id                   AS "id!: Uuid",
+                w.organization_id      AS "organization_id!: Uuid",
+                w.url                  AS "url!",
+                w.secret               AS "secret!",
+                w.event_types          AS "event_types!: Vec<String>",
+                w.active               AS "active!",
+                w.consecutive_failures AS "consecutive_failures!",
+                w.created_by           AS "created_by?: Uuid",
+                w.created_at           AS "created_at!",
+                w.updated_at           AS "updated_at!"
+            FROM webhooks w
+            JOIN projects p ON p.organization_id = w.organization_id
+            WHERE p.id = $1
+              AND w.active
+            "#,
+            project_id
+        )
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Change the URL, event types or active flag; `None` leaves a field as
+    /// is. Reactivating clears the failure count. Returns `None` if the
+    /// webhook is not in the organization.
+    pub async fn update(
+        &self,
+        organization_id: Uuid,
+        webhook_id: Uuid,
+        url: Option<&str>,
+        event_types: Option<&[String]>,
+        active: Option<bool>,
+    ) -> Result<Option<Webhook>, sqlx::Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"
+            UPDATE webhooks
+            SET url = COALESCE($3, url),
+                event_types = COALESCE($4, event_types),
+                active = COALESCE($5, active),
+                consecutive_failures = CASE WHEN $5 THEN 0 ELSE consecutive_failures END,
+                updated_at = NOW()
+            WHERE id = $2
+              AND organization_id = $1
+            RETURNING
+                id                   AS "id!: Uuid",
+                organization_id      AS "organization_id!: Uuid",
+                url                  AS "url!",
+                secret               AS "secret!",
+                event_types          AS "event_types!: Vec<String>",
+                active               AS "active!",
+                consecutive_failures AS "consecutive_failures!",
+                created_by           AS "created_by?: Uuid",
+                created_at           AS "created_at!",
+                updated_at           AS "updated_at!"
+            "#,
+            organization_id,
+            webhook_id,
+            url,
+            event_types as Option<&[String]>,
+            active
+        )
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Returns whether a webhook was deleted.
+    pub async fn delete(
+        &self,
+        organization_id: Uuid,
+        webhook_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM webhooks WHERE id = $1 AND organization_id = $2",
+            webhook_id,
+            organization_id
+        )
+        .execute(self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Newest first. Returns `None` if the webhook is not in the
+    /// organization.
+    pub async fn list_deliveries(
+        &self,
+        organization_id: Uuid,
+        webhook_id: Uuid,
+        limit: i64,
+    ) -> Result<Option<Vec<WebhookDelivery>>, sqlx::Error> {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM webhooks WHERE id = $1 AND organization_id = $2) AS "exists!""#,
+            webhook_id,
+            organization_id
+        )
+        .fetch_one(self.pool)
+        .await?;
+        if !exists {
+            return Ok(None);
+        }
+
+        let deliveries = sqlx::query_as!(
+            WebhookDelivery,
+            r#"
+            SELECT
+                id              AS "id!: Uuid",
+                webhook_id      AS "webhook_id!: Uuid",
+                event_id        AS "event_id!: Uuid",
+                event_type      AS "event_type!",
+                attempt         AS "attempt!",
+                succeeded       AS "succeeded?",
+                response_status AS "response_status?",
+                error           AS "error?",
+                latency_ms      AS "latency_ms?",
+                created_at      AS "created_at!"
+            FROM webhook_deliveries
+            WHERE webhook_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+            webhook_id,
+            limit
+        )
+        .fetch_all(self.pool)
+        .await?;
+        Ok(Some(deliveries))
+    }
+
+    /// Record that this instance is making `attempt`. Returns `None` if
+    /// another instance already claimed it.
+    pub async fn claim_attempt(
+        &self,
+        webhook_id: Uuid,
+        event_id: Uuid,
+        event_type: &str,
+        attempt: i32,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO webhook_deliveries (webhook_id, event_id, event_type, attempt)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (webhook_id, event_id, attempt) DO NOTHING
+            RETURNING id AS "id!: Uuid"
+            "#,
+            webhook_id,
+            event_id,
+            event_type,
+            attempt
+        )
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    pub async fn finish_attempt(
+        &self,
+        delivery_id: Uuid,
+        result: &AttemptResult,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE webhook_deliveries
+            SET succeeded = $2,
+                response_status = $3,
+                error = $4,
+                latency_ms = $5
+            WHERE id = $1
+            "#,
+            delivery_id,
+            result.succeeded,
+            result.response_status,
+            result.error,
+            result.latency_ms
+        )
+        .execute(self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn record_success(&self, webhook_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE webhooks SET consecutive_failures = 0 WHERE id = $1 AND consecutive_failures <> 0",
+            webhook_id
+        )
+        .execute(self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Count an event that could not be delivered, deactivating the webhook
+    /// once `disable_after` have failed in a row. Returns whether it is
+    /// still active.
+    pub async fn record_failure(
+        &self,
+        webhook_id: Uuid,
+        disable_after: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let active = sqlx::query_scalar!(
+            r#"
+            UPDATE webhooks
+            SET consecutive_failures = consecutive_failures + 1,
+                active = active AND consecutive_failures + 1 < $2,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING active
+            "#,
+            webhook_id,
+            disable_after
+        )
+        .fetch_optional(self.pool)
+        .await?;
+        Ok(active.unwrap_or(false))
+    }
+}
@@ -6,6 +6,7 @@ pub mod db;
 pub mod mail;
 pub mod routes;
 mod state;
+pub mod webhooks;
 pub mod ws;
 
 use std::{env, sync::OnceLock};
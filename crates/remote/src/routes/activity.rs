@@ -9,11 +9,18 @@ use serde::Deserialize;
 use tracing::instrument;
 use uuid::Uuid;
 
-use super::{error::ErrorResponse, organization_members::ensure_project_access};
+use super::{error::ErrorResponse, organization_members::ensure_project_access, with_served_by};
 use crate::{
-    AppState, activity::ActivityResponse, auth::RequestContext, db::activity::ActivityRepository,
+    AppState,
+    activity::{ActivityFilter, ActivityResponse},
+    auth::RequestContext,
+    db::activity::ActivityRepository,
 };
 
+/// Sent with `410 Gone` when the cursor is older than the retained activity;
+/// the client has to bulk sync and continue from the snapshot's seq.
+pub const RESYNC_REQUIRED_ERROR: &str = "resync_required";
+
 pub fn router() -> Router<AppState> {
     Router::new().route("/activity", get(get_activity_stream))
 }
@@ -26,6 +33,10 @@ pub struct ActivityQuery {
     pub after: Option<i64>,
     /// Maximum number of events to return
     pub limit: Option<i64>,
+    /// Only events about this shared task
+    pub task_id: Option<Uuid>,
+    /// Only these event types, comma separated
+    pub event_types: Option<String>,
 }
 
 #[instrument(
@@ -45,15 +56,34 @@ async fn get_activity_stream(
         .clamp(1, config.activity_max_limit);
     let after = params.after;
     let project_id = params.project_id;
+    let filter = ActivityFilter::from_query(params.task_id, params.event_types.as_deref());
 
-    let _organization_id = match ensure_project_access(state.pool(), ctx.user.id, project_id).await
-    {
+    let read = state.read_pools().read_activity(project_id, after).await;
+    let _organization_id = match ensure_project_access(read.pool(), ctx.user.id, project_id).await {
         Ok(org_id) => org_id,
         Err(error) => return error.into_response(),
     };
 
-    let repo = ActivityRepository::new(state.pool());
-    match repo.fetch_since(project_id, after, limit).await {
+    let repo = ActivityRepository::new(read.pool());
+    if let Some(after) = after {
+        match repo.pruned_through(project_id).await {
+            Ok(Some(pruned_through)) if after < pruned_through => {
+                let response =
+                    ErrorResponse::new(StatusCode::GONE, RESYNC_REQUIRED_ERROR).into_response();
+                return with_served_by(read.source(), response);
+            }
+            Ok(_) => {}
+            Err(error) => {
+                tracing::error!(?error, "failed to load activity retention");
+                return ErrorResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "failed to load activity stream",
+                )
+                .into_response();
+            }
+        }
+    }
+    let response = match repo.fetch_since(project_id, after, limit, &filter).await {
         Ok(events) => (StatusCode::OK, Json(ActivityResponse { data: events })).into_response(),
         Err(error) => {
             tracing::error!(?error, "failed to load activity stream");
@@ -63,5 +93,6 @@ async fn get_activity_stream(
             )
             .into_response()
         }
-    }
+    };
+    with_served_by(read.source(), response)
 }
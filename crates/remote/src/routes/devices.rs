@@ -0,0 +1,97 @@
+use axum::{
+    Json, Router,
+    extract::{DefaultBodyLimit, Extension, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use uuid::Uuid;
+
+use super::{SMALL_BODY_LIMIT, error::ErrorResponse, organization_members::ensure_project_access};
+use crate::{
+    AppState,
+    auth::RequestContext,
+    db::{activity::ActivityRepository, devices::DeviceCursorRepository},
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new().route(
+        "/devices/ack",
+        post(ack_device_cursor).layer(DefaultBodyLimit::max(SMALL_BODY_LIMIT)),
+    )
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeviceAckRequest {
+    /// Generated once by the local app and kept next to its credentials
+    pub device_id: Uuid,
+    pub project_id: Uuid,
+    /// Highest activity seq the device has applied
+    pub last_seq: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeviceAckResponse {
+    /// The device's cursor after the ack; it never moves backwards
+    pub last_seq: i64,
+    /// Activity after the cursor has been pruned, so the device must bulk
+    /// sync
+    pub resync_required: bool,
+}
+
+#[instrument(
+    name = "devices.ack",
+    skip(state, ctx, payload),
+    fields(user_id = %ctx.user.id, device_id = %payload.device_id, project_id = %payload.project_id)
+)]
+async fn ack_device_cursor(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Json(payload): Json<DeviceAckRequest>,
+) -> Response {
+    let pool = state.pool();
+    if let Err(error) = ensure_project_access(pool, ctx.user.id, payload.project_id).await {
+        return error.into_response();
+    }
+
+    let last_seq = match DeviceCursorRepository::new(pool)
+        .ack(
+            ctx.user.id,
+            payload.device_id,
+            payload.project_id,
+            payload.last_seq,
+        )
+        .await
+    {
+        Ok(last_seq) => last_seq,
+        Err(error) => {
+            tracing::error!(?error, "failed to store device cursor");
+            return ErrorResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to store device cursor",
+            )
+            .into_response();
+        }
+    };
+    let resync_required = match ActivityRepository::new(pool)
+        .pruned_through(payload.project_id)
+        .await
+    {
+        Ok(pruned_through) => pruned_through.is_some_and(|pruned| last_seq < pruned),
+        Err(error) => {
+            tracing::warn!(?error, "failed to load activity retention");
+            false
+        }
+    };
+
+    (
+        StatusCode::OK,
+        Json(DeviceAckResponse {
+            last_seq,
+            resync_required,
+        }),
+    )
+        .into_response()
+}
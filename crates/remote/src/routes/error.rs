@@ -5,6 +5,7 @@ use axum::{
 };
 use serde_json::json;
 
+use super::organization_members::INVALID_EMAIL_DOMAIN;
 use crate::db::{identity_errors::IdentityError, projects::ProjectError, tasks::SharedTaskError};
 
 #[derive(Debug)]
@@ -93,6 +94,10 @@ pub(crate) fn identity_error_response(error: IdentityError, message: &str) -> Re
         IdentityError::OrganizationConflict(msg) => {
             (StatusCode::CONFLICT, Json(json!({ "error": msg })))
         }
+        IdentityError::EmailDomainNotAllowed => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": INVALID_EMAIL_DOMAIN })),
+        ),
         IdentityError::Database(err) => {
             tracing::error!(?err, "identity sync failed");
             (
@@ -0,0 +1,165 @@
+//! Unauthenticated probes for load balancers. Liveness only says the
+//! process serves requests; readiness checks what a request depends on.
+
+use std::time::{Duration, Instant};
+
+use axum::{
+    Json, Router,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::{AppState, activity::ActivityBroker, auth::JwtService};
+
+/// Longest a single readiness check may take before it counts as failed
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/healthz", get(liveness))
+        .route("/readyz", get(readiness))
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthCheck {
+    pub name: &'static str,
+    pub ok: bool,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub checks: Vec<HealthCheck>,
+}
+
+async fn liveness() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+async fn readiness(State(state): State<AppState>) -> Response {
+    let report = check_readiness(state.pool(), state.broker(), &state.jwt()).await;
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report)).into_response()
+}
+
+pub async fn check_readiness(
+    pool: &PgPool,
+    broker: &ActivityBroker,
+    jwt: &JwtService,
+) -> ReadinessReport {
+    let checks = vec![
+        run_check("database", async {
+            match tokio::time::timeout(CHECK_TIMEOUT, pool.acquire()).await {
+                Ok(Ok(_connection)) => Ok(()),
+                Ok(Err(error)) => Err(error.to_string()),
+                Err(_) => Err(format!("no connection within {CHECK_TIMEOUT:?}")),
+            }
+        })
+        .await,
+        run_check("activity_broker", async {
+            if broker.is_listening() {
+                Ok(())
+            } else {
+                Err("activity listener is not connected".to_string())
+            }
+        })
+        .await,
+        run_check("jwt_secret", async {
+            jwt.check_secrets().map_err(|error| error.to_string())
+        })
+        .await,
+    ];
+
+    for check in checks.iter().filter(|check| !check.ok) {
+        tracing::warn!(
+            check = check.name,
+            error = check.error.as_deref().unwrap_or_default(),
+            "readiness check failed"
+        );
+    }
+    ReadinessReport {
+        ready: checks.iter().all(|check| check.ok),
+        checks,
+    }
+}
+
+async fn run_check(
+    name: &'static str,
+    check: impl Future<Output = Result<(), String>>,
+) -> HealthCheck {
+    let started = Instant::now();
+    let result = check.await;
+    HealthCheck {
+        name,
+        ok: result.is_ok(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: result.err(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::SecretString;
+    use sqlx::postgres::PgPoolOptions;
+
+    use super::*;
+
+    const SECRET: &str = "c2VjcmV0LXNlY3JldC1zZWNyZXQtc2VjcmV0LXNlY3JldC1zZWNyZXQ=";
+
+    fn failing(report: &ReadinessReport) -> Vec<&'static str> {
+        report
+            .checks
+            .iter()
+            .filter(|check| !check.ok)
+            .map(|check| check.name)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn closed_pool_and_lost_listener_fail_readiness() {
+        let pool = PgPoolOptions::new()
+            .connect_lazy("postgres://vk@127.0.0.1:1/vk")
+            .unwrap();
+        let broker = ActivityBroker::default();
+        let jwt = JwtService::new(vec![SecretString::new(SECRET.into())]);
+
+        broker.set_listening(true);
+        pool.close().await;
+        let report = check_readiness(&pool, &broker, &jwt).await;
+        assert!(!report.ready);
+        assert_eq!(failing(&report), ["database"]);
+
+        broker.set_listening(false);
+        let report = check_readiness(&pool, &broker, &jwt).await;
+        assert_eq!(failing(&report), ["database", "activity_broker"]);
+    }
+
+    #[tokio::test]
+    async fn undecodable_jwt_secret_fails_readiness() {
+        let pool = PgPoolOptions::new()
+            .connect_lazy("postgres://vk@127.0.0.1:1/vk")
+            .unwrap();
+        pool.close().await;
+        let broker = ActivityBroker::default();
+        broker.set_listening(true);
+
+        let jwt = JwtService::new(vec![SecretString::new("not base64!".into())]);
+        let report = check_readiness(&pool, &broker, &jwt).await;
+        assert!(failing(&report).contains(&"jwt_secret"));
+
+        let jwt = JwtService::new(Vec::new());
+        let report = check_readiness(&pool, &broker, &jwt).await;
+        assert!(failing(&report).contains(&"jwt_secret"));
+    }
+}
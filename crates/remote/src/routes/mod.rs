@@ -1,7 +1,9 @@
 use axum::{
     Router,
-    http::{Request, header::HeaderName},
+    extract::DefaultBodyLimit,
+    http::{HeaderValue, Request, header::HeaderName},
     middleware,
+    response::Response,
     routing::get,
 };
 use tower_http::{
@@ -12,17 +14,27 @@ use tower_http::{
 };
 use tracing::{Level, field};
 
-use crate::{AppState, auth::require_session};
+use self::rate_limit::RateLimiter;
+use crate::{AppState, auth::require_session, db::replicas::PoolSource};
 
 pub mod activity;
+pub mod devices;
 mod error;
+mod health;
 mod identity;
 mod oauth;
 pub(crate) mod organization_members;
 mod organizations;
 mod projects;
+mod rate_limit;
+mod sessions;
 pub mod tasks;
 mod tokens;
+mod webhooks;
+
+/// Body cap for routes whose payload is a few ids and short strings; task and
+/// project payloads only get the group-wide limits
+pub(crate) const SMALL_BODY_LIMIT: usize = 4 * 1024;
 
 pub fn router(state: AppState) -> Router {
     let trace_layer = TraceLayer::new_for_http()
@@ -45,21 +57,47 @@ pub fn router(state: AppState) -> Router {
         .on_response(DefaultOnResponse::new().level(Level::INFO))
         .on_failure(DefaultOnFailure::new().level(Level::ERROR));
 
+    let limits = &state.config().rate_limit;
+    let public_limiter = RateLimiter::new(
+        limits.public_requests,
+        limits.window,
+        limits.trust_forwarded_for,
+    );
+    let user_limiter = RateLimiter::new(
+        limits.user_requests,
+        limits.window,
+        limits.trust_forwarded_for,
+    );
+
     let v1_public = Router::<AppState>::new()
-        .route("/health", get(health))
         .merge(oauth::public_router())
         .merge(organization_members::public_router())
-        .merge(tokens::public_router());
+        .merge(tokens::public_router())
+        .layer(DefaultBodyLimit::max(limits.public_body_limit))
+        .layer(middleware::from_fn_with_state(
+            public_limiter,
+            rate_limit::limit_by_ip,
+        ))
+        // Added after the limits so load balancer probes are never refused
+        .route("/health", get(health));
 
     let v1_protected = Router::<AppState>::new()
         .merge(identity::router())
         .merge(activity::router())
+        .merge(devices::router())
         .merge(projects::router())
         .merge(tasks::router())
         .merge(organizations::router())
         .merge(organization_members::protected_router())
         .merge(oauth::protected_router())
+        .merge(sessions::router())
+        .merge(webhooks::router())
         .merge(crate::ws::router())
+        .layer(DefaultBodyLimit::max(limits.body_limit))
+        .layer(middleware::from_fn_with_state(
+            user_limiter,
+            rate_limit::limit_by_user,
+        ))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             require_session,
@@ -70,6 +108,7 @@ pub fn router(state: AppState) -> Router {
         ServeDir::new(static_dir).fallback(ServeFile::new(format!("{static_dir}/index.html")));
 
     Router::<AppState>::new()
+        .merge(health::router())
         .nest("/v1", v1_public)
         .nest("/v1", v1_protected)
         .fallback_service(spa)
@@ -88,3 +127,13 @@ pub fn router(state: AppState) -> Router {
 async fn health() -> &'static str {
     "ok"
 }
+
+/// Debug header naming the database pool that served a read
+const SERVED_BY_HEADER: HeaderName = HeaderName::from_static("x-served-by");
+
+pub(crate) fn with_served_by(source: PoolSource, mut response: Response) -> Response {
+    if let Ok(value) = HeaderValue::from_str(&source.to_string()) {
+        response.headers_mut().insert(SERVED_BY_HEADER, value);
+    }
+    response
+}
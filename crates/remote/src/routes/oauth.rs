@@ -2,30 +2,43 @@ use std::borrow::Cow;
 
 use axum::{
     Json, Router,
-    extract::{Extension, Path, Query, State},
-    http::StatusCode,
+    extract::{DefaultBodyLimit, Extension, Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
     response::{IntoResponse, Redirect, Response},
     routing::{get, post},
 };
 use serde::Deserialize;
 use tracing::warn;
 use url::Url;
-use utils::api::oauth::{
-    HandoffInitRequest, HandoffInitResponse, HandoffRedeemRequest, HandoffRedeemResponse,
-    ProfileResponse, ProviderProfile,
+use utils::{
+    api::oauth::{
+        HandoffInitRequest, HandoffInitResponse, HandoffRedeemRequest, HandoffRedeemResponse,
+        ProfileResponse, ProviderProfile,
+    },
+    text::truncate_to_char_boundary,
 };
 use uuid::Uuid;
 
+use super::SMALL_BODY_LIMIT;
 use crate::{
     AppState,
     auth::{CallbackResult, HandoffError, RequestContext},
     db::{oauth::OAuthHandoffError, oauth_accounts::OAuthAccountRepository},
 };
 
+/// Longest user agent stored with a session
+const MAX_USER_AGENT_LEN: usize = 512;
+
 pub fn public_router() -> Router<AppState> {
     Router::new()
-        .route("/oauth/web/init", post(web_init))
-        .route("/oauth/web/redeem", post(web_redeem))
+        .route(
+            "/oauth/web/init",
+            post(web_init).layer(DefaultBodyLimit::max(SMALL_BODY_LIMIT)),
+        )
+        .route(
+            "/oauth/web/redeem",
+            post(web_redeem).layer(DefaultBodyLimit::max(SMALL_BODY_LIMIT)),
+        )
         .route("/oauth/{provider}/start", get(authorize_start))
         .route("/oauth/{provider}/callback", get(authorize_callback))
 }
@@ -120,8 +133,15 @@ pub async fn authorize_callback(
     State(state): State<AppState>,
     Path(provider): Path<String>,
     Query(query): Query<CallbackQuery>,
+    headers: HeaderMap,
 ) -> Response {
     let handoff = state.handoff();
+    // The callback is opened in the user's browser, so this names the device
+    // the session belongs to
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| truncate_to_char_boundary(value, MAX_USER_AGENT_LEN));
 
     match handoff
         .handle_callback(
@@ -129,6 +149,7 @@ pub async fn authorize_callback(
             query.state.as_deref(),
             query.code.as_deref(),
             query.error.as_deref(),
+            user_agent,
         )
         .await
     {
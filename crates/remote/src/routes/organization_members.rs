@@ -1,12 +1,13 @@
 use axum::{
     Json, Router,
-    extract::{Path, State},
+    extract::{DefaultBodyLimit, Path, State},
     http::StatusCode,
     response::IntoResponse,
     routing::{delete, get, patch, post},
 };
 use chrono::{Duration, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use sqlx::PgPool;
 use tracing::warn;
 use utils::api::organizations::{
@@ -15,11 +16,15 @@ use utils::api::organizations::{
 };
 use uuid::Uuid;
 
-use super::error::{ErrorResponse, membership_error};
+use super::{
+    SMALL_BODY_LIMIT,
+    error::{ErrorResponse, membership_error},
+};
 use crate::{
     AppState,
     auth::RequestContext,
     db::{
+        audit_log::{self, AuditAction},
         identity_errors::IdentityError,
         invitations::{Invitation, InvitationRepository},
         organization_members::{self, MemberRole},
@@ -37,12 +42,12 @@ pub fn protected_router() -> Router<AppState> {
     Router::new()
         .route(
             "/organizations/{org_id}/invitations",
-            post(create_invitation),
+            post(create_invitation).layer(DefaultBodyLimit::max(SMALL_BODY_LIMIT)),
         )
         .route("/organizations/{org_id}/invitations", get(list_invitations))
         .route(
             "/organizations/{org_id}/invitations/revoke",
-            post(revoke_invitation),
+            post(revoke_invitation).layer(DefaultBodyLimit::max(SMALL_BODY_LIMIT)),
         )
         .route("/invitations/{token}/accept", post(accept_invitation))
         .route("/organizations/{org_id}/members", get(list_members))
@@ -52,7 +57,7 @@ pub fn protected_router() -> Router<AppState> {
         )
         .route(
             "/organizations/{org_id}/members/{user_id}/role",
-            patch(update_member_role),
+            patch(update_member_role).layer(DefaultBodyLimit::max(SMALL_BODY_LIMIT)),
         )
 }
 
@@ -88,6 +93,9 @@ pub struct AcceptInvitationResponse {
     pub role: MemberRole,
 }
 
+/// Error code returned when an invitee's email domain is not allowed.
+pub const INVALID_EMAIL_DOMAIN: &str = "invalid_email_domain";
+
 pub async fn create_invitation(
     State(state): State<AppState>,
     axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
@@ -118,6 +126,9 @@ pub async fn create_invitation(
                 ErrorResponse::new(StatusCode::FORBIDDEN, "Admin access required")
             }
             IdentityError::InvitationError(msg) => ErrorResponse::new(StatusCode::BAD_REQUEST, msg),
+            IdentityError::EmailDomainNotAllowed => {
+                ErrorResponse::new(StatusCode::BAD_REQUEST, INVALID_EMAIL_DOMAIN)
+            }
             _ => ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"),
         })?;
 
@@ -240,10 +251,13 @@ pub async fn accept_invitation(
     let invitation_repo = InvitationRepository::new(&state.pool);
 
     let (org, role) = invitation_repo
-        .accept_invitation(&token, user.id)
+        .accept_invitation(&token, user.id, &user.email)
         .await
         .map_err(|e| match e {
             IdentityError::InvitationError(msg) => ErrorResponse::new(StatusCode::BAD_REQUEST, msg),
+            IdentityError::EmailDomainNotAllowed => {
+                ErrorResponse::new(StatusCode::BAD_REQUEST, INVALID_EMAIL_DOMAIN)
+            }
             IdentityError::NotFound => {
                 ErrorResponse::new(StatusCode::NOT_FOUND, "Invitation not found")
             }
@@ -380,6 +394,17 @@ pub async fn remove_member(
     .await
     .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
 
+    audit_log::record(
+        &mut *tx,
+        org_id,
+        user.id,
+        AuditAction::MemberRemoved,
+        Some(user_id),
+        json!({ "role": target.role }),
+    )
+    .await
+    .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
+
     tx.commit()
         .await
         .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
@@ -479,6 +504,17 @@ pub async fn update_member_role(
     .await
     .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
 
+    audit_log::record(
+        &mut *tx,
+        org_id,
+        user.id,
+        AuditAction::MemberRoleChanged,
+        Some(user_id),
+        json!({ "from": target.role, "to": payload.role }),
+    )
+    .await
+    .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
+
     tx.commit()
         .await
         .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
@@ -1,32 +1,54 @@
 use axum::{
     Json, Router,
-    extract::{Path, State},
+    extract::{DefaultBodyLimit, Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
     routing::{delete, get, patch, post},
 };
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use utils::api::organizations::{
     CreateOrganizationRequest, CreateOrganizationResponse, GetOrganizationResponse,
-    ListOrganizationsResponse, MemberRole, UpdateOrganizationRequest,
+    ListAuditLogResponse, ListOrganizationsResponse, MemberRole, UpdateOrganizationRequest,
 };
 use uuid::Uuid;
 
-use super::error::ErrorResponse;
+use super::{SMALL_BODY_LIMIT, error::ErrorResponse, organization_members::ensure_admin_access};
 use crate::{
     AppState,
     auth::RequestContext,
     db::{
-        identity_errors::IdentityError, organization_members, organizations::OrganizationRepository,
+        audit_log::AuditLogRepository,
+        identity_errors::IdentityError,
+        organization_members,
+        organizations::{OrganizationRepository, normalize_email_domains},
     },
 };
 
 pub fn router() -> Router<AppState> {
     Router::new()
-        .route("/organizations", post(create_organization))
+        .route(
+            "/organizations",
+            post(create_organization).layer(DefaultBodyLimit::max(SMALL_BODY_LIMIT)),
+        )
         .route("/organizations", get(list_organizations))
         .route("/organizations/{org_id}", get(get_organization))
-        .route("/organizations/{org_id}", patch(update_organization))
+        .route(
+            "/organizations/{org_id}",
+            patch(update_organization).layer(DefaultBodyLimit::max(SMALL_BODY_LIMIT)),
+        )
         .route("/organizations/{org_id}", delete(delete_organization))
+        .route("/organizations/{org_id}/audit-log", get(list_audit_log))
+}
+
+const AUDIT_LOG_DEFAULT_LIMIT: i64 = 50;
+const AUDIT_LOG_MAX_LIMIT: i64 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    /// Only entries created before this time, from a previous `next_cursor`
+    pub before: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
 }
 
 pub async fn create_organization(
@@ -149,20 +171,38 @@ pub async fn update_organization(
         ));
     }
 
+    let allowed_email_domains = match &payload.allowed_email_domains {
+        Some(domains) => Some(normalize_email_domains(domains).ok_or_else(|| {
+            ErrorResponse::new(
+                StatusCode::BAD_REQUEST,
+                "Allowed email domains must look like example.com",
+            )
+        })?),
+        None => None,
+    };
+
     let org_repo = OrganizationRepository::new(&state.pool);
+    let map_err = |e| match e {
+        IdentityError::PermissionDenied => {
+            ErrorResponse::new(StatusCode::FORBIDDEN, "Admin access required")
+        }
+        IdentityError::NotFound => {
+            ErrorResponse::new(StatusCode::NOT_FOUND, "Organization not found")
+        }
+        _ => ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"),
+    };
 
-    let organization = org_repo
+    let mut organization = org_repo
         .update_organization_name(org_id, ctx.user.id, name)
         .await
-        .map_err(|e| match e {
-            IdentityError::PermissionDenied => {
-                ErrorResponse::new(StatusCode::FORBIDDEN, "Admin access required")
-            }
-            IdentityError::NotFound => {
-                ErrorResponse::new(StatusCode::NOT_FOUND, "Organization not found")
-            }
-            _ => ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"),
-        })?;
+        .map_err(map_err)?;
+
+    if let Some(domains) = allowed_email_domains {
+        organization = org_repo
+            .set_allowed_email_domains(org_id, ctx.user.id, &domains)
+            .await
+            .map_err(map_err)?;
+    }
 
     Ok(Json(organization))
 }
@@ -192,3 +232,32 @@ pub async fn delete_organization(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+pub async fn list_audit_log(
+    State(state): State<AppState>,
+    axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
+    Path(org_id): Path<Uuid>,
+    Query(params): Query<AuditLogQuery>,
+) -> Result<impl IntoResponse, ErrorResponse> {
+    ensure_admin_access(&state.pool, org_id, ctx.user.id).await?;
+
+    let limit = params
+        .limit
+        .unwrap_or(AUDIT_LOG_DEFAULT_LIMIT)
+        .clamp(1, AUDIT_LOG_MAX_LIMIT);
+    let entries = AuditLogRepository::new(&state.pool)
+        .list(org_id, params.before, limit)
+        .await
+        .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
+
+    let next_cursor = if entries.len() as i64 == limit {
+        entries.last().map(|entry| entry.created_at)
+    } else {
+        None
+    };
+
+    Ok(Json(ListAuditLogResponse {
+        entries,
+        next_cursor,
+    }))
+}
@@ -2,6 +2,7 @@ use axum::{
     Json, Router,
     extract::{Extension, Path, Query, State},
     http::StatusCode,
+    response::{IntoResponse, Response},
     routing::get,
 };
 use serde::Deserialize;
@@ -10,7 +11,7 @@ use tracing::instrument;
 use utils::api::projects::{ListProjectsResponse, RemoteProject};
 use uuid::Uuid;
 
-use super::{error::ErrorResponse, organization_members::ensure_member_access};
+use super::{error::ErrorResponse, organization_members::ensure_member_access, with_served_by};
 use crate::{
     AppState,
     auth::RequestContext,
@@ -45,11 +46,12 @@ async fn list_projects(
     State(state): State<AppState>,
     Extension(ctx): Extension<RequestContext>,
     Query(params): Query<ProjectsQuery>,
-) -> Result<Json<ListProjectsResponse>, ErrorResponse> {
+) -> Result<Response, ErrorResponse> {
     let target_org = params.organization_id;
-    ensure_member_access(state.pool(), target_org, ctx.user.id).await?;
+    let read = state.read_pools().read().await;
+    ensure_member_access(read.pool(), target_org, ctx.user.id).await?;
 
-    let projects = match ProjectRepository::list_by_organization(state.pool(), target_org).await {
+    let projects = match ProjectRepository::list_by_organization(read.pool(), target_org).await {
         Ok(rows) => rows.into_iter().map(to_remote_project).collect(),
         Err(error) => {
             tracing::error!(?error, org_id = %target_org, "failed to list remote projects");
@@ -60,7 +62,10 @@ async fn list_projects(
         }
     };
 
-    Ok(Json(ListProjectsResponse { projects }))
+    Ok(with_served_by(
+        read.source(),
+        Json(ListProjectsResponse { projects }).into_response(),
+    ))
 }
 
 #[instrument(
@@ -72,8 +77,9 @@ async fn get_project(
     State(state): State<AppState>,
     Extension(ctx): Extension<RequestContext>,
     Path(project_id): Path<Uuid>,
-) -> Result<Json<RemoteProject>, ErrorResponse> {
-    let record = ProjectRepository::fetch_by_id(state.pool(), project_id)
+) -> Result<Response, ErrorResponse> {
+    let read = state.read_pools().read().await;
+    let record = ProjectRepository::fetch_by_id(read.pool(), project_id)
         .await
         .map_err(|error| {
             tracing::error!(?error, %project_id, "failed to load project");
@@ -81,9 +87,12 @@ async fn get_project(
         })?
         .ok_or_else(|| ErrorResponse::new(StatusCode::NOT_FOUND, "project not found"))?;
 
-    ensure_member_access(state.pool(), record.organization_id, ctx.user.id).await?;
+    ensure_member_access(read.pool(), record.organization_id, ctx.user.id).await?;
 
-    Ok(Json(to_remote_project(record)))
+    Ok(with_served_by(
+        read.source(),
+        Json(to_remote_project(record)).into_response(),
+    ))
 }
 
 #[instrument(
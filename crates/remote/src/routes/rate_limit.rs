@@ -0,0 +1,219 @@
+//! Fixed-window request limits: per client IP on the unauthenticated routes,
+//! per user on the authenticated ones.
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, State},
+    http::{HeaderValue, Request, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::error::ErrorResponse;
+use crate::auth::RequestContext;
+
+/// Clients tracked before expired windows are swept out
+const PRUNE_THRESHOLD: usize = 4096;
+
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    limit: u32,
+    window: Duration,
+    trust_forwarded_for: bool,
+    windows: Mutex<HashMap<String, Window>>,
+}
+
+#[derive(Debug)]
+struct Window {
+    started: Instant,
+    count: u32,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: u32, window: Duration, trust_forwarded_for: bool) -> Arc<Self> {
+        Arc::new(Self {
+            limit,
+            window,
+            trust_forwarded_for,
+            windows: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Count a request from `key`. Once the limit is reached, returns how
+    /// long until its window resets.
+    fn check(&self, key: String) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap();
+        if windows.len() >= PRUNE_THRESHOLD {
+            windows.retain(|_, window| now.duration_since(window.started) < self.window);
+        }
+
+        let window = windows.entry(key).or_insert(Window {
+            started: now,
+            count: 0,
+        });
+        let elapsed = now.duration_since(window.started);
+        if elapsed >= self.window {
+            window.started = now;
+            window.count = 0;
+        } else if window.count >= self.limit {
+            return Err(self.window - elapsed);
+        }
+        window.count += 1;
+        Ok(())
+    }
+
+    /// With a trusted proxy in front, the right-most `X-Forwarded-For` entry
+    /// is the address that proxy saw; anything to its left came from the
+    /// client and can be made up.
+    fn client_ip(&self, req: &Request<Body>) -> Option<IpAddr> {
+        if self.trust_forwarded_for
+            && let Some(ip) = req
+                .headers()
+                .get_all("x-forwarded-for")
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .next_back()
+                .and_then(|ip| ip.trim().parse().ok())
+        {
+            return Some(ip);
+        }
+        req.extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+    }
+
+    fn ip_key(&self, req: &Request<Body>) -> String {
+        match self.client_ip(req) {
+            Some(ip) => format!("ip:{ip}"),
+            None => "ip:unknown".to_string(),
+        }
+    }
+}
+
+pub(crate) async fn limit_by_ip(
+    State(limiter): State<Arc<RateLimiter>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    match limiter.check(limiter.ip_key(&req)) {
+        Ok(()) => next.run(req).await,
+        Err(retry_after) => too_many_requests(retry_after),
+    }
+}
+
+/// Must run after `require_session`, which attaches the user. Falls back to
+/// the client IP if no user is attached.
+pub(crate) async fn limit_by_user(
+    State(limiter): State<Arc<RateLimiter>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let key = match req.extensions().get::<RequestContext>() {
+        Some(ctx) => format!("user:{}", ctx.user.id),
+        None => limiter.ip_key(&req),
+    };
+    match limiter.check(key) {
+        Ok(()) => next.run(req).await,
+        Err(retry_after) => too_many_requests(retry_after),
+    }
+}
+
+fn too_many_requests(retry_after: Duration) -> Response {
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    let mut response = ErrorResponse::new(
+        StatusCode::TOO_MANY_REQUESTS,
+        "too many requests, please retry later",
+    )
+    .into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(secs.max(1)));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, middleware, routing::post};
+
+    use super::*;
+
+    async fn serve(limiter: Arc<RateLimiter>) -> String {
+        let app = Router::new()
+            .route("/v1/oauth/web/init", post(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(limiter, limit_by_ip));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .unwrap();
+        });
+        format!("http://{addr}/v1/oauth/web/init")
+    }
+
+    #[tokio::test]
+    async fn init_is_limited_until_the_window_resets() {
+        let window = Duration::from_millis(500);
+        let url = serve(RateLimiter::new(3, window, false)).await;
+        let client = reqwest::Client::new();
+
+        for _ in 0..3 {
+            let response = client.post(&url).send().await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        for _ in 0..5 {
+            let response = client.post(&url).send().await.unwrap();
+            assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+            assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+            let body: serde_json::Value = response.json().await.unwrap();
+            assert_eq!(body["error"], "too many requests, please retry later");
+        }
+
+        tokio::time::sleep(window).await;
+        let response = client.post(&url).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn forwarded_clients_are_limited_separately() {
+        let url = serve(RateLimiter::new(1, Duration::from_secs(60), true)).await;
+        let client = reqwest::Client::new();
+        let send = |ip: &'static str| client.post(&url).header("x-forwarded-for", ip).send();
+
+        assert_eq!(send("203.0.113.7").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(
+            send("203.0.113.7").await.unwrap().status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(send("198.51.100.2").await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn spoofed_forwarded_entries_do_not_reset_the_limit() {
+        let url = serve(RateLimiter::new(1, Duration::from_secs(60), true)).await;
+        let client = reqwest::Client::new();
+        let send = |ip: &'static str| client.post(&url).header("x-forwarded-for", ip).send();
+
+        // The proxy appends the real client address after whatever was sent
+        assert_eq!(
+            send("10.0.0.1, 203.0.113.7").await.unwrap().status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            send("10.0.0.2, 203.0.113.7").await.unwrap().status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+}
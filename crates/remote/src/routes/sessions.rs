@@ -0,0 +1,73 @@
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get},
+};
+use tracing::warn;
+use utils::api::oauth::{AuthSessionSummary, ListSessionsResponse, RevokeSessionResponse};
+use uuid::Uuid;
+
+use super::error::ErrorResponse;
+use crate::{
+    AppState,
+    auth::RequestContext,
+    db::auth::{AuthSessionError, AuthSessionRepository},
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/sessions", get(list_sessions))
+        .route("/sessions/{session_id}", delete(revoke_session))
+}
+
+async fn list_sessions(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+) -> Result<Json<ListSessionsResponse>, ErrorResponse> {
+    let sessions = AuthSessionRepository::new(state.pool())
+        .list_active_for_user(ctx.user.id)
+        .await
+        .map_err(|error| {
+            warn!(?error, user_id = %ctx.user.id, "failed to list auth sessions");
+            ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "internal server error")
+        })?;
+
+    Ok(Json(ListSessionsResponse {
+        sessions: sessions
+            .into_iter()
+            .map(|session| AuthSessionSummary {
+                id: session.id,
+                created_at: session.created_at,
+                last_used_at: session.last_used_at,
+                user_agent: session.user_agent,
+                current: session.id == ctx.session_id,
+            })
+            .collect(),
+    }))
+}
+
+/// Websockets opened with the revoked session are closed at their next auth
+/// check.
+async fn revoke_session(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path(session_id): Path<Uuid>,
+) -> Response {
+    let repo = AuthSessionRepository::new(state.pool());
+    match repo.revoke_for_user(session_id, ctx.user.id).await {
+        Ok(()) => Json(RevokeSessionResponse {
+            current: session_id == ctx.session_id,
+        })
+        .into_response(),
+        Err(AuthSessionError::NotFound) => {
+            ErrorResponse::new(StatusCode::NOT_FOUND, "session not found").into_response()
+        }
+        Err(error) => {
+            warn!(?error, %session_id, "failed to revoke auth session");
+            ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "internal server error")
+                .into_response()
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use axum::{
     Json, Router,
-    extract::{Extension, Path, Query, State},
+    extract::{DefaultBodyLimit, Extension, Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Response},
     routing::{delete, get, patch, post},
@@ -11,8 +11,10 @@ use tracing::{Span, instrument};
 use uuid::Uuid;
 
 use super::{
+    SMALL_BODY_LIMIT,
     error::{identity_error_response, task_error_response},
     organization_members::{ensure_project_access, ensure_task_access},
+    with_served_by,
 };
 use crate::{
     AppState,
@@ -21,8 +23,8 @@ use crate::{
         organization_members,
         tasks::{
             AssignTaskData, CreateSharedTaskData, DeleteTaskData, SharedTask, SharedTaskError,
-            SharedTaskRepository, SharedTaskWithUser, TaskStatus, UpdateSharedTaskData,
-            ensure_text_size,
+            SharedTaskRepository, SharedTaskWithUser, TaskDigestEntry, TaskStatus,
+            UpdateSharedTaskData, ensure_text_size,
         },
         users::{UserData, UserRepository},
     },
@@ -31,10 +33,18 @@ use crate::{
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/tasks/bulk", get(bulk_shared_tasks))
+        .route("/tasks/digest", get(task_digest))
         .route("/tasks", post(create_shared_task))
+        .route("/tasks/{task_id}", get(get_shared_task))
         .route("/tasks/{task_id}", patch(update_shared_task))
-        .route("/tasks/{task_id}", delete(delete_shared_task))
-        .route("/tasks/{task_id}/assign", post(assign_task))
+        .route(
+            "/tasks/{task_id}",
+            delete(delete_shared_task).layer(DefaultBodyLimit::max(SMALL_BODY_LIMIT)),
+        )
+        .route(
+            "/tasks/{task_id}/assign",
+            post(assign_task).layer(DefaultBodyLimit::max(SMALL_BODY_LIMIT)),
+        )
 }
 
 #[derive(Debug, Deserialize)]
@@ -52,7 +62,13 @@ pub async fn bulk_shared_tasks(
     Extension(ctx): Extension<RequestContext>,
     Query(query): Query<BulkTasksQuery>,
 ) -> Response {
-    let pool = state.pool();
+    // The snapshot's latest_seq becomes the client's activity cursor, so it
+    // must not trail events already published to that client
+    let read = state
+        .read_pools()
+        .read_activity(query.project_id, None)
+        .await;
+    let pool = read.pool();
     let _organization_id = match ensure_project_access(pool, ctx.user.id, query.project_id).await {
         Ok(org_id) => {
             Span::current().record("org_id", format_args!("{org_id}"));
@@ -62,7 +78,7 @@ pub async fn bulk_shared_tasks(
     };
 
     let repo = SharedTaskRepository::new(pool);
-    match repo.bulk_fetch(query.project_id).await {
+    let response = match repo.bulk_fetch(query.project_id).await {
         Ok(snapshot) => (
             StatusCode::OK,
             Json(BulkSharedTasksResponse {
@@ -83,9 +99,91 @@ pub async fn bulk_shared_tasks(
             }
             other => task_error_response(other, "failed to load shared tasks"),
         },
+    };
+    with_served_by(read.source(), response)
+}
+
+const DEFAULT_DIGEST_PAGE_LIMIT: u32 = 1000;
+const MAX_DIGEST_PAGE_LIMIT: u32 = 5000;
+
+#[derive(Debug, Deserialize)]
+pub struct TaskDigestQuery {
+    pub project_id: Uuid,
+    pub after: Option<Uuid>,
+    pub limit: Option<u32>,
+}
+
+#[instrument(
+    name = "tasks.task_digest",
+    skip(state, ctx, query),
+    fields(user_id = %ctx.user.id, project_id = %query.project_id, org_id = tracing::field::Empty)
+)]
+pub async fn task_digest(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Query(query): Query<TaskDigestQuery>,
+) -> Response {
+    let pool = state.pool();
+    let _organization_id = match ensure_project_access(pool, ctx.user.id, query.project_id).await {
+        Ok(org_id) => {
+            Span::current().record("org_id", format_args!("{org_id}"));
+            org_id
+        }
+        Err(error) => return error.into_response(),
+    };
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_DIGEST_PAGE_LIMIT)
+        .clamp(1, MAX_DIGEST_PAGE_LIMIT);
+
+    let repo = SharedTaskRepository::new(pool);
+    match repo
+        .digest_page(query.project_id, query.after, limit as i64)
+        .await
+    {
+        Ok(page) => (
+            StatusCode::OK,
+            Json(TaskDigestResponse {
+                tasks: page.tasks,
+                deleted_task_ids: page.deleted_task_ids,
+                next_after: page.next_after,
+            }),
+        )
+            .into_response(),
+        Err(error) => task_error_response(error, "failed to load shared task digest"),
     }
 }
 
+#[instrument(
+    name = "tasks.get_shared_task",
+    skip(state, ctx),
+    fields(user_id = %ctx.user.id, task_id = %task_id, org_id = tracing::field::Empty)
+)]
+pub async fn get_shared_task(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path(task_id): Path<Uuid>,
+) -> Response {
+    let read = state.read_pools().read().await;
+    let pool = read.pool();
+    let _organization_id = match ensure_task_access(pool, ctx.user.id, task_id).await {
+        Ok(org_id) => {
+            Span::current().record("org_id", format_args!("{org_id}"));
+            org_id
+        }
+        Err(error) => return error.into_response(),
+    };
+
+    let repo = SharedTaskRepository::new(pool);
+    let response = match repo.find_with_user(task_id).await {
+        Ok(Some(task)) => (StatusCode::OK, Json(SharedTaskResponse::from(task))).into_response(),
+        Ok(None) => task_error_response(SharedTaskError::NotFound, "shared task not found"),
+        Err(error) => task_error_response(error, "failed to load shared task"),
+    };
+    with_served_by(read.source(), response)
+}
+
 #[instrument(
     name = "tasks.create_shared_task",
     skip(state, ctx, payload),
@@ -331,6 +429,13 @@ pub struct BulkSharedTasksResponse {
     pub latest_seq: Option<i64>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskDigestResponse {
+    pub tasks: Vec<TaskDigestEntry>,
+    pub deleted_task_ids: Vec<Uuid>,
+    pub next_after: Option<Uuid>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateSharedTaskRequest {
     pub project_id: Uuid,
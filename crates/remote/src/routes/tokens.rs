@@ -1,6 +1,6 @@
 use axum::{
     Json, Router,
-    extract::State,
+    extract::{DefaultBodyLimit, State},
     http::StatusCode,
     response::{IntoResponse, Response},
     routing::post,
@@ -8,6 +8,7 @@ use axum::{
 use tracing::warn;
 use utils::api::oauth::{TokenRefreshRequest, TokenRefreshResponse};
 
+use super::SMALL_BODY_LIMIT;
 use crate::{
     AppState,
     auth::JwtError,
@@ -19,7 +20,10 @@ use crate::{
 };
 
 pub fn public_router() -> Router<AppState> {
-    Router::new().route("/tokens/refresh", post(refresh_token))
+    Router::new().route(
+        "/tokens/refresh",
+        post(refresh_token).layer(DefaultBodyLimit::max(SMALL_BODY_LIMIT)),
+    )
 }
 
 #[derive(Debug, thiserror::Error)]
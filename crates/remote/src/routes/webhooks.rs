@@ -0,0 +1,239 @@
+use axum::{
+    Json, Router,
+    extract::{DefaultBodyLimit, Extension, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, patch},
+};
+use rand::{Rng, distr::Alphanumeric};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{SMALL_BODY_LIMIT, error::ErrorResponse, organization_members::ensure_admin_access};
+use crate::{
+    AppState,
+    auth::RequestContext,
+    db::webhooks::{Webhook, WebhookDelivery, WebhookRepository},
+    webhooks::check_destination,
+};
+
+const SECRET_LENGTH: usize = 48;
+const DELIVERIES_DEFAULT_LIMIT: i64 = 50;
+const DELIVERIES_MAX_LIMIT: i64 = 200;
+
+/// Activity event types a webhook can subscribe to
+const EVENT_TYPES: &[&str] = &[
+    "task.created",
+    "task.updated",
+    "task.reassigned",
+    "task.deleted",
+];
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/organizations/{org_id}/webhooks",
+            get(list_webhooks)
+                .post(create_webhook)
+                .layer(DefaultBodyLimit::max(SMALL_BODY_LIMIT)),
+        )
+        .route(
+            "/organizations/{org_id}/webhooks/{webhook_id}",
+            patch(update_webhook)
+                .delete(delete_webhook)
+                .layer(DefaultBodyLimit::max(SMALL_BODY_LIMIT)),
+        )
+        .route(
+            "/organizations/{org_id}/webhooks/{webhook_id}/deliveries",
+            get(list_deliveries),
+        )
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    /// Omit or leave empty to receive every event type
+    #[serde(default)]
+    pub event_types: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateWebhookResponse {
+    pub webhook: Webhook,
+    /// Key of the signature header; only returned here
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    /// `true` also re-enables a webhook deactivated after failures
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListWebhooksResponse {
+    pub webhooks: Vec<Webhook>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveriesQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListDeliveriesResponse {
+    /// Newest first
+    pub deliveries: Vec<WebhookDelivery>,
+}
+
+fn database_error(error: sqlx::Error) -> ErrorResponse {
+    tracing::error!(?error, "webhook query failed");
+    ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+}
+
+fn not_found() -> ErrorResponse {
+    ErrorResponse::new(StatusCode::NOT_FOUND, "Webhook not found")
+}
+
+async fn validate_url(url: &str) -> Result<String, ErrorResponse> {
+    check_destination(url)
+        .await
+        .map_err(|error| ErrorResponse::new(StatusCode::BAD_REQUEST, error.to_string()))?;
+    Ok(url.trim().to_string())
+}
+
+fn validate_event_types(event_types: &[String]) -> Result<Vec<String>, ErrorResponse> {
+    let mut validated: Vec<String> = Vec::new();
+    for event_type in event_types {
+        let event_type = event_type.trim();
+        if !EVENT_TYPES.contains(&event_type) {
+            return Err(ErrorResponse::new(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Unknown event type `{event_type}`; expected one of {}",
+                    EVENT_TYPES.join(", ")
+                ),
+            ));
+        }
+        if !validated.iter().any(|t| t == event_type) {
+            validated.push(event_type.to_string());
+        }
+    }
+    Ok(validated)
+}
+
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path(org_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ErrorResponse> {
+    ensure_admin_access(&state.pool, org_id, ctx.user.id).await?;
+
+    let webhooks = WebhookRepository::new(&state.pool)
+        .list(org_id)
+        .await
+        .map_err(database_error)?;
+
+    Ok(Json(ListWebhooksResponse { webhooks }))
+}
+
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path(org_id): Path<Uuid>,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> Result<impl IntoResponse, ErrorResponse> {
+    ensure_admin_access(&state.pool, org_id, ctx.user.id).await?;
+
+    let url = validate_url(&payload.url).await?;
+    let event_types = validate_event_types(&payload.event_types)?;
+    let secret: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(SECRET_LENGTH)
+        .map(char::from)
+        .collect();
+
+    let webhook = WebhookRepository::new(&state.pool)
+        .create(org_id, &url, &secret, &event_types, ctx.user.id)
+        .await
+        .map_err(database_error)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateWebhookResponse { webhook, secret }),
+    ))
+}
+
+pub async fn update_webhook(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path((org_id, webhook_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateWebhookRequest>,
+) -> Result<impl IntoResponse, ErrorResponse> {
+    ensure_admin_access(&state.pool, org_id, ctx.user.id).await?;
+
+    let url = match payload.url.as_deref() {
+        Some(url) => Some(validate_url(url).await?),
+        None => None,
+    };
+    let event_types = payload
+        .event_types
+        .as_deref()
+        .map(validate_event_types)
+        .transpose()?;
+
+    let webhook = WebhookRepository::new(&state.pool)
+        .update(
+            org_id,
+            webhook_id,
+            url.as_deref(),
+            event_types.as_deref(),
+            payload.active,
+        )
+        .await
+        .map_err(database_error)?
+        .ok_or_else(not_found)?;
+
+    Ok(Json(webhook))
+}
+
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path((org_id, webhook_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, ErrorResponse> {
+    ensure_admin_access(&state.pool, org_id, ctx.user.id).await?;
+
+    let deleted = WebhookRepository::new(&state.pool)
+        .delete(org_id, webhook_id)
+        .await
+        .map_err(database_error)?;
+    if !deleted {
+        return Err(not_found());
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path((org_id, webhook_id)): Path<(Uuid, Uuid)>,
+    Query(params): Query<DeliveriesQuery>,
+) -> Result<impl IntoResponse, ErrorResponse> {
+    ensure_admin_access(&state.pool, org_id, ctx.user.id).await?;
+
+    let limit = params
+        .limit
+        .unwrap_or(DELIVERIES_DEFAULT_LIMIT)
+        .clamp(1, DELIVERIES_MAX_LIMIT);
+    let deliveries = WebhookRepository::new(&state.pool)
+        .list_deliveries(org_id, webhook_id, limit)
+        .await
+        .map_err(database_error)?
+        .ok_or_else(not_found)?;
+
+    Ok(Json(ListDeliveriesResponse { deliveries }))
+}
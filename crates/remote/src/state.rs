@@ -6,23 +6,29 @@ use crate::{
     activity::ActivityBroker,
     auth::{JwtService, OAuthHandoffService},
     config::RemoteServerConfig,
+    db::replicas::ReadPools,
     mail::Mailer,
+    ws::ActiveSessions,
 };
 
 #[derive(Clone)]
 pub struct AppState {
     pub pool: PgPool,
+    pub read_pools: ReadPools,
     pub broker: ActivityBroker,
     pub config: RemoteServerConfig,
     pub jwt: Arc<JwtService>,
     pub mailer: Arc<dyn Mailer>,
     pub server_public_base_url: String,
     handoff: Arc<OAuthHandoffService>,
+    ws_sessions: ActiveSessions,
 }
 
 impl AppState {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         pool: PgPool,
+        read_pools: ReadPools,
         broker: ActivityBroker,
         config: RemoteServerConfig,
         jwt: Arc<JwtService>,
@@ -32,12 +38,14 @@ impl AppState {
     ) -> Self {
         Self {
             pool,
+            read_pools,
             broker,
             config,
             jwt,
             mailer,
             server_public_base_url,
             handoff,
+            ws_sessions: ActiveSessions::default(),
         }
     }
 
@@ -45,6 +53,12 @@ impl AppState {
         &self.pool
     }
 
+    /// Pools for reads that tolerate bounded staleness. Anything transactional
+    /// or used to decide a write must use [`Self::pool`].
+    pub fn read_pools(&self) -> &ReadPools {
+        &self.read_pools
+    }
+
     pub fn broker(&self) -> &ActivityBroker {
         &self.broker
     }
@@ -60,4 +74,9 @@ impl AppState {
     pub fn handoff(&self) -> Arc<OAuthHandoffService> {
         Arc::clone(&self.handoff)
     }
+
+    /// Open websocket sessions per project.
+    pub fn ws_sessions(&self) -> &ActiveSessions {
+        &self.ws_sessions
+    }
 }
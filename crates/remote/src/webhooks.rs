@@ -0,0 +1,389 @@
+//! Delivery of task activity to organizations' webhooks.
+//!
+//! The activity listener hands every event to [`WebhookDispatcher::enqueue`],
+//! which never waits: when the queue is full the event is dropped with a
+//! warning, so a slow endpoint cannot hold up activity ingestion. A worker
+//! looks up the webhooks that want each event and POSTs it to them, retrying
+//! failures with exponential backoff.
+//!
+//! Webhook URLs are chosen by organization admins, so the server only posts
+//! to hosts that resolve to public addresses, checked when a webhook is saved
+//! and again before every delivery, and never follows redirects.
+
+use std::{
+    fmt::Write as _,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::Sha256;
+use sqlx::PgPool;
+use thiserror::Error;
+use tokio::sync::{
+    Semaphore,
+    mpsc::{self, error::TrySendError},
+};
+use tracing::warn;
+use url::{Host, Url};
+
+use crate::{
+    activity::ActivityEvent,
+    config::RemoteServerConfig,
+    db::webhooks::{AttemptResult, Webhook, WebhookRepository},
+};
+
+/// `sha256=` followed by the hex HMAC-SHA256 of the body, keyed with the
+/// webhook's secret
+pub const SIGNATURE_HEADER: &str = "x-vk-signature";
+pub const EVENT_HEADER: &str = "x-vk-event";
+/// The activity event id; the same across retries, for deduplication
+pub const DELIVERY_HEADER: &str = "x-vk-delivery";
+
+const MAX_CONCURRENT_DELIVERIES: usize = 32;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Error)]
+pub enum DestinationError {
+    #[error("Webhook URL must be an http or https URL")]
+    InvalidUrl,
+    #[error("Could not resolve webhook host `{host}`: {source}")]
+    Unresolvable {
+        host: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Webhook host `{host}` resolves to {addr}, which is not a public address")]
+    NotPublic { host: String, addr: IpAddr },
+}
+
+/// Parse a webhook URL and make sure every address its host resolves to is
+/// publicly routable.
+pub async fn check_destination(url: &str) -> Result<Url, DestinationError> {
+    let url = Url::parse(url.trim()).map_err(|_| DestinationError::InvalidUrl)?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(DestinationError::InvalidUrl);
+    }
+    let port = url
+        .port_or_known_default()
+        .ok_or(DestinationError::InvalidUrl)?;
+    let addrs: Vec<IpAddr> = match url.host().ok_or(DestinationError::InvalidUrl)? {
+        Host::Ipv4(addr) => vec![addr.into()],
+        Host::Ipv6(addr) => vec![addr.into()],
+        Host::Domain(domain) => tokio::net::lookup_host((domain, port))
+            .await
+            .map_err(|source| DestinationError::Unresolvable {
+                host: domain.to_string(),
+                source,
+            })?
+            .map(|addr| addr.ip())
+            .collect(),
+    };
+    let host = url.host_str().unwrap_or_default().to_string();
+    if addrs.is_empty() {
+        return Err(DestinationError::Unresolvable {
+            host,
+            source: std::io::Error::new(std::io::ErrorKind::NotFound, "no addresses found"),
+        });
+    }
+    if let Some(addr) = addrs.into_iter().find(|addr| !is_public(*addr)) {
+        return Err(DestinationError::NotPublic { host, addr });
+    }
+    Ok(url)
+}
+
+fn is_public(addr: IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(addr) => is_public_v4(addr),
+        IpAddr::V6(addr) => match addr.to_ipv4_mapped() {
+            Some(mapped) => is_public_v4(mapped),
+            None => is_public_v6(addr),
+        },
+    }
+}
+
+fn is_public_v4(addr: Ipv4Addr) -> bool {
+    let [a, b, ..] = addr.octets();
+    !(addr.is_unspecified()
+        || addr.is_loopback()
+        || addr.is_private()
+        || addr.is_link_local()
+        || addr.is_broadcast()
+        || addr.is_documentation()
+        || addr.is_multicast()
+        // 0.0.0.0/8, carrier-grade NAT 100.64.0.0/10 and reserved 240.0.0.0/4
+        || a == 0
+        || (a == 100 && (b & 0xc0) == 64)
+        || a >= 240)
+}
+
+fn is_public_v6(addr: Ipv6Addr) -> bool {
+    !(addr.is_unspecified()
+        || addr.is_loopback()
+        || addr.is_multicast()
+        || addr.is_unique_local()
+        || addr.is_unicast_link_local())
+}
+
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    queue: mpsc::Sender<ActivityEvent>,
+}
+
+impl WebhookDispatcher {
+    pub fn spawn(pool: PgPool, config: &RemoteServerConfig) -> Self {
+        let (queue, events) = mpsc::channel(config.webhook_queue_capacity);
+        let client = reqwest::Client::builder()
+            .timeout(config.webhook_timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("failed to build reqwest client");
+        let worker = Worker {
+            pool,
+            client,
+            max_attempts: config.webhook_max_attempts,
+            disable_after_failures: config.webhook_disable_after_failures,
+            deliveries: Arc::new(Semaphore::new(MAX_CONCURRENT_DELIVERIES)),
+        };
+        tokio::spawn(worker.run(events));
+        Self { queue }
+    }
+
+    pub fn enqueue(&self, event: &ActivityEvent) {
+        if let Err(TrySendError::Full(event)) = self.queue.try_send(event.clone()) {
+            warn!(
+                event_id = %event.event_id,
+                project_id = %event.project_id,
+                event_type = %event.event_type,
+                "webhook queue is full; event will not be delivered"
+            );
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Worker {
+    pool: PgPool,
+    client: reqwest::Client,
+    max_attempts: u32,
+    disable_after_failures: i32,
+    deliveries: Arc<Semaphore>,
+}
+
+impl Worker {
+    async fn run(self, mut events: mpsc::Receiver<ActivityEvent>) {
+        while let Some(event) = events.recv().await {
+            let webhooks = match WebhookRepository::new(&self.pool)
+                .active_for_project(event.project_id)
+                .await
+            {
+                Ok(webhooks) => webhooks,
+                Err(error) => {
+                    warn!(?error, event_id = %event.event_id, "failed to load webhooks");
+                    continue;
+                }
+            };
+
+            let event = Arc::new(event);
+            for webhook in webhooks
+                .into_iter()
+                .filter(|webhook| webhook.wants(&event.event_type))
+            {
+                let Ok(permit) = self.deliveries.clone().acquire_owned().await else {
+                    return;
+                };
+                let worker = self.clone();
+                let event = event.clone();
+                tokio::spawn(async move {
+                    worker.deliver(&webhook, &event).await;
+                    drop(permit);
+                });
+            }
+        }
+    }
+
+    async fn deliver(&self, webhook: &Webhook, event: &ActivityEvent) {
+        let repo = WebhookRepository::new(&self.pool);
+        let body = json!({
+            "event_id": event.event_id,
+            "event_type": event.event_type,
+            "organization_id": webhook.organization_id,
+            "project_id": event.project_id,
+            "created_at": event.created_at,
+            "payload": event.payload,
+        })
+        .to_string();
+
+        for attempt in 1..=self.max_attempts {
+            let delivery_id = match repo
+                .claim_attempt(
+                    webhook.id,
+                    event.event_id,
+                    &event.event_type,
+                    attempt as i32,
+                )
+                .await
+            {
+                Ok(Some(id)) => id,
+                // Another server instance is delivering this event
+                Ok(None) => return,
+                Err(error) => {
+                    warn!(?error, webhook_id = %webhook.id, "failed to record webhook delivery");
+                    return;
+                }
+            };
+
+            let result = self.send(webhook, event, &body).await;
+            if let Err(error) = repo.finish_attempt(delivery_id, &result).await {
+                warn!(?error, webhook_id = %webhook.id, "failed to record webhook delivery");
+            }
+            if result.succeeded {
+                if let Err(error) = repo.record_success(webhook.id).await {
+                    warn!(?error, webhook_id = %webhook.id, "failed to reset webhook failures");
+                }
+                return;
+            }
+            if attempt < self.max_attempts {
+                tokio::time::sleep(retry_delay(attempt)).await;
+            }
+        }
+
+        match repo
+            .record_failure(webhook.id, self.disable_after_failures)
+            .await
+        {
+            Ok(false) => warn!(
+                webhook_id = %webhook.id,
+                organization_id = %webhook.organization_id,
+                "webhook deactivated after {} undelivered events in a row",
+                self.disable_after_failures
+            ),
+            Ok(true) => {}
+            Err(error) => {
+                warn!(?error, webhook_id = %webhook.id, "failed to record webhook failure")
+            }
+        }
+    }
+
+    async fn send(&self, webhook: &Webhook, event: &ActivityEvent, body: &str) -> AttemptResult {
+        let started = Instant::now();
+        // DNS may have changed since the webhook was saved
+        let url = match check_destination(&webhook.url).await {
+            Ok(url) => url,
+            Err(error) => {
+                return AttemptResult {
+                    succeeded: false,
+                    response_status: None,
+                    error: Some(error.to_string()),
+                    latency_ms: 0,
+                };
+            }
+        };
+        let response = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(
+                SIGNATURE_HEADER,
+                format!("sha256={}", sign(&webhook.secret, body.as_bytes())),
+            )
+            .header(EVENT_HEADER, &event.event_type)
+            .header(DELIVERY_HEADER, event.event_id.to_string())
+            .body(body.to_string())
+            .send()
+            .await;
+        let latency_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+        match response {
+            Ok(response) => {
+                let status = response.status();
+                AttemptResult {
+                    succeeded: status.is_success(),
+                    response_status: Some(i32::from(status.as_u16())),
+                    error: None,
+                    latency_ms,
+                }
+            }
+            Err(error) => AttemptResult {
+                succeeded: false,
+                response_status: None,
+                error: Some(error.to_string()),
+                latency_ms,
+            },
+        }
+    }
+}
+
+/// Wait before retry number `attempt`, doubling from one second.
+fn retry_delay(attempt: u32) -> Duration {
+    INITIAL_RETRY_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_RETRY_DELAY)
+}
+
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    let mut output = String::with_capacity(64);
+    for byte in mac.finalize().into_bytes() {
+        let _ = write!(output, "{byte:02x}");
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_are_hex_hmac_sha256() {
+        assert_eq!(
+            sign("key", b"The quick brown fox jumps over the lazy dog"),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_private_and_metadata_destinations() {
+        for url in [
+            "http://127.0.0.1:8080/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://10.1.2.3/hook",
+            "http://192.168.0.10/hook",
+            "http://100.64.0.1/hook",
+            "http://[::1]/hook",
+            "http://[fd00::1]/hook",
+            "http://[::ffff:127.0.0.1]/hook",
+            "http://2130706433/hook",
+        ] {
+            assert!(
+                matches!(
+                    check_destination(url).await,
+                    Err(DestinationError::NotPublic { .. })
+                ),
+                "{url} should be rejected"
+            );
+        }
+        assert!(matches!(
+            check_destination("ftp://example.com/hook").await,
+            Err(DestinationError::InvalidUrl)
+        ));
+        assert!(
+            check_destination("https://93.184.215.14/hook")
+                .await
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn retries_back_off_exponentially_up_to_a_cap() {
+        assert_eq!(retry_delay(1), Duration::from_secs(1));
+        assert_eq!(retry_delay(2), Duration::from_secs(2));
+        assert_eq!(retry_delay(4), Duration::from_secs(8));
+        assert_eq!(retry_delay(40), MAX_RETRY_DELAY);
+    }
+}
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use utils::ws::WS_CORRELATION_ID_FIELD;
+use uuid::Uuid;
 
 use crate::activity::ActivityEvent;
 
@@ -11,6 +13,19 @@ pub enum ClientMessage {
     AuthToken { token: String },
 }
 
+impl ClientMessage {
+    /// Parse an inbound frame, returning the correlation id the client
+    /// attached if it is waiting for an acknowledgement.
+    pub fn parse(text: &str) -> Result<(Self, Option<Uuid>), serde_json::Error> {
+        let mut frame: serde_json::Value = serde_json::from_str(text)?;
+        let correlation_id = frame
+            .as_object_mut()
+            .and_then(|object| object.remove(WS_CORRELATION_ID_FIELD))
+            .and_then(|id| serde_json::from_value(id).ok());
+        Ok((serde_json::from_value(frame)?, correlation_id))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
 pub enum ServerMessage {
@@ -18,4 +33,11 @@ pub enum ServerMessage {
     Activity(ActivityEvent),
     #[serde(rename = "error")]
     Error { message: String },
+    /// The client fell too far behind to catch up event by event and must
+    /// bulk sync. The stream continues after `latest_seq`.
+    #[serde(rename = "resync-required")]
+    ResyncRequired { latest_seq: i64 },
+    /// The client message tagged with `correlation_id` has been processed.
+    #[serde(rename = "ack")]
+    Ack { correlation_id: Uuid },
 }
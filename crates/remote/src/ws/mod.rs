@@ -7,15 +7,35 @@ use axum::{
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::{AppState, auth::RequestContext};
+use crate::{AppState, activity::ActivityFilter, auth::RequestContext};
 
 pub mod message;
+mod outbound;
 mod session;
+mod sessions;
+
+pub use sessions::{ActiveSessions, OutboundTotals};
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct WsQueryParams {
     pub project_id: Uuid,
     pub cursor: Option<i64>,
+    /// Client protocol version, 0 for clients that do not send one
+    #[serde(default)]
+    pub protocol: u32,
+    /// Only stream events about this shared task
+    pub task_id: Option<Uuid>,
+    /// Only stream these event types, comma separated
+    pub event_types: Option<String>,
+    /// Identifies one of the user's installs, whose cursor is kept between
+    /// sessions
+    pub device_id: Option<Uuid>,
+}
+
+impl WsQueryParams {
+    pub fn activity_filter(&self) -> ActivityFilter {
+        ActivityFilter::from_query(self.task_id, self.event_types.as_deref())
+    }
 }
 
 pub fn router() -> Router<AppState> {
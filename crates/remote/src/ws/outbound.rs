@@ -0,0 +1,288 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::extract::ws::Message;
+use futures::{Sink, SinkExt};
+use tokio::{sync::Notify, task::JoinHandle, time};
+
+/// Returned when a session's activity backlog is at capacity. The session is
+/// expected to fall back to the bulk-sync path.
+#[derive(Debug, PartialEq, Eq)]
+pub struct QueueFull;
+
+/// Per-session outbound counters, updated as frames are queued and sent so
+/// they can be read while the session is still open.
+#[derive(Debug, Default)]
+pub struct QueueStats {
+    depth: AtomicUsize,
+    peak_depth: AtomicUsize,
+    dropped: AtomicU64,
+}
+
+impl QueueStats {
+    /// Activity frames waiting to be sent right now
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    pub fn peak_depth(&self) -> usize {
+        self.peak_depth.load(Ordering::Relaxed)
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    control: VecDeque<Message>,
+    activity: VecDeque<Message>,
+    closed: bool,
+}
+
+/// Outbound frames for a single websocket session.
+///
+/// Activity frames are bounded and delivered in order. Control frames (errors,
+/// close) are unbounded, but only a handful are ever sent, and they are
+/// delivered ahead of any queued activity.
+pub struct OutboundQueue {
+    state: Mutex<QueueState>,
+    notify: Notify,
+    capacity: usize,
+    stats: Arc<QueueStats>,
+}
+
+impl OutboundQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(QueueState::default()),
+            notify: Notify::new(),
+            capacity: capacity.max(1),
+            stats: Arc::default(),
+        }
+    }
+
+    pub fn stats(&self) -> &Arc<QueueStats> {
+        &self.stats
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn depth(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state.control.len() + state.activity.len()
+    }
+
+    pub fn push_activity(&self, message: Message) -> Result<(), QueueFull> {
+        let mut state = self.state.lock().unwrap();
+        if state.closed || state.activity.len() >= self.capacity {
+            self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+            return Err(QueueFull);
+        }
+        state.activity.push_back(message);
+        let depth = state.activity.len();
+        self.stats.depth.store(depth, Ordering::Relaxed);
+        self.stats.peak_depth.fetch_max(depth, Ordering::Relaxed);
+        drop(state);
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    pub fn push_control(&self, message: Message) {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return;
+        }
+        state.control.push_back(message);
+        drop(state);
+        self.notify.notify_one();
+    }
+
+    /// Stop accepting frames. Queued control frames are still flushed, pending
+    /// activity is discarded since the client resyncs from its cursor anyway.
+    pub fn close(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        state.activity.clear();
+        self.stats.depth.store(0, Ordering::Relaxed);
+        drop(state);
+        self.notify.notify_one();
+    }
+
+    async fn pop(&self) -> Option<Message> {
+        loop {
+            {
+                let mut state = self.state.lock().unwrap();
+                if let Some(message) = state.control.pop_front() {
+                    return Some(message);
+                }
+                if let Some(message) = state.activity.pop_front() {
+                    self.stats
+                        .depth
+                        .store(state.activity.len(), Ordering::Relaxed);
+                    return Some(message);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            self.notify.notified().await;
+        }
+    }
+}
+
+/// Drain `queue` into `sink`. A send that does not complete within
+/// `send_timeout` closes the queue and ends the writer, which the session
+/// loop treats as a disconnect.
+pub fn spawn_writer<S>(
+    mut sink: S,
+    queue: Arc<OutboundQueue>,
+    send_timeout: Duration,
+) -> JoinHandle<()>
+where
+    S: Sink<Message> + Unpin + Send + 'static,
+    S::Error: std::fmt::Debug,
+{
+    tokio::spawn(async move {
+        while let Some(message) = queue.pop().await {
+            match time::timeout(send_timeout, sink.send(message)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(error)) => {
+                    tracing::debug!(?error, "failed to send websocket message");
+                    break;
+                }
+                Err(_) => {
+                    tracing::info!(
+                        timeout_ms = send_timeout.as_millis() as u64,
+                        depth = queue.depth(),
+                        "websocket send timed out; closing slow client"
+                    );
+                    break;
+                }
+            }
+        }
+        queue.close();
+        let _ = time::timeout(send_timeout, sink.close()).await;
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{StreamExt, channel::mpsc};
+
+    use super::*;
+
+    fn text(value: &str) -> Message {
+        Message::Text(value.to_string().into())
+    }
+
+    fn as_text(message: Message) -> String {
+        match message {
+            Message::Text(text) => text.as_str().to_owned(),
+            other => panic!("unexpected message {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn control_frames_jump_the_queue() {
+        let queue = OutboundQueue::new(8);
+        queue.push_activity(text("a1")).unwrap();
+        queue.push_activity(text("a2")).unwrap();
+        queue.push_control(text("error"));
+
+        assert_eq!(as_text(queue.pop().await.unwrap()), "error");
+        assert_eq!(as_text(queue.pop().await.unwrap()), "a1");
+        assert_eq!(as_text(queue.pop().await.unwrap()), "a2");
+    }
+
+    #[tokio::test]
+    async fn slow_reader_is_bounded_and_disconnected() {
+        let capacity = 16;
+        let queue = Arc::new(OutboundQueue::new(capacity));
+        // A zero-capacity channel whose receiver is never polled behaves like
+        // a client that stopped reading.
+        let (sink, rx) = mpsc::channel::<Message>(0);
+        let writer = spawn_writer(sink, queue.clone(), Duration::from_millis(50));
+
+        let mut full_at = None;
+        for i in 0..10_000 {
+            if queue.push_activity(text(&format!("event {i}"))).is_err() {
+                full_at = Some(i);
+                break;
+            }
+        }
+
+        // The backlog never grows past the configured bound and the overflow
+        // is reported so the session can force a bulk sync.
+        assert!(full_at.is_some());
+        assert!(queue.depth() <= capacity);
+        assert!(queue.stats().peak_depth() <= capacity);
+        assert_eq!(queue.stats().depth(), capacity);
+        assert_eq!(queue.stats().dropped(), 1);
+
+        time::timeout(Duration::from_secs(5), writer)
+            .await
+            .expect("writer should give up on the slow reader")
+            .unwrap();
+        assert!(queue.push_activity(text("late")).is_err());
+
+        // Only what fit in the channel buffer was ever handed to the client
+        assert!(rx.count().await <= 2);
+    }
+
+    #[tokio::test]
+    async fn stuck_writer_does_not_stall_the_broadcast_consumer() {
+        use tokio::sync::broadcast::{self, error::RecvError};
+
+        let capacity = 32;
+        let queue = Arc::new(OutboundQueue::new(capacity));
+        let (sink, _paused_reader) = mpsc::channel::<Message>(0);
+        let _writer = spawn_writer(sink, queue.clone(), Duration::from_secs(60));
+
+        let (events, mut subscriber) = broadcast::channel::<u64>(8);
+        tokio::spawn(async move {
+            for seq in 0..1_000u64 {
+                if events.send(seq).is_err() {
+                    break;
+                }
+                tokio::task::yield_now().await;
+            }
+        });
+
+        // Mirrors the session loop: every broadcast event is queued without
+        // waiting on the socket, so the subscriber keeps up with the sender
+        // until the bounded queue reports the client as too slow.
+        let consumed = time::timeout(Duration::from_secs(5), async {
+            let mut consumed = 0;
+            loop {
+                match subscriber.recv().await {
+                    Ok(seq) => {
+                        if queue.push_activity(text(&seq.to_string())).is_err() {
+                            return consumed;
+                        }
+                        consumed += 1;
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        panic!("subscriber lagged by {skipped} behind a stuck writer")
+                    }
+                    Err(RecvError::Closed) => return consumed,
+                }
+            }
+        })
+        .await
+        .expect("session loop should not block on the stuck writer");
+
+        // One frame may sit in the writer's pending send, the rest fill the queue
+        assert!((capacity..=capacity + 2).contains(&consumed));
+        assert_eq!(queue.stats().dropped(), 1);
+    }
+}
@@ -1,27 +1,32 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use axum::extract::ws::{Message, WebSocket};
 use chrono::{DateTime, Duration as ChronoDuration, Utc};
-use futures::{SinkExt, StreamExt};
+use futures::{Sink, Stream, StreamExt};
 use sqlx::PgPool;
 use thiserror::Error;
-use tokio::time::{self, MissedTickBehavior};
+use tokio::time::{self, Instant, MissedTickBehavior};
 use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 use tracing::{Span, instrument};
-use utils::ws::{WS_AUTH_REFRESH_INTERVAL, WS_BULK_SYNC_THRESHOLD, WS_TOKEN_EXPIRY_GRACE};
+use utils::ws::{
+    WS_AUTH_REFRESH_INTERVAL, WS_BULK_SYNC_THRESHOLD, WS_PROTOCOL_VERSION, WS_TOKEN_EXPIRY_GRACE,
+};
 use uuid::Uuid;
 
 use super::{
-    WsQueryParams,
+    ActiveSessions, WsQueryParams,
     message::{ClientMessage, ServerMessage},
+    outbound::{self, OutboundQueue, QueueFull, QueueStats},
 };
 use crate::{
     AppState,
-    activity::{ActivityBroker, ActivityEvent, ActivityStream},
+    activity::{ActivityBroker, ActivityEvent, ActivityFilter, ActivityStream},
     auth::{JwtError, JwtService, RequestContext},
+    config::RemoteServerConfig,
     db::{
         activity::ActivityRepository,
         auth::{AuthSessionError, AuthSessionRepository},
+        devices::DeviceCursorRepository,
     },
 };
 
@@ -32,7 +37,10 @@ use crate::{
         user_id = %ctx.user.id,
         project_id = %params.project_id,
         org_id = tracing::field::Empty,
-        session_id = %ctx.session_id
+        session_id = %ctx.session_id,
+        queue_depth = tracing::field::Empty,
+        queue_peak_depth = tracing::field::Empty,
+        queue_dropped = tracing::field::Empty
     )
 )]
 pub async fn handle(
@@ -41,7 +49,6 @@ pub async fn handle(
     ctx: RequestContext,
     params: WsQueryParams,
 ) {
-    let config = state.config();
     let pool_ref = state.pool();
     let project_id = params.project_id;
     let organization_id = match crate::routes::organization_members::ensure_project_access(
@@ -64,203 +71,466 @@ pub async fn handle(
     };
     Span::current().record("org_id", format_args!("{organization_id}"));
 
-    let pool = pool_ref.clone();
-    let mut last_sent_seq = params.cursor;
-    let mut auth_state = WsAuthState::new(
+    let auth_state = WsAuthState::new(
         state.jwt(),
-        pool.clone(),
+        pool_ref.clone(),
         ctx.session_id,
         ctx.user.id,
         project_id,
         ctx.access_token_expires_at,
     );
-    let mut auth_check_interval = time::interval(WS_AUTH_REFRESH_INTERVAL);
-    auth_check_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    run(
+        socket,
+        Session {
+            pool: pool_ref.clone(),
+            broker: state.broker().clone(),
+            sessions: state.ws_sessions().clone(),
+            settings: SessionSettings::from(state.config()),
+            auth_state,
+            user_id: ctx.user.id,
+            session_id: ctx.session_id,
+            organization_id,
+            params,
+        },
+    )
+    .await;
+}
 
-    let (mut sender, mut inbound) = socket.split();
-    let mut activity_stream = state.broker().subscribe(project_id);
+/// Server settings a session reads.
+#[derive(Debug, Clone)]
+struct SessionSettings {
+    outbound_queue_capacity: usize,
+    send_timeout: Duration,
+    ping_interval: Duration,
+    history_limit: i64,
+    catchup_batch_size: i64,
+}
 
-    if let Ok(history) = ActivityRepository::new(&pool)
-        .fetch_since(project_id, params.cursor, config.activity_default_limit)
-        .await
-    {
-        for event in history {
-            if send_activity(&mut sender, &event).await.is_err() {
-                return;
-            }
-            last_sent_seq = Some(event.seq);
+impl From<&RemoteServerConfig> for SessionSettings {
+    fn from(config: &RemoteServerConfig) -> Self {
+        Self {
+            outbound_queue_capacity: config.ws_outbound_queue_capacity,
+            send_timeout: config.ws_send_timeout,
+            ping_interval: config.ws_ping_interval,
+            history_limit: config.activity_default_limit,
+            catchup_batch_size: config.activity_catchup_batch_size,
         }
     }
+}
 
-    tracing::debug!(org_id = %organization_id, project_id = %project_id, "starting websocket session");
+/// A session whose project access has been checked.
+struct Session {
+    pool: PgPool,
+    broker: ActivityBroker,
+    sessions: ActiveSessions,
+    settings: SessionSettings,
+    auth_state: WsAuthState,
+    user_id: Uuid,
+    session_id: Uuid,
+    organization_id: Uuid,
+    params: WsQueryParams,
+}
 
-    loop {
-        tokio::select! {
-            maybe_activity = activity_stream.next() => {
-                match maybe_activity {
-                    Some(Ok(event)) => {
-                        tracing::trace!(?event, "received activity event");
-                        assert_eq!(event.project_id, project_id, "activity stream emitted cross-project event");
-                        if let Some(prev_seq) = last_sent_seq {
-                            if prev_seq >= event.seq {
-                                continue;
+async fn run<S, E>(socket: S, session: Session)
+where
+    S: Stream<Item = Result<Message, E>> + Sink<Message> + Send + 'static,
+    <S as Sink<Message>>::Error: std::fmt::Debug,
+    E: std::fmt::Debug,
+{
+    let Session {
+        pool,
+        broker,
+        sessions,
+        settings,
+        mut auth_state,
+        user_id,
+        session_id,
+        organization_id,
+        params,
+    } = session;
+    let project_id = params.project_id;
+    let supports_resync = params.protocol >= WS_PROTOCOL_VERSION;
+    // Applied only when queueing: every event still advances the cursor, so
+    // filtered-out events are not mistaken for gaps
+    let filter = params.activity_filter();
+    // Access was checked while upgrading, so the first check waits a full interval
+    let mut auth_check_interval = time::interval_at(
+        Instant::now() + WS_AUTH_REFRESH_INTERVAL,
+        WS_AUTH_REFRESH_INTERVAL,
+    );
+    auth_check_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    // Any inbound frame counts as a sign of life, not just Pongs
+    let ping_period = settings.ping_interval;
+    let mut ping_interval = time::interval_at(Instant::now() + ping_period, ping_period);
+    ping_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let mut last_heard = Instant::now();
+
+    let (sender, mut inbound) = socket.split();
+    let outbound = Arc::new(OutboundQueue::new(settings.outbound_queue_capacity));
+    let mut writer = outbound::spawn_writer(sender, outbound.clone(), settings.send_timeout);
+    let mut writer_finished = false;
+    let mut activity_stream = broker.subscribe(project_id);
+    let _session_guard = sessions.track(project_id, outbound.stats().clone());
+
+    // A device that connects without a cursor is still known by its last ack
+    let known_cursor = match (params.device_id, params.cursor) {
+        (Some(device_id), Some(cursor)) => {
+            record_device_cursor(&pool, user_id, device_id, project_id, cursor).await;
+            Some(cursor)
+        }
+        (Some(device_id), None) => DeviceCursorRepository::new(&pool)
+            .get(user_id, device_id, project_id)
+            .await
+            .unwrap_or_else(|error| {
+                tracing::warn!(?error, "failed to load device cursor");
+                None
+            }),
+        (None, cursor) => cursor,
+    };
+    let mut last_sent_seq = known_cursor;
+    let pruned_resync_seq = match known_cursor {
+        Some(cursor) if supports_resync => pruned_resync_seq(&pool, project_id, cursor).await,
+        _ => None,
+    };
+
+    'session: {
+        if let Some(latest_seq) = pruned_resync_seq {
+            tracing::info!(
+                cursor = ?known_cursor,
+                latest_seq,
+                "cursor predates retained activity; requesting resync"
+            );
+            if !request_resync(&outbound, supports_resync, latest_seq) {
+                break 'session;
+            }
+            last_sent_seq = Some(latest_seq);
+        } else if let Ok(history) = ActivityRepository::new(&pool)
+            .fetch_since(
+                project_id,
+                known_cursor,
+                settings.history_limit,
+                &ActivityFilter::default(),
+            )
+            .await
+        {
+            for event in history {
+                if queue_activity(&outbound, &filter, &event).is_err() {
+                    break 'session;
+                }
+                last_sent_seq = Some(event.seq);
+            }
+        }
+
+        tracing::debug!(org_id = %organization_id, project_id = %project_id, "starting websocket session");
+
+        loop {
+            tokio::select! {
+                maybe_activity = activity_stream.next() => {
+                    match maybe_activity {
+                        Some(Ok(event)) => {
+                            tracing::trace!(?event, "received activity event");
+                            assert_eq!(event.project_id, project_id, "activity stream emitted cross-project event");
+                            if let Some(prev_seq) = last_sent_seq {
+                                if prev_seq >= event.seq {
+                                    continue;
+                                }
+                                if event.seq > prev_seq + 1 {
+                                    tracing::warn!(
+                                        expected_next = prev_seq + 1,
+                                        actual = event.seq,
+                                        org_id = %organization_id,
+                                        project_id = %project_id,
+                                        "activity stream skipped sequence; running catch-up"
+                                    );
+                                    match activity_stream_catch_up(
+                                        &outbound,
+                                        &pool,
+                                        project_id,
+                                        organization_id,
+                                        prev_seq,
+                                        &broker,
+                                        settings.catchup_batch_size,
+                                        WS_BULK_SYNC_THRESHOLD as i64,
+                                        &filter,
+                                        "gap",
+                                    ).await {
+                                        Ok((seq, stream)) => {
+                                            last_sent_seq = Some(seq);
+                                            activity_stream = stream;
+                                        }
+                                        Err(CatchUpFailure::Resync { latest_seq, stream }) => {
+                                            if !request_resync(&outbound, supports_resync, latest_seq) {
+                                                break;
+                                            }
+                                            last_sent_seq = Some(latest_seq);
+                                            activity_stream = stream;
+                                        }
+                                        Err(CatchUpFailure::Closed) => break,
+                                    }
+                                    continue;
+                                }
                             }
-                            if event.seq > prev_seq + 1 {
-                                tracing::warn!(
-                                    expected_next = prev_seq + 1,
-                                    actual = event.seq,
+                            if queue_activity(&outbound, &filter, &event).is_err() {
+                                break;
+                            }
+                            last_sent_seq = Some(event.seq);
+                        }
+                        Some(Err(BroadcastStreamRecvError::Lagged(skipped))) => {
+                            tracing::warn!(skipped, org_id = %organization_id, project_id = %project_id, "activity stream lagged");
+                            let Some(prev_seq) = last_sent_seq else {
+                                tracing::info!(
                                     org_id = %organization_id,
                                     project_id = %project_id,
-                                    "activity stream skipped sequence; running catch-up"
+                                    "activity stream lagged without baseline; forcing bulk sync"
                                 );
-                                match activity_stream_catch_up(
-                                    &mut sender,
-                                    &pool,
-                                    project_id,
-                                    organization_id,
-                                    prev_seq,
-                                    state.broker(),
-                                    config.activity_catchup_batch_size,
-                                    WS_BULK_SYNC_THRESHOLD as i64,
-                                    "gap",
-                                ).await {
-                                    Ok((seq, stream)) => {
-                                        last_sent_seq = Some(seq);
-                                        activity_stream = stream;
-                                    }
-                                    Err(()) => break,
+                                let Some((latest_seq, stream)) = resubscribe(&broker, project_id).await else {
+                                    queue_error(&outbound, "activity backlog dropped");
+                                    break;
+                                };
+                                if !request_resync(&outbound, supports_resync, latest_seq) {
+                                    break;
                                 }
-                                continue;
-                            }
-                        }
-                        if send_activity(&mut sender, &event).await.is_err() {
-                            break;
-                        }
-                        last_sent_seq = Some(event.seq);
-                    }
-                    Some(Err(BroadcastStreamRecvError::Lagged(skipped))) => {
-                        tracing::warn!(skipped, org_id = %organization_id, project_id = %project_id, "activity stream lagged");
-                        let Some(prev_seq) = last_sent_seq else {
-                            tracing::info!(
-                                org_id = %organization_id,
-                                project_id = %project_id,
-                                "activity stream lagged without baseline; forcing bulk sync"
-                            );
-                            let _ = send_error(&mut sender, "activity backlog dropped").await;
-                            break;
-                        };
-
-                        match activity_stream_catch_up(
-                            &mut sender,
-                            &pool,
-                            project_id,
-                            organization_id,
-                            prev_seq,
-                            state.broker(),
-                            config.activity_catchup_batch_size,
-                            WS_BULK_SYNC_THRESHOLD as i64,
-                            "lag",
-                        ).await {
-                            Ok((seq, stream)) => {
-                                last_sent_seq = Some(seq);
+                                last_sent_seq = Some(latest_seq);
                                 activity_stream = stream;
+                                continue;
+                            };
+
+                            match activity_stream_catch_up(
+                                &outbound,
+                                &pool,
+                                project_id,
+                                organization_id,
+                                prev_seq,
+                                &broker,
+                                settings.catchup_batch_size,
+                                WS_BULK_SYNC_THRESHOLD as i64,
+                                &filter,
+                                "lag",
+                            ).await {
+                                Ok((seq, stream)) => {
+                                    last_sent_seq = Some(seq);
+                                    activity_stream = stream;
+                                }
+                                Err(CatchUpFailure::Resync { latest_seq, stream }) => {
+                                    if !request_resync(&outbound, supports_resync, latest_seq) {
+                                        break;
+                                    }
+                                    last_sent_seq = Some(latest_seq);
+                                    activity_stream = stream;
+                                }
+                                Err(CatchUpFailure::Closed) => break,
                             }
-                            Err(()) => break,
                         }
+                        None => break,
                     }
-                    None => break,
                 }
-            }
 
-            maybe_message = inbound.next() => {
-                match maybe_message {
-                    Some(Ok(msg)) => {
-                        if matches!(msg, Message::Close(_)) {
-                            break;
-                        }
-                        if let Message::Text(text) = msg {
-                            match serde_json::from_str::<ClientMessage>(&text) {
-                                Ok(ClientMessage::Ack { .. }) => {}
-                                Ok(ClientMessage::AuthToken { token }) => {
-                                    auth_state.store_token(token);
-                                }
-                                Err(error) => {
-                                    tracing::debug!(?error, "invalid inbound message");
+                maybe_message = inbound.next() => {
+                    match maybe_message {
+                        Some(Ok(msg)) => {
+                            last_heard = Instant::now();
+                            if matches!(msg, Message::Close(_)) {
+                                break;
+                            }
+                            if let Message::Text(text) = msg {
+                                let correlation_id = match ClientMessage::parse(&text) {
+                                    Ok((ClientMessage::Ack { cursor }, correlation_id)) => {
+                                        if let Some(device_id) = params.device_id {
+                                            record_device_cursor(&pool, user_id, device_id, project_id, cursor).await;
+                                        }
+                                        correlation_id
+                                    }
+                                    Ok((ClientMessage::AuthToken { token }, correlation_id)) => {
+                                        auth_state.store_token(token);
+                                        correlation_id
+                                    }
+                                    Err(error) => {
+                                        tracing::debug!(?error, "invalid inbound message");
+                                        None
+                                    }
+                                };
+                                if let Some(correlation_id) = correlation_id {
+                                    queue_ack(&outbound, correlation_id);
                                 }
                             }
                         }
+                        Some(Err(error)) => {
+                            tracing::debug!(?error, "websocket receive error");
+                            break;
+                        }
+                        None => break,
                     }
-                    Some(Err(error)) => {
-                        tracing::debug!(?error, "websocket receive error");
+                }
+
+                _ = &mut writer => {
+                    writer_finished = true;
+                    break;
+                }
+
+                _ = ping_interval.tick() => {
+                    if last_heard.elapsed() >= ping_period * 2 {
+                        tracing::info!(
+                            %session_id,
+                            silent_for = ?last_heard.elapsed(),
+                            "closing websocket after missed pongs"
+                        );
+                        outbound.push_control(Message::Close(None));
                         break;
                     }
-                    None => break,
+                    outbound.push_control(Message::Ping(Vec::new().into()));
+                    record_queue_stats(outbound.stats());
                 }
-            }
 
-            _ = auth_check_interval.tick() => {
-                match auth_state.verify().await {
-                    Ok(()) => {}
-                    Err(error) => {
-                        tracing::info!(?error, "closing websocket due to auth verification error");
-                        let message = match error {
-                            AuthVerifyError::Revoked => "authorization revoked",
-                            AuthVerifyError::MembershipRevoked => "project access revoked",
-                            AuthVerifyError::Expired => "authorization expired",
-                            AuthVerifyError::UserMismatch { .. }
-                            | AuthVerifyError::Decode(_)
-                            | AuthVerifyError::Session(_) => "authorization error",
-                        };
-                        let _ = send_error(&mut sender, message).await;
-                        let _ = sender.send(Message::Close(None)).await;
-                        break;
+                _ = auth_check_interval.tick() => {
+                    match auth_state.verify().await {
+                        Ok(()) => {}
+                        Err(error) => {
+                            tracing::info!(?error, "closing websocket due to auth verification error");
+                            let message = match error {
+                                AuthVerifyError::Revoked => "authorization revoked",
+                                AuthVerifyError::MembershipRevoked => "project access revoked",
+                                AuthVerifyError::Expired => "authorization expired",
+                                AuthVerifyError::UserMismatch { .. }
+                                | AuthVerifyError::Decode(_)
+                                | AuthVerifyError::Session(_) => "authorization error",
+                            };
+                            queue_error(&outbound, message);
+                            outbound.push_control(Message::Close(None));
+                            break;
+                        }
                     }
                 }
             }
         }
     }
+
+    // Flush pending control frames (errors, close) before the socket goes away.
+    // Every send is bounded by the writer's timeout, so this cannot hang.
+    outbound.close();
+    if !writer_finished {
+        let _ = writer.await;
+    }
+
+    let stats = outbound.stats();
+    record_queue_stats(stats);
+    tracing::debug!(
+        queue_peak_depth = stats.peak_depth(),
+        queue_dropped = stats.dropped(),
+        "websocket session ended"
+    );
 }
 
-async fn send_activity(
-    sender: &mut futures::stream::SplitSink<WebSocket, Message>,
+/// Current outbound queue counters on the session span.
+fn record_queue_stats(stats: &QueueStats) {
+    let span = Span::current();
+    span.record("queue_depth", stats.depth());
+    span.record("queue_peak_depth", stats.peak_depth());
+    span.record("queue_dropped", stats.dropped());
+}
+
+fn queue_activity(
+    outbound: &OutboundQueue,
+    filter: &ActivityFilter,
     event: &ActivityEvent,
 ) -> Result<(), ()> {
+    if !filter.matches(event) {
+        return Ok(());
+    }
     tracing::trace!(
         event_type = %event.event_type.as_str(),
         project_id = %event.project_id,
-        "sending activity event"
+        "queueing activity event"
     );
 
-    match serde_json::to_string(&ServerMessage::Activity(event.clone())) {
-        Ok(json) => sender
-            .send(Message::Text(json.into()))
-            .await
-            .map_err(|error| {
-                tracing::debug!(?error, "failed to send activity message");
-            }),
+    let json = serde_json::to_string(&ServerMessage::Activity(event.clone())).map_err(|error| {
+        tracing::error!(?error, "failed to serialise activity event");
+    })?;
+
+    outbound
+        .push_activity(Message::Text(json.into()))
+        .map_err(|QueueFull| {
+            // Every queued frame is an event the client has not received yet
+            tracing::info!(
+                project_id = %event.project_id,
+                seq = event.seq,
+                behind = outbound.depth(),
+                capacity = outbound.capacity(),
+                "outbound queue full; dropping slow client and forcing bulk sync"
+            );
+            record_queue_stats(outbound.stats());
+            queue_error(outbound, "activity backlog dropped");
+        })
+}
+
+/// Store how far this device has read so it can resume after reconnecting.
+async fn record_device_cursor(
+    pool: &PgPool,
+    user_id: Uuid,
+    device_id: Uuid,
+    project_id: Uuid,
+    cursor: i64,
+) {
+    if let Err(error) = DeviceCursorRepository::new(pool)
+        .ack(user_id, device_id, project_id, cursor)
+        .await
+    {
+        tracing::warn!(?error, %device_id, "failed to store device cursor");
+    }
+}
+
+/// The project's latest seq if activity after `cursor` was pruned, else `None`.
+async fn pruned_resync_seq(pool: &PgPool, project_id: Uuid, cursor: i64) -> Option<i64> {
+    let repo = ActivityRepository::new(pool);
+    let pruned_through = match repo.pruned_through(project_id).await {
+        Ok(pruned_through) => pruned_through?,
         Err(error) => {
-            tracing::error!(?error, "failed to serialise activity event");
-            Err(())
+            tracing::warn!(?error, "failed to load activity retention");
+            return None;
         }
+    };
+    if cursor >= pruned_through {
+        return None;
     }
+    repo.latest_seq(project_id).await.ok().flatten()
 }
 
-async fn send_error(
-    sender: &mut futures::stream::SplitSink<WebSocket, Message>,
-    message: &str,
-) -> Result<(), ()> {
+/// Ask the client to bulk sync and carry on streaming after `latest_seq`.
+/// Clients older than the `resync-required` message get the legacy error
+/// instead; returns `false` when the session must then close.
+fn request_resync(outbound: &OutboundQueue, supports_resync: bool, latest_seq: i64) -> bool {
+    if !supports_resync {
+        queue_error(outbound, "activity backlog dropped");
+        return false;
+    }
+    match serde_json::to_string(&ServerMessage::ResyncRequired { latest_seq }) {
+        Ok(json) => {
+            outbound.push_control(Message::Text(json.into()));
+            true
+        }
+        Err(error) => {
+            tracing::error!(?error, "failed to serialise websocket resync message");
+            false
+        }
+    }
+}
+
+fn queue_error(outbound: &OutboundQueue, message: &str) {
     match serde_json::to_string(&ServerMessage::Error {
         message: message.to_string(),
     }) {
-        Ok(json) => sender
-            .send(Message::Text(json.into()))
-            .await
-            .map_err(|error| {
-                tracing::debug!(?error, "failed to send websocket error message");
-            }),
+        Ok(json) => outbound.push_control(Message::Text(json.into())),
         Err(error) => {
             tracing::error!(?error, "failed to serialise websocket error message");
-            Err(())
+        }
+    }
+}
+
+fn queue_ack(outbound: &OutboundQueue, correlation_id: Uuid) {
+    match serde_json::to_string(&ServerMessage::Ack { correlation_id }) {
+        Ok(json) => outbound.push_control(Message::Text(json.into())),
+        Err(error) => {
+            tracing::error!(?error, "failed to serialise websocket ack");
         }
     }
 }
@@ -394,7 +664,7 @@ enum AuthVerifyError {
 
 #[allow(clippy::too_many_arguments)]
 async fn activity_stream_catch_up(
-    sender: &mut futures::stream::SplitSink<WebSocket, Message>,
+    outbound: &OutboundQueue,
     pool: &PgPool,
     project_id: Uuid,
     organization_id: Uuid,
@@ -402,18 +672,13 @@ async fn activity_stream_catch_up(
     broker: &ActivityBroker,
     batch_size: i64,
     bulk_limit: i64,
+    filter: &ActivityFilter,
     reason: &'static str,
-) -> Result<(i64, ActivityStream), ()> {
-    let mut activity_stream = broker.subscribe(project_id);
-
-    let event = match activity_stream.next().await {
-        Some(Ok(event)) => event,
-        Some(Err(_)) | None => {
-            let _ = send_error(sender, "activity backlog dropped").await;
-            return Err(());
-        }
+) -> Result<(i64, ActivityStream), CatchUpFailure> {
+    let Some((target_seq, activity_stream)) = resubscribe(broker, project_id).await else {
+        queue_error(outbound, "activity backlog dropped");
+        return Err(CatchUpFailure::Closed);
     };
-    let target_seq = event.seq;
 
     if target_seq <= last_seq {
         return Ok((last_seq, activity_stream));
@@ -429,47 +694,72 @@ async fn activity_stream_catch_up(
             reason,
             "activity catch up exceeded threshold; forcing bulk sync"
         );
-        let _ = send_error(sender, "activity backlog dropped").await;
-        return Err(());
+        return Err(CatchUpFailure::Resync {
+            latest_seq: target_seq,
+            stream: activity_stream,
+        });
     }
 
     let catch_up_result = catch_up_from_db(
-        sender,
+        outbound,
         pool,
         project_id,
         organization_id,
         last_seq,
         target_seq,
         batch_size.max(1),
+        filter,
     )
     .await;
 
     match catch_up_result {
         Ok(seq) => Ok((seq, activity_stream)),
-        Err(CatchUpError::Stale) => {
-            let _ = send_error(sender, "activity backlog dropped").await;
-            Err(())
-        }
-        Err(CatchUpError::Send) => Err(()),
+        Err(CatchUpError::Stale) => Err(CatchUpFailure::Resync {
+            latest_seq: target_seq,
+            stream: activity_stream,
+        }),
+        Err(CatchUpError::Queue) => Err(CatchUpFailure::Closed),
     }
 }
 
+/// A fresh subscription and the seq of the first event it delivers.
+async fn resubscribe(broker: &ActivityBroker, project_id: Uuid) -> Option<(i64, ActivityStream)> {
+    let mut activity_stream = broker.subscribe(project_id);
+    match activity_stream.next().await {
+        Some(Ok(event)) => Some((event.seq, activity_stream)),
+        Some(Err(_)) | None => None,
+    }
+}
+
+enum CatchUpFailure {
+    /// Too far behind to replay; the client must bulk sync up to `latest_seq`
+    /// and `stream` continues after it
+    Resync {
+        latest_seq: i64,
+        stream: ActivityStream,
+    },
+    /// The session cannot continue
+    Closed,
+}
+
 #[derive(Debug, Error)]
 enum CatchUpError {
     #[error("activity stream went stale during catch up")]
     Stale,
-    #[error("failed to send activity event")]
-    Send,
+    #[error("failed to queue activity event")]
+    Queue,
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn catch_up_from_db(
-    sender: &mut futures::stream::SplitSink<WebSocket, Message>,
+    outbound: &OutboundQueue,
     pool: &PgPool,
     project_id: Uuid,
     organization_id: Uuid,
     last_seq: i64,
     target_seq: i64,
     batch_size: i64,
+    filter: &ActivityFilter,
 ) -> Result<i64, CatchUpError> {
     let repository = ActivityRepository::new(pool);
     let mut current_seq = last_seq;
@@ -477,7 +767,7 @@ async fn catch_up_from_db(
 
     loop {
         let events = repository
-            .fetch_since(project_id, Some(cursor), batch_size)
+            .fetch_since(project_id, Some(cursor), batch_size, &ActivityFilter::default())
             .await
             .map_err(|error| {
                 tracing::error!(?error, org_id = %organization_id, project_id = %project_id, "failed to fetch activity catch up");
@@ -496,8 +786,8 @@ async fn catch_up_from_db(
             if event.seq > target_seq {
                 return Ok(current_seq);
             }
-            if send_activity(sender, &event).await.is_err() {
-                return Err(CatchUpError::Send);
+            if queue_activity(outbound, filter, &event).is_err() {
+                return Err(CatchUpError::Queue);
             }
             current_seq = event.seq;
             cursor = event.seq;
@@ -510,3 +800,188 @@ async fn catch_up_from_db(
 
     Ok(current_seq)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use futures::{SinkExt, channel::mpsc};
+    use sqlx::postgres::PgPoolOptions;
+
+    use super::*;
+    use crate::ws::OutboundTotals;
+
+    /// In-memory socket. The client side reads whatever the session sends
+    /// only when the test polls the other end of `outbound`.
+    struct TestSocket {
+        inbound: mpsc::Receiver<Result<Message, axum::Error>>,
+        outbound: mpsc::Sender<Message>,
+    }
+
+    impl Stream for TestSocket {
+        type Item = Result<Message, axum::Error>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.inbound.poll_next_unpin(cx)
+        }
+    }
+
+    impl Sink<Message> for TestSocket {
+        type Error = mpsc::SendError;
+
+        fn poll_ready(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            self.outbound.poll_ready_unpin(cx)
+        }
+
+        fn start_send(mut self: Pin<&mut Self>, message: Message) -> Result<(), Self::Error> {
+            self.outbound.start_send_unpin(message)
+        }
+
+        fn poll_flush(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            self.outbound.poll_flush_unpin(cx)
+        }
+
+        fn poll_close(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            self.outbound.poll_close_unpin(cx)
+        }
+    }
+
+    fn test_session(
+        broker: &ActivityBroker,
+        sessions: &ActiveSessions,
+        project_id: Uuid,
+        outbound_queue_capacity: usize,
+    ) -> Session {
+        // Nothing listens on this port, so history and cursor lookups fail
+        // fast and the session streams only what the broker publishes
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(200))
+            .connect_lazy("postgres://vk@127.0.0.1:1/vk")
+            .unwrap();
+        let (user_id, session_id) = (Uuid::new_v4(), Uuid::new_v4());
+        Session {
+            auth_state: WsAuthState::new(
+                Arc::new(JwtService::new(Vec::new())),
+                pool.clone(),
+                session_id,
+                user_id,
+                project_id,
+                Utc::now() + ChronoDuration::hours(1),
+            ),
+            pool,
+            broker: broker.clone(),
+            sessions: sessions.clone(),
+            settings: SessionSettings {
+                outbound_queue_capacity,
+                send_timeout: Duration::from_secs(10),
+                ping_interval: Duration::from_secs(300),
+                history_limit: 200,
+                catchup_batch_size: 100,
+            },
+            user_id,
+            session_id,
+            organization_id: Uuid::new_v4(),
+            params: WsQueryParams {
+                project_id,
+                cursor: None,
+                protocol: WS_PROTOCOL_VERSION,
+                task_id: None,
+                event_types: None,
+                device_id: None,
+            },
+        }
+    }
+
+    async fn wait_for(what: &str, mut done: impl FnMut() -> bool) {
+        time::timeout(Duration::from_secs(5), async {
+            while !done() {
+                time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("timed out waiting for {what}"));
+    }
+
+    #[tokio::test]
+    async fn stalled_client_is_told_to_bulk_sync() {
+        let capacity = 8;
+        let project_id = Uuid::new_v4();
+        let broker = ActivityBroker::default();
+        let sessions = ActiveSessions::default();
+        let (_client_sender, inbound) = mpsc::channel(1);
+        // No buffer: the session's writer blocks until the client reads
+        let (outbound, mut client) = mpsc::channel(0);
+        let session = tokio::spawn(run(
+            TestSocket { inbound, outbound },
+            test_session(&broker, &sessions, project_id, capacity),
+        ));
+        wait_for("the session to subscribe", || {
+            sessions.count(project_id) == 1
+        })
+        .await;
+
+        for seq in 1..=(capacity as i64 * 4) {
+            broker.publish(ActivityEvent::new(
+                seq,
+                Uuid::new_v4(),
+                project_id,
+                "task.updated".to_string(),
+                Utc::now(),
+                None,
+            ));
+            tokio::task::yield_now().await;
+        }
+        // The overflow shows up on the live counters while the client is
+        // still not reading
+        wait_for("the outbound queue to overflow", || {
+            sessions.outbound_totals().dropped == 1
+        })
+        .await;
+
+        let mut activity = 0;
+        let mut errors = Vec::new();
+        while let Some(message) = time::timeout(Duration::from_secs(5), client.next())
+            .await
+            .expect("session should flush its control frames and close")
+        {
+            let Message::Text(text) = message else {
+                continue;
+            };
+            match serde_json::from_str(&text).unwrap() {
+                ServerMessage::Activity(_) => activity += 1,
+                ServerMessage::Error { message } => errors.push(message),
+                other => panic!("unexpected frame {other:?}"),
+            }
+        }
+
+        time::timeout(Duration::from_secs(5), session)
+            .await
+            .expect("session should end after dropping the slow client")
+            .unwrap();
+        assert_eq!(errors, ["activity backlog dropped"]);
+        // Only frames already handed to the socket reach the client; the
+        // queued backlog is discarded in favour of the bulk sync
+        assert!(activity <= 2, "client received {activity} activity frames");
+        assert_eq!(sessions.total(), 0);
+        assert_eq!(
+            sessions.outbound_totals(),
+            OutboundTotals {
+                queued: 0,
+                max_depth: 0,
+                dropped: 1
+            }
+        );
+    }
+}
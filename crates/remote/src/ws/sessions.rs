@@ -0,0 +1,181 @@
+//! Open websocket sessions per project, logged as they come and go so
+//! capacity can be watched without a metrics backend.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use uuid::Uuid;
+
+use super::outbound::QueueStats;
+
+#[derive(Debug, Clone, Default)]
+pub struct ActiveSessions {
+    projects: Arc<Mutex<HashMap<Uuid, usize>>>,
+    queues: Arc<Mutex<Vec<Arc<QueueStats>>>>,
+    /// Frames dropped by sessions that have since closed
+    closed_dropped: Arc<AtomicU64>,
+}
+
+/// Outbound queue totals across all sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutboundTotals {
+    /// Activity frames currently waiting to be sent
+    pub queued: usize,
+    /// Deepest backlog of any open session
+    pub max_depth: usize,
+    /// Activity frames dropped since startup
+    pub dropped: u64,
+}
+
+impl ActiveSessions {
+    /// Count a session for `project_id` until the returned guard is dropped.
+    /// Its queue stats count towards [`Self::outbound_totals`] meanwhile.
+    pub fn track(&self, project_id: Uuid, queue: Arc<QueueStats>) -> ActiveSessionGuard {
+        self.queues.lock().unwrap().push(queue.clone());
+        let (project_sessions, total_sessions) = {
+            let mut projects = self.projects.lock().unwrap();
+            let count = projects.entry(project_id).or_default();
+            *count += 1;
+            (*count, projects.values().sum::<usize>())
+        };
+        tracing::info!(
+            %project_id,
+            project_sessions,
+            total_sessions,
+            "websocket session opened"
+        );
+        ActiveSessionGuard {
+            sessions: self.clone(),
+            project_id,
+            queue,
+        }
+    }
+
+    pub fn count(&self, project_id: Uuid) -> usize {
+        self.projects
+            .lock()
+            .unwrap()
+            .get(&project_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn total(&self) -> usize {
+        self.projects.lock().unwrap().values().sum()
+    }
+
+    pub fn outbound_totals(&self) -> OutboundTotals {
+        let queues = self.queues.lock().unwrap();
+        OutboundTotals {
+            queued: queues.iter().map(|queue| queue.depth()).sum(),
+            max_depth: queues.iter().map(|queue| queue.depth()).max().unwrap_or(0),
+            dropped: self.closed_dropped.load(Ordering::Relaxed)
+                + queues.iter().map(|queue| queue.dropped()).sum::<u64>(),
+        }
+    }
+
+    fn release(&self, project_id: Uuid, queue: &Arc<QueueStats>) {
+        {
+            let mut queues = self.queues.lock().unwrap();
+            queues.retain(|tracked| !Arc::ptr_eq(tracked, queue));
+            self.closed_dropped
+                .fetch_add(queue.dropped(), Ordering::Relaxed);
+        }
+        let (project_sessions, total_sessions) = {
+            let mut projects = self.projects.lock().unwrap();
+            let remaining = match projects.get_mut(&project_id) {
+                Some(count) if *count > 1 => {
+                    *count -= 1;
+                    *count
+                }
+                _ => {
+                    projects.remove(&project_id);
+                    0
+                }
+            };
+            (remaining, projects.values().sum::<usize>())
+        };
+        let totals = self.outbound_totals();
+        tracing::info!(
+            %project_id,
+            project_sessions,
+            total_sessions,
+            outbound_queued = totals.queued,
+            outbound_dropped = totals.dropped,
+            "websocket session closed"
+        );
+    }
+}
+
+pub struct ActiveSessionGuard {
+    sessions: ActiveSessions,
+    project_id: Uuid,
+    queue: Arc<QueueStats>,
+}
+
+impl Drop for ActiveSessionGuard {
+    fn drop(&mut self) {
+        self.sessions.release(self.project_id, &self.queue);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::extract::ws::Message;
+
+    use super::*;
+    use crate::ws::outbound::OutboundQueue;
+
+    #[test]
+    fn counts_sessions_until_guards_drop() {
+        let sessions = ActiveSessions::default();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let first = sessions.track(a, Arc::default());
+        let second = sessions.track(a, Arc::default());
+        let other = sessions.track(b, Arc::default());
+        assert_eq!((sessions.count(a), sessions.count(b)), (2, 1));
+        assert_eq!(sessions.total(), 3);
+
+        drop(first);
+        drop(other);
+        assert_eq!((sessions.count(a), sessions.count(b)), (1, 0));
+        drop(second);
+        assert_eq!(sessions.total(), 0);
+        assert!(sessions.projects.lock().unwrap().is_empty());
+        assert!(sessions.queues.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn outbound_totals_keep_drops_of_closed_sessions() {
+        let sessions = ActiveSessions::default();
+        let queue = OutboundQueue::new(2);
+        let guard = sessions.track(Uuid::new_v4(), queue.stats().clone());
+        for _ in 0..3 {
+            let _ = queue.push_activity(Message::Text("event".into()));
+        }
+        assert_eq!(
+            sessions.outbound_totals(),
+            OutboundTotals {
+                queued: 2,
+                max_depth: 2,
+                dropped: 1
+            }
+        );
+
+        drop(guard);
+        assert_eq!(
+            sessions.outbound_totals(),
+            OutboundTotals {
+                queued: 0,
+                max_depth: 0,
+                dropped: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,33 @@
+//! Normalize the stored logs of past coding agent runs into persisted
+//! conversation entries. Takes an optional batch size, default 50.
+
+use db::DBService;
+use services::services::session_entries;
+use tracing_subscriber::EnvFilter;
+
+const DEFAULT_BATCH_SIZE: i64 = 50;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new("info"))
+        .init();
+
+    let batch_size = match std::env::args().nth(1) {
+        Some(arg) => arg
+            .parse::<i64>()
+            .ok()
+            .filter(|size| *size > 0)
+            .ok_or_else(|| anyhow::anyhow!("Invalid batch size '{}'", arg))?,
+        None => DEFAULT_BATCH_SIZE,
+    };
+
+    let db = DBService::new().await?;
+    let report = session_entries::backfill(&db, batch_size).await?;
+    tracing::info!(
+        "Done: {} runs recorded, {} skipped",
+        report.recorded,
+        report.skipped
+    );
+    Ok(())
+}
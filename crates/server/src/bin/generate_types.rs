@@ -12,17 +12,54 @@ fn generate_types_content() -> String {
     let decls: Vec<String> = vec![
         services::services::filesystem::DirectoryEntry::decl(),
         services::services::filesystem::DirectoryListResponse::decl(),
+        db::models::executor_session_entry::ExecutorSessionEntry::decl(),
         db::models::project::Project::decl(),
+        db::models::project::SharedTaskAutoCreate::decl(),
         db::models::project::CreateProject::decl(),
         db::models::project::UpdateProject::decl(),
         db::models::project::SearchResult::decl(),
         db::models::project::SearchMatchType::decl(),
         server::routes::projects::CreateRemoteProjectRequest::decl(),
         server::routes::projects::LinkToExistingRequest::decl(),
+        server::routes::projects::ApplyProjectSuggestionsRequest::decl(),
+        server::routes::projects::ProjectUsage::decl(),
+        services::services::disk_usage::ProjectDiskUsage::decl(),
+        db::models::task_attempt::WorktreeDiskUsage::decl(),
+        server::routes::projects::SessionEntriesQuery::decl(),
+        server::routes::projects::ProjectEnvVars::decl(),
+        server::routes::projects::UpdateProjectEnvVars::decl(),
+        server::routes::projects::ImportProjectRequest::decl(),
+        server::routes::projects::CleanupWorktreesRequest::decl(),
+        server::routes::projects::RepairShareLinksRequest::decl(),
+        services::services::share::ShareLinkRepairReport::decl(),
+        services::services::dev_server::DevServerState::decl(),
+        services::services::dev_server::DevServerStatus::decl(),
+        services::services::worktree_cleanup::CleanupSkipReason::decl(),
+        services::services::worktree_cleanup::CleanupItem::decl(),
+        services::services::worktree_cleanup::SkippedCleanupItem::decl(),
+        services::services::worktree_cleanup::WorktreeCleanupReport::decl(),
+        services::services::bulk_tasks::BulkTaskOperation::decl(),
+        services::services::bulk_tasks::BulkTaskRequest::decl(),
+        services::services::bulk_tasks::BulkTaskOutcome::decl(),
+        services::services::bulk_tasks::BulkTaskResult::decl(),
+        services::services::bulk_tasks::BulkTaskResponse::decl(),
+        services::services::board_transfer::BoardExport::decl(),
+        services::services::board_transfer::BoardProject::decl(),
+        services::services::board_transfer::BoardTask::decl(),
+        services::services::board_transfer::BoardAttempt::decl(),
+        services::services::board_transfer::BoardProcess::decl(),
+        services::services::board_transfer::ImportBoardRequest::decl(),
+        services::services::board_transfer::ImportBoardResponse::decl(),
+        services::services::repo_analysis::ProjectAnalysis::decl(),
+        services::services::repo_analysis::ProjectSuggestion::decl(),
+        services::services::repo_analysis::SuggestionKind::decl(),
+        services::services::repo_analysis::SuggestionConfidence::decl(),
+        services::services::repo_analysis::SuggestionEvidence::decl(),
         executors::actions::ExecutorAction::decl(),
         executors::mcp_config::McpConfig::decl(),
         executors::actions::ExecutorActionType::decl(),
         executors::actions::script::ScriptContext::decl(),
+        executors::actions::setup_steps::SetupStep::decl(),
         executors::actions::script::ScriptRequest::decl(),
         executors::actions::script::ScriptRequestLanguage::decl(),
         executors::executors::BaseCodingAgent::decl(),
@@ -31,13 +68,31 @@ fn generate_types_content() -> String {
         db::models::tag::CreateTag::decl(),
         db::models::tag::UpdateTag::decl(),
         server::routes::tags::TagSearchParams::decl(),
+        db::models::prompt_template::PromptTemplate::decl(),
+        db::models::prompt_template::CreatePromptTemplate::decl(),
+        db::models::prompt_template::UpdatePromptTemplate::decl(),
+        server::routes::prompt_templates::PromptTemplateQuery::decl(),
+        db::models::task_search::SearchHighlight::decl(),
+        db::models::task_search::TaskSearchHit::decl(),
+        server::routes::search::TaskSearchQuery::decl(),
+        server::routes::search::TaskSearchResponse::decl(),
+        db::models::api_token::ApiTokenScope::decl(),
+        db::models::api_token::ApiToken::decl(),
+        db::models::api_token::CreateApiToken::decl(),
+        server::routes::api_tokens::CreateApiTokenResponse::decl(),
         db::models::task::TaskStatus::decl(),
         db::models::task::Task::decl(),
         db::models::task::TaskWithAttemptStatus::decl(),
+        db::models::task::TrashedTask::decl(),
+        db::models::queued_attempt::QueuedAttempt::decl(),
+        db::models::queued_followup::QueuedFollowUp::decl(),
+        server::routes::execution_queue::MoveQueuedAttemptRequest::decl(),
         db::models::task::TaskRelationships::decl(),
         db::models::task::CreateTask::decl(),
         db::models::task::UpdateTask::decl(),
         db::models::shared_task::SharedTask::decl(),
+        db::models::shared_task::SharedTaskDivergenceReport::decl(),
+        db::models::shared_task_outbox::SharedTaskOutboxStatus::decl(),
         db::models::image::Image::decl(),
         db::models::image::CreateImage::decl(),
         utils::response::ApiResponse::<()>::decl(),
@@ -45,6 +100,10 @@ fn generate_types_content() -> String {
         utils::api::oauth::ProfileResponse::decl(),
         utils::api::oauth::ProviderProfile::decl(),
         utils::api::oauth::StatusResponse::decl(),
+        utils::api::oauth::AuthSessionSummary::decl(),
+        utils::api::oauth::ListSessionsResponse::decl(),
+        utils::api::oauth::RevokeSessionResponse::decl(),
+        db::models::oauth_handoff::HandoffProgress::decl(),
         utils::api::organizations::MemberRole::decl(),
         utils::api::organizations::InvitationStatus::decl(),
         utils::api::organizations::Organization::decl(),
@@ -66,22 +125,37 @@ fn generate_types_content() -> String {
         utils::api::organizations::ListMembersResponse::decl(),
         utils::api::organizations::UpdateMemberRoleRequest::decl(),
         utils::api::organizations::UpdateMemberRoleResponse::decl(),
+        utils::api::organizations::AuditAction::decl(),
+        utils::api::organizations::AuditLogEntry::decl(),
+        utils::api::organizations::ListAuditLogResponse::decl(),
         utils::api::projects::RemoteProject::decl(),
         utils::api::projects::ListProjectsResponse::decl(),
         utils::api::projects::RemoteProjectMembersResponse::decl(),
         server::routes::config::UserSystemInfo::decl(),
+        services::services::config::reload::ConfigReloadStatus::decl(),
         server::routes::config::Environment::decl(),
         server::routes::config::McpServerQuery::decl(),
         server::routes::config::UpdateMcpServersBody::decl(),
+        server::routes::config::UpdateMcpServersResponse::decl(),
+        server::routes::config::McpServerProblems::decl(),
+        server::routes::config::McpValidateMode::decl(),
+        server::routes::config::RestoreMcpBackupBody::decl(),
+        executors::mcp_config::McpConfigBackup::decl(),
+        executors::mcp_validation::McpSpawnCheck::decl(),
+        executors::mcp_validation::McpSpawnStatus::decl(),
         server::routes::config::GetMcpServerResponse::decl(),
         server::routes::config::CheckEditorAvailabilityQuery::decl(),
         server::routes::config::CheckEditorAvailabilityResponse::decl(),
+        server::routes::config::OpenFileInEditorRequest::decl(),
         server::routes::config::CheckAgentAvailabilityQuery::decl(),
         executors::executors::AvailabilityInfo::decl(),
+        executors::health::ExecutorHealth::decl(),
+        server::routes::config::ExecutorProfileHealth::decl(),
         server::routes::task_attempts::CreateFollowUpAttempt::decl(),
         services::services::drafts::DraftResponse::decl(),
         services::services::drafts::UpdateFollowUpDraftRequest::decl(),
         services::services::drafts::UpdateRetryFollowUpDraftRequest::decl(),
+        server::routes::task_attempts::queued_followups::EnqueueFollowUpRequest::decl(),
         server::routes::task_attempts::ChangeTargetBranchRequest::decl(),
         server::routes::task_attempts::ChangeTargetBranchResponse::decl(),
         server::routes::task_attempts::RenameBranchRequest::decl(),
@@ -91,7 +165,14 @@ fn generate_types_content() -> String {
         server::routes::task_attempts::OpenEditorResponse::decl(),
         server::routes::shared_tasks::AssignSharedTaskRequest::decl(),
         server::routes::shared_tasks::AssignSharedTaskResponse::decl(),
+        server::routes::shared_tasks::SharedSyncStatus::decl(),
+        server::routes::shared_tasks::LocalTaskLink::decl(),
+        server::routes::shared_tasks::AssignedSharedTask::decl(),
+        server::routes::shared_tasks::AssignedSharedTasksResponse::decl(),
+        services::services::share::ProjectSyncState::decl(),
+        services::services::share::ProjectSyncStatus::decl(),
         server::routes::tasks::ShareTaskResponse::decl(),
+        server::routes::tasks::AddTaskDependencyRequest::decl(),
         server::routes::tasks::CreateAndStartTaskRequest::decl(),
         server::routes::task_attempts::CreateGitHubPrRequest::decl(),
         server::routes::images::ImageResponse::decl(),
@@ -105,14 +186,20 @@ fn generate_types_content() -> String {
         services::services::config::SoundFile::decl(),
         services::services::config::UiLanguage::decl(),
         services::services::config::ShowcaseState::decl(),
+        services::services::privacy::PrivacyMode::decl(),
+        services::services::log_batch::LogBatchConfig::decl(),
         services::services::git::GitBranch::decl(),
         utils::diff::Diff::decl(),
         utils::diff::DiffChangeKind::decl(),
         executors::command::CommandBuilder::decl(),
+        executors::command::CommandPreview::decl(),
         executors::profile::ExecutorProfileId::decl(),
         executors::profile::ExecutorConfig::decl(),
         executors::profile::ExecutorConfigs::decl(),
         executors::executors::BaseAgentCapability::decl(),
+        executors::executors::ExecutorCapabilities::decl(),
+        executors::executors::ConfigFileFormat::decl(),
+        server::routes::config::ExecutorInfo::decl(),
         executors::executors::claude::ClaudeCode::decl(),
         executors::executors::gemini::Gemini::decl(),
         executors::executors::amp::Amp::decl(),
@@ -133,6 +220,7 @@ fn generate_types_content() -> String {
         executors::actions::coding_agent_initial::CodingAgentInitialRequest::decl(),
         executors::actions::coding_agent_follow_up::CodingAgentFollowUpRequest::decl(),
         server::routes::task_attempts::CreateTaskAttemptBody::decl(),
+        server::routes::task_attempts::RetryTaskAttemptRequest::decl(),
         server::routes::task_attempts::RunAgentSetupRequest::decl(),
         server::routes::task_attempts::RunAgentSetupResponse::decl(),
         server::routes::task_attempts::gh_cli_setup::GhCliSetupError::decl(),
@@ -143,10 +231,20 @@ fn generate_types_content() -> String {
         server::routes::task_attempts::CommitInfo::decl(),
         server::routes::task_attempts::BranchStatus::decl(),
         services::services::git::ConflictOp::decl(),
+        services::services::git::DiffFileSummary::decl(),
+        services::services::git::CommitSummary::decl(),
+        server::routes::execution_processes::ExecutionProcessCommits::decl(),
+        db::models::task_attempt::VerifyStatus::decl(),
         db::models::task_attempt::TaskAttempt::decl(),
+        db::models::attempt_event::AttemptEventType::decl(),
+        db::models::attempt_event::AttemptEvent::decl(),
+        db::models::task_attempt_plan::TaskAttemptPlan::decl(),
+        executors::logs::plan::PlanStep::decl(),
         db::models::execution_process::ExecutionProcess::decl(),
+        db::models::execution_process::ProcessResourceUsage::decl(),
         db::models::execution_process::ExecutionProcessStatus::decl(),
         db::models::execution_process::ExecutionProcessRunReason::decl(),
+        db::models::execution_process_usage::TaskUsage::decl(),
         db::models::merge::Merge::decl(),
         db::models::merge::DirectMerge::decl(),
         db::models::merge::PrMerge::decl(),
@@ -156,6 +254,8 @@ fn generate_types_content() -> String {
         db::models::draft::DraftType::decl(),
         executors::logs::CommandExitStatus::decl(),
         executors::logs::CommandRunResult::decl(),
+        executors::logs::NormalizedConversation::decl(),
+        executors::logs::usage::UsageSummary::decl(),
         executors::logs::NormalizedEntry::decl(),
         executors::logs::NormalizedEntryType::decl(),
         executors::logs::FileChange::decl(),
@@ -166,6 +266,12 @@ fn generate_types_content() -> String {
         executors::logs::ToolResultValueType::decl(),
         executors::logs::ToolStatus::decl(),
         executors::logs::utils::patch::PatchType::decl(),
+        executors::logs::utils::patch::EntryUpdate::decl(),
+        services::services::conversation_compare::ConversationComparison::decl(),
+        services::services::conversation_compare::AlignedEntryPair::decl(),
+        services::services::conversation_compare::AlignmentKind::decl(),
+        services::services::conversation_compare::WordDiffSegment::decl(),
+        services::services::conversation_compare::WordDiffKind::decl(),
         utils::approvals::ApprovalStatus::decl(),
         utils::approvals::CreateApprovalRequest::decl(),
         utils::approvals::ApprovalResponse::decl(),
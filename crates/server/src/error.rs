@@ -5,20 +5,28 @@ use axum::{
     response::{IntoResponse, Response},
 };
 use db::models::{
-    execution_process::ExecutionProcessError, project::ProjectError, task_attempt::TaskAttemptError,
+    execution_process::ExecutionProcessError, project::ProjectError, task::Task,
+    task_attempt::TaskAttemptError, task_dependency::TaskDependencyError,
 };
 use deployment::{DeploymentError, RemoteClientNotConfigured};
-use executors::executors::ExecutorError;
+use executors::{executors::ExecutorError, health::ExecutorHealth};
 use git2::Error as Git2Error;
 use services::services::{
+    board_transfer::BoardTransferError,
+    bulk_tasks::BulkTaskError,
     config::{ConfigError, EditorOpenError},
     container::ContainerError,
     drafts::DraftsServiceError,
     git::GitServiceError,
+    git_forge::GitForgeError,
     github::GitHubServiceError,
     image::ImageError,
+    pr_monitor::PrMonitorError,
+    project_import::ProjectImportError,
     remote_client::RemoteClientError,
+    repo_analysis::RepoAnalysisError,
     share::ShareError,
+    worktree_cleanup::WorktreeCleanupError,
     worktree_manager::WorktreeError,
 };
 use thiserror::Error;
@@ -32,12 +40,16 @@ pub enum ApiError {
     #[error(transparent)]
     TaskAttempt(#[from] TaskAttemptError),
     #[error(transparent)]
+    TaskDependency(#[from] TaskDependencyError),
+    #[error(transparent)]
     ExecutionProcess(#[from] ExecutionProcessError),
     #[error(transparent)]
     GitService(#[from] GitServiceError),
     #[error(transparent)]
     GitHubService(#[from] GitHubServiceError),
     #[error(transparent)]
+    GitForge(#[from] GitForgeError),
+    #[error(transparent)]
     Deployment(#[from] DeploymentError),
     #[error(transparent)]
     Container(#[from] ContainerError),
@@ -53,6 +65,8 @@ pub enum ApiError {
     Image(#[from] ImageError),
     #[error(transparent)]
     Drafts(#[from] DraftsServiceError),
+    #[error(transparent)]
+    ProjectImport(#[from] ProjectImportError),
     #[error("Multipart error: {0}")]
     Multipart(#[from] MultipartError),
     #[error("IO error: {0}")]
@@ -65,10 +79,19 @@ pub enum ApiError {
     Unauthorized,
     #[error("Bad request: {0}")]
     BadRequest(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
     #[error("Conflict: {0}")]
     Conflict(String),
     #[error("Forbidden: {0}")]
     Forbidden(String),
+    #[error("Timed out: {0}")]
+    TimedOut(String),
+    #[error("Executor unavailable: {}", .0.error.as_deref().unwrap_or("unknown reason"))]
+    ExecutorUnavailable(ExecutorHealth),
+    /// The task depends on these tasks, which are not done yet
+    #[error("Waiting on unfinished dependencies: {}", .0.iter().map(|task| task.title.as_str()).collect::<Vec<_>>().join(", "))]
+    TaskBlocked(Vec<Task>),
 }
 
 impl From<&'static str> for ApiError {
@@ -83,6 +106,49 @@ impl From<Git2Error> for ApiError {
     }
 }
 
+impl From<WorktreeCleanupError> for ApiError {
+    fn from(err: WorktreeCleanupError) -> Self {
+        match err {
+            WorktreeCleanupError::Database(err) => ApiError::Database(err),
+            WorktreeCleanupError::GitService(err) => ApiError::GitService(err),
+        }
+    }
+}
+
+impl From<BulkTaskError> for ApiError {
+    fn from(err: BulkTaskError) -> Self {
+        match err {
+            BulkTaskError::Database(err) => ApiError::Database(err),
+            BulkTaskError::TaskAttempt(err) => ApiError::TaskAttempt(err),
+            BulkTaskError::TooManyTasks | BulkTaskError::MissingStatus => {
+                ApiError::BadRequest(err.to_string())
+            }
+        }
+    }
+}
+
+impl From<BoardTransferError> for ApiError {
+    fn from(err: BoardTransferError) -> Self {
+        match err {
+            BoardTransferError::Database(err) => ApiError::Database(err),
+            BoardTransferError::TaskAttempt(err) => ApiError::TaskAttempt(err),
+            BoardTransferError::UnsupportedVersion(_) | BoardTransferError::Invalid { .. } => {
+                ApiError::BadRequest(err.to_string())
+            }
+        }
+    }
+}
+
+impl From<PrMonitorError> for ApiError {
+    fn from(err: PrMonitorError) -> Self {
+        match err {
+            PrMonitorError::GitForge(err) => ApiError::GitForge(err),
+            PrMonitorError::TaskAttemptError(err) => ApiError::TaskAttempt(err),
+            PrMonitorError::Sqlx(err) => ApiError::Database(err),
+        }
+    }
+}
+
 impl From<RemoteClientNotConfigured> for ApiError {
     fn from(_: RemoteClientNotConfigured) -> Self {
         ApiError::BadRequest("Remote client not configured".to_string())
@@ -91,9 +157,40 @@ impl From<RemoteClientNotConfigured> for ApiError {
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
+        // Carries the health check so the client can say what is missing
+        if let ApiError::ExecutorUnavailable(health) = self {
+            let message = health
+                .error
+                .clone()
+                .unwrap_or_else(|| "Executor is not available".to_string());
+            let response =
+                ApiResponse::<(), ExecutorHealth>::error_with_message_and_data(&message, health);
+            return (StatusCode::UNPROCESSABLE_ENTITY, Json(response)).into_response();
+        }
+        // Lists the blocking tasks so the client can show or override them
+        if let ApiError::TaskBlocked(tasks) = &self {
+            let response = ApiResponse::<(), Vec<Task>>::error_with_message_and_data(
+                &self.to_string(),
+                tasks.clone(),
+            );
+            return (StatusCode::CONFLICT, Json(response)).into_response();
+        }
+
         let (status_code, error_type) = match &self {
-            ApiError::Project(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ProjectError"),
+            ApiError::Project(err) => match err {
+                ProjectError::ProjectNotFound => (StatusCode::NOT_FOUND, "ProjectError"),
+                ProjectError::Archived => (StatusCode::CONFLICT, "ProjectError"),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "ProjectError"),
+            },
             ApiError::TaskAttempt(_) => (StatusCode::INTERNAL_SERVER_ERROR, "TaskAttemptError"),
+            ApiError::TaskDependency(err) => match err {
+                TaskDependencyError::Database(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "TaskDependencyError")
+                }
+                TaskDependencyError::TaskNotFound => (StatusCode::NOT_FOUND, "TaskDependencyError"),
+                TaskDependencyError::Cycle(_) => (StatusCode::CONFLICT, "TaskDependencyError"),
+                _ => (StatusCode::BAD_REQUEST, "TaskDependencyError"),
+            },
             ApiError::ExecutionProcess(err) => match err {
                 ExecutionProcessError::ExecutionProcessNotFound => {
                     (StatusCode::NOT_FOUND, "ExecutionProcessError")
@@ -110,8 +207,27 @@ impl IntoResponse for ApiError {
                 }
                 _ => (StatusCode::INTERNAL_SERVER_ERROR, "GitServiceError"),
             },
+            ApiError::GitHubService(GitHubServiceError::RateLimited(_)) => {
+                (StatusCode::TOO_MANY_REQUESTS, "GitHubServiceError")
+            }
+            ApiError::GitHubService(GitHubServiceError::TimedOut(_)) => {
+                (StatusCode::GATEWAY_TIMEOUT, "GitHubServiceError")
+            }
             ApiError::GitHubService(_) => (StatusCode::INTERNAL_SERVER_ERROR, "GitHubServiceError"),
+            ApiError::GitForge(err) if err.is_rate_limited() => {
+                (StatusCode::TOO_MANY_REQUESTS, "GitForgeError")
+            }
+            ApiError::GitForge(GitForgeError::UnsupportedRemote(_)) => {
+                (StatusCode::BAD_REQUEST, "GitForgeError")
+            }
+            ApiError::GitForge(GitForgeError::GitHub(GitHubServiceError::TimedOut(_))) => {
+                (StatusCode::GATEWAY_TIMEOUT, "GitForgeError")
+            }
+            ApiError::GitForge(_) => (StatusCode::INTERNAL_SERVER_ERROR, "GitForgeError"),
             ApiError::Deployment(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DeploymentError"),
+            ApiError::Container(ContainerError::ShuttingDown) => {
+                (StatusCode::SERVICE_UNAVAILABLE, "ContainerError")
+            }
             ApiError::Container(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ContainerError"),
             ApiError::Executor(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ExecutorError"),
             ApiError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DatabaseError"),
@@ -136,6 +252,12 @@ impl IntoResponse for ApiError {
                     (StatusCode::INTERNAL_SERVER_ERROR, "ExecutionProcessError")
                 }
             },
+            ApiError::ProjectImport(err) => match err {
+                ProjectImportError::Io(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "ProjectImportError")
+                }
+                _ => (StatusCode::BAD_REQUEST, "ProjectImportError"),
+            },
             ApiError::Io(_) => (StatusCode::INTERNAL_SERVER_ERROR, "IoError"),
             ApiError::EditorOpen(err) => match err {
                 EditorOpenError::LaunchFailed { .. } => {
@@ -153,6 +275,7 @@ impl IntoResponse for ApiError {
                     "RemoteClientError",
                 ),
                 RemoteClientError::Token(_) => (StatusCode::BAD_GATEWAY, "RemoteClientError"),
+                RemoteClientError::ResyncRequired => (StatusCode::CONFLICT, "RemoteClientError"),
                 RemoteClientError::Api(code) => match code {
                     services::services::remote_client::HandoffErrorCode::NotFound => {
                         (StatusCode::NOT_FOUND, "RemoteClientError")
@@ -178,8 +301,14 @@ impl IntoResponse for ApiError {
             },
             ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized"),
             ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, "BadRequest"),
+            ApiError::NotFound(_) => (StatusCode::NOT_FOUND, "NotFound"),
             ApiError::Conflict(_) => (StatusCode::CONFLICT, "ConflictError"),
             ApiError::Forbidden(_) => (StatusCode::FORBIDDEN, "ForbiddenError"),
+            ApiError::TimedOut(_) => (StatusCode::SERVICE_UNAVAILABLE, "TimedOut"),
+            ApiError::ExecutorUnavailable(_) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "ExecutorUnavailable")
+            }
+            ApiError::TaskBlocked(_) => (StatusCode::CONFLICT, "TaskBlocked"),
         };
 
         let error_message = match &self {
@@ -220,6 +349,9 @@ impl IntoResponse for ApiError {
                 RemoteClientError::Storage(_) => {
                     "Failed to persist credentials locally. Please retry.".to_string()
                 }
+                RemoteClientError::ResyncRequired => {
+                    "Shared tasks are out of date and are being resynchronized.".to_string()
+                }
                 RemoteClientError::Api(code) => match code {
                     services::services::remote_client::HandoffErrorCode::NotFound => {
                         "The requested resource was not found.".to_string()
@@ -245,6 +377,9 @@ impl IntoResponse for ApiError {
                     services::services::remote_client::HandoffErrorCode::InternalError => {
                         "Internal remote service error. Please try again.".to_string()
                     }
+                    services::services::remote_client::HandoffErrorCode::InvalidEmailDomain => {
+                        "That email domain is not allowed in this organization.".to_string()
+                    }
                     services::services::remote_client::HandoffErrorCode::Other(msg) => {
                         format!("Authentication error: {}", msg)
                     }
@@ -252,10 +387,17 @@ impl IntoResponse for ApiError {
                 RemoteClientError::Serde(_) => "Unexpected response from remote service.".to_string(),
                 RemoteClientError::Url(_) => "Remote service URL is invalid.".to_string(),
             },
+            ApiError::GitForge(err @ GitForgeError::UnsupportedRemote(_)) => err.to_string(),
+            ApiError::TaskDependency(err @ TaskDependencyError::Database(_)) => {
+                format!("{}: {}", error_type, err)
+            }
+            ApiError::TaskDependency(err) => err.to_string(),
             ApiError::Unauthorized => "Unauthorized. Please sign in again.".to_string(),
             ApiError::BadRequest(msg) => msg.clone(),
+            ApiError::NotFound(msg) => msg.clone(),
             ApiError::Conflict(msg) => msg.clone(),
             ApiError::Forbidden(msg) => msg.clone(),
+            ApiError::TimedOut(msg) => msg.clone(),
             ApiError::Drafts(drafts_err) => match drafts_err {
                 DraftsServiceError::Conflict(msg) => msg.clone(),
                 DraftsServiceError::Database(_) => format!("{}: {}", error_type, drafts_err),
@@ -328,3 +470,20 @@ impl From<ShareError> for ApiError {
         }
     }
 }
+
+impl From<RepoAnalysisError> for ApiError {
+    fn from(err: RepoAnalysisError) -> Self {
+        match err {
+            RepoAnalysisError::Io(err) => ApiError::Io(err),
+            RepoAnalysisError::TimedOut => ApiError::TimedOut(
+                "Repository analysis took too long. Configure the project manually.".to_string(),
+            ),
+            RepoAnalysisError::UnknownSuggestion(_) => ApiError::Conflict(format!(
+                "{err}. The repository changed since it was analyzed, analyze it again."
+            )),
+            RepoAnalysisError::RepoNotFound(_)
+            | RepoAnalysisError::Conflicting(_)
+            | RepoAnalysisError::NotApplicable(_) => ApiError::BadRequest(err.to_string()),
+        }
+    }
+}
@@ -1,10 +1,13 @@
+use std::time::Duration;
+
 use anyhow::{self, Error as AnyhowError};
 use deployment::{Deployment, DeploymentError};
 use server::{DeploymentImpl, routes};
-use services::services::container::ContainerService;
+use services::services::{container::ContainerService, shutdown::DEFAULT_SHUTDOWN_TIMEOUT};
 use sqlx::Error as SqlxError;
 use strip_ansi_escapes::strip;
 use thiserror::Error;
+use tokio::sync::oneshot;
 use tracing_subscriber::{EnvFilter, prelude::*};
 use utils::{
     assets::asset_dir,
@@ -28,6 +31,7 @@ pub enum VibeKanbanError {
 #[tokio::main]
 async fn main() -> Result<(), VibeKanbanError> {
     sentry_utils::init_once(SentrySource::Backend);
+    let shutdown_timeout = shutdown_timeout_from_args()?;
 
     let log_level = std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
     let filter_string = format!(
@@ -57,7 +61,19 @@ async fn main() -> Result<(), VibeKanbanError> {
         .backfill_before_head_commits()
         .await
         .map_err(DeploymentError::from)?;
+    // Attempts left queued by the previous run
+    deployment
+        .container()
+        .start_queued_attempts()
+        .await
+        .map_err(DeploymentError::from)?;
     deployment.spawn_pr_monitor_service().await;
+    if let Err(e) = deployment.spawn_config_reloader() {
+        tracing::warn!("Config file changes will not be picked up: {}", e);
+    }
+    if let Err(e) = deployment.spawn_mcp_config_watcher() {
+        tracing::warn!("MCP config file changes will not be picked up: {}", e);
+    }
     deployment
         .track_if_analytics_allowed("session_start", serde_json::json!({}))
         .await;
@@ -113,15 +129,55 @@ async fn main() -> Result<(), VibeKanbanError> {
         });
     }
 
-    axum::serve(listener, app_router)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    let (stop_tx, stop_rx) = oneshot::channel::<()>();
+    let mut server = tokio::spawn(
+        axum::serve(listener, app_router)
+            .with_graceful_shutdown(async move {
+                let _ = stop_rx.await;
+            })
+            .into_future(),
+    );
+    tokio::select! {
+        _ = shutdown_signal() => {}
+        result = &mut server => {
+            result.map_err(AnyhowError::from)??;
+            return Ok(());
+        }
+    }
 
-    perform_cleanup_actions(&deployment).await;
+    tracing::info!("Shutting down...");
+    // Stop accepting connections; open event streams are dropped once the
+    // cleanup is done
+    let _ = stop_tx.send(());
+    perform_cleanup_actions(&deployment, shutdown_timeout).await;
+    server.abort();
 
     Ok(())
 }
 
+/// `--shutdown-timeout <secs>`: how long running executors get to stop, and
+/// their output to be written, before they are killed.
+fn shutdown_timeout_from_args() -> Result<Duration, VibeKanbanError> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = if arg == "--shutdown-timeout" {
+            args.next()
+        } else if let Some(value) = arg.strip_prefix("--shutdown-timeout=") {
+            Some(value.to_string())
+        } else {
+            continue;
+        };
+        return value
+            .as_deref()
+            .and_then(|secs| secs.trim().parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .ok_or_else(|| {
+                anyhow::anyhow!("--shutdown-timeout expects a number of seconds").into()
+            });
+    }
+    Ok(DEFAULT_SHUTDOWN_TIMEOUT)
+}
+
 pub async fn shutdown_signal() {
     // Always wait for Ctrl+C
     let ctrl_c = async {
@@ -158,10 +214,13 @@ pub async fn shutdown_signal() {
     }
 }
 
-pub async fn perform_cleanup_actions(deployment: &DeploymentImpl) {
-    deployment
-        .container()
-        .kill_all_running_processes()
-        .await
-        .expect("Failed to cleanly kill running execution processes");
+pub async fn perform_cleanup_actions(deployment: &DeploymentImpl, timeout: Duration) {
+    let summary = deployment.shutdown(timeout).await;
+    tracing::info!(
+        processes_stopped = summary.processes_stopped,
+        processes_failed = summary.processes_failed,
+        logs_flushed = summary.logs_flushed,
+        remote_sync_stopped = summary.remote_sync_stopped,
+        "Shutdown complete"
+    );
 }
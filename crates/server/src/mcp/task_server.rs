@@ -559,8 +559,9 @@ impl TaskServer {
 
         let payload = CreateTaskAttemptBody {
             task_id,
-            executor_profile_id,
+            executor_profile_id: Some(executor_profile_id),
             base_branch,
+            override_dependencies: false,
         };
 
         let url = self.url("/api/task-attempts");
@@ -0,0 +1,112 @@
+use axum::{
+    extract::{Request, State},
+    http::{Method, StatusCode, header},
+    middleware::Next,
+    response::Response,
+};
+use db::models::api_token::{ApiToken, ApiTokenScope};
+use deployment::Deployment;
+use services::services::api_tokens::{self, API_TOKEN_PREFIX, ApiTokenError};
+
+use crate::DeploymentImpl;
+
+/// Routes a task execution token may call, relative to `/api`
+const TASK_EXECUTION_ROUTES: &[&str] = &["/tasks", "/task-attempts", "/execution-processes"];
+
+/// Authenticates `Authorization: Bearer vk_...` requests and enforces the
+/// token's scope. Requests without an API token pass through unchanged. The
+/// authenticated [`ApiToken`] is inserted as an extension.
+pub async fn api_token_middleware(
+    State(deployment): State<DeploymentImpl>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let Some(presented) = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .filter(|value| value.starts_with(API_TOKEN_PREFIX))
+    else {
+        return Ok(next.run(request).await);
+    };
+
+    let token = match api_tokens::authenticate(&deployment.db().pool, presented).await {
+        Ok(token) => token,
+        Err(ApiTokenError::Database(e)) => {
+            tracing::error!("Failed to check API token: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Err(e) => {
+            tracing::warn!("Rejected API token: {}", e);
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+    };
+
+    let allowed = match token.scope {
+        ApiTokenScope::ReadOnly => matches!(
+            *request.method(),
+            Method::GET | Method::HEAD | Method::OPTIONS
+        ),
+        ApiTokenScope::TaskExecution => is_task_execution_route(request.uri().path()),
+    };
+    if !allowed {
+        tracing::warn!(
+            "API token {} with scope {:?} used for {} {}",
+            token.id,
+            token.scope,
+            request.method(),
+            request.uri().path()
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    request.extensions_mut().insert(token);
+    Ok(next.run(request).await)
+}
+
+fn is_task_execution_route(path: &str) -> bool {
+    TASK_EXECUTION_ROUTES.iter().any(|route| {
+        path.strip_prefix(route)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+/// Keeps token management to the interactive UI, so a leaked token cannot
+/// mint or revoke others.
+pub async fn reject_api_token_middleware(
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    if request.extensions().get::<ApiToken>().is_some() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn task_execution_tokens_are_limited_to_task_routes() {
+        for path in [
+            "/tasks",
+            "/tasks/create-and-start",
+            "/task-attempts/7b0c/follow-up",
+            "/execution-processes/7b0c/stop",
+        ] {
+            assert!(is_task_execution_route(path), "{path}");
+        }
+        for path in [
+            "/config",
+            "/profiles",
+            "/projects/7b0c",
+            "/tasks-export",
+            "/settings/tokens",
+            "/images/upload",
+        ] {
+            assert!(!is_task_execution_route(path), "{path}");
+        }
+    }
+}
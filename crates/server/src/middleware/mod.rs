@@ -1,3 +1,9 @@
+pub mod api_token;
 pub mod model_loaders;
+pub mod privacy;
+pub mod request_tracing;
 
+pub use api_token::*;
 pub use model_loaders::*;
+pub use privacy::*;
+pub use request_tracing::*;
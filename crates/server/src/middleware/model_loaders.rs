@@ -5,8 +5,8 @@ use axum::{
     response::Response,
 };
 use db::models::{
-    execution_process::ExecutionProcess, project::Project, tag::Tag, task::Task,
-    task_attempt::TaskAttempt,
+    execution_process::ExecutionProcess, project::Project, prompt_template::PromptTemplate,
+    tag::Tag, task::Task, task_attempt::TaskAttempt,
 };
 use deployment::Deployment;
 use uuid::Uuid;
@@ -147,3 +147,26 @@ pub async fn load_tag_middleware(
     // Continue with the next middleware/handler
     Ok(next.run(request).await)
 }
+
+// Middleware that loads and injects PromptTemplate based on the template_id path parameter
+pub async fn load_prompt_template_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(template_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let template = match PromptTemplate::find_by_id(&deployment.db().pool, template_id).await {
+        Ok(Some(template)) => template,
+        Ok(None) => {
+            tracing::warn!("Prompt template {} not found", template_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch prompt template {}: {}", template_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(template);
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,70 @@
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Query, Request, State},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::Response,
+};
+use deployment::Deployment;
+use services::services::privacy::{PrivacyMode, PrivacyParams, Redactor};
+
+use crate::DeploymentImpl;
+
+/// The mode to apply for a request: the stricter of `?privacy=` and the
+/// server-wide config setting.
+pub async fn effective_privacy_mode(
+    deployment: &DeploymentImpl,
+    params: &PrivacyParams,
+) -> Option<PrivacyMode> {
+    let forced = deployment.config().read().await.privacy_mode;
+    PrivacyMode::resolve(params.privacy, forced)
+}
+
+/// Rewrites JSON responses through a [`Redactor`] when a privacy mode is in
+/// effect. Non-JSON responses pass through untouched.
+pub async fn redact_response_middleware(
+    State(deployment): State<DeploymentImpl>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let params = match Query::<PrivacyParams>::try_from_uri(request.uri()) {
+        Ok(Query(params)) => params,
+        Err(e) => {
+            tracing::warn!("Invalid privacy parameter: {}", e);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+    let Some(mode) = effective_privacy_mode(&deployment, &params).await else {
+        return Ok(next.run(request).await);
+    };
+
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !is_json {
+        return Ok(response);
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = to_bytes(body, usize::MAX).await.map_err(|e| {
+        tracing::error!("Failed to buffer response for redaction: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let mut value: serde_json::Value = serde_json::from_slice(&bytes).map_err(|e| {
+        tracing::error!("Failed to parse response for redaction: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Redactor::new(mode).redact_value(&mut value);
+    let redacted = serde_json::to_vec(&value).map_err(|e| {
+        tracing::error!("Failed to serialize redacted response: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    parts
+        .headers
+        .insert(header::CONTENT_LENGTH, HeaderValue::from(redacted.len()));
+    Ok(Response::from_parts(parts, Body::from(redacted)))
+}
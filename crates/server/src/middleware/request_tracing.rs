@@ -0,0 +1,121 @@
+use std::time::{Duration, Instant};
+
+use axum::{
+    Router,
+    extract::Request,
+    http::header::HeaderName,
+    middleware::{Next, from_fn},
+    response::Response,
+};
+use tower_http::request_id::{
+    MakeRequestUuid, PropagateRequestIdLayer, RequestId, SetRequestIdLayer,
+};
+use tracing::Instrument;
+use utils::request_id::{self, REQUEST_ID_HEADER};
+
+/// Requests taking longer than this to produce a response are logged as
+/// warnings
+const SLOW_REQUEST_THRESHOLD: Duration = Duration::from_secs(2);
+
+/// Give every request an id, reusing the caller's `x-request-id` if set,
+/// and echo it back on the response. Handlers run inside a span carrying
+/// the id, and it is available to them through [`request_id::current`].
+pub fn request_tracing<S>(router: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let header = HeaderName::from_static(REQUEST_ID_HEADER);
+    router
+        .layer(from_fn(request_tracing_middleware))
+        .layer(PropagateRequestIdLayer::new(header.clone()))
+        .layer(SetRequestIdLayer::new(header, MakeRequestUuid))
+}
+
+async fn request_tracing_middleware(request: Request, next: Next) -> Response {
+    let id = request
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let span = tracing::info_span!("request", request_id = %id, %method, %path);
+
+    async move {
+        let started = Instant::now();
+        let response = request_id::scope(id, next.run(request)).await;
+        let latency = started.elapsed();
+        let status = response.status().as_u16();
+        if latency > SLOW_REQUEST_THRESHOLD {
+            tracing::warn!(
+                status,
+                latency_ms = latency.as_millis() as u64,
+                "Slow request {} {}",
+                method,
+                path
+            );
+        } else {
+            tracing::info!(
+                status,
+                latency_ms = latency.as_millis() as u64,
+                "{} {}",
+                method,
+                path
+            );
+        }
+        response
+    }
+    .instrument(span)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Json, http::StatusCode, routing::get};
+    use utils::response::ApiResponse;
+
+    use super::*;
+
+    async fn serve(router: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn request_id_round_trips_and_is_reported_in_errors() {
+        let router = request_tracing(Router::new().route(
+            "/fail",
+            get(|| async {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::<()>::error("Bad input")),
+                )
+            }),
+        ));
+        let base = serve(router).await;
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("{base}/fail"))
+            .header(REQUEST_ID_HEADER, "req-123")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.headers()[REQUEST_ID_HEADER], "req-123");
+        let body: ApiResponse<()> = res.json().await.unwrap();
+        assert_eq!(body.request_id(), Some("req-123"));
+
+        // Requests without one get a fresh id
+        let res = client.get(format!("{base}/fail")).send().await.unwrap();
+        let generated = res.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(!generated.is_empty());
+        let body: ApiResponse<()> = res.json().await.unwrap();
+        assert_eq!(body.request_id(), Some(generated.as_str()));
+    }
+}
@@ -0,0 +1,87 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    middleware::from_fn,
+    response::Json as ResponseJson,
+    routing::{delete, get},
+};
+use db::models::api_token::{ApiToken, CreateApiToken};
+use deployment::Deployment;
+use serde::Serialize;
+use services::services::api_tokens;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::reject_api_token_middleware};
+
+#[derive(Debug, Serialize, TS)]
+pub struct CreateApiTokenResponse {
+    pub token: ApiToken,
+    /// The bearer value; it cannot be retrieved again
+    pub value: String,
+}
+
+pub async fn list_api_tokens(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ApiToken>>>, ApiError> {
+    let tokens = ApiToken::find_all(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(tokens)))
+}
+
+pub async fn create_api_token(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateApiToken>,
+) -> Result<ResponseJson<ApiResponse<CreateApiTokenResponse>>, ApiError> {
+    if payload.name.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "Token name cannot be empty".to_string(),
+        ));
+    }
+    if payload
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= chrono::Utc::now())
+    {
+        return Err(ApiError::BadRequest(
+            "Token expiry must be in the future".to_string(),
+        ));
+    }
+
+    let (token, value) = api_tokens::issue(&deployment.db().pool, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "api_token_created",
+            serde_json::json!({
+                "scope": token.scope,
+                "expires": token.expires_at.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(CreateApiTokenResponse {
+        token,
+        value,
+    })))
+}
+
+pub async fn revoke_api_token(
+    State(deployment): State<DeploymentImpl>,
+    Path(token_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if ApiToken::find_by_id(pool, token_id).await?.is_none() {
+        return Err(ApiError::Database(sqlx::Error::RowNotFound));
+    }
+    ApiToken::revoke(pool, token_id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    let inner = Router::new()
+        .route("/", get(list_api_tokens).post(create_api_token))
+        .route("/{token_id}", delete(revoke_api_token))
+        .layer(from_fn(reject_api_token_middleware));
+
+    Router::new().nest("/settings/tokens", inner)
+}
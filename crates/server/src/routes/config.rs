@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use axum::{
     Json, Router,
@@ -6,14 +6,27 @@ use axum::{
     extract::{Path, Query, State},
     http,
     response::{Json as ResponseJson, Response},
-    routing::{get, put},
+    routing::{get, post, put},
+};
+use db::models::{
+    project::{Project, ProjectError},
+    task::Task,
+    task_attempt::TaskAttempt,
 };
 use deployment::{Deployment, DeploymentError};
 use executors::{
     executors::{
-        AvailabilityInfo, BaseAgentCapability, BaseCodingAgent, StandardCodingAgentExecutor,
+        AvailabilityInfo, BaseAgentCapability, BaseCodingAgent, CodingAgent, ExecutorCapabilities,
+        StandardCodingAgentExecutor,
+    },
+    health::{self, ExecutorHealth},
+    mcp_config::{
+        McpConfig, McpConfigBackup, backup_agent_config, list_agent_config_backups, mcp_backup_dir,
+        read_agent_config, restore_agent_config, write_agent_config,
+    },
+    mcp_validation::{
+        McpSpawnCheck, SPAWN_CHECK_TIMEOUT, spawn_check_mcp_servers, validate_mcp_servers,
     },
-    mcp_config::{McpConfig, read_agent_config, write_agent_config},
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
 use serde::{Deserialize, Serialize};
@@ -21,13 +34,19 @@ use serde_json::Value;
 use services::services::config::{
     Config, ConfigError, SoundFile,
     editor::{EditorConfig, EditorType},
-    save_config_to_file,
+    reload::ConfigReloadStatus,
+    resolve_executor_args, save_config_to_file,
 };
 use tokio::fs;
 use ts_rs::TS;
 use utils::{api::oauth::LoginStatus, assets::config_path, response::ApiResponse};
+use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    routes::task_attempts::{OpenEditorResponse, util::ensure_worktree_path},
+};
 
 pub fn router() -> Router<DeploymentImpl> {
     Router::new()
@@ -35,12 +54,17 @@ pub fn router() -> Router<DeploymentImpl> {
         .route("/config", put(update_config))
         .route("/sounds/{sound}", get(get_sound))
         .route("/mcp-config", get(get_mcp_servers).post(update_mcp_servers))
+        .route("/mcp-config/backups", get(list_mcp_backups))
+        .route("/mcp-config/restore", post(restore_mcp_backup))
         .route("/profiles", get(get_profiles).put(update_profiles))
         .route(
             "/editors/check-availability",
             get(check_editor_availability),
         )
+        .route("/editor/open", post(open_file_in_editor))
         .route("/agents/check-availability", get(check_agent_availability))
+        .route("/executors", get(list_executors))
+        .route("/executors/health", get(get_executors_health))
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -79,6 +103,8 @@ pub struct UserSystemInfo {
     pub environment: Environment,
     /// Capabilities supported per executor (e.g., { "CLAUDE_CODE": ["SESSION_FORK"] })
     pub capabilities: HashMap<String, Vec<BaseAgentCapability>>,
+    /// Outcome of picking up edits made to the config file on disk
+    pub config_reload: ConfigReloadStatus,
 }
 
 // TODO: update frontend, BE schema has changed, this replaces GET /config and /config/constants
@@ -105,6 +131,7 @@ async fn get_user_system_info(
             }
             caps
         },
+        config_reload: deployment.config_reloader().status().await,
     };
 
     ResponseJson(ApiResponse::success(user_system_info))
@@ -123,6 +150,11 @@ async fn update_config(
         ));
     }
 
+    let extra_args = match resolve_executor_args(&new_config) {
+        Ok(extra_args) => extra_args,
+        Err(e) => return ResponseJson(ApiResponse::error(&e.to_string())),
+    };
+
     // Get old config state before updating
     let old_config = deployment.config().read().await.clone();
 
@@ -131,6 +163,7 @@ async fn update_config(
             let mut config = deployment.config().write().await;
             *config = new_config.clone();
             drop(config);
+            ExecutorConfigs::set_extra_args(extra_args);
 
             // Track config events when fields transition from false → true and run side effects
             handle_config_events(&deployment, &old_config, &new_config).await;
@@ -183,6 +216,10 @@ async fn handle_config_events(deployment: &DeploymentImpl, old: &Config, new: &C
             deployment_clone.trigger_auto_project_setup().await;
         });
     }
+
+    if old.executor_profile != new.executor_profile {
+        deployment.mcp_config_watcher().refresh();
+    }
 }
 
 async fn get_sound(Path(sound): Path<SoundFile>) -> Result<Response, ApiError> {
@@ -198,9 +235,21 @@ async fn get_sound(Path(sound): Path<SoundFile>) -> Result<Response, ApiError> {
     Ok(response)
 }
 
+#[derive(TS, Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[ts(rename_all = "lowercase")]
+pub enum McpValidateMode {
+    /// Launch each server with `--help` instead of saving
+    Spawn,
+}
+
 #[derive(TS, Debug, Deserialize)]
 pub struct McpServerQuery {
     executor: BaseCodingAgent,
+    /// Only used when updating servers
+    #[serde(default)]
+    #[ts(optional)]
+    validate: Option<McpValidateMode>,
 }
 
 #[derive(TS, Debug, Serialize, Deserialize)]
@@ -215,6 +264,20 @@ pub struct UpdateMcpServersBody {
     servers: HashMap<String, Value>,
 }
 
+#[derive(TS, Debug, Serialize)]
+pub struct UpdateMcpServersResponse {
+    message: String,
+    /// Per-server results for `validate=spawn`, in which case nothing was saved
+    spawn_checks: BTreeMap<String, McpSpawnCheck>,
+}
+
+/// Returned as `error_data` when server entries are malformed
+#[derive(TS, Debug, Serialize)]
+pub struct McpServerProblems {
+    /// Problems keyed by server name
+    servers: BTreeMap<String, Vec<String>>,
+}
+
 async fn get_mcp_servers(
     State(_deployment): State<DeploymentImpl>,
     Query(query): Query<McpServerQuery>,
@@ -255,7 +318,7 @@ async fn update_mcp_servers(
     State(_deployment): State<DeploymentImpl>,
     Query(query): Query<McpServerQuery>,
     Json(payload): Json<UpdateMcpServersBody>,
-) -> Result<ResponseJson<ApiResponse<String>>, ApiError> {
+) -> Result<ResponseJson<ApiResponse<UpdateMcpServersResponse, McpServerProblems>>, ApiError> {
     let profiles = ExecutorConfigs::get_cached();
     let agent = profiles
         .get_coding_agent(&ExecutorProfileId::new(query.executor))
@@ -280,8 +343,32 @@ async fn update_mcp_servers(
     };
 
     let mcpc = agent.get_mcp_config();
-    match update_mcp_servers_in_config(&config_path, &mcpc, payload.servers).await {
-        Ok(message) => Ok(ResponseJson(ApiResponse::success(message))),
+    let problems = validate_mcp_servers(&payload.servers, mcpc.is_toml_config);
+    if !problems.is_empty() {
+        return Ok(ResponseJson(ApiResponse::error_with_message_and_data(
+            "Invalid MCP server configuration",
+            McpServerProblems { servers: problems },
+        )));
+    }
+
+    if let Some(McpValidateMode::Spawn) = query.validate {
+        let spawn_checks = spawn_check_mcp_servers(&payload.servers, SPAWN_CHECK_TIMEOUT).await;
+        return Ok(ResponseJson(ApiResponse::success(
+            UpdateMcpServersResponse {
+                message: format!("Checked {} MCP server(s)", spawn_checks.len()),
+                spawn_checks,
+            },
+        )));
+    }
+
+    let backup_dir = mcp_backup_dir(&query.executor.to_string());
+    match update_mcp_servers_in_config(&config_path, &backup_dir, &mcpc, payload.servers).await {
+        Ok(message) => Ok(ResponseJson(ApiResponse::success(
+            UpdateMcpServersResponse {
+                message,
+                spawn_checks: BTreeMap::new(),
+            },
+        ))),
         Err(e) => Ok(ResponseJson(ApiResponse::error(&format!(
             "Failed to update MCP servers: {}",
             e
@@ -291,6 +378,7 @@ async fn update_mcp_servers(
 
 async fn update_mcp_servers_in_config(
     config_path: &std::path::Path,
+    backup_dir: &std::path::Path,
     mcpc: &McpConfig,
     new_servers: HashMap<String, Value>,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
@@ -307,7 +395,9 @@ async fn update_mcp_servers_in_config(
     // Set the MCP servers using the correct attribute path
     set_mcp_servers_in_config_path(&mut config, &mcpc.servers_path, &new_servers)?;
 
-    // Write the updated config back to file (JSON or TOML depending on agent)
+    // Keep a copy of the file as it was, then write the updated config back
+    // (JSON or TOML depending on agent)
+    backup_agent_config(config_path, backup_dir).await?;
     write_agent_config(config_path, mcpc, &config).await?;
 
     let new_count = new_servers.len();
@@ -369,16 +459,75 @@ fn set_mcp_servers_in_config_path(
         }
     }
 
-    // Set the final attribute
+    // Set the final attribute, keeping servers that already existed in their
+    // original order and adding new ones after them
     let final_attr = path.last().unwrap();
+    let mut ordered = serde_json::Map::new();
+    if let Some(Value::Object(existing)) = current.get(final_attr.as_str()) {
+        for name in existing.keys() {
+            if let Some(server) = servers.get(name) {
+                ordered.insert(name.clone(), server.clone());
+            }
+        }
+    }
+    let mut added: Vec<_> = servers
+        .iter()
+        .filter(|(name, _)| !ordered.contains_key(name.as_str()))
+        .collect();
+    added.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, server) in added {
+        ordered.insert(name.clone(), server.clone());
+    }
     current
         .as_object_mut()
         .unwrap()
-        .insert(final_attr.to_string(), serde_json::to_value(servers)?);
+        .insert(final_attr.to_string(), Value::Object(ordered));
 
     Ok(())
 }
 
+async fn list_mcp_backups(
+    Query(query): Query<McpServerQuery>,
+) -> ResponseJson<ApiResponse<Vec<McpConfigBackup>>> {
+    match list_agent_config_backups(&mcp_backup_dir(&query.executor.to_string())).await {
+        Ok(backups) => ResponseJson(ApiResponse::success(backups)),
+        Err(e) => ResponseJson(ApiResponse::error(&format!(
+            "Failed to list MCP config backups: {e}"
+        ))),
+    }
+}
+
+#[derive(TS, Debug, Deserialize)]
+pub struct RestoreMcpBackupBody {
+    id: String,
+}
+
+async fn restore_mcp_backup(
+    Query(query): Query<McpServerQuery>,
+    Json(payload): Json<RestoreMcpBackupBody>,
+) -> Result<ResponseJson<ApiResponse<String>>, ApiError> {
+    let agent = ExecutorConfigs::get_cached()
+        .get_coding_agent(&ExecutorProfileId::new(query.executor))
+        .ok_or(ConfigError::ValidationError(
+            "Executor not found".to_string(),
+        ))?;
+    let Some(config_path) = agent.default_mcp_config_path() else {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Could not determine config file path",
+        )));
+    };
+
+    let backup_dir = mcp_backup_dir(&query.executor.to_string());
+    if restore_agent_config(&config_path, &backup_dir, &payload.id).await? {
+        Ok(ResponseJson(ApiResponse::success(format!(
+            "Restored {}",
+            config_path.display()
+        ))))
+    } else {
+        Ok(ResponseJson(ApiResponse::error("Backup not found")))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProfilesContent {
     pub content: String,
@@ -406,7 +555,7 @@ async fn get_profiles(
 }
 
 async fn update_profiles(
-    State(_deployment): State<DeploymentImpl>,
+    State(deployment): State<DeploymentImpl>,
     body: String,
 ) -> ResponseJson<ApiResponse<String>> {
     // Try to parse as ExecutorProfileConfigs format
@@ -418,6 +567,8 @@ async fn update_profiles(
                     tracing::info!("Executor profiles saved successfully");
                     // Reload the cached profiles
                     ExecutorConfigs::reload();
+                    // Variants may point their MCP config somewhere else now
+                    deployment.mcp_config_watcher().refresh();
                     ResponseJson(ApiResponse::success(
                         "Executor profiles updated successfully".to_string(),
                     ))
@@ -466,6 +617,64 @@ async fn check_editor_availability(
     }))
 }
 
+#[derive(Debug, Deserialize, TS)]
+pub struct OpenFileInEditorRequest {
+    project_id: Uuid,
+    /// Resolve `path` against this attempt's worktree rather than the
+    /// project's repository
+    attempt_id: Option<Uuid>,
+    /// Relative to the project or worktree root
+    path: String,
+    line: Option<u32>,
+    editor_type: Option<String>,
+}
+
+async fn open_file_in_editor(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<OpenFileInEditorRequest>,
+) -> Result<ResponseJson<ApiResponse<OpenEditorResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let project = Project::find_by_id(pool, payload.project_id)
+        .await?
+        .ok_or(ProjectError::ProjectNotFound)?;
+
+    let root = match payload.attempt_id {
+        Some(attempt_id) => {
+            let attempt = TaskAttempt::find_by_id(pool, attempt_id)
+                .await?
+                .ok_or_else(|| ApiError::NotFound("Task attempt not found".to_string()))?;
+            if Task::find_by_id_and_project_id(pool, attempt.task_id, project.id)
+                .await?
+                .is_none()
+            {
+                return Err(ApiError::BadRequest(
+                    "Task attempt does not belong to the project".to_string(),
+                ));
+            }
+            ensure_worktree_path(&deployment, &attempt).await?
+        }
+        None => project.git_repo_path.clone(),
+    };
+
+    let editor_config = {
+        let config = deployment.config().read().await;
+        config.editor.with_override(payload.editor_type.as_deref())
+    };
+    let url = editor_config
+        .open_in_editor(&root, &payload.path, payload.line)
+        .await?;
+    tracing::info!(
+        "Opened {} in editor for project {}{}",
+        payload.path,
+        project.id,
+        if url.is_some() { " (remote mode)" } else { "" }
+    );
+
+    Ok(ResponseJson(ApiResponse::success(OpenEditorResponse {
+        url,
+    })))
+}
+
 #[derive(Debug, Serialize, Deserialize, TS)]
 pub struct CheckAgentAvailabilityQuery {
     executor: BaseCodingAgent,
@@ -485,3 +694,65 @@ async fn check_agent_availability(
 
     ResponseJson(ApiResponse::success(info))
 }
+
+#[derive(Debug, Serialize, TS)]
+pub struct ExecutorProfileHealth {
+    pub profile: ExecutorProfileId,
+    pub health: ExecutorHealth,
+}
+
+/// Whether each configured executor profile can be launched. Results are
+/// cached briefly, so repeated calls do not re-run version checks.
+async fn get_executors_health(
+    State(_deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<Vec<ExecutorProfileHealth>>> {
+    let profiles = ExecutorConfigs::get_cached();
+    let mut agents: Vec<(ExecutorProfileId, CodingAgent)> = profiles
+        .executors
+        .iter()
+        .flat_map(|(executor, config)| {
+            config.configurations.iter().map(|(variant, agent)| {
+                let profile = if variant == "DEFAULT" {
+                    ExecutorProfileId::new(*executor)
+                } else {
+                    ExecutorProfileId::with_variant(*executor, variant.clone())
+                };
+                (profile, agent.clone())
+            })
+        })
+        .collect();
+    agents.sort_by_key(|(profile, _)| profile.to_string());
+
+    let checks = agents.into_iter().map(|(profile, agent)| async move {
+        let health = health::cached_availability(&profile, &agent).await;
+        ExecutorProfileHealth { profile, health }
+    });
+    ResponseJson(ApiResponse::success(
+        futures_util::future::join_all(checks).await,
+    ))
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ExecutorInfo {
+    pub executor: BaseCodingAgent,
+    pub display_name: String,
+    pub capabilities: ExecutorCapabilities,
+}
+
+/// Every configured executor with what it supports.
+async fn list_executors(
+    State(_deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<Vec<ExecutorInfo>>> {
+    let profiles = ExecutorConfigs::get_cached();
+    let mut executors: Vec<ExecutorInfo> = profiles
+        .executors
+        .keys()
+        .map(|executor| ExecutorInfo {
+            executor: *executor,
+            display_name: executor.display_name().to_string(),
+            capabilities: executor.capabilities(),
+        })
+        .collect();
+    executors.sort_by_key(|info| info.executor.to_string());
+    ResponseJson(ApiResponse::success(executors))
+}
@@ -1,25 +1,47 @@
+use std::time::Duration;
+
 use anyhow;
 use axum::{
-    Extension, Router,
+    BoxError, Extension, Router,
     extract::{
         Path, Query, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
     middleware::from_fn_with_state,
-    response::{IntoResponse, Json as ResponseJson},
+    response::{
+        IntoResponse, Json as ResponseJson, Sse,
+        sse::{Event, KeepAlive},
+    },
     routing::{get, post},
 };
 use db::models::execution_process::{
     ExecutionProcess, ExecutionProcessError, ExecutionProcessStatus,
 };
 use deployment::Deployment;
+use executors::logs::utils::patch::entry_updates_from_patch;
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
-use serde::Deserialize;
-use services::services::container::ContainerService;
+use serde::{Deserialize, Serialize};
+use services::services::{
+    container::{ContainerService, DEFAULT_STOP_GRACE},
+    git::CommitSummary,
+    privacy::{PrivacyMode, PrivacyParams, Redactor},
+};
+use ts_rs::TS;
 use utils::{log_msg::LogMsg, response::ApiResponse};
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_execution_process_middleware};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{
+        effective_privacy_mode, load_execution_process_middleware, redact_response_middleware,
+    },
+};
+
+const SSE_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Upper bound on the grace period a stop request can ask for.
+const MAX_STOP_GRACE: Duration = Duration::from_secs(60);
 
 #[derive(Debug, Deserialize)]
 pub struct ExecutionProcessQuery {
@@ -36,11 +58,68 @@ pub async fn get_execution_process_by_id(
     Ok(ResponseJson(ApiResponse::success(execution_process)))
 }
 
+/// Most commits returned for one process; more only happens when the agent
+/// reset the worktree onto unrelated history.
+const MAX_PROCESS_COMMITS: usize = 200;
+
+#[derive(Debug, Serialize, TS)]
+pub struct ExecutionProcessCommits {
+    pub before_head_commit: Option<String>,
+    /// `None` while the process is still running
+    pub after_head_commit: Option<String>,
+    pub commits: Vec<CommitSummary>,
+    pub history_rewritten: bool,
+}
+
+pub async fn get_execution_process_commits(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcessCommits>>, ApiError> {
+    let ExecutionProcess {
+        id,
+        before_head_commit,
+        after_head_commit,
+        ..
+    } = execution_process;
+    let range = match (&before_head_commit, &after_head_commit) {
+        (Some(before), Some(after)) => {
+            let pool = &deployment.db().pool;
+            let ctx = ExecutionProcess::load_context(pool, id).await?;
+            let project = ctx
+                .task
+                .parent_project(pool)
+                .await?
+                .ok_or(sqlx::Error::RowNotFound)?;
+            Some(deployment.git().commits_between(
+                &project.git_repo_path,
+                before,
+                after,
+                MAX_PROCESS_COMMITS,
+            )?)
+        }
+        _ => None,
+    };
+    let (commits, history_rewritten) = range
+        .map(|range| (range.commits, range.history_rewritten))
+        .unwrap_or_default();
+
+    Ok(ResponseJson(ApiResponse::success(
+        ExecutionProcessCommits {
+            before_head_commit,
+            after_head_commit,
+            commits,
+            history_rewritten,
+        },
+    )))
+}
+
 pub async fn stream_raw_logs_ws(
     ws: WebSocketUpgrade,
     State(deployment): State<DeploymentImpl>,
     Path(exec_id): Path<Uuid>,
+    Query(privacy): Query<PrivacyParams>,
 ) -> Result<impl IntoResponse, ApiError> {
+    let privacy = effective_privacy_mode(&deployment, &privacy).await;
     // Check if the stream exists before upgrading the WebSocket
     let _stream = deployment
         .container()
@@ -51,7 +130,7 @@ pub async fn stream_raw_logs_ws(
         })?;
 
     Ok(ws.on_upgrade(move |socket| async move {
-        if let Err(e) = handle_raw_logs_ws(socket, deployment, exec_id).await {
+        if let Err(e) = handle_raw_logs_ws(socket, deployment, exec_id, privacy).await {
             tracing::warn!("raw logs WS closed: {}", e);
         }
     }))
@@ -61,6 +140,7 @@ async fn handle_raw_logs_ws(
     socket: WebSocket,
     deployment: DeploymentImpl,
     exec_id: Uuid,
+    privacy: Option<PrivacyMode>,
 ) -> anyhow::Result<()> {
     use std::sync::{
         Arc,
@@ -78,21 +158,26 @@ async fn handle_raw_logs_ws(
         .ok_or_else(|| anyhow::anyhow!("Execution process not found"))?;
 
     let counter = Arc::new(AtomicUsize::new(0));
+    let mut redactor = privacy.map(Redactor::new);
     let mut stream = raw_stream.map_ok({
         let counter = counter.clone();
-        move |m| match m {
-            LogMsg::Stdout(content) => {
-                let index = counter.fetch_add(1, Ordering::SeqCst);
-                let patch = ConversationPatch::add_stdout(index, content);
-                LogMsg::JsonPatch(patch).to_ws_message_unchecked()
-            }
-            LogMsg::Stderr(content) => {
-                let index = counter.fetch_add(1, Ordering::SeqCst);
-                let patch = ConversationPatch::add_stderr(index, content);
-                LogMsg::JsonPatch(patch).to_ws_message_unchecked()
+        move |m| {
+            let msg = match m {
+                LogMsg::Stdout(content) => {
+                    let index = counter.fetch_add(1, Ordering::SeqCst);
+                    LogMsg::JsonPatch(ConversationPatch::add_stdout(index, content))
+                }
+                LogMsg::Stderr(content) => {
+                    let index = counter.fetch_add(1, Ordering::SeqCst);
+                    LogMsg::JsonPatch(ConversationPatch::add_stderr(index, content))
+                }
+                LogMsg::Finished => LogMsg::Finished,
+                _ => unreachable!("Raw stream should only have Stdout/Stderr/Finished"),
+            };
+            match redactor.as_mut() {
+                Some(redactor) => redactor.redact_log_msg(msg).to_ws_message_unchecked(),
+                None => msg.to_ws_message_unchecked(),
             }
-            LogMsg::Finished => LogMsg::Finished.to_ws_message_unchecked(),
-            _ => unreachable!("Raw stream should only have Stdout/Stderr/Finished"),
         }
     });
 
@@ -123,7 +208,9 @@ pub async fn stream_normalized_logs_ws(
     ws: WebSocketUpgrade,
     State(deployment): State<DeploymentImpl>,
     Path(exec_id): Path<Uuid>,
+    Query(privacy): Query<PrivacyParams>,
 ) -> Result<impl IntoResponse, ApiError> {
+    let privacy = effective_privacy_mode(&deployment, &privacy).await;
     let stream = deployment
         .container()
         .stream_normalized_logs(&exec_id)
@@ -136,7 +223,7 @@ pub async fn stream_normalized_logs_ws(
     let stream = stream.err_into::<anyhow::Error>().into_stream();
 
     Ok(ws.on_upgrade(move |socket| async move {
-        if let Err(e) = handle_normalized_logs_ws(socket, stream).await {
+        if let Err(e) = handle_normalized_logs_ws(socket, stream, privacy).await {
             tracing::warn!("normalized logs WS closed: {}", e);
         }
     }))
@@ -145,8 +232,15 @@ pub async fn stream_normalized_logs_ws(
 async fn handle_normalized_logs_ws(
     socket: WebSocket,
     stream: impl futures_util::Stream<Item = anyhow::Result<LogMsg>> + Unpin + Send + 'static,
+    privacy: Option<PrivacyMode>,
 ) -> anyhow::Result<()> {
-    let mut stream = stream.map_ok(|msg| msg.to_ws_message_unchecked());
+    let mut redactor = privacy.map(Redactor::new);
+    let mut stream = stream.map_ok(move |mut msg| {
+        if let Some(redactor) = redactor.as_mut() {
+            msg = redactor.redact_log_msg(msg);
+        }
+        msg.to_ws_message_unchecked()
+    });
     let (mut sender, mut receiver) = socket.split();
     tokio::spawn(async move { while let Some(Ok(_)) = receiver.next().await {} });
     while let Some(item) = stream.next().await {
@@ -165,13 +259,68 @@ async fn handle_normalized_logs_ws(
     Ok(())
 }
 
+/// Normalized entries as Server-Sent Events. Each `entry` event carries one
+/// `EntryUpdate`; the stream ends with `finished` once the process is done.
+pub async fn stream_normalized_logs_sse(
+    State(deployment): State<DeploymentImpl>,
+    Path(exec_id): Path<Uuid>,
+    Query(privacy): Query<PrivacyParams>,
+) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, BoxError>>>, ApiError> {
+    let privacy = effective_privacy_mode(&deployment, &privacy).await;
+    let stream = deployment
+        .container()
+        .stream_normalized_logs(&exec_id)
+        .await
+        .ok_or_else(|| {
+            ApiError::ExecutionProcess(ExecutionProcessError::ExecutionProcessNotFound)
+        })?;
+
+    let mut redactor = privacy.map(Redactor::new);
+    let events = stream
+        .map_ok(move |mut msg| {
+            if let Some(redactor) = redactor.as_mut() {
+                msg = redactor.redact_log_msg(msg);
+            }
+            let events = match &msg {
+                LogMsg::JsonPatch(patch) => entry_updates_from_patch(patch)
+                    .into_iter()
+                    .filter_map(|update| Event::default().event("entry").json_data(update).ok())
+                    .collect(),
+                other => vec![other.to_sse_event()],
+            };
+            futures_util::stream::iter(events.into_iter().map(Ok::<_, std::io::Error>))
+        })
+        .try_flatten()
+        .map_err(|e| -> BoxError { e.into() });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::new().interval(SSE_KEEP_ALIVE_INTERVAL)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StopExecutionQuery {
+    /// Seconds the process gets to exit after the interrupt before it is
+    /// killed
+    pub grace_secs: Option<u64>,
+}
+
 pub async fn stop_execution_process(
     Extension(execution_process): Extension<ExecutionProcess>,
     State(deployment): State<DeploymentImpl>,
+    Query(query): Query<StopExecutionQuery>,
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if execution_process.status != ExecutionProcessStatus::Running {
+        return Err(ApiError::BadRequest(
+            "Execution process is not running".to_string(),
+        ));
+    }
+    let grace = query
+        .grace_secs
+        .map(|secs| Duration::from_secs(secs).min(MAX_STOP_GRACE))
+        .unwrap_or(DEFAULT_STOP_GRACE);
+
     deployment
         .container()
-        .stop_execution(&execution_process, ExecutionProcessStatus::Killed)
+        .stop_execution_with_grace(&execution_process, ExecutionProcessStatus::Killed, grace)
         .await?;
 
     Ok(ResponseJson(ApiResponse::success(())))
@@ -234,10 +383,18 @@ async fn handle_execution_processes_ws(
 
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_attempt_id_router = Router::new()
-        .route("/", get(get_execution_process_by_id))
+        .route(
+            "/",
+            get(get_execution_process_by_id).layer(from_fn_with_state(
+                deployment.clone(),
+                redact_response_middleware,
+            )),
+        )
         .route("/stop", post(stop_execution_process))
+        .route("/commits", get(get_execution_process_commits))
         .route("/raw-logs/ws", get(stream_raw_logs_ws))
         .route("/normalized-logs/ws", get(stream_normalized_logs_ws))
+        .route("/normalized-logs/stream", get(stream_normalized_logs_sse))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_execution_process_middleware,
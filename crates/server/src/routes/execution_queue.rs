@@ -0,0 +1,64 @@
+use axum::{
+    Router,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+    routing::{delete, get, post},
+};
+use db::models::queued_attempt::QueuedAttempt;
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::container::ContainerService;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct MoveQueuedAttemptRequest {
+    /// Zero-based place to move the attempt to; past the end moves it last
+    pub position: usize,
+}
+
+pub async fn get_execution_queue(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<QueuedAttempt>>>, ApiError> {
+    let queue = QueuedAttempt::list(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(queue)))
+}
+
+pub async fn move_queued_attempt(
+    Path(task_attempt_id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+    ResponseJson(payload): ResponseJson<MoveQueuedAttemptRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<QueuedAttempt>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    // Keep the order stable while the queue is being drained
+    let _guard = deployment.container().execution_queue_lock().lock().await;
+    if !QueuedAttempt::move_to(pool, task_attempt_id, payload.position).await? {
+        return Err(ApiError::Database(sqlx::Error::RowNotFound));
+    }
+    let queue = QueuedAttempt::list(pool).await?;
+    Ok(ResponseJson(ApiResponse::success(queue)))
+}
+
+/// Take an attempt out of the queue so it never starts. The attempt itself
+/// is kept.
+pub async fn cancel_queued_attempt(
+    Path(task_attempt_id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if !QueuedAttempt::remove(&deployment.db().pool, task_attempt_id).await? {
+        return Err(ApiError::Database(sqlx::Error::RowNotFound));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    let inner = Router::new()
+        .route("/", get(get_execution_queue))
+        .route("/{task_attempt_id}", delete(cancel_queued_attempt))
+        .route("/{task_attempt_id}/move", post(move_queued_attempt));
+
+    Router::new().nest("/execution-queue", inner)
+}
@@ -1,10 +1,15 @@
 use axum::{
     Router,
+    middleware::from_fn_with_state,
     routing::{IntoMakeService, get},
 };
 
-use crate::DeploymentImpl;
+use crate::{
+    DeploymentImpl,
+    middleware::{api_token_middleware, request_tracing},
+};
 
+pub mod api_tokens;
 pub mod approvals;
 pub mod config;
 pub mod containers;
@@ -13,12 +18,15 @@ pub mod filesystem;
 pub mod drafts;
 pub mod events;
 pub mod execution_processes;
+pub mod execution_queue;
 pub mod frontend;
 pub mod health;
 pub mod images;
 pub mod oauth;
 pub mod organizations;
 pub mod projects;
+pub mod prompt_templates;
+pub mod search;
 pub mod shared_tasks;
 pub mod tags;
 pub mod task_attempts;
@@ -36,14 +44,19 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
         .merge(shared_tasks::router())
         .merge(task_attempts::router(&deployment))
         .merge(execution_processes::router(&deployment))
+        .merge(execution_queue::router())
         .merge(tags::router(&deployment))
+        .merge(prompt_templates::router(&deployment))
+        .merge(search::router())
         .merge(oauth::router())
         .merge(organizations::router())
         .merge(filesystem::router())
         .merge(events::router(&deployment))
         .merge(approvals::router())
+        .merge(api_tokens::router())
         .nest("/images", images::routes())
-        .with_state(deployment);
+        .layer(from_fn_with_state(deployment.clone(), api_token_middleware));
+    let base_routes = request_tracing(base_routes).with_state(deployment);
 
     Router::new()
         .route("/", get(frontend::serve_frontend_root))
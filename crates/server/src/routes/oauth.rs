@@ -1,17 +1,21 @@
 use axum::{
     Router,
-    extract::{Json, Query, State},
+    extract::{Json, Path, Query, State},
     http::{Response, StatusCode},
     response::Json as ResponseJson,
-    routing::{get, post},
+    routing::{delete, get, post},
 };
+use db::models::oauth_handoff::{HandoffProgress, HandoffRedemption};
 use deployment::Deployment;
 use rand::{Rng, distributions::Alphanumeric};
 use serde::{Deserialize, Serialize};
 use services::services::{config::save_config_to_file, oauth_credentials::Credentials};
 use sha2::{Digest, Sha256};
 use utils::{
-    api::oauth::{HandoffInitRequest, HandoffRedeemRequest, StatusResponse},
+    api::oauth::{
+        HandoffInitRequest, HandoffRedeemRequest, ListSessionsResponse, RevokeSessionResponse,
+        StatusResponse,
+    },
     assets::config_path,
     jwt::extract_expiration,
     response::ApiResponse,
@@ -24,8 +28,11 @@ pub fn router() -> Router<DeploymentImpl> {
     Router::new()
         .route("/auth/handoff/init", post(handoff_init))
         .route("/auth/handoff/complete", get(handoff_complete))
+        .route("/auth/handoff/{handoff_id}/status", get(handoff_status))
         .route("/auth/logout", post(logout))
         .route("/auth/status", get(status))
+        .route("/auth/sessions", get(list_sessions))
+        .route("/auth/sessions/{session_id}", delete(revoke_session))
 }
 
 #[derive(Debug, Deserialize)]
@@ -58,8 +65,8 @@ async fn handoff_init(
     let response = client.handoff_init(&request).await?;
 
     deployment
-        .store_oauth_handoff(response.handoff_id, payload.provider, app_verifier)
-        .await;
+        .store_oauth_handoff(response.handoff_id, &payload.provider, &app_verifier)
+        .await?;
 
     Ok(ResponseJson(ApiResponse::success(
         HandoffInitResponseBody {
@@ -83,6 +90,14 @@ async fn handoff_complete(
     Query(query): Query<HandoffCompleteQuery>,
 ) -> Result<Response<String>, ApiError> {
     if let Some(error) = query.error {
+        // Only a handoff still waiting for its callback can fail this way
+        if let HandoffRedemption::Ready { .. } =
+            deployment.take_oauth_handoff(query.handoff_id).await?
+        {
+            deployment
+                .finish_oauth_handoff(query.handoff_id, Some(&error))
+                .await?;
+        }
         return Ok(simple_html_response(
             StatusCode::BAD_REQUEST,
             format!("OAuth authorization failed: {error}"),
@@ -96,46 +111,50 @@ async fn handoff_complete(
         ));
     };
 
-    let (provider, app_verifier) = match deployment.take_oauth_handoff(&query.handoff_id).await {
-        Some(state) => state,
-        None => {
+    let (provider, app_verifier) = match deployment.take_oauth_handoff(query.handoff_id).await? {
+        HandoffRedemption::Ready {
+            provider,
+            app_verifier,
+        } => (provider, app_verifier),
+        HandoffRedemption::Expired => {
+            tracing::warn!(handoff_id = %query.handoff_id, "received callback for expired handoff");
+            return Ok(simple_html_response(
+                StatusCode::GONE,
+                "This sign-in link has expired. Start the sign-in again from the app.".to_string(),
+            ));
+        }
+        HandoffRedemption::AlreadyUsed => {
+            tracing::warn!(handoff_id = %query.handoff_id, "received repeated callback for handoff");
+            return Ok(simple_html_response(
+                StatusCode::CONFLICT,
+                "This sign-in has already been completed. You can return to the app.".to_string(),
+            ));
+        }
+        HandoffRedemption::Unknown => {
             tracing::warn!(
                 handoff_id = %query.handoff_id,
                 "received callback for unknown handoff"
             );
             return Ok(simple_html_response(
-                StatusCode::BAD_REQUEST,
-                "OAuth handoff not found or already completed".to_string(),
+                StatusCode::NOT_FOUND,
+                "OAuth handoff not found. Start the sign-in again from the app.".to_string(),
             ));
         }
     };
 
-    let client = deployment.remote_client()?;
-
-    let redeem_request = HandoffRedeemRequest {
-        handoff_id: query.handoff_id,
-        app_code,
-        app_verifier,
-    };
-
-    let redeem = client.handoff_redeem(&redeem_request).await?;
-
-    let expires_at = extract_expiration(&redeem.access_token)
-        .map_err(|err| ApiError::BadRequest(format!("Invalid access token: {err}")))?;
-    let credentials = Credentials {
-        access_token: Some(redeem.access_token.clone()),
-        refresh_token: redeem.refresh_token.clone(),
-        expires_at: Some(expires_at),
-    };
-
-    deployment
-        .auth_context()
-        .save_credentials(&credentials)
-        .await
-        .map_err(|e| {
-            tracing::error!(?e, "failed to save credentials");
-            ApiError::Io(e)
-        })?;
+    match redeem_handoff(&deployment, query.handoff_id, app_code, app_verifier).await {
+        Ok(()) => {
+            deployment
+                .finish_oauth_handoff(query.handoff_id, None)
+                .await?;
+        }
+        Err(err) => {
+            deployment
+                .finish_oauth_handoff(query.handoff_id, Some(&err.to_string()))
+                .await?;
+            return Err(err);
+        }
+    }
 
     // Enable analytics automatically on login if not already enabled
     let config_guard = deployment.config().read().await;
@@ -195,9 +214,11 @@ async fn handoff_complete(
         drop(handle_guard);
 
         if should_start {
-            if let Some(share_config) = deployment.share_config() {
+            if let (Some(share_config), Ok(client)) =
+                (deployment.share_config(), deployment.remote_client())
+            {
                 tracing::info!("Starting remote sync after login");
-                deployment.spawn_remote_sync(share_config.clone());
+                deployment.spawn_remote_sync(share_config.clone(), client);
             } else {
                 tracing::debug!(
                     "Share config not available; skipping remote sync spawn after login"
@@ -211,6 +232,51 @@ async fn handoff_complete(
     )))
 }
 
+/// Exchange the app code for tokens and store them.
+async fn redeem_handoff(
+    deployment: &DeploymentImpl,
+    handoff_id: Uuid,
+    app_code: String,
+    app_verifier: String,
+) -> Result<(), ApiError> {
+    let client = deployment.remote_client()?;
+
+    let redeem_request = HandoffRedeemRequest {
+        handoff_id,
+        app_code,
+        app_verifier,
+    };
+
+    let redeem = client.handoff_redeem(&redeem_request).await?;
+
+    let expires_at = extract_expiration(&redeem.access_token)
+        .map_err(|err| ApiError::BadRequest(format!("Invalid access token: {err}")))?;
+    let credentials = Credentials {
+        access_token: Some(redeem.access_token.clone()),
+        refresh_token: redeem.refresh_token.clone(),
+        expires_at: Some(expires_at),
+    };
+
+    deployment
+        .auth_context()
+        .save_credentials(&credentials)
+        .await
+        .map_err(|e| {
+            tracing::error!(?e, "failed to save credentials");
+            ApiError::Io(e)
+        })
+}
+
+/// Polled by the sign-in dialog, which cannot rely on the popup closing
+/// itself.
+async fn handoff_status(
+    State(deployment): State<DeploymentImpl>,
+    Path(handoff_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<HandoffProgress>>, ApiError> {
+    let progress = deployment.oauth_handoff_progress(handoff_id).await?;
+    Ok(ResponseJson(ApiResponse::success(progress)))
+}
+
 async fn logout(State(deployment): State<DeploymentImpl>) -> Result<StatusCode, ApiError> {
     // Stop remote sync if running
     if let Some(handle) = deployment.share_sync_handle().lock().await.take() {
@@ -218,12 +284,18 @@ async fn logout(State(deployment): State<DeploymentImpl>) -> Result<StatusCode,
         handle.shutdown().await;
     }
 
-    let auth_context = deployment.auth_context();
-
     if let Ok(client) = deployment.remote_client() {
         let _ = client.logout().await;
     }
 
+    clear_local_session(&deployment).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn clear_local_session(deployment: &DeploymentImpl) -> Result<(), ApiError> {
+    let auth_context = deployment.auth_context();
+
     auth_context.clear_credentials().await.map_err(|e| {
         tracing::error!(?e, "failed to clear credentials");
         ApiError::Io(e)
@@ -231,7 +303,34 @@ async fn logout(State(deployment): State<DeploymentImpl>) -> Result<StatusCode,
 
     auth_context.clear_profile().await;
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(())
+}
+
+async fn list_sessions(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ListSessionsResponse>>, ApiError> {
+    let client = deployment.remote_client()?;
+    let response = client.list_sessions().await?;
+    Ok(ResponseJson(ApiResponse::success(response)))
+}
+
+/// Revoking the session this app signed in with signs it out, as logout does.
+async fn revoke_session(
+    State(deployment): State<DeploymentImpl>,
+    Path(session_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<RevokeSessionResponse>>, ApiError> {
+    let client = deployment.remote_client()?;
+    let response = client.revoke_session(session_id).await?;
+
+    if response.current {
+        if let Some(handle) = deployment.share_sync_handle().lock().await.take() {
+            tracing::info!("Stopping remote sync after revoking the current session");
+            handle.shutdown().await;
+        }
+        clear_local_session(&deployment).await?;
+    }
+
+    Ok(ResponseJson(ApiResponse::success(response)))
 }
 
 async fn status(
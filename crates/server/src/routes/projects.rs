@@ -1,36 +1,65 @@
-use std::path::Path as StdPath;
+use std::{collections::BTreeMap, path::Path as StdPath, str::FromStr, sync::Arc, time::Duration};
 
 use axum::{
     Extension, Json, Router,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{StatusCode, header},
     middleware::from_fn_with_state,
-    response::Json as ResponseJson,
+    response::{IntoResponse, Json as ResponseJson},
     routing::{get, post},
 };
 use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    execution_process_usage::{ExecutionProcessUsage, TaskUsage},
+    executor_session_entry::ExecutorSessionEntry,
     project::{CreateProject, Project, ProjectError, SearchMatchType, SearchResult, UpdateProject},
-    task::Task,
+    task::{CreateTask, Task, TaskStatus, TrashedTask},
+    task_attempt::{CreateTaskAttempt, TaskAttempt},
 };
 use deployment::Deployment;
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType,
+        script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+    },
+    env::validate_env_vars,
+    logs::usage::UsageSummary,
+    profile::ExecutorProfileId,
+};
 use ignore::WalkBuilder;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use services::services::{
+    board_transfer::{self, BoardExport, ImportBoardRequest, ImportBoardResponse},
+    bulk_tasks::{self, BulkTaskOperation, BulkTaskOutcome, BulkTaskRequest, BulkTaskResponse},
+    container::ContainerService,
+    dev_server::{self, DevServerStatus},
+    disk_usage::ProjectDiskUsage,
     file_ranker::FileRanker,
     file_search_cache::{CacheError, SearchMode, SearchQuery},
     git::GitBranch,
+    github::GitHubRepoInfo,
+    project_import::{self, DestinationGuard},
     remote_client::CreateRemoteProjectPayload,
-    share::link_shared_tasks_to_project,
+    repo_analysis::{self, ProjectAnalysis},
+    share::{ShareLinkRepairReport, link_shared_tasks_to_project, repair_shared_task_links},
+    worktree_cleanup::{self, WorktreeCleanupOptions, WorktreeCleanupReport},
 };
 use ts_rs::TS;
 use utils::{
     api::projects::{RemoteProject, RemoteProjectMembersResponse},
+    msg_store::MsgStore,
     path::expand_tilde,
     response::ApiResponse,
+    text::git_branch_id,
 };
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_project_middleware};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::load_project_middleware,
+    routes::task_attempts::util::{ensure_worktree_path, resolve_executor_profile},
+};
 
 #[derive(Deserialize, TS)]
 pub struct LinkToExistingRequest {
@@ -43,10 +72,108 @@ pub struct CreateRemoteProjectRequest {
     pub name: String,
 }
 
+#[derive(Deserialize, TS)]
+pub struct ApplyProjectSuggestionsRequest {
+    pub suggestion_ids: Vec<String>,
+}
+
+#[derive(Serialize, TS)]
+pub struct ProjectUsage {
+    /// `None` when no run in the project has reported usage
+    pub total: Option<UsageSummary>,
+    /// Heaviest tasks first
+    pub tasks: Vec<TaskUsage>,
+    /// Disk space taken by the project's attempt worktrees
+    pub worktree_disk: ProjectDiskUsage,
+}
+
+#[derive(Deserialize, TS)]
+pub struct SessionEntriesQuery {
+    /// Entry type, e.g. `tool_use`
+    pub kind: Option<String>,
+    /// Tool action, e.g. `file_edit`
+    pub action: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Deserialize, Serialize, TS)]
+pub struct ProjectEnvVars {
+    /// Values may reference the server's environment as `${VAR}`
+    pub vars: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize, TS)]
+pub struct UpdateProjectEnvVars {
+    pub vars: BTreeMap<String, String>,
+    /// Permit overriding variables such as `PATH` that executors rely on
+    #[serde(default)]
+    pub allow_dangerous: bool,
+}
+
+#[derive(Deserialize, TS)]
+pub struct ImportProjectRequest {
+    /// Chosen by the client so it can follow clone progress on
+    /// `/api/execution-processes/{import_id}/raw-logs/ws` while this runs;
+    /// rejected if a log with this id already exists
+    pub import_id: Uuid,
+    pub repo_url: String,
+    /// Directory to clone into; must be new or empty
+    pub destination_dir: String,
+    pub setup_script: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, TS)]
+pub struct CleanupWorktreesRequest {
+    /// Also remove worktrees with uncommitted changes and unmerged branches
+    /// left by deleted attempts
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Default, Deserialize, TS)]
+pub struct RepairShareLinksRequest {
+    /// Report the planned changes without applying them
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BoardExportQuery {
+    /// Include each execution process's raw output
+    #[serde(default)]
+    pub include_logs: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ProjectListQuery {
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+const REPO_ANALYSIS_TIMEOUT: Duration = Duration::from_secs(5);
+const SESSION_ENTRIES_DEFAULT_LIMIT: i64 = 100;
+const SESSION_ENTRIES_MAX_LIMIT: i64 = 1000;
+
+/// Normalize a project's executor override. Blank clears it; anything else
+/// must parse as `EXECUTOR[:VARIANT]`.
+fn normalize_default_executor(value: Option<String>) -> Result<Option<String>, String> {
+    match value.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(raw) => ExecutorProfileId::from_str(raw)
+            .map(|profile| Some(profile.to_string()))
+            .map_err(|err| format!("Invalid default executor: {err}")),
+    }
+}
+
 pub async fn get_projects(
     State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ProjectListQuery>,
 ) -> Result<ResponseJson<ApiResponse<Vec<Project>>>, ApiError> {
-    let projects = Project::find_all(&deployment.db().pool).await?;
+    let mut projects = Project::find_all(&deployment.db().pool).await?;
+    if !query.include_archived {
+        projects.retain(|project| !project.is_archived());
+    }
     Ok(ResponseJson(ApiResponse::success(projects)))
 }
 
@@ -130,6 +257,98 @@ pub async fn unlink_project(
     Ok(ResponseJson(ApiResponse::success(updated_project)))
 }
 
+pub async fn archive_project(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Project>>, ApiError> {
+    set_project_archived(&deployment, &project, true).await
+}
+
+pub async fn unarchive_project(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Project>>, ApiError> {
+    set_project_archived(&deployment, &project, false).await
+}
+
+async fn set_project_archived(
+    deployment: &DeploymentImpl,
+    project: &Project,
+    archived: bool,
+) -> Result<ResponseJson<ApiResponse<Project>>, ApiError> {
+    let pool = &deployment.db().pool;
+    Project::set_archived(pool, project.id, archived).await?;
+    let updated_project = Project::find_by_id(pool, project.id)
+        .await?
+        .ok_or(ProjectError::ProjectNotFound)?;
+
+    // Start or stop the project's sync watcher now rather than on the next
+    // refresh
+    if project.remote_project_id.is_some()
+        && let Some(handle) = deployment.share_sync_handle().lock().await.as_ref()
+    {
+        handle.request_reconcile();
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            if archived {
+                "project_archived"
+            } else {
+                "project_unarchived"
+            },
+            serde_json::json!({ "project_id": project.id.to_string() }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(updated_project)))
+}
+
+pub async fn get_dev_server_status(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<DevServerStatus>>, ApiError> {
+    let status = deployment.container().dev_server_status(project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+/// Start the project's dev server again in the worktree it last ran in.
+pub async fn restart_dev_server(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<DevServerStatus>>, ApiError> {
+    let status = deployment.container().dev_server_status(project.id).await?;
+    let Some(task_attempt_id) = status.task_attempt_id else {
+        return Ok(ResponseJson(ApiResponse::error(
+            "No dev server has been started for this project",
+        )));
+    };
+    if project.dev_script.is_none() {
+        return Ok(ResponseJson(ApiResponse::error(
+            "No dev server script configured for this project",
+        )));
+    }
+    let task_attempt = TaskAttempt::find_by_id(&deployment.db().pool, task_attempt_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+    deployment
+        .container()
+        .start_dev_server(&task_attempt, &project, false)
+        .await?;
+
+    let status = deployment.container().dev_server_status(project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+pub async fn stop_dev_server(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<DevServerStatus>>, ApiError> {
+    deployment.container().stop_dev_server(project.id).await?;
+    let status = deployment.container().dev_server_status(project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
 pub async fn get_remote_project_by_id(
     State(deployment): State<DeploymentImpl>,
     Path(remote_project_id): Path<Uuid>,
@@ -180,7 +399,8 @@ async fn apply_remote_project_link(
 
     let current_profile = deployment.auth_context().cached_profile().await;
     let current_user_id = current_profile.as_ref().map(|p| p.user_id);
-    link_shared_tasks_to_project(pool, current_user_id, project_id, remote_project.id).await?;
+    link_shared_tasks_to_project(pool, current_user_id, &updated_project, remote_project.id)
+        .await?;
 
     deployment
         .track_if_analytics_allowed(
@@ -204,12 +424,32 @@ pub async fn create_project(
         git_repo_path,
         setup_script,
         dev_script,
+        dev_server_url_pattern,
         cleanup_script,
         copy_files,
         use_existing_repo,
+        verify_script,
+        verify_timeout_secs,
+        default_executor,
+        max_concurrent_executions,
+        close_task_on_pr_merge,
     } = payload;
     tracing::debug!("Creating project '{}'", name);
 
+    let default_executor = match normalize_default_executor(default_executor) {
+        Ok(default_executor) => default_executor,
+        Err(message) => return Ok(ResponseJson(ApiResponse::error(&message))),
+    };
+    let dev_server_url_pattern = match dev_server::normalize_url_pattern(dev_server_url_pattern) {
+        Ok(pattern) => pattern,
+        Err(message) => return Ok(ResponseJson(ApiResponse::error(&message))),
+    };
+    if max_concurrent_executions.is_some_and(|limit| limit < 1) {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Max concurrent executions must be at least 1",
+        )));
+    }
+
     // Validate and setup git repository
     let path = std::path::absolute(expand_tilde(&git_repo_path))?;
     // Check if git repo path is already used by another project
@@ -291,8 +531,14 @@ pub async fn create_project(
             use_existing_repo,
             setup_script,
             dev_script,
+            dev_server_url_pattern,
             cleanup_script,
             copy_files,
+            verify_script,
+            verify_timeout_secs,
+            default_executor,
+            max_concurrent_executions,
+            close_task_on_pr_merge,
         },
         id,
     )
@@ -319,6 +565,219 @@ pub async fn create_project(
     }
 }
 
+/// Clone a GitHub repository and create a project for it. A failed clone
+/// leaves nothing behind. The setup script, if given, is started as the
+/// project's first execution without waiting for it to finish.
+pub async fn import_project(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ImportProjectRequest>,
+) -> Result<ResponseJson<ApiResponse<Project>>, ApiError> {
+    let ImportProjectRequest {
+        import_id,
+        repo_url,
+        destination_dir,
+        setup_script,
+    } = payload;
+    let repo_url = repo_url.trim();
+    let repo_info = match GitHubRepoInfo::from_remote_url(repo_url) {
+        Ok(repo_info) => repo_info,
+        Err(_) => {
+            return Ok(ResponseJson(ApiResponse::error(
+                "Enter a GitHub repository URL, e.g. https://github.com/owner/repo",
+            )));
+        }
+    };
+    let setup_script = setup_script
+        .map(|script| script.trim().to_string())
+        .filter(|script| !script.is_empty());
+
+    let destination = std::path::absolute(expand_tilde(destination_dir.trim()))?;
+    let projects = Project::find_all(&deployment.db().pool).await?;
+    if let Err(e) = project_import::check_destination(
+        &destination,
+        projects
+            .iter()
+            .map(|project| project.git_repo_path.as_path()),
+    ) {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
+
+    // The id names a log in the same map as execution process logs, so one
+    // that is in use must not be taken over
+    if ExecutionProcess::find_by_id(&deployment.db().pool, import_id)
+        .await?
+        .is_some()
+    {
+        return Err(ApiError::Conflict(format!(
+            "Import id {import_id} is already in use"
+        )));
+    }
+    let log = Arc::new(MsgStore::new());
+    {
+        let mut msg_stores = deployment.container().msg_stores().write().await;
+        if msg_stores.contains_key(&import_id) {
+            return Err(ApiError::Conflict(format!(
+                "Import id {import_id} is already in use"
+            )));
+        }
+        msg_stores.insert(import_id, log.clone());
+    }
+
+    let result = clone_and_create_project(
+        &deployment,
+        repo_url,
+        &repo_info,
+        &destination,
+        setup_script,
+        log.clone(),
+    )
+    .await;
+
+    if let Err(e) = &result {
+        log.push_stderr(format!("{e}\n"));
+    }
+    log.push_finished();
+    deployment
+        .container()
+        .msg_stores()
+        .write()
+        .await
+        .remove(&import_id);
+
+    match result {
+        Ok(project) => Ok(ResponseJson(ApiResponse::success(project))),
+        Err(ApiError::ProjectImport(e)) => Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+        Err(e) => Err(e),
+    }
+}
+
+async fn clone_and_create_project(
+    deployment: &DeploymentImpl,
+    repo_url: &str,
+    repo_info: &GitHubRepoInfo,
+    destination: &StdPath,
+    setup_script: Option<String>,
+    log: Arc<MsgStore>,
+) -> Result<Project, ApiError> {
+    let guard = DestinationGuard::new(destination);
+    project_import::clone_repository(repo_url, destination, &log).await?;
+
+    let project = Project::create(
+        &deployment.db().pool,
+        &CreateProject {
+            name: repo_info.repo_name.clone(),
+            git_repo_path: destination.to_string_lossy().to_string(),
+            use_existing_repo: true,
+            setup_script: setup_script.clone(),
+            dev_script: None,
+            dev_server_url_pattern: None,
+            cleanup_script: None,
+            copy_files: None,
+            verify_script: None,
+            verify_timeout_secs: None,
+            default_executor: None,
+            max_concurrent_executions: None,
+            close_task_on_pr_merge: None,
+        },
+        Uuid::new_v4(),
+    )
+    .await
+    .map_err(|e| ProjectError::CreateFailed(e.to_string()))?;
+    guard.disarm();
+
+    if let Some(script) = setup_script {
+        match start_setup_script(deployment, &project, script).await {
+            Ok(_) => log.push_stdout(format!(
+                "Setup script started in the \"{}\" task\n",
+                setup_task_title(&project)
+            )),
+            Err(e) => log.push_stderr(format!(
+                "Failed to start setup script: {e}. The project was created; run it from a task.\n"
+            )),
+        }
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_created",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "use_existing_repo": true,
+                "has_setup_script": project.setup_script.is_some(),
+                "has_dev_script": false,
+                "trigger": "import",
+            }),
+        )
+        .await;
+
+    Ok(project)
+}
+
+fn setup_task_title(project: &Project) -> String {
+    format!("Set up {}", project.name)
+}
+
+/// Run an imported project's setup script as its first execution, in the
+/// worktree of a task created for it on the cloned branch.
+async fn start_setup_script(
+    deployment: &DeploymentImpl,
+    project: &Project,
+    script: String,
+) -> Result<ExecutionProcess, ApiError> {
+    let pool = &deployment.db().pool;
+    let base_branch = deployment
+        .git()
+        .get_current_branch(&project.git_repo_path)?;
+    let task = Task::create(
+        pool,
+        &CreateTask::from_title_description(project.id, setup_task_title(project), None),
+        Uuid::new_v4(),
+    )
+    .await?;
+
+    let executor_profile_id = resolve_executor_profile(deployment, project, None).await;
+    let attempt_id = Uuid::new_v4();
+    let branch = deployment
+        .container()
+        .git_branch_from_task_attempt(&attempt_id, &task.title)
+        .await;
+    let task_attempt = TaskAttempt::create(
+        pool,
+        &CreateTaskAttempt {
+            executor: executor_profile_id.executor,
+            base_branch,
+            branch,
+            retried_from_attempt_id: None,
+        },
+        attempt_id,
+        task.id,
+    )
+    .await?;
+    ensure_worktree_path(deployment, &task_attempt).await?;
+    let task_attempt = TaskAttempt::find_by_id(pool, task_attempt.id)
+        .await?
+        .ok_or(ApiError::Database(sqlx::Error::RowNotFound))?;
+
+    let executor_action = ExecutorAction::new(
+        ExecutorActionType::ScriptRequest(ScriptRequest {
+            script,
+            language: ScriptRequestLanguage::Bash,
+            context: ScriptContext::SetupScript,
+            timeout_secs: None,
+            stall_timeout_secs: None,
+        }),
+        None,
+    );
+    Ok(deployment
+        .container()
+        .start_execution(
+            &task_attempt,
+            &executor_action,
+            &ExecutionProcessRunReason::SetupScript,
+        )
+        .await?)
+}
+
 pub async fn update_project(
     Extension(existing_project): Extension<Project>,
     State(deployment): State<DeploymentImpl>,
@@ -332,9 +791,29 @@ pub async fn update_project(
         git_repo_path,
         setup_script,
         dev_script,
+        dev_server_url_pattern,
         cleanup_script,
         copy_files,
+        verify_script,
+        verify_timeout_secs,
+        default_executor,
+        max_concurrent_executions,
+        close_task_on_pr_merge,
+        shared_task_auto_create,
     } = payload;
+    let default_executor = match normalize_default_executor(default_executor) {
+        Ok(default_executor) => default_executor,
+        Err(message) => return Ok(ResponseJson(ApiResponse::error(&message))),
+    };
+    let dev_server_url_pattern = match dev_server::normalize_url_pattern(dev_server_url_pattern) {
+        Ok(pattern) => pattern,
+        Err(message) => return Ok(ResponseJson(ApiResponse::error(&message))),
+    };
+    if max_concurrent_executions.is_some_and(|limit| limit < 1) {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Max concurrent executions must be at least 1",
+        )));
+    }
     // If git_repo_path is being changed, check if the new path is already used by another project
     let git_repo_path = if let Some(new_git_repo_path) = git_repo_path.map(|s| expand_tilde(&s))
         && new_git_repo_path != existing_project.git_repo_path
@@ -368,8 +847,15 @@ pub async fn update_project(
         git_repo_path.to_string_lossy().to_string(),
         setup_script,
         dev_script,
+        dev_server_url_pattern,
         cleanup_script,
         copy_files,
+        verify_script,
+        verify_timeout_secs,
+        default_executor,
+        max_concurrent_executions,
+        close_task_on_pr_merge.unwrap_or(existing_project.close_task_on_pr_merge),
+        shared_task_auto_create.unwrap_or(existing_project.shared_task_auto_create),
     )
     .await
     {
@@ -381,6 +867,63 @@ pub async fn update_project(
     }
 }
 
+pub async fn analyze_project(
+    Extension(project): Extension<Project>,
+) -> Result<ResponseJson<ApiResponse<ProjectAnalysis>>, ApiError> {
+    let analysis = repo_analysis::analyze_repository_with_timeout(
+        project.git_repo_path,
+        REPO_ANALYSIS_TIMEOUT,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(analysis)))
+}
+
+pub async fn apply_project_suggestions(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ApplyProjectSuggestionsRequest>,
+) -> Result<ResponseJson<ApiResponse<Project>>, ApiError> {
+    // Analyze again rather than trusting values from the client, so only
+    // commands that were actually found in the repository can be applied
+    let analysis = repo_analysis::analyze_repository_with_timeout(
+        project.git_repo_path.clone(),
+        REPO_ANALYSIS_TIMEOUT,
+    )
+    .await?;
+    let applied = analysis.resolve(&payload.suggestion_ids)?;
+
+    let project = Project::update(
+        &deployment.db().pool,
+        project.id,
+        project.name,
+        project.git_repo_path.to_string_lossy().to_string(),
+        applied.setup_script.or(project.setup_script),
+        applied.dev_script.or(project.dev_script),
+        project.dev_server_url_pattern,
+        project.cleanup_script,
+        project.copy_files,
+        project.verify_script,
+        project.verify_timeout_secs,
+        project.default_executor,
+        project.max_concurrent_executions,
+        project.close_task_on_pr_merge,
+        project.shared_task_auto_create,
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_suggestions_applied",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "suggestion_count": payload.suggestion_ids.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(project)))
+}
+
 pub async fn delete_project(
     Extension(project): Extension<Project>,
     State(deployment): State<DeploymentImpl>,
@@ -654,6 +1197,268 @@ async fn search_files_in_repo(
     Ok(results)
 }
 
+pub async fn get_project_usage(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ProjectUsage>>, ApiError> {
+    let tasks = ExecutionProcessUsage::totals_by_task(&deployment.db().pool, project.id).await?;
+    let total = (!tasks.is_empty()).then(|| tasks.iter().map(|task| task.usage).sum());
+    let worktree_disk = ProjectDiskUsage::load(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(ProjectUsage {
+        total,
+        tasks,
+        worktree_disk,
+    })))
+}
+
+/// Persisted conversation entries across the project's coding agent runs.
+pub async fn get_project_session_entries(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<SessionEntriesQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutorSessionEntry>>>, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(SESSION_ENTRIES_DEFAULT_LIMIT)
+        .clamp(1, SESSION_ENTRIES_MAX_LIMIT);
+    let entries = ExecutorSessionEntry::find_by_project_id(
+        &deployment.db().pool,
+        project.id,
+        query.kind.as_deref(),
+        query.action.as_deref(),
+        limit,
+        query.offset.unwrap_or(0).max(0),
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(entries)))
+}
+
+pub async fn get_project_env_vars(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ProjectEnvVars>>, ApiError> {
+    let vars = Project::find_env(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(ProjectEnvVars { vars })))
+}
+
+/// Replace the project's executor environment variables.
+pub async fn update_project_env_vars(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateProjectEnvVars>,
+) -> Result<ResponseJson<ApiResponse<ProjectEnvVars>>, ApiError> {
+    if let Err(e) = validate_env_vars(&payload.vars, payload.allow_dangerous) {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
+    Project::set_env(&deployment.db().pool, project.id, &payload.vars).await?;
+    Ok(ResponseJson(ApiResponse::success(ProjectEnvVars {
+        vars: payload.vars,
+    })))
+}
+
+/// Remove the worktrees and branches this project's attempts no longer
+/// need, and report what was kept and why.
+pub async fn cleanup_project_worktrees(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    payload: Option<Json<CleanupWorktreesRequest>>,
+) -> Result<ResponseJson<ApiResponse<WorktreeCleanupReport>>, ApiError> {
+    let Json(payload) = payload.unwrap_or_default();
+    let (branch_prefix, retention_hours) = {
+        let config = deployment.config().read().await;
+        (
+            config.git_branch_prefix.clone(),
+            config.worktree_retention_hours,
+        )
+    };
+    let report = worktree_cleanup::cleanup_project(
+        deployment.db(),
+        deployment.git(),
+        &project,
+        &branch_prefix,
+        WorktreeCleanupOptions::new(retention_hours, payload.force),
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+/// Unlink local tasks from shared tasks that no longer exist and link the
+/// remote project's shared tasks again.
+pub async fn repair_share_links(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    payload: Option<Json<RepairShareLinksRequest>>,
+) -> Result<ResponseJson<ApiResponse<ShareLinkRepairReport>>, ApiError> {
+    let Json(payload) = payload.unwrap_or_default();
+    let current_profile = deployment.auth_context().cached_profile().await;
+    let current_user_id = current_profile.as_ref().map(|p| p.user_id);
+    let report = repair_shared_task_links(
+        &deployment.db().pool,
+        current_user_id,
+        &project,
+        payload.dry_run,
+    )
+    .await?;
+
+    if !report.dry_run {
+        tracing::info!(
+            project_id = %project.id,
+            unlinked = report.unlinked_count,
+            created = report.created_count,
+            skipped = report.skipped_count,
+            "repaired shared task links"
+        );
+    }
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+/// Deleted tasks that can still be restored.
+pub async fn get_project_trash(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TrashedTask>>>, ApiError> {
+    let tasks = Task::find_deleted_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(tasks)))
+}
+
+/// Change the status of, or delete, many of the project's tasks at once.
+pub async fn bulk_update_tasks(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<BulkTaskRequest>,
+) -> Result<ResponseJson<ApiResponse<BulkTaskResponse>>, ApiError> {
+    let publisher = deployment.share_publisher().ok();
+    let mut changes = bulk_tasks::apply_bulk_task_operation(
+        &deployment.db().pool,
+        &project,
+        &payload,
+        publisher.is_some(),
+    )
+    .await?;
+
+    if let Some(publisher) = &publisher {
+        for task_id in &changes.shared_updates {
+            if let Err(e) = publisher.update_shared_task_by_id(*task_id).await {
+                tracing::warn!(?e, %task_id, "failed to publish bulk status change");
+                if let Some(result) = changes.results.iter_mut().find(|r| r.task_id == *task_id) {
+                    result.reason = Some(format!("Remote copy not updated: {e}"));
+                }
+            }
+        }
+    }
+
+    if payload.operation == BulkTaskOperation::SetStatus && payload.status == Some(TaskStatus::Done)
+    {
+        for result in &changes.results {
+            if result.outcome == BulkTaskOutcome::Applied {
+                deployment
+                    .container()
+                    .start_unblocked_attempts(result.task_id)
+                    .await;
+            }
+        }
+    }
+
+    let applied = changes
+        .results
+        .iter()
+        .filter(|result| result.outcome == BulkTaskOutcome::Applied)
+        .count();
+    deployment
+        .track_if_analytics_allowed(
+            "tasks_bulk_updated",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "operation": payload.operation,
+                "applied_count": applied,
+                "skipped_count": changes.results.len() - applied,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(BulkTaskResponse {
+        results: changes.results,
+    })))
+}
+
+/// The project's tasks, attempts and settings as a JSON document that
+/// `POST /projects/import-board` can recreate on another machine.
+pub async fn export_project_board(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<BoardExportQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let board =
+        board_transfer::export_board(&deployment.db().pool, &project, query.include_logs).await?;
+    let filename = format!("{}-board.json", git_branch_id(&project.name));
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}\""),
+        )],
+        Json::<BoardExport>(board),
+    ))
+}
+
+/// Recreate an exported board as a new project for a repository that
+/// already exists on this machine.
+pub async fn import_project_board(
+    State(deployment): State<DeploymentImpl>,
+    Json(mut payload): Json<ImportBoardRequest>,
+) -> Result<ResponseJson<ApiResponse<ImportBoardResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let path = std::path::absolute(expand_tilde(&payload.git_repo_path))?;
+    if !path.join(".git").exists() {
+        return Err(ApiError::BadRequest(format!(
+            "{} is not a git repository",
+            path.display()
+        )));
+    }
+    if Project::find_by_git_repo_path(pool, path.to_string_lossy().as_ref())
+        .await?
+        .is_some()
+    {
+        return Err(ApiError::Conflict(
+            "A project with this git repository path already exists".to_string(),
+        ));
+    }
+    payload.git_repo_path = path.to_string_lossy().to_string();
+
+    let imported = board_transfer::import_board(pool, &payload).await?;
+
+    if let Some(remote_project_id) = imported.project.remote_project_id {
+        let current_profile = deployment.auth_context().cached_profile().await;
+        let current_user_id = current_profile.as_ref().map(|p| p.user_id);
+        if let Err(e) = link_shared_tasks_to_project(
+            pool,
+            current_user_id,
+            &imported.project,
+            remote_project_id,
+        )
+        .await
+        {
+            tracing::warn!(
+                "Failed to link shared tasks to imported project {}: {}",
+                imported.project.id,
+                e
+            );
+        }
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_board_imported",
+            serde_json::json!({
+                "project_id": imported.project.id.to_string(),
+                "tasks": imported.tasks,
+                "attempts": imported.attempts,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(imported)))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let project_id_router = Router::new()
         .route(
@@ -662,13 +1467,31 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         )
         .route("/remote/members", get(get_project_remote_members))
         .route("/branches", get(get_project_branches))
+        .route("/analyze", post(analyze_project))
+        .route("/apply-suggestions", post(apply_project_suggestions))
         .route("/search", get(search_project_files))
+        .route("/usage", get(get_project_usage))
+        .route("/session-entries", get(get_project_session_entries))
+        .route(
+            "/env",
+            get(get_project_env_vars).put(update_project_env_vars),
+        )
+        .route("/cleanup-worktrees", post(cleanup_project_worktrees))
+        .route("/tasks/bulk", post(bulk_update_tasks))
+        .route("/tasks/trash", get(get_project_trash))
+        .route("/export", get(export_project_board))
         .route("/open-editor", post(open_project_in_editor))
+        .route("/dev-server", get(get_dev_server_status))
+        .route("/dev-server/restart", post(restart_dev_server))
+        .route("/dev-server/stop", post(stop_dev_server))
+        .route("/archive", post(archive_project))
+        .route("/unarchive", post(unarchive_project))
         .route(
             "/link",
             post(link_project_to_existing_remote).delete(unlink_project),
         )
         .route("/link/create", post(create_and_link_remote_project))
+        .route("/share/repair", post(repair_share_links))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_project_middleware,
@@ -676,6 +1499,8 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
 
     let projects_router = Router::new()
         .route("/", get(get_projects).post(create_project))
+        .route("/import", post(import_project))
+        .route("/import-board", post(import_project_board))
         .nest("/{id}", project_id_router);
 
     Router::new().nest("/projects", projects_router).route(
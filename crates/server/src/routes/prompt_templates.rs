@@ -0,0 +1,123 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Query, State},
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::get,
+};
+use db::models::{
+    project::Project,
+    prompt_template::{CreatePromptTemplate, PromptTemplate, UpdatePromptTemplate},
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_prompt_template_middleware};
+
+#[derive(Deserialize, TS)]
+pub struct PromptTemplateQuery {
+    /// Limit to global templates and this project's own
+    #[serde(default)]
+    pub project_id: Option<Uuid>,
+}
+
+fn validate_fields(name: Option<&str>, body: Option<&str>) -> Result<(), ApiError> {
+    if name.is_some_and(|name| name.trim().is_empty()) {
+        return Err(ApiError::BadRequest(
+            "Template name cannot be empty".to_string(),
+        ));
+    }
+    if body.is_some_and(|body| body.trim().is_empty()) {
+        return Err(ApiError::BadRequest(
+            "Template body cannot be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn get_prompt_templates(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<PromptTemplateQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<PromptTemplate>>>, ApiError> {
+    let templates = PromptTemplate::find_all(&deployment.db().pool, query.project_id).await?;
+    Ok(ResponseJson(ApiResponse::success(templates)))
+}
+
+pub async fn get_prompt_template(
+    Extension(template): Extension<PromptTemplate>,
+) -> Result<ResponseJson<ApiResponse<PromptTemplate>>, ApiError> {
+    Ok(ResponseJson(ApiResponse::success(template)))
+}
+
+pub async fn create_prompt_template(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreatePromptTemplate>,
+) -> Result<ResponseJson<ApiResponse<PromptTemplate>>, ApiError> {
+    validate_fields(Some(&payload.name), Some(&payload.body))?;
+    if let Some(project_id) = payload.project_id
+        && Project::find_by_id(&deployment.db().pool, project_id)
+            .await?
+            .is_none()
+    {
+        return Err(ApiError::BadRequest("Project not found".to_string()));
+    }
+
+    let template = PromptTemplate::create(&deployment.db().pool, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "prompt_template_created",
+            serde_json::json!({
+                "template_id": template.id.to_string(),
+                "scope": if template.project_id.is_some() { "project" } else { "global" },
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(template)))
+}
+
+pub async fn update_prompt_template(
+    Extension(template): Extension<PromptTemplate>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdatePromptTemplate>,
+) -> Result<ResponseJson<ApiResponse<PromptTemplate>>, ApiError> {
+    validate_fields(payload.name.as_deref(), payload.body.as_deref())?;
+    let updated = PromptTemplate::update(&deployment.db().pool, template.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(updated)))
+}
+
+pub async fn delete_prompt_template(
+    Extension(template): Extension<PromptTemplate>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = PromptTemplate::delete(&deployment.db().pool, template.id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(sqlx::Error::RowNotFound))
+    } else {
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let template_router = Router::new()
+        .route(
+            "/",
+            get(get_prompt_template)
+                .put(update_prompt_template)
+                .delete(delete_prompt_template),
+        )
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_prompt_template_middleware,
+        ));
+
+    let inner = Router::new()
+        .route("/", get(get_prompt_templates).post(create_prompt_template))
+        .nest("/{template_id}", template_router);
+
+    Router::new().nest("/prompt-templates", inner)
+}
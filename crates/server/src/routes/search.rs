@@ -0,0 +1,70 @@
+use axum::{
+    Router,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+    routing::get,
+};
+use db::models::{
+    task::TaskStatus,
+    task_search::{TaskSearch, TaskSearchHit},
+};
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+
+#[derive(Debug, Deserialize, TS)]
+pub struct TaskSearchQuery {
+    pub q: String,
+    pub project_id: Option<Uuid>,
+    pub status: Option<TaskStatus>,
+    /// Defaults to 20, at most 100
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct TaskSearchResponse {
+    pub hits: Vec<TaskSearchHit>,
+    /// Offset of the next page, if there is one
+    pub next_offset: Option<u32>,
+}
+
+pub async fn search_tasks(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TaskSearchQuery>,
+) -> Result<ResponseJson<ApiResponse<TaskSearchResponse>>, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let offset = query.offset.unwrap_or(0);
+
+    // One extra row tells whether another page follows
+    let mut hits = TaskSearch::search(
+        &deployment.db().pool,
+        &query.q,
+        query.project_id,
+        query.status,
+        limit + 1,
+        offset,
+    )
+    .await?;
+    let next_offset = (hits.len() > limit as usize).then(|| offset + limit);
+    hits.truncate(limit as usize);
+
+    Ok(ResponseJson(ApiResponse::success(TaskSearchResponse {
+        hits,
+        next_offset,
+    })))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/search/tasks", get(search_tasks))
+}
@@ -1,13 +1,22 @@
+use std::collections::HashSet;
+
 use axum::{
     Json, Router,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     response::Json as ResponseJson,
-    routing::{delete, post},
+    routing::{delete, get, post},
+};
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    project::Project,
+    shared_task::{SharedActivityCursor, SharedTask, SharedTaskDivergenceReport},
+    shared_task_outbox::{SharedTaskOutboxOp, SharedTaskOutboxStatus},
+    task::{Task, TaskStatus},
+    task_attempt::TaskAttempt,
 };
-use db::models::shared_task::SharedTask;
 use deployment::Deployment;
 use serde::{Deserialize, Serialize};
-use services::services::share::ShareError;
+use services::services::share::{ProjectSyncStatus, ShareError};
 use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
@@ -27,8 +36,69 @@ pub struct AssignSharedTaskResponse {
     pub shared_task: SharedTask,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SharedSyncStatusQuery {
+    pub project_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SharedSyncStatus {
+    pub remote_project_id: Uuid,
+    pub last_seq: Option<i64>,
+    /// Outcome of the most recent consistency check, if one has run
+    pub divergence: Option<SharedTaskDivergenceReport>,
+}
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct SharedTasksQuery {
+    /// `me` for the signed-in user, or a remote user id. Defaults to `me`.
+    pub assignee: Option<String>,
+    pub status: Option<TaskStatus>,
+    /// Defaults to 50, at most 200
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// The local task a shared task is linked to, with its attempt state.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct LocalTaskLink {
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub status: TaskStatus,
+    pub attempt_count: usize,
+    /// Most recently created attempt
+    pub latest_attempt_id: Option<Uuid>,
+    pub has_in_progress_attempt: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AssignedSharedTask {
+    pub shared_task: SharedTask,
+    /// `None` if no task on this machine is linked to it yet
+    pub local_task: Option<LocalTaskLink>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AssignedSharedTasksResponse {
+    /// Most recently updated first
+    pub tasks: Vec<AssignedSharedTask>,
+    /// Offset of the next page, if there is one
+    pub next_offset: Option<u32>,
+}
+
 pub fn router() -> Router<DeploymentImpl> {
     Router::new()
+        .route("/shared-tasks", get(list_shared_tasks))
+        .route("/shared-tasks/sync-status", get(get_sync_status))
+        .route("/shared-tasks/outbox", get(get_outbox_status))
+        .route("/share/status", get(get_share_status))
         .route(
             "/shared-tasks/{shared_task_id}/assign",
             post(assign_shared_task),
@@ -36,6 +106,76 @@ pub fn router() -> Router<DeploymentImpl> {
         .route("/shared-tasks/{shared_task_id}", delete(delete_shared_task))
 }
 
+/// Shared tasks assigned to a user across all projects, each with its local
+/// counterpart if there is one.
+pub async fn list_shared_tasks(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<SharedTasksQuery>,
+) -> Result<ResponseJson<ApiResponse<AssignedSharedTasksResponse>>, ApiError> {
+    let assignee = match query.assignee.as_deref().map(str::trim) {
+        None | Some("me") => {
+            deployment
+                .auth_context()
+                .cached_profile()
+                .await
+                .ok_or(ApiError::Unauthorized)?
+                .user_id
+        }
+        Some(user_id) => Uuid::parse_str(user_id).map_err(|_| {
+            ApiError::BadRequest(format!(
+                "assignee must be `me` or a user id, got `{user_id}`"
+            ))
+        })?,
+    };
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let offset = query.offset.unwrap_or(0);
+    let pool = &deployment.db().pool;
+
+    // One extra row tells whether another page follows
+    let mut shared_tasks =
+        SharedTask::list_by_assignee(pool, assignee, query.status, limit + 1, offset).await?;
+    let next_offset = (shared_tasks.len() > limit as usize).then(|| offset + limit);
+    shared_tasks.truncate(limit as usize);
+
+    let running_attempts: HashSet<Uuid> = ExecutionProcess::find_running(pool)
+        .await?
+        .into_iter()
+        .filter(|process| process.run_reason != ExecutionProcessRunReason::DevServer)
+        .map(|process| process.task_attempt_id)
+        .collect();
+
+    let mut tasks = Vec::with_capacity(shared_tasks.len());
+    for shared_task in shared_tasks {
+        let local_task = match Task::find_by_shared_task_id(pool, shared_task.id).await? {
+            Some(task) => {
+                let attempts = TaskAttempt::fetch_all(pool, Some(task.id)).await?;
+                Some(LocalTaskLink {
+                    task_id: task.id,
+                    project_id: task.project_id,
+                    status: task.status,
+                    attempt_count: attempts.len(),
+                    latest_attempt_id: attempts.first().map(|attempt| attempt.id),
+                    has_in_progress_attempt: attempts
+                        .iter()
+                        .any(|attempt| running_attempts.contains(&attempt.id)),
+                })
+            }
+            None => None,
+        };
+        tasks.push(AssignedSharedTask {
+            shared_task,
+            local_task,
+        });
+    }
+
+    Ok(ResponseJson(ApiResponse::success(
+        AssignedSharedTasksResponse { tasks, next_offset },
+    )))
+}
+
 pub async fn assign_shared_task(
     Path(shared_task_id): Path<Uuid>,
     State(deployment): State<DeploymentImpl>,
@@ -91,3 +231,51 @@ pub async fn delete_shared_task(
 
     Ok(ResponseJson(ApiResponse::success(())))
 }
+
+pub async fn get_sync_status(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<SharedSyncStatusQuery>,
+) -> Result<ResponseJson<ApiResponse<SharedSyncStatus>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let project = Project::find_by_id(pool, query.project_id)
+        .await?
+        .ok_or(ShareError::ProjectNotFound(query.project_id))?;
+    let remote_project_id = project
+        .remote_project_id
+        .ok_or(ShareError::ProjectNotLinked(project.id))?;
+
+    let last_seq = SharedActivityCursor::get(pool, remote_project_id)
+        .await?
+        .map(|cursor| cursor.last_seq);
+    let divergence = SharedTaskDivergenceReport::find(pool, remote_project_id).await?;
+
+    Ok(ResponseJson(ApiResponse::success(SharedSyncStatus {
+        remote_project_id,
+        last_seq,
+        divergence,
+    })))
+}
+
+/// Changes made while the remote service was unreachable that are still
+/// waiting to be synced.
+pub async fn get_outbox_status(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<SharedTaskOutboxStatus>>, ApiError> {
+    let status = SharedTaskOutboxOp::status(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+/// What the sync watcher for each linked remote project is doing. Empty when
+/// sharing is not configured.
+pub async fn get_share_status(
+    State(deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<Vec<ProjectSyncStatus>>> {
+    let statuses = deployment
+        .share_sync_handle()
+        .lock()
+        .await
+        .as_ref()
+        .map(|handle| handle.status().snapshot())
+        .unwrap_or_default();
+    ResponseJson(ApiResponse::success(statuses))
+}
@@ -2,56 +2,86 @@ pub mod codex_setup;
 pub mod cursor_setup;
 pub mod drafts;
 pub mod gh_cli_setup;
+pub mod queued_followups;
 pub mod util;
 
+use std::collections::HashMap;
+
 use axum::{
-    Extension, Json, Router,
+    BoxError, Extension, Json, Router,
+    body::Body,
     extract::{
         Query, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
-    http::StatusCode,
+    http::{HeaderMap, StatusCode, header},
     middleware::from_fn_with_state,
-    response::{IntoResponse, Json as ResponseJson},
-    routing::{get, post},
+    response::{
+        IntoResponse, Json as ResponseJson, Sse,
+        sse::{Event, KeepAlive},
+    },
+    routing::{delete, get, post},
 };
 use db::models::{
+    attempt_event::{AttemptEvent, AttemptEventType},
     draft::{Draft, DraftType},
-    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    execution_process::{
+        ExecutionProcess, ExecutionProcessError, ExecutionProcessRunReason, ExecutionProcessStatus,
+        ProcessResourceUsage,
+    },
+    execution_process_usage::ExecutionProcessUsage,
     merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
     project::{Project, ProjectError},
+    prompt_template::PromptTemplate,
     task::{Task, TaskRelationships, TaskStatus},
     task_attempt::{CreateTaskAttempt, TaskAttempt, TaskAttemptError},
+    task_attempt_plan::TaskAttemptPlan,
+    task_dependency::TaskDependency,
 };
 use deployment::Deployment;
 use executors::{
     actions::{
-        ExecutorAction, ExecutorActionType,
-        coding_agent_follow_up::CodingAgentFollowUpRequest,
-        script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+        ExecutorAction, ExecutorActionType, coding_agent_follow_up::CodingAgentFollowUpRequest,
     },
     executors::{CodingAgent, ExecutorError},
+    logs::{NormalizedConversation, usage::UsageSummary},
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
+use futures_util::TryStreamExt;
 use git2::BranchType;
 use serde::{Deserialize, Serialize};
 use services::services::{
+    attempt_export::{self, ArchiveFile},
     container::ContainerService,
-    git::{ConflictOp, GitCliError, GitServiceError, WorktreeResetOptions},
-    github::{CreatePrRequest, GitHubService, GitHubServiceError},
+    conversation_compare::{self, ConversationComparison},
+    dev_server,
+    git::{
+        ConflictOp, DiffFileSummary, DiffTarget, GitCliError, GitServiceError, WorktreeResetOptions,
+    },
+    git_forge::{self, GitForge, GitForgeError},
+    github::{CreatePrRequest, GitHubServiceError},
+    gitlab::GitLabServiceError,
+    pr_body::PrBodyBuilder,
+    privacy::{PrivacyMode, PrivacyParams, Redactor},
+    verify,
 };
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
-use utils::response::ApiResponse;
+use utils::{response::ApiResponse, text::short_uuid};
 use uuid::Uuid;
 
 use crate::{
     DeploymentImpl,
     error::ApiError,
-    middleware::load_task_attempt_middleware,
+    middleware::{
+        effective_privacy_mode, load_task_attempt_middleware, redact_response_middleware,
+    },
     routes::task_attempts::{
         gh_cli_setup::GhCliSetupError,
-        util::{ensure_worktree_path, handle_images_for_prompt},
+        util::{
+            ensure_executor_available, ensure_worktree_path, handle_images_for_prompt,
+            resolve_executor_profile,
+        },
     },
 };
 
@@ -74,6 +104,13 @@ pub struct CreateGitHubPrRequest {
     pub title: String,
     pub body: Option<String>,
     pub target_branch: Option<String>,
+    /// Replace `body` with a draft written from the task and the attempt's
+    /// conversation
+    #[serde(default)]
+    pub generate_body: bool,
+    /// Open the PR as a draft
+    #[serde(default)]
+    pub draft: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -87,6 +124,26 @@ pub struct DiffStreamQuery {
     pub stats_only: bool,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct DevServerLogTailQuery {
+    /// Number of past lines to send on connect
+    pub lines: Option<usize>,
+}
+
+// Upper bound on the replayed lines so a client cannot ask for the whole history
+const MAX_DEV_SERVER_TAIL_LINES: usize = 5000;
+
+#[derive(Debug, Deserialize)]
+pub struct ConversationCompareQuery {
+    pub left: Uuid,
+    pub right: Uuid,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+const DEFAULT_COMPARE_PAGE_SIZE: usize = 200;
+const MAX_COMPARE_PAGE_SIZE: usize = 1000;
+
 pub async fn get_task_attempts(
     State(deployment): State<DeploymentImpl>,
     Query(query): Query<TaskAttemptQuery>,
@@ -106,16 +163,14 @@ pub async fn get_task_attempt(
 #[derive(Debug, Serialize, Deserialize, ts_rs::TS)]
 pub struct CreateTaskAttemptBody {
     pub task_id: Uuid,
-    /// Executor profile specification
-    pub executor_profile_id: ExecutorProfileId,
+    /// Executor profile specification. Falls back to the project's default
+    /// executor, then the global one.
+    pub executor_profile_id: Option<ExecutorProfileId>,
     pub base_branch: String,
-}
-
-impl CreateTaskAttemptBody {
-    /// Get the executor profile ID
-    pub fn get_executor_profile_id(&self) -> ExecutorProfileId {
-        self.executor_profile_id.clone()
-    }
+    /// Start even though tasks this one depends on are not done yet
+    #[serde(default, rename = "override")]
+    #[ts(rename = "override")]
+    pub override_dependencies: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize, TS)]
@@ -131,10 +186,25 @@ pub async fn create_task_attempt(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<CreateTaskAttemptBody>,
 ) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, ApiError> {
-    let executor_profile_id = payload.get_executor_profile_id();
     let task = Task::find_by_id(&deployment.db().pool, payload.task_id)
         .await?
         .ok_or(SqlxError::RowNotFound)?;
+    let project = task
+        .parent_project(&deployment.db().pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    if project.is_archived() {
+        return Err(ProjectError::Archived.into());
+    }
+    if !payload.override_dependencies {
+        let blocking = TaskDependency::find_blocking(&deployment.db().pool, task.id).await?;
+        if !blocking.is_empty() {
+            return Err(ApiError::TaskBlocked(blocking));
+        }
+    }
+    let executor_profile_id =
+        resolve_executor_profile(&deployment, &project, payload.executor_profile_id).await;
+    ensure_executor_available(&executor_profile_id).await?;
 
     let attempt_id = Uuid::new_v4();
     let git_branch_name = deployment
@@ -148,6 +218,7 @@ pub async fn create_task_attempt(
             executor: executor_profile_id.executor,
             base_branch: payload.base_branch.clone(),
             branch: git_branch_name.clone(),
+            retried_from_attempt_id: None,
         },
         attempt_id,
         payload.task_id,
@@ -156,7 +227,11 @@ pub async fn create_task_attempt(
 
     if let Err(err) = deployment
         .container()
-        .start_attempt(&task_attempt, executor_profile_id.clone())
+        .start_or_queue_attempt(
+            &task_attempt,
+            executor_profile_id.clone(),
+            payload.override_dependencies,
+        )
         .await
     {
         tracing::error!("Failed to start task attempt: {}", err);
@@ -179,6 +254,105 @@ pub async fn create_task_attempt(
     Ok(ResponseJson(ApiResponse::success(task_attempt)))
 }
 
+#[derive(Debug, Default, Deserialize, TS)]
+pub struct RetryTaskAttemptRequest {
+    /// Defaults to the profile the original attempt started with
+    #[serde(default)]
+    #[ts(optional)]
+    pub executor_profile_id: Option<ExecutorProfileId>,
+    /// Retry even though another attempt of the task is running
+    #[serde(default)]
+    pub allow_parallel: bool,
+}
+
+/// Re-run an attempt from scratch: a new attempt of the same task, on a
+/// fresh worktree from the current target branch, started with the original
+/// prompt. The original attempt is left as it is.
+pub async fn retry_task_attempt(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<RetryTaskAttemptRequest>,
+) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let project = task
+        .parent_project(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    if project.is_archived() {
+        return Err(ProjectError::Archived.into());
+    }
+    if !payload.allow_parallel
+        && deployment
+            .container()
+            .has_running_processes(task.id)
+            .await?
+    {
+        return Err(ApiError::Conflict(
+            "Another attempt of this task is running; pass allow_parallel to retry anyway"
+                .to_string(),
+        ));
+    }
+
+    let original_profile = deployment
+        .container()
+        .initial_request(task_attempt.id)
+        .await?
+        .map(|request| request.executor_profile_id);
+    let executor_profile_id = resolve_executor_profile(
+        &deployment,
+        &project,
+        payload.executor_profile_id.or(original_profile),
+    )
+    .await;
+    ensure_executor_available(&executor_profile_id).await?;
+
+    let attempt_id = Uuid::new_v4();
+    let git_branch_name = deployment
+        .container()
+        .git_branch_from_task_attempt(&attempt_id, &task.title)
+        .await;
+    let retry = TaskAttempt::create(
+        pool,
+        &CreateTaskAttempt {
+            executor: executor_profile_id.executor,
+            base_branch: task_attempt.target_branch.clone(),
+            branch: git_branch_name,
+            retried_from_attempt_id: Some(task_attempt.id),
+        },
+        attempt_id,
+        task.id,
+    )
+    .await?;
+
+    // The original attempt already got past the task's dependencies
+    if let Err(err) = deployment
+        .container()
+        .start_or_queue_attempt(&retry, executor_profile_id.clone(), true)
+        .await
+    {
+        tracing::error!("Failed to start retried task attempt: {}", err);
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_retried",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "variant": &executor_profile_id.variant,
+                "executor": &executor_profile_id.executor,
+                "attempt_id": retry.id.to_string(),
+                "retried_from_attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(retry)))
+}
+
 #[axum::debug_handler]
 pub async fn run_agent_setup(
     Extension(task_attempt): Extension<TaskAttempt>,
@@ -213,7 +387,17 @@ pub async fn run_agent_setup(
 
 #[derive(Debug, Deserialize, TS)]
 pub struct CreateFollowUpAttempt {
+    /// Ignored when `template_id` is set
+    #[serde(default)]
     pub prompt: String,
+    /// Send this prompt template instead of `prompt`
+    #[serde(default)]
+    #[ts(optional)]
+    pub template_id: Option<Uuid>,
+    /// Values for the template's `{placeholders}`
+    #[serde(default)]
+    #[ts(optional)]
+    pub variables: Option<HashMap<String, String>>,
     pub variant: Option<String>,
     pub image_ids: Option<Vec<Uuid>>,
     pub retry_process_id: Option<Uuid>,
@@ -255,6 +439,20 @@ pub async fn follow_up(
         .await?
         .ok_or(SqlxError::RowNotFound)?;
 
+    // Render before anything is reset or stopped so a bad template changes nothing
+    let prompt = match payload.template_id {
+        Some(template_id) => {
+            let template = PromptTemplate::find_by_id(&deployment.db().pool, template_id)
+                .await?
+                .filter(|template| template.is_available_in(project.id))
+                .ok_or_else(|| ApiError::BadRequest("Prompt template not found".to_string()))?;
+            template
+                .render(&payload.variables.unwrap_or_default())
+                .map_err(|e| ApiError::BadRequest(e.to_string()))?
+        }
+        None => payload.prompt,
+    };
+
     // If retry settings provided, perform replace-logic before proceeding
     if let Some(proc_id) = payload.retry_process_id {
         let pool = &deployment.db().pool;
@@ -320,20 +518,19 @@ pub async fn follow_up(
     )
     .await?;
 
-    let mut prompt = payload.prompt;
+    let mut prompt = prompt;
     if let Some(image_ids) = &payload.image_ids {
         prompt = handle_images_for_prompt(&deployment, &task_attempt, task.id, image_ids, &prompt)
             .await?;
     }
 
-    let cleanup_action = deployment
-        .container()
-        .cleanup_action(project.cleanup_script);
+    let post_agent_action = deployment.container().post_agent_action(&project);
 
     let action_type = if let Some(session_id) = latest_session_id {
         ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
             prompt: prompt.clone(),
             session_id,
+            task_context: Some(task.to_prompt()),
             executor_profile_id: executor_profile_id.clone(),
         })
     } else {
@@ -345,7 +542,7 @@ pub async fn follow_up(
         )
     };
 
-    let action = ExecutorAction::new(action_type, cleanup_action);
+    let action = ExecutorAction::new(action_type, post_agent_action);
 
     let execution_process = deployment
         .container()
@@ -372,13 +569,15 @@ pub async fn follow_up(
 pub async fn stream_task_attempt_diff_ws(
     ws: WebSocketUpgrade,
     Query(params): Query<DiffStreamQuery>,
+    Query(privacy): Query<PrivacyParams>,
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
 ) -> impl IntoResponse {
     let stats_only = params.stats_only;
+    let privacy = effective_privacy_mode(&deployment, &privacy).await;
     ws.on_upgrade(move |socket| async move {
         if let Err(e) =
-            handle_task_attempt_diff_ws(socket, deployment, task_attempt, stats_only).await
+            handle_task_attempt_diff_ws(socket, deployment, task_attempt, stats_only, privacy).await
         {
             tracing::warn!("diff WS closed: {}", e);
         }
@@ -390,8 +589,9 @@ async fn handle_task_attempt_diff_ws(
     deployment: DeploymentImpl,
     task_attempt: TaskAttempt,
     stats_only: bool,
+    privacy: Option<PrivacyMode>,
 ) -> anyhow::Result<()> {
-    use futures_util::{SinkExt, StreamExt, TryStreamExt};
+    use futures_util::{SinkExt, StreamExt};
     use utils::log_msg::LogMsg;
 
     let stream = deployment
@@ -399,7 +599,13 @@ async fn handle_task_attempt_diff_ws(
         .stream_diff(&task_attempt, stats_only)
         .await?;
 
-    let mut stream = stream.map_ok(|msg: LogMsg| msg.to_ws_message_unchecked());
+    let mut redactor = privacy.map(Redactor::new);
+    let mut stream = stream.map_ok(move |mut msg: LogMsg| {
+        if let Some(redactor) = redactor.as_mut() {
+            msg = redactor.redact_log_msg(msg);
+        }
+        msg.to_ws_message_unchecked()
+    });
 
     let (mut sender, mut receiver) = socket.split();
 
@@ -492,6 +698,92 @@ pub async fn compare_commit_to_head(
     })))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct DiffSummaryQuery {
+    #[serde(default)]
+    pub include_patch: bool,
+}
+
+/// Total size of the patches returned by the diff summary; files past it are
+/// marked `patch_omitted`.
+const MAX_DIFF_SUMMARY_PATCH_BYTES: usize = 512 * 1024;
+
+/// What the attempt changed relative to where its branch forked from the
+/// target branch. Reads the live worktree when there is one, otherwise the
+/// attempt branch (or its merge commit) in the project repo.
+pub async fn get_task_attempt_diff(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Query(params): Query<DiffSummaryQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<DiffFileSummary>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let project = Project::find_by_id(pool, task.project_id)
+        .await?
+        .ok_or(ApiError::Project(ProjectError::ProjectNotFound))?;
+    let patch_budget = params.include_patch.then_some(MAX_DIFF_SUMMARY_PATCH_BYTES);
+    let files = attempt_diff_summary(&deployment, &task_attempt, &project, patch_budget).await?;
+    Ok(ResponseJson(ApiResponse::success(files)))
+}
+
+async fn attempt_diff_summary(
+    deployment: &DeploymentImpl,
+    task_attempt: &TaskAttempt,
+    project: &Project,
+    patch_budget: Option<usize>,
+) -> Result<Vec<DiffFileSummary>, ApiError> {
+    let pool = &deployment.db().pool;
+    let repo_path = project.git_repo_path.as_path();
+    let git = deployment.git();
+
+    let worktree_path = task_attempt
+        .container_ref
+        .as_ref()
+        .filter(|_| !task_attempt.worktree_deleted)
+        .map(std::path::PathBuf::from)
+        .filter(|path| path.exists());
+    let files = if let Some(worktree_path) = worktree_path {
+        let base_commit =
+            git.get_base_commit(repo_path, &task_attempt.branch, &task_attempt.target_branch)?;
+        git.get_diff_summary(
+            DiffTarget::Worktree {
+                worktree_path: &worktree_path,
+                base_commit: &base_commit,
+            },
+            patch_budget,
+        )?
+    } else if git.check_branch_exists(repo_path, &task_attempt.branch)? {
+        git.get_diff_summary(
+            DiffTarget::Branch {
+                repo_path,
+                branch_name: &task_attempt.branch,
+                base_branch: &task_attempt.target_branch,
+            },
+            patch_budget,
+        )?
+    } else if let Some(commit_sha) = Merge::find_latest_by_task_attempt_id(pool, task_attempt.id)
+        .await?
+        .and_then(|merge| merge.merge_commit())
+    {
+        git.get_diff_summary(
+            DiffTarget::Commit {
+                repo_path,
+                commit_sha: &commit_sha,
+            },
+            patch_budget,
+        )?
+    } else {
+        return Err(ApiError::GitService(GitServiceError::BranchNotFound(
+            task_attempt.branch.clone(),
+        )));
+    };
+
+    Ok(files)
+}
+
 #[axum::debug_handler]
 pub async fn merge_task_attempt(
     Extension(task_attempt): Extension<TaskAttempt>,
@@ -537,7 +829,18 @@ pub async fn merge_task_attempt(
         &merge_commit_id,
     )
     .await?;
+    AttemptEvent::record(
+        pool,
+        task_attempt.id,
+        AttemptEventType::BranchMerged,
+        serde_json::json!({ "commit": merge_commit_id }),
+    )
+    .await;
     Task::update_status(pool, ctx.task.id, TaskStatus::Done).await?;
+    deployment
+        .container()
+        .start_unblocked_attempts(ctx.task.id)
+        .await;
 
     // Stop any running dev servers for this task attempt
     let dev_servers =
@@ -598,8 +901,7 @@ pub async fn push_task_attempt_branch(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
 ) -> Result<ResponseJson<ApiResponse<(), PushError>>, ApiError> {
-    let github_service = GitHubService::new()?;
-    github_service.check_token().await?;
+    ensure_forge_signed_in(&deployment, &task_attempt).await?;
 
     let ws_path = ensure_worktree_path(&deployment, &task_attempt).await?;
 
@@ -619,8 +921,7 @@ pub async fn force_push_task_attempt_branch(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
 ) -> Result<ResponseJson<ApiResponse<(), PushError>>, ApiError> {
-    let github_service = GitHubService::new()?;
-    github_service.check_token().await?;
+    ensure_forge_signed_in(&deployment, &task_attempt).await?;
 
     let ws_path = ensure_worktree_path(&deployment, &task_attempt).await?;
 
@@ -630,6 +931,39 @@ pub async fn force_push_task_attempt_branch(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+/// The forge hosting the project's default remote
+async fn project_forge(
+    deployment: &DeploymentImpl,
+    project: &Project,
+) -> Result<Box<dyn GitForge>, ApiError> {
+    let remote_url = deployment.git().get_remote_url(&project.git_repo_path)?;
+    let gitlab_hosts = deployment.config().read().await.gitlab_hosts.clone();
+    Ok(git_forge::forge_for_remote_url(&remote_url, &gitlab_hosts)?)
+}
+
+/// Pushes authenticate through the forge CLI's git credentials, so check it
+/// is signed in first. Remotes on other hosts are left to git's own
+/// credentials.
+async fn ensure_forge_signed_in(
+    deployment: &DeploymentImpl,
+    task_attempt: &TaskAttempt,
+) -> Result<(), ApiError> {
+    let pool = &deployment.db().pool;
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let project = Project::find_by_id(pool, task.project_id)
+        .await?
+        .ok_or(ApiError::Project(ProjectError::ProjectNotFound))?;
+
+    match project_forge(deployment, &project).await {
+        Ok(forge) => Ok(forge.check_auth().await?),
+        Err(ApiError::GitForge(GitForgeError::UnsupportedRemote(_))) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[ts(tag = "type", rename_all = "snake_case")]
@@ -643,14 +977,66 @@ pub enum PushError {
 pub enum CreatePrError {
     GithubCliNotInstalled,
     GithubCliNotLoggedIn,
+    GitlabCliNotInstalled,
+    GitlabCliNotLoggedIn,
     GitCliNotLoggedIn,
     GitCliNotInstalled,
     TargetBranchNotFound { branch: String },
 }
 
+/// Where the user opened the app, for linking back to the task from a PR.
+fn frontend_origin(headers: &HeaderMap) -> Option<String> {
+    if let Some(origin) = headers
+        .get(header::ORIGIN)
+        .and_then(|value| value.to_str().ok())
+    {
+        return Some(origin.trim_end_matches('/').to_string());
+    }
+    headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .map(|host| format!("http://{host}"))
+}
+
+async fn generate_pr_body(
+    deployment: &DeploymentImpl,
+    task: &Task,
+    task_attempt: &TaskAttempt,
+    headers: &HeaderMap,
+) -> String {
+    let conversation = match deployment
+        .container()
+        .attempt_conversation(task_attempt)
+        .await
+    {
+        Ok(conversation) => Some(conversation),
+        Err(e) => {
+            tracing::warn!(
+                "Failed to load conversation for PR body of attempt {}: {}",
+                task_attempt.id,
+                e
+            );
+            None
+        }
+    };
+
+    let mut builder = PrBodyBuilder::new(task);
+    if let Some(conversation) = &conversation {
+        builder = builder.conversation(conversation);
+    }
+    if let Some(origin) = frontend_origin(headers) {
+        builder = builder.task_url(format!(
+            "{origin}/projects/{}/tasks/{}/attempts/{}",
+            task.project_id, task.id, task_attempt.id
+        ));
+    }
+    builder.build()
+}
+
 pub async fn create_github_pr(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
+    headers: HeaderMap,
     Json(request): Json<CreateGitHubPrRequest>,
 ) -> Result<ResponseJson<ApiResponse<String, CreatePrError>>, ApiError> {
     let github_config = deployment.config().read().await.github.clone();
@@ -704,7 +1090,9 @@ pub async fn create_github_pr(
         Ok(true) => {}
     }
 
-    // Push the branch to GitHub first
+    let forge = project_forge(&deployment, &project).await?;
+
+    // Push the branch to the remote first
     if let Err(e) = deployment
         .git()
         .push_to_github(&workspace_path, &task_attempt.branch, false)
@@ -744,21 +1132,20 @@ pub async fn create_github_pr(
     } else {
         target_branch
     };
+    let body = if request.generate_body {
+        Some(generate_pr_body(&deployment, &task, &task_attempt, &headers).await)
+    } else {
+        request.body.clone()
+    };
     // Create the PR using GitHub service
     let pr_request = CreatePrRequest {
         title: request.title.clone(),
-        body: request.body.clone(),
+        body,
         head_branch: task_attempt.branch.clone(),
         base_branch: norm_target_branch_name.clone(),
+        draft: request.draft,
     };
-    // Use GitService to get the remote URL, then create GitHubRepoInfo
-    let repo_info = deployment
-        .git()
-        .get_github_repo_info(&project.git_repo_path)?;
-
-    // Use GitHubService to create the PR
-    let github_service = GitHubService::new()?;
-    match github_service.create_pr(&repo_info, &pr_request).await {
+    match forge.create_pr(&pr_request).await {
         Ok(pr_info) => {
             // Update the task attempt with PR information
             if let Err(e) = Merge::create_pr(
@@ -772,6 +1159,13 @@ pub async fn create_github_pr(
             {
                 tracing::error!("Failed to update task attempt PR status: {}", e);
             }
+            AttemptEvent::record(
+                pool,
+                task_attempt.id,
+                AttemptEventType::PrOpened,
+                serde_json::json!({ "number": pr_info.number, "url": pr_info.url }),
+            )
+            .await;
 
             // Auto-open PR in browser
             if let Err(e) = utils::browser::open_browser(&pr_info.url).await {
@@ -784,6 +1178,7 @@ pub async fn create_github_pr(
                         "task_id": task.id.to_string(),
                         "project_id": project.id.to_string(),
                         "attempt_id": task_attempt.id.to_string(),
+                        "forge": forge.name(),
                     }),
                 )
                 .await;
@@ -792,18 +1187,29 @@ pub async fn create_github_pr(
         }
         Err(e) => {
             tracing::error!(
-                "Failed to create GitHub PR for attempt {}: {}",
+                "Failed to create {} PR for attempt {}: {}",
+                forge.name(),
                 task_attempt.id,
                 e
             );
             match &e {
-                GitHubServiceError::GhCliNotInstalled(_) => Ok(ResponseJson(
-                    ApiResponse::error_with_data(CreatePrError::GithubCliNotInstalled),
-                )),
-                GitHubServiceError::AuthFailed(_) => Ok(ResponseJson(
+                GitForgeError::GitHub(GitHubServiceError::GhCliNotInstalled(_)) => {
+                    Ok(ResponseJson(ApiResponse::error_with_data(
+                        CreatePrError::GithubCliNotInstalled,
+                    )))
+                }
+                GitForgeError::GitHub(GitHubServiceError::AuthFailed(_)) => Ok(ResponseJson(
                     ApiResponse::error_with_data(CreatePrError::GithubCliNotLoggedIn),
                 )),
-                _ => Err(ApiError::GitHubService(e)),
+                GitForgeError::GitLab(GitLabServiceError::GlabCliNotInstalled(_)) => {
+                    Ok(ResponseJson(ApiResponse::error_with_data(
+                        CreatePrError::GitlabCliNotInstalled,
+                    )))
+                }
+                GitForgeError::GitLab(GitLabServiceError::AuthFailed(_)) => Ok(ResponseJson(
+                    ApiResponse::error_with_data(CreatePrError::GitlabCliNotLoggedIn),
+                )),
+                _ => Err(ApiError::GitForge(e)),
             }
         }
     }
@@ -1260,6 +1666,13 @@ pub async fn rebase_task_attempt(
             other => Err(ApiError::GitService(other)),
         };
     }
+    AttemptEvent::record(
+        pool,
+        task_attempt.id,
+        AttemptEventType::Rebased,
+        serde_json::json!({ "base_branch": new_base_branch }),
+    )
+    .await;
 
     deployment
         .track_if_analytics_allowed(
@@ -1290,12 +1703,64 @@ pub async fn abort_conflicts_task_attempt(
 }
 
 #[axum::debug_handler]
+/// Re-run the project's verify script against the attempt's current state.
+/// Nothing runs after it, so the task goes back to review once it finishes.
+pub async fn rerun_verify(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let task = task_attempt
+        .parent_task(&deployment.db().pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let project = task
+        .parent_project(&deployment.db().pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+
+    let Some(request) = verify::verify_request(&project) else {
+        return Ok(ResponseJson(ApiResponse::error(
+            "No verify script configured for this project",
+        )));
+    };
+    if deployment
+        .container()
+        .has_running_processes(task.id)
+        .await?
+    {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Wait for the running execution to finish before verifying",
+        )));
+    }
+
+    let executor_action = ExecutorAction::new(ExecutorActionType::ScriptRequest(request), None);
+    let execution_process = deployment
+        .container()
+        .start_execution(
+            &task_attempt,
+            &executor_action,
+            &ExecutionProcessRunReason::VerifyScript,
+        )
+        .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "verify_rerun",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "project_id": project.id.to_string(),
+                "attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
+
 pub async fn start_dev_server(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
-    let pool = &deployment.db().pool;
-
     // Get parent task
     let task = task_attempt
         .parent_task(&deployment.db().pool)
@@ -1308,62 +1773,16 @@ pub async fn start_dev_server(
         .await?
         .ok_or(SqlxError::RowNotFound)?;
 
-    // Stop any existing dev servers for this project
-    let existing_dev_servers =
-        match ExecutionProcess::find_running_dev_servers_by_project(pool, project.id).await {
-            Ok(servers) => servers,
-            Err(e) => {
-                tracing::error!(
-                    "Failed to find running dev servers for project {}: {}",
-                    project.id,
-                    e
-                );
-                return Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
-                    e.to_string(),
-                )));
-            }
-        };
-
-    for dev_server in existing_dev_servers {
-        tracing::info!(
-            "Stopping existing dev server {} for project {}",
-            dev_server.id,
-            project.id
-        );
-
-        if let Err(e) = deployment
-            .container()
-            .stop_execution(&dev_server, ExecutionProcessStatus::Killed)
-            .await
-        {
-            tracing::error!("Failed to stop dev server {}: {}", dev_server.id, e);
-        }
-    }
-
-    if let Some(dev_server) = project.dev_script {
-        // TODO: Derive script language from system config
-        let executor_action = ExecutorAction::new(
-            ExecutorActionType::ScriptRequest(ScriptRequest {
-                script: dev_server,
-                language: ScriptRequestLanguage::Bash,
-                context: ScriptContext::DevServer,
-            }),
-            None,
-        );
-
-        deployment
-            .container()
-            .start_execution(
-                &task_attempt,
-                &executor_action,
-                &ExecutionProcessRunReason::DevServer,
-            )
-            .await?
-    } else {
+    if project.dev_script.is_none() {
         return Ok(ResponseJson(ApiResponse::error(
             "No dev server script configured for this project",
         )));
-    };
+    }
+    // Stops the project's current dev server, in this or any other worktree
+    deployment
+        .container()
+        .start_dev_server(&task_attempt, &project, false)
+        .await?;
 
     deployment
         .track_if_analytics_allowed(
@@ -1379,6 +1798,219 @@ pub async fn start_dev_server(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+pub async fn compare_conversations(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ConversationCompareQuery>,
+) -> Result<ResponseJson<ApiResponse<ConversationComparison>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let mut conversations = Vec::with_capacity(2);
+    for process_id in [query.left, query.right] {
+        let process = ExecutionProcess::find_by_id(pool, process_id)
+            .await?
+            .filter(|process| process.task_attempt_id == task_attempt.id)
+            .ok_or(ApiError::ExecutionProcess(
+                ExecutionProcessError::ExecutionProcessNotFound,
+            ))?;
+        if process.run_reason != ExecutionProcessRunReason::CodingAgent {
+            return Err(ApiError::BadRequest(format!(
+                "Execution process {process_id} is not a coding agent run"
+            )));
+        }
+
+        let entries = deployment
+            .container()
+            .normalized_entries(&process.id)
+            .await
+            .unwrap_or_default();
+        conversations.push(entries);
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_COMPARE_PAGE_SIZE)
+        .clamp(1, MAX_COMPARE_PAGE_SIZE);
+    let comparison = conversation_compare::compare(
+        &conversations[0],
+        &conversations[1],
+        query.offset.unwrap_or(0),
+        limit,
+    );
+
+    Ok(ResponseJson(ApiResponse::success(comparison)))
+}
+
+pub async fn get_attempt_conversation(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<NormalizedConversation>>, ApiError> {
+    let conversation = deployment
+        .container()
+        .attempt_conversation(&task_attempt)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(conversation)))
+}
+
+/// The attempt's conversation, diff and process metadata as a `.tar.gz` for
+/// attaching to a review or bug report, with secrets redacted.
+pub async fn export_task_attempt(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<impl IntoResponse, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let project = Project::find_by_id(pool, task.project_id)
+        .await?
+        .ok_or(ApiError::Project(ProjectError::ProjectNotFound))?;
+    let processes = ExecutionProcess::find_by_task_attempt_id(pool, task_attempt.id, false).await?;
+
+    let dir = format!("attempt-{}", short_uuid(&task_attempt.id));
+    let disposition = format!("attachment; filename=\"{dir}.tar.gz\"");
+    let (files, archive) = tokio::sync::mpsc::channel(1);
+    tokio::spawn(async move {
+        let metadata = attempt_export::metadata(&task, &task_attempt, &processes);
+        let file = ArchiveFile::new(
+            format!("{dir}/metadata.json"),
+            &serde_json::to_string_pretty(&metadata).unwrap_or_default(),
+        );
+        if files.send(file).await.is_err() {
+            return;
+        }
+
+        match deployment
+            .container()
+            .attempt_conversation(&task_attempt)
+            .await
+        {
+            Ok(mut conversation) => {
+                attempt_export::cap_conversation(&mut conversation);
+                let json = serde_json::to_string_pretty(&conversation).unwrap_or_default();
+                let markdown = attempt_export::render_markdown(&task.title, &conversation);
+                for file in [
+                    ArchiveFile::new(format!("{dir}/conversation.json"), &json),
+                    ArchiveFile::new(format!("{dir}/conversation.md"), &markdown),
+                ] {
+                    if files.send(file).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(e) => tracing::warn!(
+                "Failed to load conversation to export attempt {}: {}",
+                task_attempt.id,
+                e
+            ),
+        }
+
+        let budget = Some(attempt_export::MAX_EXPORTED_DIFF_BYTES);
+        match attempt_diff_summary(&deployment, &task_attempt, &project, budget).await {
+            Ok(diff) => {
+                let patch = attempt_export::unified_diff(&diff);
+                let _ = files
+                    .send(ArchiveFile::new(format!("{dir}/diff.patch"), &patch))
+                    .await;
+            }
+            Err(e) => tracing::warn!(
+                "Failed to compute diff to export attempt {}: {}",
+                task_attempt.id,
+                e
+            ),
+        }
+    });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/gzip".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(attempt_export::tar_gz_stream(archive)),
+    ))
+}
+
+/// Tokens used by the attempt's coding agent runs. `None` until a run whose
+/// agent reports usage has finished.
+pub async fn get_attempt_usage(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<UsageSummary>>>, ApiError> {
+    let usage =
+        ExecutionProcessUsage::total_for_task_attempt(&deployment.db().pool, task_attempt.id)
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
+
+/// CPU time and peak memory of the attempt's processes, oldest first.
+/// Values are `null` for processes that could not be sampled.
+pub async fn get_attempt_resource_usage(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProcessResourceUsage>>>, ApiError> {
+    let processes =
+        ExecutionProcess::find_by_task_attempt_id(&deployment.db().pool, task_attempt.id, true)
+            .await?;
+    let usage = processes.iter().map(ProcessResourceUsage::from).collect();
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
+
+/// Lifecycle events of the attempt, oldest first.
+pub async fn get_task_attempt_events(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<AttemptEvent>>>, ApiError> {
+    let events =
+        AttemptEvent::find_by_task_attempt_id(&deployment.db().pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(events)))
+}
+
+/// The plan the attempt's latest plan-mode run presented, split into steps
+/// that can each be sent back as a follow-up.
+pub async fn get_task_attempt_plan(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<TaskAttemptPlan>>>, ApiError> {
+    let plan =
+        TaskAttemptPlan::find_by_task_attempt_id(&deployment.db().pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(plan)))
+}
+
+pub async fn stream_dev_server_log_tail(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<DevServerLogTailQuery>,
+) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, BoxError>>>, ApiError> {
+    let dev_server = ExecutionProcess::find_latest_by_task_attempt_and_run_reason(
+        &deployment.db().pool,
+        task_attempt.id,
+        &ExecutionProcessRunReason::DevServer,
+    )
+    .await?
+    .ok_or_else(|| ApiError::BadRequest("No dev server started for this attempt".to_string()))?;
+
+    let lines = query
+        .lines
+        .unwrap_or(dev_server::DEFAULT_TAIL_LINES)
+        .min(MAX_DEV_SERVER_TAIL_LINES);
+
+    let stream = deployment
+        .container()
+        .stream_log_tail(&dev_server.id, lines)
+        .await
+        .ok_or_else(|| {
+            ApiError::ExecutionProcess(ExecutionProcessError::ExecutionProcessNotFound)
+        })?;
+
+    Ok(Sse::new(
+        stream
+            .map_ok(|msg| msg.to_sse_event())
+            .map_err(|e| -> BoxError { e.into() }),
+    )
+    .keep_alive(KeepAlive::default()))
+}
+
 pub async fn get_task_attempt_children(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
@@ -1461,15 +2093,10 @@ pub async fn attach_existing_pr(
         return Err(ApiError::Project(ProjectError::ProjectNotFound));
     };
 
-    let github_service = GitHubService::new()?;
-    let repo_info = deployment
-        .git()
-        .get_github_repo_info(&project.git_repo_path)?;
+    let forge = project_forge(&deployment, &project).await?;
 
     // List all PRs for branch (open, closed, and merged)
-    let prs = github_service
-        .list_all_prs_for_branch(&repo_info, &task_attempt.branch)
-        .await?;
+    let prs = forge.list_prs_for_branch(&task_attempt.branch).await?;
 
     // Take the first PR (prefer open, but also accept merged/closed)
     if let Some(pr_info) = prs.into_iter().next() {
@@ -1493,10 +2120,30 @@ pub async fn attach_existing_pr(
             )
             .await?;
         }
-
-        // If PR is merged, mark task as done
+        AttemptEvent::record(
+            pool,
+            task_attempt.id,
+            AttemptEventType::PrOpened,
+            serde_json::json!({ "number": pr_info.number, "url": pr_info.url }),
+        )
+        .await;
         if matches!(pr_info.status, MergeStatus::Merged) {
+            AttemptEvent::record(
+                pool,
+                task_attempt.id,
+                AttemptEventType::PrMerged,
+                serde_json::json!({ "number": pr_info.number, "url": pr_info.url }),
+            )
+            .await;
+        }
+
+        // If PR is merged, mark task as done unless the project opted out
+        if matches!(pr_info.status, MergeStatus::Merged) && project.close_task_on_pr_merge {
             Task::update_status(pool, task.id, TaskStatus::Done).await?;
+            deployment
+                .container()
+                .start_unblocked_attempts(task.id)
+                .await;
 
             // Try broadcast update to other users in organization
             if let Ok(publisher) = deployment.share_publisher() {
@@ -1531,6 +2178,26 @@ pub async fn attach_existing_pr(
     }
 }
 
+/// Check the attempt's PR on GitHub now instead of waiting for the next poll
+pub async fn refresh_task_attempt_pr(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<PullRequestInfo>>, ApiError> {
+    let pr_merge = Merge::find_by_task_attempt_id(&deployment.db().pool, task_attempt.id)
+        .await?
+        .into_iter()
+        .find_map(|merge| match merge {
+            Merge::Pr(pr_merge) => Some(pr_merge),
+            Merge::Direct(_) => None,
+        })
+        .ok_or_else(|| {
+            ApiError::BadRequest("This attempt has no pull request to refresh".to_string())
+        })?;
+
+    let pr_info = deployment.pr_monitor().refresh_pr(&pr_merge).await?;
+    Ok(ResponseJson(ApiResponse::success(pr_info)))
+}
+
 #[axum::debug_handler]
 pub async fn gh_cli_setup_handler(
     Extension(task_attempt): Extension<TaskAttempt>,
@@ -1570,8 +2237,15 @@ pub async fn gh_cli_setup_handler(
 
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_attempt_id_router = Router::new()
-        .route("/", get(get_task_attempt))
+        .route(
+            "/",
+            get(get_task_attempt).layer(from_fn_with_state(
+                deployment.clone(),
+                redact_response_middleware,
+            )),
+        )
         .route("/follow-up", post(follow_up))
+        .route("/retry", post(retry_task_attempt))
         .route("/run-agent-setup", post(run_agent_setup))
         .route("/gh-cli-setup", post(gh_cli_setup_handler))
         .route(
@@ -1581,10 +2255,40 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
                 .delete(drafts::delete_draft),
         )
         .route("/draft/queue", post(drafts::set_draft_queue))
+        .route(
+            "/queued-follow-ups",
+            get(queued_followups::list_queued_followups).post(queued_followups::enqueue_followup),
+        )
+        .route(
+            "/queued-follow-ups/{followup_id}",
+            delete(queued_followups::delete_queued_followup),
+        )
         .route("/commit-info", get(get_commit_info))
         .route("/commit-compare", get(compare_commit_to_head))
+        .route("/usage", get(get_attempt_usage))
+        .route("/resource-usage", get(get_attempt_resource_usage))
+        .route("/events", get(get_task_attempt_events))
+        .route("/plan", get(get_task_attempt_plan))
+        .route("/export", get(export_task_attempt))
+        .route(
+            "/conversation",
+            get(get_attempt_conversation).layer(from_fn_with_state(
+                deployment.clone(),
+                redact_response_middleware,
+            )),
+        )
+        .route(
+            "/conversation/compare",
+            get(compare_conversations).layer(from_fn_with_state(
+                deployment.clone(),
+                redact_response_middleware,
+            )),
+        )
         .route("/start-dev-server", post(start_dev_server))
+        .route("/verify", post(rerun_verify))
+        .route("/dev-server/logs/tail", get(stream_dev_server_log_tail))
         .route("/branch-status", get(get_task_attempt_branch_status))
+        .route("/diff", get(get_task_attempt_diff))
         .route("/diff/ws", get(stream_task_attempt_diff_ws))
         .route("/merge", post(merge_task_attempt))
         .route("/push", post(push_task_attempt_branch))
@@ -1593,6 +2297,7 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/conflicts/abort", post(abort_conflicts_task_attempt))
         .route("/pr", post(create_github_pr))
         .route("/pr/attach", post(attach_existing_pr))
+        .route("/refresh-pr", post(refresh_task_attempt_pr))
         .route("/open-editor", post(open_task_attempt_in_editor))
         .route("/children", get(get_task_attempt_children))
         .route("/stop", post(stop_task_attempt_execution))
@@ -1604,7 +2309,15 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         ));
 
     let task_attempts_router = Router::new()
-        .route("/", get(get_task_attempts).post(create_task_attempt))
+        .route(
+            "/",
+            get(get_task_attempts)
+                .layer(from_fn_with_state(
+                    deployment.clone(),
+                    redact_response_middleware,
+                ))
+                .post(create_task_attempt),
+        )
         .nest("/{id}", task_attempt_id_router);
 
     Router::new().nest("/task-attempts", task_attempts_router)
@@ -67,6 +67,8 @@ async fn get_setup_helper_action(codex: &Codex) -> Result<ExecutorAction, ApiErr
         script: login_script,
         language: ScriptRequestLanguage::Bash,
         context: ScriptContext::ToolInstallScript,
+        timeout_secs: None,
+        stall_timeout_secs: None,
     };
 
     Ok(ExecutorAction::new(
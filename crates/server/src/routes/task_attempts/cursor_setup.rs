@@ -86,6 +86,8 @@ fi"#
             script: install_script,
             language: ScriptRequestLanguage::Bash,
             context: ScriptContext::ToolInstallScript,
+            timeout_secs: None,
+            stall_timeout_secs: None,
         };
         // Second action (chained): Login
         let login_script = format!(
@@ -99,6 +101,8 @@ export PATH="$HOME/.local/bin:$PATH"
             script: login_script,
             language: ScriptRequestLanguage::Bash,
             context: ScriptContext::ToolInstallScript,
+            timeout_secs: None,
+            stall_timeout_secs: None,
         };
 
         // Chain them: install → login
@@ -72,6 +72,8 @@ fi"#
             script: install_script,
             language: ScriptRequestLanguage::Bash,
             context: ScriptContext::ToolInstallScript,
+            timeout_secs: None,
+            stall_timeout_secs: None,
         };
 
         // Auth script
@@ -86,6 +88,8 @@ gh auth login --web --git-protocol https --skip-ssh-key
             script: auth_script,
             language: ScriptRequestLanguage::Bash,
             context: ScriptContext::ToolInstallScript,
+            timeout_secs: None,
+            stall_timeout_secs: None,
         };
 
         // Chain them: install → auth
@@ -0,0 +1,82 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+};
+use db::models::{queued_followup::QueuedFollowUp, task_attempt::TaskAttempt};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::container::ContainerService;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct EnqueueFollowUpRequest {
+    pub prompt: String,
+}
+
+/// Follow-ups waiting to be sent, oldest first.
+pub async fn list_queued_followups(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<QueuedFollowUp>>>, ApiError> {
+    let queued =
+        QueuedFollowUp::find_pending_by_task_attempt_id(&deployment.db().pool, task_attempt.id)
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(queued)))
+}
+
+/// Queue a follow-up to send when the running process finishes. With
+/// nothing running it is sent straight away.
+pub async fn enqueue_followup(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<EnqueueFollowUpRequest>,
+) -> Result<ResponseJson<ApiResponse<QueuedFollowUp>>, ApiError> {
+    let prompt = payload.prompt.trim();
+    if prompt.is_empty() {
+        return Err(ApiError::BadRequest(
+            "Follow-up prompt cannot be empty".to_string(),
+        ));
+    }
+
+    let queued = QueuedFollowUp::create(&deployment.db().pool, task_attempt.id, prompt).await?;
+    if let Err(e) = deployment
+        .container()
+        .dispatch_queued_followup(&task_attempt)
+        .await
+    {
+        tracing::error!(
+            "Failed to dispatch queued follow-up for attempt {}: {}",
+            task_attempt.id,
+            e
+        );
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "follow_up_queued",
+            serde_json::json!({
+                "attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(queued)))
+}
+
+pub async fn delete_queued_followup(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, followup_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if !QueuedFollowUp::delete_pending(&deployment.db().pool, followup_id, task_attempt.id).await? {
+        return Err(ApiError::Conflict(
+            "Follow-up was already sent or removed".to_string(),
+        ));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
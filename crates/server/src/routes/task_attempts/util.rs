@@ -1,10 +1,43 @@
-use db::models::image::TaskImage;
+use db::models::{image::TaskImage, project::Project};
 use deployment::Deployment;
+use executors::{
+    health,
+    profile::{ExecutorConfigs, ExecutorProfileId},
+};
 use services::services::{container::ContainerService, image::ImageService};
 use uuid::Uuid;
 
 use crate::error::ApiError;
 
+/// Executor profile for a new attempt: the one requested, then the project's
+/// override, then the global default.
+pub async fn resolve_executor_profile(
+    deployment: &crate::DeploymentImpl,
+    project: &Project,
+    requested: Option<ExecutorProfileId>,
+) -> ExecutorProfileId {
+    match requested.or_else(|| project.default_executor_profile()) {
+        Some(profile) => profile,
+        None => deployment.config().read().await.executor_profile.clone(),
+    }
+}
+
+/// Refuse to create an attempt whose executor cannot be launched. Unknown
+/// profiles are left for the start itself to report.
+pub async fn ensure_executor_available(
+    executor_profile_id: &ExecutorProfileId,
+) -> Result<(), ApiError> {
+    let Some(agent) = ExecutorConfigs::get_cached().get_coding_agent(executor_profile_id) else {
+        return Ok(());
+    };
+    let health = health::cached_availability(executor_profile_id, &agent).await;
+    if health.available {
+        Ok(())
+    } else {
+        Err(ApiError::ExecutorUnavailable(health))
+    }
+}
+
 /// Resolve and ensure the worktree path for a task attempt.
 pub async fn ensure_worktree_path(
     deployment: &crate::DeploymentImpl,
@@ -1,10 +1,10 @@
-use std::path::PathBuf;
+use std::str::FromStr;
 
 use anyhow;
 use axum::{
     Extension, Json, Router,
     extract::{
-        Query, State,
+        Path, Query, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
     http::StatusCode,
@@ -14,24 +14,38 @@ use axum::{
 };
 use db::models::{
     image::TaskImage,
-    task::{CreateTask, Task, TaskWithAttemptStatus, UpdateTask},
+    project::{Project, ProjectError},
+    queued_attempt::QueuedAttempt,
+    task::{CreateTask, Task, TaskStatus, TaskWithAttemptStatus, UpdateTask},
     task_attempt::{CreateTaskAttempt, TaskAttempt},
+    task_dependency::TaskDependency,
 };
 use deployment::Deployment;
-use executors::profile::ExecutorProfileId;
+use executors::{
+    command::CommandPreview,
+    env::ExecutionEnv,
+    executors::StandardCodingAgentExecutor,
+    profile::{ExecutorConfigs, ExecutorProfileId},
+};
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use services::services::{
-    container::ContainerService,
+    container::{AttemptStart, ContainerService},
+    privacy::{PrivacyMode, PrivacyParams, Redactor},
     share::ShareError,
-    worktree_manager::{WorktreeCleanup, WorktreeManager},
+    worktree_manager::WorktreeManager,
 };
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
-use utils::response::ApiResponse;
+use utils::{response::ApiResponse, text::git_branch_id};
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_task_middleware};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{effective_privacy_mode, load_task_middleware, redact_response_middleware},
+    routes::task_attempts::util::{ensure_executor_available, resolve_executor_profile},
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TaskQuery {
@@ -53,9 +67,11 @@ pub async fn stream_tasks_ws(
     ws: WebSocketUpgrade,
     State(deployment): State<DeploymentImpl>,
     Query(query): Query<TaskQuery>,
+    Query(privacy): Query<PrivacyParams>,
 ) -> impl IntoResponse {
+    let privacy = effective_privacy_mode(&deployment, &privacy).await;
     ws.on_upgrade(move |socket| async move {
-        if let Err(e) = handle_tasks_ws(socket, deployment, query.project_id).await {
+        if let Err(e) = handle_tasks_ws(socket, deployment, query.project_id, privacy).await {
             tracing::warn!("tasks WS closed: {}", e);
         }
     })
@@ -65,13 +81,21 @@ async fn handle_tasks_ws(
     socket: WebSocket,
     deployment: DeploymentImpl,
     project_id: Uuid,
+    privacy: Option<PrivacyMode>,
 ) -> anyhow::Result<()> {
+    let mut redactor = privacy.map(Redactor::new);
+
     // Get the raw stream and convert LogMsg to WebSocket messages
     let mut stream = deployment
         .events()
         .stream_tasks_raw(project_id)
         .await?
-        .map_ok(|msg| msg.to_ws_message_unchecked());
+        .map_ok(move |mut msg| {
+            if let Some(redactor) = redactor.as_mut() {
+                msg = redactor.redact_log_msg(msg);
+            }
+            msg.to_ws_message_unchecked()
+        });
 
     // Split socket into sender and receiver
     let (mut sender, mut receiver) = socket.split();
@@ -115,6 +139,13 @@ pub async fn create_task(
         payload.project_id
     );
 
+    let project = Project::find_by_id(&deployment.db().pool, payload.project_id)
+        .await?
+        .ok_or(ProjectError::ProjectNotFound)?;
+    if project.is_archived() {
+        return Err(ProjectError::Archived.into());
+    }
+
     let task = Task::create(&deployment.db().pool, &payload, id).await?;
 
     if let Some(image_ids) = &payload.image_ids {
@@ -139,7 +170,8 @@ pub async fn create_task(
 #[derive(Debug, Deserialize, TS)]
 pub struct CreateAndStartTaskRequest {
     pub task: CreateTask,
-    pub executor_profile_id: ExecutorProfileId,
+    /// Falls back to the project's default executor, then the global one
+    pub executor_profile_id: Option<ExecutorProfileId>,
     pub base_branch: String,
 }
 
@@ -147,6 +179,17 @@ pub async fn create_task_and_start(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<CreateAndStartTaskRequest>,
 ) -> Result<ResponseJson<ApiResponse<TaskWithAttemptStatus>>, ApiError> {
+    let project = Project::find_by_id(&deployment.db().pool, payload.task.project_id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+    if project.is_archived() {
+        return Err(ProjectError::Archived.into());
+    }
+    let executor_profile_id =
+        resolve_executor_profile(&deployment, &project, payload.executor_profile_id).await;
+    // Check before anything is created so a missing agent leaves no trace
+    ensure_executor_available(&executor_profile_id).await?;
+
     let task_id = Uuid::new_v4();
     let task = Task::create(&deployment.db().pool, &payload.task, task_id).await?;
 
@@ -165,6 +208,7 @@ pub async fn create_task_and_start(
             }),
         )
         .await;
+
     let attempt_id = Uuid::new_v4();
     let git_branch_name = deployment
         .container()
@@ -174,27 +218,27 @@ pub async fn create_task_and_start(
     let task_attempt = TaskAttempt::create(
         &deployment.db().pool,
         &CreateTaskAttempt {
-            executor: payload.executor_profile_id.executor,
+            executor: executor_profile_id.executor,
             base_branch: payload.base_branch,
             branch: git_branch_name,
+            retried_from_attempt_id: None,
         },
         attempt_id,
         task.id,
     )
     .await?;
-    let is_attempt_running = deployment
+    let attempt_start = deployment
         .container()
-        .start_attempt(&task_attempt, payload.executor_profile_id.clone())
+        .start_or_queue_attempt(&task_attempt, executor_profile_id.clone(), false)
         .await
-        .inspect_err(|err| tracing::error!("Failed to start task attempt: {}", err))
-        .is_ok();
+        .inspect_err(|err| tracing::error!("Failed to start task attempt: {}", err));
     deployment
         .track_if_analytics_allowed(
             "task_attempt_started",
             serde_json::json!({
                 "task_id": task.id.to_string(),
-                "executor": &payload.executor_profile_id.executor,
-                "variant": &payload.executor_profile_id.variant,
+                "executor": &executor_profile_id.executor,
+                "variant": &executor_profile_id.variant,
                 "attempt_id": task_attempt.id.to_string(),
             }),
         )
@@ -207,10 +251,14 @@ pub async fn create_task_and_start(
     tracing::info!("Started attempt for task {}", task.id);
     Ok(ResponseJson(ApiResponse::success(TaskWithAttemptStatus {
         task,
-        has_in_progress_attempt: is_attempt_running,
+        has_in_progress_attempt: matches!(attempt_start, Ok(AttemptStart::Started(_))),
         has_merged_attempt: false,
         last_attempt_failed: false,
+        has_queued_attempt: matches!(attempt_start, Ok(AttemptStart::Queued)),
+        // A task that was just created has no dependencies yet
+        blocked: false,
         executor: task_attempt.executor,
+        last_verify_status: None,
     })))
 }
 
@@ -227,6 +275,7 @@ pub async fn update_task(
         Some(s) => Some(s),                     // Non-empty string = update description
         None => existing_task.description,      // Field omitted = keep existing
     };
+    let was_done = existing_task.status == TaskStatus::Done;
     let status = payload.status.unwrap_or(existing_task.status);
     let parent_task_attempt = payload
         .parent_task_attempt
@@ -248,6 +297,13 @@ pub async fn update_task(
         TaskImage::associate_many_dedup(&deployment.db().pool, task.id, image_ids).await?;
     }
 
+    if task.status == TaskStatus::Done && !was_done {
+        deployment
+            .container()
+            .start_unblocked_attempts(task.id)
+            .await;
+    }
+
     // If task has been shared, broadcast update
     if task.shared_task_id.is_some() {
         let Ok(publisher) = deployment.share_publisher() else {
@@ -272,7 +328,6 @@ pub async fn delete_task(
         return Err(ApiError::Conflict("Task has running execution processes. Please wait for them to complete or stop them first.".to_string()));
     }
 
-    // Gather task attempts data needed for background cleanup
     let attempts = TaskAttempt::fetch_all(&deployment.db().pool, Some(task.id))
         .await
         .map_err(|e| {
@@ -280,61 +335,16 @@ pub async fn delete_task(
             ApiError::TaskAttempt(e)
         })?;
 
-    // Gather cleanup data before deletion
-    let project = task
-        .parent_project(&deployment.db().pool)
-        .await?
-        .ok_or_else(|| ApiError::Database(SqlxError::RowNotFound))?;
-
-    let cleanup_args: Vec<WorktreeCleanup> = attempts
-        .iter()
-        .filter_map(|attempt| {
-            attempt
-                .container_ref
-                .as_ref()
-                .map(|worktree_path| WorktreeCleanup {
-                    worktree_path: PathBuf::from(worktree_path),
-                    git_repo_path: Some(project.git_repo_path.clone()),
-                })
-        })
-        .collect();
-
-    if let Some(shared_task_id) = task.shared_task_id {
-        let Ok(publisher) = deployment.share_publisher() else {
-            return Err(ShareError::MissingConfig("share publisher unavailable").into());
-        };
-        publisher.delete_shared_task(shared_task_id).await?;
-    }
-
-    // Use a transaction to ensure atomicity: either all operations succeed or all are rolled back
+    // The task goes to the trash; its attempts, worktrees and any shared task
+    // it was linked to are kept until it is purged
     let mut tx = deployment.db().pool.begin().await?;
-
-    // Nullify parent_task_attempt for all child tasks before deletion
-    // This breaks parent-child relationships to avoid foreign key constraint violations
-    let mut total_children_affected = 0u64;
-    for attempt in &attempts {
-        let children_affected = Task::nullify_children_by_attempt_id(&mut *tx, attempt.id).await?;
-        total_children_affected += children_affected;
-    }
-
-    // Delete task from database (FK CASCADE will handle task_attempts)
-    let rows_affected = Task::delete(&mut *tx, task.id).await?;
-
+    let rows_affected = Task::soft_delete(&mut *tx, task.id).await?;
     if rows_affected == 0 {
         return Err(ApiError::Database(SqlxError::RowNotFound));
     }
-
-    // Commit the transaction - if this fails, all changes are rolled back
+    QueuedAttempt::remove_for_task(&mut *tx, task.id).await?;
     tx.commit().await?;
 
-    if total_children_affected > 0 {
-        tracing::info!(
-            "Nullified {} child task references before deleting task {}",
-            total_children_affected,
-            task.id
-        );
-    }
-
     deployment
         .track_if_analytics_allowed(
             "task_deleted",
@@ -342,35 +352,36 @@ pub async fn delete_task(
                 "task_id": task.id.to_string(),
                 "project_id": task.project_id.to_string(),
                 "attempt_count": attempts.len(),
+                "was_shared": task.shared_task_id.is_some(),
             }),
         )
         .await;
 
-    // Spawn background worktree cleanup task
-    let task_id = task.id;
-    tokio::spawn(async move {
-        let span = tracing::info_span!("background_worktree_cleanup", task_id = %task_id);
-        let _enter = span.enter();
+    // 202: permanent removal happens once the trash retention has passed
+    Ok((StatusCode::ACCEPTED, ResponseJson(ApiResponse::success(()))))
+}
 
-        tracing::info!(
-            "Starting background cleanup for task {} ({} worktrees)",
-            task_id,
-            cleanup_args.len()
-        );
+/// Bring a task back from the trash.
+pub async fn restore_task(
+    State(deployment): State<DeploymentImpl>,
+    Path(task_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    let task = Task::restore(&deployment.db().pool, task_id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
 
-        if let Err(e) = WorktreeManager::batch_cleanup_worktrees(&cleanup_args).await {
-            tracing::error!(
-                "Background worktree cleanup failed for task {}: {}",
-                task_id,
-                e
-            );
-        } else {
-            tracing::info!("Background cleanup completed for task {}", task_id);
-        }
-    });
+    deployment
+        .track_if_analytics_allowed(
+            "task_restored",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "project_id": task.project_id.to_string(),
+                "relinked": task.shared_task_id.is_some(),
+            }),
+        )
+        .await;
 
-    // Return 202 Accepted to indicate deletion was scheduled
-    Ok((StatusCode::ACCEPTED, ResponseJson(ApiResponse::success(()))))
+    Ok(ResponseJson(ApiResponse::success(task)))
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -405,21 +416,133 @@ pub async fn share_task(
     })))
 }
 
+#[derive(Debug, Deserialize, TS)]
+pub struct AddTaskDependencyRequest {
+    pub depends_on_task_id: Uuid,
+}
+
+/// Tasks this task waits for, done or not.
+pub async fn get_task_dependencies(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    let dependencies = TaskDependency::find_dependencies(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(dependencies)))
+}
+
+pub async fn add_task_dependency(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<AddTaskDependencyRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    TaskDependency::add(pool, task.id, payload.depends_on_task_id).await?;
+    let dependencies = TaskDependency::find_dependencies(pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(dependencies)))
+}
+
+pub async fn remove_task_dependency(
+    State(deployment): State<DeploymentImpl>,
+    Path((task_id, depends_on_task_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if !TaskDependency::remove(pool, task_id, depends_on_task_id).await? {
+        return Err(ApiError::Database(SqlxError::RowNotFound));
+    }
+    // Queued attempts of this task may have been waiting on the removed one
+    if let Err(e) = deployment.container().start_queued_attempts().await {
+        tracing::error!("Failed to start queued task attempts: {}", e);
+    }
+    let dependencies = TaskDependency::find_dependencies(pool, task_id).await?;
+    Ok(ResponseJson(ApiResponse::success(dependencies)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommandPreviewQuery {
+    /// `EXECUTOR[:VARIANT]`; defaults to the project's executor, then the
+    /// global one
+    pub executor: Option<String>,
+}
+
+/// What a new attempt on this task would run, without creating it.
+pub async fn preview_attempt_command(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<CommandPreviewQuery>,
+) -> Result<ResponseJson<ApiResponse<CommandPreview>>, ApiError> {
+    let requested = query
+        .executor
+        .as_deref()
+        .map(ExecutorProfileId::from_str)
+        .transpose()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let project = task
+        .parent_project(&deployment.db().pool)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+    let executor_profile_id = resolve_executor_profile(&deployment, &project, requested).await;
+    let coding_agent = ExecutorConfigs::get_cached()
+        .get_coding_agent(&executor_profile_id)
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!("Unknown executor profile: {executor_profile_id}"))
+        })?;
+
+    // A new attempt gets its own worktree, named after an id that does not
+    // exist yet
+    let working_dir = WorktreeManager::get_worktree_base_dir()
+        .join(format!("{{attempt}}-{}", git_branch_id(&task.title)));
+
+    let project_env = ExecutionEnv::from_project_vars(
+        &Project::find_env(&deployment.db().pool, project.id).await?,
+    );
+    let mut preview = coding_agent.preview_command(&working_dir).await?;
+    for (name, value) in project_env.masked() {
+        preview = preview.env(name, value);
+    }
+    Ok(ResponseJson(ApiResponse::success(preview)))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_actions_router = Router::new()
         .route("/", put(update_task))
         .route("/", delete(delete_task))
-        .route("/share", post(share_task));
+        .route("/share", post(share_task))
+        .route(
+            "/dependencies",
+            get(get_task_dependencies).post(add_task_dependency),
+        );
 
     let task_id_router = Router::new()
-        .route("/", get(get_task))
+        .route(
+            "/",
+            get(get_task).layer(from_fn_with_state(
+                deployment.clone(),
+                redact_response_middleware,
+            )),
+        )
+        .route("/attempts/preview", get(preview_attempt_command))
         .merge(task_actions_router)
         .layer(from_fn_with_state(deployment.clone(), load_task_middleware));
 
     let inner = Router::new()
-        .route("/", get(get_tasks).post(create_task))
+        .route(
+            "/",
+            get(get_tasks)
+                .layer(from_fn_with_state(
+                    deployment.clone(),
+                    redact_response_middleware,
+                ))
+                .post(create_task),
+        )
         .route("/stream/ws", get(stream_tasks_ws))
         .route("/create-and-start", post(create_task_and_start))
+        // Outside the task router, whose middleware expects a single path id
+        .route(
+            "/{task_id}/dependencies/{depends_on_task_id}",
+            delete(remove_task_dependency),
+        )
+        // Trashed tasks are not found by the task router's middleware
+        .route("/{task_id}/restore", post(restore_task))
         .nest("/{task_id}", task_id_router);
 
     // mount under /projects/:project_id/tasks
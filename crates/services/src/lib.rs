@@ -1,3 +1,7 @@
 pub mod services;
 
 pub use services::remote_client::{HandoffErrorCode, RemoteClient, RemoteClientError};
+
+#[cfg(test)]
+#[path = "../tests/common/mod.rs"]
+mod test_common;
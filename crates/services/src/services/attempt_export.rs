@@ -0,0 +1,355 @@
+//! Self-contained archives of what an attempt did, for attaching to a code
+//! review or a bug report.
+//!
+//! An export is a `.tar.gz` holding the attempt's metadata, its conversation
+//! as JSON and markdown, and the diff of its branch. Every file has secrets
+//! redacted by the same rules as the command preview. The archive is
+//! compressed as files arrive, so only the file being written is held in
+//! memory.
+
+use std::io::{self, Write};
+
+use chrono::Utc;
+use db::models::{execution_process::ExecutionProcess, task::Task, task_attempt::TaskAttempt};
+use executors::{
+    actions::ExecutorActionType,
+    command::redact_secrets,
+    logs::{ActionType, NormalizedConversation, NormalizedEntryType},
+};
+use flate2::{Compression, write::GzEncoder};
+use futures::Stream;
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use utils::text::truncate_to_char_boundary;
+
+use crate::services::git::DiffFileSummary;
+
+/// Output or message text kept per conversation entry; the rest is replaced
+/// by a truncation notice.
+pub const MAX_EXPORTED_ENTRY_BYTES: usize = 64 * 1024;
+
+/// Total size of the patches in `diff.patch`; files past it are listed
+/// without their patch.
+pub const MAX_EXPORTED_DIFF_BYTES: usize = 8 * 1024 * 1024;
+
+/// One file of the archive, with secrets already redacted.
+pub struct ArchiveFile {
+    path: String,
+    contents: String,
+}
+
+impl ArchiveFile {
+    pub fn new(path: impl Into<String>, contents: &str) -> Self {
+        Self {
+            path: path.into(),
+            contents: redact_secrets(contents),
+        }
+    }
+}
+
+/// Process metadata and every prompt sent to the agent, in the order they
+/// ran.
+pub fn metadata(task: &Task, task_attempt: &TaskAttempt, processes: &[ExecutionProcess]) -> Value {
+    let mut prompts = Vec::new();
+    let processes: Vec<Value> = processes
+        .iter()
+        .map(|process| {
+            let executor = match process.executor_action().map(|action| action.typ()) {
+                Ok(ExecutorActionType::CodingAgentInitialRequest(request)) => {
+                    prompts.push(json!({
+                        "execution_process_id": process.id,
+                        "kind": "initial",
+                        "prompt": request.prompt,
+                    }));
+                    Some(request.executor_profile_id.to_string())
+                }
+                Ok(ExecutorActionType::CodingAgentFollowUpRequest(request)) => {
+                    prompts.push(json!({
+                        "execution_process_id": process.id,
+                        "kind": "follow_up",
+                        "prompt": request.prompt,
+                    }));
+                    Some(request.executor_profile_id.to_string())
+                }
+                Ok(ExecutorActionType::ScriptRequest(_)) | Err(_) => None,
+            };
+            json!({
+                "id": process.id,
+                "run_reason": process.run_reason,
+                "executor": executor,
+                "status": process.status,
+                "exit_code": process.exit_code,
+                "dropped": process.dropped,
+                "started_at": process.started_at,
+                "completed_at": process.completed_at,
+                "duration_ms": process
+                    .completed_at
+                    .map(|completed| (completed - process.started_at).num_milliseconds()),
+                "cpu_time_ms": process.cpu_time_ms,
+                "peak_rss_bytes": process.peak_rss_bytes,
+            })
+        })
+        .collect();
+
+    json!({
+        "exported_at": Utc::now(),
+        "task": {
+            "id": task.id,
+            "title": task.title,
+            "description": task.description,
+        },
+        "attempt": {
+            "id": task_attempt.id,
+            "executor": task_attempt.executor,
+            "branch": task_attempt.branch,
+            "target_branch": task_attempt.target_branch,
+            "created_at": task_attempt.created_at,
+        },
+        "processes": processes,
+        "prompts": prompts,
+    })
+}
+
+/// Cut entry content and command output down to
+/// [`MAX_EXPORTED_ENTRY_BYTES`].
+pub fn cap_conversation(conversation: &mut NormalizedConversation) {
+    for entry in &mut conversation.entries {
+        cap_text(&mut entry.content);
+        if let NormalizedEntryType::ToolUse {
+            action_type:
+                ActionType::CommandRun {
+                    result: Some(result),
+                    ..
+                },
+            ..
+        } = &mut entry.entry_type
+            && let Some(output) = &mut result.output
+        {
+            cap_text(output);
+        }
+    }
+}
+
+fn cap_text(text: &mut String) {
+    if text.len() <= MAX_EXPORTED_ENTRY_BYTES {
+        return;
+    }
+    let kept = truncate_to_char_boundary(text, MAX_EXPORTED_ENTRY_BYTES).len();
+    let notice = format!("\n… [truncated {} bytes]", text.len() - kept);
+    text.truncate(kept);
+    text.push_str(&notice);
+}
+
+/// The conversation as a readable transcript.
+pub fn render_markdown(title: &str, conversation: &NormalizedConversation) -> String {
+    let mut out = format!(
+        "# {}\n\n_Executor: {}_\n",
+        title.trim(),
+        conversation.executor_type
+    );
+    for entry in &conversation.entries {
+        let content = entry.content.trim();
+        match &entry.entry_type {
+            NormalizedEntryType::UserMessage => {
+                out.push_str(&format!("\n## User\n\n{content}\n"));
+            }
+            NormalizedEntryType::AssistantMessage => {
+                out.push_str(&format!("\n## Assistant\n\n{content}\n"));
+            }
+            NormalizedEntryType::Thinking => {
+                out.push_str(&format!("\n_Thinking:_ {content}\n"));
+            }
+            NormalizedEntryType::UserFeedback { denied_tool } => {
+                out.push_str(&format!("\n**Denied {denied_tool}:** {content}\n"));
+            }
+            NormalizedEntryType::SystemMessage => {
+                out.push_str(&format!("\n_System:_ {content}\n"));
+            }
+            NormalizedEntryType::ErrorMessage { .. } => {
+                out.push_str(&format!("\n**Error:** {content}\n"));
+            }
+            NormalizedEntryType::ToolUse {
+                tool_name,
+                action_type,
+                status,
+            } => {
+                let status = serde_json::to_value(status)
+                    .ok()
+                    .and_then(|value| value.get("status")?.as_str().map(str::to_string))
+                    .unwrap_or_default();
+                out.push_str(&format!("\n- **{tool_name}** ({status}): {content}\n"));
+                if let ActionType::CommandRun {
+                    command,
+                    result: Some(result),
+                } = action_type
+                {
+                    let output = result.output.as_deref().unwrap_or_default();
+                    out.push_str(&fenced(&format!("$ {command}\n{output}")));
+                }
+            }
+            NormalizedEntryType::Loading | NormalizedEntryType::NextAction { .. } => {}
+        }
+    }
+    out
+}
+
+/// A code block whose fence is longer than any backtick run in `text`, so
+/// the text cannot close it.
+fn fenced(text: &str) -> String {
+    let longest_run = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("\n{fence}\n{}\n{fence}\n", text.trim_end())
+}
+
+/// Patches of every changed file joined into one unified diff. Files whose
+/// patch could not be included get a comment line instead.
+pub fn unified_diff(files: &[DiffFileSummary]) -> String {
+    let mut out = String::new();
+    for file in files {
+        match &file.patch {
+            Some(patch) => {
+                out.push_str(patch);
+                if !patch.ends_with('\n') {
+                    out.push('\n');
+                }
+            }
+            None if file.binary => {
+                out.push_str(&format!("# Binary file {} differs\n", file.path));
+            }
+            None if file.patch_omitted => out.push_str(&format!(
+                "# {}: patch omitted, the diff is larger than {} MiB\n",
+                file.path,
+                MAX_EXPORTED_DIFF_BYTES / (1024 * 1024)
+            )),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Sends what the gzip encoder writes on to the response body.
+struct ChannelWriter(mpsc::Sender<io::Result<Vec<u8>>>);
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.blocking_send(Ok(buf.to_vec())).map_err(|_| {
+            io::Error::new(io::ErrorKind::BrokenPipe, "export download was dropped")
+        })?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A `.tar.gz` of the files sent on `files`, produced as they arrive. The
+/// archive ends once the sender is dropped.
+pub fn tar_gz_stream(
+    mut files: mpsc::Receiver<ArchiveFile>,
+) -> impl Stream<Item = io::Result<Vec<u8>>> {
+    let (tx, rx) = mpsc::channel(8);
+    let errors = tx.clone();
+    tokio::task::spawn_blocking(move || {
+        let result = (|| {
+            let mut builder =
+                tar::Builder::new(GzEncoder::new(ChannelWriter(tx), Compression::default()));
+            let mtime = Utc::now().timestamp().max(0) as u64;
+            while let Some(file) = files.blocking_recv() {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(file.contents.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(mtime);
+                builder.append_data(&mut header, &file.path, file.contents.as_bytes())?;
+            }
+            builder.into_inner()?.finish()?;
+            Ok::<_, io::Error>(())
+        })();
+        if let Err(e) = result {
+            tracing::warn!("Failed to write attempt export: {}", e);
+            let _ = errors.blocking_send(Err(e));
+        }
+    });
+    ReceiverStream::new(rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::logs::{CommandExitStatus, CommandRunResult, NormalizedEntry, ToolStatus};
+
+    use super::*;
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn long_command_output_is_truncated_with_a_notice() {
+        let mut conversation = NormalizedConversation {
+            entries: vec![
+                entry(NormalizedEntryType::UserMessage, "Run the tests"),
+                entry(
+                    NormalizedEntryType::ToolUse {
+                        tool_name: "bash".to_string(),
+                        action_type: ActionType::CommandRun {
+                            command: "cargo test".to_string(),
+                            result: Some(CommandRunResult {
+                                exit_status: Some(CommandExitStatus::ExitCode { code: 0 }),
+                                output: Some("ok\n".repeat(MAX_EXPORTED_ENTRY_BYTES)),
+                            }),
+                        },
+                        status: ToolStatus::Success,
+                    },
+                    "cargo test",
+                ),
+            ],
+            session_id: None,
+            executor_type: "CLAUDE_CODE".to_string(),
+            prompt: None,
+            summary: None,
+            usage: None,
+            mcp_degraded: false,
+        };
+
+        cap_conversation(&mut conversation);
+
+        let NormalizedEntryType::ToolUse {
+            action_type:
+                ActionType::CommandRun {
+                    result: Some(result),
+                    ..
+                },
+            ..
+        } = &conversation.entries[1].entry_type
+        else {
+            unreachable!();
+        };
+        let output = result.output.as_deref().unwrap();
+        assert!(output.len() < MAX_EXPORTED_ENTRY_BYTES + 64);
+        assert!(output.ends_with(&format!(
+            "[truncated {} bytes]",
+            2 * MAX_EXPORTED_ENTRY_BYTES
+        )));
+
+        let markdown = render_markdown("Fix tests", &conversation);
+        assert!(markdown.starts_with("# Fix tests\n"));
+        assert!(markdown.contains("\n## User\n\nRun the tests\n"));
+        assert!(markdown.contains("\n```\n$ cargo test\nok\n"));
+    }
+
+    #[test]
+    fn archive_files_are_redacted() {
+        let file = ArchiveFile::new("diff.patch", "+export API_TOKEN=abc123\n");
+        assert_eq!(file.contents, "+export API_TOKEN=<redacted>\n");
+    }
+}
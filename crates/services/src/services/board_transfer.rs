@@ -0,0 +1,539 @@
+//! Moving a project's task board between machines as a JSON document.
+//!
+//! An export holds the project's settings, its tasks with their statuses and
+//! dependencies, and each attempt with its execution processes. Raw process
+//! logs are left out unless asked for. On import every id is replaced by a
+//! fresh one, timestamps are kept, and the board is attached to a repository
+//! that already exists on this machine. Attempts come back without their
+//! worktrees, which only existed on the exporting machine.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    str::FromStr,
+};
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    execution_process::{
+        ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus, ImportExecutionProcess,
+    },
+    execution_process_logs::ExecutionProcessLogs,
+    project::{ImportProject, Project, SharedTaskAutoCreate},
+    shared_task::SharedTask,
+    task::{ImportTask, Task, TaskStatus},
+    task_attempt::{ImportTaskAttempt, TaskAttempt, TaskAttemptError, VerifyStatus},
+    task_dependency::TaskDependency,
+};
+use executors::{actions::ExecutorAction, executors::BaseCodingAgent};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Bumped whenever the document changes in a way older importers cannot read
+pub const BOARD_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum BoardTransferError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    TaskAttempt(#[from] TaskAttemptError),
+    #[error("Unsupported board format version {0}, expected {BOARD_FORMAT_VERSION}")]
+    UnsupportedVersion(u32),
+    /// `path` locates the offending record, e.g. `tasks[3].depends_on[0]`
+    #[error("{path}: {message}")]
+    Invalid { path: String, message: String },
+}
+
+fn invalid(path: impl Into<String>, message: impl Into<String>) -> BoardTransferError {
+    BoardTransferError::Invalid {
+        path: path.into(),
+        message: message.into(),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct BoardExport {
+    pub version: u32,
+    #[ts(type = "Date")]
+    pub exported_at: DateTime<Utc>,
+    pub project: BoardProject,
+    pub tasks: Vec<BoardTask>,
+}
+
+/// Project settings. Environment variables are not exported since they
+/// often hold secrets.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct BoardProject {
+    pub name: String,
+    pub setup_script: Option<String>,
+    pub dev_script: Option<String>,
+    pub dev_server_url_pattern: Option<String>,
+    pub cleanup_script: Option<String>,
+    pub copy_files: Option<String>,
+    pub verify_script: Option<String>,
+    pub verify_timeout_secs: Option<i64>,
+    pub default_executor: Option<String>,
+    pub max_concurrent_executions: Option<i64>,
+    pub close_task_on_pr_merge: bool,
+    /// Absent from boards exported before the setting existed
+    #[serde(default)]
+    pub shared_task_auto_create: SharedTaskAutoCreate,
+    /// Remote project the board was linked to
+    pub remote_project_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct BoardTask {
+    /// Id on the exporting machine; only used to resolve references within
+    /// the document
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub status: TaskStatus,
+    /// Attempt this task was created from, if it is part of the document
+    pub parent_task_attempt: Option<Uuid>,
+    /// Remote shared task this task was linked to
+    pub shared_task_id: Option<Uuid>,
+    /// Tasks of the document this one waits for
+    #[serde(default)]
+    pub depends_on: Vec<Uuid>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub attempts: Vec<BoardAttempt>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct BoardAttempt {
+    pub id: Uuid,
+    pub branch: String,
+    pub target_branch: String,
+    pub executor: String,
+    pub verify_status: Option<VerifyStatus>,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub processes: Vec<BoardProcess>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct BoardProcess {
+    pub run_reason: ExecutionProcessRunReason,
+    /// What was run, including the prompt sent to the agent
+    #[ts(type = "ExecutorAction")]
+    pub executor_action: Value,
+    pub status: ExecutionProcessStatus,
+    pub exit_code: Option<i64>,
+    pub before_head_commit: Option<String>,
+    pub after_head_commit: Option<String>,
+    #[ts(type = "Date")]
+    pub started_at: DateTime<Utc>,
+    #[ts(type = "Date | null")]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Raw output as JSONL; only present when the export asked for logs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub logs: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ImportBoardRequest {
+    /// Existing git repository the board is attached to
+    pub git_repo_path: String,
+    /// Defaults to the exported project's name
+    pub name: Option<String>,
+    pub board: BoardExport,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ImportBoardResponse {
+    pub project: Project,
+    pub tasks: usize,
+    pub attempts: usize,
+    /// Tasks whose shared task link was dropped because the board's remote
+    /// project is not available to this project
+    pub unlinked_shared_tasks: usize,
+}
+
+/// The project's board, with raw process logs only if `include_logs`.
+pub async fn export_board(
+    pool: &SqlitePool,
+    project: &Project,
+    include_logs: bool,
+) -> Result<BoardExport, BoardTransferError> {
+    let mut tasks = Vec::new();
+    for task in Task::find_by_project_id_with_attempt_status(pool, project.id).await? {
+        let task = task.task;
+        let depends_on = TaskDependency::find_dependencies(pool, task.id)
+            .await?
+            .into_iter()
+            .map(|dependency| dependency.id)
+            .collect();
+
+        let mut attempts = Vec::new();
+        // Oldest first, so an import recreates them in the order they ran
+        for attempt in TaskAttempt::fetch_all(pool, Some(task.id))
+            .await?
+            .into_iter()
+            .rev()
+        {
+            let mut processes = Vec::new();
+            for process in
+                ExecutionProcess::find_by_task_attempt_id(pool, attempt.id, false).await?
+            {
+                let logs = if include_logs {
+                    let records =
+                        ExecutionProcessLogs::find_by_execution_id(pool, process.id).await?;
+                    let lines = records.iter().flat_map(|record| record.logs.lines());
+                    Some(
+                        lines
+                            .filter(|line| !line.trim().is_empty())
+                            .map(|line| format!("{line}\n"))
+                            .collect(),
+                    )
+                } else {
+                    None
+                };
+                processes.push(BoardProcess {
+                    run_reason: process.run_reason,
+                    executor_action: serde_json::to_value(&process.executor_action.0)
+                        .unwrap_or_default(),
+                    status: process.status,
+                    exit_code: process.exit_code,
+                    before_head_commit: process.before_head_commit,
+                    after_head_commit: process.after_head_commit,
+                    started_at: process.started_at,
+                    completed_at: process.completed_at,
+                    logs,
+                });
+            }
+            attempts.push(BoardAttempt {
+                id: attempt.id,
+                branch: attempt.branch,
+                target_branch: attempt.target_branch,
+                executor: attempt.executor,
+                verify_status: attempt.verify_status,
+                created_at: attempt.created_at,
+                updated_at: attempt.updated_at,
+                processes,
+            });
+        }
+
+        tasks.push(BoardTask {
+            id: task.id,
+            title: task.title,
+            description: task.description,
+            status: task.status,
+            parent_task_attempt: task.parent_task_attempt,
+            shared_task_id: task.shared_task_id,
+            depends_on,
+            created_at: task.created_at,
+            updated_at: task.updated_at,
+            attempts,
+        });
+    }
+    tasks.sort_by_key(|task| task.created_at);
+
+    Ok(BoardExport {
+        version: BOARD_FORMAT_VERSION,
+        exported_at: Utc::now(),
+        project: BoardProject {
+            name: project.name.clone(),
+            setup_script: project.setup_script.clone(),
+            dev_script: project.dev_script.clone(),
+            dev_server_url_pattern: project.dev_server_url_pattern.clone(),
+            cleanup_script: project.cleanup_script.clone(),
+            copy_files: project.copy_files.clone(),
+            verify_script: project.verify_script.clone(),
+            verify_timeout_secs: project.verify_timeout_secs,
+            default_executor: project.default_executor.clone(),
+            max_concurrent_executions: project.max_concurrent_executions,
+            close_task_on_pr_merge: project.close_task_on_pr_merge,
+            shared_task_auto_create: project.shared_task_auto_create,
+            remote_project_id: project.remote_project_id,
+        },
+        tasks,
+    })
+}
+
+/// Check the document before anything is written, naming the first bad
+/// record.
+pub fn validate_board(board: &BoardExport) -> Result<(), BoardTransferError> {
+    if board.version != BOARD_FORMAT_VERSION {
+        return Err(BoardTransferError::UnsupportedVersion(board.version));
+    }
+    if board.project.name.trim().is_empty() {
+        return Err(invalid("project.name", "must not be empty"));
+    }
+
+    let mut task_ids = HashSet::new();
+    let mut attempt_ids = HashSet::new();
+    for (i, task) in board.tasks.iter().enumerate() {
+        if !task_ids.insert(task.id) {
+            return Err(invalid(format!("tasks[{i}].id"), "duplicate task id"));
+        }
+        if task.title.trim().is_empty() {
+            return Err(invalid(format!("tasks[{i}].title"), "must not be empty"));
+        }
+        for (j, attempt) in task.attempts.iter().enumerate() {
+            let path = format!("tasks[{i}].attempts[{j}]");
+            if !attempt_ids.insert(attempt.id) {
+                return Err(invalid(format!("{path}.id"), "duplicate attempt id"));
+            }
+            if BaseCodingAgent::from_str(&attempt.executor).is_err() {
+                return Err(invalid(
+                    format!("{path}.executor"),
+                    format!("unknown executor `{}`", attempt.executor),
+                ));
+            }
+            for (k, process) in attempt.processes.iter().enumerate() {
+                if let Err(e) =
+                    serde_json::from_value::<ExecutorAction>(process.executor_action.clone())
+                {
+                    return Err(invalid(
+                        format!("{path}.processes[{k}].executor_action"),
+                        e.to_string(),
+                    ));
+                }
+            }
+        }
+    }
+
+    for (i, task) in board.tasks.iter().enumerate() {
+        for (j, dependency) in task.depends_on.iter().enumerate() {
+            let path = format!("tasks[{i}].depends_on[{j}]");
+            if *dependency == task.id {
+                return Err(invalid(path, "a task cannot depend on itself"));
+            }
+            if !task_ids.contains(dependency) {
+                return Err(invalid(path, format!("no task with id {dependency}")));
+            }
+        }
+    }
+    if let Some(i) = first_task_in_cycle(&board.tasks) {
+        return Err(invalid(
+            format!("tasks[{i}].depends_on"),
+            "dependencies form a cycle",
+        ));
+    }
+    Ok(())
+}
+
+/// Index of a task on a dependency cycle, if there is one.
+fn first_task_in_cycle(tasks: &[BoardTask]) -> Option<usize> {
+    let index: HashMap<Uuid, usize> = tasks.iter().enumerate().map(|(i, t)| (t.id, i)).collect();
+    let mut dependents = vec![Vec::new(); tasks.len()];
+    for (i, task) in tasks.iter().enumerate() {
+        for dependency in &task.depends_on {
+            dependents[index[dependency]].push(i);
+        }
+    }
+
+    // Peel off tasks nothing left depends on; what remains is a cycle or
+    // depended on by one
+    let mut left: Vec<usize> = dependents.iter().map(Vec::len).collect();
+    let mut removed = vec![false; tasks.len()];
+    let mut queue: VecDeque<usize> = (0..tasks.len()).filter(|i| left[*i] == 0).collect();
+    while let Some(i) = queue.pop_front() {
+        removed[i] = true;
+        for dependency in &tasks[i].depends_on {
+            let j = index[dependency];
+            left[j] -= 1;
+            if left[j] == 0 {
+                queue.push_back(j);
+            }
+        }
+    }
+
+    // Every task left has a dependent left, so following dependents comes
+    // back round to a task on the cycle
+    let mut i = (0..tasks.len()).find(|i| !removed[*i])?;
+    let mut seen = vec![false; tasks.len()];
+    while !seen[i] {
+        seen[i] = true;
+        i = *dependents[i].iter().find(|k| !removed[**k])?;
+    }
+    Some(i)
+}
+
+/// Create a project for `git_repo_path` holding the board, in a single
+/// transaction. The project is linked to the board's remote project only if
+/// no other local project is, and shared task links are kept only for shared
+/// tasks of that remote project known on this machine.
+pub async fn import_board(
+    pool: &SqlitePool,
+    request: &ImportBoardRequest,
+) -> Result<ImportBoardResponse, BoardTransferError> {
+    let board = &request.board;
+    validate_board(board)?;
+
+    let remote_project_id = match board.project.remote_project_id {
+        Some(remote_id)
+            if Project::find_by_remote_project_id(pool, remote_id)
+                .await?
+                .is_none() =>
+        {
+            Some(remote_id)
+        }
+        _ => None,
+    };
+    let mut shared_links = HashMap::new();
+    let mut unlinked_shared_tasks = 0;
+    for task in &board.tasks {
+        let Some(shared_task_id) = task.shared_task_id else {
+            continue;
+        };
+        let linkable = match remote_project_id {
+            Some(remote_id) => {
+                SharedTask::find_by_id(pool, shared_task_id)
+                    .await?
+                    .is_some_and(|shared| shared.remote_project_id == remote_id)
+                    && Task::find_by_shared_task_id(pool, shared_task_id)
+                        .await?
+                        .is_none()
+            }
+            None => false,
+        };
+        if linkable {
+            shared_links.insert(task.id, shared_task_id);
+        } else {
+            unlinked_shared_tasks += 1;
+        }
+    }
+
+    let project_id = Uuid::new_v4();
+    let task_ids: HashMap<Uuid, Uuid> =
+        board.tasks.iter().map(|t| (t.id, Uuid::new_v4())).collect();
+    let attempt_ids: HashMap<Uuid, Uuid> = board
+        .tasks
+        .iter()
+        .flat_map(|t| &t.attempts)
+        .map(|a| (a.id, Uuid::new_v4()))
+        .collect();
+
+    let name = request
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or(&board.project.name);
+    let settings = &board.project;
+    let mut tx = pool.begin().await?;
+    Project::insert_imported(
+        &mut *tx,
+        &ImportProject {
+            name: name.to_string(),
+            git_repo_path: request.git_repo_path.clone(),
+            setup_script: settings.setup_script.clone(),
+            dev_script: settings.dev_script.clone(),
+            dev_server_url_pattern: settings.dev_server_url_pattern.clone(),
+            cleanup_script: settings.cleanup_script.clone(),
+            copy_files: settings.copy_files.clone(),
+            verify_script: settings.verify_script.clone(),
+            verify_timeout_secs: settings.verify_timeout_secs,
+            default_executor: settings.default_executor.clone(),
+            max_concurrent_executions: settings.max_concurrent_executions,
+            close_task_on_pr_merge: settings.close_task_on_pr_merge,
+            shared_task_auto_create: settings.shared_task_auto_create,
+            remote_project_id,
+        },
+        project_id,
+    )
+    .await?;
+
+    for task in &board.tasks {
+        let task_id = task_ids[&task.id];
+        Task::insert_imported(
+            &mut *tx,
+            &ImportTask {
+                project_id,
+                title: task.title.clone(),
+                description: task.description.clone(),
+                status: task.status.clone(),
+                shared_task_id: shared_links.get(&task.id).copied(),
+                created_at: task.created_at,
+                updated_at: task.updated_at,
+            },
+            task_id,
+        )
+        .await?;
+
+        for attempt in &task.attempts {
+            let attempt_id = attempt_ids[&attempt.id];
+            TaskAttempt::insert_imported(
+                &mut *tx,
+                &ImportTaskAttempt {
+                    task_id,
+                    branch: attempt.branch.clone(),
+                    target_branch: attempt.target_branch.clone(),
+                    executor: attempt.executor.clone(),
+                    verify_status: attempt.verify_status,
+                    created_at: attempt.created_at,
+                    updated_at: attempt.updated_at,
+                },
+                attempt_id,
+            )
+            .await?;
+
+            for process in &attempt.processes {
+                let process_id = Uuid::new_v4();
+                // Nothing is running for it on this machine
+                let status = match &process.status {
+                    ExecutionProcessStatus::Running => ExecutionProcessStatus::Killed,
+                    status => status.clone(),
+                };
+                let completed_at = process.completed_at.or(Some(process.started_at));
+                ExecutionProcess::insert_imported(
+                    &mut *tx,
+                    &ImportExecutionProcess {
+                        task_attempt_id: attempt_id,
+                        run_reason: process.run_reason.clone(),
+                        executor_action: process.executor_action.clone(),
+                        before_head_commit: process.before_head_commit.clone(),
+                        after_head_commit: process.after_head_commit.clone(),
+                        status,
+                        exit_code: process.exit_code,
+                        started_at: process.started_at,
+                        completed_at,
+                    },
+                    process_id,
+                )
+                .await?;
+
+                if let Some(logs) = process.logs.as_deref().filter(|logs| !logs.is_empty()) {
+                    ExecutionProcessLogs::insert_imported(&mut *tx, process_id, logs, completed_at)
+                        .await?;
+                }
+            }
+        }
+    }
+
+    for task in &board.tasks {
+        let task_id = task_ids[&task.id];
+        if let Some(parent) = task.parent_task_attempt.and_then(|id| attempt_ids.get(&id)) {
+            Task::set_parent_task_attempt(&mut *tx, task_id, *parent).await?;
+        }
+        for dependency in &task.depends_on {
+            TaskDependency::insert_imported(&mut *tx, task_id, task_ids[dependency]).await?;
+        }
+    }
+    tx.commit().await?;
+
+    let project = Project::find_by_id(pool, project_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+    Ok(ImportBoardResponse {
+        project,
+        tasks: task_ids.len(),
+        attempts: attempt_ids.len(),
+        unlinked_shared_tasks,
+    })
+}
@@ -0,0 +1,214 @@
+//! Status changes and deletes applied to many tasks of a project at once.
+//!
+//! Every task is checked first and either applied or skipped with a reason;
+//! the applied changes are then written in a single transaction. Deleted
+//! tasks go to the trash like single deletes do. Remote copies of shared
+//! tasks are not touched here: the caller publishes
+//! [`BulkTaskChanges::shared_updates`] once the transaction has committed.
+
+use std::collections::HashSet;
+
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessStatus},
+    project::Project,
+    queued_attempt::QueuedAttempt,
+    task::{Task, TaskStatus},
+    task_attempt::{TaskAttempt, TaskAttemptError},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Most tasks a single bulk request may name
+pub const MAX_BULK_TASKS: usize = 200;
+
+#[derive(Debug, Error)]
+pub enum BulkTaskError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    TaskAttempt(#[from] TaskAttemptError),
+    #[error("At most {MAX_BULK_TASKS} tasks can be changed at once")]
+    TooManyTasks,
+    #[error("`set_status` needs a `status`")]
+    MissingStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum BulkTaskOperation {
+    SetStatus,
+    Delete,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct BulkTaskRequest {
+    pub operation: BulkTaskOperation,
+    pub task_ids: Vec<Uuid>,
+    /// Required for `set_status`
+    pub status: Option<TaskStatus>,
+    /// Allow deleting tasks linked to a shared task. The local task is
+    /// detached and moved to the trash; the shared task stays on the remote
+    /// server.
+    #[serde(default)]
+    pub detach_shared: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum BulkTaskOutcome {
+    Applied,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct BulkTaskResult {
+    pub task_id: Uuid,
+    pub outcome: BulkTaskOutcome,
+    /// Why the task was skipped, or why an applied change did not reach the
+    /// task's remote copy
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct BulkTaskResponse {
+    /// One entry per distinct task id, in request order
+    pub results: Vec<BulkTaskResult>,
+}
+
+#[derive(Debug, Default)]
+pub struct BulkTaskChanges {
+    pub results: Vec<BulkTaskResult>,
+    /// Shared tasks whose status changed locally and still need publishing
+    pub shared_updates: Vec<Uuid>,
+}
+
+impl BulkTaskChanges {
+    fn skip(&mut self, task_id: Uuid, reason: impl Into<String>) {
+        self.results.push(BulkTaskResult {
+            task_id,
+            outcome: BulkTaskOutcome::Skipped,
+            reason: Some(reason.into()),
+        });
+    }
+
+    fn apply(&mut self, task_id: Uuid) {
+        self.results.push(BulkTaskResult {
+            task_id,
+            outcome: BulkTaskOutcome::Applied,
+            reason: None,
+        });
+    }
+}
+
+/// Apply `request` to the project's tasks. `can_publish` tells whether
+/// status changes of shared tasks can be sent to the remote server; without
+/// it those tasks are skipped rather than left out of sync.
+pub async fn apply_bulk_task_operation(
+    pool: &SqlitePool,
+    project: &Project,
+    request: &BulkTaskRequest,
+    can_publish: bool,
+) -> Result<BulkTaskChanges, BulkTaskError> {
+    let mut seen = HashSet::new();
+    let task_ids: Vec<Uuid> = request
+        .task_ids
+        .iter()
+        .copied()
+        .filter(|id| seen.insert(*id))
+        .collect();
+    if task_ids.len() > MAX_BULK_TASKS {
+        return Err(BulkTaskError::TooManyTasks);
+    }
+    let status = match request.operation {
+        BulkTaskOperation::SetStatus => {
+            Some(request.status.clone().ok_or(BulkTaskError::MissingStatus)?)
+        }
+        BulkTaskOperation::Delete => None,
+    };
+
+    let mut changes = BulkTaskChanges::default();
+    let mut to_update = Vec::new();
+    let mut to_delete = Vec::new();
+    for task_id in task_ids {
+        let Some(task) = Task::find_by_id_and_project_id(pool, task_id, project.id).await? else {
+            changes.skip(task_id, "Task not found in this project");
+            continue;
+        };
+        match &status {
+            Some(status) => {
+                if task.status == *status {
+                    changes.skip(task_id, "Task already has this status");
+                } else if task.shared_task_id.is_some() && !can_publish {
+                    changes.skip(
+                        task_id,
+                        "Task is shared, but sharing is not configured to update it",
+                    );
+                } else {
+                    to_update.push(task);
+                }
+            }
+            None => {
+                if task.shared_task_id.is_some() && !request.detach_shared {
+                    changes.skip(
+                        task_id,
+                        "Task is linked to a shared task; set `detach_shared` to delete it",
+                    );
+                    continue;
+                }
+                let attempts = TaskAttempt::fetch_all(pool, Some(task.id)).await?;
+                if has_running_processes(pool, &attempts).await? {
+                    changes.skip(task_id, "Task has running execution processes");
+                    continue;
+                }
+                to_delete.push(task);
+            }
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    if let Some(status) = &status {
+        for task in &to_update {
+            Task::update_status(&mut *tx, task.id, status.clone()).await?;
+        }
+    }
+    for task in &to_delete {
+        Task::soft_delete(&mut *tx, task.id).await?;
+        QueuedAttempt::remove_for_task(&mut *tx, task.id).await?;
+    }
+    tx.commit().await?;
+
+    for task in to_update {
+        if task.shared_task_id.is_some() {
+            changes.shared_updates.push(task.id);
+        }
+        changes.apply(task.id);
+    }
+    for task in to_delete {
+        changes.apply(task.id);
+    }
+    changes
+        .results
+        .sort_by_key(|result| request.task_ids.iter().position(|id| *id == result.task_id));
+    Ok(changes)
+}
+
+async fn has_running_processes(
+    pool: &SqlitePool,
+    attempts: &[TaskAttempt],
+) -> Result<bool, sqlx::Error> {
+    for attempt in attempts {
+        let processes = ExecutionProcess::find_by_task_attempt_id(pool, attempt.id, false).await?;
+        if processes
+            .iter()
+            .any(|process| process.status == ExecutionProcessStatus::Running)
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
@@ -1,6 +1,12 @@
-use std::{path::Path, str::FromStr};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-use executors::{command::CommandBuilder, executors::ExecutorError};
+use executors::{
+    command::{CommandBuilder, CommandParts},
+    executors::ExecutorError,
+};
 use serde::{Deserialize, Serialize};
 use strum_macros::{EnumIter, EnumString};
 use thiserror::Error;
@@ -27,8 +33,16 @@ pub enum EditorOpenError {
         details: String,
         editor_type: EditorType,
     },
+    #[error("Cannot open '{path}': {details}")]
+    InvalidPath { path: String, details: String },
 }
 
+/// Replaced in a custom editor command by the file to open; without it the
+/// file is appended
+pub const FILE_PLACEHOLDER: &str = "{file}";
+/// Replaced in a custom editor command by the line to open at, or 1
+pub const LINE_PLACEHOLDER: &str = "{line}";
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct EditorConfig {
     editor_type: EditorType,
@@ -96,18 +110,59 @@ impl EditorConfig {
         CommandBuilder::new(base_command)
     }
 
+    fn build_command(&self) -> Result<CommandParts, EditorOpenError> {
+        self.get_command()
+            .build_initial()
+            .map_err(|e| EditorOpenError::InvalidCommand {
+                details: e.to_string(),
+                editor_type: self.editor_type.clone(),
+            })
+    }
+
+    /// The command that opens `path`, at `line` when given. The program is
+    /// not yet resolved against PATH.
+    fn file_command(
+        &self,
+        path: &Path,
+        line: Option<u32>,
+    ) -> Result<CommandParts, EditorOpenError> {
+        let (program, mut args) = self.build_command()?.into_parts();
+        let path = path.to_string_lossy().into_owned();
+        match (&self.editor_type, line) {
+            (EditorType::Custom, _) => {
+                let line = line.unwrap_or(1).to_string();
+                let has_file = args.iter().any(|arg| arg.contains(FILE_PLACEHOLDER));
+                for arg in &mut args {
+                    *arg = arg
+                        .replace(FILE_PLACEHOLDER, &path)
+                        .replace(LINE_PLACEHOLDER, &line);
+                }
+                if !has_file {
+                    args.push(path);
+                }
+            }
+            (EditorType::VsCode | EditorType::Cursor | EditorType::Windsurf, Some(line)) => {
+                args.extend(["-g".to_string(), format!("{path}:{line}")]);
+            }
+            (EditorType::IntelliJ | EditorType::Xcode, Some(line)) => {
+                args.extend(["--line".to_string(), line.to_string(), path]);
+            }
+            (EditorType::Zed, Some(line)) => args.push(format!("{path}:{line}")),
+            (_, None) => args.push(path),
+        }
+        Ok(CommandParts::new(program, args))
+    }
+
     /// Resolve the editor command to an executable path and args.
     /// This is shared logic used by both check_availability() and spawn_local().
-    async fn resolve_command(&self) -> Result<(std::path::PathBuf, Vec<String>), EditorOpenError> {
-        let command_builder = self.get_command();
-        let command_parts =
-            command_builder
-                .build_initial()
-                .map_err(|e| EditorOpenError::InvalidCommand {
-                    details: e.to_string(),
-                    editor_type: self.editor_type.clone(),
-                })?;
+    async fn resolve_command(&self) -> Result<(PathBuf, Vec<String>), EditorOpenError> {
+        self.resolve(self.build_command()?).await
+    }
 
+    async fn resolve(
+        &self,
+        command_parts: CommandParts,
+    ) -> Result<(PathBuf, Vec<String>), EditorOpenError> {
         let (executable, args) = command_parts.into_resolved().await.map_err(|e| match e {
             ExecutorError::ExecutableNotFound { program } => EditorOpenError::ExecutableNotFound {
                 executable: program,
@@ -129,14 +184,36 @@ impl EditorConfig {
     }
 
     pub async fn open_file(&self, path: &Path) -> Result<Option<String>, EditorOpenError> {
-        if let Some(url) = self.remote_url(path) {
+        self.open_file_at(path, None).await
+    }
+
+    /// Open `path`, at `line` when the editor supports it. Returns a URL for
+    /// the browser to open instead when editing over SSH.
+    pub async fn open_file_at(
+        &self,
+        path: &Path,
+        line: Option<u32>,
+    ) -> Result<Option<String>, EditorOpenError> {
+        if let Some(url) = self.remote_url(path, line) {
             return Ok(Some(url));
         }
-        self.spawn_local(path).await?;
+        self.spawn_local_at(path, line).await?;
         Ok(None)
     }
 
-    fn remote_url(&self, path: &Path) -> Option<String> {
+    /// Open a file given relative to `project_root`, refusing paths that
+    /// resolve outside it, including through symlinks.
+    pub async fn open_in_editor(
+        &self,
+        project_root: &Path,
+        relative_path: &str,
+        line: Option<u32>,
+    ) -> Result<Option<String>, EditorOpenError> {
+        let path = resolve_within(project_root, relative_path)?;
+        self.open_file_at(&path, line).await
+    }
+
+    fn remote_url(&self, path: &Path, line: Option<u32>) -> Option<String> {
         let remote_host = self.remote_ssh_host.as_ref()?;
         let scheme = match self.editor_type {
             EditorType::VsCode => "vscode",
@@ -150,7 +227,11 @@ impl EditorConfig {
             .map(|u| format!("{u}@"))
             .unwrap_or_default();
         // files must contain a line and column number
-        let line_col = if path.is_file() { ":1:1" } else { "" };
+        let line_col = match line {
+            Some(line) => format!(":{line}:1"),
+            None if path.is_file() => ":1:1".to_string(),
+            None => String::new(),
+        };
         let path = path.to_string_lossy();
         Some(format!(
             "{scheme}://vscode-remote/ssh-remote+{user_part}{remote_host}{path}{line_col}"
@@ -158,10 +239,14 @@ impl EditorConfig {
     }
 
     pub async fn spawn_local(&self, path: &Path) -> Result<(), EditorOpenError> {
-        let (executable, args) = self.resolve_command().await?;
+        self.spawn_local_at(path, None).await
+    }
+
+    async fn spawn_local_at(&self, path: &Path, line: Option<u32>) -> Result<(), EditorOpenError> {
+        let (executable, args) = self.resolve(self.file_command(path, line)?).await?;
 
         let mut cmd = std::process::Command::new(&executable);
-        cmd.args(&args).arg(path);
+        cmd.args(&args);
         cmd.spawn().map_err(|e| EditorOpenError::LaunchFailed {
             executable: executable.to_string_lossy().into_owned(),
             details: e.to_string(),
@@ -185,3 +270,115 @@ impl EditorConfig {
         }
     }
 }
+
+fn resolve_within(root: &Path, relative_path: &str) -> Result<PathBuf, EditorOpenError> {
+    let invalid = |details: &str| EditorOpenError::InvalidPath {
+        path: relative_path.to_string(),
+        details: details.to_string(),
+    };
+    let root = std::fs::canonicalize(root).map_err(|_| invalid("project root not found"))?;
+    let path =
+        std::fs::canonicalize(root.join(relative_path)).map_err(|_| invalid("file not found"))?;
+    if path.starts_with(&root) {
+        Ok(path)
+    } else {
+        Err(invalid("path is outside the project"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(
+        editor_type: EditorType,
+        custom_command: Option<&str>,
+        line: Option<u32>,
+    ) -> CommandParts {
+        EditorConfig::new(editor_type, custom_command.map(str::to_string), None, None)
+            .file_command(Path::new("/repo/src/main.rs"), line)
+            .unwrap()
+    }
+
+    fn parts(program: &str, args: &[&str]) -> CommandParts {
+        CommandParts::new(
+            program.to_string(),
+            args.iter().map(|arg| arg.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn builds_go_to_line_commands_for_each_editor() {
+        let line = Some(42);
+        assert_eq!(
+            command(EditorType::VsCode, None, line),
+            parts("code", &["-g", "/repo/src/main.rs:42"])
+        );
+        assert_eq!(
+            command(EditorType::Cursor, None, line),
+            parts("cursor", &["-g", "/repo/src/main.rs:42"])
+        );
+        assert_eq!(
+            command(EditorType::Windsurf, None, line),
+            parts("windsurf", &["-g", "/repo/src/main.rs:42"])
+        );
+        assert_eq!(
+            command(EditorType::IntelliJ, None, line),
+            parts("idea", &["--line", "42", "/repo/src/main.rs"])
+        );
+        assert_eq!(
+            command(EditorType::Xcode, None, line),
+            parts("xed", &["--line", "42", "/repo/src/main.rs"])
+        );
+        assert_eq!(
+            command(EditorType::Zed, None, line),
+            parts("zed", &["/repo/src/main.rs:42"])
+        );
+    }
+
+    #[test]
+    fn opens_the_file_alone_without_a_line() {
+        assert_eq!(
+            command(EditorType::VsCode, None, None),
+            parts("code", &["/repo/src/main.rs"])
+        );
+        assert_eq!(
+            command(EditorType::IntelliJ, None, None),
+            parts("idea", &["/repo/src/main.rs"])
+        );
+    }
+
+    #[test]
+    fn fills_custom_command_placeholders() {
+        assert_eq!(
+            command(EditorType::Custom, Some("nvim +{line} {file}"), Some(7)),
+            parts("nvim", &["+7", "/repo/src/main.rs"])
+        );
+        assert_eq!(
+            command(EditorType::Custom, Some("nvim +{line} {file}"), None),
+            parts("nvim", &["+1", "/repo/src/main.rs"])
+        );
+        assert_eq!(
+            command(EditorType::Custom, Some("subl -w"), Some(7)),
+            parts("subl", &["-w", "/repo/src/main.rs"])
+        );
+    }
+
+    #[test]
+    fn rejects_paths_outside_the_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("project");
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "").unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "").unwrap();
+
+        let path = resolve_within(&root, "src/lib.rs").unwrap();
+        assert!(path.ends_with("src/lib.rs"));
+        for escape in ["../secret.txt", "/etc/passwd", "src/missing.rs"] {
+            assert!(matches!(
+                resolve_within(&root, escape),
+                Err(EditorOpenError::InvalidPath { .. })
+            ));
+        }
+    }
+}
@@ -1,8 +1,10 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf, str::FromStr};
 
+use executors::{command::validate_extra_args, executors::BaseCodingAgent};
 use thiserror::Error;
 
 pub mod editor;
+pub mod reload;
 mod versions;
 
 pub use editor::EditorOpenError;
@@ -17,6 +19,31 @@ pub enum ConfigError {
     ValidationError(String),
 }
 
+/// Why a config file could not be used
+#[derive(Debug, Error)]
+pub enum ConfigLoadError {
+    #[error("config is not valid JSON: {0}")]
+    InvalidJson(serde_json::Error),
+    #[error("config does not match the {version} schema: {source}")]
+    InvalidSchema {
+        version: String,
+        source: serde_json::Error,
+    },
+    #[error("config version {0} is newer than this build supports")]
+    UnsupportedVersion(String),
+}
+
+impl ConfigLoadError {
+    /// The step that failed, e.g. `v5-schema`
+    pub fn step(&self) -> String {
+        match self {
+            ConfigLoadError::InvalidJson(_) => "json".to_string(),
+            ConfigLoadError::InvalidSchema { version, .. } => format!("{version}-schema"),
+            ConfigLoadError::UnsupportedVersion(version) => format!("{version}-unsupported"),
+        }
+    }
+}
+
 pub type Config = versions::v8::Config;
 pub type NotificationConfig = versions::v8::NotificationConfig;
 pub type EditorConfig = versions::v8::EditorConfig;
@@ -27,10 +54,30 @@ pub type GitHubConfig = versions::v8::GitHubConfig;
 pub type UiLanguage = versions::v8::UiLanguage;
 pub type ShowcaseState = versions::v8::ShowcaseState;
 
-/// Will always return config, trying old schemas or eventually returning default
+/// Will always return config, migrating old schemas or eventually returning
+/// default. A file that cannot be used is copied aside first, to
+/// `config.json.<step>.bak`, so saving the default does not lose it.
 pub async fn load_config_from_file(config_path: &PathBuf) -> Config {
     match std::fs::read_to_string(config_path) {
-        Ok(raw_config) => Config::from(raw_config),
+        Ok(raw_config) => match Config::load(&raw_config) {
+            Ok(config) => config,
+            Err(e) => {
+                let backup_path = config_path.with_extension(format!("json.{}.bak", e.step()));
+                match std::fs::write(&backup_path, &raw_config) {
+                    Ok(()) => tracing::error!(
+                        "Failed to load config: {}. Using defaults; the old file was saved to {}",
+                        e,
+                        backup_path.display()
+                    ),
+                    Err(write_err) => tracing::error!(
+                        "Failed to load config: {}. Using defaults; backing up the old file also failed: {}",
+                        e,
+                        write_err
+                    ),
+                }
+                Config::default()
+            }
+        },
         Err(_) => {
             tracing::info!("No config file found, creating one");
             Config::default()
@@ -44,6 +91,30 @@ pub async fn save_config_to_file(
     config_path: &PathBuf,
 ) -> Result<(), ConfigError> {
     let raw_config = serde_json::to_string_pretty(config)?;
+    reload::record_self_write(&raw_config);
     std::fs::write(config_path, raw_config)?;
     Ok(())
 }
+
+/// Check `config.executor_args` and key it by executor. Keys are matched
+/// case-insensitively, with `-` accepted for `_`.
+pub fn resolve_executor_args(
+    config: &Config,
+) -> Result<HashMap<BaseCodingAgent, Vec<String>>, ConfigError> {
+    config
+        .executor_args
+        .iter()
+        .map(|(key, args)| {
+            let executor = BaseCodingAgent::from_str(&key.trim().to_uppercase().replace('-', "_"))
+                .map_err(|_| {
+                    ConfigError::ValidationError(format!(
+                        "Unknown executor in executor_args: {key}"
+                    ))
+                })?;
+            validate_extra_args(args).map_err(|e| {
+                ConfigError::ValidationError(format!("Invalid arguments for {key}: {e}"))
+            })?;
+            Ok((executor, args.clone()))
+        })
+        .collect()
+}
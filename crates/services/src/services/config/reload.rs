@@ -0,0 +1,233 @@
+//! Picks up edits made to the config file while the server is running, e.g.
+//! by hand or by a dotfiles sync, instead of overwriting them on the next save.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorConfigs;
+use futures::{SinkExt, StreamExt, channel::mpsc::channel};
+use notify::RecursiveMode;
+use notify_debouncer_full::{DebounceEventResult, new_debouncer};
+use serde::Serialize;
+use tokio::{
+    sync::{RwLock, broadcast},
+    task::JoinHandle,
+};
+use ts_rs::TS;
+
+use crate::services::{
+    config::{Config, resolve_executor_args},
+    filesystem_watcher::FilesystemWatcherError,
+};
+
+/// Hash of the last content the app itself wrote, so the watcher can skip it
+static LAST_SAVED: Mutex<Option<u64>> = Mutex::new(None);
+
+fn content_hash(raw_config: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    raw_config.hash(&mut hasher);
+    hasher.finish()
+}
+
+pub(super) fn record_self_write(raw_config: &str) {
+    *LAST_SAVED.lock().unwrap() = Some(content_hash(raw_config));
+}
+
+fn is_self_write(raw_config: &str) -> bool {
+    *LAST_SAVED.lock().unwrap() == Some(content_hash(raw_config))
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct ConfigReloadStatus {
+    /// When edits made on disk were last applied
+    pub last_reload_at: Option<DateTime<Utc>>,
+    /// Why the file on disk was last rejected; cleared by the next good reload
+    pub last_error: Option<String>,
+}
+
+/// Sent to subscribers after an edit on disk has been applied
+#[derive(Debug, Clone)]
+pub struct ConfigChange {
+    pub old: Config,
+    pub new: Config,
+    /// Top-level keys whose values differ
+    pub changed: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct ConfigReloader {
+    config: Arc<RwLock<Config>>,
+    path: PathBuf,
+    status: Arc<RwLock<ConfigReloadStatus>>,
+    changes: broadcast::Sender<Arc<ConfigChange>>,
+}
+
+impl ConfigReloader {
+    pub fn new(config: Arc<RwLock<Config>>, path: PathBuf) -> Self {
+        let (changes, _) = broadcast::channel(16);
+        Self {
+            config,
+            path,
+            status: Arc::new(RwLock::new(ConfigReloadStatus::default())),
+            changes,
+        }
+    }
+
+    pub async fn status(&self) -> ConfigReloadStatus {
+        self.status.read().await.clone()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<ConfigChange>> {
+        self.changes.subscribe()
+    }
+
+    /// Re-read the file and apply it if it differs from the running config.
+    /// Files the app just wrote, and files that fail to load, are left alone.
+    pub async fn reload(&self) -> Option<Arc<ConfigChange>> {
+        let raw_config = match tokio::fs::read_to_string(&self.path).await {
+            Ok(raw_config) => raw_config,
+            // Mid-replace by an editor; the create event follows
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                self.record_error(format!("Failed to read config: {e}"))
+                    .await;
+                return None;
+            }
+        };
+        if is_self_write(&raw_config) {
+            return None;
+        }
+
+        let new = match Config::load(&raw_config) {
+            Ok(new) => new,
+            Err(e) => {
+                self.record_error(e.to_string()).await;
+                return None;
+            }
+        };
+
+        let mut current = self.config.write().await;
+        let changed = changed_keys(&current, &new);
+        if changed.is_empty() {
+            return None;
+        }
+        let old = std::mem::replace(&mut *current, new.clone());
+        drop(current);
+
+        match resolve_executor_args(&new) {
+            Ok(extra_args) => ExecutorConfigs::set_extra_args(extra_args),
+            Err(e) => tracing::warn!("Ignoring executor_args from reloaded config: {}", e),
+        }
+        *self.status.write().await = ConfigReloadStatus {
+            last_reload_at: Some(Utc::now()),
+            last_error: None,
+        };
+        tracing::info!("Reloaded config from disk; changed: {}", changed.join(", "));
+
+        let change = Arc::new(ConfigChange { old, new, changed });
+        let _ = self.changes.send(change.clone());
+        Some(change)
+    }
+
+    async fn record_error(&self, error: String) {
+        tracing::warn!("Keeping the running config: {}", error);
+        self.status.write().await.last_error = Some(error);
+    }
+
+    /// Watch the config file and reload it on change. The directory is
+    /// watched rather than the file, since editors often save by replacing
+    /// the file.
+    pub fn spawn(self) -> Result<JoinHandle<()>, FilesystemWatcherError> {
+        let dir =
+            self.path.parent().map(Path::to_path_buf).ok_or_else(|| {
+                FilesystemWatcherError::InvalidPath(self.path.display().to_string())
+            })?;
+        let file_name = self.path.file_name().map(|name| name.to_os_string());
+
+        let (mut tx, mut rx) = channel(16);
+        let mut debouncer = new_debouncer(
+            Duration::from_millis(300),
+            None,
+            move |result: DebounceEventResult| {
+                futures::executor::block_on(async {
+                    let _ = tx.send(result).await;
+                });
+            },
+        )?;
+        debouncer.watch(&dir, RecursiveMode::NonRecursive)?;
+
+        Ok(tokio::spawn(async move {
+            // Dropping the debouncer stops the watch
+            let _debouncer = debouncer;
+            while let Some(result) = rx.next().await {
+                let touches_config = match result {
+                    Ok(events) => events.iter().any(|event| {
+                        event
+                            .paths
+                            .iter()
+                            .any(|path| path.file_name() == file_name.as_deref())
+                    }),
+                    Err(errors) => {
+                        tracing::warn!("Config watcher error: {:?}", errors);
+                        false
+                    }
+                };
+                if touches_config {
+                    self.reload().await;
+                }
+            }
+        }))
+    }
+}
+
+fn changed_keys(old: &Config, new: &Config) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+    new.iter()
+        .filter(|(key, value)| old.get(key.as_str()) != Some(value))
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn applies_external_edits_but_not_its_own_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let config = Arc::new(RwLock::new(Config::default()));
+        let reloader = ConfigReloader::new(config.clone(), path.clone());
+
+        let mut edited = Config::default();
+        edited.git_branch_prefix = "me".to_string();
+        let raw = serde_json::to_string_pretty(&edited).unwrap();
+
+        // Written by the app: skipped even though it differs from memory
+        record_self_write(&raw);
+        std::fs::write(&path, &raw).unwrap();
+        assert!(reloader.reload().await.is_none());
+
+        // Written by hand
+        std::fs::write(&path, raw.replace("\"me\"", "\"you\"")).unwrap();
+        let change = reloader.reload().await.unwrap();
+        assert_eq!(change.changed, ["git_branch_prefix"]);
+        assert_eq!(config.read().await.git_branch_prefix, "you");
+        assert!(reloader.status().await.last_reload_at.is_some());
+
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(reloader.reload().await.is_none());
+        assert_eq!(config.read().await.git_branch_prefix, "you");
+        assert!(reloader.status().await.last_error.is_some());
+    }
+}
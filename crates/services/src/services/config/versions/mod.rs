@@ -1,3 +1,7 @@
+use serde::de::DeserializeOwned;
+
+use crate::services::config::ConfigLoadError;
+
 pub(super) mod v1;
 pub(super) mod v2;
 pub(super) mod v3;
@@ -6,3 +10,30 @@ pub(super) mod v5;
 pub(super) mod v6;
 pub(super) mod v7;
 pub(super) mod v8;
+
+/// Check that a config file parses as the schema of the version it declares,
+/// before any migration runs. Files without `config_version` predate v2.
+pub(super) fn check_declared_schema(
+    version: &str,
+    raw_config: &str,
+) -> Result<(), ConfigLoadError> {
+    fn parses<T: DeserializeOwned>(raw_config: &str) -> serde_json::Result<()> {
+        serde_json::from_str::<T>(raw_config).map(|_| ())
+    }
+
+    let result = match version {
+        "v1" => parses::<v1::Config>(raw_config),
+        "v2" => parses::<v2::Config>(raw_config),
+        "v3" => parses::<v3::Config>(raw_config),
+        "v4" => parses::<v4::Config>(raw_config),
+        "v5" => parses::<v5::Config>(raw_config),
+        "v6" => parses::<v6::Config>(raw_config),
+        "v7" => parses::<v7::Config>(raw_config),
+        "v8" => parses::<v8::Config>(raw_config),
+        other => return Err(ConfigLoadError::UnsupportedVersion(other.to_string())),
+    };
+    result.map_err(|source| ConfigLoadError::InvalidSchema {
+        version: version.to_string(),
+        source,
+    })
+}
@@ -151,11 +151,18 @@ impl From<v1::GitHubConfig> for GitHubConfig {
     }
 }
 
+fn default_true() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct NotificationConfig {
     pub sound_enabled: bool,
     pub push_enabled: bool,
     pub sound_file: SoundFile,
+    /// Notify when someone else assigns a shared task to you
+    #[serde(default = "default_true")]
+    pub shared_task_assigned: bool,
 }
 
 impl From<v1::Config> for NotificationConfig {
@@ -164,6 +171,7 @@ impl From<v1::Config> for NotificationConfig {
             sound_enabled: old.sound_alerts,
             push_enabled: old.push_notifications,
             sound_file: SoundFile::from(old.sound_file), // Now SCREAMING_SNAKE_CASE
+            shared_task_assigned: true,
         }
     }
 }
@@ -174,6 +182,7 @@ impl Default for NotificationConfig {
             sound_enabled: true,
             push_enabled: true,
             sound_file: SoundFile::CowMooing,
+            shared_task_assigned: true,
         }
     }
 }
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use anyhow::Error;
 use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
 use serde::{Deserialize, Serialize};
@@ -7,12 +9,39 @@ pub use v7::{
     ThemeMode, UiLanguage,
 };
 
-use crate::services::config::versions::v7;
+use crate::services::{
+    config::{
+        ConfigLoadError,
+        versions::{check_declared_schema, v7},
+    },
+    disk_usage::DEFAULT_WORKTREE_DISK_WARNING_GB,
+    log_batch::LogBatchConfig,
+    privacy::PrivacyMode,
+    stall::DEFAULT_STALL_TIMEOUT_MINUTES,
+    task_trash::DEFAULT_TASK_TRASH_RETENTION_DAYS,
+    worktree_cleanup::DEFAULT_WORKTREE_RETENTION_HOURS,
+};
 
 fn default_git_branch_prefix() -> String {
     "vk".to_string()
 }
 
+fn default_executor_stall_timeout_minutes() -> u32 {
+    DEFAULT_STALL_TIMEOUT_MINUTES
+}
+
+fn default_worktree_retention_hours() -> u32 {
+    DEFAULT_WORKTREE_RETENTION_HOURS
+}
+
+fn default_worktree_disk_warning_gb() -> u32 {
+    DEFAULT_WORKTREE_DISK_WARNING_GB
+}
+
+fn default_task_trash_retention_days() -> u32 {
+    DEFAULT_TASK_TRASH_RETENTION_DAYS
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, TS)]
 pub struct Config {
     pub config_version: String,
@@ -33,6 +62,40 @@ pub struct Config {
     pub git_branch_prefix: String,
     #[serde(default)]
     pub showcases: ShowcaseState,
+    /// Redact every supported response regardless of the request, for kiosk
+    /// and demo machines
+    #[serde(default)]
+    pub privacy_mode: Option<PrivacyMode>,
+    /// How process output is batched before being written to the database
+    #[serde(default)]
+    pub log_batching: LogBatchConfig,
+    /// Minutes a coding agent or script may go without output before it is
+    /// reported as stalled. 0 disables stall detection.
+    #[serde(default = "default_executor_stall_timeout_minutes")]
+    pub executor_stall_timeout_minutes: u32,
+    /// Attempts allowed to run a coding agent at once across all projects;
+    /// further attempts are queued. 0 means no limit.
+    #[serde(default)]
+    pub max_concurrent_executions: u32,
+    /// Hours an attempt must sit idle before its worktree is cleaned up
+    #[serde(default = "default_worktree_retention_hours")]
+    pub worktree_retention_hours: u32,
+    /// Combined size of a project's worktrees, in GB, above which a
+    /// notification is sent. 0 disables the warning.
+    #[serde(default = "default_worktree_disk_warning_gb")]
+    pub worktree_disk_warning_gb: u32,
+    /// Days a deleted task stays in the trash before it is removed for good,
+    /// with its attempts and worktrees. 0 keeps deleted tasks until restored.
+    #[serde(default = "default_task_trash_retention_days")]
+    pub task_trash_retention_days: u32,
+    /// Extra CLI arguments appended to every command of an executor, keyed by
+    /// executor type (e.g. `CLAUDE_CODE`)
+    #[serde(default)]
+    pub executor_args: HashMap<String, Vec<String>>,
+    /// Hosts of self-hosted GitLab instances, e.g. `gitlab.example.com`.
+    /// gitlab.com is always recognised.
+    #[serde(default)]
+    pub gitlab_hosts: Vec<String>,
 }
 
 impl Config {
@@ -56,6 +119,15 @@ impl Config {
             language: old_config.language,
             git_branch_prefix: old_config.git_branch_prefix,
             showcases: old_config.showcases,
+            privacy_mode: None,
+            log_batching: LogBatchConfig::default(),
+            executor_stall_timeout_minutes: DEFAULT_STALL_TIMEOUT_MINUTES,
+            max_concurrent_executions: 0,
+            worktree_retention_hours: DEFAULT_WORKTREE_RETENTION_HOURS,
+            worktree_disk_warning_gb: DEFAULT_WORKTREE_DISK_WARNING_GB,
+            task_trash_retention_days: DEFAULT_TASK_TRASH_RETENTION_DAYS,
+            executor_args: HashMap::new(),
+            gitlab_hosts: Vec::new(),
         }
     }
 
@@ -63,26 +135,61 @@ impl Config {
         let old_config = v7::Config::from(raw_config.to_string());
         Ok(Self::from_v7_config(old_config))
     }
-}
 
-impl From<String> for Config {
-    fn from(raw_config: String) -> Self {
-        if let Ok(config) = serde_json::from_str::<Config>(&raw_config)
-            && config.config_version == "v8"
-        {
-            return config;
+    /// Parse a config file of any version, migrating it to v8. Unlike the
+    /// `From<String>` fallback, a file that does not match the schema of its
+    /// declared version is an error rather than silently becoming defaults.
+    pub fn load(raw_config: &str) -> Result<Self, ConfigLoadError> {
+        let value: serde_json::Value =
+            serde_json::from_str(raw_config).map_err(ConfigLoadError::InvalidJson)?;
+        let version = value
+            .get("config_version")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("v1");
+        check_declared_schema(version, raw_config)?;
+
+        if version != "v8" {
+            // The declared schema parsed, so every step up from it succeeds
+            let config = Self::from_v7_config(v7::Config::from(raw_config.to_string()));
+            tracing::info!("Config upgraded from {} to v8", version);
+            return Ok(config);
         }
 
-        match Self::from_previous_version(&raw_config) {
-            Ok(config) => {
-                tracing::info!("Config upgraded to v8");
-                config
-            }
-            Err(e) => {
-                tracing::warn!("Config migration failed: {}, using default", e);
-                Self::default()
+        let config = serde_json::from_value::<Config>(value.clone()).map_err(|source| {
+            ConfigLoadError::InvalidSchema {
+                version: version.to_string(),
+                source,
             }
+        })?;
+        let unknown = unknown_keys(&value, &config);
+        if !unknown.is_empty() {
+            tracing::warn!(
+                "Ignoring unknown config keys (misspelt or from a newer version?): {}",
+                unknown.join(", ")
+            );
         }
+        Ok(config)
+    }
+}
+
+/// Top-level keys in the file that no config field reads
+fn unknown_keys(raw: &serde_json::Value, config: &Config) -> Vec<String> {
+    let Some(raw) = raw.as_object() else {
+        return Vec::new();
+    };
+    let known = serde_json::to_value(config).unwrap_or_default();
+    raw.keys()
+        .filter(|key| known.get(key.as_str()).is_none())
+        .cloned()
+        .collect()
+}
+
+impl From<String> for Config {
+    fn from(raw_config: String) -> Self {
+        Self::load(&raw_config).unwrap_or_else(|e| {
+            tracing::warn!("Config could not be loaded: {}, using default", e);
+            Self::default()
+        })
     }
 }
 
@@ -104,6 +211,15 @@ impl Default for Config {
             language: UiLanguage::default(),
             git_branch_prefix: default_git_branch_prefix(),
             showcases: ShowcaseState::default(),
+            privacy_mode: None,
+            log_batching: LogBatchConfig::default(),
+            executor_stall_timeout_minutes: DEFAULT_STALL_TIMEOUT_MINUTES,
+            max_concurrent_executions: 0,
+            worktree_retention_hours: DEFAULT_WORKTREE_RETENTION_HOURS,
+            worktree_disk_warning_gb: DEFAULT_WORKTREE_DISK_WARNING_GB,
+            task_trash_retention_days: DEFAULT_TASK_TRASH_RETENTION_DAYS,
+            executor_args: HashMap::new(),
+            gitlab_hosts: Vec::new(),
         }
     }
 }
@@ -1,38 +1,64 @@
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
 };
 
 use anyhow::{Error as AnyhowError, anyhow};
 use async_trait::async_trait;
+use chrono::Utc;
 use db::{
     DBService,
     models::{
+        attempt_event::{AttemptEvent, AttemptEventType},
         execution_process::{
             CreateExecutionProcess, ExecutionContext, ExecutionProcess, ExecutionProcessRunReason,
             ExecutionProcessStatus,
         },
         execution_process_logs::ExecutionProcessLogs,
+        execution_process_usage::ExecutionProcessUsage,
         executor_session::{CreateExecutorSession, ExecutorSession},
+        executor_session_entry::ExecutorSessionEntry,
+        project::Project,
+        queued_attempt::QueuedAttempt,
+        queued_followup::QueuedFollowUp,
         task::{Task, TaskStatus},
-        task_attempt::{TaskAttempt, TaskAttemptError},
+        task_attempt::{TaskAttempt, TaskAttemptError, VerifyStatus},
+        task_attempt_plan::TaskAttemptPlan,
+        task_dependency::TaskDependency,
     },
 };
 use executors::{
     actions::{
         ExecutorAction, ExecutorActionType,
+        coding_agent_follow_up::CodingAgentFollowUpRequest,
         coding_agent_initial::CodingAgentInitialRequest,
         script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+        setup_steps::normalize_setup_step_logs,
     },
     executors::{ExecutorError, StandardCodingAgentExecutor},
-    logs::{NormalizedEntry, NormalizedEntryError, NormalizedEntryType, utils::ConversationPatch},
+    logs::{
+        NormalizedConversation, NormalizedEntry, NormalizedEntryError, NormalizedEntryType,
+        session_id::SessionIdScanner,
+        utils::{
+            ConversationPatch, EntryIndexProvider, mcp::mcp_degraded,
+            patch::extract_normalized_entry_from_patch,
+        },
+    },
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
 use futures::{StreamExt, future};
-use sqlx::Error as SqlxError;
+use sqlx::{Error as SqlxError, SqlitePool};
 use thiserror::Error;
-use tokio::{sync::RwLock, task::JoinHandle};
+use tokio::{
+    sync::{Mutex, RwLock},
+    task::JoinHandle,
+};
+use tokio_stream::wrappers::BroadcastStream;
 use utils::{
     log_msg::LogMsg,
     msg_store::MsgStore,
@@ -42,14 +68,82 @@ use uuid::Uuid;
 
 use crate::services::{
     config::Config,
+    conversation, conversation_compare,
+    dev_server::{
+        self, DevServerOutputMatcher, DevServerRegistry, DevServerState, DevServerStatus,
+    },
     git::{GitService, GitServiceError},
     image::ImageService,
+    log_batch::{LogBatch, LogBatchConfig},
     notification::NotificationService,
+    session_entries,
     share::SharePublisher,
+    shutdown::{ShutdownState, ShutdownSummary},
+    verify,
     worktree_manager::WorktreeError,
 };
 pub type ContainerRef = String;
 
+/// Outcome of asking to start an attempt under the concurrency limits.
+#[derive(Debug)]
+pub enum AttemptStart {
+    Started(ExecutionProcess),
+    /// Waiting in the execution queue
+    Queued,
+}
+
+/// A queued attempt stays in the queue while its task has unfinished
+/// dependencies, unless it was started with `override`.
+async fn waits_for_dependencies(
+    pool: &SqlitePool,
+    queued: &QueuedAttempt,
+) -> Result<bool, SqlxError> {
+    if queued.ignore_dependencies {
+        return Ok(false);
+    }
+    Ok(!TaskDependency::find_blocking(pool, queued.task_id)
+        .await?
+        .is_empty())
+}
+
+/// Record that `process` finished with `status`. Does nothing for a process
+/// that is still running.
+pub async fn record_process_event(
+    pool: &SqlitePool,
+    process: &ExecutionProcess,
+    status: ExecutionProcessStatus,
+    exit_code: Option<i64>,
+) {
+    let event_type = match status {
+        ExecutionProcessStatus::Running => return,
+        ExecutionProcessStatus::Completed => AttemptEventType::ProcessCompleted,
+        ExecutionProcessStatus::Failed => AttemptEventType::ProcessFailed,
+        ExecutionProcessStatus::Killed => AttemptEventType::ProcessKilled,
+    };
+    AttemptEvent::record(
+        pool,
+        process.task_attempt_id,
+        event_type,
+        serde_json::json!({
+            "execution_process_id": process.id,
+            "run_reason": process.run_reason,
+            "exit_code": exit_code,
+        }),
+    )
+    .await;
+}
+
+const NORMALIZED_SNAPSHOT_IDLE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Stored output budgeted per requested line when tailing a finished
+/// process's logs from the database. Each stored line is a JSON-wrapped
+/// `LogMsg`, so this leaves room for long lines.
+const TAIL_BYTES_PER_LINE: usize = 1024;
+
+/// How long a stopped process gets to exit after being interrupted, unless
+/// the caller asks for longer.
+pub const DEFAULT_STOP_GRACE: Duration = Duration::from_secs(2);
+
 #[derive(Debug, Error)]
 pub enum ContainerError {
     #[error(transparent)]
@@ -66,6 +160,8 @@ pub enum ContainerError {
     KillFailed(std::io::Error),
     #[error(transparent)]
     TaskAttemptError(#[from] TaskAttemptError),
+    #[error("Server is shutting down")]
+    ShuttingDown,
     #[error(transparent)]
     Other(#[from] AnyhowError), // Catches any unclassified errors
 }
@@ -82,9 +178,56 @@ pub trait ContainerService {
 
     fn task_attempt_to_current_dir(&self, task_attempt: &TaskAttempt) -> PathBuf;
 
+    fn dev_servers(&self) -> &DevServerRegistry;
+
+    fn shutdown_state(&self) -> &ShutdownState;
+
+    /// OS process id of a running execution process
+    async fn execution_pid(&self, execution_process_id: &Uuid) -> Option<u32>;
+
     async fn create(&self, task_attempt: &TaskAttempt) -> Result<ContainerRef, ContainerError>;
 
-    async fn kill_all_running_processes(&self) -> Result<(), ContainerError>;
+    /// Stop starting processes, stop the running ones, giving each `grace`
+    /// to wind down, and wait up to `grace` more for their output to reach
+    /// the database.
+    async fn shutdown(&self, grace: Duration) -> ShutdownSummary {
+        let mut summary = ShutdownSummary::default();
+        if !self.shutdown_state().begin() {
+            return summary;
+        }
+
+        let running = match ExecutionProcess::find_running(&self.db().pool).await {
+            Ok(running) => running,
+            Err(e) => {
+                tracing::error!("Failed to load running execution processes: {}", e);
+                Vec::new()
+            }
+        };
+        tracing::info!("Stopping {} running execution processes", running.len());
+        let results = future::join_all(running.iter().map(|process| {
+            self.stop_execution_with_grace(process, ExecutionProcessStatus::Killed, grace)
+        }))
+        .await;
+        for (process, result) in running.iter().zip(results) {
+            match result {
+                Ok(()) => summary.processes_stopped += 1,
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to cleanly stop execution process {}: {}",
+                        process.id,
+                        e
+                    );
+                    summary.processes_failed += 1;
+                }
+            }
+        }
+
+        summary.logs_flushed = self.shutdown_state().wait_for_log_writers(grace).await;
+        if !summary.logs_flushed {
+            tracing::warn!("Some execution output was still being written at shutdown");
+        }
+        summary
+    }
 
     async fn delete(&self, task_attempt: &TaskAttempt) -> Result<(), ContainerError> {
         self.try_stop(task_attempt).await;
@@ -125,12 +268,18 @@ pub trait ContainerService {
     /// - The next action is None (no follow-up actions)
     /// - The run reason is not DevServer
     fn should_finalize(&self, ctx: &ExecutionContext) -> bool {
-        // Always finalize failed or killed executions
+        // Always finalize failed or killed executions, except a failed verify
+        // script: cleanup still runs after it and finalizes instead
         if matches!(
             ctx.execution_process.status,
             ExecutionProcessStatus::Failed | ExecutionProcessStatus::Killed
         ) {
-            return true;
+            return !(ctx.execution_process.status == ExecutionProcessStatus::Failed
+                && ctx.execution_process.run_reason == ExecutionProcessRunReason::VerifyScript
+                && ctx
+                    .execution_process
+                    .executor_action()
+                    .is_ok_and(|action| action.next_action.is_some()));
         }
         // Otherwise, finalize only if no next action and not a dev server
         ctx.execution_process
@@ -196,6 +345,13 @@ pub trait ContainerService {
                 );
                 continue;
             }
+            record_process_event(
+                &self.db().pool,
+                &process,
+                ExecutionProcessStatus::Failed,
+                None,
+            )
+            .await;
             // Capture after-head commit OID (best-effort)
             if let Ok(Some(task_attempt)) =
                 TaskAttempt::find_by_id(&self.db().pool, process.task_attempt_id).await
@@ -219,6 +375,7 @@ pub trait ContainerService {
                 ExecutionProcessRunReason::CodingAgent
                     | ExecutionProcessRunReason::SetupScript
                     | ExecutionProcessRunReason::CleanupScript
+                    | ExecutionProcessRunReason::VerifyScript
             ) && let Ok(Some(task_attempt)) =
                 TaskAttempt::find_by_id(&self.db().pool, process.task_attempt_id).await
                 && let Ok(Some(task)) = task_attempt.parent_task(&self.db().pool).await
@@ -302,12 +459,27 @@ pub trait ContainerService {
                     script,
                     language: ScriptRequestLanguage::Bash,
                     context: ScriptContext::CleanupScript,
+                    timeout_secs: None,
+                    stall_timeout_secs: None,
                 }),
                 None,
             ))
         })
     }
 
+    /// What runs after a coding agent request: the project's verify script,
+    /// if configured, then its cleanup script.
+    fn post_agent_action(&self, project: &Project) -> Option<Box<ExecutorAction>> {
+        let cleanup_action = self.cleanup_action(project.cleanup_script.clone());
+        match verify::verify_request(project) {
+            Some(request) => Some(Box::new(ExecutorAction::new(
+                ExecutorActionType::ScriptRequest(request),
+                cleanup_action,
+            ))),
+            None => cleanup_action,
+        }
+    }
+
     async fn try_stop(&self, task_attempt: &TaskAttempt) {
         // stop all execution processes for this attempt
         if let Ok(processes) =
@@ -350,6 +522,18 @@ pub trait ContainerService {
         &self,
         execution_process: &ExecutionProcess,
         status: ExecutionProcessStatus,
+    ) -> Result<(), ContainerError> {
+        self.stop_execution_with_grace(execution_process, status, DEFAULT_STOP_GRACE)
+            .await
+    }
+
+    /// Interrupt the process, wait up to `grace` for it to exit on its own,
+    /// then kill it. Output it produced on the way out is kept.
+    async fn stop_execution_with_grace(
+        &self,
+        execution_process: &ExecutionProcess,
+        status: ExecutionProcessStatus,
+        grace: Duration,
     ) -> Result<(), ContainerError>;
 
     async fn try_commit_changes(&self, ctx: &ExecutionContext) -> Result<bool, ContainerError>;
@@ -438,6 +622,72 @@ pub trait ContainerService {
         }
     }
 
+    /// Last `tail` lines of output followed by live output, ending with
+    /// `Finished`. Output is passed through untouched, ANSI escapes included.
+    async fn stream_log_tail(
+        &self,
+        id: &Uuid,
+        tail: usize,
+    ) -> Option<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>> {
+        if let Some(store) = self.get_msg_store_by_id(id).await {
+            let (history, rx) = (store.get_history(), store.get_receiver());
+            let finished = history.iter().any(|msg| matches!(msg, LogMsg::Finished));
+            let head = futures::stream::iter(dev_server::tail_lines(history, tail));
+            let done = futures::stream::once(async { LogMsg::Finished });
+
+            if finished {
+                return Some(head.chain(done).map(Ok::<_, std::io::Error>).boxed());
+            }
+
+            let live = BroadcastStream::new(rx)
+                .filter_map(|res| async move { res.ok() })
+                .take_while(|msg| future::ready(!matches!(msg, LogMsg::Finished)))
+                .filter(|msg| {
+                    future::ready(matches!(msg, LogMsg::Stdout(..) | LogMsg::Stderr(..)))
+                });
+
+            Some(
+                head.chain(live)
+                    .chain(done)
+                    .map(Ok::<_, std::io::Error>)
+                    .boxed(),
+            )
+        } else {
+            // Process is no longer running, tail whatever was persisted. Only
+            // the newest records are loaded, not the whole history.
+            let max_bytes =
+                i64::try_from(tail.saturating_mul(TAIL_BYTES_PER_LINE)).unwrap_or(i64::MAX);
+            let records = match ExecutionProcessLogs::find_tail_by_execution_id(
+                &self.db().pool,
+                *id,
+                max_bytes,
+            )
+            .await
+            {
+                Ok(records) if !records.is_empty() => records,
+                Ok(_) => return None,
+                Err(e) => {
+                    tracing::error!("Failed to fetch log tail for execution {}: {}", id, e);
+                    return None;
+                }
+            };
+            let history = match ExecutionProcessLogs::parse_logs(&records) {
+                Ok(history) => history,
+                Err(e) => {
+                    tracing::error!("Failed to parse log tail for execution {}: {}", id, e);
+                    return None;
+                }
+            };
+
+            Some(
+                futures::stream::iter(dev_server::tail_lines(history, tail))
+                    .chain(futures::stream::once(async { LogMsg::Finished }))
+                    .map(Ok::<_, std::io::Error>)
+                    .boxed(),
+            )
+        }
+    }
+
     async fn stream_normalized_logs(
         &self,
         id: &Uuid,
@@ -454,6 +704,44 @@ pub trait ContainerService {
                     .boxed(),
             )
         } else {
+            // Entries persisted while the process ran outlast its raw logs
+            let pool = &self.db().pool;
+            let persisted = ExecutorSessionEntry::find_by_execution_process_id(pool, *id)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!(
+                        "Failed to load conversation entries for execution {}: {}",
+                        id,
+                        e
+                    );
+                    Vec::new()
+                });
+            if !persisted.is_empty() {
+                let killed = matches!(
+                    ExecutionProcess::find_by_id(pool, *id).await,
+                    Ok(Some(process)) if process.status == ExecutionProcessStatus::Killed
+                );
+                let stopped = killed.then(|| conversation::stopped_entry(*id));
+                let patches: Vec<_> = persisted
+                    .into_iter()
+                    .map(|persisted| persisted.entry)
+                    .chain(stopped)
+                    .enumerate()
+                    .map(|(index, entry)| {
+                        Ok(LogMsg::JsonPatch(ConversationPatch::add_normalized_entry(
+                            index, entry,
+                        )))
+                    })
+                    .collect();
+                return Some(
+                    futures::stream::iter(patches)
+                        .chain(futures::stream::once(async {
+                            Ok::<_, std::io::Error>(LogMsg::Finished)
+                        }))
+                        .boxed(),
+                );
+            }
+
             // Fallback: load from DB and normalize
             let log_records =
                 match ExecutionProcessLogs::find_by_execution_id(&self.db().pool, *id).await {
@@ -547,18 +835,43 @@ pub trait ContainerService {
                         .get_coding_agent_or_default(&request.executor_profile_id);
                     executor.normalize_logs(temp_store.clone(), &current_dir);
                 }
-                _ => {
-                    tracing::debug!(
-                        "Executor action doesn't support log normalization: {:?}",
-                        process.executor_action()
+                ExecutorActionType::ScriptRequest(request) => {
+                    let Some(steps) = request.setup_steps() else {
+                        tracing::debug!(
+                            "Executor action doesn't support log normalization: {:?}",
+                            process.executor_action()
+                        );
+                        return None;
+                    };
+                    normalize_setup_step_logs(
+                        temp_store.clone(),
+                        steps,
+                        EntryIndexProvider::start_from(&temp_store),
                     );
-                    return None;
                 }
             }
+            // A stopped run ends with a note after whatever it got to
+            let stopped = (process.status == ExecutionProcessStatus::Killed)
+                .then(|| conversation::stopped_entry(process.id));
+            let next_index = Arc::new(AtomicUsize::new(0));
+            let seen_index = next_index.clone();
             Some(
                 temp_store
                     .history_plus_stream()
                     .filter(|msg| future::ready(matches!(msg, Ok(LogMsg::JsonPatch(..)))))
+                    .inspect(move |msg| {
+                        if let Ok(LogMsg::JsonPatch(patch)) = msg
+                            && let Some((index, _)) = extract_normalized_entry_from_patch(patch)
+                        {
+                            seen_index.fetch_max(index + 1, Ordering::Relaxed);
+                        }
+                    })
+                    .chain(futures::stream::iter(stopped).map(move |entry| {
+                        Ok(LogMsg::JsonPatch(ConversationPatch::add_normalized_entry(
+                            next_index.load(Ordering::Relaxed),
+                            entry,
+                        )))
+                    }))
                     .chain(futures::stream::once(async {
                         Ok::<_, std::io::Error>(LogMsg::Finished)
                     }))
@@ -567,80 +880,586 @@ pub trait ContainerService {
         }
     }
 
-    fn spawn_stream_raw_logs_to_db(&self, execution_id: &Uuid) -> JoinHandle<()> {
+    /// Snapshot of a process's normalized conversation. Running processes
+    /// return what has been normalized so far.
+    async fn normalized_entries(&self, id: &Uuid) -> Option<Vec<NormalizedEntry>> {
+        if let Some(store) = self.get_msg_store_by_id(id).await {
+            return Some(conversation_compare::entries_from_patches(
+                store.get_history(),
+            ));
+        }
+
+        // Stored logs are re-normalized in the background; stop once it goes quiet
+        let mut stream = self.stream_normalized_logs(id).await?;
+        let mut messages = Vec::new();
+        while let Ok(Some(Ok(msg))) =
+            tokio::time::timeout(NORMALIZED_SNAPSHOT_IDLE_TIMEOUT, stream.next()).await
+        {
+            if matches!(msg, LogMsg::Finished) {
+                break;
+            }
+            messages.push(msg);
+        }
+        Some(conversation_compare::entries_from_patches(messages))
+    }
+
+    /// Stdout and stderr of a process so far. `None` when its stored logs
+    /// cannot be read.
+    async fn raw_log_snapshot(&self, id: &Uuid) -> Option<Vec<LogMsg>> {
+        if let Some(store) = self.get_msg_store_by_id(id).await {
+            return Some(store.get_history());
+        }
+
+        let records = match ExecutionProcessLogs::find_by_execution_id(&self.db().pool, *id).await {
+            Ok(records) => records,
+            Err(e) => {
+                tracing::error!("Failed to fetch logs for execution {}: {}", id, e);
+                return None;
+            }
+        };
+        match ExecutionProcessLogs::parse_logs(&records) {
+            Ok(messages) => Some(messages),
+            Err(e) => {
+                tracing::error!("Failed to parse logs for execution {}: {}", id, e);
+                None
+            }
+        }
+    }
+
+    /// Every execution process of an attempt, in the order they ran, merged
+    /// into one conversation. A process whose logs cannot be loaded shows up
+    /// as an error entry rather than failing the whole conversation.
+    async fn attempt_conversation(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<NormalizedConversation, ContainerError> {
+        let pool = &self.db().pool;
+        let processes =
+            ExecutionProcess::find_by_task_attempt_id(pool, task_attempt.id, false).await?;
+
+        let mut merged = NormalizedConversation {
+            entries: Vec::new(),
+            session_id: None,
+            executor_type: task_attempt.executor.clone(),
+            prompt: None,
+            summary: None,
+            usage: ExecutionProcessUsage::total_for_task_attempt(pool, task_attempt.id).await?,
+            mcp_degraded: false,
+        };
+        for process in &processes {
+            let Ok(action) = process.executor_action() else {
+                merged
+                    .entries
+                    .push(conversation::invalid_action_entry(process.id));
+                continue;
+            };
+
+            let prompt = match action.typ() {
+                ExecutorActionType::CodingAgentInitialRequest(request) => &request.prompt,
+                ExecutorActionType::CodingAgentFollowUpRequest(request) => &request.prompt,
+                ExecutorActionType::ScriptRequest(request) => {
+                    let output = self
+                        .raw_log_snapshot(&process.id)
+                        .await
+                        .map(conversation::script_output);
+                    merged
+                        .entries
+                        .extend(conversation::script_entries(process, request, output));
+                    continue;
+                }
+            };
+
+            let entries = self.normalized_entries(&process.id).await;
+            merged.mcp_degraded |= mcp_degraded(entries.iter().flatten());
+            merged
+                .entries
+                .extend(conversation::agent_entries(process.id, prompt, entries));
+            merged.prompt.get_or_insert_with(|| prompt.clone());
+
+            if let Some(session) =
+                ExecutorSession::find_by_execution_process_id(pool, process.id).await?
+            {
+                merged.session_id = session.session_id.or(merged.session_id);
+                merged.summary = session.summary.or(merged.summary);
+            }
+        }
+
+        Ok(merged)
+    }
+
+    /// Store the token usage a coding agent run reported in its logs. Runs of
+    /// agents that do not report usage are left without a record.
+    async fn record_execution_usage(
+        &self,
+        process: &ExecutionProcess,
+    ) -> Result<(), ContainerError> {
+        let Ok(action) = process.executor_action() else {
+            return Ok(());
+        };
+        let executor_profile_id = match action.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => &request.executor_profile_id,
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => &request.executor_profile_id,
+            ExecutorActionType::ScriptRequest(_) => return Ok(()),
+        };
+        let Some(executor) = ExecutorConfigs::get_cached().get_coding_agent(executor_profile_id)
+        else {
+            return Ok(());
+        };
+        let Some(history) = self.raw_log_snapshot(&process.id).await else {
+            return Ok(());
+        };
+
+        if let Some(usage) = executor.usage_from_logs(&history) {
+            ExecutionProcessUsage::record(&self.db().pool, process.id, &usage).await?;
+        }
+        Ok(())
+    }
+
+    /// Store the plan a plan-mode coding agent run presented, so it can be
+    /// read back as steps without going through the conversation.
+    async fn record_execution_plan(
+        &self,
+        process: &ExecutionProcess,
+    ) -> Result<(), ContainerError> {
+        let Ok(action) = process.executor_action() else {
+            return Ok(());
+        };
+        let executor_profile_id = match action.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => &request.executor_profile_id,
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => &request.executor_profile_id,
+            ExecutorActionType::ScriptRequest(_) => return Ok(()),
+        };
+        let Some(executor) = ExecutorConfigs::get_cached().get_coding_agent(executor_profile_id)
+        else {
+            return Ok(());
+        };
+        let Some(history) = self.raw_log_snapshot(&process.id).await else {
+            return Ok(());
+        };
+
+        if let Some(plan) = executor.plan_from_logs(&history) {
+            TaskAttemptPlan::record(&self.db().pool, process.task_attempt_id, process.id, &plan)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Output batching thresholds for new execution processes.
+    async fn log_batch_config(&self) -> LogBatchConfig {
+        LogBatchConfig::default()
+    }
+
+    /// Persist the process's output as it arrives, along with its session id.
+    /// `session_id_scanner` looks for the id in stdout in case the log
+    /// normalizer does not report one.
+    fn spawn_stream_raw_logs_to_db(
+        &self,
+        execution_id: &Uuid,
+        batch_config: LogBatchConfig,
+        mut session_id_scanner: Option<SessionIdScanner>,
+    ) -> JoinHandle<()> {
         let execution_id = *execution_id;
         let msg_stores = self.msg_stores().clone();
         let db = self.db().clone();
 
-        tokio::spawn(async move {
+        self.shutdown_state().spawn_log_writer(async move {
             // Get the message store for this execution
             let store = {
                 let map = msg_stores.read().await;
                 map.get(&execution_id).cloned()
             };
+            let Some(store) = store else {
+                return;
+            };
 
-            if let Some(store) = store {
-                let mut stream = store.history_plus_stream();
-
-                while let Some(Ok(msg)) = stream.next().await {
-                    match &msg {
-                        LogMsg::Stdout(_) | LogMsg::Stderr(_) => {
-                            // Serialize this individual message as a JSONL line
-                            match serde_json::to_string(&msg) {
-                                Ok(jsonl_line) => {
-                                    let jsonl_line_with_newline = format!("{jsonl_line}\n");
-
-                                    // Append this line to the database
-                                    if let Err(e) = ExecutionProcessLogs::append_log_line(
-                                        &db.pool,
-                                        execution_id,
-                                        &jsonl_line_with_newline,
-                                    )
-                                    .await
-                                    {
-                                        tracing::error!(
-                                            "Failed to append log line for execution {}: {}",
-                                            execution_id,
-                                            e
-                                        );
-                                    }
-                                }
-                                Err(e) => {
-                                    tracing::error!(
-                                        "Failed to serialize log message for execution {}: {}",
-                                        execution_id,
-                                        e
-                                    );
+            let save_session_id = |session_id: String| {
+                let pool = db.pool.clone();
+                async move {
+                    if let Err(e) =
+                        ExecutorSession::update_session_id(&pool, execution_id, &session_id).await
+                    {
+                        tracing::error!(
+                            "Failed to update session_id {} for execution process {}: {}",
+                            session_id,
+                            execution_id,
+                            e
+                        );
+                    }
+                }
+            };
+
+            let write_batch = |batch: String| {
+                let pool = db.pool.clone();
+                async move {
+                    if let Err(e) =
+                        ExecutionProcessLogs::append_log_line(&pool, execution_id, &batch).await
+                    {
+                        tracing::error!(
+                            "Failed to append log lines for execution {}: {}",
+                            execution_id,
+                            e
+                        );
+                    }
+                }
+            };
+
+            // Stdout and stderr share one batch so rows stay in output order
+            let mut batch = LogBatch::new(batch_config);
+            let mut stream = store.history_plus_stream();
+            loop {
+                let deadline = batch.deadline();
+                let msg = tokio::select! {
+                    msg = stream.next() => msg,
+                    _ = tokio::time::sleep_until(deadline.unwrap_or_else(tokio::time::Instant::now)),
+                        if deadline.is_some() =>
+                    {
+                        if let Some(lines) = batch.take() {
+                            write_batch(lines).await;
+                        }
+                        continue;
+                    }
+                };
+
+                if let Some(Ok(LogMsg::Stdout(chunk))) = &msg
+                    && let Some(scanner) = session_id_scanner.as_mut()
+                    && let Some(session_id) = scanner.push_chunk(chunk)
+                {
+                    save_session_id(session_id).await;
+                }
+
+                match msg {
+                    Some(Ok(msg @ (LogMsg::Stdout(_) | LogMsg::Stderr(_)))) => {
+                        match serde_json::to_string(&msg) {
+                            Ok(jsonl_line) => {
+                                if let Some(lines) = batch.push(&format!("{jsonl_line}\n")) {
+                                    write_batch(lines).await;
                                 }
                             }
-                        }
-                        LogMsg::SessionId(session_id) => {
-                            // Append this line to the database
-                            if let Err(e) = ExecutorSession::update_session_id(
-                                &db.pool,
-                                execution_id,
-                                session_id,
-                            )
-                            .await
-                            {
+                            Err(e) => {
                                 tracing::error!(
-                                    "Failed to update session_id {} for execution process {}: {}",
-                                    session_id,
+                                    "Failed to serialize log message for execution {}: {}",
                                     execution_id,
                                     e
                                 );
                             }
                         }
-                        LogMsg::Finished => {
-                            break;
+                    }
+                    Some(Ok(LogMsg::SessionId(session_id))) => {
+                        // The normalizer's id wins; stop scanning for one
+                        if let Some(scanner) = session_id_scanner.as_mut() {
+                            scanner.finish();
                         }
-                        LogMsg::JsonPatch(_) => continue,
+                        save_session_id(session_id).await;
                     }
+                    Some(Ok(LogMsg::JsonPatch(_))) => continue,
+                    Some(Ok(LogMsg::Finished)) | Some(Err(_)) | None => break,
                 }
             }
+
+            if let Some(lines) = batch.take() {
+                write_batch(lines).await;
+            }
         })
     }
 
+    /// Attempts allowed to run a coding agent at once across all projects,
+    /// 0 for no limit.
+    async fn max_concurrent_executions(&self) -> u32 {
+        0
+    }
+
+    /// Held while deciding whether an attempt starts or waits, so two
+    /// requests cannot take the same free slot.
+    fn execution_queue_lock(&self) -> &Mutex<()>;
+
+    /// Whether another attempt in `project` may start now under the global
+    /// and per-project limits.
+    async fn has_execution_slot(&self, project: &Project) -> Result<bool, ContainerError> {
+        let pool = &self.db().pool;
+        let global_limit = self.max_concurrent_executions().await;
+        if global_limit > 0
+            && ExecutionProcess::count_active_attempts(pool, None).await? >= i64::from(global_limit)
+        {
+            return Ok(false);
+        }
+        if let Some(project_limit) = project.max_concurrent_executions
+            && ExecutionProcess::count_active_attempts(pool, Some(project.id)).await?
+                >= project_limit
+        {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Start the attempt if a slot is free and no earlier attempt from its
+    /// project is waiting, otherwise put it at the back of the queue. Once
+    /// queued, the attempt also waits for its task's dependencies unless
+    /// `ignore_dependencies` is set.
+    async fn start_or_queue_attempt(
+        &self,
+        task_attempt: &TaskAttempt,
+        executor_profile_id: ExecutorProfileId,
+        ignore_dependencies: bool,
+    ) -> Result<AttemptStart, ContainerError> {
+        let _guard = self.execution_queue_lock().lock().await;
+        // Let attempts that were already waiting go first
+        self.start_queued_attempts_locked().await?;
+
+        let pool = &self.db().pool;
+        let project = task_attempt
+            .parent_task(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?
+            .parent_project(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let mut project_has_waiting = false;
+        for queued in QueuedAttempt::list(pool).await? {
+            if queued.project_id == project.id && !waits_for_dependencies(pool, &queued).await? {
+                project_has_waiting = true;
+                break;
+            }
+        }
+
+        if !project_has_waiting && self.has_execution_slot(&project).await? {
+            return self
+                .start_attempt(task_attempt, executor_profile_id)
+                .await
+                .map(AttemptStart::Started);
+        }
+
+        QueuedAttempt::enqueue(
+            pool,
+            task_attempt.id,
+            &executor_profile_id,
+            ignore_dependencies,
+        )
+        .await?;
+        AttemptEvent::record(
+            pool,
+            task_attempt.id,
+            AttemptEventType::Queued,
+            serde_json::json!({}),
+        )
+        .await;
+        tracing::info!(
+            "Queued task attempt {} until an execution slot frees up",
+            task_attempt.id
+        );
+        Ok(AttemptStart::Queued)
+    }
+
+    /// Start waiting attempts in queue order for as long as slots are free.
+    /// Call whenever an attempt may have finished.
+    async fn start_queued_attempts(&self) -> Result<(), ContainerError> {
+        let _guard = self.execution_queue_lock().lock().await;
+        self.start_queued_attempts_locked().await
+    }
+
+    /// Send the attempt's oldest queued follow-up once nothing else runs for
+    /// it. The follow-up stays queued, marked blocked, while the last run
+    /// failed or there is no agent session to continue.
+    async fn dispatch_queued_followup(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<Option<ExecutionProcess>, ContainerError> {
+        let pool = &self.db().pool;
+        let processes =
+            ExecutionProcess::find_by_task_attempt_id(pool, task_attempt.id, false).await?;
+        let mut agent_processes = processes
+            .iter()
+            .filter(|p| !matches!(p.run_reason, ExecutionProcessRunReason::DevServer));
+        if agent_processes
+            .clone()
+            .any(|p| matches!(p.status, ExecutionProcessStatus::Running))
+        {
+            return Ok(None);
+        }
+        let Some(followup) = QueuedFollowUp::next_pending(pool, task_attempt.id).await? else {
+            return Ok(None);
+        };
+
+        let blocked_reason = match agent_processes.next_back().map(|p| &p.status) {
+            Some(ExecutionProcessStatus::Failed) => Some("The last run failed"),
+            Some(ExecutionProcessStatus::Killed) => Some("The last run was stopped"),
+            _ => None,
+        };
+        let session_id =
+            ExecutionProcess::find_latest_session_id_by_task_attempt(pool, task_attempt.id).await?;
+        let latest_agent = ExecutionProcess::find_latest_by_task_attempt_and_run_reason(
+            pool,
+            task_attempt.id,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+        let executor_profile_id = match latest_agent.as_ref().map(|p| p.executor_action()) {
+            Some(Ok(action)) => match &action.typ {
+                ExecutorActionType::CodingAgentInitialRequest(req) => {
+                    Some(req.executor_profile_id.clone())
+                }
+                ExecutorActionType::CodingAgentFollowUpRequest(req) => {
+                    Some(req.executor_profile_id.clone())
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        let (session_id, executor_profile_id) =
+            match (blocked_reason, session_id, executor_profile_id) {
+                (None, Some(session_id), Some(executor_profile_id)) => {
+                    (session_id, executor_profile_id)
+                }
+                (reason, session_id, _) => {
+                    let reason = reason.unwrap_or(if session_id.is_none() {
+                        "No agent session was captured to continue"
+                    } else {
+                        "No coding agent run to continue"
+                    });
+                    QueuedFollowUp::block(pool, followup.id, reason).await?;
+                    tracing::info!(
+                        "Queued follow-up {} for attempt {} is blocked: {}",
+                        followup.id,
+                        task_attempt.id,
+                        reason
+                    );
+                    return Ok(None);
+                }
+            };
+
+        if !QueuedFollowUp::claim(pool, followup.id).await? {
+            return Ok(None);
+        }
+        let task = task_attempt
+            .parent_task(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let post_agent_action = task
+            .parent_project(pool)
+            .await?
+            .and_then(|project| self.post_agent_action(&project));
+        let action = ExecutorAction::new(
+            ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                prompt: followup.prompt.clone(),
+                session_id,
+                task_context: Some(task.to_prompt()),
+                executor_profile_id,
+            }),
+            post_agent_action,
+        );
+        match self
+            .start_execution(
+                task_attempt,
+                &action,
+                &ExecutionProcessRunReason::CodingAgent,
+            )
+            .await
+        {
+            Ok(process) => Ok(Some(process)),
+            Err(e) => {
+                QueuedFollowUp::block(pool, followup.id, &format!("Failed to start: {e}")).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Start queued attempts of tasks that depend on `task_id`, now that it
+    /// is done. Call whenever a task moves to done.
+    async fn start_unblocked_attempts(&self, task_id: Uuid) {
+        match TaskDependency::find_dependent_ids(&self.db().pool, task_id).await {
+            Ok(dependents) if dependents.is_empty() => {}
+            Ok(_) => {
+                if let Err(e) = self.start_queued_attempts().await {
+                    tracing::error!("Failed to start queued task attempts: {}", e);
+                }
+            }
+            Err(e) => tracing::error!("Failed to look up dependents of task {}: {}", task_id, e),
+        }
+    }
+
+    /// [`Self::start_queued_attempts`] for callers already holding the queue
+    /// lock. An attempt blocked by its project's limit or by unfinished
+    /// dependencies does not hold up the attempts behind it.
+    async fn start_queued_attempts_locked(&self) -> Result<(), ContainerError> {
+        let pool = &self.db().pool;
+        for queued in QueuedAttempt::list(pool).await? {
+            if waits_for_dependencies(pool, &queued).await? {
+                continue;
+            }
+            let Some(project) = Project::find_by_id(pool, queued.project_id).await? else {
+                continue;
+            };
+            if !self.has_execution_slot(&project).await? {
+                continue;
+            }
+            let Some(task_attempt) = TaskAttempt::find_by_id(pool, queued.task_attempt_id).await?
+            else {
+                continue;
+            };
+
+            QueuedAttempt::remove(pool, task_attempt.id).await?;
+            tracing::info!("Starting queued task attempt {}", task_attempt.id);
+            if let Err(e) = self
+                .start_attempt(&task_attempt, queued.executor_profile_id)
+                .await
+            {
+                tracing::error!(
+                    "Failed to start queued task attempt {}: {}",
+                    task_attempt.id,
+                    e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// The request an attempt's first coding agent run was started with,
+    /// including one chained after its setup script.
+    async fn initial_request(
+        &self,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<CodingAgentInitialRequest>, ContainerError> {
+        let processes =
+            ExecutionProcess::find_by_task_attempt_id(&self.db().pool, task_attempt_id, true)
+                .await?;
+        for process in &processes {
+            let mut action = process.executor_action().ok();
+            while let Some(current) = action {
+                if let ExecutorActionType::CodingAgentInitialRequest(request) = current.typ() {
+                    return Ok(Some(request.clone()));
+                }
+                action = current.next_action();
+            }
+        }
+        Ok(None)
+    }
+
+    /// The prompt a retry re-sends: the one its original attempt started
+    /// with, with image paths moved to the new worktree. `None` for attempts
+    /// that are not retries, or whose original never started an agent.
+    async fn retried_prompt(
+        &self,
+        task_attempt: &TaskAttempt,
+        worktree_path: &Path,
+    ) -> Result<Option<String>, ContainerError> {
+        let Some(original_id) = task_attempt.retried_from_attempt_id else {
+            return Ok(None);
+        };
+        let Some(request) = self.initial_request(original_id).await? else {
+            return Ok(None);
+        };
+        let original_worktree = TaskAttempt::find_by_id(&self.db().pool, original_id)
+            .await?
+            .and_then(|original| original.container_ref);
+        let prompt = match original_worktree {
+            Some(original_worktree) => request.prompt.replace(
+                &original_worktree.replace('\\', "/"),
+                &worktree_path.to_string_lossy().replace('\\', "/"),
+            ),
+            None => request.prompt,
+        };
+        Ok(Some(prompt))
+    }
+
     async fn start_attempt(
         &self,
         task_attempt: &TaskAttempt,
@@ -673,9 +1492,22 @@ pub trait ContainerService {
                 .as_ref()
                 .ok_or_else(|| ContainerError::Other(anyhow!("Container ref not found")))?,
         );
-        let prompt = ImageService::canonicalise_image_paths(&task.to_prompt(), &worktree_path);
+        AttemptEvent::record(
+            &self.db().pool,
+            task_attempt.id,
+            AttemptEventType::WorktreeCreated,
+            serde_json::json!({
+                "branch": task_attempt.branch,
+                "path": worktree_path,
+            }),
+        )
+        .await;
+        let prompt = match self.retried_prompt(&task_attempt, &worktree_path).await? {
+            Some(prompt) => prompt,
+            None => ImageService::canonicalise_image_paths(&task.to_prompt(), &worktree_path),
+        };
 
-        let cleanup_action = self.cleanup_action(project.cleanup_script);
+        let post_agent_action = self.post_agent_action(&project);
 
         // Choose whether to execute the setup_script or coding agent first
         let execution_process = if let Some(setup_script) = project.setup_script {
@@ -684,6 +1516,8 @@ pub trait ContainerService {
                     script: setup_script,
                     language: ScriptRequestLanguage::Bash,
                     context: ScriptContext::SetupScript,
+                    timeout_secs: None,
+                    stall_timeout_secs: None,
                 }),
                 // once the setup script is done, run the initial coding agent request
                 Some(Box::new(ExecutorAction::new(
@@ -691,7 +1525,7 @@ pub trait ContainerService {
                         prompt,
                         executor_profile_id: executor_profile_id.clone(),
                     }),
-                    cleanup_action,
+                    post_agent_action,
                 ))),
             );
 
@@ -707,7 +1541,7 @@ pub trait ContainerService {
                     prompt,
                     executor_profile_id: executor_profile_id.clone(),
                 }),
-                cleanup_action,
+                post_agent_action,
             );
 
             self.start_execution(
@@ -720,12 +1554,125 @@ pub trait ContainerService {
         Ok(execution_process)
     }
 
+    /// Start the project's dev script in the attempt's worktree, stopping
+    /// any dev server already running for the project. `restart` marks an
+    /// automatic restart after a crash, which keeps counting towards the
+    /// crash loop limit.
+    async fn start_dev_server(
+        &self,
+        task_attempt: &TaskAttempt,
+        project: &Project,
+        restart: bool,
+    ) -> Result<ExecutionProcess, ContainerError> {
+        let Some(dev_script) = project.dev_script.clone() else {
+            return Err(ContainerError::Other(anyhow!(
+                "No dev server script configured for this project"
+            )));
+        };
+
+        // Stopped processes are not restarted, and registering the new one
+        // below cancels a restart still waiting to happen
+        self.stop_project_dev_servers(project.id).await?;
+
+        // TODO: Derive script language from system config
+        let executor_action = ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: dev_script,
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::DevServer,
+                timeout_secs: None,
+                stall_timeout_secs: None,
+            }),
+            None,
+        );
+        let execution_process = self
+            .start_execution(
+                task_attempt,
+                &executor_action,
+                &ExecutionProcessRunReason::DevServer,
+            )
+            .await?;
+        self.dev_servers()
+            .started(project.id, task_attempt.id, execution_process.id, restart);
+        Ok(execution_process)
+    }
+
+    /// Stop the project's dev server and cancel any pending restart.
+    async fn stop_dev_server(&self, project_id: Uuid) -> Result<(), ContainerError> {
+        self.dev_servers().stopped(project_id);
+        self.stop_project_dev_servers(project_id).await
+    }
+
+    async fn stop_project_dev_servers(&self, project_id: Uuid) -> Result<(), ContainerError> {
+        let running =
+            ExecutionProcess::find_running_dev_servers_by_project(&self.db().pool, project_id)
+                .await?;
+        for dev_server in running {
+            tracing::info!(
+                "Stopping dev server {} for project {}",
+                dev_server.id,
+                project_id
+            );
+            if let Err(e) = self
+                .stop_execution(&dev_server, ExecutionProcessStatus::Killed)
+                .await
+            {
+                tracing::error!("Failed to stop dev server {}: {}", dev_server.id, e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn dev_server_status(&self, project_id: Uuid) -> Result<DevServerStatus, ContainerError> {
+        let supervised = self.dev_servers().get(project_id);
+        let running =
+            ExecutionProcess::find_running_dev_servers_by_project(&self.db().pool, project_id)
+                .await?
+                .pop();
+        // Fall back to the last process started for the project so a crash
+        // loop still shows its URL and exit code
+        let process = match (&running, &supervised) {
+            (Some(process), _) => Some(process.clone()),
+            (None, Some(server)) => {
+                ExecutionProcess::find_by_id(&self.db().pool, server.execution_process_id).await?
+            }
+            (None, None) => None,
+        };
+        // A supervised server that is not running was stopped some other
+        // way, e.g. when its attempt was merged
+        let state = match (&running, &supervised) {
+            (Some(_), _) => DevServerState::Running,
+            (None, Some(server)) if server.state != DevServerState::Running => server.state,
+            (None, _) => DevServerState::Stopped,
+        };
+        let pid = match &running {
+            Some(process) => self.execution_pid(&process.id).await,
+            None => None,
+        };
+        Ok(DevServerStatus {
+            state,
+            task_attempt_id: process.as_ref().map(|p| p.task_attempt_id),
+            execution_process_id: process.as_ref().map(|p| p.id),
+            pid,
+            url: process.as_ref().and_then(|p| p.dev_server_url.clone()),
+            port_conflict: process.as_ref().is_some_and(|p| p.dev_server_port_conflict),
+            uptime_secs: running
+                .as_ref()
+                .map(|p| (Utc::now() - p.started_at).num_seconds()),
+            restart_count: supervised.as_ref().map_or(0, |s| s.restart_count),
+            last_exit_code: supervised.and_then(|s| s.last_exit_code),
+        })
+    }
+
     async fn start_execution(
         &self,
         task_attempt: &TaskAttempt,
         executor_action: &ExecutorAction,
         run_reason: &ExecutionProcessRunReason,
     ) -> Result<ExecutionProcess, ContainerError> {
+        if self.shutdown_state().is_closing() {
+            return Err(ContainerError::ShuttingDown);
+        }
         // Update task status to InProgress when starting an attempt
         let task = task_attempt
             .parent_task(&self.db().pool)
@@ -770,6 +1717,16 @@ pub trait ContainerService {
         )
         .await?;
 
+        if run_reason == &ExecutionProcessRunReason::VerifyScript {
+            TaskAttempt::update_verify_status(
+                &self.db().pool,
+                task_attempt.id,
+                VerifyStatus::Running,
+                None,
+            )
+            .await?;
+        }
+
         if let Some(prompt) = match executor_action.typ() {
             ExecutorActionType::CodingAgentInitialRequest(coding_agent_request) => {
                 Some(coding_agent_request.prompt.clone())
@@ -795,6 +1752,19 @@ pub trait ContainerService {
             .await?;
         }
 
+        if matches!(
+            executor_action.typ(),
+            ExecutorActionType::CodingAgentFollowUpRequest(_)
+        ) {
+            AttemptEvent::record(
+                &self.db().pool,
+                task_attempt.id,
+                AttemptEventType::FollowUpRequested,
+                serde_json::json!({ "execution_process_id": execution_process.id }),
+            )
+            .await;
+        }
+
         if let Err(start_error) = self
             .start_execution_inner(task_attempt, &execution_process, executor_action)
             .await
@@ -814,6 +1784,13 @@ pub trait ContainerService {
                     update_error
                 );
             }
+            record_process_event(
+                &self.db().pool,
+                &execution_process,
+                ExecutionProcessStatus::Failed,
+                None,
+            )
+            .await;
             Task::update_status(&self.db().pool, task.id, TaskStatus::InReview).await?;
 
             // Emit stderr error message
@@ -853,22 +1830,44 @@ pub trait ContainerService {
             return Err(start_error);
         }
 
+        AttemptEvent::record(
+            &self.db().pool,
+            task_attempt.id,
+            AttemptEventType::ProcessStarted,
+            serde_json::json!({
+                "execution_process_id": execution_process.id,
+                "run_reason": execution_process.run_reason,
+            }),
+        )
+        .await;
+
+        let coding_agent_profile_id = match executor_action.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => {
+                Some(&request.executor_profile_id)
+            }
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+                Some(&request.executor_profile_id)
+            }
+            _ => None,
+        };
+
         // Start processing normalised logs for executor requests and follow ups
         if let Some(msg_store) = self.get_msg_store_by_id(&execution_process.id).await
-            && let Some(executor_profile_id) = match executor_action.typ() {
-                ExecutorActionType::CodingAgentInitialRequest(request) => {
-                    Some(&request.executor_profile_id)
-                }
-                ExecutorActionType::CodingAgentFollowUpRequest(request) => {
-                    Some(&request.executor_profile_id)
-                }
-                _ => None,
-            }
+            && let Some(executor_profile_id) = coding_agent_profile_id
         {
             if let Some(executor) =
                 ExecutorConfigs::get_cached().get_coding_agent(executor_profile_id)
             {
-                executor.normalize_logs(msg_store, &self.task_attempt_to_current_dir(task_attempt));
+                executor.normalize_logs(
+                    msg_store.clone(),
+                    &self.task_attempt_to_current_dir(task_attempt),
+                );
+                self.shutdown_state()
+                    .spawn_log_writer(session_entries::record_entries(
+                        self.db().clone(),
+                        execution_process.id,
+                        msg_store,
+                    ));
             } else {
                 tracing::error!(
                     "Failed to resolve profile '{:?}' for normalization",
@@ -877,7 +1876,35 @@ pub trait ContainerService {
             }
         }
 
-        self.spawn_stream_raw_logs_to_db(&execution_process.id);
+        // Track the steps of a setup script written as a list of steps
+        if let ExecutorActionType::ScriptRequest(request) = executor_action.typ()
+            && let Some(steps) = request.setup_steps()
+            && let Some(msg_store) = self.get_msg_store_by_id(&execution_process.id).await
+        {
+            let index_provider = EntryIndexProvider::start_from(&msg_store);
+            normalize_setup_step_logs(msg_store, steps, index_provider);
+        }
+
+        // Pick up the listen URL and port conflicts from dev server output
+        if run_reason == &ExecutionProcessRunReason::DevServer
+            && let Some(msg_store) = self.get_msg_store_by_id(&execution_process.id).await
+        {
+            let matcher = match task.parent_project(&self.db().pool).await {
+                Ok(Some(project)) => DevServerOutputMatcher::for_project(&project),
+                _ => DevServerOutputMatcher::new(),
+            };
+            dev_server::spawn_output_watcher(
+                self.db().clone(),
+                execution_process.id,
+                msg_store,
+                matcher,
+            );
+        }
+
+        let batch_config = self.log_batch_config().await;
+        let session_id_scanner = coding_agent_profile_id
+            .map(|profile_id| SessionIdScanner::for_executor(profile_id.executor));
+        self.spawn_stream_raw_logs_to_db(&execution_process.id, batch_config, session_id_scanner);
         Ok(execution_process)
     }
 
@@ -891,20 +1918,19 @@ pub trait ContainerService {
         };
 
         // Determine the run reason of the next action
-        let next_run_reason = match (action.typ(), next_action.typ()) {
-            (ExecutorActionType::ScriptRequest(_), ExecutorActionType::ScriptRequest(_)) => {
-                ExecutionProcessRunReason::SetupScript
-            }
-            (
-                ExecutorActionType::CodingAgentInitialRequest(_)
-                | ExecutorActionType::CodingAgentFollowUpRequest(_),
-                ExecutorActionType::ScriptRequest(_),
-            ) => ExecutionProcessRunReason::CleanupScript,
-            (
-                _,
-                ExecutorActionType::CodingAgentFollowUpRequest(_)
-                | ExecutorActionType::CodingAgentInitialRequest(_),
-            ) => ExecutionProcessRunReason::CodingAgent,
+        let next_run_reason = match next_action.typ() {
+            ExecutorActionType::ScriptRequest(request) => match request.context {
+                ScriptContext::SetupScript | ScriptContext::ToolInstallScript => {
+                    ExecutionProcessRunReason::SetupScript
+                }
+                ScriptContext::CleanupScript => ExecutionProcessRunReason::CleanupScript,
+                ScriptContext::VerifyScript => ExecutionProcessRunReason::VerifyScript,
+                ScriptContext::DevServer => ExecutionProcessRunReason::DevServer,
+            },
+            ExecutorActionType::CodingAgentFollowUpRequest(_)
+            | ExecutorActionType::CodingAgentInitialRequest(_) => {
+                ExecutionProcessRunReason::CodingAgent
+            }
         };
 
         self.start_execution(&ctx.task_attempt, next_action, &next_run_reason)
@@ -0,0 +1,269 @@
+//! One conversation for a whole attempt.
+//!
+//! An attempt runs several execution processes in turn: the setup script, the
+//! initial coding agent run, follow-ups, verify and cleanup scripts. These
+//! helpers turn each of them into normalized entries the way the conversation
+//! view renders them, so the container can concatenate them into a single
+//! [`executors::logs::NormalizedConversation`]. Every entry carries the id of
+//! the process it came from in its metadata.
+
+use db::models::execution_process::{ExecutionProcess, ExecutionProcessStatus};
+use executors::{
+    actions::{
+        script::{ScriptContext, ScriptRequest},
+        setup_steps::SetupStepTracker,
+    },
+    logs::{
+        ActionType, CommandExitStatus, CommandRunResult, NormalizedEntry, NormalizedEntryError,
+        NormalizedEntryType, ToolStatus,
+    },
+};
+use serde_json::{Map, Value};
+use utils::log_msg::LogMsg;
+use uuid::Uuid;
+
+/// Metadata key holding the originating execution process id.
+pub const EXECUTION_PROCESS_ID_KEY: &str = "execution_process_id";
+
+/// Entries for one coding agent run: the prompt as a user message, then what
+/// the agent produced. `entries` is `None` when the run's logs could not be
+/// loaded or normalized, in which case an error entry takes their place.
+pub fn agent_entries(
+    process_id: Uuid,
+    prompt: &str,
+    entries: Option<Vec<NormalizedEntry>>,
+) -> Vec<NormalizedEntry> {
+    let user_message = NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::UserMessage,
+        content: prompt.to_string(),
+        metadata: None,
+    };
+
+    let produced = match entries {
+        // Agents echo the prompt back in their own format; the action's prompt
+        // is the canonical one
+        Some(entries) => entries
+            .into_iter()
+            .filter(|entry| {
+                !matches!(
+                    entry.entry_type,
+                    NormalizedEntryType::UserMessage
+                        | NormalizedEntryType::Loading
+                        | NormalizedEntryType::NextAction { .. }
+                )
+            })
+            .collect(),
+        None => vec![unavailable_entry()],
+    };
+
+    std::iter::once(user_message)
+        .chain(produced)
+        .map(|entry| tag_entry(entry, process_id))
+        .collect()
+}
+
+/// A script run shown as a single command entry. Dev servers are not part of
+/// the conversation and yield nothing. `output` is `None` when the stored logs
+/// could not be parsed.
+pub fn script_entries(
+    process: &ExecutionProcess,
+    request: &ScriptRequest,
+    output: Option<String>,
+) -> Vec<NormalizedEntry> {
+    let tool_name = match request.context {
+        ScriptContext::SetupScript => "Setup Script",
+        ScriptContext::CleanupScript => "Cleanup Script",
+        ScriptContext::ToolInstallScript => "Tool Install Script",
+        ScriptContext::VerifyScript => "Verify Script",
+        ScriptContext::DevServer => return Vec::new(),
+    };
+
+    // A setup script written as steps shows one entry per step
+    if let (Some(steps), Some(output)) = (request.setup_steps(), &output) {
+        let mut tracker = SetupStepTracker::new(steps);
+        for line in output.lines() {
+            tracker.push_line(line);
+        }
+        return tracker
+            .entries()
+            .into_iter()
+            .map(|entry| tag_entry(entry, process.id))
+            .collect();
+    }
+
+    let running = process.status == ExecutionProcessStatus::Running;
+    let exit_code = process
+        .exit_code
+        .and_then(|code| i32::try_from(code).ok())
+        .unwrap_or_default();
+    let status = if running {
+        ToolStatus::Created
+    } else if exit_code == 0 && process.status == ExecutionProcessStatus::Completed {
+        ToolStatus::Success
+    } else {
+        ToolStatus::Failed
+    };
+
+    let mut entries = vec![NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::ToolUse {
+            tool_name: tool_name.to_string(),
+            action_type: ActionType::CommandRun {
+                command: request.script.clone(),
+                result: Some(CommandRunResult {
+                    exit_status: (!running)
+                        .then_some(CommandExitStatus::ExitCode { code: exit_code }),
+                    output: output.clone(),
+                }),
+            },
+            status,
+        },
+        content: tool_name.to_string(),
+        metadata: None,
+    }];
+    if output.is_none() {
+        entries.push(unavailable_entry());
+    }
+
+    entries
+        .into_iter()
+        .map(|entry| tag_entry(entry, process.id))
+        .collect()
+}
+
+/// Error entry for a process whose executor action cannot be read.
+pub fn invalid_action_entry(process_id: Uuid) -> NormalizedEntry {
+    tag_entry(
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ErrorMessage {
+                error_type: NormalizedEntryError::Other,
+            },
+            content: "This execution process has an unreadable executor action".to_string(),
+            metadata: None,
+        },
+        process_id,
+    )
+}
+
+/// Closing entry for a process that was stopped before it finished.
+pub fn stopped_entry(process_id: Uuid) -> NormalizedEntry {
+    tag_entry(
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::SystemMessage,
+            content: "Execution was stopped before it finished".to_string(),
+            metadata: None,
+        },
+        process_id,
+    )
+}
+
+/// Stdout and stderr of a script, in the order they were produced.
+pub fn script_output(history: impl IntoIterator<Item = LogMsg>) -> String {
+    history
+        .into_iter()
+        .filter_map(|msg| match msg {
+            LogMsg::Stdout(text) | LogMsg::Stderr(text) => Some(text),
+            _ => None,
+        })
+        .collect()
+}
+
+fn unavailable_entry() -> NormalizedEntry {
+    NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::ErrorMessage {
+            error_type: NormalizedEntryError::Other,
+        },
+        content: "Logs for this execution process could not be loaded".to_string(),
+        metadata: None,
+    }
+}
+
+/// Add the process id to the entry's metadata, keeping what is already there.
+fn tag_entry(mut entry: NormalizedEntry, process_id: Uuid) -> NormalizedEntry {
+    let mut metadata = match entry.metadata.take() {
+        Some(Value::Object(map)) => map,
+        None | Some(Value::Null) => Map::new(),
+        Some(other) => Map::from_iter([("value".to_string(), other)]),
+    };
+    metadata.insert(
+        EXECUTION_PROCESS_ID_KEY.to_string(),
+        Value::String(process_id.to_string()),
+    );
+    entry.metadata = Some(Value::Object(metadata));
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn process_id_of(entry: &NormalizedEntry) -> Option<&str> {
+        entry.metadata.as_ref()?[EXECUTION_PROCESS_ID_KEY].as_str()
+    }
+
+    #[test]
+    fn agent_entries_lead_with_prompt_and_tag_every_entry() {
+        let process_id = Uuid::new_v4();
+        let mut model = entry(NormalizedEntryType::SystemMessage, "model: gpt-5");
+        model.metadata = Some(json!({ "tool_call_id": "call-1" }));
+
+        let entries = agent_entries(
+            process_id,
+            "Fix the flaky test",
+            Some(vec![
+                entry(NormalizedEntryType::UserMessage, "echoed prompt"),
+                model,
+                entry(NormalizedEntryType::AssistantMessage, "Done."),
+            ]),
+        );
+
+        let contents: Vec<&str> = entries.iter().map(|e| e.content.as_str()).collect();
+        assert_eq!(contents, ["Fix the flaky test", "model: gpt-5", "Done."]);
+        let expected = process_id.to_string();
+        assert!(
+            entries
+                .iter()
+                .all(|e| process_id_of(e) == Some(expected.as_str()))
+        );
+        assert_eq!(
+            entries[1].metadata.as_ref().unwrap()["tool_call_id"],
+            "call-1"
+        );
+    }
+
+    #[test]
+    fn unparseable_agent_logs_become_an_error_entry() {
+        let entries = agent_entries(Uuid::new_v4(), "Add a README", None);
+        assert_eq!(entries.len(), 2);
+        assert!(matches!(
+            entries[1].entry_type,
+            NormalizedEntryType::ErrorMessage { .. }
+        ));
+    }
+
+    #[test]
+    fn script_output_keeps_stream_order() {
+        let output = script_output([
+            LogMsg::Stdout("installing\n".to_string()),
+            LogMsg::Stderr("warning: peer dep\n".to_string()),
+            LogMsg::Finished,
+            LogMsg::Stdout("done\n".to_string()),
+        ]);
+        assert_eq!(output, "installing\nwarning: peer dep\ndone\n");
+    }
+}
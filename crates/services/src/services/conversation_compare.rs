@@ -0,0 +1,480 @@
+use executors::logs::{ActionType, NormalizedEntry, NormalizedEntryType};
+use json_patch::Patch;
+use serde::Serialize;
+use serde_json::{Value, json};
+use ts_rs::TS;
+use utils::log_msg::LogMsg;
+
+/// Past this many cells the LCS table is too expensive to build and entries
+/// are paired up by position instead.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum AlignmentKind {
+    Unchanged,
+    Changed,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum WordDiffKind {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct WordDiffSegment {
+    pub kind: WordDiffKind,
+    pub text: String,
+}
+
+/// One row of the side-by-side view. `left_index`/`right_index` point into the
+/// respective conversations; either is missing for added/removed rows.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AlignedEntryPair {
+    pub index: usize,
+    pub kind: AlignmentKind,
+    pub left_index: Option<usize>,
+    pub right_index: Option<usize>,
+    pub left: Option<NormalizedEntry>,
+    pub right: Option<NormalizedEntry>,
+    /// Word-level diff, only for changed assistant message pairs
+    pub text_diff: Option<Vec<WordDiffSegment>>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ConversationComparison {
+    pub total: usize,
+    pub offset: usize,
+    pub has_more: bool,
+    pub pairs: Vec<AlignedEntryPair>,
+}
+
+/// Rebuild the conversation from the normalized patch stream of a process.
+pub fn entries_from_patches(messages: impl IntoIterator<Item = LogMsg>) -> Vec<NormalizedEntry> {
+    let mut doc = json!({ "entries": [] });
+    for msg in messages {
+        if let LogMsg::JsonPatch(patch) = msg {
+            apply_patch(&mut doc, &patch);
+        }
+    }
+
+    doc["entries"]
+        .as_array()
+        .map(|entries| {
+            entries
+                .iter()
+                .filter(|value| value["type"] == "NORMALIZED_ENTRY")
+                .filter_map(|value| serde_json::from_value(value["content"].clone()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn apply_patch(doc: &mut Value, patch: &Patch) {
+    if let Err(err) = json_patch::patch(doc, patch) {
+        tracing::debug!("Skipping conversation patch that does not apply: {}", err);
+    }
+}
+
+/// Align two conversations and return the `limit` rows starting at `offset`.
+pub fn compare(
+    left: &[NormalizedEntry],
+    right: &[NormalizedEntry],
+    offset: usize,
+    limit: usize,
+) -> ConversationComparison {
+    let left: Vec<&NormalizedEntry> = left.iter().filter(|e| is_comparable(e)).collect();
+    let right: Vec<&NormalizedEntry> = right.iter().filter(|e| is_comparable(e)).collect();
+    let left_keys: Vec<String> = left.iter().map(|e| fingerprint(e)).collect();
+    let right_keys: Vec<String> = right.iter().map(|e| fingerprint(e)).collect();
+
+    let rows = align(&left_keys, &right_keys);
+    let total = rows.len();
+    let pairs = rows
+        .into_iter()
+        .enumerate()
+        .skip(offset)
+        .take(limit)
+        .map(|(index, (l, r))| build_pair(index, l.map(|i| (i, left[i])), r.map(|i| (i, right[i]))))
+        .collect();
+
+    ConversationComparison {
+        total,
+        offset,
+        has_more: offset.saturating_add(limit) < total,
+        pairs,
+    }
+}
+
+fn build_pair(
+    index: usize,
+    left: Option<(usize, &NormalizedEntry)>,
+    right: Option<(usize, &NormalizedEntry)>,
+) -> AlignedEntryPair {
+    let (kind, text_diff) = match (left, right) {
+        (Some((_, l)), Some((_, r))) if same_entry(l, r) => (AlignmentKind::Unchanged, None),
+        (Some((_, l)), Some((_, r))) => {
+            let text_diff = matches!(l.entry_type, NormalizedEntryType::AssistantMessage)
+                .then(|| word_diff(&l.content, &r.content));
+            (AlignmentKind::Changed, text_diff)
+        }
+        (Some(_), None) => (AlignmentKind::Removed, None),
+        (None, _) => (AlignmentKind::Added, None),
+    };
+
+    AlignedEntryPair {
+        index,
+        kind,
+        left_index: left.map(|(i, _)| i),
+        right_index: right.map(|(i, _)| i),
+        left: left.map(|(_, e)| e.clone()),
+        right: right.map(|(_, e)| e.clone()),
+        text_diff,
+    }
+}
+
+fn is_comparable(entry: &NormalizedEntry) -> bool {
+    !matches!(
+        entry.entry_type,
+        NormalizedEntryType::Loading | NormalizedEntryType::NextAction { .. }
+    )
+}
+
+fn same_entry(left: &NormalizedEntry, right: &NormalizedEntry) -> bool {
+    left.content == right.content
+        && serde_json::to_value(&left.entry_type).ok()
+            == serde_json::to_value(&right.entry_type).ok()
+}
+
+/// Key used to decide whether two entries are "the same step". Messages match
+/// on their kind alone so that rewording shows up as a change; tool calls also
+/// need the same tool, action and target.
+fn fingerprint(entry: &NormalizedEntry) -> String {
+    match &entry.entry_type {
+        NormalizedEntryType::ToolUse {
+            tool_name,
+            action_type,
+            ..
+        } => format!("tool:{tool_name}:{}", action_fingerprint(action_type)),
+        NormalizedEntryType::UserFeedback { denied_tool } => format!("user_feedback:{denied_tool}"),
+        NormalizedEntryType::ErrorMessage { .. } => "error_message".to_string(),
+        NormalizedEntryType::NextAction { .. } => "next_action".to_string(),
+        NormalizedEntryType::UserMessage => "user_message".to_string(),
+        NormalizedEntryType::AssistantMessage => "assistant_message".to_string(),
+        NormalizedEntryType::SystemMessage => "system_message".to_string(),
+        NormalizedEntryType::Thinking => "thinking".to_string(),
+        NormalizedEntryType::Loading => "loading".to_string(),
+    }
+}
+
+fn action_fingerprint(action: &ActionType) -> String {
+    match action {
+        ActionType::FileRead { path } => format!("file_read:{}", normalize_path(path)),
+        ActionType::FileEdit { path, .. } => format!("file_edit:{}", normalize_path(path)),
+        ActionType::FileDelete { path } => format!("file_delete:{}", normalize_path(path)),
+        ActionType::FileMove { from, to } => {
+            format!("file_move:{}:{}", normalize_path(from), normalize_path(to))
+        }
+        ActionType::CommandRun { command, .. } => {
+            format!("command_run:{}", normalize_command(command))
+        }
+        ActionType::Search { query } => format!("search:{}", query.trim()),
+        ActionType::WebFetch { url } => format!("web_fetch:{}", url.trim()),
+        ActionType::Tool { tool_name, .. } => format!("tool:{tool_name}"),
+        ActionType::TaskCreate { .. } => "task_create".to_string(),
+        ActionType::PlanPresentation { .. } => "plan_presentation".to_string(),
+        ActionType::TodoManagement { operation, .. } => format!("todo_management:{operation}"),
+        ActionType::Other { .. } => "other".to_string(),
+    }
+}
+
+fn normalize_path(path: &str) -> String {
+    let path = path.trim().replace('\\', "/");
+    let path = path.strip_prefix("./").unwrap_or(&path);
+    path.trim_end_matches('/').to_string()
+}
+
+fn normalize_command(command: &str) -> String {
+    command.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Pair up equal keys, keeping order. Rows are `(left, right)` index pairs
+/// with `None` on the side an entry is missing from.
+fn align<T: PartialEq>(left: &[T], right: &[T]) -> Vec<(Option<usize>, Option<usize>)> {
+    let prefix = left
+        .iter()
+        .zip(right.iter())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let suffix = left[prefix..]
+        .iter()
+        .rev()
+        .zip(right[prefix..].iter().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let left_mid = &left[prefix..left.len() - suffix];
+    let right_mid = &right[prefix..right.len() - suffix];
+
+    let mut rows: Vec<_> = (0..prefix).map(|i| (Some(i), Some(i))).collect();
+
+    if left_mid.len().saturating_mul(right_mid.len()) > MAX_LCS_CELLS {
+        let len = left_mid.len().max(right_mid.len());
+        rows.extend((0..len).map(|i| {
+            (
+                (i < left_mid.len()).then_some(prefix + i),
+                (i < right_mid.len()).then_some(prefix + i),
+            )
+        }));
+    } else {
+        rows.extend(
+            lcs_rows(left_mid, right_mid)
+                .into_iter()
+                .map(|(l, r)| (l.map(|i| prefix + i), r.map(|i| prefix + i))),
+        );
+    }
+
+    rows.extend((0..suffix).map(|i| {
+        (
+            Some(left.len() - suffix + i),
+            Some(right.len() - suffix + i),
+        )
+    }));
+    rows
+}
+
+fn lcs_rows<T: PartialEq>(left: &[T], right: &[T]) -> Vec<(Option<usize>, Option<usize>)> {
+    let (n, m) = (left.len(), right.len());
+    // lengths[i][j] = LCS length of left[i..] and right[j..]
+    let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[at(i, j)] = if left[i] == right[j] {
+                lengths[at(i + 1, j + 1)] + 1
+            } else {
+                lengths[at(i + 1, j)].max(lengths[at(i, j + 1)])
+            };
+        }
+    }
+
+    let mut rows = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if left[i] == right[j] {
+            rows.push((Some(i), Some(j)));
+            i += 1;
+            j += 1;
+        } else if lengths[at(i + 1, j)] >= lengths[at(i, j + 1)] {
+            rows.push((Some(i), None));
+            i += 1;
+        } else {
+            rows.push((None, Some(j)));
+            j += 1;
+        }
+    }
+    rows.extend((i..n).map(|i| (Some(i), None)));
+    rows.extend((j..m).map(|j| (None, Some(j))));
+    rows
+}
+
+/// Word-level diff of two texts. Whitespace stays attached to the preceding
+/// word so joining the segments reproduces either side exactly.
+pub fn word_diff(left: &str, right: &str) -> Vec<WordDiffSegment> {
+    let left_words = split_words(left);
+    let right_words = split_words(right);
+
+    let mut segments: Vec<WordDiffSegment> = Vec::new();
+    let mut push = |kind: WordDiffKind, text: &str| match segments.last_mut() {
+        Some(last) if last.kind == kind => last.text.push_str(text),
+        _ => segments.push(WordDiffSegment {
+            kind,
+            text: text.to_string(),
+        }),
+    };
+
+    for (l, r) in align(&left_words, &right_words) {
+        match (l, r) {
+            (Some(i), Some(_)) => push(WordDiffKind::Equal, left_words[i]),
+            (Some(i), None) => push(WordDiffKind::Delete, left_words[i]),
+            (None, Some(j)) => push(WordDiffKind::Insert, right_words[j]),
+            (None, None) => {}
+        }
+    }
+    segments
+}
+
+fn split_words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut in_space = false;
+    for (i, c) in text.char_indices() {
+        if c.is_whitespace() {
+            in_space = true;
+        } else if in_space {
+            words.push(&text[start..i]);
+            start = i;
+            in_space = false;
+        }
+    }
+    if start < text.len() {
+        words.push(&text[start..]);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::logs::{ToolStatus, utils::ConversationPatch};
+
+    use super::*;
+
+    fn message(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn tool(action_type: ActionType, content: &str) -> NormalizedEntry {
+        message(
+            NormalizedEntryType::ToolUse {
+                tool_name: "Bash".to_string(),
+                action_type,
+                status: ToolStatus::Success,
+            },
+            content,
+        )
+    }
+
+    fn command(cmd: &str) -> ActionType {
+        ActionType::CommandRun {
+            command: cmd.to_string(),
+            result: None,
+        }
+    }
+
+    fn run_one() -> Vec<NormalizedEntry> {
+        vec![
+            message(NormalizedEntryType::UserMessage, "Fix the failing test"),
+            tool(
+                ActionType::FileRead {
+                    path: "./src/lib.rs".to_string(),
+                },
+                "src/lib.rs",
+            ),
+            tool(command("cargo  test"), "cargo test"),
+            message(
+                NormalizedEntryType::AssistantMessage,
+                "The test now passes after fixing the off-by-one error.",
+            ),
+        ]
+    }
+
+    fn run_two() -> Vec<NormalizedEntry> {
+        vec![
+            message(NormalizedEntryType::UserMessage, "Fix the failing test"),
+            tool(
+                ActionType::FileRead {
+                    path: "src/lib.rs".to_string(),
+                },
+                "src/lib.rs",
+            ),
+            tool(command("cargo test --workspace"), "cargo test --workspace"),
+            message(
+                NormalizedEntryType::AssistantMessage,
+                "All tests now pass after fixing the off-by-one error.",
+            ),
+        ]
+    }
+
+    #[test]
+    fn aligns_runs_differing_in_one_tool_call_and_one_message() {
+        let result = compare(&run_one(), &run_two(), 0, 100);
+        let rows: Vec<_> = result
+            .pairs
+            .iter()
+            .map(|p| (p.kind, p.left_index, p.right_index))
+            .collect();
+
+        assert_eq!(
+            rows,
+            vec![
+                (AlignmentKind::Unchanged, Some(0), Some(0)),
+                // "./src/lib.rs" and "src/lib.rs" are the same file
+                (AlignmentKind::Unchanged, Some(1), Some(1)),
+                (AlignmentKind::Removed, Some(2), None),
+                (AlignmentKind::Added, None, Some(2)),
+                (AlignmentKind::Changed, Some(3), Some(3)),
+            ]
+        );
+        assert_eq!(result.total, 5);
+        assert!(!result.has_more);
+
+        let diff = result.pairs[4].text_diff.as_ref().unwrap();
+        assert_eq!(
+            diff,
+            &vec![
+                WordDiffSegment {
+                    kind: WordDiffKind::Delete,
+                    text: "The test ".to_string(),
+                },
+                WordDiffSegment {
+                    kind: WordDiffKind::Insert,
+                    text: "All tests ".to_string(),
+                },
+                WordDiffSegment {
+                    kind: WordDiffKind::Equal,
+                    text: "now ".to_string(),
+                },
+                WordDiffSegment {
+                    kind: WordDiffKind::Delete,
+                    text: "passes ".to_string(),
+                },
+                WordDiffSegment {
+                    kind: WordDiffKind::Insert,
+                    text: "pass ".to_string(),
+                },
+                WordDiffSegment {
+                    kind: WordDiffKind::Equal,
+                    text: "after fixing the off-by-one error.".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn paginates_aligned_rows() {
+        let result = compare(&run_one(), &run_two(), 2, 2);
+        assert_eq!(result.total, 5);
+        assert!(result.has_more);
+        assert_eq!(
+            result.pairs.iter().map(|p| p.index).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+    }
+
+    #[test]
+    fn rebuilds_entries_from_patches() {
+        let entries = run_one();
+        let mut messages: Vec<LogMsg> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| LogMsg::JsonPatch(ConversationPatch::add_normalized_entry(i, e.clone())))
+            .collect();
+        messages.push(LogMsg::JsonPatch(ConversationPatch::replace(
+            3,
+            message(NormalizedEntryType::AssistantMessage, "Done."),
+        )));
+
+        let rebuilt = entries_from_patches(messages);
+        assert_eq!(rebuilt.len(), 4);
+        assert_eq!(rebuilt[3].content, "Done.");
+    }
+}
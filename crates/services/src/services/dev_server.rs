@@ -0,0 +1,488 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::Utc;
+use db::{
+    DBService,
+    models::{execution_process::ExecutionProcess, project::Project},
+};
+use futures::StreamExt;
+use regex::Regex;
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use ts_rs::TS;
+use utils::{log_msg::LogMsg, msg_store::MsgStore};
+use uuid::Uuid;
+
+lazy_static::lazy_static! {
+    static ref ANSI_ESCAPE: Regex = Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]").unwrap();
+    static ref BUILTIN_URL_PATTERNS: Vec<Regex> = [
+        // vite: "  ➜  Local:   http://localhost:5173/"
+        // next: "   - Local:        http://localhost:3000"
+        r"(?i)\blocal:\s+(?P<url>https?://\S+)",
+        // next (<= 13): "ready - started server on 0.0.0.0:3000, url: http://localhost:3000"
+        r"(?i)started server on .*\burl:\s*(?P<url>https?://\S+)",
+        // rails / puma: "* Listening on http://127.0.0.1:3000"
+        r"(?i)\blistening on:?\s+(?P<url>https?://\S+)",
+        // anything else that prints a loopback URL with a port
+        r"(?P<url>https?://(?:localhost|127\.0\.0\.1|0\.0\.0\.0|\[::1?\]):\d+\S*)",
+    ]
+    .iter()
+    .map(|p| Regex::new(p).unwrap())
+    .collect();
+    static ref PORT_CONFLICT: Regex = Regex::new(
+        r"(?i)EADDRINUSE|address already in use|port \d+ is already in use"
+    )
+    .unwrap();
+}
+
+/// Extracts the listen URL and port conflicts from dev server output lines.
+///
+/// Custom patterns are tried before the built-in vite/next/rails/generic ones.
+/// A pattern should expose the URL as a named `url` capture group; otherwise
+/// the whole match is used.
+#[derive(Debug, Clone, Default)]
+pub struct DevServerOutputMatcher {
+    extra_patterns: Vec<Regex>,
+}
+
+impl DevServerOutputMatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_patterns(patterns: impl IntoIterator<Item = Regex>) -> Self {
+        Self {
+            extra_patterns: patterns.into_iter().collect(),
+        }
+    }
+
+    /// Matcher using the project's custom pattern, if it has a valid one.
+    pub fn for_project(project: &Project) -> Self {
+        let Some(pattern) = project.dev_server_url_pattern.as_deref() else {
+            return Self::new();
+        };
+        match Regex::new(pattern) {
+            Ok(re) => Self::with_patterns([re]),
+            Err(err) => {
+                tracing::warn!(
+                    "Ignoring dev server URL pattern for project {}: {}",
+                    project.id,
+                    err
+                );
+                Self::new()
+            }
+        }
+    }
+
+    /// Returns the URL announced on this line, if any.
+    pub fn detect_url(&self, line: &str) -> Option<String> {
+        let line = ANSI_ESCAPE.replace_all(line, "");
+        self.extra_patterns
+            .iter()
+            .chain(BUILTIN_URL_PATTERNS.iter())
+            .find_map(|re| {
+                let caps = re.captures(&line)?;
+                let url = caps.name("url").or_else(|| caps.get(0))?.as_str();
+                Some(normalize_url(url))
+            })
+    }
+
+    /// Returns true if this line reports that the listen port is taken.
+    pub fn is_port_conflict(&self, line: &str) -> bool {
+        PORT_CONFLICT.is_match(&ANSI_ESCAPE.replace_all(line, ""))
+    }
+}
+
+/// Normalize a project's dev server URL pattern. Blank clears it; anything
+/// else must compile as a regex.
+pub fn normalize_url_pattern(value: Option<String>) -> Result<Option<String>, String> {
+    match value.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(pattern) => Regex::new(pattern)
+            .map(|_| Some(pattern.to_string()))
+            .map_err(|err| format!("Invalid dev server URL pattern: {err}")),
+    }
+}
+
+/// Trim trailing punctuation picked up from prose and swap the wildcard
+/// address for one a browser can open.
+fn normalize_url(url: &str) -> String {
+    url.trim_end_matches([',', '.', ';', ')', ']', '"', '\''])
+        .replacen("://0.0.0.0", "://localhost", 1)
+}
+
+/// Lines sent to a log tail subscriber on connect when it does not ask for a
+/// specific amount.
+pub const DEFAULT_TAIL_LINES: usize = 200;
+
+/// Keep only the last `limit` stdout/stderr lines of `history`, one line per
+/// message with line endings preserved.
+pub fn tail_lines(history: impl IntoIterator<Item = LogMsg>, limit: usize) -> VecDeque<LogMsg> {
+    let mut ring = VecDeque::with_capacity(limit.min(DEFAULT_TAIL_LINES));
+    if limit == 0 {
+        return ring;
+    }
+
+    for msg in history {
+        let (text, is_stderr) = match msg {
+            LogMsg::Stdout(text) => (text, false),
+            LogMsg::Stderr(text) => (text, true),
+            _ => continue,
+        };
+        for line in text.split_inclusive('\n') {
+            if ring.len() == limit {
+                ring.pop_front();
+            }
+            ring.push_back(if is_stderr {
+                LogMsg::Stderr(line.to_string())
+            } else {
+                LogMsg::Stdout(line.to_string())
+            });
+        }
+    }
+    ring
+}
+
+/// Watch a dev server's output and record the first detected URL and any
+/// port conflict on its execution process row.
+pub fn spawn_output_watcher(
+    db: DBService,
+    execution_id: Uuid,
+    msg_store: Arc<MsgStore>,
+    matcher: DevServerOutputMatcher,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut lines = futures::stream::select(
+            msg_store.stdout_lines_stream(),
+            msg_store.stderr_lines_stream(),
+        );
+        let mut url_found = false;
+        let mut conflict_found = false;
+
+        while let Some(Ok(line)) = lines.next().await {
+            if !url_found && let Some(url) = matcher.detect_url(&line) {
+                url_found = true;
+                if let Err(e) =
+                    ExecutionProcess::update_dev_server_url(&db.pool, execution_id, &url).await
+                {
+                    tracing::error!(
+                        "Failed to store dev server url for execution {}: {}",
+                        execution_id,
+                        e
+                    );
+                }
+            }
+
+            if !conflict_found && matcher.is_port_conflict(&line) {
+                conflict_found = true;
+                if let Err(e) =
+                    ExecutionProcess::set_dev_server_port_conflict(&db.pool, execution_id, true)
+                        .await
+                {
+                    tracing::error!(
+                        "Failed to flag dev server port conflict for execution {}: {}",
+                        execution_id,
+                        e
+                    );
+                }
+            }
+
+            if url_found && conflict_found {
+                break;
+            }
+        }
+    })
+}
+
+/// Unexpected exits in a row after which a dev server is left stopped
+pub const MAX_DEV_SERVER_RESTARTS: u32 = 5;
+const RESTART_BASE_DELAY: Duration = Duration::from_secs(1);
+const RESTART_MAX_DELAY: Duration = Duration::from_secs(30);
+/// A dev server that stayed up this long is considered healthy again, so a
+/// later crash starts the backoff over
+const STABLE_RUN: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum DevServerState {
+    Running,
+    /// Exited unexpectedly and waiting to be started again
+    Restarting,
+    /// Kept exiting; no further restarts until it is started by hand
+    CrashLoop,
+    Stopped,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DevServerStatus {
+    pub state: DevServerState,
+    pub task_attempt_id: Option<Uuid>,
+    pub execution_process_id: Option<Uuid>,
+    pub pid: Option<u32>,
+    pub url: Option<String>,
+    pub port_conflict: bool,
+    pub uptime_secs: Option<i64>,
+    /// Restarts since the dev server was last started by hand or ran
+    /// steadily
+    pub restart_count: u32,
+    pub last_exit_code: Option<i64>,
+}
+
+/// How long the process has been running, or ran before it exited.
+pub fn running_time(process: &ExecutionProcess) -> Duration {
+    let end = process.completed_at.unwrap_or_else(Utc::now);
+    (end - process.started_at).to_std().unwrap_or_default()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupervisedDevServer {
+    pub task_attempt_id: Uuid,
+    pub execution_process_id: Uuid,
+    pub state: DevServerState,
+    pub restart_count: u32,
+    pub last_exit_code: Option<i64>,
+}
+
+/// What to do after a supervised dev server exited on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevServerExit {
+    /// Start it again after the delay
+    Restart(Duration),
+    /// Too many restarts; the project is now in [`DevServerState::CrashLoop`]
+    GiveUp,
+    /// Not the project's current dev server; it was stopped or replaced
+    Ignore,
+}
+
+/// The dev server each project is supposed to be running, used to restart
+/// it when it exits unexpectedly. Only one dev server runs per project.
+#[derive(Debug, Clone, Default)]
+pub struct DevServerRegistry {
+    servers: Arc<Mutex<HashMap<Uuid, SupervisedDevServer>>>,
+}
+
+impl DevServerRegistry {
+    pub fn get(&self, project_id: Uuid) -> Option<SupervisedDevServer> {
+        self.servers.lock().unwrap().get(&project_id).cloned()
+    }
+
+    /// Record a newly started dev server. A restart keeps counting towards
+    /// the crash loop limit; a start by hand resets it.
+    pub fn started(
+        &self,
+        project_id: Uuid,
+        task_attempt_id: Uuid,
+        execution_process_id: Uuid,
+        restart: bool,
+    ) {
+        let mut servers = self.servers.lock().unwrap();
+        let previous = servers.get(&project_id);
+        let (restart_count, last_exit_code) = match previous {
+            Some(previous) if restart => (previous.restart_count, previous.last_exit_code),
+            _ => (0, None),
+        };
+        servers.insert(
+            project_id,
+            SupervisedDevServer {
+                task_attempt_id,
+                execution_process_id,
+                state: DevServerState::Running,
+                restart_count,
+                last_exit_code,
+            },
+        );
+    }
+
+    /// Forget the project's dev server so a pending restart is dropped.
+    pub fn stopped(&self, project_id: Uuid) {
+        self.servers.lock().unwrap().remove(&project_id);
+    }
+
+    /// Decide how to handle `execution_process_id` exiting by itself after
+    /// running for `ran_for`.
+    pub fn exited(
+        &self,
+        project_id: Uuid,
+        execution_process_id: Uuid,
+        exit_code: Option<i64>,
+        ran_for: Duration,
+    ) -> DevServerExit {
+        let mut servers = self.servers.lock().unwrap();
+        let Some(server) = servers
+            .get_mut(&project_id)
+            .filter(|server| server.execution_process_id == execution_process_id)
+        else {
+            return DevServerExit::Ignore;
+        };
+        server.last_exit_code = exit_code;
+        if ran_for >= STABLE_RUN {
+            server.restart_count = 0;
+        }
+        if server.restart_count >= MAX_DEV_SERVER_RESTARTS {
+            server.state = DevServerState::CrashLoop;
+            return DevServerExit::GiveUp;
+        }
+        let delay = RESTART_BASE_DELAY
+            .saturating_mul(2u32.saturating_pow(server.restart_count))
+            .min(RESTART_MAX_DELAY);
+        server.restart_count += 1;
+        server.state = DevServerState::Restarting;
+        DevServerExit::Restart(delay)
+    }
+
+    /// Whether the restart scheduled when `execution_process_id` exited is
+    /// still wanted, i.e. nobody stopped or replaced the dev server since.
+    pub fn restart_pending(&self, project_id: Uuid, execution_process_id: Uuid) -> bool {
+        self.get(project_id).is_some_and(|server| {
+            server.execution_process_id == execution_process_id
+                && server.state == DevServerState::Restarting
+        })
+    }
+
+    /// Leave the project's dev server stopped after a failed restart.
+    pub fn restart_failed(&self, project_id: Uuid) {
+        if let Some(server) = self.servers.lock().unwrap().get_mut(&project_id) {
+            server.state = DevServerState::CrashLoop;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VITE_OUTPUT: &str = "\n> web@0.0.0 dev\n> vite\n\n\
+        \x1b[32m\x1b[1mVITE\x1b[22m v5.4.10\x1b[39m  \x1b[2mready in \x1b[0m\x1b[1m312\x1b[22m\x1b[2m\x1b[0m ms\x1b[22m\n\n  \
+        \x1b[32m➜\x1b[39m  \x1b[1mLocal\x1b[22m:   \x1b[36mhttp://localhost:\x1b[1m5173\x1b[22m/\x1b[39m\n  \
+        \x1b[32m➜\x1b[39m  \x1b[1mNetwork\x1b[22m\x1b[2m: use \x1b[22m\x1b[1m--host\x1b[22m\x1b[2m to expose\x1b[22m\n";
+
+    const NEXT_OUTPUT: &str = "\n> app@0.1.0 dev\n> next dev\n\n   \
+        ▲ Next.js 14.2.5\n   - Local:        http://localhost:3000\n   - Environments: .env.local\n\n \
+        ✓ Starting...\n ✓ Ready in 1843ms\n";
+
+    const NEXT_LEGACY_OUTPUT: &str =
+        "ready - started server on 0.0.0.0:3000, url: http://localhost:3000\n";
+
+    const CONFLICT_OUTPUT: &str = "\n> app@0.1.0 dev\n> node server.js\n\n\
+        node:events:497\n      throw er; // Unhandled 'error' event\n      ^\n\n\
+        Error: listen EADDRINUSE: address already in use :::3000\n    \
+        at Server.setupListenHandle [as _listen2] (node:net:1872:16)\n";
+
+    fn first_url(output: &str) -> Option<String> {
+        let matcher = DevServerOutputMatcher::new();
+        output.lines().find_map(|l| matcher.detect_url(l))
+    }
+
+    #[test]
+    fn detects_vite_url_through_ansi() {
+        assert_eq!(
+            first_url(VITE_OUTPUT).as_deref(),
+            Some("http://localhost:5173/")
+        );
+    }
+
+    #[test]
+    fn detects_next_url() {
+        assert_eq!(
+            first_url(NEXT_OUTPUT).as_deref(),
+            Some("http://localhost:3000")
+        );
+        assert_eq!(
+            first_url(NEXT_LEGACY_OUTPUT).as_deref(),
+            Some("http://localhost:3000")
+        );
+    }
+
+    #[test]
+    fn detects_rails_url_and_rewrites_wildcard_host() {
+        assert_eq!(
+            first_url("* Listening on http://0.0.0.0:3000\n").as_deref(),
+            Some("http://localhost:3000")
+        );
+    }
+
+    #[test]
+    fn detects_port_conflict() {
+        let matcher = DevServerOutputMatcher::new();
+        assert!(CONFLICT_OUTPUT.lines().any(|l| matcher.is_port_conflict(l)));
+        assert!(first_url(CONFLICT_OUTPUT).is_none());
+        // vite falls back to the next port by itself, so this is not a conflict
+        assert!(!matcher.is_port_conflict("Port 5173 is in use, trying another one..."));
+    }
+
+    #[test]
+    fn tail_keeps_last_lines_with_ansi() {
+        let history = vec![
+            LogMsg::Stdout("one\ntwo\n".to_string()),
+            LogMsg::Finished,
+            LogMsg::Stderr("\x1b[31mthree\x1b[0m\nfour".to_string()),
+        ];
+        let tail: Vec<String> = tail_lines(history, 3)
+            .into_iter()
+            .map(|msg| match msg {
+                LogMsg::Stdout(s) => format!("out:{s}"),
+                LogMsg::Stderr(s) => format!("err:{s}"),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(
+            tail,
+            vec!["out:two\n", "err:\x1b[31mthree\x1b[0m\n", "err:four"]
+        );
+    }
+
+    #[test]
+    fn restarts_back_off_until_the_crash_loop_limit() {
+        let registry = DevServerRegistry::default();
+        let (project, attempt) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut process = Uuid::new_v4();
+        registry.started(project, attempt, process, false);
+
+        let mut delays = Vec::new();
+        while let DevServerExit::Restart(delay) =
+            registry.exited(project, process, Some(1), Duration::from_secs(2))
+        {
+            assert!(registry.restart_pending(project, process));
+            delays.push(delay.as_secs());
+            process = Uuid::new_v4();
+            registry.started(project, attempt, process, true);
+        }
+        assert_eq!(delays, [1, 2, 4, 8, 16]);
+        let server = registry.get(project).unwrap();
+        assert_eq!(server.state, DevServerState::CrashLoop);
+        assert_eq!(server.restart_count, MAX_DEV_SERVER_RESTARTS);
+
+        // Starting by hand clears the crash loop; a stopped or replaced
+        // process is not restarted
+        registry.started(project, attempt, process, false);
+        assert_eq!(registry.get(project).unwrap().restart_count, 0);
+        assert_eq!(
+            registry.exited(project, Uuid::new_v4(), Some(1), Duration::ZERO),
+            DevServerExit::Ignore
+        );
+        registry.stopped(project);
+        assert_eq!(
+            registry.exited(project, process, Some(1), Duration::ZERO),
+            DevServerExit::Ignore
+        );
+    }
+
+    #[test]
+    fn custom_patterns_take_precedence() {
+        let matcher = DevServerOutputMatcher::with_patterns([Regex::new(
+            r"serving at (?P<url>https?://\S+)",
+        )
+        .unwrap()]);
+        assert_eq!(
+            matcher
+                .detect_url("serving at http://dev.test:8080")
+                .as_deref(),
+            Some("http://dev.test:8080")
+        );
+    }
+}
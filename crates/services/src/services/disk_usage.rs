@@ -0,0 +1,269 @@
+//! Disk usage of attempt worktrees.
+//!
+//! A background job walks every remaining worktree, one project at a time,
+//! and stores its size on the attempt. Worktrees with a running process are
+//! skipped and keep their previous size, so the scan does not compete with
+//! an agent that is busy writing. When a project's worktrees together grow
+//! past the configured threshold, a notification is sent.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use db::{
+    DBService,
+    models::{
+        project::Project,
+        task_attempt::{TaskAttempt, WorktreeDiskUsage},
+    },
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::services::{config::Config, notification::NotificationService};
+
+pub const DEFAULT_WORKTREE_DISK_WARNING_GB: u32 = 10;
+/// Longest a single worktree is walked before its size is reported as a
+/// lower bound
+const MAX_SCAN_TIME: Duration = Duration::from_secs(30);
+const BYTES_PER_GB: u64 = 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirectorySize {
+    pub bytes: u64,
+    /// The walk stopped at the deadline
+    pub truncated: bool,
+}
+
+/// Total size of the files under `root`, like `du -s`. Symlinks count as
+/// links and are never followed, so a link to a large directory, or a link
+/// loop, cannot inflate the total. Stops at `deadline`.
+pub fn directory_size(root: &Path, deadline: Instant) -> DirectorySize {
+    let mut size = DirectorySize {
+        bytes: 0,
+        truncated: false,
+    };
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        if Instant::now() >= deadline {
+            size.truncated = true;
+            break;
+        }
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            // Does not traverse symlinks
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                size.bytes += metadata.len();
+            }
+        }
+    }
+    size
+}
+
+/// Combined size of a project's worktrees as of their last scans.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProjectDiskUsage {
+    pub total_bytes: i64,
+    /// When the least recently scanned worktree was scanned; `None` before
+    /// the first scan
+    pub computed_at: Option<DateTime<Utc>>,
+    /// Largest first
+    pub worktrees: Vec<WorktreeDiskUsage>,
+}
+
+impl ProjectDiskUsage {
+    pub async fn load(pool: &SqlitePool, project_id: Uuid) -> Result<Self, sqlx::Error> {
+        let worktrees = TaskAttempt::worktree_disk_usage_by_project(pool, project_id).await?;
+        Ok(Self {
+            total_bytes: worktrees.iter().map(|worktree| worktree.bytes).sum(),
+            computed_at: worktrees.iter().map(|worktree| worktree.computed_at).min(),
+            worktrees,
+        })
+    }
+}
+
+/// Measure each idle worktree of the project, one after another.
+pub async fn scan_project(pool: &SqlitePool, project_id: Uuid) -> Result<(), sqlx::Error> {
+    for candidate in TaskAttempt::find_disk_scan_candidates(pool, project_id).await? {
+        if candidate.is_active {
+            tracing::debug!(
+                "Skipping disk usage scan of active attempt {}",
+                candidate.attempt_id
+            );
+            continue;
+        }
+        let path = PathBuf::from(&candidate.container_ref);
+        if !path.is_dir() {
+            continue;
+        }
+        let size = match tokio::task::spawn_blocking(move || {
+            directory_size(&path, Instant::now() + MAX_SCAN_TIME)
+        })
+        .await
+        {
+            Ok(size) => size,
+            Err(e) => {
+                tracing::warn!(
+                    "Disk usage scan of attempt {} failed: {}",
+                    candidate.attempt_id,
+                    e
+                );
+                continue;
+            }
+        };
+        let bytes = i64::try_from(size.bytes).unwrap_or(i64::MAX);
+        TaskAttempt::record_worktree_disk_usage(pool, candidate.attempt_id, bytes, size.truncated)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Scans every project and warns about those over the threshold. A project
+/// is warned about once, and again only after it has dropped below the
+/// threshold in between.
+#[derive(Debug, Default)]
+pub struct DiskUsageMonitor {
+    warned: HashSet<Uuid>,
+}
+
+impl DiskUsageMonitor {
+    pub async fn scan_all_projects(&mut self, db: &DBService, config: &Config) {
+        let projects = match Project::find_all(&db.pool).await {
+            Ok(projects) => projects,
+            Err(e) => {
+                tracing::error!("Failed to load projects for disk usage scan: {}", e);
+                return;
+            }
+        };
+        for project in projects.iter().filter(|project| !project.is_archived()) {
+            if let Err(e) = scan_project(&db.pool, project.id).await {
+                tracing::error!(
+                    "Failed to scan worktree disk usage of project {}: {}",
+                    project.id,
+                    e
+                );
+                continue;
+            }
+            let usage = match ProjectDiskUsage::load(&db.pool, project.id).await {
+                Ok(usage) => usage,
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to load worktree disk usage of project {}: {}",
+                        project.id,
+                        e
+                    );
+                    continue;
+                }
+            };
+            if self.should_warn(
+                project.id,
+                usage.total_bytes,
+                config.worktree_disk_warning_gb,
+            ) {
+                NotificationService::notify(
+                    config.notifications.clone(),
+                    &format!("Worktrees of {} are large", project.name),
+                    &format!(
+                        "Attempt worktrees use {}, above the {} GB warning threshold. \
+                         Cleaning up finished attempts frees the space.",
+                        format_gb(usage.total_bytes),
+                        config.worktree_disk_warning_gb
+                    ),
+                )
+                .await;
+            }
+        }
+    }
+
+    /// `threshold_gb` of 0 disables warnings.
+    fn should_warn(&mut self, project_id: Uuid, total_bytes: i64, threshold_gb: u32) -> bool {
+        let threshold = u64::from(threshold_gb).saturating_mul(BYTES_PER_GB);
+        let over = threshold_gb > 0 && u64::try_from(total_bytes).unwrap_or(0) > threshold;
+        if over {
+            self.warned.insert(project_id)
+        } else {
+            self.warned.remove(&project_id);
+            false
+        }
+    }
+}
+
+fn format_gb(bytes: i64) -> String {
+    format!("{:.1} GB", bytes as f64 / BYTES_PER_GB as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn far_deadline() -> Instant {
+        Instant::now() + Duration::from_secs(60)
+    }
+
+    #[test]
+    fn sums_nested_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), vec![0u8; 100]).unwrap();
+        std::fs::create_dir_all(dir.path().join("node_modules/pkg")).unwrap();
+        std::fs::write(dir.path().join("node_modules/pkg/index.js"), vec![0u8; 250]).unwrap();
+
+        assert_eq!(
+            directory_size(dir.path(), far_deadline()),
+            DirectorySize {
+                bytes: 350,
+                truncated: false
+            }
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn does_not_follow_symlinks() {
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("big.bin"), vec![0u8; 10_000]).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), vec![0u8; 100]).unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("linked")).unwrap();
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("loop")).unwrap();
+
+        let size = directory_size(dir.path(), far_deadline());
+        assert!(size.bytes < 1_000, "counted {} bytes", size.bytes);
+        assert!(!size.truncated);
+    }
+
+    #[test]
+    fn stops_at_the_deadline() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), vec![0u8; 100]).unwrap();
+
+        let size = directory_size(dir.path(), Instant::now());
+        assert!(size.truncated);
+    }
+
+    #[test]
+    fn warns_once_until_usage_drops_below_the_threshold() {
+        let mut monitor = DiskUsageMonitor::default();
+        let project_id = Uuid::new_v4();
+        let gb = BYTES_PER_GB as i64;
+
+        assert!(!monitor.should_warn(project_id, 5 * gb, 10));
+        assert!(monitor.should_warn(project_id, 12 * gb, 10));
+        assert!(!monitor.should_warn(project_id, 13 * gb, 10));
+        assert!(!monitor.should_warn(project_id, 2 * gb, 10));
+        assert!(monitor.should_warn(project_id, 11 * gb, 10));
+        // 0 disables warnings
+        assert!(!monitor.should_warn(Uuid::new_v4(), 100 * gb, 0));
+    }
+}
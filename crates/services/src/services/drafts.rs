@@ -235,7 +235,7 @@ impl DraftsService {
             .ok_or(SqlxError::RowNotFound)
             .map_err(DraftsServiceError::from)?;
 
-        let cleanup_action = container.cleanup_action(project.cleanup_script);
+        let post_agent_action = container.post_agent_action(&project);
 
         let mut prompt = draft.prompt.clone();
         if let Some(image_ids) = &draft.image_ids {
@@ -252,6 +252,7 @@ impl DraftsService {
             ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
                 prompt: prompt.clone(),
                 session_id,
+                task_context: Some(task.to_prompt()),
                 executor_profile_id,
             })
         } else {
@@ -263,7 +264,7 @@ impl DraftsService {
             )
         };
 
-        let follow_up_action = ExecutorAction::new(action_type, cleanup_action);
+        let follow_up_action = ExecutorAction::new(action_type, post_agent_action);
 
         let execution_process = container
             .start_execution(
@@ -5,6 +5,7 @@ use db::{
     models::{
         draft::{Draft, DraftType},
         execution_process::ExecutionProcess,
+        project::Project,
         shared_task::SharedTask as SharedDbTask,
         task::Task,
         task_attempt::TaskAttempt,
@@ -184,6 +185,17 @@ impl EventService {
                                     // Deletions handled in preupdate hook for reliable data capture
                                     return;
                                 }
+                                (HookTables::Projects, SqliteOperation::Delete) => return,
+                                (HookTables::Projects, _) => {
+                                    match Project::find_by_rowid(&db.pool, rowid).await {
+                                        Ok(Some(project)) => RecordTypes::Project(project),
+                                        Ok(None) => return,
+                                        Err(e) => {
+                                            tracing::error!("Failed to fetch project: {:?}", e);
+                                            return;
+                                        }
+                                    }
+                                }
                                 (HookTables::SharedTasks, _) => {
                                     match SharedDbTask::find_by_rowid(&db.pool, rowid).await {
                                         Ok(Some(task)) => RecordTypes::SharedTask(task),
@@ -283,17 +295,18 @@ impl EventService {
                                             task.project_id,
                                         )
                                         .await
-                                        && let Some(task_with_status) =
-                                            task_list.into_iter().find(|t| t.id == task.id)
                                     {
-                                        let patch = match hook.operation {
-                                            SqliteOperation::Insert => {
+                                        let patch = match task_list
+                                            .into_iter()
+                                            .find(|t| t.id == task.id)
+                                        {
+                                            // Moved to the trash
+                                            None => task_patch::remove(task.id),
+                                            // `add` also covers a task restored from the
+                                            // trash, which clients no longer have
+                                            Some(task_with_status) => {
                                                 task_patch::add(&task_with_status)
                                             }
-                                            SqliteOperation::Update => {
-                                                task_patch::replace(&task_with_status)
-                                            }
-                                            _ => task_patch::replace(&task_with_status), // fallback
                                         };
                                         msg_store_for_hook.push_patch(patch);
                                         return;
@@ -2,6 +2,7 @@ use anyhow::Error as AnyhowError;
 use db::models::{
     draft::{Draft, DraftType},
     execution_process::ExecutionProcess,
+    project::Project,
     shared_task::SharedTask,
     task::Task,
     task_attempt::TaskAttempt,
@@ -35,6 +36,8 @@ pub enum HookTables {
     Drafts,
     #[strum(to_string = "shared_tasks")]
     SharedTasks,
+    #[strum(to_string = "projects")]
+    Projects,
 }
 
 #[derive(Serialize, Deserialize, TS)]
@@ -46,6 +49,7 @@ pub enum RecordTypes {
     Draft(Draft),
     RetryDraft(Draft),
     SharedTask(SharedTask),
+    Project(Project),
     DeletedTask {
         rowid: i64,
         project_id: Option<Uuid>,
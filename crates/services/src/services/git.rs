@@ -12,11 +12,10 @@ use utils::diff::{Diff, DiffChangeKind, FileDiffDetails};
 
 mod cli;
 
-use cli::{ChangeType, StatusDiffEntry, StatusDiffOptions};
+use cli::{ChangeType, FileDiffStat, StatusDiffEntry, StatusDiffOptions};
 pub use cli::{GitCli, GitCliError};
 
 use super::file_ranker::FileStat;
-use crate::services::github::GitHubRepoInfo;
 
 #[derive(Debug, Error)]
 pub enum GitServiceError {
@@ -47,6 +46,23 @@ pub struct GitService {}
 // their contents omitted from the diff stream to avoid UI crashes.
 const MAX_INLINE_DIFF_BYTES: usize = 2 * 1024 * 1024; // ~2MB
 
+/// One changed file in a diff summary
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DiffFileSummary {
+    pub change: DiffChangeKind,
+    pub path: String,
+    /// Previous path for renames and copies
+    pub old_path: Option<String>,
+    /// Line counts; `None` for binary files
+    pub additions: Option<usize>,
+    pub deletions: Option<usize>,
+    pub binary: bool,
+    /// Unified diff for this file, when patches were requested
+    pub patch: Option<String>,
+    /// True when the patch was dropped to stay within the size cap
+    pub patch_omitted: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 #[ts(rename_all = "snake_case")]
@@ -72,6 +88,22 @@ pub struct HeadInfo {
     pub oid: String,
 }
 
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct CommitSummary {
+    pub sha: String,
+    pub subject: String,
+}
+
+/// The commits that took a worktree from one HEAD to another.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct CommitRange {
+    /// Reachable from the new HEAD but not the old one, newest first
+    pub commits: Vec<CommitSummary>,
+    /// The new HEAD does not descend from the old one, e.g. after an amend
+    /// or rebase; `commits` then holds the rewritten commits
+    pub history_rewritten: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct Commit(git2::Oid);
 
@@ -407,6 +439,83 @@ impl GitService {
         }
     }
 
+    /// Per-file line counts for `target`, with unified patches when
+    /// `patch_budget` is set. Patches stop being included once their total
+    /// size would exceed the budget. Binary files never carry a patch.
+    pub fn get_diff_summary(
+        &self,
+        target: DiffTarget,
+        patch_budget: Option<usize>,
+    ) -> Result<Vec<DiffFileSummary>, GitServiceError> {
+        let git = GitCli::new();
+        let with_patch = patch_budget.is_some();
+        let files = match target {
+            DiffTarget::Worktree {
+                worktree_path,
+                base_commit,
+            } => git.diff_worktree_files(worktree_path, base_commit, with_patch)?,
+            DiffTarget::Branch {
+                repo_path,
+                branch_name,
+                base_branch,
+            } => git.diff_range_files(
+                repo_path,
+                &format!("{base_branch}...{branch_name}"),
+                with_patch,
+            )?,
+            DiffTarget::Commit {
+                repo_path,
+                commit_sha,
+            } => git.diff_range_files(repo_path, &format!("{commit_sha}^!"), with_patch)?,
+        };
+
+        let mut remaining = patch_budget.unwrap_or(0);
+        Ok(files
+            .into_iter()
+            .map(|file| {
+                let FileDiffStat {
+                    entry,
+                    additions,
+                    deletions,
+                    patch,
+                } = file;
+                let binary = additions.is_none();
+                let patch = patch.filter(|_| !binary);
+                let patch_omitted = patch.as_ref().is_some_and(|p| p.len() > remaining);
+                let patch = match patch {
+                    Some(p) if !patch_omitted => {
+                        remaining -= p.len();
+                        Some(p)
+                    }
+                    _ => None,
+                };
+                DiffFileSummary {
+                    change: Self::change_kind(&entry.change),
+                    path: entry.path,
+                    old_path: entry.old_path,
+                    additions,
+                    deletions,
+                    binary,
+                    patch,
+                    patch_omitted,
+                }
+            })
+            .collect())
+    }
+
+    fn change_kind(change: &ChangeType) -> DiffChangeKind {
+        match change {
+            ChangeType::Added => DiffChangeKind::Added,
+            ChangeType::Deleted => DiffChangeKind::Deleted,
+            ChangeType::Modified => DiffChangeKind::Modified,
+            ChangeType::Renamed => DiffChangeKind::Renamed,
+            ChangeType::Copied => DiffChangeKind::Copied,
+            // Treat type changes and unmerged as modified for now
+            ChangeType::TypeChanged | ChangeType::Unmerged => DiffChangeKind::Modified,
+            ChangeType::Unknown(_) => DiffChangeKind::Modified,
+        }
+    }
+
     /// Convert git2::Diff to our Diff structs
     fn convert_diff_to_file_diffs(
         &self,
@@ -642,16 +751,7 @@ impl GitService {
     /// New Diff format is flattened with change kind, paths, and optional contents.
     fn status_entry_to_diff(repo: &Repository, base_tree: &git2::Tree, e: StatusDiffEntry) -> Diff {
         // Map ChangeType to DiffChangeKind
-        let mut change = match e.change {
-            ChangeType::Added => DiffChangeKind::Added,
-            ChangeType::Deleted => DiffChangeKind::Deleted,
-            ChangeType::Modified => DiffChangeKind::Modified,
-            ChangeType::Renamed => DiffChangeKind::Renamed,
-            ChangeType::Copied => DiffChangeKind::Copied,
-            // Treat type changes and unmerged as modified for now
-            ChangeType::TypeChanged | ChangeType::Unmerged => DiffChangeKind::Modified,
-            ChangeType::Unknown(_) => DiffChangeKind::Modified,
-        };
+        let mut change = Self::change_kind(&e.change);
 
         // Determine old/new paths based on change
         let (old_path_opt, new_path_opt): (Option<String>, Option<String>) = match e.change {
@@ -1048,6 +1148,39 @@ impl GitService {
         Ok(commit.summary().unwrap_or("(no subject)").to_string())
     }
 
+    /// Commits between two HEADs of the same worktree, at most `limit`.
+    pub fn commits_between(
+        &self,
+        repo_path: &Path,
+        before_oid: &str,
+        after_oid: &str,
+        limit: usize,
+    ) -> Result<CommitRange, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let before = git2::Oid::from_str(before_oid)
+            .map_err(|_| GitServiceError::InvalidRepository("Invalid before OID".into()))?;
+        let after = git2::Oid::from_str(after_oid)
+            .map_err(|_| GitServiceError::InvalidRepository("Invalid after OID".into()))?;
+        let history_rewritten = before != after && !repo.graph_descendant_of(after, before)?;
+
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(after)?;
+        revwalk.hide(before)?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+        let mut commits = Vec::new();
+        for oid in revwalk.take(limit) {
+            let commit = repo.find_commit(oid?)?;
+            commits.push(CommitSummary {
+                sha: commit.id().to_string(),
+                subject: commit.summary().unwrap_or("(no subject)").to_string(),
+            });
+        }
+        Ok(CommitRange {
+            commits,
+            history_rewritten,
+        })
+    }
+
     /// Compare two OIDs and return (ahead, behind) counts: how many commits
     /// `from_oid` is ahead of and behind `to_oid`.
     pub fn ahead_behind_commits_by_oid(
@@ -1449,6 +1582,56 @@ impl GitService {
         Ok(())
     }
 
+    /// Names of the local branches starting with `prefix`.
+    pub fn list_local_branches_with_prefix(
+        &self,
+        repo_path: &Path,
+        prefix: &str,
+    ) -> Result<Vec<String>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let mut names = Vec::new();
+        for branch in repo.branches(Some(BranchType::Local))? {
+            let (branch, _) = branch?;
+            if let Some(name) = branch.name()?
+                && name.starts_with(prefix)
+            {
+                names.push(name.to_string());
+            }
+        }
+        Ok(names)
+    }
+
+    /// Whether every commit on the local branch is already reachable from HEAD.
+    pub fn is_branch_merged_into_head(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+    ) -> Result<bool, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let branch_oid = repo
+            .find_branch(branch_name, BranchType::Local)
+            .map_err(|_| GitServiceError::BranchNotFound(branch_name.to_string()))?
+            .get()
+            .peel_to_commit()?
+            .id();
+        let head_oid = repo.head()?.peel_to_commit()?.id();
+        Ok(branch_oid == head_oid || repo.graph_descendant_of(head_oid, branch_oid)?)
+    }
+
+    /// Delete a local branch. Fails if it is checked out in any worktree.
+    pub fn delete_local_branch(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let mut branch = repo
+            .find_branch(branch_name, BranchType::Local)
+            .map_err(|_| GitServiceError::BranchNotFound(branch_name.to_string()))?;
+        branch.delete()?;
+        Ok(())
+    }
+
     /// Return true if a rebase is currently in progress in this worktree.
     pub fn is_rebase_in_progress(&self, worktree_path: &Path) -> Result<bool, GitServiceError> {
         let git = GitCli::new();
@@ -1553,23 +1736,18 @@ impl GitService {
         }
     }
 
-    /// Extract GitHub owner and repo name from git repo path
-    pub fn get_github_repo_info(
-        &self,
-        repo_path: &Path,
-    ) -> Result<GitHubRepoInfo, GitServiceError> {
+    /// URL of the repository's default remote, used to pick its forge
+    pub fn get_remote_url(&self, repo_path: &Path) -> Result<String, GitServiceError> {
         let repo = self.open_repo(repo_path)?;
         let remote_name = self.default_remote_name(&repo);
         let remote = repo.find_remote(&remote_name).map_err(|_| {
             GitServiceError::InvalidRepository(format!("No '{remote_name}' remote found"))
         })?;
 
-        let url = remote
+        remote
             .url()
-            .ok_or_else(|| GitServiceError::InvalidRepository("Remote has no URL".to_string()))?;
-        GitHubRepoInfo::from_remote_url(url).map_err(|e| {
-            GitServiceError::InvalidRepository(format!("Failed to parse remote URL: {e}"))
-        })
+            .map(str::to_string)
+            .ok_or_else(|| GitServiceError::InvalidRepository("Remote has no URL".to_string()))
     }
 
     pub fn get_remote_name_from_branch_name(
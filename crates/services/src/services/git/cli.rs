@@ -71,6 +71,16 @@ pub struct WorktreeEntry {
     pub branch: Option<String>,
 }
 
+/// A status diff entry with its line counts and, if requested, its patch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiffStat {
+    pub entry: StatusDiffEntry,
+    /// `None` for binary files
+    pub additions: Option<usize>,
+    pub deletions: Option<usize>,
+    pub patch: Option<String>,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct StatusDiffOptions {
     pub path_filter: Option<Vec<String>>, // pathspecs to limit diff
@@ -135,15 +145,14 @@ impl GitCli {
         Ok(!out.is_empty())
     }
 
-    /// Diff status vs a base branch using a temporary index (always includes untracked).
-    /// Path filter limits the reported paths.
-    pub fn diff_status(
+    /// Stage everything in the worktree, untracked files included, into a
+    /// temporary index so it can be diffed without touching the real index.
+    /// Returns the directory holding the index (keep it alive while it is
+    /// used) and the environment that points git at it.
+    fn snapshot_index(
         &self,
         worktree_path: &Path,
-        base_commit: &Commit,
-        opts: StatusDiffOptions,
-    ) -> Result<Vec<StatusDiffEntry>, GitCliError> {
-        // Create a temp index file
+    ) -> Result<(tempfile::TempDir, Vec<(OsString, OsString)>), GitCliError> {
         let tmp_dir = tempfile::TempDir::new()
             .map_err(|e| GitCliError::CommandFailed(format!("temp dir create failed: {e}")))?;
         let tmp_index = tmp_dir.path().join("index");
@@ -157,6 +166,132 @@ impl GitCli {
 
         // Stage all in temp index
         let _ = self.git_with_env(worktree_path, ["add", "-A"], &envs)?;
+        Ok((tmp_dir, envs))
+    }
+
+    /// Per-file changes between `base_commit` and the worktree, untracked
+    /// files included, with line counts and optionally the patch text.
+    pub fn diff_worktree_files(
+        &self,
+        worktree_path: &Path,
+        base_commit: &Commit,
+        with_patch: bool,
+    ) -> Result<Vec<FileDiffStat>, GitCliError> {
+        // Every format reads the same snapshot, so entries line up even if
+        // an executor is still writing to the worktree
+        let (_tmp_dir, envs) = self.snapshot_index(worktree_path)?;
+        let base = base_commit.to_string();
+        Self::collect_file_diffs(with_patch, |format| {
+            let args = Self::diff_args(format, &["--cached", base.as_str()]);
+            self.git_with_env(worktree_path, args, &envs)
+        })
+    }
+
+    /// Per-file changes for a revision range such as `main...feature` or
+    /// `<sha>^!`.
+    pub fn diff_range_files(
+        &self,
+        repo_path: &Path,
+        range: &str,
+        with_patch: bool,
+    ) -> Result<Vec<FileDiffStat>, GitCliError> {
+        Self::collect_file_diffs(with_patch, |format| {
+            self.git(repo_path, Self::diff_args(format, &[range]))
+        })
+    }
+
+    fn diff_args<'a>(format: &[&'a str], target: &[&'a str]) -> Vec<&'a str> {
+        let mut args = vec![
+            "-c",
+            "core.quotepath=false",
+            "diff",
+            "-M",
+            "--no-color",
+            "--no-ext-diff",
+        ];
+        args.extend_from_slice(format);
+        args.extend_from_slice(target);
+        args
+    }
+
+    /// Run the name-status, numstat and (optionally) patch formats of the
+    /// same diff and zip them together. Git lists files in the same order
+    /// for each format.
+    fn collect_file_diffs(
+        with_patch: bool,
+        run: impl Fn(&[&str]) -> Result<String, GitCliError>,
+    ) -> Result<Vec<FileDiffStat>, GitCliError> {
+        let entries = Self::parse_name_status(&run(&["--name-status"])?);
+        let counts = Self::parse_numstat_z(&run(&["--numstat", "-z"])?);
+        let patches = if with_patch {
+            Some(Self::split_patch(&run(&["--patch"])?))
+        } else {
+            None
+        };
+        if counts.len() != entries.len()
+            || patches.as_ref().is_some_and(|p| p.len() != entries.len())
+        {
+            return Err(GitCliError::CommandFailed(
+                "diff formats listed different files".to_string(),
+            ));
+        }
+
+        let mut patches = patches.map(Vec::into_iter);
+        Ok(entries
+            .into_iter()
+            .zip(counts)
+            .map(|(entry, counts)| FileDiffStat {
+                entry,
+                additions: counts.map(|(added, _)| added),
+                deletions: counts.map(|(_, deleted)| deleted),
+                patch: patches.as_mut().and_then(Iterator::next),
+            })
+            .collect())
+    }
+
+    // Parse `git diff --numstat -z` into (added, deleted) per file, `None`
+    // for binary files. Renames put an empty path after the counts and the
+    // old and new paths in the next two fields.
+    fn parse_numstat_z(output: &str) -> Vec<Option<(usize, usize)>> {
+        let mut out = Vec::new();
+        let mut fields = output.split('\0');
+        while let Some(field) = fields.next() {
+            let mut parts = field.splitn(3, '\t');
+            let (Some(added), Some(deleted), Some(path)) =
+                (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            if path.is_empty() {
+                fields.next();
+                fields.next();
+            }
+            out.push(added.parse().ok().zip(deleted.parse().ok()));
+        }
+        out
+    }
+
+    // Split `git diff --patch` output into one chunk per file.
+    fn split_patch(output: &str) -> Vec<String> {
+        let mut out: Vec<String> = Vec::new();
+        for line in output.split_inclusive('\n') {
+            match out.last_mut() {
+                Some(current) if !line.starts_with("diff --git ") => current.push_str(line),
+                _ => out.push(line.to_string()),
+            }
+        }
+        out
+    }
+
+    /// Diff status vs a base branch using a temporary index (always includes untracked).
+    /// Path filter limits the reported paths.
+    pub fn diff_status(
+        &self,
+        worktree_path: &Path,
+        base_commit: &Commit,
+        opts: StatusDiffOptions,
+    ) -> Result<Vec<StatusDiffEntry>, GitCliError> {
+        let (_tmp_dir, envs) = self.snapshot_index(worktree_path)?;
 
         // git diff --cached
         let mut args: Vec<OsString> = vec![
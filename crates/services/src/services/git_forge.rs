@@ -0,0 +1,198 @@
+//! Hosting-provider agnostic access to pull requests, so the PR workflow works
+//! for GitHub and GitLab remotes alike. GitLab merge requests are mapped onto
+//! the same [`PullRequestInfo`] as GitHub pull requests.
+
+use async_trait::async_trait;
+use db::models::merge::PullRequestInfo;
+use thiserror::Error;
+
+use crate::services::{
+    github::{CreatePrRequest, GitHubRepoInfo, GitHubService, GitHubServiceError},
+    gitlab::{GitLabRepoInfo, GitLabService, GitLabServiceError},
+};
+
+const GITHUB_HOST: &str = "github.com";
+const GITLAB_HOST: &str = "gitlab.com";
+
+#[derive(Debug, Error)]
+pub enum GitForgeError {
+    #[error(transparent)]
+    GitHub(#[from] GitHubServiceError),
+    #[error(transparent)]
+    GitLab(#[from] GitLabServiceError),
+    #[error(
+        "Remote '{0}' is not hosted on GitHub or a known GitLab instance. Self-hosted GitLab hosts can be added in settings."
+    )]
+    UnsupportedRemote(String),
+}
+
+impl GitForgeError {
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(
+            self,
+            GitForgeError::GitHub(GitHubServiceError::RateLimited(_))
+                | GitForgeError::GitLab(GitLabServiceError::RateLimited(_))
+        )
+    }
+}
+
+/// Pull request operations on the repository a remote points at
+#[async_trait]
+pub trait GitForge: Send + Sync {
+    /// e.g. "GitHub", for logs and messages
+    fn name(&self) -> &'static str;
+
+    fn host(&self) -> &str;
+
+    async fn check_auth(&self) -> Result<(), GitForgeError>;
+
+    async fn create_pr(&self, request: &CreatePrRequest) -> Result<PullRequestInfo, GitForgeError>;
+
+    async fn view_pr(&self, number: i64) -> Result<PullRequestInfo, GitForgeError>;
+
+    /// Pull requests opened from `branch`, including closed and merged ones
+    async fn list_prs_for_branch(
+        &self,
+        branch: &str,
+    ) -> Result<Vec<PullRequestInfo>, GitForgeError>;
+}
+
+pub struct GitHubForge {
+    service: GitHubService,
+    repo_info: GitHubRepoInfo,
+}
+
+impl GitHubForge {
+    pub fn new(repo_info: GitHubRepoInfo) -> Result<Self, GitForgeError> {
+        Ok(Self {
+            service: GitHubService::new()?,
+            repo_info,
+        })
+    }
+}
+
+#[async_trait]
+impl GitForge for GitHubForge {
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    fn host(&self) -> &str {
+        GITHUB_HOST
+    }
+
+    async fn check_auth(&self) -> Result<(), GitForgeError> {
+        Ok(self.service.check_token().await?)
+    }
+
+    async fn create_pr(&self, request: &CreatePrRequest) -> Result<PullRequestInfo, GitForgeError> {
+        Ok(self.service.create_pr(&self.repo_info, request).await?)
+    }
+
+    async fn view_pr(&self, number: i64) -> Result<PullRequestInfo, GitForgeError> {
+        Ok(self
+            .service
+            .update_pr_status(&self.repo_info, number)
+            .await?)
+    }
+
+    async fn list_prs_for_branch(
+        &self,
+        branch: &str,
+    ) -> Result<Vec<PullRequestInfo>, GitForgeError> {
+        Ok(self
+            .service
+            .list_all_prs_for_branch(&self.repo_info, branch)
+            .await?)
+    }
+}
+
+pub struct GitLabForge {
+    service: GitLabService,
+    repo_info: GitLabRepoInfo,
+}
+
+impl GitLabForge {
+    pub fn new(repo_info: GitLabRepoInfo) -> Result<Self, GitForgeError> {
+        Ok(Self {
+            service: GitLabService::new()?,
+            repo_info,
+        })
+    }
+}
+
+#[async_trait]
+impl GitForge for GitLabForge {
+    fn name(&self) -> &'static str {
+        "GitLab"
+    }
+
+    fn host(&self) -> &str {
+        &self.repo_info.host
+    }
+
+    async fn check_auth(&self) -> Result<(), GitForgeError> {
+        Ok(self.service.check_token(&self.repo_info.host).await?)
+    }
+
+    async fn create_pr(&self, request: &CreatePrRequest) -> Result<PullRequestInfo, GitForgeError> {
+        Ok(self.service.create_mr(&self.repo_info, request).await?)
+    }
+
+    async fn view_pr(&self, number: i64) -> Result<PullRequestInfo, GitForgeError> {
+        Ok(self
+            .service
+            .update_mr_status(&self.repo_info, number)
+            .await?)
+    }
+
+    async fn list_prs_for_branch(
+        &self,
+        branch: &str,
+    ) -> Result<Vec<PullRequestInfo>, GitForgeError> {
+        Ok(self
+            .service
+            .list_all_mrs_for_branch(&self.repo_info, branch)
+            .await?)
+    }
+}
+
+/// Picks the forge for a remote, or pull/merge request, URL. gitlab.com is
+/// always recognised; self-hosted instances must be listed in
+/// `gitlab_hosts`.
+pub fn forge_for_remote_url(
+    remote_url: &str,
+    gitlab_hosts: &[String],
+) -> Result<Box<dyn GitForge>, GitForgeError> {
+    let unsupported = || GitForgeError::UnsupportedRemote(remote_url.to_string());
+    let (host, _) = split_remote_url(remote_url).ok_or_else(unsupported)?;
+
+    if host == GITHUB_HOST {
+        let repo_info = GitHubRepoInfo::from_remote_url(remote_url)?;
+        return Ok(Box::new(GitHubForge::new(repo_info)?));
+    }
+    if host == GITLAB_HOST
+        || gitlab_hosts
+            .iter()
+            .any(|known| known.trim().eq_ignore_ascii_case(&host))
+    {
+        let repo_info = GitLabRepoInfo::from_remote_url(remote_url)?;
+        return Ok(Box::new(GitLabForge::new(repo_info)?));
+    }
+    Err(unsupported())
+}
+
+/// Splits a git remote into its lowercased host and the path after it.
+/// Handles `scheme://[user@]host[:port]/path` and scp-like
+/// `[user@]host:path` forms.
+pub(crate) fn split_remote_url(remote_url: &str) -> Option<(String, &str)> {
+    let (authority, path) = match remote_url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        None => remote_url.split_once(':')?,
+    };
+    let host = authority.rsplit('@').next()?.split(':').next()?;
+    if host.is_empty() {
+        return None;
+    }
+    Some((host.to_ascii_lowercase(), path.trim_start_matches('/')))
+}
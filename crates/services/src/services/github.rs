@@ -4,7 +4,6 @@ use backon::{ExponentialBuilder, Retryable};
 use db::models::merge::PullRequestInfo;
 use regex::Regex;
 use thiserror::Error;
-use tokio::task;
 use tracing::info;
 
 mod cli;
@@ -23,6 +22,10 @@ pub enum GitHubServiceError {
     InsufficientPermissions(GhCliError),
     #[error("GitHub repository not found or no access: {0}")]
     RepoNotFoundOrNoAccess(GhCliError),
+    #[error("GitHub rate limit exceeded: {0}")]
+    RateLimited(GhCliError),
+    #[error("{0}")]
+    TimedOut(GhCliError),
     #[error(
         "GitHub CLI is not installed or not available in PATH. Please install it from https://cli.github.com/ and authenticate with 'gh auth login'"
     )]
@@ -36,7 +39,11 @@ impl From<GhCliError> for GitHubServiceError {
             GhCliError::NotAvailable => Self::GhCliNotInstalled(error),
             GhCliError::CommandFailed(msg) => {
                 let lower = msg.to_ascii_lowercase();
-                if lower.contains("403") || lower.contains("forbidden") {
+                // Primary and secondary (abuse detection) limits both come back
+                // as 403s, so check for them first
+                if lower.contains("rate limit") || lower.contains("abuse detection") {
+                    Self::RateLimited(error)
+                } else if lower.contains("403") || lower.contains("forbidden") {
                     Self::InsufficientPermissions(error)
                 } else if lower.contains("404") || lower.contains("not found") {
                     Self::RepoNotFoundOrNoAccess(error)
@@ -45,6 +52,7 @@ impl From<GhCliError> for GitHubServiceError {
                 }
             }
             GhCliError::UnexpectedOutput(msg) => Self::PullRequest(msg.to_string()),
+            GhCliError::TimedOut(_) => Self::TimedOut(error),
         }
     }
 }
@@ -57,6 +65,7 @@ impl GitHubServiceError {
                 | GitHubServiceError::InsufficientPermissions(_)
                 | GitHubServiceError::RepoNotFoundOrNoAccess(_)
                 | GitHubServiceError::GhCliNotInstalled(_)
+                | GitHubServiceError::RateLimited(_)
         )
     }
 }
@@ -108,6 +117,7 @@ pub struct CreatePrRequest {
     pub body: Option<String>,
     pub head_branch: String,
     pub base_branch: String,
+    pub draft: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -124,24 +134,17 @@ impl GitHubService {
     }
 
     pub async fn check_token(&self) -> Result<(), GitHubServiceError> {
-        let cli = self.gh_cli.clone();
-        task::spawn_blocking(move || cli.check_auth())
-            .await
-            .map_err(|err| {
-                GitHubServiceError::Repository(format!(
-                    "Failed to execute GitHub CLI for auth check: {err}"
-                ))
-            })?
-            .map_err(|err| match err {
-                GhCliError::NotAvailable => GitHubServiceError::GhCliNotInstalled(err),
-                GhCliError::AuthFailed(_) => GitHubServiceError::AuthFailed(err),
-                GhCliError::CommandFailed(msg) => {
-                    GitHubServiceError::Repository(format!("GitHub CLI auth check failed: {msg}"))
-                }
-                GhCliError::UnexpectedOutput(msg) => GitHubServiceError::Repository(format!(
-                    "Unexpected output from GitHub CLI auth check: {msg}"
-                )),
-            })
+        self.gh_cli.check_auth().await.map_err(|err| match err {
+            GhCliError::NotAvailable => GitHubServiceError::GhCliNotInstalled(err),
+            GhCliError::AuthFailed(_) => GitHubServiceError::AuthFailed(err),
+            GhCliError::TimedOut(_) => GitHubServiceError::TimedOut(err),
+            GhCliError::CommandFailed(msg) => {
+                GitHubServiceError::Repository(format!("GitHub CLI auth check failed: {msg}"))
+            }
+            GhCliError::UnexpectedOutput(msg) => GitHubServiceError::Repository(format!(
+                "Unexpected output from GitHub CLI auth check: {msg}"
+            )),
+        })
     }
 
     /// Create a pull request on GitHub
@@ -174,17 +177,7 @@ impl GitHubService {
         repo_info: &GitHubRepoInfo,
         request: &CreatePrRequest,
     ) -> Result<PullRequestInfo, GitHubServiceError> {
-        let cli = self.gh_cli.clone();
-        let request_clone = request.clone();
-        let repo_clone = repo_info.clone();
-        let cli_result = task::spawn_blocking(move || cli.create_pr(&request_clone, &repo_clone))
-            .await
-            .map_err(|err| {
-                GitHubServiceError::PullRequest(format!(
-                    "Failed to execute GitHub CLI for PR creation: {err}"
-                ))
-            })?
-            .map_err(GitHubServiceError::from)?;
+        let cli_result = self.gh_cli.create_pr(request, repo_info).await?;
 
         info!(
             "Created GitHub PR #{} for branch {} in {}/{}",
@@ -201,22 +194,10 @@ impl GitHubService {
         pr_number: i64,
     ) -> Result<PullRequestInfo, GitHubServiceError> {
         (|| async {
-            let owner = repo_info.owner.clone();
-            let repo = repo_info.repo_name.clone();
-            let cli = self.gh_cli.clone();
-            let pr = task::spawn_blocking({
-                let owner = owner.clone();
-                let repo = repo.clone();
-                move || cli.view_pr(&owner, &repo, pr_number)
-            })
-            .await
-            .map_err(|err| {
-                GitHubServiceError::PullRequest(format!(
-                    "Failed to execute GitHub CLI for viewing PR #{pr_number}: {err}"
-                ))
-            })?;
-            let pr = pr.map_err(GitHubServiceError::from)?;
-            Ok(pr)
+            Ok(self
+                .gh_cli
+                .view_pr(&repo_info.owner, &repo_info.repo_name, pr_number)
+                .await?)
         })
         .retry(
             &ExponentialBuilder::default()
@@ -243,24 +224,10 @@ impl GitHubService {
         branch_name: &str,
     ) -> Result<Vec<PullRequestInfo>, GitHubServiceError> {
         (|| async {
-            let owner = repo_info.owner.clone();
-            let repo = repo_info.repo_name.clone();
-            let branch = branch_name.to_string();
-            let cli = self.gh_cli.clone();
-            let prs = task::spawn_blocking({
-                let owner = owner.clone();
-                let repo = repo.clone();
-                let branch = branch.clone();
-                move || cli.list_prs_for_branch(&owner, &repo, &branch)
-            })
-            .await
-            .map_err(|err| {
-                GitHubServiceError::PullRequest(format!(
-                    "Failed to execute GitHub CLI for listing PRs on branch '{branch_name}': {err}"
-                ))
-            })?;
-            let prs = prs.map_err(GitHubServiceError::from)?;
-            Ok(prs)
+            Ok(self
+                .gh_cli
+                .list_prs_for_branch(&repo_info.owner, &repo_info.repo_name, branch_name)
+                .await?)
         })
         .retry(
             &ExponentialBuilder::default()
@@ -280,3 +247,51 @@ impl GitHubService {
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_stderr(stderr: &str) -> GitHubServiceError {
+        GhCliError::CommandFailed(stderr.to_string()).into()
+    }
+
+    #[test]
+    fn cli_failures_map_to_service_errors() {
+        assert!(matches!(
+            from_stderr("HTTP 403: Resource not accessible by integration"),
+            GitHubServiceError::InsufficientPermissions(_)
+        ));
+        assert!(matches!(
+            from_stderr("HTTP 403: API rate limit exceeded for user"),
+            GitHubServiceError::RateLimited(_)
+        ));
+        assert!(matches!(
+            from_stderr("HTTP 404: Not Found"),
+            GitHubServiceError::RepoNotFoundOrNoAccess(_)
+        ));
+        assert!(matches!(
+            from_stderr("pull request create failed: no commits"),
+            GitHubServiceError::PullRequest(_)
+        ));
+        assert!(matches!(
+            GitHubServiceError::from(GhCliError::NotAvailable),
+            GitHubServiceError::GhCliNotInstalled(_)
+        ));
+        assert!(matches!(
+            GitHubServiceError::from(GhCliError::AuthFailed("gh auth login".to_string())),
+            GitHubServiceError::AuthFailed(_)
+        ));
+    }
+
+    #[test]
+    fn only_transient_failures_are_retried() {
+        assert!(from_stderr("HTTP 502: Bad Gateway").should_retry());
+        assert!(
+            GitHubServiceError::from(GhCliError::TimedOut(Duration::from_secs(60))).should_retry()
+        );
+        assert!(!from_stderr("HTTP 403: Forbidden").should_retry());
+        assert!(!from_stderr("secondary rate limit").should_retry());
+        assert!(!GitHubServiceError::from(GhCliError::NotAvailable).should_retry());
+    }
+}
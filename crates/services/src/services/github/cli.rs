@@ -6,17 +6,28 @@
 
 use std::{
     ffi::{OsStr, OsString},
-    process::Command,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
 };
 
 use chrono::{DateTime, Utc};
 use db::models::merge::{MergeStatus, PullRequestInfo};
 use serde_json::Value;
 use thiserror::Error;
-use utils::shell::resolve_executable_path_blocking;
+use tokio::process::Command;
+use utils::shell::resolve_executable_path;
 
 use crate::services::github::{CreatePrRequest, GitHubRepoInfo};
 
+/// Bodies up to this many bytes without line breaks are passed inline with
+/// `--body`; anything else goes through `--body-file`.
+const MAX_INLINE_BODY_LEN: usize = 1024;
+
+/// How long a single `gh` invocation may run before it is killed
+pub const DEFAULT_GH_TIMEOUT: Duration = Duration::from_secs(60);
+
 /// High-level errors originating from the GitHub CLI.
 #[derive(Debug, Error)]
 pub enum GhCliError {
@@ -28,37 +39,65 @@ pub enum GhCliError {
     AuthFailed(String),
     #[error("GitHub CLI returned unexpected output: {0}")]
     UnexpectedOutput(String),
+    #[error("GitHub CLI did not finish within {0:?}")]
+    TimedOut(Duration),
+}
+
+/// Wrapper for invoking the `gh` command.
+#[derive(Debug, Clone)]
+pub struct GhCli {
+    timeout: Duration,
+    /// Runs this executable instead of looking `gh` up on PATH
+    program: Option<PathBuf>,
 }
 
-/// Newtype wrapper for invoking the `gh` command.
-#[derive(Debug, Clone, Default)]
-pub struct GhCli;
+impl Default for GhCli {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl GhCli {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            timeout: DEFAULT_GH_TIMEOUT,
+            program: None,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
     }
 
-    /// Ensure the GitHub CLI binary is discoverable.
-    fn ensure_available(&self) -> Result<(), GhCliError> {
-        resolve_executable_path_blocking("gh").ok_or(GhCliError::NotAvailable)?;
-        Ok(())
+    pub fn with_program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.program = Some(program.into());
+        self
     }
 
     /// Generic helper to execute `gh <args>` and return stdout on success.
-    fn run<I, S>(&self, args: I) -> Result<String, GhCliError>
+    /// gh is run without a terminal or prompts, and killed if it outlives the
+    /// timeout, e.g. while stuck on a credential prompt or a dead proxy.
+    async fn run<I, S>(&self, args: I) -> Result<String, GhCliError>
     where
         I: IntoIterator<Item = S>,
         S: AsRef<OsStr>,
     {
-        self.ensure_available()?;
-        let gh = resolve_executable_path_blocking("gh").ok_or(GhCliError::NotAvailable)?;
+        let gh = match &self.program {
+            Some(program) => program.clone(),
+            None => resolve_executable_path("gh")
+                .await
+                .ok_or(GhCliError::NotAvailable)?,
+        };
         let mut cmd = Command::new(&gh);
-        for arg in args {
-            cmd.arg(arg);
-        }
-        let output = cmd
-            .output()
+        cmd.args(args)
+            .env("GH_PROMPT_DISABLED", "1")
+            .env("GH_NO_UPDATE_NOTIFIER", "1")
+            .stdin(Stdio::null())
+            .kill_on_drop(true);
+        let output = tokio::time::timeout(self.timeout, cmd.output())
+            .await
+            .map_err(|_| GhCliError::TimedOut(self.timeout))?
             .map_err(|err| GhCliError::CommandFailed(err.to_string()))?;
 
         if output.status.success() {
@@ -86,41 +125,41 @@ impl GhCli {
         Err(GhCliError::CommandFailed(stderr))
     }
 
-    /// Run `gh pr create` and parse the response.
-    ///
-    /// TODO: support writing the body to a temp file (`--body-file`) for large/multi-line
-    /// content and expand stdout/stderr mapping into richer error variants.
-    pub fn create_pr(
+    /// Run `gh pr create` and parse the response. Bodies that are long or
+    /// span lines are passed through a temporary file, which is removed when
+    /// this returns.
+    pub async fn create_pr(
         &self,
         request: &CreatePrRequest,
         repo_info: &GitHubRepoInfo,
     ) -> Result<PullRequestInfo, GhCliError> {
-        let mut args: Vec<OsString> = Vec::with_capacity(12);
-        args.push(OsString::from("pr"));
-        args.push(OsString::from("create"));
-        args.push(OsString::from("--repo"));
-        args.push(OsString::from(format!(
-            "{}/{}",
-            repo_info.owner, repo_info.repo_name
-        )));
-        args.push(OsString::from("--head"));
-        args.push(OsString::from(&request.head_branch));
-        args.push(OsString::from("--base"));
-        args.push(OsString::from(&request.base_branch));
-        args.push(OsString::from("--title"));
-        args.push(OsString::from(&request.title));
-
         let body = request.body.as_deref().unwrap_or("");
-        args.push(OsString::from("--body"));
-        args.push(OsString::from(body));
+        let body_file = if body_needs_file(body) {
+            let mut file = tempfile::Builder::new()
+                .prefix("vk-pr-body-")
+                .suffix(".md")
+                .tempfile()
+                .map_err(|err| {
+                    GhCliError::CommandFailed(format!("Failed to create PR body file: {err}"))
+                })?;
+            file.write_all(body.as_bytes())
+                .and_then(|_| file.flush())
+                .map_err(|err| {
+                    GhCliError::CommandFailed(format!("Failed to write PR body file: {err}"))
+                })?;
+            Some(file)
+        } else {
+            None
+        };
 
-        let raw = self.run(args)?;
+        let args = create_pr_args(request, repo_info, body_file.as_ref().map(|f| f.path()));
+        let raw = self.run(args).await?;
         Self::parse_pr_create_text(&raw)
     }
 
     /// Ensure the GitHub CLI has valid auth.
-    pub fn check_auth(&self) -> Result<(), GhCliError> {
-        match self.run(["auth", "status"]) {
+    pub async fn check_auth(&self) -> Result<(), GhCliError> {
+        match self.run(["auth", "status"]).await {
             Ok(_) => Ok(()),
             Err(GhCliError::CommandFailed(msg)) => Err(GhCliError::AuthFailed(msg)),
             Err(err) => Err(err),
@@ -128,47 +167,90 @@ impl GhCli {
     }
 
     /// Retrieve details for a single pull request.
-    pub fn view_pr(
+    pub async fn view_pr(
         &self,
         owner: &str,
         repo: &str,
         pr_number: i64,
     ) -> Result<PullRequestInfo, GhCliError> {
-        let raw = self.run([
-            "pr",
-            "view",
-            &pr_number.to_string(),
-            "--repo",
-            &format!("{owner}/{repo}"),
-            "--json",
-            "number,url,state,mergedAt,mergeCommit",
-        ])?;
+        let raw = self
+            .run([
+                "pr",
+                "view",
+                &pr_number.to_string(),
+                "--repo",
+                &format!("{owner}/{repo}"),
+                "--json",
+                "number,url,state,mergedAt,mergeCommit",
+            ])
+            .await?;
         Self::parse_pr_view(&raw)
     }
 
     /// List pull requests for a branch (includes closed/merged).
-    pub fn list_prs_for_branch(
+    pub async fn list_prs_for_branch(
         &self,
         owner: &str,
         repo: &str,
         branch: &str,
     ) -> Result<Vec<PullRequestInfo>, GhCliError> {
-        let raw = self.run([
-            "pr",
-            "list",
-            "--repo",
-            &format!("{owner}/{repo}"),
-            "--state",
-            "all",
-            "--head",
-            &format!("{owner}:{branch}"),
-            "--json",
-            "number,url,state,mergedAt,mergeCommit",
-        ])?;
+        let raw = self
+            .run([
+                "pr",
+                "list",
+                "--repo",
+                &format!("{owner}/{repo}"),
+                "--state",
+                "all",
+                "--head",
+                &format!("{owner}:{branch}"),
+                "--json",
+                "number,url,state,mergedAt,mergeCommit",
+            ])
+            .await?;
         Self::parse_pr_list(&raw)
     }
 }
 
+fn body_needs_file(body: &str) -> bool {
+    body.len() > MAX_INLINE_BODY_LEN || body.contains(['\n', '\r'])
+}
+
+/// Arguments for `gh pr create`. The body is read from `body_file` when one
+/// is given.
+fn create_pr_args(
+    request: &CreatePrRequest,
+    repo_info: &GitHubRepoInfo,
+    body_file: Option<&Path>,
+) -> Vec<OsString> {
+    let mut args: Vec<OsString> = vec![
+        "pr".into(),
+        "create".into(),
+        "--repo".into(),
+        format!("{}/{}", repo_info.owner, repo_info.repo_name).into(),
+        "--head".into(),
+        (&request.head_branch).into(),
+        "--base".into(),
+        (&request.base_branch).into(),
+        "--title".into(),
+        (&request.title).into(),
+    ];
+    match body_file {
+        Some(path) => {
+            args.push("--body-file".into());
+            args.push(path.into());
+        }
+        None => {
+            args.push("--body".into());
+            args.push(request.body.as_deref().unwrap_or("").into());
+        }
+    }
+    if request.draft {
+        args.push("--draft".into());
+    }
+    args
+}
+
 impl GhCli {
     fn parse_pr_create_text(raw: &str) -> Result<PullRequestInfo, GhCliError> {
         let pr_url = raw
@@ -275,3 +357,90 @@ impl GhCli {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(body: &str, draft: bool) -> CreatePrRequest {
+        CreatePrRequest {
+            title: "Fix login".to_string(),
+            body: Some(body.to_string()),
+            head_branch: "vk/fix-login".to_string(),
+            base_branch: "main".to_string(),
+            draft,
+        }
+    }
+
+    fn repo() -> GitHubRepoInfo {
+        GitHubRepoInfo {
+            owner: "owner".to_string(),
+            repo_name: "repo".to_string(),
+        }
+    }
+
+    #[test]
+    fn short_single_line_bodies_stay_inline() {
+        let request = request("Fixes the login redirect", false);
+        assert!(!body_needs_file(request.body.as_deref().unwrap()));
+
+        let args = create_pr_args(&request, &repo(), None);
+        let tail: Vec<_> = args[args.len() - 2..].iter().collect();
+        assert_eq!(tail, ["--body", "Fixes the login redirect"]);
+        assert!(!args.iter().any(|arg| arg == "--draft"));
+    }
+
+    #[test]
+    fn long_or_multi_line_bodies_use_a_file() {
+        assert!(body_needs_file("## Summary\r\n- one"));
+        assert!(body_needs_file(&"x".repeat(MAX_INLINE_BODY_LEN + 1)));
+
+        let args = create_pr_args(
+            &request("## Summary\n- one", true),
+            &repo(),
+            Some(Path::new("/tmp/vk-pr-body-1.md")),
+        );
+        assert!(!args.iter().any(|arg| arg == "--body"));
+        let file_flag = args.iter().position(|arg| arg == "--body-file").unwrap();
+        assert_eq!(args[file_flag + 1], "/tmp/vk-pr-body-1.md");
+        assert_eq!(args.last().unwrap(), "--draft");
+    }
+
+    #[cfg(unix)]
+    fn fake_gh(dir: &Path, script: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("gh");
+        std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn hung_gh_is_killed_after_the_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let cli = GhCli::new()
+            .with_program(fake_gh(dir.path(), "sleep 5"))
+            .with_timeout(Duration::from_millis(200));
+
+        let started = std::time::Instant::now();
+        let result = cli.check_auth().await;
+
+        assert!(matches!(result, Err(GhCliError::TimedOut(_))));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn gh_runs_without_prompts_or_update_checks() {
+        let dir = tempfile::tempdir().unwrap();
+        let cli = GhCli::new().with_program(fake_gh(
+            dir.path(),
+            "echo \"$GH_PROMPT_DISABLED $GH_NO_UPDATE_NOTIFIER\"",
+        ));
+
+        let stdout = cli.run(["auth", "status"]).await.unwrap();
+        assert_eq!(stdout.trim(), "1 1");
+    }
+}
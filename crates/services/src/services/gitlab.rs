@@ -0,0 +1,221 @@
+use std::time::Duration;
+
+use backon::{ExponentialBuilder, Retryable};
+use db::models::merge::PullRequestInfo;
+use thiserror::Error;
+use tokio::task;
+use tracing::info;
+
+mod cli;
+
+use cli::{GlabCli, GlabCliError};
+
+use crate::services::{git_forge::split_remote_url, github::CreatePrRequest};
+
+#[derive(Debug, Error)]
+pub enum GitLabServiceError {
+    #[error("Repository error: {0}")]
+    Repository(String),
+    #[error("Merge request error: {0}")]
+    MergeRequest(String),
+    #[error("GitLab authentication failed: {0}")]
+    AuthFailed(GlabCliError),
+    #[error("Insufficient permissions: {0}")]
+    InsufficientPermissions(GlabCliError),
+    #[error("GitLab project not found or no access: {0}")]
+    RepoNotFoundOrNoAccess(GlabCliError),
+    #[error("GitLab rate limit exceeded: {0}")]
+    RateLimited(GlabCliError),
+    #[error(
+        "GitLab CLI is not installed or not available in PATH. Please install it from https://gitlab.com/gitlab-org/cli and authenticate with 'glab auth login'"
+    )]
+    GlabCliNotInstalled(GlabCliError),
+}
+
+impl From<GlabCliError> for GitLabServiceError {
+    fn from(error: GlabCliError) -> Self {
+        match &error {
+            GlabCliError::AuthFailed(_) => Self::AuthFailed(error),
+            GlabCliError::NotAvailable => Self::GlabCliNotInstalled(error),
+            GlabCliError::CommandFailed(msg) => {
+                let lower = msg.to_ascii_lowercase();
+                if lower.contains("429") || lower.contains("rate limit") {
+                    Self::RateLimited(error)
+                } else if lower.contains("403") || lower.contains("forbidden") {
+                    Self::InsufficientPermissions(error)
+                } else if lower.contains("404") || lower.contains("not found") {
+                    Self::RepoNotFoundOrNoAccess(error)
+                } else {
+                    Self::MergeRequest(msg.to_string())
+                }
+            }
+            GlabCliError::UnexpectedOutput(msg) => Self::MergeRequest(msg.to_string()),
+        }
+    }
+}
+
+impl GitLabServiceError {
+    pub fn should_retry(&self) -> bool {
+        !matches!(
+            self,
+            GitLabServiceError::AuthFailed(_)
+                | GitLabServiceError::InsufficientPermissions(_)
+                | GitLabServiceError::RepoNotFoundOrNoAccess(_)
+                | GitLabServiceError::GlabCliNotInstalled(_)
+                | GitLabServiceError::RateLimited(_)
+        )
+    }
+}
+
+/// A GitLab project, which may sit in nested groups (`group/subgroup/repo`)
+#[derive(Debug, Clone)]
+pub struct GitLabRepoInfo {
+    pub host: String,
+    pub project_path: String,
+}
+
+impl GitLabRepoInfo {
+    /// Parses SSH, HTTPS and merge request URLs. The caller decides whether
+    /// the host is a GitLab instance.
+    pub fn from_remote_url(remote_url: &str) -> Result<Self, GitLabServiceError> {
+        let invalid =
+            || GitLabServiceError::Repository(format!("Invalid GitLab URL format: {remote_url}"));
+        let (host, path) = split_remote_url(remote_url).ok_or_else(invalid)?;
+
+        // Merge request and other project pages live under `/-/`
+        let path = path.split("/-/").next().unwrap_or_default();
+        let project_path = path.trim_end_matches('/').trim_end_matches(".git");
+        if !project_path.contains('/') {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            host,
+            project_path: project_path.to_string(),
+        })
+    }
+
+    pub fn web_url(&self) -> String {
+        format!("https://{}/{}", self.host, self.project_path)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GitLabService {
+    glab_cli: GlabCli,
+}
+
+impl GitLabService {
+    pub fn new() -> Result<Self, GitLabServiceError> {
+        Ok(Self {
+            glab_cli: GlabCli::new(),
+        })
+    }
+
+    pub async fn check_token(&self, host: &str) -> Result<(), GitLabServiceError> {
+        let cli = self.glab_cli.clone();
+        let host = host.to_string();
+        task::spawn_blocking(move || cli.check_auth(&host))
+            .await
+            .map_err(|err| {
+                GitLabServiceError::Repository(format!(
+                    "Failed to execute GitLab CLI for auth check: {err}"
+                ))
+            })?
+            .map_err(|err| match err {
+                GlabCliError::NotAvailable => GitLabServiceError::GlabCliNotInstalled(err),
+                GlabCliError::AuthFailed(_) => GitLabServiceError::AuthFailed(err),
+                GlabCliError::CommandFailed(msg) => {
+                    GitLabServiceError::Repository(format!("GitLab CLI auth check failed: {msg}"))
+                }
+                GlabCliError::UnexpectedOutput(msg) => GitLabServiceError::Repository(format!(
+                    "Unexpected output from GitLab CLI auth check: {msg}"
+                )),
+            })
+    }
+
+    /// Open a merge request on GitLab
+    pub async fn create_mr(
+        &self,
+        repo_info: &GitLabRepoInfo,
+        request: &CreatePrRequest,
+    ) -> Result<PullRequestInfo, GitLabServiceError> {
+        let mr = self
+            .run_with_retry("creating a merge request", {
+                let repo_info = repo_info.clone();
+                let request = request.clone();
+                move |cli| cli.create_mr(&request, &repo_info)
+            })
+            .await?;
+
+        info!(
+            "Created GitLab MR !{} for branch {} in {}",
+            mr.number, request.head_branch, repo_info.project_path
+        );
+        Ok(mr)
+    }
+
+    /// Get the current status of a merge request
+    pub async fn update_mr_status(
+        &self,
+        repo_info: &GitLabRepoInfo,
+        iid: i64,
+    ) -> Result<PullRequestInfo, GitLabServiceError> {
+        self.run_with_retry(&format!("viewing MR !{iid}"), {
+            let repo_info = repo_info.clone();
+            move |cli| cli.view_mr(&repo_info, iid)
+        })
+        .await
+    }
+
+    /// List all merge requests from a branch (including closed/merged)
+    pub async fn list_all_mrs_for_branch(
+        &self,
+        repo_info: &GitLabRepoInfo,
+        branch_name: &str,
+    ) -> Result<Vec<PullRequestInfo>, GitLabServiceError> {
+        self.run_with_retry(&format!("listing MRs on branch '{branch_name}'"), {
+            let repo_info = repo_info.clone();
+            let branch = branch_name.to_string();
+            move |cli| cli.list_mrs_for_branch(&repo_info, &branch)
+        })
+        .await
+    }
+
+    /// Runs a blocking `glab` call off the async runtime, retrying transient
+    /// failures with the same policy as the GitHub service.
+    async fn run_with_retry<T, F>(&self, action: &str, f: F) -> Result<T, GitLabServiceError>
+    where
+        T: Send + 'static,
+        F: Fn(&GlabCli) -> Result<T, GlabCliError> + Clone + Send + 'static,
+    {
+        (|| async {
+            let cli = self.glab_cli.clone();
+            let f = f.clone();
+            task::spawn_blocking(move || f(&cli))
+                .await
+                .map_err(|err| {
+                    GitLabServiceError::MergeRequest(format!(
+                        "Failed to execute GitLab CLI for {action}: {err}"
+                    ))
+                })?
+                .map_err(GitLabServiceError::from)
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|err: &GitLabServiceError| err.should_retry())
+        .notify(|err: &GitLabServiceError, dur: Duration| {
+            tracing::warn!(
+                "GitLab API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+}
@@ -0,0 +1,237 @@
+//! Minimal helpers around the GitLab CLI (`glab`), mirroring the `gh` wrapper
+//! in `github/cli.rs`.
+
+use std::{
+    ffi::{OsStr, OsString},
+    process::Command,
+};
+
+use chrono::{DateTime, Utc};
+use db::models::merge::{MergeStatus, PullRequestInfo};
+use serde_json::Value;
+use thiserror::Error;
+use utils::shell::resolve_executable_path_blocking;
+
+use crate::services::{github::CreatePrRequest, gitlab::GitLabRepoInfo};
+
+/// High-level errors originating from the GitLab CLI.
+#[derive(Debug, Error)]
+pub enum GlabCliError {
+    #[error("GitLab CLI (`glab`) executable not found or not runnable")]
+    NotAvailable,
+    #[error("GitLab CLI command failed: {0}")]
+    CommandFailed(String),
+    #[error("GitLab CLI authentication failed: {0}")]
+    AuthFailed(String),
+    #[error("GitLab CLI returned unexpected output: {0}")]
+    UnexpectedOutput(String),
+}
+
+/// Newtype wrapper for invoking the `glab` command.
+#[derive(Debug, Clone, Default)]
+pub struct GlabCli;
+
+impl GlabCli {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Generic helper to execute `glab <args>` and return stdout on success.
+    fn run<I, S>(&self, args: I) -> Result<String, GlabCliError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let glab = resolve_executable_path_blocking("glab").ok_or(GlabCliError::NotAvailable)?;
+        let mut cmd = Command::new(&glab);
+        for arg in args {
+            cmd.arg(arg);
+        }
+        let output = cmd
+            .output()
+            .map_err(|err| GlabCliError::CommandFailed(err.to_string()))?;
+
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+        }
+
+        // glab exits with 1 for every failure, so auth problems can only be
+        // told apart by their message
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let lower = stderr.to_ascii_lowercase();
+        if lower.contains("401")
+            || lower.contains("unauthorized")
+            || lower.contains("not logged in")
+            || lower.contains("no token")
+            || lower.contains("glab auth login")
+        {
+            return Err(GlabCliError::AuthFailed(stderr));
+        }
+
+        Err(GlabCliError::CommandFailed(stderr))
+    }
+
+    /// Ensure the GitLab CLI has valid auth for `host`.
+    pub fn check_auth(&self, host: &str) -> Result<(), GlabCliError> {
+        match self.run(["auth", "status", "--hostname", host]) {
+            Ok(_) => Ok(()),
+            Err(GlabCliError::CommandFailed(msg)) => Err(GlabCliError::AuthFailed(msg)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Run `glab mr create` and parse the merge request URL it prints.
+    pub fn create_mr(
+        &self,
+        request: &CreatePrRequest,
+        repo_info: &GitLabRepoInfo,
+    ) -> Result<PullRequestInfo, GlabCliError> {
+        let mut args: Vec<OsString> = vec![
+            "mr".into(),
+            "create".into(),
+            "--repo".into(),
+            repo_info.web_url().into(),
+            "--source-branch".into(),
+            (&request.head_branch).into(),
+            "--target-branch".into(),
+            (&request.base_branch).into(),
+            "--title".into(),
+            (&request.title).into(),
+            "--description".into(),
+            request.body.as_deref().unwrap_or("").into(),
+            "--yes".into(),
+        ];
+        if request.draft {
+            args.push("--draft".into());
+        }
+        let raw = self.run(args)?;
+        Self::parse_mr_create_text(&raw)
+    }
+
+    /// Retrieve details for a single merge request.
+    pub fn view_mr(
+        &self,
+        repo_info: &GitLabRepoInfo,
+        iid: i64,
+    ) -> Result<PullRequestInfo, GlabCliError> {
+        let raw = self.run([
+            "mr",
+            "view",
+            &iid.to_string(),
+            "--repo",
+            &repo_info.web_url(),
+            "--output",
+            "json",
+        ])?;
+        let value: Value = serde_json::from_str(raw.trim()).map_err(|err| {
+            GlabCliError::UnexpectedOutput(format!(
+                "Failed to parse glab mr view response: {err}; raw: {raw}"
+            ))
+        })?;
+        Self::extract_mr_info(&value).ok_or_else(|| {
+            GlabCliError::UnexpectedOutput(format!(
+                "glab mr view response missing required fields: {value:#?}"
+            ))
+        })
+    }
+
+    /// List merge requests from a source branch (includes closed/merged).
+    pub fn list_mrs_for_branch(
+        &self,
+        repo_info: &GitLabRepoInfo,
+        branch: &str,
+    ) -> Result<Vec<PullRequestInfo>, GlabCliError> {
+        let raw = self.run([
+            "mr",
+            "list",
+            "--repo",
+            &repo_info.web_url(),
+            "--source-branch",
+            branch,
+            "--all",
+            "--output",
+            "json",
+        ])?;
+        let value: Value = serde_json::from_str(raw.trim()).map_err(|err| {
+            GlabCliError::UnexpectedOutput(format!(
+                "Failed to parse glab mr list response: {err}; raw: {raw}"
+            ))
+        })?;
+        let arr = value.as_array().ok_or_else(|| {
+            GlabCliError::UnexpectedOutput(format!(
+                "glab mr list response is not an array: {value:#?}"
+            ))
+        })?;
+        arr.iter()
+            .map(|item| {
+                Self::extract_mr_info(item).ok_or_else(|| {
+                    GlabCliError::UnexpectedOutput(format!(
+                        "glab mr list item missing required fields: {item:#?}"
+                    ))
+                })
+            })
+            .collect()
+    }
+}
+
+impl GlabCli {
+    fn parse_mr_create_text(raw: &str) -> Result<PullRequestInfo, GlabCliError> {
+        let mr_url = raw
+            .lines()
+            .rev()
+            .flat_map(|line| line.split_whitespace())
+            .find(|token| token.starts_with("http") && token.contains("/merge_requests/"))
+            .ok_or_else(|| {
+                GlabCliError::UnexpectedOutput(format!(
+                    "glab mr create did not return a merge request URL; raw output: {raw}"
+                ))
+            })?
+            .trim_end_matches(['.', ',', ';'])
+            .to_string();
+
+        let number = mr_url
+            .rsplit('/')
+            .next()
+            .and_then(|iid| iid.parse::<i64>().ok())
+            .ok_or_else(|| {
+                GlabCliError::UnexpectedOutput(format!(
+                    "Failed to parse merge request number from URL '{mr_url}'"
+                ))
+            })?;
+
+        Ok(PullRequestInfo {
+            number,
+            url: mr_url,
+            status: MergeStatus::Open,
+            merged_at: None,
+            merge_commit_sha: None,
+        })
+    }
+
+    fn extract_mr_info(value: &Value) -> Option<PullRequestInfo> {
+        let number = value.get("iid")?.as_i64()?;
+        let url = value.get("web_url")?.as_str()?.to_string();
+        let merged_at = value
+            .get("merged_at")
+            .and_then(Value::as_str)
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.with_timezone(&Utc));
+        // Squash merges record their commit separately
+        let merge_commit_sha = ["merge_commit_sha", "squash_commit_sha"]
+            .iter()
+            .find_map(|key| value.get(key).and_then(Value::as_str))
+            .map(|s| s.to_string());
+        Some(PullRequestInfo {
+            number,
+            url,
+            status: match value.get("state").and_then(Value::as_str).unwrap_or("") {
+                "opened" => MergeStatus::Open,
+                "merged" => MergeStatus::Merged,
+                "closed" | "locked" => MergeStatus::Closed,
+                _ => MergeStatus::Unknown,
+            },
+            merged_at,
+            merge_commit_sha,
+        })
+    }
+}
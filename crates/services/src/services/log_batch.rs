@@ -0,0 +1,78 @@
+//! Batching of raw process output before it is written to
+//! `execution_process_logs`, so a chatty executor does not cost one INSERT
+//! per chunk.
+
+use serde::{Deserialize, Serialize};
+use tokio::time::{Duration, Instant};
+use ts_rs::TS;
+
+/// Thresholds for flushing buffered output to the database. A batch is
+/// written as soon as any one of them is reached.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct LogBatchConfig {
+    /// Longest time output may sit in the buffer before it is written
+    pub flush_interval_ms: u32,
+    pub max_batch_bytes: usize,
+    pub max_batch_lines: usize,
+}
+
+impl Default for LogBatchConfig {
+    fn default() -> Self {
+        Self {
+            flush_interval_ms: 250,
+            max_batch_bytes: 8 * 1024,
+            max_batch_lines: 1000,
+        }
+    }
+}
+
+/// JSONL lines waiting to be written as a single log row.
+#[derive(Debug)]
+pub struct LogBatch {
+    config: LogBatchConfig,
+    buffer: String,
+    lines: usize,
+    started_at: Option<Instant>,
+}
+
+impl LogBatch {
+    pub fn new(config: LogBatchConfig) -> Self {
+        Self {
+            config,
+            buffer: String::new(),
+            lines: 0,
+            started_at: None,
+        }
+    }
+
+    /// Buffer a JSONL line (including its trailing newline). Returns the
+    /// batch if this line filled it.
+    pub fn push(&mut self, jsonl_line: &str) -> Option<String> {
+        self.started_at.get_or_insert_with(Instant::now);
+        self.buffer.push_str(jsonl_line);
+        self.lines += 1;
+
+        if self.buffer.len() >= self.config.max_batch_bytes
+            || self.lines >= self.config.max_batch_lines
+        {
+            self.take()
+        } else {
+            None
+        }
+    }
+
+    /// When the buffered output is due to be written, or `None` if nothing is
+    /// buffered.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.started_at
+            .map(|started| started + Duration::from_millis(self.config.flush_interval_ms.into()))
+    }
+
+    /// Drain the buffer, returning `None` if it was empty.
+    pub fn take(&mut self) -> Option<String> {
+        self.started_at = None;
+        self.lines = 0;
+        (!self.buffer.is_empty()).then(|| std::mem::take(&mut self.buffer))
+    }
+}
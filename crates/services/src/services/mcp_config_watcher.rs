@@ -0,0 +1,201 @@
+//! Notices edits to coding agents' MCP config files made outside the app,
+//! e.g. by `claude mcp add` in a terminal, so the UI can re-read them.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::OsString,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use executors::{
+    executors::{BaseCodingAgent, StandardCodingAgentExecutor},
+    health,
+    profile::{ExecutorConfigs, ExecutorProfileId},
+};
+use futures::{SinkExt, StreamExt, channel::mpsc::channel};
+use notify::RecursiveMode;
+use notify_debouncer_full::{DebounceEventResult, new_debouncer};
+use tokio::{
+    sync::{Notify, RwLock, broadcast},
+    task::JoinHandle,
+};
+
+use crate::services::{config::Config, filesystem_watcher::FilesystemWatcherError};
+
+/// Name of the event sent on `/api/events` when an executor's MCP config
+/// file changed; the data is the executor name
+pub const MCP_CONFIG_CHANGED_EVENT: &str = "mcp-config-changed";
+
+/// MCP config files to watch: the directory each lives in, then the file
+/// name and the executors reading it. Directories are watched rather than
+/// the files, since editors often save by deleting and recreating the file.
+type WatchedFiles = BTreeMap<PathBuf, HashMap<OsString, Vec<BaseCodingAgent>>>;
+
+#[derive(Clone)]
+pub struct McpConfigWatcher {
+    config: Arc<RwLock<Config>>,
+    changes: broadcast::Sender<BaseCodingAgent>,
+    refresh: Arc<Notify>,
+}
+
+impl McpConfigWatcher {
+    pub fn new(config: Arc<RwLock<Config>>) -> Self {
+        let (changes, _) = broadcast::channel(16);
+        Self {
+            config,
+            changes,
+            refresh: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Executors whose MCP config file changed on disk
+    pub fn subscribe(&self) -> broadcast::Receiver<BaseCodingAgent> {
+        self.changes.subscribe()
+    }
+
+    /// Recompute which files are watched, e.g. after the selected executor
+    /// profile changed.
+    pub fn refresh(&self) {
+        self.refresh.notify_one();
+    }
+
+    /// Config file of each executor that supports MCP. The configured
+    /// profile decides the variant used for its executor; other executors
+    /// use their default.
+    async fn config_paths(&self) -> Vec<(BaseCodingAgent, PathBuf)> {
+        let selected = self.config.read().await.executor_profile.clone();
+        let profiles = ExecutorConfigs::get_cached();
+        profiles
+            .executors
+            .keys()
+            .filter_map(|executor| {
+                let profile_id = if selected.executor == *executor {
+                    selected.clone()
+                } else {
+                    ExecutorProfileId::new(*executor)
+                };
+                let agent = profiles.get_coding_agent(&profile_id)?;
+                Some((*executor, agent.default_mcp_config_path()?))
+            })
+            .collect()
+    }
+
+    /// Files whose directory exists. A file that does not exist yet is
+    /// still watched, so one created later, or deleted and recreated by an
+    /// editor, is picked up.
+    async fn files_to_watch(&self) -> WatchedFiles {
+        let mut files = WatchedFiles::new();
+        for (executor, path) in self.config_paths().await {
+            let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+                continue;
+            };
+            let Ok(dir) = dir.canonicalize() else {
+                continue;
+            };
+            files
+                .entry(dir)
+                .or_default()
+                .entry(file_name.to_os_string())
+                .or_default()
+                .push(executor);
+        }
+        files
+    }
+
+    /// Watch the config files and broadcast changes until the server stops.
+    pub fn spawn(self) -> Result<JoinHandle<()>, FilesystemWatcherError> {
+        let (mut tx, mut rx) = channel(16);
+        let mut debouncer = new_debouncer(
+            Duration::from_millis(300),
+            None,
+            move |result: DebounceEventResult| {
+                futures::executor::block_on(async {
+                    let _ = tx.send(result).await;
+                });
+            },
+        )?;
+
+        Ok(tokio::spawn(async move {
+            let mut watched = WatchedFiles::new();
+            loop {
+                let next = self.files_to_watch().await;
+                for dir in watched.keys().filter(|dir| !next.contains_key(*dir)) {
+                    let _ = debouncer.unwatch(dir);
+                }
+                for dir in next.keys().filter(|dir| !watched.contains_key(*dir)) {
+                    if let Err(e) = debouncer.watch(dir, RecursiveMode::NonRecursive) {
+                        tracing::warn!(
+                            "Failed to watch MCP config directory {}: {}",
+                            dir.display(),
+                            e
+                        );
+                    }
+                }
+                watched = next;
+
+                tokio::select! {
+                    Some(result) = rx.next() => match result {
+                        Ok(events) => {
+                            let mut changed: Vec<BaseCodingAgent> = events
+                                .iter()
+                                .flat_map(|event| event.paths.iter())
+                                .flat_map(|path| executors_for_path(&watched, path))
+                                .collect();
+                            changed.sort_by_key(|executor| executor.to_string());
+                            changed.dedup();
+                            for executor in changed {
+                                tracing::info!("MCP config of {} changed on disk", executor);
+                                health::invalidate_cached_availability(executor);
+                                let _ = self.changes.send(executor);
+                            }
+                        }
+                        Err(errors) => {
+                            tracing::warn!("MCP config watcher error: {:?}", errors);
+                        }
+                    },
+                    _ = self.refresh.notified() => {}
+                }
+            }
+        }))
+    }
+}
+
+fn executors_for_path(watched: &WatchedFiles, path: &Path) -> Vec<BaseCodingAgent> {
+    let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return Vec::new();
+    };
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    watched
+        .get(&dir)
+        .and_then(|names| names.get(file_name))
+        .cloned()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_map_to_the_executors_reading_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let canonical = dir.path().canonicalize().unwrap();
+        let watched = WatchedFiles::from([(
+            canonical,
+            HashMap::from([(
+                OsString::from("settings.json"),
+                vec![BaseCodingAgent::Gemini],
+            )]),
+        )]);
+
+        assert_eq!(
+            executors_for_path(&watched, &dir.path().join("settings.json")),
+            [BaseCodingAgent::Gemini]
+        );
+        // Swap files written by editors during an atomic save
+        assert!(executors_for_path(&watched, &dir.path().join("settings.json.tmp")).is_empty());
+        assert!(executors_for_path(&watched, Path::new("/elsewhere/settings.json")).is_empty());
+    }
+}
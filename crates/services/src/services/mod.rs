@@ -1,9 +1,17 @@
 pub mod analytics;
+pub mod api_tokens;
 pub mod approvals;
+pub mod attempt_export;
 pub mod auth;
+pub mod board_transfer;
+pub mod bulk_tasks;
 pub mod config;
 pub mod container;
+pub mod conversation;
+pub mod conversation_compare;
+pub mod dev_server;
 pub mod diff_stream;
+pub mod disk_usage;
 pub mod drafts;
 pub mod events;
 pub mod file_ranker;
@@ -11,11 +19,25 @@ pub mod file_search_cache;
 pub mod filesystem;
 pub mod filesystem_watcher;
 pub mod git;
+pub mod git_forge;
 pub mod github;
+pub mod gitlab;
 pub mod image;
+pub mod log_batch;
+pub mod mcp_config_watcher;
 pub mod notification;
 pub mod oauth_credentials;
+pub mod pr_body;
 pub mod pr_monitor;
+pub mod privacy;
+pub mod project_import;
 pub mod remote_client;
+pub mod repo_analysis;
+pub mod session_entries;
 pub mod share;
+pub mod shutdown;
+pub mod stall;
+pub mod task_trash;
+pub mod verify;
+pub mod worktree_cleanup;
 pub mod worktree_manager;
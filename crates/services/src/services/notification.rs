@@ -1,6 +1,9 @@
 use std::sync::OnceLock;
 
-use db::models::execution_process::{ExecutionContext, ExecutionProcessStatus};
+use db::models::{
+    execution_process::{ExecutionContext, ExecutionProcessStatus},
+    task_attempt::VerifyStatus,
+};
 use utils;
 
 use crate::services::config::SoundFile;
@@ -21,7 +24,7 @@ impl NotificationService {
         }
 
         let title = format!("Task Complete: {}", ctx.task.title);
-        let message = match ctx.execution_process.status {
+        let mut message = match ctx.execution_process.status {
             ExecutionProcessStatus::Completed => format!(
                 "✅ '{}' completed successfully\nBranch: {:?}\nExecutor: {}",
                 ctx.task.title, ctx.task_attempt.branch, ctx.task_attempt.executor
@@ -42,6 +45,15 @@ impl NotificationService {
                 return;
             }
         };
+        if let Some(verify_status) = ctx.task_attempt.verify_status {
+            let verify = match verify_status {
+                VerifyStatus::Running => "still running",
+                VerifyStatus::Passed => "passed",
+                VerifyStatus::Failed => "failed",
+                VerifyStatus::TimedOut => "timed out",
+            };
+            message.push_str(&format!("\nVerify: {verify}"));
+        }
         Self::notify(config, &title, &message).await;
     }
 
@@ -0,0 +1,226 @@
+//! Draft pull request descriptions written from what an attempt did.
+
+use db::models::task::Task;
+use executors::logs::{ActionType, NormalizedConversation, NormalizedEntryType};
+
+/// GitHub rejects pull request bodies longer than this many characters.
+pub const GITHUB_PR_BODY_LIMIT: usize = 65_536;
+
+const TRUNCATED_MARKER: &str = "\n\n_(truncated)_";
+/// Files listed before the rest are summarised as a count
+const MAX_LISTED_FILES: usize = 50;
+
+/// Builds a PR body from the task, the files the agent touched and its final
+/// message, ending with a link back to the task.
+pub struct PrBodyBuilder<'a> {
+    task: &'a Task,
+    conversation: Option<&'a NormalizedConversation>,
+    task_url: Option<String>,
+}
+
+impl<'a> PrBodyBuilder<'a> {
+    pub fn new(task: &'a Task) -> Self {
+        Self {
+            task,
+            conversation: None,
+            task_url: None,
+        }
+    }
+
+    pub fn conversation(mut self, conversation: &'a NormalizedConversation) -> Self {
+        self.conversation = Some(conversation);
+        self
+    }
+
+    pub fn task_url(mut self, task_url: impl Into<String>) -> Self {
+        self.task_url = Some(task_url.into());
+        self
+    }
+
+    pub fn build(&self) -> String {
+        let mut body = format!("## {}\n", escape_fences(self.task.title.trim()));
+        if let Some(description) = self
+            .task
+            .description
+            .as_deref()
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+        {
+            body.push_str(&format!("\n{}\n", escape_fences(description)));
+        }
+
+        if let Some(conversation) = self.conversation {
+            let files = touched_files(conversation);
+            if !files.is_empty() {
+                body.push_str("\n### Files changed\n\n");
+                for file in files.iter().take(MAX_LISTED_FILES) {
+                    body.push_str(&format!("- {}\n", escape_fences(file)));
+                }
+                if files.len() > MAX_LISTED_FILES {
+                    body.push_str(&format!("- …and {} more\n", files.len() - MAX_LISTED_FILES));
+                }
+            }
+
+            if let Some(message) = final_message(conversation) {
+                body.push_str(&format!("\n### Summary\n\n{}\n", escape_fences(message)));
+            }
+        }
+
+        let footer = self
+            .task_url
+            .as_ref()
+            .map(|url| format!("\n---\n[View task in Vibe Kanban]({url})\n"))
+            .unwrap_or_default();
+        truncate(
+            body,
+            GITHUB_PR_BODY_LIMIT.saturating_sub(footer.chars().count()),
+        ) + &footer
+    }
+}
+
+/// Files the agent edited, moved or deleted, in first-touched order.
+fn touched_files(conversation: &NormalizedConversation) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    for entry in &conversation.entries {
+        let NormalizedEntryType::ToolUse { action_type, .. } = &entry.entry_type else {
+            continue;
+        };
+        let file = match action_type {
+            ActionType::FileEdit { path, .. } => format!("`{path}`"),
+            ActionType::FileDelete { path } => format!("`{path}` (deleted)"),
+            ActionType::FileMove { from, to } => format!("`{from}` → `{to}`"),
+            _ => continue,
+        };
+        if !files.contains(&file) {
+            files.push(file);
+        }
+    }
+    files
+}
+
+/// The agent's last word, falling back to the stored session summary.
+fn final_message(conversation: &NormalizedConversation) -> Option<&str> {
+    conversation
+        .entries
+        .iter()
+        .rev()
+        .find(|entry| matches!(entry.entry_type, NormalizedEntryType::AssistantMessage))
+        .map(|entry| entry.content.as_str())
+        .or(conversation.summary.as_deref())
+        .map(str::trim)
+        .filter(|message| !message.is_empty())
+}
+
+/// Break up ``` so embedded content cannot close or open a code block in the
+/// surrounding markdown.
+fn escape_fences(text: &str) -> String {
+    text.replace("```", "`\u{200B}`\u{200B}`")
+}
+
+fn truncate(body: String, limit: usize) -> String {
+    if body.chars().count() <= limit {
+        return body;
+    }
+    let keep = limit.saturating_sub(TRUNCATED_MARKER.chars().count());
+    let mut truncated: String = body.chars().take(keep).collect();
+    truncated.push_str(TRUNCATED_MARKER);
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use db::models::task::TaskStatus;
+    use executors::logs::{NormalizedEntry, ToolStatus};
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn task(description: &str) -> Task {
+        Task {
+            id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            title: "Fix login".to_string(),
+            description: Some(description.to_string()),
+            status: TaskStatus::InReview,
+            parent_task_attempt: None,
+            shared_task_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn edit(path: &str) -> NormalizedEntry {
+        entry(
+            NormalizedEntryType::ToolUse {
+                tool_name: "edit".to_string(),
+                action_type: ActionType::FileEdit {
+                    path: path.to_string(),
+                    changes: vec![],
+                },
+                status: ToolStatus::Success,
+            },
+            "",
+        )
+    }
+
+    fn conversation(entries: Vec<NormalizedEntry>) -> NormalizedConversation {
+        NormalizedConversation {
+            entries,
+            session_id: None,
+            executor_type: "CLAUDE_CODE".to_string(),
+            prompt: None,
+            summary: None,
+            usage: None,
+            mcp_degraded: false,
+        }
+    }
+
+    #[test]
+    fn lists_files_once_and_uses_the_last_message() {
+        let task = task("Users are logged out on refresh");
+        let conversation = conversation(vec![
+            entry(NormalizedEntryType::AssistantMessage, "Looking into it"),
+            edit("src/auth.rs"),
+            edit("src/session.rs"),
+            edit("src/auth.rs"),
+            entry(
+                NormalizedEntryType::AssistantMessage,
+                "Refresh now keeps the session",
+            ),
+        ]);
+        let body = PrBodyBuilder::new(&task)
+            .conversation(&conversation)
+            .task_url("http://127.0.0.1:3000/projects/p/tasks/t")
+            .build();
+
+        assert!(body.starts_with("## Fix login\n\nUsers are logged out on refresh\n"));
+        assert_eq!(body.matches("`src/auth.rs`").count(), 1);
+        assert!(body.contains("- `src/session.rs`\n"));
+        assert!(body.contains("### Summary\n\nRefresh now keeps the session\n"));
+        assert!(!body.contains("Looking into it"));
+        assert!(body.ends_with("(http://127.0.0.1:3000/projects/p/tasks/t)\n"));
+    }
+
+    #[test]
+    fn escapes_fences_and_truncates_before_the_link() {
+        let task = task(&format!(
+            "```rust\n{}\n```",
+            "x".repeat(GITHUB_PR_BODY_LIMIT)
+        ));
+        let body = PrBodyBuilder::new(&task).task_url("http://t").build();
+
+        assert!(!body.contains("```"));
+        assert!(body.chars().count() <= GITHUB_PR_BODY_LIMIT);
+        assert!(body.contains("_(truncated)_\n---\n[View task in Vibe Kanban](http://t)\n"));
+    }
+}
@@ -1,9 +1,11 @@
-use std::time::Duration;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use db::{
     DBService,
     models::{
-        merge::{Merge, MergeStatus, PrMerge},
+        attempt_event::{AttemptEvent, AttemptEventType},
+        merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
+        project::Project,
         task::{Task, TaskStatus},
         task_attempt::{TaskAttempt, TaskAttemptError},
     },
@@ -11,47 +13,79 @@ use db::{
 use serde_json::json;
 use sqlx::error::Error as SqlxError;
 use thiserror::Error;
-use tokio::time::interval;
-use tracing::{debug, error, info};
+use tokio::{
+    sync::{RwLock, mpsc},
+    time::sleep,
+};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
 
 use crate::services::{
     analytics::AnalyticsContext,
-    github::{GitHubRepoInfo, GitHubService, GitHubServiceError},
+    config::Config,
+    git_forge::{self, GitForge, GitForgeError},
     share::SharePublisher,
 };
 
+/// Pause between PR lookups so a long list of open PRs is spread out rather
+/// than sent to the host in a burst
+const PR_CHECK_SPACING: Duration = Duration::from_secs(2);
+/// Upper bound for the wait after a host keeps rate limiting us
+const MAX_BACKOFF: Duration = Duration::from_secs(30 * 60);
+
 #[derive(Debug, Error)]
-enum PrMonitorError {
+pub enum PrMonitorError {
     #[error(transparent)]
-    GitHubServiceError(#[from] GitHubServiceError),
+    GitForge(#[from] GitForgeError),
     #[error(transparent)]
     TaskAttemptError(#[from] TaskAttemptError),
     #[error(transparent)]
     Sqlx(#[from] SqlxError),
 }
 
-/// Service to monitor GitHub PRs and update task status when they are merged
+impl PrMonitorError {
+    fn is_rate_limited(&self) -> bool {
+        matches!(self, PrMonitorError::GitForge(err) if err.is_rate_limited())
+    }
+}
+
+/// Service to monitor GitHub PRs and GitLab MRs and update task status when
+/// they are merged
 pub struct PrMonitorService {
     db: DBService,
+    config: Arc<RwLock<Config>>,
     poll_interval: Duration,
     analytics: Option<AnalyticsContext>,
     publisher: Option<SharePublisher>,
+    completed_tasks: Option<mpsc::UnboundedSender<Uuid>>,
 }
 
 impl PrMonitorService {
-    pub async fn spawn(
+    pub fn new(
         db: DBService,
+        config: Arc<RwLock<Config>>,
         analytics: Option<AnalyticsContext>,
         publisher: Option<SharePublisher>,
-    ) -> tokio::task::JoinHandle<()> {
-        let service = Self {
+    ) -> Self {
+        Self {
             db,
+            config,
             poll_interval: Duration::from_secs(60), // Check every minute
             analytics,
             publisher,
-        };
+            completed_tasks: None,
+        }
+    }
+
+    /// Send the id of every task this service moves to done.
+    pub fn notify_completed_tasks(mut self, sender: mpsc::UnboundedSender<Uuid>) -> Self {
+        self.completed_tasks = Some(sender);
+        self
+    }
+
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
-            service.start().await;
+            self.start().await;
         })
     }
 
@@ -61,17 +95,24 @@ impl PrMonitorService {
             self.poll_interval
         );
 
-        let mut interval = interval(self.poll_interval);
-
+        let mut backoff = None;
         loop {
-            interval.tick().await;
-            if let Err(e) = self.check_all_open_prs().await {
-                error!("Error checking open PRs: {}", e);
+            match self.check_all_open_prs().await {
+                Ok(()) => backoff = None,
+                Err(e) if e.is_rate_limited() => {
+                    let wait = next_backoff(backoff, self.poll_interval);
+                    warn!("Rate limited while checking PRs, waiting {:?}: {}", wait, e);
+                    backoff = Some(wait);
+                }
+                Err(e) => error!("Error checking open PRs: {}", e),
             }
+            sleep(backoff.unwrap_or(self.poll_interval)).await;
         }
     }
 
-    /// Check all open PRs for updates with the provided GitHub token
+    /// Check all open PRs, one at a time. PRs on hosts whose CLI is not
+    /// signed in are skipped. Stops early if the host rate limits us so the
+    /// caller can back off.
     async fn check_all_open_prs(&self) -> Result<(), PrMonitorError> {
         let open_prs = Merge::get_open_prs(&self.db.pool).await?;
 
@@ -82,84 +123,187 @@ impl PrMonitorService {
 
         info!("Checking {} open PRs", open_prs.len());
 
-        for pr_merge in open_prs {
-            if let Err(e) = self.check_pr_status(&pr_merge).await {
-                error!(
+        let gitlab_hosts = self.config.read().await.gitlab_hosts.clone();
+        let mut signed_in: HashMap<String, bool> = HashMap::new();
+        let mut first_request = true;
+        for pr_merge in &open_prs {
+            let forge = match git_forge::forge_for_remote_url(&pr_merge.pr_info.url, &gitlab_hosts)
+            {
+                Ok(forge) => forge,
+                Err(e) => {
+                    debug!("Skipping PR {}: {}", pr_merge.pr_info.url, e);
+                    continue;
+                }
+            };
+            if !self.is_signed_in(forge.as_ref(), &mut signed_in).await {
+                continue;
+            }
+
+            if !first_request {
+                sleep(PR_CHECK_SPACING).await;
+            }
+            first_request = false;
+            match self.check_pr_status(forge.as_ref(), pr_merge).await {
+                Ok(_) => {}
+                Err(e) if e.is_rate_limited() => return Err(e),
+                Err(e) => error!(
                     "Error checking PR #{} for attempt {}: {}",
                     pr_merge.pr_info.number, pr_merge.task_attempt_id, e
-                );
+                ),
             }
         }
         Ok(())
     }
 
-    /// Check the status of a specific PR
-    async fn check_pr_status(&self, pr_merge: &PrMerge) -> Result<(), PrMonitorError> {
-        // GitHubService now uses gh CLI, no token needed
-        let github_service = GitHubService::new()?;
-        let repo_info = GitHubRepoInfo::from_remote_url(&pr_merge.pr_info.url)?;
+    /// Checks CLI auth once per host and round
+    async fn is_signed_in(
+        &self,
+        forge: &dyn GitForge,
+        checked: &mut HashMap<String, bool>,
+    ) -> bool {
+        if let Some(signed_in) = checked.get(forge.host()) {
+            return *signed_in;
+        }
+        let signed_in = match forge.check_auth().await {
+            Ok(()) => true,
+            Err(e) => {
+                debug!(
+                    "Skipping PR checks on {}, {} CLI is not signed in: {}",
+                    forge.host(),
+                    forge.name(),
+                    e
+                );
+                false
+            }
+        };
+        checked.insert(forge.host().to_string(), signed_in);
+        signed_in
+    }
 
-        let pr_status = github_service
-            .update_pr_status(&repo_info, pr_merge.pr_info.number)
-            .await?;
+    /// Fetch the current state of one PR from its host and apply it, as the
+    /// background poll would.
+    pub async fn refresh_pr(&self, pr_merge: &PrMerge) -> Result<PullRequestInfo, PrMonitorError> {
+        let gitlab_hosts = self.config.read().await.gitlab_hosts.clone();
+        let forge = git_forge::forge_for_remote_url(&pr_merge.pr_info.url, &gitlab_hosts)?;
+        self.check_pr_status(forge.as_ref(), pr_merge).await
+    }
+
+    /// Check the status of a specific PR
+    async fn check_pr_status(
+        &self,
+        forge: &dyn GitForge,
+        pr_merge: &PrMerge,
+    ) -> Result<PullRequestInfo, PrMonitorError> {
+        let pr_status = forge.view_pr(pr_merge.pr_info.number).await?;
 
         debug!(
-            "PR #{} status: {:?} (was open)",
-            pr_merge.pr_info.number, pr_status.status
+            "PR #{} status: {:?} (was {:?})",
+            pr_merge.pr_info.number, pr_status.status, pr_merge.pr_info.status
         );
 
-        // Update the PR status in the database
-        if !matches!(&pr_status.status, MergeStatus::Open) {
-            // Update merge status with the latest information from GitHub
-            Merge::update_status(
-                &self.db.pool,
-                pr_merge.id,
-                pr_status.status.clone(),
-                pr_status.merge_commit_sha,
-            )
-            .await?;
-
-            // If the PR was merged, update the task status to done
-            if matches!(&pr_status.status, MergeStatus::Merged)
-                && let Some(task_attempt) =
-                    TaskAttempt::find_by_id(&self.db.pool, pr_merge.task_attempt_id).await?
+        if pr_status.status == pr_merge.pr_info.status {
+            return Ok(pr_status);
+        }
+
+        // Update merge status with the latest information from the host
+        Merge::update_status(
+            &self.db.pool,
+            pr_merge.id,
+            pr_status.status.clone(),
+            pr_status.merge_commit_sha.clone(),
+        )
+        .await?;
+
+        if matches!(&pr_status.status, MergeStatus::Merged) {
+            self.on_pr_merged(pr_merge).await?;
+        }
+
+        Ok(pr_status)
+    }
+
+    /// Move the task to done, unless its project opted out, and record the
+    /// merge
+    async fn on_pr_merged(&self, pr_merge: &PrMerge) -> Result<(), PrMonitorError> {
+        let pool = &self.db.pool;
+        AttemptEvent::record(
+            pool,
+            pr_merge.task_attempt_id,
+            AttemptEventType::PrMerged,
+            json!({ "number": pr_merge.pr_info.number, "url": pr_merge.pr_info.url }),
+        )
+        .await;
+        let Some(task_attempt) = TaskAttempt::find_by_id(pool, pr_merge.task_attempt_id).await?
+        else {
+            return Ok(());
+        };
+        let Some(task) = Task::find_by_id(pool, task_attempt.task_id).await? else {
+            return Ok(());
+        };
+
+        let close_task = Project::find_by_id(pool, task.project_id)
+            .await?
+            .is_none_or(|project| project.close_task_on_pr_merge);
+        if close_task {
+            info!(
+                "PR #{} was merged, updating task {} to done",
+                pr_merge.pr_info.number, task.id
+            );
+            Task::update_status(pool, task.id, TaskStatus::Done).await?;
+            if let Some(completed_tasks) = &self.completed_tasks {
+                let _ = completed_tasks.send(task.id);
+            }
+
+            if let Some(publisher) = &self.publisher
+                && let Err(err) = publisher.update_shared_task_by_id(task.id).await
             {
-                info!(
-                    "PR #{} was merged, updating task {} to done",
-                    pr_merge.pr_info.number, task_attempt.task_id
+                warn!(
+                    ?err,
+                    "Failed to propagate shared task update for {}", task.id
                 );
-                Task::update_status(&self.db.pool, task_attempt.task_id, TaskStatus::Done).await?;
-
-                // Track analytics event
-                if let Some(analytics) = &self.analytics
-                    && let Ok(Some(task)) =
-                        Task::find_by_id(&self.db.pool, task_attempt.task_id).await
-                {
-                    analytics.analytics_service.track_event(
-                        &analytics.user_id,
-                        "pr_merged",
-                        Some(json!({
-                            "task_id": task_attempt.task_id.to_string(),
-                            "task_attempt_id": task_attempt.id.to_string(),
-                            "project_id": task.project_id.to_string(),
-                        })),
-                    );
-                }
-
-                if let Some(publisher) = &self.publisher
-                    && let Err(err) = publisher
-                        .update_shared_task_by_id(task_attempt.task_id)
-                        .await
-                {
-                    tracing::warn!(
-                        ?err,
-                        "Failed to propagate shared task update for {}",
-                        task_attempt.task_id
-                    );
-                }
             }
+        } else {
+            info!(
+                "PR #{} was merged, leaving task {} open as its project does not close tasks on merge",
+                pr_merge.pr_info.number, task.id
+            );
+        }
+
+        // Track analytics event
+        if let Some(analytics) = &self.analytics {
+            analytics.analytics_service.track_event(
+                &analytics.user_id,
+                "pr_merged",
+                Some(json!({
+                    "task_id": task.id.to_string(),
+                    "task_attempt_id": task_attempt.id.to_string(),
+                    "project_id": task.project_id.to_string(),
+                    "task_closed": close_task,
+                })),
+            );
         }
 
         Ok(())
     }
 }
+
+/// Doubles the wait after each rate-limited round, starting from twice the
+/// poll interval
+fn next_backoff(current: Option<Duration>, poll_interval: Duration) -> Duration {
+    current
+        .map_or(poll_interval * 2, |wait| wait * 2)
+        .min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let poll = Duration::from_secs(60);
+        let first = next_backoff(None, poll);
+        assert_eq!(first, Duration::from_secs(120));
+        assert_eq!(next_backoff(Some(first), poll), Duration::from_secs(240));
+        assert_eq!(next_backoff(Some(MAX_BACKOFF), poll), MAX_BACKOFF);
+    }
+}
@@ -0,0 +1,597 @@
+//! Response redaction for screen recordings and shared screens.
+//!
+//! Read endpoints can be asked to return sanitized data with
+//! `?privacy=redact_content|paths_only`. Nothing stored is changed: the
+//! [`Redactor`] rewrites the serialized response, replacing titles, paths and
+//! conversation content with placeholders. Pseudonyms are deterministic for
+//! the lifetime of one redactor, so the same task is "Task A" everywhere in a
+//! response (or for the whole of a websocket stream).
+
+use std::collections::HashMap;
+
+use json_patch::Patch;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ts_rs::TS;
+use utils::log_msg::LogMsg;
+
+/// Ordered from least to most redaction, so the stricter of a requested and
+/// a forced mode is `max(a, b)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum PrivacyMode {
+    /// Only file paths are generalized
+    PathsOnly,
+    /// Titles, descriptions, conversation content and paths are all replaced
+    RedactContent,
+}
+
+/// Query parameters accepted by endpoints that support redaction.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PrivacyParams {
+    pub privacy: Option<PrivacyMode>,
+}
+
+impl PrivacyMode {
+    /// Combine the mode a request asked for with the one forced by config.
+    pub fn resolve(requested: Option<Self>, forced: Option<Self>) -> Option<Self> {
+        requested.max(forced)
+    }
+}
+
+pub const REDACTED: &str = "[redacted]";
+
+const TITLE_KEYS: &[&str] = &["title"];
+
+const BRANCH_KEYS: &[&str] = &["branch"];
+
+const PATH_KEYS: &[&str] = &[
+    "path",
+    "file_path",
+    "old_path",
+    "new_path",
+    "oldPath",
+    "newPath",
+    "container_ref",
+    "worktree_path",
+    "git_repo_path",
+    "working_dir",
+    "cwd",
+];
+
+const CONTENT_KEYS: &[&str] = &[
+    "description",
+    "content",
+    "prompt",
+    "summary",
+    "body",
+    "plan",
+    "command",
+    "query",
+    "url",
+    "output",
+    "verify_output",
+    "reason",
+    "text",
+    "arguments",
+    "unified_diff",
+    "patch",
+    "old_content",
+    "new_content",
+    "oldContent",
+    "newContent",
+];
+
+/// Stable pseudonyms for one class of value.
+#[derive(Default)]
+struct Pseudonyms {
+    assigned: HashMap<String, String>,
+}
+
+impl Pseudonyms {
+    fn get_or_insert(&mut self, raw: &str, make: impl FnOnce(usize) -> String) -> String {
+        if let Some(existing) = self.assigned.get(raw) {
+            return existing.clone();
+        }
+        let pseudonym = make(self.assigned.len());
+        self.assigned.insert(raw.to_string(), pseudonym.clone());
+        pseudonym
+    }
+}
+
+pub struct Redactor {
+    mode: PrivacyMode,
+    titles: Pseudonyms,
+    branches: Pseudonyms,
+    paths: Pseudonyms,
+    /// Raw strings that may also appear inside free text, longest first
+    replacements: Vec<(String, String)>,
+}
+
+impl Redactor {
+    pub fn new(mode: PrivacyMode) -> Self {
+        Self {
+            mode,
+            titles: Pseudonyms::default(),
+            branches: Pseudonyms::default(),
+            paths: Pseudonyms::default(),
+            replacements: Vec::new(),
+        }
+    }
+
+    pub fn mode(&self) -> PrivacyMode {
+        self.mode
+    }
+
+    /// Redact a whole response body in place.
+    pub fn redact_value(&mut self, value: &mut Value) {
+        self.redact_fields(value);
+        self.scrub_text(value);
+    }
+
+    /// Redact one message of a websocket or SSE stream.
+    pub fn redact_log_msg(&mut self, msg: LogMsg) -> LogMsg {
+        match msg {
+            LogMsg::Stdout(text) => LogMsg::Stdout(self.redact_text(text)),
+            LogMsg::Stderr(text) => LogMsg::Stderr(self.redact_text(text)),
+            LogMsg::JsonPatch(patch) => LogMsg::JsonPatch(self.redact_patch(patch)),
+            other => other,
+        }
+    }
+
+    fn redact_text(&mut self, text: String) -> String {
+        match self.mode {
+            PrivacyMode::RedactContent => REDACTED.to_string(),
+            PrivacyMode::PathsOnly => self.replace_known(&text).unwrap_or(text),
+        }
+    }
+
+    fn redact_patch(&mut self, patch: Patch) -> Patch {
+        let Ok(mut ops) = serde_json::to_value(&patch) else {
+            return patch;
+        };
+        if let Value::Array(ops) = &mut ops {
+            for op in ops.iter_mut() {
+                let Value::Object(op) = op else { continue };
+                if let Some(value) = op.get_mut("value") {
+                    self.redact_fields(value);
+                }
+                for key in ["path", "from"] {
+                    if let Some(Value::String(pointer)) = op.get_mut(key) {
+                        *pointer = self.redact_pointer(pointer);
+                    }
+                }
+                if let Some(value) = op.get_mut("value") {
+                    self.scrub_text(value);
+                }
+            }
+        }
+        serde_json::from_value(ops).unwrap_or(patch)
+    }
+
+    /// Diff streams key their entries by file path (`/entries/src~1main.rs`),
+    /// conversation streams by index (`/entries/3`).
+    fn redact_pointer(&mut self, pointer: &str) -> String {
+        let mut segments: Vec<String> = pointer.split('/').map(str::to_string).collect();
+        for i in 1..segments.len() {
+            if segments[i - 1] != "entries" {
+                continue;
+            }
+            let segment = &segments[i];
+            if segment.is_empty() || segment.chars().all(|c| c.is_ascii_digit()) {
+                continue;
+            }
+            let raw = segment.replace("~1", "/").replace("~0", "~");
+            let pseudonym = self.path_pseudonym(&raw);
+            segments[i] = pseudonym.replace('~', "~0").replace('/', "~1");
+        }
+        segments.join("/")
+    }
+
+    fn redact_fields(&mut self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                // `ToolResult` keeps arbitrary tool output under `value`
+                let is_tool_result = map.contains_key("type") && map.contains_key("value");
+                for (key, field) in map.iter_mut() {
+                    if is_tool_result && key == "value" {
+                        self.redact_content(field);
+                    } else {
+                        self.redact_field(key, field);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_fields(item)),
+            _ => {}
+        }
+    }
+
+    fn redact_field(&mut self, key: &str, field: &mut Value) {
+        if PATH_KEYS.contains(&key) {
+            if let Value::String(raw) = field {
+                *raw = self.path_pseudonym(raw);
+            }
+            return;
+        }
+        if self.mode == PrivacyMode::RedactContent {
+            if TITLE_KEYS.contains(&key) {
+                if let Value::String(raw) = field {
+                    let pseudonym = self.titles.get_or_insert(raw, task_label);
+                    self.remember(raw, &pseudonym);
+                    *raw = pseudonym;
+                }
+                return;
+            }
+            if BRANCH_KEYS.contains(&key) {
+                if let Value::String(raw) = field {
+                    let pseudonym = self
+                        .branches
+                        .get_or_insert(raw, |n| format!("branch-{}", n + 1));
+                    self.remember(raw, &pseudonym);
+                    *raw = pseudonym;
+                }
+                return;
+            }
+            if CONTENT_KEYS.contains(&key) {
+                self.redact_content(field);
+                return;
+            }
+        }
+        self.redact_fields(field);
+    }
+
+    fn redact_content(&mut self, field: &mut Value) {
+        if self.mode != PrivacyMode::RedactContent {
+            return;
+        }
+        match field {
+            Value::Null => {}
+            // Todo lists and similar keep their shape, only the text goes
+            Value::Array(items) if items.iter().all(Value::is_object) => {
+                items.iter_mut().for_each(|item| self.redact_fields(item))
+            }
+            Value::Object(_) => *field = Value::Null,
+            _ => *field = Value::String(REDACTED.to_string()),
+        }
+    }
+
+    fn path_pseudonym(&mut self, raw: &str) -> String {
+        let extension = file_extension(raw);
+        let pseudonym = self.paths.get_or_insert(raw, |n| match extension {
+            Some(ext) => format!("file-{}.{ext}", n + 1),
+            None => format!("path-{}", n + 1),
+        });
+        self.remember(raw, &pseudonym);
+        // Tool output usually names files by their base name only
+        if let Some(name) = base_name(raw)
+            && name.contains('.')
+            && name.len() >= 4
+        {
+            self.remember(name, &pseudonym);
+        }
+        pseudonym
+    }
+
+    fn remember(&mut self, raw: &str, pseudonym: &str) {
+        if raw.is_empty() || self.replacements.iter().any(|(known, _)| known == raw) {
+            return;
+        }
+        self.replacements
+            .push((raw.to_string(), pseudonym.to_string()));
+        self.replacements
+            .sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+    }
+
+    fn replace_known(&self, text: &str) -> Option<String> {
+        let mut replaced: Option<String> = None;
+        for (raw, pseudonym) in &self.replacements {
+            let current = replaced.as_deref().unwrap_or(text);
+            if current.contains(raw.as_str()) {
+                replaced = Some(current.replace(raw.as_str(), pseudonym));
+            }
+        }
+        replaced
+    }
+
+    /// Catch raw titles and paths that leak into free text, such as a tool
+    /// call summary naming the file it read.
+    fn scrub_text(&self, value: &mut Value) {
+        if self.replacements.is_empty() {
+            return;
+        }
+        match value {
+            Value::String(text) => {
+                if let Some(replaced) = self.replace_known(text) {
+                    *text = replaced;
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.scrub_text(item)),
+            Value::Object(map) => map.values_mut().for_each(|field| self.scrub_text(field)),
+            _ => {}
+        }
+    }
+}
+
+/// "Task A" … "Task Z", then "Task AA" and so on.
+fn task_label(n: usize) -> String {
+    let mut label = String::new();
+    let mut n = n + 1;
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        label.insert(0, (b'A' + rem as u8) as char);
+        n = (n - 1) / 26;
+    }
+    format!("Task {label}")
+}
+
+fn base_name(path: &str) -> Option<&str> {
+    path.rsplit(['/', '\\'])
+        .next()
+        .filter(|name| !name.is_empty())
+}
+
+fn file_extension(path: &str) -> Option<&str> {
+    let name = base_name(path)?;
+    let (stem, ext) = name.rsplit_once('.')?;
+    (!stem.is_empty()
+        && !ext.is_empty()
+        && ext.len() <= 8
+        && ext.chars().all(char::is_alphanumeric))
+    .then_some(ext)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use db::models::{
+        task::{Task, TaskStatus, TaskWithAttemptStatus},
+        task_attempt::TaskAttempt,
+    };
+    use executors::logs::{
+        ActionType, CommandRunResult, FileChange, NormalizedEntry, NormalizedEntryType, ToolResult,
+        ToolStatus,
+        utils::patch::{ConversationPatch, escape_json_pointer_segment},
+    };
+    use serde_json::json;
+    use utils::diff::{Diff, DiffChangeKind};
+    use uuid::Uuid;
+
+    use super::*;
+
+    const TITLE: &str = "Fix the Acme Corp billing export";
+    const OTHER_TITLE: &str = "Rotate staging credentials";
+    const DESCRIPTION: &str = "Customer invoices for ACME-4471 are off by one cent";
+    const WORKTREE: &str = "/home/alice/worktrees/vk-acme-billing";
+    const FILE: &str = "src/billing/acme_export.rs";
+    const BRANCH: &str = "vk/1234-fix-the-acme-corp-billing";
+    const PROMPT: &str = "Please fix the acme rounding bug";
+
+    fn task(title: &str, description: Option<&str>) -> TaskWithAttemptStatus {
+        TaskWithAttemptStatus {
+            task: Task {
+                id: Uuid::new_v4(),
+                project_id: Uuid::new_v4(),
+                title: title.to_string(),
+                description: description.map(str::to_string),
+                status: TaskStatus::InProgress,
+                parent_task_attempt: None,
+                shared_task_id: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            },
+            has_in_progress_attempt: true,
+            has_merged_attempt: false,
+            last_attempt_failed: false,
+            has_queued_attempt: false,
+            blocked: false,
+            executor: "CLAUDE_CODE".to_string(),
+            last_verify_status: None,
+        }
+    }
+
+    fn attempt() -> TaskAttempt {
+        TaskAttempt {
+            id: Uuid::new_v4(),
+            task_id: Uuid::new_v4(),
+            container_ref: Some(WORKTREE.to_string()),
+            branch: BRANCH.to_string(),
+            target_branch: "main".to_string(),
+            executor: "CLAUDE_CODE".to_string(),
+            worktree_deleted: false,
+            setup_completed_at: None,
+            verify_status: None,
+            verify_output: None,
+            retried_from_attempt_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn conversation() -> Vec<NormalizedEntry> {
+        let entry = |entry_type, content: &str| NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        };
+        vec![
+            entry(NormalizedEntryType::UserMessage, PROMPT),
+            entry(
+                NormalizedEntryType::ToolUse {
+                    tool_name: "Edit".to_string(),
+                    action_type: ActionType::FileEdit {
+                        path: FILE.to_string(),
+                        changes: vec![FileChange::Edit {
+                            unified_diff: "-let cents = 1;\n+let cents = 0;".to_string(),
+                            has_line_numbers: false,
+                        }],
+                    },
+                    status: ToolStatus::Success,
+                },
+                &format!("`{FILE}`"),
+            ),
+            entry(
+                NormalizedEntryType::ToolUse {
+                    tool_name: "Bash".to_string(),
+                    action_type: ActionType::CommandRun {
+                        command: format!("cargo test --manifest-path {WORKTREE}/Cargo.toml"),
+                        result: Some(CommandRunResult {
+                            exit_status: None,
+                            output: Some("test acme_export ... ok".to_string()),
+                        }),
+                    },
+                    status: ToolStatus::Success,
+                },
+                "cargo test",
+            ),
+            entry(
+                NormalizedEntryType::ToolUse {
+                    tool_name: "mcp".to_string(),
+                    action_type: ActionType::Tool {
+                        tool_name: "lookup".to_string(),
+                        arguments: Some(json!({ "customer": "ACME-4471" })),
+                        result: Some(ToolResult::markdown("ACME-4471 has 3 invoices")),
+                    },
+                    status: ToolStatus::Success,
+                },
+                "lookup",
+            ),
+            entry(
+                NormalizedEntryType::AssistantMessage,
+                &format!("I changed acme_export.rs; PR body: {TITLE}"),
+            ),
+        ]
+    }
+
+    fn diff_patch() -> Patch {
+        let diff = Diff {
+            change: DiffChangeKind::Modified,
+            old_path: Some(FILE.to_string()),
+            new_path: Some(FILE.to_string()),
+            old_content: Some("let cents = 1; // acme".to_string()),
+            new_content: Some("let cents = 0; // acme".to_string()),
+            content_omitted: false,
+            additions: Some(1),
+            deletions: Some(1),
+        };
+        let key = escape_json_pointer_segment(FILE);
+        let mut patch = ConversationPatch::add_diff(key.clone(), diff);
+        patch.0.extend(ConversationPatch::remove_diff(key).0);
+        patch
+    }
+
+    fn assert_clean(body: &str, raw: &[&str]) {
+        for needle in raw {
+            assert!(!body.contains(needle), "{needle:?} leaked into {body}");
+        }
+    }
+
+    fn redact<T: Serialize>(redactor: &mut Redactor, data: &T) -> Value {
+        let mut value = serde_json::to_value(data).unwrap();
+        redactor.redact_value(&mut value);
+        value
+    }
+
+    #[test]
+    fn redact_content_removes_every_fixture_string() {
+        let mut redactor = Redactor::new(PrivacyMode::RedactContent);
+        let body = json!({
+            "tasks": redact(&mut redactor, &[
+                task(TITLE, Some(DESCRIPTION)),
+                task(OTHER_TITLE, None),
+                task(TITLE, None),
+            ]),
+            "attempt": redact(&mut redactor, &attempt()),
+            "conversation": redact(&mut redactor, &conversation()),
+        });
+        let text = body.to_string();
+        assert_clean(
+            &text,
+            &[
+                TITLE,
+                OTHER_TITLE,
+                DESCRIPTION,
+                WORKTREE,
+                FILE,
+                "acme_export",
+                BRANCH,
+                PROMPT,
+                "ACME-4471",
+                "cents",
+            ],
+        );
+
+        // Pseudonyms are stable within a response and keep the shape usable
+        assert_eq!(body["tasks"][0]["title"], "Task A");
+        assert_eq!(body["tasks"][1]["title"], "Task B");
+        assert_eq!(body["tasks"][2]["title"], "Task A");
+        assert_eq!(body["tasks"][0]["description"], REDACTED);
+        assert_eq!(body["tasks"][1]["description"], Value::Null);
+        assert_eq!(body["attempt"]["target_branch"], "main");
+        assert_eq!(
+            body["conversation"][1]["entry_type"]["action_type"]["path"],
+            "file-2.rs"
+        );
+        let entries: Vec<NormalizedEntry> =
+            serde_json::from_value(body["conversation"].clone()).unwrap();
+        assert_eq!(entries.len(), 5);
+    }
+
+    #[test]
+    fn paths_only_keeps_content_but_generalizes_paths() {
+        let mut redactor = Redactor::new(PrivacyMode::PathsOnly);
+        let body = json!({
+            "attempt": redact(&mut redactor, &attempt()),
+            "conversation": redact(&mut redactor, &conversation()),
+        });
+        let text = body.to_string();
+        assert_clean(&text, &[WORKTREE, FILE, "acme_export.rs"]);
+        assert!(text.contains(PROMPT));
+        assert!(text.contains(TITLE));
+        assert_eq!(body["attempt"]["branch"], BRANCH);
+        assert_eq!(body["conversation"][1]["content"], "`file-2.rs`");
+        assert_eq!(
+            body["conversation"][2]["entry_type"]["action_type"]["command"],
+            "cargo test --manifest-path path-1/Cargo.toml"
+        );
+    }
+
+    #[test]
+    fn stream_patches_redact_pointers_and_values() {
+        for mode in [PrivacyMode::PathsOnly, PrivacyMode::RedactContent] {
+            let mut redactor = Redactor::new(mode);
+            let LogMsg::JsonPatch(patch) = redactor.redact_log_msg(LogMsg::JsonPatch(diff_patch()))
+            else {
+                panic!("expected a patch");
+            };
+            let text = serde_json::to_string(&patch).unwrap();
+            assert_clean(&text, &[FILE, "acme_export"]);
+            assert!(text.contains("/entries/file-1.rs"));
+            assert_eq!(text.contains("acme"), mode == PrivacyMode::PathsOnly);
+
+            let LogMsg::Stdout(line) =
+                redactor.redact_log_msg(LogMsg::Stdout(format!("compiling {FILE}")))
+            else {
+                panic!("expected stdout");
+            };
+            assert_clean(&line, &[FILE]);
+        }
+    }
+
+    #[test]
+    fn forced_mode_wins_when_stricter() {
+        use PrivacyMode::*;
+        assert_eq!(PrivacyMode::resolve(None, None), None);
+        assert_eq!(PrivacyMode::resolve(Some(PathsOnly), None), Some(PathsOnly));
+        assert_eq!(
+            PrivacyMode::resolve(Some(PathsOnly), Some(RedactContent)),
+            Some(RedactContent)
+        );
+        assert_eq!(
+            PrivacyMode::resolve(Some(RedactContent), Some(PathsOnly)),
+            Some(RedactContent)
+        );
+        assert_eq!(task_label(26), "Task AA");
+    }
+}
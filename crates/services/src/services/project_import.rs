@@ -0,0 +1,239 @@
+//! Creating a project by cloning a remote repository.
+//!
+//! Clone progress goes to a [`MsgStore`] that the UI follows through the raw
+//! log stream, the same way it follows an execution process. Nothing is left
+//! on disk if the clone fails.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use thiserror::Error;
+use tokio::{io::AsyncReadExt, process::Command};
+use utils::{msg_store::MsgStore, shell::resolve_executable_path};
+
+#[derive(Debug, Error)]
+pub enum ProjectImportError {
+    #[error("{0} already exists and is not empty")]
+    DestinationNotEmpty(String),
+    #[error("{destination} is inside the existing project at {project}")]
+    NestedInProject {
+        destination: String,
+        project: String,
+    },
+    #[error("git executable not found or not runnable")]
+    GitNotAvailable,
+    #[error("git clone failed: {0}")]
+    CloneFailed(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// A clone target must be new or empty, and must not sit inside another
+/// project's repository.
+pub fn check_destination<'a>(
+    destination: &Path,
+    project_paths: impl IntoIterator<Item = &'a Path>,
+) -> Result<(), ProjectImportError> {
+    if let Some(project) = project_paths
+        .into_iter()
+        .find(|project| destination.starts_with(project))
+    {
+        return Err(ProjectImportError::NestedInProject {
+            destination: destination.display().to_string(),
+            project: project.display().to_string(),
+        });
+    }
+    if destination.exists() && (!destination.is_dir() || destination.read_dir()?.next().is_some()) {
+        return Err(ProjectImportError::DestinationNotEmpty(
+            destination.display().to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Deletes whatever an unfinished import wrote to the destination. A
+/// directory that existed beforehand is left in place, empty.
+pub struct DestinationGuard {
+    path: PathBuf,
+    existed: bool,
+    armed: bool,
+}
+
+impl DestinationGuard {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            existed: path.exists(),
+            armed: true,
+        }
+    }
+
+    /// Keep the destination; the import succeeded.
+    pub fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for DestinationGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        if let Err(e) = std::fs::remove_dir_all(&self.path)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::warn!(
+                "Failed to clean up partial clone at {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+        if self.existed {
+            let _ = std::fs::create_dir(&self.path);
+        }
+    }
+}
+
+/// Run `git clone --progress`, writing each progress line to `log`. Uses the
+/// user's git credentials; prompts are disabled so a missing credential fails
+/// instead of hanging.
+pub async fn clone_repository(
+    repo_url: &str,
+    destination: &Path,
+    log: &MsgStore,
+) -> Result<(), ProjectImportError> {
+    let git = resolve_executable_path("git")
+        .await
+        .ok_or(ProjectImportError::GitNotAvailable)?;
+    let mut child = Command::new(git)
+        .args(["clone", "--progress", "--"])
+        .arg(repo_url)
+        .arg(destination)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let mut lines = ProgressLines::default();
+    let mut last_line = None;
+    let mut buf = [0u8; 4096];
+    loop {
+        let read = stderr.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        for line in lines.push(&String::from_utf8_lossy(&buf[..read])) {
+            log.push_stdout(format!("{line}\n"));
+            last_line = Some(line);
+        }
+    }
+    if let Some(line) = lines.finish() {
+        log.push_stdout(format!("{line}\n"));
+        last_line = Some(line);
+    }
+
+    if child.wait().await?.success() {
+        Ok(())
+    } else {
+        Err(ProjectImportError::CloneFailed(
+            last_line.unwrap_or_else(|| "git exited without output".to_string()),
+        ))
+    }
+}
+
+/// Splits git's progress output, which redraws a line with `\r` as counts
+/// change, into one line per update.
+#[derive(Default)]
+struct ProgressLines {
+    partial: String,
+}
+
+impl ProgressLines {
+    fn push(&mut self, chunk: &str) -> Vec<String> {
+        self.partial.push_str(chunk);
+        let mut lines = Vec::new();
+        while let Some(end) = self.partial.find(['\r', '\n']) {
+            let line: String = self.partial.drain(..=end).collect();
+            let line = line.trim_end();
+            if !line.is_empty() {
+                lines.push(line.to_string());
+            }
+        }
+        lines
+    }
+
+    fn finish(self) -> Option<String> {
+        let line = self.partial.trim();
+        (!line.is_empty()).then(|| line.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_redraws_become_separate_lines() {
+        let mut lines = ProgressLines::default();
+        assert_eq!(
+            lines.push("Cloning into 'repo'...\nReceiving objects:  50% (1/2)\rRecei"),
+            ["Cloning into 'repo'...", "Receiving objects:  50% (1/2)"]
+        );
+        assert_eq!(
+            lines.push("ving objects: 100% (2/2), done.\r\n"),
+            ["Receiving objects: 100% (2/2), done."]
+        );
+        lines.push("fatal: early EOF");
+        assert_eq!(lines.finish().as_deref(), Some("fatal: early EOF"));
+    }
+
+    #[test]
+    fn destinations_inside_projects_or_with_files_are_rejected() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("app");
+        std::fs::create_dir(&project).unwrap();
+
+        assert!(matches!(
+            check_destination(&project.join("vendor/lib"), [project.as_path()]),
+            Err(ProjectImportError::NestedInProject { .. })
+        ));
+        // Sharing a name prefix is not nesting
+        assert!(check_destination(&root.path().join("app-two"), [project.as_path()]).is_ok());
+
+        std::fs::write(project.join("README.md"), "").unwrap();
+        assert!(matches!(
+            check_destination(&project, []),
+            Err(ProjectImportError::DestinationNotEmpty(_))
+        ));
+        assert!(check_destination(&root.path().join("new"), []).is_ok());
+    }
+
+    #[test]
+    fn guard_removes_a_failed_clone_but_keeps_a_finished_one() {
+        let root = tempfile::tempdir().unwrap();
+        let failed = root.path().join("failed");
+        let guard = DestinationGuard::new(&failed);
+        std::fs::create_dir_all(failed.join(".git")).unwrap();
+        drop(guard);
+        assert!(!failed.exists());
+
+        let existing = root.path().join("existing");
+        std::fs::create_dir(&existing).unwrap();
+        let guard = DestinationGuard::new(&existing);
+        std::fs::write(existing.join("partial"), "").unwrap();
+        drop(guard);
+        assert!(existing.exists());
+        assert!(existing.read_dir().unwrap().next().is_none());
+
+        let finished = root.path().join("finished");
+        let guard = DestinationGuard::new(&finished);
+        std::fs::create_dir(&finished).unwrap();
+        guard.disarm();
+        assert!(finished.exists());
+    }
+}
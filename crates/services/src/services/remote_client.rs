@@ -3,12 +3,17 @@
 use std::time::Duration;
 
 use backon::{ExponentialBuilder, Retryable};
-use chrono::Duration as ChronoDuration;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use remote::{
-    activity::ActivityResponse,
-    routes::tasks::{
-        AssignSharedTaskRequest, BulkSharedTasksResponse, CreateSharedTaskRequest,
-        DeleteSharedTaskRequest, SharedTaskResponse, UpdateSharedTaskRequest,
+    activity::{ActivityFilter, ActivityResponse},
+    routes::{
+        activity::RESYNC_REQUIRED_ERROR,
+        devices::{DeviceAckRequest, DeviceAckResponse},
+        tasks::{
+            AssignSharedTaskRequest, BulkSharedTasksResponse, CreateSharedTaskRequest,
+            DeleteSharedTaskRequest, SharedTaskResponse, TaskDigestResponse,
+            UpdateSharedTaskRequest,
+        },
     },
 };
 use reqwest::{Client, StatusCode};
@@ -21,18 +26,21 @@ use utils::{
     api::{
         oauth::{
             HandoffInitRequest, HandoffInitResponse, HandoffRedeemRequest, HandoffRedeemResponse,
-            ProfileResponse, TokenRefreshRequest, TokenRefreshResponse,
+            ListSessionsResponse, ProfileResponse, RevokeSessionResponse, TokenRefreshRequest,
+            TokenRefreshResponse,
         },
         organizations::{
             AcceptInvitationResponse, CreateInvitationRequest, CreateInvitationResponse,
             CreateOrganizationRequest, CreateOrganizationResponse, GetInvitationResponse,
-            GetOrganizationResponse, ListInvitationsResponse, ListMembersResponse,
-            ListOrganizationsResponse, Organization, RevokeInvitationRequest,
+            GetOrganizationResponse, ListAuditLogResponse, ListInvitationsResponse,
+            ListMembersResponse, ListOrganizationsResponse, Organization, RevokeInvitationRequest,
             UpdateMemberRoleRequest, UpdateMemberRoleResponse, UpdateOrganizationRequest,
         },
         projects::{ListProjectsResponse, RemoteProject},
     },
     jwt::extract_expiration,
+    network::NetworkConfig,
+    request_id::{self, REQUEST_ID_HEADER},
 };
 use uuid::Uuid;
 
@@ -58,6 +66,8 @@ pub enum RemoteClientError {
     Storage(String),
     #[error("invalid access token: {0}")]
     Token(String),
+    #[error("activity cursor is older than the remote keeps; a bulk sync is required")]
+    ResyncRequired,
 }
 
 impl RemoteClientError {
@@ -69,6 +79,11 @@ impl RemoteClientError {
             _ => false,
         }
     }
+
+    /// Returns true if the remote answered that the resource does not exist.
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Self::Http { status: 404, .. })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -81,6 +96,7 @@ pub enum HandoffErrorCode {
     Expired,
     AccessDenied,
     InternalError,
+    InvalidEmailDomain,
     Other(String),
 }
 
@@ -94,6 +110,7 @@ fn map_error_code(code: Option<&str>) -> HandoffErrorCode {
         "expired" | "expired_token" => HandoffErrorCode::Expired,
         "access_denied" => HandoffErrorCode::AccessDenied,
         "internal_error" => HandoffErrorCode::InternalError,
+        "invalid_email_domain" => HandoffErrorCode::InvalidEmailDomain,
         other => HandoffErrorCode::Other(other.to_string()),
     }
 }
@@ -135,11 +152,23 @@ impl RemoteClient {
     const TOKEN_REFRESH_LEEWAY_SECS: i64 = 20;
 
     pub fn new(base_url: &str, auth_context: AuthContext) -> Result<Self, RemoteClientError> {
+        Self::with_network(base_url, auth_context, &NetworkConfig::default())
+    }
+
+    /// Like [`RemoteClient::new`], connecting through the configured proxy
+    /// and trusting its extra root certificates.
+    pub fn with_network(
+        base_url: &str,
+        auth_context: AuthContext,
+        network: &NetworkConfig,
+    ) -> Result<Self, RemoteClientError> {
         let base = Url::parse(base_url).map_err(|e| RemoteClientError::Url(e.to_string()))?;
-        let http = Client::builder()
+        let builder = Client::builder()
             .timeout(Self::REQUEST_TIMEOUT)
-            .user_agent(concat!("remote-client/", env!("CARGO_PKG_VERSION")))
-            .build()
+            .user_agent(concat!("remote-client/", env!("CARGO_PKG_VERSION")));
+        let http = network
+            .apply_to_reqwest(builder)
+            .and_then(|builder| builder.build())
             .map_err(|e| RemoteClientError::Transport(e.to_string()))?;
         Ok(Self {
             base,
@@ -184,17 +213,55 @@ impl RemoteClient {
                 self.refresh_credentials(&latest).await
             };
 
-            match refreshed {
-                Ok(updated) => updated.access_token.ok_or(RemoteClientError::Auth),
-                Err(RemoteClientError::Auth) => {
-                    let _ = self.auth_context.clear_credentials().await;
-                    Err(RemoteClientError::Auth)
+            self.refreshed_token(refreshed).await
+        })
+    }
+
+    /// Refreshes after the server rejected `rejected` even though it had not
+    /// expired by our clock. Requests that hit a 401 together share one
+    /// refresh: once the lock is released, the others find `rejected` already
+    /// replaced and reuse the new token.
+    fn refresh_access_token<'a>(
+        &'a self,
+        rejected: &'a str,
+    ) -> std::pin::Pin<
+        Box<dyn std::future::Future<Output = Result<String, RemoteClientError>> + Send + 'a>,
+    > {
+        Box::pin(async move {
+            let refreshed = {
+                let _refresh_guard = self.auth_context.refresh_guard().await;
+                let latest = self
+                    .auth_context
+                    .get_credentials()
+                    .await
+                    .ok_or(RemoteClientError::Auth)?;
+                if let Some(token) = latest.access_token.as_ref()
+                    && token != rejected
+                {
+                    return Ok(token.clone());
                 }
-                Err(err) => Err(err),
-            }
+
+                self.refresh_credentials(&latest).await
+            };
+
+            self.refreshed_token(refreshed).await
         })
     }
 
+    async fn refreshed_token(
+        &self,
+        refreshed: Result<Credentials, RemoteClientError>,
+    ) -> Result<String, RemoteClientError> {
+        match refreshed {
+            Ok(updated) => updated.access_token.ok_or(RemoteClientError::Auth),
+            Err(RemoteClientError::Auth) => {
+                let _ = self.auth_context.clear_credentials().await;
+                Err(RemoteClientError::Auth)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
     async fn refresh_credentials(
         &self,
         creds: &Credentials,
@@ -281,28 +348,50 @@ impl RemoteClient {
             .base
             .join(path)
             .map_err(|e| RemoteClientError::Url(e.to_string()))?;
+        // Lets the remote server's logs be joined with ours
+        let request_id = request_id::current();
 
         (|| async {
-            let mut req = self.http.request(method.clone(), url.clone());
+            let mut token = if requires_auth {
+                Some(self.require_token().await?)
+            } else {
+                None
+            };
+            // A 401 is retried once with a freshly refreshed token
+            let mut refreshed = false;
 
-            if requires_auth {
-                let token = self.require_token().await?;
-                req = req.bearer_auth(token);
-            }
+            loop {
+                let mut req = self.http.request(method.clone(), url.clone());
 
-            if let Some(b) = body {
-                req = req.json(b);
-            }
+                if let Some(token) = &token {
+                    req = req.bearer_auth(token);
+                }
+
+                if let Some(request_id) = &request_id {
+                    req = req.header(REQUEST_ID_HEADER, request_id);
+                }
 
-            let res = req.send().await.map_err(map_reqwest_error)?;
+                if let Some(b) = body {
+                    req = req.json(b);
+                }
 
-            match res.status() {
-                s if s.is_success() => Ok(res),
-                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(RemoteClientError::Auth),
-                s => {
-                    let status = s.as_u16();
-                    let body = res.text().await.unwrap_or_default();
-                    Err(RemoteClientError::Http { status, body })
+                let res = req.send().await.map_err(map_reqwest_error)?;
+
+                match res.status() {
+                    s if s.is_success() => return Ok(res),
+                    StatusCode::UNAUTHORIZED if !refreshed && token.is_some() => {
+                        let rejected = token.take().unwrap_or_default();
+                        token = Some(self.refresh_access_token(&rejected).await?);
+                        refreshed = true;
+                    }
+                    StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                        return Err(RemoteClientError::Auth);
+                    }
+                    s => {
+                        let status = s.as_u16();
+                        let body = res.text().await.unwrap_or_default();
+                        return Err(RemoteClientError::Http { status, body });
+                    }
                 }
             }
         })
@@ -410,6 +499,29 @@ impl RemoteClient {
         self.delete_authed("/v1/oauth/logout").await
     }
 
+    /// Lists the user's active sessions, marking the one this client uses.
+    pub async fn list_sessions(&self) -> Result<ListSessionsResponse, RemoteClientError> {
+        self.get_authed("/v1/sessions").await
+    }
+
+    /// Revokes one of the user's sessions.
+    pub async fn revoke_session(
+        &self,
+        session_id: Uuid,
+    ) -> Result<RevokeSessionResponse, RemoteClientError> {
+        let res = self
+            .send(
+                reqwest::Method::DELETE,
+                &format!("/v1/sessions/{session_id}"),
+                true,
+                None::<&()>,
+            )
+            .await?;
+        res.json::<RevokeSessionResponse>()
+            .await
+            .map_err(|e| RemoteClientError::Serde(e.to_string()))
+    }
+
     /// Lists organizations for the authenticated user.
     pub async fn list_organizations(&self) -> Result<ListOrganizationsResponse, RemoteClientError> {
         self.get_authed("/v1/organizations").await
@@ -479,6 +591,7 @@ impl RemoteClient {
             Some(request),
         )
         .await
+        .map_err(|e| self.map_api_error(e))
     }
 
     /// Lists invitations for an organization.
@@ -513,6 +626,7 @@ impl RemoteClient {
             None::<&()>,
         )
         .await
+        .map_err(|e| self.map_api_error(e))
     }
 
     /// Lists members of an organization.
@@ -548,6 +662,26 @@ impl RemoteClient {
         .await
     }
 
+    /// Lists an organization's audit log, newest first. Pass the previous
+    /// page's `next_cursor` as `before` to continue.
+    pub async fn list_audit_log(
+        &self,
+        org_id: Uuid,
+        before: Option<DateTime<Utc>>,
+        limit: u32,
+    ) -> Result<ListAuditLogResponse, RemoteClientError> {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        query.append_pair("limit", &limit.to_string());
+        if let Some(before) = before {
+            query.append_pair("before", &before.to_rfc3339());
+        }
+        self.get_authed(&format!(
+            "/v1/organizations/{org_id}/audit-log?{}",
+            query.finish()
+        ))
+        .await
+    }
+
     /// Creates a shared task.
     pub async fn create_shared_task(
         &self,
@@ -595,18 +729,34 @@ impl RemoteClient {
             .map_err(|e| RemoteClientError::Serde(e.to_string()))
     }
 
-    /// Fetches activity events for a project.
+    /// Fetches activity events for a project, narrowed by `filter`.
     pub async fn fetch_activity(
         &self,
         project_id: Uuid,
         after: Option<i64>,
         limit: u32,
+        filter: &ActivityFilter,
     ) -> Result<ActivityResponse, RemoteClientError> {
         let mut path = format!("/v1/activity?project_id={project_id}&limit={limit}");
         if let Some(seq) = after {
             path.push_str(&format!("&after={seq}"));
         }
-        self.get_authed(&path).await
+        if !filter.is_empty() {
+            let query = url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(filter.query_pairs())
+                .finish();
+            path.push('&');
+            path.push_str(&query);
+        }
+        self.get_authed(&path).await.map_err(|err| match &err {
+            RemoteClientError::Http { status: 410, body }
+                if serde_json::from_str::<ApiErrorResponse>(body)
+                    .is_ok_and(|api_err| api_err.error == RESYNC_REQUIRED_ERROR) =>
+            {
+                RemoteClientError::ResyncRequired
+            }
+            _ => err,
+        })
     }
 
     /// Fetches bulk snapshot of shared tasks for a project.
@@ -617,6 +767,46 @@ impl RemoteClient {
         self.get_authed(&format!("/v1/tasks/bulk?project_id={project_id}"))
             .await
     }
+
+    /// Fetches a page of shared task digests for a project.
+    pub async fn fetch_task_digest(
+        &self,
+        project_id: Uuid,
+        after: Option<Uuid>,
+        limit: u32,
+    ) -> Result<TaskDigestResponse, RemoteClientError> {
+        let mut path = format!("/v1/tasks/digest?project_id={project_id}&limit={limit}");
+        if let Some(after) = after {
+            path.push_str(&format!("&after={after}"));
+        }
+        self.get_authed(&path).await
+    }
+
+    /// Fetches a single shared task.
+    pub async fn fetch_shared_task(
+        &self,
+        task_id: Uuid,
+    ) -> Result<SharedTaskResponse, RemoteClientError> {
+        self.get_authed(&format!("/v1/tasks/{task_id}")).await
+    }
+
+    /// Records how far this device has applied a project's activity.
+    pub async fn ack_device(
+        &self,
+        device_id: Uuid,
+        project_id: Uuid,
+        last_seq: i64,
+    ) -> Result<DeviceAckResponse, RemoteClientError> {
+        self.post_authed(
+            "/v1/devices/ack",
+            Some(&DeviceAckRequest {
+                device_id,
+                project_id,
+                last_seq,
+            }),
+        )
+        .await
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -0,0 +1,522 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use ts_rs::TS;
+
+/// Files bigger than this are not worth reading for suggestions
+const MAX_MANIFEST_BYTES: u64 = 1024 * 1024;
+
+pub const VIBE_KANBAN_CONFIG_FILE: &str = ".vibe-kanban.toml";
+
+// npm writes this into package.json when no test script was set up
+const NPM_PLACEHOLDER_TEST: &str = "echo \"Error: no test specified\" && exit 1";
+
+const LOCKFILES: &[&str] = &[
+    "package-lock.json",
+    "pnpm-lock.yaml",
+    "yarn.lock",
+    "bun.lockb",
+    "Cargo.lock",
+    "poetry.lock",
+    "uv.lock",
+    "Pipfile.lock",
+];
+
+const BUILD_DIRS: &[&str] = &[
+    "node_modules",
+    "dist",
+    "build",
+    ".next",
+    ".nuxt",
+    "target",
+    ".venv",
+    "venv",
+    "__pycache__",
+];
+
+#[derive(Debug, Error)]
+pub enum RepoAnalysisError {
+    #[error("Repository path does not exist: {0}")]
+    RepoNotFound(PathBuf),
+    #[error("Repository analysis timed out")]
+    TimedOut,
+    #[error("Unknown suggestion: {0}")]
+    UnknownSuggestion(String),
+    #[error("Only one {0} suggestion can be applied")]
+    Conflicting(&'static str),
+    #[error("Suggestion cannot be applied: {0}")]
+    NotApplicable(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionKind {
+    PackageManager,
+    SetupScript,
+    DevScript,
+    TestCommand,
+    DefaultBranch,
+    IgnorePattern,
+    ConfigFile,
+}
+
+impl SuggestionKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SuggestionKind::PackageManager => "package_manager",
+            SuggestionKind::SetupScript => "setup_script",
+            SuggestionKind::DevScript => "dev_script",
+            SuggestionKind::TestCommand => "test_command",
+            SuggestionKind::DefaultBranch => "default_branch",
+            SuggestionKind::IgnorePattern => "ignore_pattern",
+            SuggestionKind::ConfigFile => "config_file",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionConfidence {
+    Low,
+    Medium,
+    High,
+}
+
+/// Where a suggestion came from, e.g. `package.json` line 7: `"dev": "vite"`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct SuggestionEvidence {
+    pub path: String,
+    /// 1-based line number, when the evidence is a specific line
+    pub line: Option<usize>,
+    pub snippet: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ProjectSuggestion {
+    /// Stable across analyses of the same repository contents
+    pub id: String,
+    pub kind: SuggestionKind,
+    pub value: String,
+    pub confidence: SuggestionConfidence,
+    pub evidence: Vec<SuggestionEvidence>,
+    /// Whether this can be written to the project settings
+    pub applicable: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct ProjectAnalysis {
+    pub suggestions: Vec<ProjectSuggestion>,
+    pub has_vibe_kanban_config: bool,
+}
+
+/// Project settings produced from a set of accepted suggestions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppliedSuggestions {
+    pub setup_script: Option<String>,
+    pub dev_script: Option<String>,
+}
+
+impl ProjectAnalysis {
+    fn push(
+        &mut self,
+        kind: SuggestionKind,
+        value: impl Into<String>,
+        confidence: SuggestionConfidence,
+        evidence: Vec<SuggestionEvidence>,
+    ) {
+        let value = value.into();
+        let id = format!("{}:{}", kind.as_str(), value);
+        if self.suggestions.iter().any(|s| s.id == id) {
+            return;
+        }
+        self.suggestions.push(ProjectSuggestion {
+            id,
+            kind,
+            value,
+            confidence,
+            evidence,
+            applicable: matches!(
+                kind,
+                SuggestionKind::SetupScript | SuggestionKind::DevScript
+            ),
+        });
+    }
+
+    /// Resolve the accepted suggestion ids into project settings. Setup scripts
+    /// are chained in the order they were suggested; only one dev script may be
+    /// picked.
+    pub fn resolve(&self, ids: &[String]) -> Result<AppliedSuggestions, RepoAnalysisError> {
+        if let Some(unknown) = ids
+            .iter()
+            .find(|id| !self.suggestions.iter().any(|s| &s.id == *id))
+        {
+            return Err(RepoAnalysisError::UnknownSuggestion(unknown.clone()));
+        }
+
+        let selected: Vec<&ProjectSuggestion> = self
+            .suggestions
+            .iter()
+            .filter(|s| ids.contains(&s.id))
+            .collect();
+        if let Some(s) = selected.iter().find(|s| !s.applicable) {
+            return Err(RepoAnalysisError::NotApplicable(s.id.clone()));
+        }
+
+        let setup: Vec<&str> = selected
+            .iter()
+            .filter(|s| s.kind == SuggestionKind::SetupScript)
+            .map(|s| s.value.as_str())
+            .collect();
+        let dev: Vec<&str> = selected
+            .iter()
+            .filter(|s| s.kind == SuggestionKind::DevScript)
+            .map(|s| s.value.as_str())
+            .collect();
+        if dev.len() > 1 {
+            return Err(RepoAnalysisError::Conflicting("dev script"));
+        }
+
+        Ok(AppliedSuggestions {
+            setup_script: (!setup.is_empty()).then(|| setup.join("\n")),
+            dev_script: dev.first().map(|s| s.to_string()),
+        })
+    }
+}
+
+/// Inspect `repo_path` on a blocking thread, giving up after `timeout`.
+pub async fn analyze_repository_with_timeout(
+    repo_path: PathBuf,
+    timeout: Duration,
+) -> Result<ProjectAnalysis, RepoAnalysisError> {
+    let task = tokio::task::spawn_blocking(move || analyze_repository(&repo_path));
+    match tokio::time::timeout(timeout, task).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(join_error)) => Err(std::io::Error::other(join_error).into()),
+        Err(_) => Err(RepoAnalysisError::TimedOut),
+    }
+}
+
+/// Suggest project settings from the files in `repo_path`. This only reads
+/// manifests and lockfiles at the repository root; nothing from the
+/// repository is ever executed.
+pub fn analyze_repository(repo_path: &Path) -> Result<ProjectAnalysis, RepoAnalysisError> {
+    if !repo_path.is_dir() {
+        return Err(RepoAnalysisError::RepoNotFound(repo_path.to_path_buf()));
+    }
+
+    let mut analysis = ProjectAnalysis::default();
+    detect_node(repo_path, &mut analysis);
+    detect_rust(repo_path, &mut analysis);
+    detect_python(repo_path, &mut analysis);
+    detect_ignore_patterns(repo_path, &mut analysis);
+    detect_default_branch(repo_path, &mut analysis);
+
+    if repo_path.join(VIBE_KANBAN_CONFIG_FILE).is_file() {
+        analysis.has_vibe_kanban_config = true;
+        analysis.push(
+            SuggestionKind::ConfigFile,
+            VIBE_KANBAN_CONFIG_FILE,
+            SuggestionConfidence::High,
+            vec![file_evidence(VIBE_KANBAN_CONFIG_FILE)],
+        );
+    }
+
+    Ok(analysis)
+}
+
+fn read_small(repo_path: &Path, name: &str) -> Option<String> {
+    let path = repo_path.join(name);
+    let metadata = fs::metadata(&path).ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_MANIFEST_BYTES {
+        return None;
+    }
+    fs::read_to_string(path).ok()
+}
+
+fn file_evidence(path: &str) -> SuggestionEvidence {
+    SuggestionEvidence {
+        path: path.to_string(),
+        line: None,
+        snippet: None,
+    }
+}
+
+/// Evidence pointing at the first line of `contents` containing `needle`
+fn line_evidence(path: &str, contents: &str, needle: &str) -> SuggestionEvidence {
+    contents
+        .lines()
+        .enumerate()
+        .find(|(_, line)| line.contains(needle))
+        .map(|(i, line)| SuggestionEvidence {
+            path: path.to_string(),
+            line: Some(i + 1),
+            snippet: Some(line.trim().trim_end_matches(',').to_string()),
+        })
+        .unwrap_or_else(|| file_evidence(path))
+}
+
+fn detect_node(repo_path: &Path, analysis: &mut ProjectAnalysis) {
+    let Some(contents) = read_small(repo_path, "package.json") else {
+        return;
+    };
+    let manifest: Value = serde_json::from_str(&contents).unwrap_or(Value::Null);
+
+    let (manager, install, lock_evidence) = if repo_path.join("pnpm-lock.yaml").is_file() {
+        ("pnpm", "pnpm install", Some("pnpm-lock.yaml"))
+    } else if repo_path.join("yarn.lock").is_file() {
+        ("yarn", "yarn install", Some("yarn.lock"))
+    } else if repo_path.join("bun.lockb").is_file() {
+        ("bun", "bun install", Some("bun.lockb"))
+    } else if repo_path.join("package-lock.json").is_file() {
+        ("npm", "npm ci", Some("package-lock.json"))
+    } else {
+        let declared = manifest["packageManager"]
+            .as_str()
+            .and_then(|pm| pm.split('@').next())
+            .unwrap_or("npm");
+        match declared {
+            "pnpm" => ("pnpm", "pnpm install", None),
+            "yarn" => ("yarn", "yarn install", None),
+            "bun" => ("bun", "bun install", None),
+            _ => ("npm", "npm install", None),
+        }
+    };
+
+    let (confidence, evidence) = match lock_evidence {
+        Some(lockfile) => (SuggestionConfidence::High, file_evidence(lockfile)),
+        None if manifest["packageManager"].is_string() => (
+            SuggestionConfidence::High,
+            line_evidence("package.json", &contents, "\"packageManager\""),
+        ),
+        None => (SuggestionConfidence::Medium, file_evidence("package.json")),
+    };
+    analysis.push(
+        SuggestionKind::PackageManager,
+        manager,
+        confidence,
+        vec![evidence.clone()],
+    );
+    analysis.push(
+        SuggestionKind::SetupScript,
+        install,
+        confidence,
+        vec![evidence],
+    );
+
+    let scripts = manifest["scripts"].as_object();
+    let run = |script: &str| format!("{manager} run {script}");
+
+    if let Some(scripts) = scripts {
+        let dev = [
+            ("dev", SuggestionConfidence::High),
+            ("start", SuggestionConfidence::Medium),
+            ("serve", SuggestionConfidence::Medium),
+        ]
+        .into_iter()
+        .find(|(name, _)| scripts.get(*name).is_some_and(Value::is_string));
+        if let Some((name, confidence)) = dev {
+            analysis.push(
+                SuggestionKind::DevScript,
+                run(name),
+                confidence,
+                vec![line_evidence(
+                    "package.json",
+                    &contents,
+                    &format!("\"{name}\":"),
+                )],
+            );
+        }
+
+        if let Some(test) = scripts.get("test").and_then(Value::as_str)
+            && test.trim() != NPM_PLACEHOLDER_TEST
+        {
+            analysis.push(
+                SuggestionKind::TestCommand,
+                run("test"),
+                SuggestionConfidence::High,
+                vec![line_evidence("package.json", &contents, "\"test\":")],
+            );
+        }
+    }
+}
+
+fn detect_rust(repo_path: &Path, analysis: &mut ProjectAnalysis) {
+    let Some(contents) = read_small(repo_path, "Cargo.toml") else {
+        return;
+    };
+
+    let evidence = if repo_path.join("Cargo.lock").is_file() {
+        file_evidence("Cargo.lock")
+    } else {
+        line_evidence("Cargo.toml", &contents, "[")
+    };
+    analysis.push(
+        SuggestionKind::PackageManager,
+        "cargo",
+        SuggestionConfidence::High,
+        vec![evidence.clone()],
+    );
+    analysis.push(
+        SuggestionKind::SetupScript,
+        "cargo fetch",
+        SuggestionConfidence::High,
+        vec![evidence],
+    );
+    analysis.push(
+        SuggestionKind::TestCommand,
+        "cargo test",
+        SuggestionConfidence::High,
+        vec![file_evidence("Cargo.toml")],
+    );
+
+    let is_workspace = contents.lines().any(|l| l.trim() == "[workspace]");
+    if !is_workspace && repo_path.join("src/main.rs").is_file() {
+        analysis.push(
+            SuggestionKind::DevScript,
+            "cargo run",
+            SuggestionConfidence::Low,
+            vec![file_evidence("src/main.rs")],
+        );
+    }
+}
+
+fn detect_python(repo_path: &Path, analysis: &mut ProjectAnalysis) {
+    let requirements = read_small(repo_path, "requirements.txt");
+    let pyproject = read_small(repo_path, "pyproject.toml");
+    if requirements.is_none() && pyproject.is_none() {
+        return;
+    }
+
+    let (manager, install, evidence) = if repo_path.join("uv.lock").is_file() {
+        ("uv", "uv sync", file_evidence("uv.lock"))
+    } else if let Some(pyproject) = &pyproject
+        && pyproject.contains("[tool.poetry]")
+    {
+        (
+            "poetry",
+            "poetry install",
+            line_evidence("pyproject.toml", pyproject, "[tool.poetry]"),
+        )
+    } else if requirements.is_some() {
+        (
+            "pip",
+            "pip install -r requirements.txt",
+            file_evidence("requirements.txt"),
+        )
+    } else {
+        ("pip", "pip install -e .", file_evidence("pyproject.toml"))
+    };
+    analysis.push(
+        SuggestionKind::PackageManager,
+        manager,
+        SuggestionConfidence::High,
+        vec![evidence.clone()],
+    );
+    analysis.push(
+        SuggestionKind::SetupScript,
+        install,
+        SuggestionConfidence::High,
+        vec![evidence],
+    );
+
+    if repo_path.join("manage.py").is_file() {
+        analysis.push(
+            SuggestionKind::DevScript,
+            "python manage.py runserver",
+            SuggestionConfidence::Medium,
+            vec![file_evidence("manage.py")],
+        );
+    }
+
+    let pytest_evidence = ["pytest.ini", "conftest.py"]
+        .into_iter()
+        .find(|f| repo_path.join(f).is_file())
+        .map(file_evidence)
+        .or_else(|| {
+            requirements
+                .as_deref()
+                .filter(|r| r.contains("pytest"))
+                .map(|r| line_evidence("requirements.txt", r, "pytest"))
+        })
+        .or_else(|| {
+            pyproject
+                .as_deref()
+                .filter(|p| p.contains("pytest"))
+                .map(|p| line_evidence("pyproject.toml", p, "pytest"))
+        });
+    if let Some(evidence) = pytest_evidence {
+        analysis.push(
+            SuggestionKind::TestCommand,
+            "pytest",
+            SuggestionConfidence::Medium,
+            vec![evidence],
+        );
+    }
+}
+
+fn detect_ignore_patterns(repo_path: &Path, analysis: &mut ProjectAnalysis) {
+    for lockfile in LOCKFILES {
+        if repo_path.join(lockfile).is_file() {
+            analysis.push(
+                SuggestionKind::IgnorePattern,
+                *lockfile,
+                SuggestionConfidence::High,
+                vec![file_evidence(lockfile)],
+            );
+        }
+    }
+    for dir in BUILD_DIRS {
+        if repo_path.join(dir).is_dir() {
+            analysis.push(
+                SuggestionKind::IgnorePattern,
+                format!("{dir}/"),
+                SuggestionConfidence::High,
+                vec![file_evidence(&format!("{dir}/"))],
+            );
+        }
+    }
+}
+
+fn detect_default_branch(repo_path: &Path, analysis: &mut ProjectAnalysis) {
+    let Ok(repo) = git2::Repository::open(repo_path) else {
+        return;
+    };
+
+    let remote_head = repo
+        .find_reference("refs/remotes/origin/HEAD")
+        .ok()
+        .and_then(|r| r.symbolic_target().map(str::to_string))
+        .and_then(|target| {
+            target
+                .strip_prefix("refs/remotes/origin/")
+                .map(str::to_string)
+        });
+    if let Some(branch) = remote_head {
+        analysis.push(
+            SuggestionKind::DefaultBranch,
+            branch,
+            SuggestionConfidence::High,
+            vec![file_evidence("refs/remotes/origin/HEAD")],
+        );
+        return;
+    }
+
+    if let Ok(head) = repo.head()
+        && let Some(branch) = head.shorthand()
+    {
+        analysis.push(
+            SuggestionKind::DefaultBranch,
+            branch,
+            SuggestionConfidence::Low,
+            vec![file_evidence("HEAD")],
+        );
+    }
+}
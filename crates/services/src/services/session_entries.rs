@@ -0,0 +1,286 @@
+//! Persistence of coding agent conversations.
+//!
+//! While a run is live its normalized entries are written to
+//! `executor_session_entries` as the normalizer produces them, so the
+//! conversation no longer depends on the raw logs being kept. Runs from
+//! before the table existed are filled in by [`backfill`].
+
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc, time::Duration};
+
+use db::{
+    DBService,
+    models::{
+        execution_process::ExecutionProcess, execution_process_logs::ExecutionProcessLogs,
+        executor_session_entry::ExecutorSessionEntry,
+    },
+};
+use executors::{
+    actions::ExecutorActionType,
+    executors::StandardCodingAgentExecutor,
+    logs::{
+        NormalizedEntry,
+        utils::patch::{EntryUpdate, entry_updates_from_patch},
+    },
+    profile::ExecutorConfigs,
+};
+use futures::StreamExt;
+use json_patch::Patch;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use utils::{log_msg::LogMsg, msg_store::MsgStore};
+use uuid::Uuid;
+
+use super::conversation_compare;
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+/// How long to keep listening for late patches once the run has finished,
+/// and how long a backfilled run may stay quiet before it is considered
+/// normalized
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Error)]
+pub enum SessionEntriesError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// Entry changes seen since the last flush; only the latest version of each
+/// entry is kept, `None` marking a removed one.
+#[derive(Debug, Default)]
+struct EntryRecorder {
+    pending: BTreeMap<usize, Option<NormalizedEntry>>,
+}
+
+impl EntryRecorder {
+    fn record(&mut self, patch: &Patch) {
+        for update in entry_updates_from_patch(patch) {
+            match update {
+                EntryUpdate::Upsert { index, entry } => {
+                    self.pending.insert(index, Some(entry));
+                }
+                EntryUpdate::Remove { index } => {
+                    self.pending.insert(index, None);
+                }
+            }
+        }
+    }
+
+    async fn flush(&mut self, pool: &SqlitePool, execution_id: Uuid) -> Result<(), sqlx::Error> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let pending = std::mem::take(&mut self.pending);
+        let mut tx = pool.begin().await?;
+        for (index, entry) in &pending {
+            let ordinal = *index as i64;
+            match entry {
+                Some(entry) => {
+                    ExecutorSessionEntry::upsert(&mut *tx, execution_id, ordinal, entry).await?
+                }
+                None => ExecutorSessionEntry::delete(&mut *tx, execution_id, ordinal).await?,
+            }
+        }
+        if let Err(e) = tx.commit().await {
+            // Keep the changes for the next flush, unless newer ones arrived
+            for (index, entry) in pending {
+                self.pending.entry(index).or_insert(entry);
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+/// Persist the normalized entries of a live run as they are produced, until
+/// the run finishes.
+pub async fn record_entries(db: DBService, execution_id: Uuid, msg_store: Arc<MsgStore>) {
+    let mut stream = msg_store.history_plus_stream();
+    let mut recorder = EntryRecorder::default();
+    let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            msg = stream.next() => match msg {
+                Some(Ok(LogMsg::JsonPatch(patch))) => recorder.record(&patch),
+                Some(Ok(LogMsg::Finished)) | None => break,
+                _ => {}
+            },
+            _ = ticker.tick() => {
+                if let Err(e) = recorder.flush(&db.pool, execution_id).await {
+                    tracing::warn!(
+                        "Failed to persist conversation entries for execution {}: {}",
+                        execution_id,
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    // The normalizer may still be working through the last lines
+    while let Ok(Some(Ok(msg))) = tokio::time::timeout(SETTLE_TIMEOUT, stream.next()).await {
+        if let LogMsg::JsonPatch(patch) = msg {
+            recorder.record(&patch);
+        }
+    }
+    if let Err(e) = recorder.flush(&db.pool, execution_id).await {
+        tracing::error!(
+            "Failed to persist conversation entries for execution {}: {}",
+            execution_id,
+            e
+        );
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BackfillReport {
+    /// Runs whose stored logs were normalized into entries
+    pub recorded: usize,
+    /// Runs without usable logs, left to the fallback
+    pub skipped: usize,
+}
+
+/// Normalize the stored logs of finished coding agent runs that have no
+/// persisted entries, `batch_size` runs at a time.
+pub async fn backfill(
+    db: &DBService,
+    batch_size: i64,
+) -> Result<BackfillReport, SessionEntriesError> {
+    let mut report = BackfillReport::default();
+    let mut cursor = 0;
+    loop {
+        let batch =
+            ExecutorSessionEntry::find_unrecorded_runs(&db.pool, cursor, batch_size).await?;
+        let Some(last) = batch.last() else {
+            break;
+        };
+        cursor = last.rowid;
+
+        for run in &batch {
+            match normalize_stored_logs(&db.pool, run.id).await? {
+                Some(entries) if !entries.is_empty() => {
+                    ExecutorSessionEntry::replace_for_execution_process(&db.pool, run.id, &entries)
+                        .await?;
+                    report.recorded += 1;
+                }
+                _ => report.skipped += 1,
+            }
+        }
+        tracing::info!(
+            "Backfilled conversation entries: {} recorded, {} skipped",
+            report.recorded,
+            report.skipped
+        );
+    }
+    Ok(report)
+}
+
+/// Re-run the executor's normalizer over a run's stored logs. `None` when
+/// there are no logs or they cannot be read.
+async fn normalize_stored_logs(
+    pool: &SqlitePool,
+    execution_id: Uuid,
+) -> Result<Option<Vec<NormalizedEntry>>, sqlx::Error> {
+    let Some(process) = ExecutionProcess::find_by_id(pool, execution_id).await? else {
+        return Ok(None);
+    };
+    let profile_id = match process.executor_action().map(|action| action.typ()) {
+        Ok(ExecutorActionType::CodingAgentInitialRequest(request)) => {
+            request.executor_profile_id.clone()
+        }
+        Ok(ExecutorActionType::CodingAgentFollowUpRequest(request)) => {
+            request.executor_profile_id.clone()
+        }
+        _ => return Ok(None),
+    };
+
+    let records = ExecutionProcessLogs::find_by_execution_id(pool, execution_id).await?;
+    let messages = match ExecutionProcessLogs::parse_logs(&records) {
+        Ok(messages) if !messages.is_empty() => messages,
+        Ok(_) => return Ok(None),
+        Err(e) => {
+            tracing::warn!("Failed to parse logs for execution {}: {}", execution_id, e);
+            return Ok(None);
+        }
+    };
+    // Paths in the conversation are shown relative to the attempt's worktree
+    let current_dir = process
+        .parent_task_attempt(pool)
+        .await?
+        .and_then(|attempt| attempt.container_ref)
+        .map(PathBuf::from)
+        .unwrap_or_default();
+
+    let store = Arc::new(MsgStore::new());
+    for msg in messages {
+        if matches!(
+            msg,
+            LogMsg::Stdout(_) | LogMsg::Stderr(_) | LogMsg::JsonPatch(_)
+        ) {
+            store.push(msg);
+        }
+    }
+    store.push_finished();
+    ExecutorConfigs::get_cached()
+        .get_coding_agent_or_default(&profile_id)
+        .normalize_logs(store.clone(), &current_dir);
+
+    let mut stream = store.history_plus_stream();
+    let mut patches = Vec::new();
+    while let Ok(Some(Ok(msg))) = tokio::time::timeout(SETTLE_TIMEOUT, stream.next()).await {
+        if matches!(msg, LogMsg::JsonPatch(_)) {
+            patches.push(msg);
+        }
+    }
+    Ok(Some(conversation_compare::entries_from_patches(patches)))
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::logs::{NormalizedEntryType, utils::ConversationPatch};
+
+    use super::*;
+
+    fn message(content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::AssistantMessage,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn pending_contents(recorder: &EntryRecorder) -> Vec<(usize, Option<String>)> {
+        recorder
+            .pending
+            .iter()
+            .map(|(index, entry)| (*index, entry.as_ref().map(|e| e.content.clone())))
+            .collect()
+    }
+
+    #[test]
+    fn keeps_only_the_latest_version_of_each_entry() {
+        let mut recorder = EntryRecorder::default();
+        recorder.record(&ConversationPatch::add_normalized_entry(0, message("Hel")));
+        recorder.record(&ConversationPatch::add_normalized_entry(1, message("Next")));
+        recorder.record(&ConversationPatch::replace(0, message("Hello")));
+
+        assert_eq!(
+            pending_contents(&recorder),
+            vec![
+                (0, Some("Hello".to_string())),
+                (1, Some("Next".to_string()))
+            ]
+        );
+    }
+
+    #[test]
+    fn removed_entries_are_marked_for_deletion() {
+        let mut recorder = EntryRecorder::default();
+        recorder.record(&ConversationPatch::add_normalized_entry(3, message("Gone")));
+        recorder.record(&ConversationPatch::remove(3));
+
+        assert_eq!(pending_contents(&recorder), vec![(3, None)]);
+    }
+}
@@ -1,7 +1,13 @@
+mod assignment;
 mod config;
+mod consistency;
+mod outbox;
 mod processor;
 mod publisher;
+mod remote_project;
+mod repair;
 mod status;
+mod sync_status;
 
 use std::{
     collections::{HashMap, HashSet},
@@ -12,11 +18,14 @@ use std::{
 
 use async_trait::async_trait;
 use axum::http::{HeaderName, HeaderValue, header::AUTHORIZATION};
-pub use config::ShareConfig;
+pub use config::{ShareConfig, load_or_create_device_id};
 use db::{
     DBService,
     models::{
-        shared_task::{SharedActivityCursor, SharedTask, SharedTaskInput},
+        project::{Project, SharedTaskAutoCreate},
+        shared_task::{
+            SharedActivityCursor, SharedTask, SharedTaskDivergenceReport, SharedTaskInput,
+        },
         task::{SyncTask, Task},
     },
 };
@@ -26,12 +35,15 @@ use remote::{
     ClientMessage, ServerMessage,
     db::{tasks::SharedTask as RemoteSharedTask, users::UserData as RemoteUserData},
 };
-use sqlx::{Executor, Sqlite, SqlitePool};
+use remote_project::{RemoteProjectRefresh, refresh_remote_project};
+pub use repair::{ShareLinkRepairReport, repair_shared_task_links};
+use sqlx::{Executor, Sqlite, SqlitePool, Transaction};
+pub use sync_status::{ProjectSyncState, ProjectSyncStatus, SyncStatusRegistry};
 use thiserror::Error;
 use tokio::{
-    sync::{mpsc, oneshot},
+    sync::{Notify, RwLock, mpsc, oneshot},
     task::JoinHandle,
-    time::{MissedTickBehavior, interval, sleep},
+    time::{Instant, MissedTickBehavior, interval, sleep, sleep_until},
 };
 use tokio_tungstenite::tungstenite::Message as WsMessage;
 use url::Url;
@@ -43,8 +55,8 @@ use uuid::Uuid;
 use crate::{
     RemoteClientError,
     services::{
-        auth::AuthContext, git::GitServiceError, github::GitHubServiceError,
-        remote_client::RemoteClient,
+        auth::AuthContext, config::Config, git::GitServiceError, github::GitHubServiceError,
+        notification::NotificationService, remote_client::RemoteClient,
     },
 };
 
@@ -68,6 +80,8 @@ pub enum ShareError {
     ProjectNotFound(Uuid),
     #[error("project {0} is not linked to a remote project")]
     ProjectNotLinked(Uuid),
+    #[error("remote project {0} was deleted; unlink the project to stop syncing it")]
+    RemoteProjectDeleted(Uuid),
     #[error("invalid response from remote share service")]
     InvalidResponse,
     #[error("task {0} is already shared")]
@@ -89,6 +103,13 @@ pub enum ShareError {
 }
 
 const WS_BACKOFF_BASE_DELAY: Duration = Duration::from_secs(1);
+/// How often shared task changes queued while offline are retried
+const OUTBOX_DRAIN_INTERVAL: Duration = Duration::from_secs(30);
+/// How often old entries are dropped from the applied activity event ledger
+const APPLIED_EVENT_PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// How often a connected watcher re-fetches its remote project's name and
+/// metadata
+const REMOTE_PROJECT_REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
 const WS_BACKOFF_MAX_DELAY: Duration = Duration::from_secs(30);
 
 struct Backoff {
@@ -106,6 +127,11 @@ impl Backoff {
         self.current = WS_BACKOFF_BASE_DELAY;
     }
 
+    /// How long the next `wait` sleeps for.
+    fn delay(&self) -> Duration {
+        self.current
+    }
+
     async fn wait(&mut self) {
         let wait = self.current;
         sleep(wait).await;
@@ -114,6 +140,98 @@ impl Backoff {
     }
 }
 
+/// Spread consistency checks by up to 10% either way so projects linked at
+/// the same time do not all hit the remote together.
+fn jittered(interval: Duration) -> Duration {
+    let spread = interval / 10;
+    let offset = spread.mul_f64((Uuid::new_v4().as_u128() % 1000) as f64 / 1000.0);
+    interval - spread + offset * 2
+}
+
+/// Schedule the next check relative to the last recorded one, so restarts do
+/// not postpone it indefinitely.
+async fn next_consistency_check(
+    db: &DBService,
+    remote_project_id: Uuid,
+    config: &ShareConfig,
+) -> Instant {
+    let interval = jittered(config.consistency_check_interval);
+    let last_checked = match SharedTaskDivergenceReport::find(&db.pool, remote_project_id).await {
+        Ok(report) => report.map(|report| report.checked_at),
+        Err(err) => {
+            tracing::warn!(?err, %remote_project_id, "failed to load divergence report");
+            None
+        }
+    };
+    let elapsed = last_checked
+        .and_then(|at| (chrono::Utc::now() - at).to_std().ok())
+        .unwrap_or_default();
+    Instant::now() + interval.saturating_sub(elapsed)
+}
+
+async fn run_consistency_check(
+    db: &DBService,
+    processor: &ActivityProcessor,
+    user_config: &Arc<RwLock<Config>>,
+    remote_project_id: Uuid,
+) {
+    let divergence = match processor.verify_consistency(remote_project_id).await {
+        Ok(divergence) => divergence,
+        Err(err) => {
+            tracing::warn!(?err, %remote_project_id, "shared task consistency check failed");
+            return;
+        }
+    };
+    if divergence.is_empty() {
+        tracing::debug!(%remote_project_id, "shared tasks consistent with remote");
+        return;
+    }
+
+    let project_name = Project::find_by_remote_project_id(&db.pool, remote_project_id)
+        .await
+        .ok()
+        .flatten()
+        .map(|project| project.name)
+        .unwrap_or_else(|| remote_project_id.to_string());
+    let notifications = user_config.read().await.notifications.clone();
+    NotificationService::notify(
+        notifications,
+        "Shared tasks repaired",
+        &format!(
+            "{} shared task(s) in {} were out of sync and have been repaired",
+            divergence.total(),
+            project_name
+        ),
+    )
+    .await;
+}
+
+/// Refresh the cached name and metadata of the remote project. Fails with
+/// [`ShareError::RemoteProjectDeleted`] once the remote project is gone;
+/// other failures are logged and retried on the next refresh.
+async fn check_remote_project(
+    db: &DBService,
+    remote_client: &RemoteClient,
+    status: &SyncStatusRegistry,
+    remote_project_id: Uuid,
+) -> Result<(), ShareError> {
+    match refresh_remote_project(db, remote_client, remote_project_id).await {
+        Ok(RemoteProjectRefresh::Unchanged) => {}
+        Ok(RemoteProjectRefresh::Updated) => {
+            tracing::info!(%remote_project_id, "remote project name or metadata changed");
+        }
+        Ok(RemoteProjectRefresh::Deleted) => {
+            tracing::warn!(%remote_project_id, "remote project was deleted; stopping sync");
+            status.mark_link_broken(remote_project_id);
+            return Err(ShareError::RemoteProjectDeleted(remote_project_id));
+        }
+        Err(err) => {
+            tracing::warn!(?err, %remote_project_id, "failed to refresh remote project");
+        }
+    }
+    Ok(())
+}
+
 struct ProjectWatcher {
     shutdown: oneshot::Sender<()>,
     join: JoinHandle<()>,
@@ -129,20 +247,37 @@ pub struct RemoteSync {
     processor: ActivityProcessor,
     config: ShareConfig,
     auth_ctx: AuthContext,
+    user_config: Arc<RwLock<Config>>,
+    status: SyncStatusRegistry,
+    reconcile: Arc<Notify>,
 }
 
 impl RemoteSync {
-    pub fn spawn(db: DBService, config: ShareConfig, auth_ctx: AuthContext) -> RemoteSyncHandle {
+    pub fn spawn(
+        db: DBService,
+        config: ShareConfig,
+        remote_client: RemoteClient,
+        auth_ctx: AuthContext,
+        user_config: Arc<RwLock<Config>>,
+    ) -> RemoteSyncHandle {
         tracing::info!(api = %config.api_base, "starting shared task synchronizer");
-        let remote_client = RemoteClient::new(config.api_base.as_str(), auth_ctx.clone())
-            .expect("failed to create remote client");
-        let processor =
-            ActivityProcessor::new(db.clone(), config.clone(), remote_client, auth_ctx.clone());
+        let processor = ActivityProcessor::new(
+            db.clone(),
+            config.clone(),
+            remote_client,
+            auth_ctx.clone(),
+            user_config.clone(),
+        );
+        let status = SyncStatusRegistry::default();
+        let reconcile = Arc::new(Notify::new());
         let sync = Self {
             db,
             processor,
             config,
             auth_ctx,
+            user_config,
+            status: status.clone(),
+            reconcile: reconcile.clone(),
         };
         let (shutdown_tx, shutdown_rx) = oneshot::channel();
         let join = tokio::spawn(async move {
@@ -151,7 +286,7 @@ impl RemoteSync {
             }
         });
 
-        RemoteSyncHandle::new(shutdown_tx, join)
+        RemoteSyncHandle::new(shutdown_tx, join, status, reconcile)
     }
 
     pub async fn run(self, mut shutdown_rx: oneshot::Receiver<()>) -> Result<(), ShareError> {
@@ -159,6 +294,10 @@ impl RemoteSync {
         let (event_tx, mut event_rx) = mpsc::unbounded_channel();
         let mut refresh_interval = interval(Duration::from_secs(5));
         refresh_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let mut outbox_interval = interval(OUTBOX_DRAIN_INTERVAL);
+        outbox_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let mut prune_interval = interval(APPLIED_EVENT_PRUNE_INTERVAL);
+        prune_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
 
         self.reconcile_watchers(&mut watchers, &event_tx).await?;
 
@@ -181,9 +320,12 @@ impl RemoteSync {
                     match event.result {
                         Ok(()) => {
                             tracing::debug!(project_id = %event.project_id, "project watcher exited cleanly");
+                            self.status.remove(event.project_id);
                         }
                         Err(err) => {
                             tracing::warn!(project_id = %event.project_id, ?err, "project watcher terminated with error");
+                            // Kept until the watcher is restarted on the next refresh
+                            self.status.record_error(event.project_id, &err);
                         }
                     }
                     watchers.remove(&event.project_id);
@@ -191,6 +333,18 @@ impl RemoteSync {
                 _ = refresh_interval.tick() => {
                     self.reconcile_watchers(&mut watchers, &event_tx).await?;
                 }
+                _ = self.reconcile.notified() => {
+                    self.reconcile_watchers(&mut watchers, &event_tx).await?;
+                    refresh_interval.reset();
+                }
+                _ = outbox_interval.tick() => {
+                    self.drain_outbox().await;
+                }
+                _ = prune_interval.tick() => {
+                    if let Err(err) = self.processor.prune_applied_events().await {
+                        tracing::warn!(?err, "failed to prune applied activity events");
+                    }
+                }
             }
         }
     }
@@ -202,6 +356,12 @@ impl RemoteSync {
     ) -> Result<(), ShareError> {
         let linked_projects = self.linked_remote_projects().await?;
         let desired: HashSet<Uuid> = linked_projects.iter().copied().collect();
+        let broken: HashSet<Uuid> = self.broken_remote_links().await?.into_iter().collect();
+        for project_id in &broken {
+            self.status.mark_link_broken(*project_id);
+        }
+        self.status
+            .retain(|id| desired.contains(id) || broken.contains(id));
 
         for project_id in linked_projects {
             if let std::collections::hash_map::Entry::Vacant(e) = watchers.entry(project_id) {
@@ -234,12 +394,40 @@ impl RemoteSync {
         Ok(())
     }
 
+    async fn drain_outbox(&self) {
+        if self.auth_ctx.cached_profile().await.is_none() {
+            return;
+        }
+        if let Err(err) = outbox::drain(&self.db, &self.processor.remote_client()).await {
+            tracing::warn!(?err, "failed to replay queued shared task changes");
+        }
+    }
+
     async fn linked_remote_projects(&self) -> Result<Vec<Uuid>, ShareError> {
         let rows = sqlx::query_scalar::<_, Uuid>(
             r#"
             SELECT remote_project_id
             FROM projects
             WHERE remote_project_id IS NOT NULL
+              AND archived_at IS NULL
+              AND remote_link_broken_at IS NULL
+            "#,
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Remote projects found deleted that are still linked locally.
+    async fn broken_remote_links(&self) -> Result<Vec<Uuid>, ShareError> {
+        let rows = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT remote_project_id
+            FROM projects
+            WHERE remote_project_id IS NOT NULL
+              AND archived_at IS NULL
+              AND remote_link_broken_at IS NOT NULL
             "#,
         )
         .fetch_all(&self.db.pool)
@@ -256,8 +444,10 @@ impl RemoteSync {
         let processor = self.processor.clone();
         let config = self.config.clone();
         let auth_ctx = self.auth_ctx.clone();
+        let user_config = self.user_config.clone();
         let remote_client = processor.remote_client();
         let db = self.db.clone();
+        let status = self.status.clone();
         let (shutdown_tx, shutdown_rx) = oneshot::channel();
 
         let join = tokio::spawn(async move {
@@ -266,7 +456,9 @@ impl RemoteSync {
                 processor,
                 config,
                 auth_ctx,
+                user_config,
                 remote_client,
+                status,
                 project_id,
                 shutdown_rx,
             )
@@ -284,6 +476,7 @@ impl RemoteSync {
 
 struct SharedWsHandler {
     processor: ActivityProcessor,
+    status: SyncStatusRegistry,
     close_tx: Option<oneshot::Sender<()>>,
     remote_project_id: Uuid,
 }
@@ -307,9 +500,31 @@ impl WsHandler for SharedWsHandler {
                         .process_event(event)
                         .await
                         .map_err(|err| WsError::Handler(Box::new(err)))?;
+                    self.status.record_event(self.remote_project_id, seq);
 
                     tracing::debug!(seq, "processed remote activity");
                 }
+                Ok(ServerMessage::ResyncRequired { latest_seq }) => {
+                    tracing::info!(
+                        remote_project_id = %self.remote_project_id,
+                        latest_seq,
+                        "remote requested resync; running bulk sync"
+                    );
+                    // Events after `latest_seq` keep arriving on this socket and
+                    // are handled once the snapshot is applied
+                    self.status
+                        .set_state(self.remote_project_id, ProjectSyncState::CatchingUp);
+                    self.processor
+                        .bulk_sync(self.remote_project_id)
+                        .await
+                        .map_err(|err| WsError::Handler(Box::new(err)))?;
+                    self.status
+                        .set_state(self.remote_project_id, ProjectSyncState::Connected);
+                }
+                Ok(ServerMessage::Ack { correlation_id }) => {
+                    // Acks we are waiting for are consumed by the client
+                    tracing::debug!(%correlation_id, "received unexpected websocket ack");
+                }
                 Ok(ServerMessage::Error { message }) => {
                     tracing::warn!(?message, "received WS error message");
                     // Remote sends this error when client has lagged too far behind.
@@ -338,11 +553,13 @@ impl WsHandler for SharedWsHandler {
 async fn spawn_shared_remote(
     processor: ActivityProcessor,
     remote_client: RemoteClient,
+    status: SyncStatusRegistry,
     url: Url,
     close_tx: oneshot::Sender<()>,
     remote_project_id: Uuid,
 ) -> Result<WsClient, ShareError> {
     let remote_client_clone = remote_client.clone();
+    let network = processor.config().network.clone();
     let ws_config = WsConfig {
         url,
         ping_interval: Some(std::time::Duration::from_secs(30)),
@@ -361,10 +578,14 @@ async fn spawn_shared_remote(
                 }
             })
         })),
+        // `project_watcher_task` re-dials itself after catching up
+        reconnect: None,
+        network,
     };
 
     let handler = SharedWsHandler {
         processor,
+        status,
         close_tx: Some(close_tx),
         remote_project_id,
     };
@@ -376,20 +597,49 @@ async fn spawn_shared_remote(
     Ok(client)
 }
 
+/// Store the device's cursor on the server. Returns the cursor to connect
+/// with, which is fresh when the server has already pruned past it.
+async fn ack_device_cursor(
+    processor: &ActivityProcessor,
+    remote_client: &RemoteClient,
+    device_id: Uuid,
+    remote_project_id: Uuid,
+    last_seq: i64,
+) -> Result<Option<i64>, ShareError> {
+    match remote_client
+        .ack_device(device_id, remote_project_id, last_seq)
+        .await
+    {
+        Ok(ack) if ack.resync_required => {
+            tracing::info!(%remote_project_id, last_seq, "device cursor was pruned; bulk syncing");
+            processor.bulk_sync(remote_project_id).await
+        }
+        Ok(_) => Ok(Some(last_seq)),
+        Err(err) => {
+            tracing::warn!(%remote_project_id, ?err, "failed to store device cursor");
+            Ok(Some(last_seq))
+        }
+    }
+}
+
 async fn project_watcher_task(
     db: DBService,
     processor: ActivityProcessor,
     config: ShareConfig,
     auth_ctx: AuthContext,
+    user_config: Arc<RwLock<Config>>,
     remote_client: RemoteClient,
+    status: SyncStatusRegistry,
     remote_project_id: Uuid,
     mut shutdown_rx: oneshot::Receiver<()>,
 ) -> Result<(), ShareError> {
     let mut backoff = Backoff::new();
+    let mut next_check = next_consistency_check(&db, remote_project_id, &config).await;
 
     loop {
         if auth_ctx.cached_profile().await.is_none() {
             tracing::debug!(%remote_project_id, "waiting for authentication before syncing project");
+            status.wait_for_auth(remote_project_id, backoff.delay());
             tokio::select! {
                 _ = &mut shutdown_rx => return Ok(()),
                 _ = backoff.wait() => {}
@@ -397,6 +647,7 @@ async fn project_watcher_task(
             continue;
         }
 
+        status.set_state(remote_project_id, ProjectSyncState::CatchingUp);
         let mut last_seq = SharedActivityCursor::get(&db.pool, remote_project_id)
             .await?
             .map(|cursor| cursor.last_seq);
@@ -407,16 +658,34 @@ async fn project_watcher_task(
         {
             Ok(seq) => {
                 last_seq = seq;
+                status.record_seq(remote_project_id, last_seq);
+                if let (Some(device_id), Some(seq)) = (config.device_id, last_seq) {
+                    last_seq = ack_device_cursor(
+                        &processor,
+                        &remote_client,
+                        device_id,
+                        remote_project_id,
+                        seq,
+                    )
+                    .await?;
+                }
             }
-            Err(ShareError::MissingAuth) => {
+            Err(err @ ShareError::MissingAuth) => {
                 tracing::debug!(%remote_project_id, "missing auth during catch-up; retrying after backoff");
+                status.back_off(remote_project_id, backoff.delay(), Some(&err));
                 tokio::select! {
                     _ = &mut shutdown_rx => return Ok(()),
                     _ = backoff.wait() => {}
                 }
                 continue;
             }
-            Err(err) => return Err(err),
+            Err(err) => {
+                if matches!(&err, ShareError::RemoteClientError(e) if e.is_not_found()) {
+                    // The project itself may be gone, not just its activity
+                    check_remote_project(&db, &remote_client, &status, remote_project_id).await?;
+                }
+                return Err(err);
+            }
         }
 
         let ws_url = match config.websocket_endpoint(remote_project_id, last_seq) {
@@ -424,10 +693,11 @@ async fn project_watcher_task(
             Err(err) => return Err(ShareError::Url(err)),
         };
 
-        let (close_tx, close_rx) = oneshot::channel();
+        let (close_tx, mut close_rx) = oneshot::channel();
         let ws_connection = match spawn_shared_remote(
             processor.clone(),
             remote_client.clone(),
+            status.clone(),
             ws_url,
             close_tx,
             remote_project_id,
@@ -436,10 +706,12 @@ async fn project_watcher_task(
         {
             Ok(conn) => {
                 backoff.reset();
+                status.set_state(remote_project_id, ProjectSyncState::Connected);
                 conn
             }
-            Err(ShareError::MissingAuth) => {
+            Err(err @ ShareError::MissingAuth) => {
                 tracing::debug!(%remote_project_id, "missing auth during websocket connect; retrying");
+                status.back_off(remote_project_id, backoff.delay(), Some(&err));
                 tokio::select! {
                     _ = &mut shutdown_rx => return Ok(()),
                     _ = backoff.wait() => {}
@@ -448,6 +720,7 @@ async fn project_watcher_task(
             }
             Err(err) => {
                 tracing::error!(%remote_project_id, ?err, "failed to establish websocket; retrying");
+                status.back_off(remote_project_id, backoff.delay(), Some(&err));
                 tokio::select! {
                     _ = &mut shutdown_rx => return Ok(()),
                     _ = backoff.wait() => {}
@@ -456,34 +729,54 @@ async fn project_watcher_task(
             }
         };
 
-        tokio::select! {
-            _ = &mut shutdown_rx => {
-                tracing::info!(%remote_project_id, "shutdown signal received for project watcher");
-                if let Err(err) = ws_connection.close() {
-                    tracing::debug!(?err, %remote_project_id, "failed to close websocket during shutdown");
-                }
-                return Ok(());
-            }
-            res = close_rx => {
-                match res {
-                    Ok(()) => {
-                        tracing::info!(%remote_project_id, "project websocket closed; scheduling reconnect");
-                    }
-                    Err(_) => {
-                        tracing::warn!(%remote_project_id, "project websocket close signal dropped");
+        // Checked on every connect, so a reconnect picks up remote renames
+        let mut next_refresh = Instant::now();
+        let res = loop {
+            tokio::select! {
+                _ = &mut shutdown_rx => {
+                    tracing::info!(%remote_project_id, "shutdown signal received for project watcher");
+                    if let Err(err) = ws_connection.close() {
+                        tracing::debug!(?err, %remote_project_id, "failed to close websocket during shutdown");
                     }
+                    return Ok(());
                 }
-                if let Err(err) = ws_connection.close() {
-                    tracing::debug!(?err, %remote_project_id, "project websocket already closed when reconnecting");
+                res = &mut close_rx => break res,
+                _ = sleep_until(next_check) => {
+                    run_consistency_check(&db, &processor, &user_config, remote_project_id).await;
+                    next_check = Instant::now() + jittered(config.consistency_check_interval);
                 }
-                tokio::select! {
-                    _ = &mut shutdown_rx => {
-                        tracing::info!(%remote_project_id, "shutdown received during reconnect wait");
-                        return Ok(());
+                _ = sleep_until(next_refresh) => {
+                    if let Err(err) =
+                        check_remote_project(&db, &remote_client, &status, remote_project_id).await
+                    {
+                        if let Err(close_err) = ws_connection.close() {
+                            tracing::debug!(?close_err, %remote_project_id, "failed to close websocket of deleted project");
+                        }
+                        return Err(err);
                     }
-                    _ = backoff.wait() => {}
+                    next_refresh = Instant::now() + REMOTE_PROJECT_REFRESH_INTERVAL;
                 }
             }
+        };
+
+        match res {
+            Ok(()) => {
+                tracing::info!(%remote_project_id, "project websocket closed; scheduling reconnect");
+            }
+            Err(_) => {
+                tracing::warn!(%remote_project_id, "project websocket close signal dropped");
+            }
+        }
+        if let Err(err) = ws_connection.close() {
+            tracing::debug!(?err, %remote_project_id, "project websocket already closed when reconnecting");
+        }
+        status.back_off(remote_project_id, backoff.delay(), None);
+        tokio::select! {
+            _ = &mut shutdown_rx => {
+                tracing::info!(%remote_project_id, "shutdown received during reconnect wait");
+                return Ok(());
+            }
+            _ = backoff.wait() => {}
         }
     }
 }
@@ -542,18 +835,38 @@ pub struct RemoteSyncHandle {
 struct RemoteSyncHandleInner {
     shutdown: StdMutex<Option<oneshot::Sender<()>>>,
     join: StdMutex<Option<JoinHandle<()>>>,
+    status: SyncStatusRegistry,
+    reconcile: Arc<Notify>,
 }
 
 impl RemoteSyncHandle {
-    fn new(shutdown: oneshot::Sender<()>, join: JoinHandle<()>) -> Self {
+    fn new(
+        shutdown: oneshot::Sender<()>,
+        join: JoinHandle<()>,
+        status: SyncStatusRegistry,
+        reconcile: Arc<Notify>,
+    ) -> Self {
         Self {
             inner: Arc::new(RemoteSyncHandleInner {
                 shutdown: StdMutex::new(Some(shutdown)),
                 join: StdMutex::new(Some(join)),
+                status,
+                reconcile,
             }),
         }
     }
 
+    /// Read handle on what each project watcher is doing.
+    pub fn status(&self) -> SyncStatusRegistry {
+        self.inner.status.clone()
+    }
+
+    /// Start and stop project watchers now instead of on the next refresh,
+    /// e.g. after a project was archived or unarchived.
+    pub fn request_reconcile(&self) {
+        self.inner.reconcile.notify_one();
+    }
+
     pub fn request_shutdown(&self) {
         if let Some(tx) = self.inner.shutdown.lock().unwrap().take() {
             let _ = tx.send(());
@@ -608,51 +921,97 @@ pub(super) fn convert_remote_task(
     }
 }
 
+/// What the sync knows about a shared task when deciding whether it needs
+/// a local task.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct AutoCreateContext {
+    pub assignee_is_current_user: bool,
+    /// A local task is being shared as this task and is about to be linked
+    /// to it
+    pub being_linked: bool,
+}
+
+impl AutoCreateContext {
+    fn new(shared_task: &SharedTask, current_user_id: Option<Uuid>) -> Self {
+        Self {
+            assignee_is_current_user: matches!(
+                (shared_task.assignee_user_id.as_ref(), current_user_id.as_ref()),
+                (Some(assignee), Some(current)) if assignee == current
+            ),
+            being_linked: SharedTaskLinks::is_linking(shared_task.id),
+        }
+    }
+}
+
+/// Whether syncing a shared task with no local task should create one.
+pub(super) fn should_create_local_task(
+    policy: SharedTaskAutoCreate,
+    ctx: AutoCreateContext,
+) -> bool {
+    if ctx.being_linked {
+        return false;
+    }
+    match policy {
+        SharedTaskAutoCreate::AssignedToMe => ctx.assignee_is_current_user,
+        SharedTaskAutoCreate::All => true,
+        SharedTaskAutoCreate::None => false,
+    }
+}
+
 pub(super) async fn sync_local_task_for_shared_task<'e, E>(
     executor: E,
     shared_task: &SharedTask,
     current_user_id: Option<uuid::Uuid>,
-    creator_user_id: Option<uuid::Uuid>,
-    project_id: Option<Uuid>,
+    project: Option<&Project>,
 ) -> Result<(), ShareError>
 where
     E: Executor<'e, Database = Sqlite>,
 {
-    let Some(project_id) = project_id else {
+    let Some(project) = project else {
         return Ok(());
     };
 
-    let create_task_if_not_exists = {
-        let assignee_is_current_user = matches!(
-            (shared_task.assignee_user_id.as_ref(), current_user_id.as_ref()),
-            (Some(assignee), Some(current)) if assignee == current
-        );
-        let creator_is_current_user = matches!((creator_user_id.as_ref(), current_user_id.as_ref()), (Some(creator), Some(current)) if creator == current);
-
-        assignee_is_current_user
-            && !(creator_is_current_user && SHARED_TASK_LINKING_LOCK.lock().unwrap().is_locked())
-    };
+    let ctx = AutoCreateContext::new(shared_task, current_user_id);
 
     Task::sync_from_shared_task(
         executor,
         SyncTask {
             shared_task_id: shared_task.id,
-            project_id,
+            project_id: project.id,
             title: shared_task.title.clone(),
             description: shared_task.description.clone(),
             status: shared_task.status.clone(),
         },
-        create_task_if_not_exists,
+        should_create_local_task(project.shared_task_auto_create, ctx),
     )
     .await?;
 
     Ok(())
 }
 
+/// Drop shared task rows, unlinking any local tasks that pointed at them.
+pub(super) async fn remove_shared_tasks(
+    tx: &mut Transaction<'_, Sqlite>,
+    ids: &[Uuid],
+) -> Result<(), ShareError> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    for id in ids {
+        if let Some(local_task) = Task::find_by_shared_task_id(tx.as_mut(), *id).await? {
+            Task::set_shared_task_id(tx.as_mut(), local_task.id, None).await?;
+        }
+    }
+
+    SharedTask::remove_many(tx.as_mut(), ids).await?;
+    Ok(())
+}
+
 pub async fn link_shared_tasks_to_project(
     pool: &SqlitePool,
     current_user_id: Option<uuid::Uuid>,
-    project_id: Uuid,
+    project: &Project,
     remote_project_id: Uuid,
 ) -> Result<(), ShareError> {
     let tasks = SharedTask::list_by_remote_project_id(pool, remote_project_id).await?;
@@ -662,45 +1021,105 @@ pub async fn link_shared_tasks_to_project(
     }
 
     for task in tasks {
-        sync_local_task_for_shared_task(pool, &task, current_user_id, None, Some(project_id))
-            .await?;
+        sync_local_task_for_shared_task(pool, &task, current_user_id, Some(project)).await?;
     }
 
     Ok(())
 }
 
-// Prevent duplicate local tasks from being created during task sharing.
-// The activity event handler can create a duplicate local task when it receives a shared task assigned to the current user.
 lazy_static::lazy_static! {
-    pub(super) static ref SHARED_TASK_LINKING_LOCK: StdMutex<SharedTaskLinkingLock> = StdMutex::new(SharedTaskLinkingLock::new());
+    static ref SHARED_TASK_LINKS: StdMutex<HashMap<Uuid, usize>> = StdMutex::new(HashMap::new());
 }
 
-#[derive(Debug)]
-pub(super) struct SharedTaskLinkingLock {
-    count: usize,
+/// Shared tasks that a local task is being linked to. Sharing a task creates
+/// it remotely before the local row points at it, so the activity event for
+/// the new task can arrive first; syncing it must not create a duplicate.
+pub(super) struct SharedTaskLinks;
+
+impl SharedTaskLinks {
+    pub(super) fn is_linking(shared_task_id: Uuid) -> bool {
+        SHARED_TASK_LINKS
+            .lock()
+            .unwrap()
+            .contains_key(&shared_task_id)
+    }
+
+    /// Mark `shared_task_id` as being linked until the guard is dropped.
+    pub(super) fn guard(shared_task_id: Uuid) -> SharedTaskLinkGuard {
+        *SHARED_TASK_LINKS
+            .lock()
+            .unwrap()
+            .entry(shared_task_id)
+            .or_default() += 1;
+        SharedTaskLinkGuard { shared_task_id }
+    }
 }
 
-impl SharedTaskLinkingLock {
-    fn new() -> Self {
-        Self { count: 0 }
+pub(super) struct SharedTaskLinkGuard {
+    shared_task_id: Uuid,
+}
+
+impl Drop for SharedTaskLinkGuard {
+    fn drop(&mut self) {
+        let mut links = SHARED_TASK_LINKS.lock().unwrap();
+        if let Some(count) = links.get_mut(&self.shared_task_id) {
+            *count -= 1;
+            if *count == 0 {
+                links.remove(&self.shared_task_id);
+            }
+        }
     }
+}
 
-    pub(super) fn is_locked(&self) -> bool {
-        self.count > 0
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(assignee_is_current_user: bool, being_linked: bool) -> AutoCreateContext {
+        AutoCreateContext {
+            assignee_is_current_user,
+            being_linked,
+        }
     }
 
-    #[allow(dead_code)]
-    pub(super) fn guard(&mut self) -> SharedTaskLinkingGuard {
-        self.count += 1;
-        SharedTaskLinkingGuard
+    #[test]
+    fn auto_create_follows_the_project_policy() {
+        let mine = SharedTaskAutoCreate::AssignedToMe;
+        assert!(should_create_local_task(mine, ctx(true, false)));
+        assert!(!should_create_local_task(mine, ctx(false, false)));
+        assert!(should_create_local_task(
+            SharedTaskAutoCreate::All,
+            ctx(false, false)
+        ));
+        assert!(!should_create_local_task(
+            SharedTaskAutoCreate::None,
+            ctx(true, false)
+        ));
     }
-}
 
-#[allow(dead_code)]
-pub(super) struct SharedTaskLinkingGuard;
+    #[test]
+    fn tasks_being_linked_are_never_created() {
+        for policy in [
+            SharedTaskAutoCreate::AssignedToMe,
+            SharedTaskAutoCreate::All,
+            SharedTaskAutoCreate::None,
+        ] {
+            assert!(!should_create_local_task(policy, ctx(true, true)));
+        }
+    }
 
-impl Drop for SharedTaskLinkingGuard {
-    fn drop(&mut self) {
-        SHARED_TASK_LINKING_LOCK.lock().unwrap().count -= 1;
+    #[test]
+    fn link_guards_only_cover_their_task() {
+        let linked = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let first = SharedTaskLinks::guard(linked);
+        let second = SharedTaskLinks::guard(linked);
+        assert!(SharedTaskLinks::is_linking(linked));
+        assert!(!SharedTaskLinks::is_linking(other));
+
+        drop(first);
+        assert!(SharedTaskLinks::is_linking(linked));
+        drop(second);
+        assert!(!SharedTaskLinks::is_linking(linked));
     }
 }
@@ -0,0 +1,70 @@
+//! Tells the current user when someone else assigns them a shared task.
+
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::services::{config::Config, notification::NotificationService};
+
+/// Whether applying a task update moved it onto `current_user_id`. Updates
+/// that leave an existing assignment alone, such as replayed or bulk-synced
+/// rows, do not count.
+pub(super) fn newly_assigned(
+    previous_assignee: Option<Uuid>,
+    new_assignee: Option<Uuid>,
+    current_user_id: Option<Uuid>,
+) -> bool {
+    current_user_id.is_some()
+        && new_assignee == current_user_id
+        && previous_assignee != current_user_id
+}
+
+#[derive(Clone)]
+pub(super) struct AssignmentNotifier {
+    user_config: Arc<RwLock<Config>>,
+}
+
+impl AssignmentNotifier {
+    pub(super) fn new(user_config: Arc<RwLock<Config>>) -> Self {
+        Self { user_config }
+    }
+
+    /// One notification for the batch, so catching up on many assignments
+    /// plays a single sound.
+    pub(super) async fn notify(&self, titles: &[String]) {
+        let notifications = self.user_config.read().await.notifications.clone();
+        if titles.is_empty() || !notifications.shared_task_assigned {
+            return;
+        }
+        let message = match titles {
+            [title] => format!("You were assigned: {title}"),
+            _ => format!(
+                "You were assigned {} shared tasks, including: {}",
+                titles.len(),
+                titles[0]
+            ),
+        };
+        NotificationService::notify(notifications, "Shared task assigned", &message).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_a_change_onto_the_current_user_counts() {
+        let me = Some(Uuid::new_v4());
+        let other = Some(Uuid::new_v4());
+
+        assert!(newly_assigned(None, me, me));
+        assert!(newly_assigned(other, me, me));
+        // Assignment unchanged, e.g. an edit to a task already mine
+        assert!(!newly_assigned(me, me, me));
+        assert!(!newly_assigned(None, other, me));
+        assert!(!newly_assigned(me, None, me));
+        // Signed out
+        assert!(!newly_assigned(None, None, None));
+    }
+}
@@ -1,8 +1,16 @@
+use std::{path::Path, time::Duration};
+
 use url::Url;
-use utils::ws::{WS_BULK_SYNC_THRESHOLD, derive_ws_url};
+use utils::{
+    network::NetworkConfig,
+    ws::{WS_BULK_SYNC_THRESHOLD, WS_PROTOCOL_VERSION, derive_ws_url},
+};
 use uuid::Uuid;
 
 const DEFAULT_ACTIVITY_LIMIT: u32 = 200;
+const DEFAULT_DIGEST_PAGE_LIMIT: u32 = 1000;
+const DEFAULT_CONSISTENCY_CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const DEFAULT_APPLIED_EVENT_RETENTION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
 
 #[derive(Clone)]
 pub struct ShareConfig {
@@ -10,24 +18,55 @@ pub struct ShareConfig {
     pub websocket_base: Url,
     pub activity_page_limit: u32,
     pub bulk_sync_threshold: u32,
+    pub digest_page_limit: u32,
+    /// How often each linked project is compared against the remote digest
+    pub consistency_check_interval: Duration,
+    /// How long applied activity event ids are remembered to skip replays
+    pub applied_event_retention: Duration,
+    /// Proxy and extra trusted roots for both HTTP and the websocket
+    pub network: NetworkConfig,
+    /// Lets the server keep this install's cursor between connections
+    pub device_id: Option<Uuid>,
 }
 
 impl ShareConfig {
-    pub fn from_env() -> Option<Self> {
+    pub fn from_env(network: NetworkConfig) -> Option<Self> {
         let raw_base = std::env::var("VK_SHARED_API_BASE")
             .ok()
             .or_else(|| option_env!("VK_SHARED_API_BASE").map(|s| s.to_string()))?;
         let api_base = Url::parse(raw_base.trim()).ok()?;
         let websocket_base = derive_ws_url(api_base.clone()).ok()?;
+        let consistency_check_interval = std::env::var("VK_SHARED_CONSISTENCY_CHECK_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_CONSISTENCY_CHECK_INTERVAL);
+        let applied_event_retention = std::env::var("VK_SHARED_APPLIED_EVENT_RETENTION_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_APPLIED_EVENT_RETENTION);
 
         Some(Self {
             api_base,
             websocket_base,
             activity_page_limit: DEFAULT_ACTIVITY_LIMIT,
             bulk_sync_threshold: WS_BULK_SYNC_THRESHOLD,
+            digest_page_limit: DEFAULT_DIGEST_PAGE_LIMIT,
+            consistency_check_interval,
+            applied_event_retention,
+            network,
+            device_id: None,
         })
     }
 
+    pub fn with_device_id(mut self, device_id: Uuid) -> Self {
+        self.device_id = Some(device_id);
+        self
+    }
+
     pub fn activity_endpoint(&self) -> Result<Url, url::ParseError> {
         self.api_base.join("/v1/activity")
     }
@@ -48,7 +87,43 @@ impl ShareConfig {
             if let Some(c) = cursor {
                 qp.append_pair("cursor", &c.to_string());
             }
+            qp.append_pair("protocol", &WS_PROTOCOL_VERSION.to_string());
+            if let Some(device_id) = self.device_id {
+                qp.append_pair("device_id", &device_id.to_string());
+            }
         }
         Ok(url)
     }
 }
+
+/// Read the id stored at `path`, generating and storing one the first time
+/// or when the file is unreadable.
+pub fn load_or_create_device_id(path: &Path) -> std::io::Result<Uuid> {
+    if let Ok(contents) = std::fs::read_to_string(path)
+        && let Ok(device_id) = Uuid::parse_str(contents.trim())
+    {
+        return Ok(device_id);
+    }
+    let device_id = Uuid::new_v4();
+    std::fs::write(path, device_id.to_string())?;
+    Ok(device_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn device_id_is_stable_once_created() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("device_id");
+
+        let first = load_or_create_device_id(&path).unwrap();
+        assert_eq!(load_or_create_device_id(&path).unwrap(), first);
+
+        std::fs::write(&path, "not a uuid").unwrap();
+        let replaced = load_or_create_device_id(&path).unwrap();
+        assert_ne!(replaced, first);
+        assert_eq!(load_or_create_device_id(&path).unwrap(), replaced);
+    }
+}
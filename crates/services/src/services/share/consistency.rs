@@ -0,0 +1,383 @@
+//! Periodic check that local `shared_tasks` rows still match the remote.
+//!
+//! Activity cursors only tell us which events we have applied, so a row that
+//! drifted (for example through an old bug) is never corrected by catching up.
+//! The checker compares per-task digests from the remote against the local
+//! rows and re-fetches only the tasks that disagree.
+
+use std::collections::{HashMap, HashSet};
+
+use async_trait::async_trait;
+use db::{
+    DBService,
+    models::{
+        project::Project,
+        shared_task::{SharedTask, SharedTaskDivergenceReport},
+    },
+};
+use remote::{
+    db::tasks::TaskDigestFields,
+    routes::tasks::{SharedTaskResponse, TaskDigestResponse},
+};
+use uuid::Uuid;
+
+use super::{
+    ShareError, convert_remote_task, remove_shared_tasks, status, sync_local_task_for_shared_task,
+};
+use crate::{RemoteClientError, services::remote_client::RemoteClient};
+
+/// Remote side of a consistency check.
+#[async_trait]
+pub trait TaskDigestSource: Send + Sync {
+    async fn digest_page(
+        &self,
+        remote_project_id: Uuid,
+        after: Option<Uuid>,
+        limit: u32,
+    ) -> Result<TaskDigestResponse, ShareError>;
+
+    /// `None` when the task no longer exists remotely.
+    async fn task(&self, task_id: Uuid) -> Result<Option<SharedTaskResponse>, ShareError>;
+}
+
+#[async_trait]
+impl TaskDigestSource for RemoteClient {
+    async fn digest_page(
+        &self,
+        remote_project_id: Uuid,
+        after: Option<Uuid>,
+        limit: u32,
+    ) -> Result<TaskDigestResponse, ShareError> {
+        Ok(self
+            .fetch_task_digest(remote_project_id, after, limit)
+            .await?)
+    }
+
+    async fn task(&self, task_id: Uuid) -> Result<Option<SharedTaskResponse>, ShareError> {
+        match self.fetch_shared_task(task_id).await {
+            Ok(task) => Ok(Some(task)),
+            Err(RemoteClientError::Http { status: 404, .. }) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Task ids that disagreed during one check.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Present on both sides with different content
+    pub mismatched: Vec<Uuid>,
+    /// Live remotely but missing locally
+    pub missing: Vec<Uuid>,
+    /// Present locally but deleted or unknown remotely
+    pub strays: Vec<Uuid>,
+}
+
+impl Divergence {
+    pub fn total(&self) -> usize {
+        self.mismatched.len() + self.missing.len() + self.strays.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+struct RemoteDigest {
+    live: HashMap<Uuid, String>,
+}
+
+async fn collect_remote_digest<S>(
+    source: &S,
+    remote_project_id: Uuid,
+    page_limit: u32,
+) -> Result<RemoteDigest, ShareError>
+where
+    S: TaskDigestSource + ?Sized,
+{
+    let mut live = HashMap::new();
+    let mut after = None;
+    loop {
+        let page = source
+            .digest_page(remote_project_id, after, page_limit)
+            .await?;
+        live.extend(page.tasks.into_iter().map(|entry| (entry.id, entry.hash)));
+
+        match page.next_after {
+            Some(next) if Some(next) != after => after = Some(next),
+            _ => break,
+        }
+    }
+    Ok(RemoteDigest { live })
+}
+
+fn local_digest_hash(task: &SharedTask) -> String {
+    TaskDigestFields {
+        version: task.version,
+        title: &task.title,
+        description: task.description.as_deref(),
+        status: status::to_remote(&task.status),
+        assignee_user_id: task.assignee_user_id,
+        updated_at: task.updated_at,
+    }
+    .hash()
+}
+
+fn find_divergence(local: &[SharedTask], remote: &RemoteDigest) -> Divergence {
+    let mut divergence = Divergence::default();
+    let mut seen = HashSet::new();
+
+    for task in local {
+        seen.insert(task.id);
+        match remote.live.get(&task.id) {
+            Some(hash) if *hash == local_digest_hash(task) => {}
+            Some(_) => divergence.mismatched.push(task.id),
+            None => divergence.strays.push(task.id),
+        }
+    }
+    divergence.missing = remote
+        .live
+        .keys()
+        .filter(|id| !seen.contains(id))
+        .copied()
+        .collect();
+
+    divergence.mismatched.sort();
+    divergence.missing.sort();
+    divergence.strays.sort();
+    divergence
+}
+
+/// Compare one project against the remote digest, repair what differs and
+/// record the outcome on its divergence report.
+pub async fn check_project<S>(
+    db: &DBService,
+    source: &S,
+    remote_project_id: Uuid,
+    current_user_id: Option<Uuid>,
+    page_limit: u32,
+) -> Result<Divergence, ShareError>
+where
+    S: TaskDigestSource + ?Sized,
+{
+    let remote = collect_remote_digest(source, remote_project_id, page_limit).await?;
+    let local = SharedTask::list_by_remote_project_id(&db.pool, remote_project_id).await?;
+    let divergence = find_divergence(&local, &remote);
+
+    if divergence.is_empty() {
+        SharedTaskDivergenceReport::record_clean_check(&db.pool, remote_project_id).await?;
+        return Ok(divergence);
+    }
+
+    tracing::warn!(
+        %remote_project_id,
+        mismatched = divergence.mismatched.len(),
+        missing = divergence.missing.len(),
+        strays = divergence.strays.len(),
+        "shared tasks diverged from remote; repairing"
+    );
+
+    let mut replacements = Vec::new();
+    let mut to_remove = divergence.strays.clone();
+    for id in divergence.mismatched.iter().chain(&divergence.missing) {
+        match source.task(*id).await? {
+            Some(task) => replacements.push(task),
+            // Deleted since the digest was taken
+            None => to_remove.push(*id),
+        }
+    }
+
+    let last_event_seqs: HashMap<Uuid, Option<i64>> = local
+        .iter()
+        .map(|task| (task.id, task.last_event_seq))
+        .collect();
+    let project = Project::find_by_remote_project_id(&db.pool, remote_project_id).await?;
+
+    let mut tx = db.pool.begin().await?;
+    remove_shared_tasks(&mut tx, &to_remove).await?;
+    for SharedTaskResponse { task, user } in replacements {
+        let last_event_seq = last_event_seqs.get(&task.id).copied().flatten();
+        let input = convert_remote_task(&task, user.as_ref(), last_event_seq);
+        let shared_task = SharedTask::upsert(tx.as_mut(), input).await?;
+        sync_local_task_for_shared_task(
+            tx.as_mut(),
+            &shared_task,
+            current_user_id,
+            project.as_ref(),
+        )
+        .await?;
+    }
+    SharedTaskDivergenceReport::record_divergence(
+        tx.as_mut(),
+        remote_project_id,
+        &divergence.mismatched,
+        &divergence.missing,
+        &divergence.strays,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(divergence)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use db::models::shared_task::SharedTaskInput;
+    use remote::db::tasks::{SharedTask as RemoteSharedTask, TaskDigestEntry};
+
+    use super::*;
+    use crate::test_common::{insert_shared_task, remote_task, test_pool};
+
+    struct FakeRemote {
+        project_id: Uuid,
+        tasks: Vec<RemoteSharedTask>,
+        fetched: Mutex<Vec<Uuid>>,
+    }
+
+    #[async_trait]
+    impl TaskDigestSource for FakeRemote {
+        async fn digest_page(
+            &self,
+            _remote_project_id: Uuid,
+            after: Option<Uuid>,
+            limit: u32,
+        ) -> Result<TaskDigestResponse, ShareError> {
+            let mut tasks: Vec<&RemoteSharedTask> = self
+                .tasks
+                .iter()
+                .filter(|task| after.is_none_or(|after| task.id > after))
+                .collect();
+            tasks.sort_by_key(|task| task.id);
+            tasks.truncate(limit as usize);
+
+            let next_after = (tasks.len() == limit as usize)
+                .then(|| tasks.last().map(|task| task.id))
+                .flatten();
+            Ok(TaskDigestResponse {
+                tasks: tasks
+                    .iter()
+                    .map(|task| TaskDigestEntry {
+                        id: task.id,
+                        version: task.version,
+                        hash: TaskDigestFields {
+                            version: task.version,
+                            title: &task.title,
+                            description: task.description.as_deref(),
+                            status: task.status,
+                            assignee_user_id: task.assignee_user_id,
+                            updated_at: task.updated_at,
+                        }
+                        .hash(),
+                    })
+                    .collect(),
+                deleted_task_ids: Vec::new(),
+                next_after,
+            })
+        }
+
+        async fn task(&self, task_id: Uuid) -> Result<Option<SharedTaskResponse>, ShareError> {
+            self.fetched.lock().unwrap().push(task_id);
+            Ok(self
+                .tasks
+                .iter()
+                .find(|task| task.id == task_id)
+                .map(|task| SharedTaskResponse {
+                    task: task.clone(),
+                    user: None,
+                }))
+        }
+    }
+
+    #[tokio::test]
+    async fn repairs_corrupted_row_and_reports_it() {
+        let db = DBService {
+            pool: test_pool().await,
+        };
+        let project_id = Uuid::new_v4();
+        for i in 0..5 {
+            insert_shared_task(&db.pool, project_id, &format!("task {i}"), None).await;
+        }
+        // The remote starts out in step with the local rows
+        let remote = FakeRemote {
+            project_id,
+            tasks: SharedTask::list_by_remote_project_id(&db.pool, project_id)
+                .await
+                .unwrap()
+                .iter()
+                .map(|task| {
+                    remote_task(
+                        task.id,
+                        project_id,
+                        &task.title,
+                        task.version,
+                        task.updated_at,
+                    )
+                })
+                .collect(),
+            fetched: Mutex::new(Vec::new()),
+        };
+
+        // Page size smaller than the project to exercise pagination
+        let clean = check_project(&db, &remote, remote.project_id, None, 2)
+            .await
+            .unwrap();
+        assert!(clean.is_empty());
+        assert!(remote.fetched.lock().unwrap().is_empty());
+
+        // Same version, different content: the cursor would never notice this
+        let corrupted = remote.tasks[2].clone();
+        SharedTask::upsert(
+            &db.pool,
+            SharedTaskInput {
+                title: "stale title".to_string(),
+                ..convert_remote_task(&corrupted, None, Some(7))
+            },
+        )
+        .await
+        .unwrap();
+        let stray = insert_shared_task(&db.pool, project_id, "deleted remotely", None).await;
+
+        let divergence = check_project(&db, &remote, remote.project_id, None, 2)
+            .await
+            .unwrap();
+        assert_eq!(divergence.mismatched, vec![corrupted.id]);
+        assert!(divergence.missing.is_empty());
+        assert_eq!(divergence.strays, vec![stray]);
+        assert_eq!(*remote.fetched.lock().unwrap(), vec![corrupted.id]);
+
+        let repaired = SharedTask::find_by_id(&db.pool, corrupted.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(repaired.title, corrupted.title);
+        assert_eq!(repaired.last_event_seq, Some(7));
+        assert!(
+            SharedTask::find_by_id(&db.pool, stray)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let report = SharedTaskDivergenceReport::find(&db.pool, project_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.total(), 2);
+        assert_eq!(report.mismatched_task_ids.0, vec![corrupted.id]);
+        assert_eq!(report.stray_task_ids.0, vec![stray]);
+        assert!(report.detected_at.is_some());
+
+        // A later clean run keeps the last divergence on the report
+        let again = check_project(&db, &remote, remote.project_id, None, 2)
+            .await
+            .unwrap();
+        assert!(again.is_empty());
+        let report = SharedTaskDivergenceReport::find(&db.pool, project_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.total(), 2);
+    }
+}
@@ -0,0 +1,157 @@
+//! Shared task changes made while the remote service was unreachable. They are
+//! queued locally and replayed in order by [`drain`] once it is reachable
+//! again. Replays carry the version the change was based on, so a change that
+//! lost a race with a newer remote edit is dropped in favour of the remote
+//! copy.
+
+use db::{
+    DBService,
+    models::{
+        shared_task::SharedTask,
+        shared_task_outbox::{SharedTaskOpType, SharedTaskOutboxOp},
+    },
+};
+use remote::routes::tasks::{DeleteSharedTaskRequest, SharedTaskResponse, UpdateSharedTaskRequest};
+use uuid::Uuid;
+
+use super::{ShareError, convert_remote_task};
+use crate::services::remote_client::{RemoteClient, RemoteClientError};
+
+/// Replays after which an operation that keeps failing for reasons other than
+/// connectivity is given up on.
+const MAX_REPLAY_ATTEMPTS: i64 = 10;
+
+/// Queue an update. A pending update for the same task is overwritten but
+/// keeps its base version, since it has not reached the remote yet.
+pub(super) async fn enqueue_update(
+    db: &DBService,
+    shared_task_id: Uuid,
+    payload: UpdateSharedTaskRequest,
+) -> Result<(), ShareError> {
+    if let Some(pending) =
+        SharedTaskOutboxOp::find_pending(&db.pool, shared_task_id, SharedTaskOpType::Update).await?
+    {
+        let base_version = serde_json::from_value::<UpdateSharedTaskRequest>(pending.payload.0)
+            .ok()
+            .and_then(|pending| pending.version);
+        let payload = UpdateSharedTaskRequest {
+            version: base_version.or(payload.version),
+            ..payload
+        };
+        SharedTaskOutboxOp::replace_payload(&db.pool, pending.id, serde_json::to_value(payload)?)
+            .await?;
+        return Ok(());
+    }
+    SharedTaskOutboxOp::enqueue(
+        &db.pool,
+        SharedTaskOpType::Update,
+        shared_task_id,
+        serde_json::to_value(payload)?,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Queue a delete, dropping any pending updates to the task.
+pub(super) async fn enqueue_delete(
+    db: &DBService,
+    shared_task_id: Uuid,
+    payload: DeleteSharedTaskRequest,
+) -> Result<(), ShareError> {
+    SharedTaskOutboxOp::delete_for_shared_task(&db.pool, shared_task_id).await?;
+    SharedTaskOutboxOp::enqueue(
+        &db.pool,
+        SharedTaskOpType::Delete,
+        shared_task_id,
+        serde_json::to_value(payload)?,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Replay queued operations in order. Stops at the first connectivity error,
+/// leaving the rest for the next pass.
+pub(super) async fn drain(db: &DBService, client: &RemoteClient) -> Result<(), ShareError> {
+    for op in SharedTaskOutboxOp::list_pending(&db.pool).await? {
+        match replay(db, client, &op).await {
+            Ok(()) => {
+                tracing::debug!(op_id = %op.id, shared_task_id = %op.shared_task_id, "replayed queued shared task change");
+                SharedTaskOutboxOp::delete(&db.pool, op.id).await?;
+            }
+            Err(ShareError::RemoteClientError(err))
+                if err.should_retry() || matches!(err, RemoteClientError::Auth) =>
+            {
+                SharedTaskOutboxOp::record_failure(&db.pool, op.id, &err.to_string()).await?;
+                return Ok(());
+            }
+            Err(err) if op.attempt_count + 1 >= MAX_REPLAY_ATTEMPTS => {
+                tracing::warn!(?err, op_id = %op.id, shared_task_id = %op.shared_task_id, "giving up on queued shared task change");
+                SharedTaskOutboxOp::delete(&db.pool, op.id).await?;
+            }
+            Err(err) => {
+                tracing::warn!(?err, op_id = %op.id, shared_task_id = %op.shared_task_id, "failed to replay queued shared task change");
+                SharedTaskOutboxOp::record_failure(&db.pool, op.id, &err.to_string()).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn replay(
+    db: &DBService,
+    client: &RemoteClient,
+    op: &SharedTaskOutboxOp,
+) -> Result<(), ShareError> {
+    let result = match op.op_type {
+        SharedTaskOpType::Update => {
+            let payload: UpdateSharedTaskRequest = serde_json::from_value(op.payload.0.clone())?;
+            client
+                .update_shared_task(op.shared_task_id, &payload)
+                .await
+                .map(Some)
+        }
+        SharedTaskOpType::Delete => {
+            let payload: DeleteSharedTaskRequest = serde_json::from_value(op.payload.0.clone())?;
+            client
+                .delete_shared_task(op.shared_task_id, &payload)
+                .await
+                .map(|_| None)
+        }
+    };
+
+    match result {
+        Ok(Some(remote_task)) => store_remote_copy(db, &remote_task).await,
+        Ok(None) => Ok(()),
+        Err(RemoteClientError::Http { status: 409, .. }) => {
+            tracing::warn!(
+                op_id = %op.id,
+                shared_task_id = %op.shared_task_id,
+                op_type = ?op.op_type,
+                "queued shared task change conflicts with a newer remote version; keeping the remote copy"
+            );
+            match client.fetch_shared_task(op.shared_task_id).await {
+                Ok(remote_task) => store_remote_copy(db, &remote_task).await,
+                Err(RemoteClientError::Http { status: 404, .. }) => Ok(()),
+                Err(err) => Err(err.into()),
+            }
+        }
+        Err(RemoteClientError::Http { status: 404, .. }) => {
+            tracing::info!(
+                op_id = %op.id,
+                shared_task_id = %op.shared_task_id,
+                "shared task no longer exists remotely; dropping queued change"
+            );
+            Ok(())
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+async fn store_remote_copy(
+    db: &DBService,
+    remote_task: &SharedTaskResponse,
+) -> Result<(), ShareError> {
+    let input = convert_remote_task(&remote_task.task, remote_task.user.as_ref(), None);
+    SharedTask::upsert(&db.pool, input).await?;
+    Ok(())
+}
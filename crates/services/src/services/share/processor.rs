@@ -1,27 +1,39 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::Arc};
 
 use db::{
     DBService,
     models::{
         project::Project,
-        shared_task::{SharedActivityCursor, SharedTask, SharedTaskInput},
+        shared_task::{
+            AppliedActivityEvent, SharedActivityCursor, SharedTask, SharedTaskInput, UpsertOutcome,
+        },
         task::Task,
     },
 };
 use remote::{
-    activity::ActivityEvent, db::tasks::SharedTaskActivityPayload,
+    activity::{ActivityEvent, ActivityFilter},
+    db::tasks::SharedTaskActivityPayload,
     routes::tasks::BulkSharedTasksResponse,
 };
 use sqlx::{Sqlite, Transaction};
+use tokio::sync::RwLock;
 use uuid::Uuid;
 
-use super::{ShareConfig, ShareError, convert_remote_task, sync_local_task_for_shared_task};
-use crate::services::{auth::AuthContext, remote_client::RemoteClient};
+use super::{
+    ShareConfig, ShareError,
+    assignment::{AssignmentNotifier, newly_assigned},
+    consistency::{self, Divergence},
+    convert_remote_task, remove_shared_tasks, sync_local_task_for_shared_task,
+};
+use crate::services::{
+    auth::AuthContext,
+    config::Config,
+    remote_client::{RemoteClient, RemoteClientError},
+};
 
 struct PreparedBulkTask {
     input: SharedTaskInput,
-    creator_user_id: Option<uuid::Uuid>,
-    project_id: Option<Uuid>,
+    project: Option<Project>,
 }
 
 /// Processor for handling activity events and synchronizing shared tasks.
@@ -31,6 +43,7 @@ pub struct ActivityProcessor {
     config: ShareConfig,
     remote_client: RemoteClient,
     auth_ctx: AuthContext,
+    assignments: AssignmentNotifier,
 }
 
 impl ActivityProcessor {
@@ -39,12 +52,14 @@ impl ActivityProcessor {
         config: ShareConfig,
         remote_client: RemoteClient,
         auth_ctx: AuthContext,
+        user_config: Arc<RwLock<Config>>,
     ) -> Self {
         Self {
             db,
             config,
             remote_client,
             auth_ctx,
+            assignments: AssignmentNotifier::new(user_config),
         }
     }
 
@@ -52,16 +67,49 @@ impl ActivityProcessor {
         self.remote_client.clone()
     }
 
+    pub fn config(&self) -> &ShareConfig {
+        &self.config
+    }
+
+    /// Drop ledger entries older than the configured retention.
+    pub async fn prune_applied_events(&self) -> Result<u64, ShareError> {
+        let retention_secs = self.config.applied_event_retention.as_secs() as i64;
+        Ok(AppliedActivityEvent::prune(&self.db.pool, retention_secs).await?)
+    }
+
     pub async fn process_event(&self, event: ActivityEvent) -> Result<(), ShareError> {
-        let mut tx = self.db.pool.begin().await?;
-        match event.event_type.as_str() {
-            "task.deleted" => self.process_deleted_task_event(&mut tx, &event).await?,
-            _ => self.process_upsert_event(&mut tx, &event).await?,
+        if let Some(title) = self.apply_event(&event).await? {
+            self.assignments.notify(&[title]).await;
         }
+        Ok(())
+    }
+
+    /// Returns the task's title if it was just assigned to the current user.
+    async fn apply_event(&self, event: &ActivityEvent) -> Result<Option<String>, ShareError> {
+        let mut tx = self.db.pool.begin().await?;
+        let first_delivery =
+            AppliedActivityEvent::record(tx.as_mut(), event.event_id, event.project_id, event.seq)
+                .await?;
+        let assigned = if !first_delivery {
+            tracing::debug!(
+                event_id = %event.event_id,
+                seq = event.seq,
+                "skipping activity event that was already applied"
+            );
+            None
+        } else {
+            match event.event_type.as_str() {
+                "task.deleted" => {
+                    self.process_deleted_task_event(&mut tx, event).await?;
+                    None
+                }
+                _ => self.process_upsert_event(&mut tx, event).await?,
+            }
+        };
 
         SharedActivityCursor::upsert(tx.as_mut(), event.project_id, event.seq).await?;
         tx.commit().await?;
-        Ok(())
+        Ok(assigned)
     }
 
     /// Fetch and process activity events until caught up, falling back to bulk syncs when needed.
@@ -74,18 +122,45 @@ impl ActivityProcessor {
             last_seq = self.bulk_sync(remote_project_id).await?;
         }
 
+        let mut assigned = Vec::new();
+        let result = self
+            .catch_up_pages(remote_project_id, &mut last_seq, &mut assigned)
+            .await;
+        // Applied events stay applied if a later page fails
+        self.assignments.notify(&assigned).await;
+        result.map(|()| last_seq)
+    }
+
+    async fn catch_up_pages(
+        &self,
+        remote_project_id: Uuid,
+        last_seq: &mut Option<i64>,
+        assigned: &mut Vec<String>,
+    ) -> Result<(), ShareError> {
         loop {
-            let events = self.fetch_activity(remote_project_id, last_seq).await?;
+            let events = match self.fetch_activity(remote_project_id, *last_seq).await {
+                Ok(events) => events,
+                // The remote has pruned events after our cursor
+                Err(ShareError::RemoteClientError(RemoteClientError::ResyncRequired)) => {
+                    tracing::info!(
+                        %remote_project_id,
+                        "activity cursor is past the remote's retention; running bulk sync"
+                    );
+                    *last_seq = self.bulk_sync(remote_project_id).await?;
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
             if events.is_empty() {
                 break;
             }
 
             // Perform a bulk sync if we've fallen too far behind
-            if let Some(prev_seq) = last_seq
+            if let Some(prev_seq) = *last_seq
                 && let Some(newest) = events.last()
                 && newest.seq.saturating_sub(prev_seq) > self.config.bulk_sync_threshold as i64
             {
-                last_seq = self.bulk_sync(remote_project_id).await?;
+                *last_seq = self.bulk_sync(remote_project_id).await?;
                 continue;
             }
 
@@ -99,8 +174,8 @@ impl ActivityProcessor {
                     );
                     continue;
                 }
-                self.process_event(ev.clone()).await?;
-                last_seq = Some(ev.seq);
+                assigned.extend(self.apply_event(&ev).await?);
+                *last_seq = Some(ev.seq);
             }
 
             if page_len < (self.config.activity_page_limit as usize) {
@@ -108,7 +183,7 @@ impl ActivityProcessor {
             }
         }
 
-        Ok(last_seq)
+        Ok(())
     }
 
     /// Fetch a page of activity events from the remote service.
@@ -119,7 +194,12 @@ impl ActivityProcessor {
     ) -> Result<Vec<ActivityEvent>, ShareError> {
         let resp = self
             .remote_client
-            .fetch_activity(remote_project_id, after, self.config.activity_page_limit)
+            .fetch_activity(
+                remote_project_id,
+                after,
+                self.config.activity_page_limit,
+                &ActivityFilter::default(),
+            )
             .await?;
         Ok(resp.data)
     }
@@ -150,10 +230,10 @@ impl ActivityProcessor {
         &self,
         tx: &mut Transaction<'_, Sqlite>,
         event: &ActivityEvent,
-    ) -> Result<(), ShareError> {
+    ) -> Result<Option<String>, ShareError> {
         let Some(payload) = &event.payload else {
             tracing::warn!(event_id = %event.event_id, "received activity event with empty payload");
-            return Ok(());
+            return Ok(None);
         };
 
         match serde_json::from_value::<SharedTaskActivityPayload>(payload.clone()) {
@@ -167,9 +247,23 @@ impl ActivityProcessor {
                     );
                 }
 
-                let project_id = project.as_ref().map(|p| p.id);
+                let previous_assignee = SharedTask::find_by_id(&self.db.pool, task.id)
+                    .await?
+                    .and_then(|existing| existing.assignee_user_id);
                 let input = convert_remote_task(&task, user.as_ref(), Some(event.seq));
-                let shared_task = SharedTask::upsert(tx.as_mut(), input).await?;
+                let shared_task = match SharedTask::upsert_if_newer(tx.as_mut(), input).await? {
+                    UpsertOutcome::Applied(shared_task) => shared_task,
+                    UpsertOutcome::IgnoredStale => {
+                        // The cursor still advances past this event
+                        tracing::debug!(
+                            task_id = %task.id,
+                            version = task.version,
+                            seq = event.seq,
+                            "ignoring activity event older than the stored shared task"
+                        );
+                        return Ok(None);
+                    }
+                };
 
                 let current_profile = self.auth_ctx.cached_profile().await;
                 let current_user_id = current_profile.as_ref().map(|p| p.user_id);
@@ -177,10 +271,16 @@ impl ActivityProcessor {
                     tx.as_mut(),
                     &shared_task,
                     current_user_id,
-                    task.creator_user_id,
-                    project_id,
+                    project.as_ref(),
                 )
                 .await?;
+
+                Ok(newly_assigned(
+                    previous_assignee,
+                    shared_task.assignee_user_id,
+                    current_user_id,
+                )
+                .then(|| shared_task.title.clone()))
             }
             Err(error) => {
                 tracing::warn!(
@@ -188,10 +288,9 @@ impl ActivityProcessor {
                     event_id = %event.event_id,
                     "unrecognized shared task payload; skipping"
                 );
+                Ok(None)
             }
         }
-
-        Ok(())
     }
 
     async fn process_deleted_task_event(
@@ -228,7 +327,9 @@ impl ActivityProcessor {
         Ok(())
     }
 
-    async fn bulk_sync(&self, remote_project_id: Uuid) -> Result<Option<i64>, ShareError> {
+    /// Replace the project's shared tasks with the remote snapshot and move
+    /// the activity cursor to the snapshot's seq.
+    pub async fn bulk_sync(&self, remote_project_id: Uuid) -> Result<Option<i64>, ShareError> {
         let bulk_resp = self.fetch_bulk_snapshot(remote_project_id).await?;
         let latest_seq = bulk_resp.latest_seq;
 
@@ -248,14 +349,9 @@ impl ActivityProcessor {
                 );
             }
 
-            let project_id = project.as_ref().map(|p| p.id);
             keep_ids.insert(payload.task.id);
             let input = convert_remote_task(&payload.task, payload.user.as_ref(), latest_seq);
-            replacements.push(PreparedBulkTask {
-                input,
-                creator_user_id: payload.task.creator_user_id,
-                project_id,
-            });
+            replacements.push(PreparedBulkTask { input, project });
         }
 
         let mut stale: HashSet<Uuid> =
@@ -282,21 +378,15 @@ impl ActivityProcessor {
         let current_user_id = current_profile.as_ref().map(|p| p.user_id);
 
         let mut tx = self.db.pool.begin().await?;
-        self.remove_stale_tasks(&mut tx, &stale_vec).await?;
+        remove_shared_tasks(&mut tx, &stale_vec).await?;
 
-        for PreparedBulkTask {
-            input,
-            creator_user_id,
-            project_id,
-        } in replacements
-        {
+        for PreparedBulkTask { input, project } in replacements {
             let shared_task = SharedTask::upsert(tx.as_mut(), input).await?;
             sync_local_task_for_shared_task(
                 tx.as_mut(),
                 &shared_task,
                 current_user_id,
-                creator_user_id,
-                project_id,
+                project.as_ref(),
             )
             .await?;
         }
@@ -309,23 +399,25 @@ impl ActivityProcessor {
         Ok(latest_seq)
     }
 
-    async fn remove_stale_tasks(
+    /// Compare the project's shared tasks with the remote digest and repair
+    /// any rows that drifted.
+    pub async fn verify_consistency(
         &self,
-        tx: &mut Transaction<'_, Sqlite>,
-        ids: &[Uuid],
-    ) -> Result<(), ShareError> {
-        if ids.is_empty() {
-            return Ok(());
-        }
-
-        for id in ids {
-            if let Some(local_task) = Task::find_by_shared_task_id(tx.as_mut(), *id).await? {
-                Task::set_shared_task_id(tx.as_mut(), local_task.id, None).await?;
-            }
-        }
-
-        SharedTask::remove_many(tx.as_mut(), ids).await?;
-        Ok(())
+        remote_project_id: Uuid,
+    ) -> Result<Divergence, ShareError> {
+        let current_user_id = self
+            .auth_ctx
+            .cached_profile()
+            .await
+            .map(|profile| profile.user_id);
+        consistency::check_project(
+            &self.db,
+            &self.remote_client,
+            remote_project_id,
+            current_user_id,
+            self.config.digest_page_limit,
+        )
+        .await
     }
 
     async fn fetch_bulk_snapshot(
@@ -338,3 +430,190 @@ impl ActivityProcessor {
             .await?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{DateTime, Utc};
+    use db::models::project::SharedTaskAutoCreate;
+    use remote::db::tasks::SharedTask as RemoteSharedTask;
+    use serde_json::json;
+    use sqlx::SqlitePool;
+    use url::Url;
+
+    use super::*;
+    use crate::{
+        services::oauth_credentials::OAuthCredentials,
+        test_common::{insert_project, remote_task, test_pool},
+    };
+
+    async fn test_processor() -> ActivityProcessor {
+        let api_base = Url::parse("http://127.0.0.1:9").unwrap();
+        let config = ShareConfig {
+            websocket_base: api_base.clone(),
+            api_base,
+            activity_page_limit: 200,
+            bulk_sync_threshold: 1000,
+            digest_page_limit: 1000,
+            consistency_check_interval: Duration::from_secs(60),
+            // Pruning forgets every event applied before the call
+            applied_event_retention: Duration::ZERO,
+            network: Default::default(),
+            device_id: None,
+        };
+        let credentials = std::env::temp_dir().join(format!("vk-creds-{}.json", Uuid::new_v4()));
+        let auth_ctx = AuthContext::new(
+            Arc::new(OAuthCredentials::new(credentials)),
+            Arc::new(RwLock::new(None)),
+        );
+        let remote_client = RemoteClient::new(config.api_base.as_str(), auth_ctx.clone()).unwrap();
+        ActivityProcessor::new(
+            DBService {
+                pool: test_pool().await,
+            },
+            config,
+            remote_client,
+            auth_ctx,
+            Arc::new(RwLock::new(Config::default())),
+        )
+    }
+
+    /// A local project linked to `remote_project_id` that picks up every
+    /// shared task. Returns the local project id.
+    async fn link_project(pool: &SqlitePool, remote_project_id: Uuid) -> Uuid {
+        let id = insert_project(pool, "shop", Some(remote_project_id)).await;
+        let project = Project::find_by_id(pool, id).await.unwrap().unwrap();
+        Project::update(
+            pool,
+            id,
+            project.name,
+            project.git_repo_path.to_string_lossy().to_string(),
+            project.setup_script,
+            project.dev_script,
+            project.dev_server_url_pattern,
+            project.cleanup_script,
+            project.copy_files,
+            project.verify_script,
+            project.verify_timeout_secs,
+            project.default_executor,
+            project.max_concurrent_executions,
+            project.close_task_on_pr_merge,
+            SharedTaskAutoCreate::All,
+        )
+        .await
+        .unwrap();
+        id
+    }
+
+    fn event(
+        seq: i64,
+        event_type: &str,
+        task_id: Uuid,
+        project_id: Uuid,
+        version: i64,
+    ) -> ActivityEvent {
+        let at = DateTime::parse_from_rfc3339("2025-11-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+            + chrono::Duration::minutes(seq);
+        let task = RemoteSharedTask {
+            deleted_at: (event_type == "task.deleted").then_some(at),
+            ..remote_task(task_id, project_id, &format!("v{version}"), version, at)
+        };
+        ActivityEvent::new(
+            seq,
+            Uuid::new_v4(),
+            project_id,
+            event_type.to_string(),
+            at,
+            Some(json!({ "task": task, "user": null })),
+        )
+    }
+
+    /// Shared tasks as (id, version, title) and local tasks as (title, link)
+    type Snapshot = (Vec<(Uuid, i64, String)>, Vec<(String, Option<Uuid>)>);
+
+    async fn snapshot(pool: &SqlitePool, remote_project_id: Uuid, project_id: Uuid) -> Snapshot {
+        let mut shared: Vec<_> = SharedTask::list_by_remote_project_id(pool, remote_project_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|task| (task.id, task.version, task.title))
+            .collect();
+        shared.sort();
+        let mut local: Vec<_> = Task::find_by_project_id_with_attempt_status(pool, project_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|task| (task.task.title, task.task.shared_task_id))
+            .collect();
+        local.sort();
+        (shared, local)
+    }
+
+    #[tokio::test]
+    async fn replaying_events_leaves_the_same_state() {
+        let processor = test_processor().await;
+        let pool = processor.db.pool.clone();
+        let project_id = Uuid::new_v4();
+        let local_project_id = link_project(&pool, project_id).await;
+        let (kept, deleted) = (Uuid::new_v4(), Uuid::new_v4());
+        let events = vec![
+            event(1, "task.created", kept, project_id, 1),
+            event(2, "task.created", deleted, project_id, 1),
+            event(3, "task.updated", kept, project_id, 2),
+            event(4, "task.deleted", deleted, project_id, 2),
+        ];
+
+        for ev in &events {
+            processor.process_event(ev.clone()).await.unwrap();
+        }
+        let first = snapshot(&pool, project_id, local_project_id).await;
+        assert_eq!(first.0.len(), 1);
+        assert_eq!(first.0[0].1, 2);
+
+        for ev in &events {
+            processor.process_event(ev.clone()).await.unwrap();
+        }
+        assert_eq!(snapshot(&pool, project_id, local_project_id).await, first);
+    }
+
+    #[tokio::test]
+    async fn replayed_upsert_does_not_resurrect_a_deleted_task() {
+        let processor = test_processor().await;
+        let pool = processor.db.pool.clone();
+        let project_id = Uuid::new_v4();
+        let local_project_id = link_project(&pool, project_id).await;
+        let task_id = Uuid::new_v4();
+        let created = event(1, "task.created", task_id, project_id, 1);
+
+        processor.process_event(created.clone()).await.unwrap();
+        processor
+            .process_event(event(2, "task.deleted", task_id, project_id, 2))
+            .await
+            .unwrap();
+        let after_delete = snapshot(&pool, project_id, local_project_id).await;
+        assert!(after_delete.0.is_empty());
+
+        processor.process_event(created).await.unwrap();
+        assert_eq!(
+            snapshot(&pool, project_id, local_project_id).await,
+            after_delete
+        );
+    }
+
+    #[tokio::test]
+    async fn prune_forgets_old_entries() {
+        let processor = test_processor().await;
+        let project_id = Uuid::new_v4();
+        processor
+            .process_event(event(1, "task.created", Uuid::new_v4(), project_id, 1))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert_eq!(processor.prune_applied_events().await.unwrap(), 1);
+        assert_eq!(processor.prune_applied_events().await.unwrap(), 0);
+    }
+}
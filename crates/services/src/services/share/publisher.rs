@@ -8,7 +8,7 @@ use remote::routes::tasks::{
 };
 use uuid::Uuid;
 
-use super::{ShareError, convert_remote_task, status};
+use super::{ShareError, SharedTaskLinks, convert_remote_task, outbox, status};
 use crate::services::remote_client::RemoteClient;
 
 #[derive(Clone)]
@@ -47,6 +47,7 @@ impl SharePublisher {
 
         let remote_task = self.client.create_shared_task(&payload).await?;
 
+        let _linking = SharedTaskLinks::guard(remote_task.task.id);
         self.sync_shared_task(&task, &remote_task).await?;
         Ok(remote_task.task.id)
     }
@@ -64,14 +65,28 @@ impl SharePublisher {
             version: None,
         };
 
-        let remote_task = self
+        match self
             .client
             .update_shared_task(shared_task_id, &payload)
-            .await?;
-
-        self.sync_shared_task(task, &remote_task).await?;
-
-        Ok(())
+            .await
+        {
+            Ok(remote_task) => self.sync_shared_task(task, &remote_task).await,
+            Err(err) if err.should_retry() => {
+                tracing::warn!(?err, %shared_task_id, "remote unreachable; queueing shared task update");
+                // Replayed against the version we last saw so a newer remote
+                // edit is not overwritten
+                let version = SharedTask::find_by_id(&self.db.pool, shared_task_id)
+                    .await?
+                    .map(|shared_task| shared_task.version);
+                outbox::enqueue_update(
+                    &self.db,
+                    shared_task_id,
+                    UpdateSharedTaskRequest { version, ..payload },
+                )
+                .await
+            }
+            Err(err) => Err(err.into()),
+        }
     }
 
     pub async fn update_shared_task_by_id(&self, task_id: Uuid) -> Result<(), ShareError> {
@@ -120,9 +135,18 @@ impl SharePublisher {
             version: Some(shared_task.version),
         };
 
-        self.client
+        match self
+            .client
             .delete_shared_task(shared_task.id, &payload)
-            .await?;
+            .await
+        {
+            Ok(_) => {}
+            Err(err) if err.should_retry() => {
+                tracing::warn!(?err, %shared_task_id, "remote unreachable; queueing shared task delete");
+                outbox::enqueue_delete(&self.db, shared_task.id, payload).await?;
+            }
+            Err(err) => return Err(err.into()),
+        }
 
         if let Some(local_task) =
             Task::find_by_shared_task_id(&self.db.pool, shared_task.id).await?
@@ -0,0 +1,45 @@
+//! Keeps the cached name and metadata of a linked remote project current,
+//! and notices when the remote project has been deleted.
+
+use db::{DBService, models::project::Project};
+use uuid::Uuid;
+
+use super::ShareError;
+use crate::services::remote_client::RemoteClient;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum RemoteProjectRefresh {
+    Unchanged,
+    /// The name or metadata changed and the local project row was updated
+    Updated,
+    /// The remote project no longer exists; the link has been marked broken
+    Deleted,
+}
+
+pub(super) async fn refresh_remote_project(
+    db: &DBService,
+    remote_client: &RemoteClient,
+    remote_project_id: Uuid,
+) -> Result<RemoteProjectRefresh, ShareError> {
+    let remote = match remote_client.get_project(remote_project_id).await {
+        Ok(remote) => remote,
+        Err(err) if err.is_not_found() => {
+            Project::mark_remote_link_broken(&db.pool, remote_project_id).await?;
+            return Ok(RemoteProjectRefresh::Deleted);
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let updated = Project::update_remote_project_cache(
+        &db.pool,
+        remote_project_id,
+        &remote.name,
+        &remote.metadata,
+    )
+    .await?;
+    Ok(if updated {
+        RemoteProjectRefresh::Updated
+    } else {
+        RemoteProjectRefresh::Unchanged
+    })
+}
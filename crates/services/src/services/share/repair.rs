@@ -0,0 +1,96 @@
+//! Repair of the links between local tasks and shared tasks, for example
+//! after restoring a database backup.
+
+use db::models::{project::Project, shared_task::SharedTask, task::Task};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::{
+    AutoCreateContext, ShareError, should_create_local_task, sync_local_task_for_shared_task,
+};
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct ShareLinkRepairReport {
+    /// Nothing was changed; the lists are what a real run would do
+    pub dry_run: bool,
+    /// Local tasks linked to a shared task that no longer exists; the link
+    /// is cleared
+    pub unlinked_tasks: Vec<Uuid>,
+    /// Shared tasks that get a new local task
+    pub created_for: Vec<Uuid>,
+    /// Shared tasks left without a local task by the project's
+    /// `shared_task_auto_create` setting
+    pub skipped: Vec<Uuid>,
+    pub unlinked_count: usize,
+    pub created_count: usize,
+    pub skipped_count: usize,
+}
+
+/// Clear the project's links to missing shared tasks, then link the shared
+/// tasks of its remote project again as [`super::link_shared_tasks_to_project`]
+/// does.
+///
+/// Everything happens in one transaction. Outside a dry run its first
+/// statement is a write, so SQLite's write lock is held throughout and
+/// activity events wait for the repair instead of interleaving with it.
+pub async fn repair_shared_task_links(
+    pool: &SqlitePool,
+    current_user_id: Option<Uuid>,
+    project: &Project,
+    dry_run: bool,
+) -> Result<ShareLinkRepairReport, ShareError> {
+    let mut tx = pool.begin().await?;
+
+    let unlinked_tasks = if dry_run {
+        Task::find_with_missing_shared_task(&mut *tx, project.id).await?
+    } else {
+        Task::clear_missing_shared_task_ids(&mut *tx, project.id).await?
+    };
+
+    let mut created_for = Vec::new();
+    let mut skipped = Vec::new();
+    if let Some(remote_project_id) = project.remote_project_id {
+        for shared_task in
+            SharedTask::list_by_remote_project_id(&mut *tx, remote_project_id).await?
+        {
+            if Task::find_by_shared_task_id(&mut *tx, shared_task.id)
+                .await?
+                .is_none()
+            {
+                let ctx = AutoCreateContext::new(&shared_task, current_user_id);
+                if should_create_local_task(project.shared_task_auto_create, ctx) {
+                    created_for.push(shared_task.id);
+                } else {
+                    skipped.push(shared_task.id);
+                }
+            }
+            if !dry_run {
+                sync_local_task_for_shared_task(
+                    &mut *tx,
+                    &shared_task,
+                    current_user_id,
+                    Some(project),
+                )
+                .await?;
+            }
+        }
+    }
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+
+    Ok(ShareLinkRepairReport {
+        dry_run,
+        unlinked_count: unlinked_tasks.len(),
+        created_count: created_for.len(),
+        skipped_count: skipped.len(),
+        unlinked_tasks,
+        created_for,
+        skipped,
+    })
+}
@@ -0,0 +1,142 @@
+//! What each project watcher is currently doing, so a stalled sync can be
+//! told apart from one that is waiting for auth or backing off.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::ShareError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectSyncState {
+    WaitingAuth,
+    CatchingUp,
+    Connected,
+    Backoff,
+    /// The remote project was deleted; nothing syncs until the project is
+    /// unlinked
+    LinkBroken,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProjectSyncStatus {
+    pub remote_project_id: Uuid,
+    pub state: ProjectSyncState,
+    /// Last activity sequence number applied locally
+    pub last_seq: Option<i64>,
+    /// When the last activity event arrived over the websocket
+    pub last_event_at: Option<DateTime<Utc>>,
+    /// When the watcher tries again, while waiting or backing off
+    pub retry_at: Option<DateTime<Utc>>,
+    /// Most recent failure, kept after the watcher recovers
+    pub last_error: Option<String>,
+}
+
+/// Shared between the watchers, which write to it, and the local server,
+/// which reads it through [`super::RemoteSyncHandle::status`].
+#[derive(Debug, Clone, Default)]
+pub struct SyncStatusRegistry {
+    projects: Arc<Mutex<HashMap<Uuid, ProjectSyncStatus>>>,
+}
+
+impl SyncStatusRegistry {
+    /// One entry per watched remote project, ordered by id.
+    pub fn snapshot(&self) -> Vec<ProjectSyncStatus> {
+        let mut projects: Vec<ProjectSyncStatus> =
+            self.projects.lock().unwrap().values().cloned().collect();
+        projects.sort_by_key(|status| status.remote_project_id);
+        projects
+    }
+
+    pub(super) fn set_state(&self, remote_project_id: Uuid, state: ProjectSyncState) {
+        self.update(remote_project_id, |status| {
+            status.state = state;
+            status.retry_at = None;
+        });
+    }
+
+    pub(super) fn wait_for_auth(&self, remote_project_id: Uuid, retry_in: Duration) {
+        self.update(remote_project_id, |status| {
+            status.state = ProjectSyncState::WaitingAuth;
+            status.retry_at = Some(retry_at(retry_in));
+        });
+    }
+
+    /// `error` is `None` when the connection simply dropped.
+    pub(super) fn back_off(
+        &self,
+        remote_project_id: Uuid,
+        retry_in: Duration,
+        error: Option<&ShareError>,
+    ) {
+        self.update(remote_project_id, |status| {
+            status.state = ProjectSyncState::Backoff;
+            status.retry_at = Some(retry_at(retry_in));
+            if let Some(error) = error {
+                status.last_error = Some(error.to_string());
+            }
+        });
+    }
+
+    pub(super) fn record_error(&self, remote_project_id: Uuid, error: &ShareError) {
+        self.update(remote_project_id, |status| {
+            status.last_error = Some(error.to_string());
+        });
+    }
+
+    pub(super) fn mark_link_broken(&self, remote_project_id: Uuid) {
+        self.update(remote_project_id, |status| {
+            status.state = ProjectSyncState::LinkBroken;
+            status.retry_at = None;
+            status.last_error =
+                Some(ShareError::RemoteProjectDeleted(remote_project_id).to_string());
+        });
+    }
+
+    pub(super) fn record_seq(&self, remote_project_id: Uuid, last_seq: Option<i64>) {
+        self.update(remote_project_id, |status| status.last_seq = last_seq);
+    }
+
+    pub(super) fn record_event(&self, remote_project_id: Uuid, seq: i64) {
+        self.update(remote_project_id, |status| {
+            status.last_seq = Some(seq);
+            status.last_event_at = Some(Utc::now());
+        });
+    }
+
+    pub(super) fn remove(&self, remote_project_id: Uuid) {
+        self.projects.lock().unwrap().remove(&remote_project_id);
+    }
+
+    /// Drop entries for remote projects no longer linked to any project.
+    pub(super) fn retain(&self, linked: impl Fn(&Uuid) -> bool) {
+        self.projects.lock().unwrap().retain(|id, _| linked(id));
+    }
+
+    fn update(&self, remote_project_id: Uuid, f: impl FnOnce(&mut ProjectSyncStatus)) {
+        let mut projects = self.projects.lock().unwrap();
+        let status = projects
+            .entry(remote_project_id)
+            .or_insert_with(|| ProjectSyncStatus {
+                remote_project_id,
+                state: ProjectSyncState::WaitingAuth,
+                last_seq: None,
+                last_event_at: None,
+                retry_at: None,
+                last_error: None,
+            });
+        f(status);
+    }
+}
+
+fn retry_at(retry_in: Duration) -> DateTime<Utc> {
+    Utc::now() + chrono::Duration::from_std(retry_in).unwrap_or_else(|_| chrono::Duration::zero())
+}
@@ -0,0 +1,71 @@
+//! State shared by the steps of the local server's shutdown.
+//!
+//! Once shutdown begins no new execution processes are started. Tasks that
+//! write a process's output to the database are tracked here so the final
+//! batches can be awaited instead of being dropped with the runtime.
+
+use std::{
+    future::Future,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::task::JoinHandle;
+use tokio_util::task::TaskTracker;
+
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownState {
+    closing: Arc<AtomicBool>,
+    log_writers: TaskTracker,
+}
+
+impl ShutdownState {
+    /// Stop starting new execution processes. Returns false if shutdown had
+    /// already begun.
+    pub fn begin(&self) -> bool {
+        !self.closing.swap(true, Ordering::SeqCst)
+    }
+
+    pub fn is_closing(&self) -> bool {
+        self.closing.load(Ordering::SeqCst)
+    }
+
+    /// Run a task that persists process output, awaited on shutdown.
+    pub fn spawn_log_writer<F>(&self, task: F) -> JoinHandle<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.log_writers.spawn(task)
+    }
+
+    /// Wait up to `timeout` for the log writers to finish. False if some were
+    /// still running.
+    pub async fn wait_for_log_writers(&self, timeout: Duration) -> bool {
+        self.log_writers.close();
+        let pending = self.log_writers.len();
+        if pending > 0 {
+            tracing::info!("Waiting for {} log writers to flush", pending);
+        }
+        tokio::time::timeout(timeout, self.log_writers.wait())
+            .await
+            .is_ok()
+    }
+}
+
+/// What a shutdown stopped, logged once it completes.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ShutdownSummary {
+    /// Execution processes stopped and marked killed
+    pub processes_stopped: usize,
+    /// Execution processes that could not be stopped cleanly
+    pub processes_failed: usize,
+    /// Every log writer finished within the timeout
+    pub logs_flushed: bool,
+    /// The shared task synchronizer was running and stopped in time
+    pub remote_sync_stopped: bool,
+}
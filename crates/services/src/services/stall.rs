@@ -0,0 +1,193 @@
+//! Detection of running processes that stop producing output, e.g. an agent
+//! waiting on an interactive prompt or a network call that never returns.
+
+use std::{sync::Arc, time::Duration};
+
+use db::{DBService, models::execution_process::ExecutionProcess};
+use executors::{
+    actions::{ExecutorAction, ExecutorActionType, script::ScriptContext},
+    logs::{
+        NormalizedEntry, NormalizedEntryType,
+        utils::{ConversationPatch, EntryIndexProvider},
+    },
+};
+use futures::StreamExt;
+use tokio::{task::JoinHandle, time::Instant};
+use utils::{log_msg::LogMsg, msg_store::MsgStore};
+use uuid::Uuid;
+
+use crate::services::{config::NotificationConfig, notification::NotificationService};
+
+pub const DEFAULT_STALL_TIMEOUT_MINUTES: u32 = 10;
+
+/// How long `action` may go without output before it counts as stalled, or
+/// `None` if it should not be watched. Dev servers are expected to sit idle,
+/// and scripts can carry their own timeout.
+pub fn stall_timeout(action: &ExecutorAction, default_minutes: u32) -> Option<Duration> {
+    let default =
+        (default_minutes > 0).then(|| Duration::from_secs(u64::from(default_minutes) * 60));
+    match action.typ() {
+        ExecutorActionType::ScriptRequest(script) if script.context == ScriptContext::DevServer => {
+            None
+        }
+        ExecutorActionType::ScriptRequest(script) => match script.stall_timeout_secs {
+            Some(0) => None,
+            Some(secs) => Some(Duration::from_secs(secs)),
+            None => default,
+        },
+        _ => default,
+    }
+}
+
+fn describe(timeout: Duration) -> String {
+    let secs = timeout.as_secs();
+    match (secs / 60, secs % 60) {
+        (1, 0) => "1 minute".to_string(),
+        (minutes, 0) => format!("{minutes} minutes"),
+        _ => format!("{secs} seconds"),
+    }
+}
+
+/// Conversation entry added when a process goes quiet.
+pub fn stalled_entry(timeout: Duration) -> NormalizedEntry {
+    NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::SystemMessage,
+        content: format!(
+            "No output for {}. The process may be waiting for input or stuck.",
+            describe(timeout)
+        ),
+        metadata: None,
+    }
+}
+
+/// Watch a running process's output and flag it as stalled once nothing has
+/// arrived for `timeout`. The flag is cleared if output resumes.
+pub fn spawn_stall_watcher(
+    db: DBService,
+    execution_id: Uuid,
+    msg_store: Arc<MsgStore>,
+    timeout: Duration,
+    notifications: NotificationConfig,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut messages = msg_store.history_plus_stream();
+        let mut deadline = Instant::now() + timeout;
+        let mut stalled = false;
+
+        loop {
+            tokio::select! {
+                msg = messages.next() => match msg {
+                    Some(Ok(LogMsg::Stdout(_) | LogMsg::Stderr(_))) => {
+                        deadline = Instant::now() + timeout;
+                        if stalled {
+                            stalled = false;
+                            if let Err(e) =
+                                ExecutionProcess::set_stalled(&db.pool, execution_id, false).await
+                            {
+                                tracing::error!(
+                                    "Failed to clear stalled flag for execution {}: {}",
+                                    execution_id,
+                                    e
+                                );
+                            }
+                        }
+                    }
+                    Some(Ok(LogMsg::Finished)) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+                _ = tokio::time::sleep_until(deadline), if !stalled => {
+                    stalled = true;
+                    report_stall(&db, execution_id, &msg_store, timeout, notifications.clone())
+                        .await;
+                }
+            }
+        }
+    })
+}
+
+async fn report_stall(
+    db: &DBService,
+    execution_id: Uuid,
+    msg_store: &MsgStore,
+    timeout: Duration,
+    notifications: NotificationConfig,
+) {
+    tracing::warn!(
+        "Execution {} produced no output for {}",
+        execution_id,
+        describe(timeout)
+    );
+    if let Err(e) = ExecutionProcess::set_stalled(&db.pool, execution_id, true).await {
+        tracing::error!(
+            "Failed to flag execution {} as stalled: {}",
+            execution_id,
+            e
+        );
+    }
+
+    // Nothing else is being appended while the process is quiet, so the next
+    // free index is safe to take
+    let index = EntryIndexProvider::start_from(msg_store).next();
+    msg_store.push_patch(ConversationPatch::add_normalized_entry(
+        index,
+        stalled_entry(timeout),
+    ));
+
+    let title = match ExecutionProcess::load_context(&db.pool, execution_id).await {
+        Ok(ctx) => format!("Task Stalled: {}", ctx.task.title),
+        Err(_) => "Task Stalled".to_string(),
+    };
+    let message = format!("⏳ No output for {}", describe(timeout));
+    NotificationService::notify(notifications, &title, &message).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::actions::script::{ScriptRequest, ScriptRequestLanguage};
+
+    use super::*;
+
+    fn script(context: ScriptContext, stall_timeout_secs: Option<u64>) -> ExecutorAction {
+        ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: "make".to_string(),
+                language: ScriptRequestLanguage::Bash,
+                context,
+                timeout_secs: None,
+                stall_timeout_secs,
+            }),
+            None,
+        )
+    }
+
+    #[test]
+    fn scripts_can_override_the_stall_timeout() {
+        let setup = script(ScriptContext::SetupScript, None);
+        assert_eq!(stall_timeout(&setup, 10), Some(Duration::from_secs(600)));
+        assert_eq!(stall_timeout(&setup, 0), None);
+
+        let slow_build = script(ScriptContext::SetupScript, Some(3600));
+        assert_eq!(
+            stall_timeout(&slow_build, 10),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(
+            stall_timeout(&slow_build, 0),
+            Some(Duration::from_secs(3600))
+        );
+
+        let unwatched = script(ScriptContext::SetupScript, Some(0));
+        assert_eq!(stall_timeout(&unwatched, 10), None);
+
+        let dev_server = script(ScriptContext::DevServer, Some(60));
+        assert_eq!(stall_timeout(&dev_server, 10), None);
+    }
+
+    #[test]
+    fn describes_timeouts_in_minutes_when_whole() {
+        assert_eq!(describe(Duration::from_secs(600)), "10 minutes");
+        assert_eq!(describe(Duration::from_secs(60)), "1 minute");
+        assert_eq!(describe(Duration::from_secs(90)), "90 seconds");
+    }
+}
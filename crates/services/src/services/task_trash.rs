@@ -0,0 +1,61 @@
+//! Permanent removal of deleted tasks.
+//!
+//! Deleting a task only moves it to its project's trash, from where it can be
+//! restored. Once it has been there longer than the retention period it is
+//! removed for good: its attempts and their execution processes go with the
+//! row, and their worktrees are handed back for removal.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, Utc};
+use db::models::{
+    project::Project,
+    task::Task,
+    task_attempt::{TaskAttempt, TaskAttemptError},
+};
+use sqlx::SqlitePool;
+use thiserror::Error;
+
+use crate::services::worktree_manager::WorktreeCleanup;
+
+pub const DEFAULT_TASK_TRASH_RETENTION_DAYS: u32 = 30;
+
+#[derive(Debug, Error)]
+pub enum TaskTrashError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    TaskAttempt(#[from] TaskAttemptError),
+}
+
+/// Remove tasks deleted more than `retention` before `now`, returning the
+/// worktrees their attempts left behind.
+pub async fn purge_expired_tasks(
+    pool: &SqlitePool,
+    retention: Duration,
+    now: DateTime<Utc>,
+) -> Result<Vec<WorktreeCleanup>, TaskTrashError> {
+    let mut cleanup = Vec::new();
+    for task in Task::find_deleted_before(pool, now - retention).await? {
+        let attempts = TaskAttempt::fetch_all(pool, Some(task.id)).await?;
+        let git_repo_path = Project::find_by_id(pool, task.project_id)
+            .await?
+            .map(|project| project.git_repo_path);
+
+        let mut tx = pool.begin().await?;
+        // Tasks started from these attempts stay, without a parent
+        for attempt in &attempts {
+            Task::nullify_children_by_attempt_id(&mut *tx, attempt.id).await?;
+        }
+        Task::delete(&mut *tx, task.id).await?;
+        tx.commit().await?;
+
+        cleanup.extend(attempts.into_iter().filter_map(|attempt| {
+            attempt.container_ref.map(|worktree_path| WorktreeCleanup {
+                worktree_path: PathBuf::from(worktree_path),
+                git_repo_path: git_repo_path.clone(),
+            })
+        }));
+    }
+    Ok(cleanup)
+}
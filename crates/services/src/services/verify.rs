@@ -0,0 +1,85 @@
+//! Optional verify phase between the coding agent and cleanup.
+//!
+//! A project can configure a verify command (tests, lint, type checks). It
+//! runs as its own execution process once the agent has committed, and its
+//! outcome is recorded on the attempt as a [`VerifyStatus`]. A failing verify
+//! never fails the attempt: cleanup still runs and the task still moves to
+//! review, where the result is shown alongside the diff.
+
+use db::models::{
+    project::Project,
+    task_attempt::{TaskAttempt, VerifyStatus},
+};
+use executors::actions::script::{ScriptContext, ScriptRequest, ScriptRequestLanguage};
+use sqlx::SqlitePool;
+use utils::{log_msg::LogMsg, msg_store::MsgStore};
+use uuid::Uuid;
+
+use crate::services::dev_server;
+
+/// Used when the project does not set `verify_timeout_secs`.
+pub const DEFAULT_VERIFY_TIMEOUT_SECS: u64 = 600;
+
+/// Output lines kept on the attempt when verification does not pass.
+pub const VERIFY_OUTPUT_TAIL_LINES: usize = 100;
+
+/// The script request for the project's verify command, if it has one.
+pub fn verify_request(project: &Project) -> Option<ScriptRequest> {
+    let script = project.verify_script.as_deref()?.trim();
+    if script.is_empty() {
+        return None;
+    }
+    let timeout_secs = project
+        .verify_timeout_secs
+        .and_then(|secs| u64::try_from(secs).ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_VERIFY_TIMEOUT_SECS);
+
+    Some(ScriptRequest {
+        script: script.to_string(),
+        language: ScriptRequestLanguage::Bash,
+        context: ScriptContext::VerifyScript,
+        timeout_secs: Some(timeout_secs),
+        stall_timeout_secs: None,
+    })
+}
+
+/// Classify a finished verify process.
+pub fn verify_status(exit_code: Option<i64>, timed_out: bool) -> VerifyStatus {
+    if timed_out {
+        VerifyStatus::TimedOut
+    } else if exit_code == Some(0) {
+        VerifyStatus::Passed
+    } else {
+        VerifyStatus::Failed
+    }
+}
+
+/// The last [`VERIFY_OUTPUT_TAIL_LINES`] lines of stdout and stderr,
+/// interleaved in the order they were produced.
+pub fn output_tail(history: impl IntoIterator<Item = LogMsg>) -> String {
+    dev_server::tail_lines(history, VERIFY_OUTPUT_TAIL_LINES)
+        .into_iter()
+        .filter_map(|msg| match msg {
+            LogMsg::Stdout(line) | LogMsg::Stderr(line) => Some(line),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Store the outcome of a verify run on its attempt. Output is only kept when
+/// the run did not pass.
+pub async fn record_result(
+    pool: &SqlitePool,
+    attempt_id: Uuid,
+    status: VerifyStatus,
+    msg_store: Option<&MsgStore>,
+) -> Result<(), sqlx::Error> {
+    let output = match (status, msg_store) {
+        (VerifyStatus::Failed | VerifyStatus::TimedOut, Some(store)) => {
+            Some(output_tail(store.get_history()))
+        }
+        _ => None,
+    };
+    TaskAttempt::update_verify_status(pool, attempt_id, status, output.as_deref()).await
+}
@@ -0,0 +1,355 @@
+//! Removal of worktrees and branches left behind by attempts that finished,
+//! were merged, or were deleted.
+//!
+//! Worktrees of idle attempts are removed once the retention period has
+//! passed; the attempt's branch is kept unless it was merged, so the attempt
+//! can be picked up again later. Worktrees and branches that no attempt
+//! refers to any more are removed too. Nothing with uncommitted changes or
+//! unmerged commits is deleted unless `force` is set.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use db::{
+    DBService,
+    models::{
+        attempt_event::{AttemptEvent, AttemptEventType},
+        project::Project,
+        task_attempt::{TaskAttempt, WorktreeCleanupCandidate},
+    },
+};
+use serde::Serialize;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::services::{
+    git::{GitCli, GitService, GitServiceError},
+    worktree_manager::{WorktreeCleanup, WorktreeManager},
+};
+
+pub const DEFAULT_WORKTREE_RETENTION_HOURS: u32 = 72;
+
+#[derive(Debug, Error)]
+pub enum WorktreeCleanupError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    GitService(#[from] GitServiceError),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WorktreeCleanupOptions {
+    /// How long an attempt must have been idle before its worktree goes
+    pub retention: Duration,
+    /// Also delete worktrees with uncommitted changes and unmerged branches
+    /// of deleted attempts
+    pub force: bool,
+}
+
+impl WorktreeCleanupOptions {
+    pub fn new(retention_hours: u32, force: bool) -> Self {
+        Self {
+            retention: Duration::hours(i64::from(retention_hours)),
+            force,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum CleanupSkipReason {
+    /// A process is running or the attempt is waiting in the queue
+    Active,
+    /// Used more recently than the retention period
+    WithinRetention,
+    UncommittedChanges,
+    /// The branch has commits that are not on the repository's HEAD
+    UnmergedCommits,
+    /// Removal was attempted and failed
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct CleanupItem {
+    /// `None` for leftovers of attempts that were deleted
+    pub task_attempt_id: Option<Uuid>,
+    pub worktree_path: Option<String>,
+    pub branch: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct SkippedCleanupItem {
+    pub item: CleanupItem,
+    pub reason: CleanupSkipReason,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct WorktreeCleanupReport {
+    pub removed: Vec<CleanupItem>,
+    pub skipped: Vec<SkippedCleanupItem>,
+}
+
+impl WorktreeCleanupReport {
+    fn skip(&mut self, item: CleanupItem, reason: CleanupSkipReason, detail: Option<String>) {
+        self.skipped.push(SkippedCleanupItem {
+            item,
+            reason,
+            detail,
+        });
+    }
+
+    fn extend(&mut self, other: Self) {
+        self.removed.extend(other.removed);
+        self.skipped.extend(other.skipped);
+    }
+}
+
+/// Clean up every project. A project whose repository cannot be read is
+/// logged and left out of the report.
+pub async fn cleanup_all_projects(
+    db: &DBService,
+    git: &GitService,
+    branch_prefix: &str,
+    options: WorktreeCleanupOptions,
+) -> Result<WorktreeCleanupReport, WorktreeCleanupError> {
+    let mut report = WorktreeCleanupReport::default();
+    for project in Project::find_all(&db.pool).await? {
+        match cleanup_project(db, git, &project, branch_prefix, options).await {
+            Ok(project_report) => report.extend(project_report),
+            Err(e) => tracing::warn!("Worktree cleanup failed for project {}: {}", project.id, e),
+        }
+    }
+    Ok(report)
+}
+
+/// Clean up the worktrees and branches of one project's attempts.
+pub async fn cleanup_project(
+    db: &DBService,
+    git: &GitService,
+    project: &Project,
+    branch_prefix: &str,
+    options: WorktreeCleanupOptions,
+) -> Result<WorktreeCleanupReport, WorktreeCleanupError> {
+    let candidates = TaskAttempt::find_cleanup_candidates(&db.pool, project.id).await?;
+    let mut cleanup = Cleanup {
+        db,
+        git,
+        repo_path: &project.git_repo_path,
+        cutoff: Utc::now() - options.retention,
+        force: options.force,
+        report: WorktreeCleanupReport::default(),
+    };
+
+    for candidate in &candidates {
+        cleanup.attempt(candidate).await;
+    }
+    // Orphaned worktrees go first so their branches are no longer checked out
+    cleanup.orphaned_worktrees(&candidates).await?;
+    cleanup.orphaned_branches(branch_prefix).await?;
+    Ok(cleanup.report)
+}
+
+struct Cleanup<'a> {
+    db: &'a DBService,
+    git: &'a GitService,
+    repo_path: &'a Path,
+    cutoff: DateTime<Utc>,
+    force: bool,
+    report: WorktreeCleanupReport,
+}
+
+impl Cleanup<'_> {
+    async fn attempt(&mut self, candidate: &WorktreeCleanupCandidate) {
+        let worktree = candidate
+            .container_ref
+            .as_deref()
+            .filter(|_| !candidate.worktree_deleted)
+            .map(PathBuf::from);
+        let delete_branch = candidate.is_merged
+            && self
+                .git
+                .check_branch_exists(self.repo_path, &candidate.branch)
+                .unwrap_or(false);
+        if worktree.is_none() && !delete_branch {
+            return;
+        }
+
+        let item = CleanupItem {
+            task_attempt_id: Some(candidate.attempt_id),
+            worktree_path: worktree.as_ref().map(|p| p.to_string_lossy().to_string()),
+            branch: delete_branch.then(|| candidate.branch.clone()),
+        };
+        if candidate.is_active {
+            return self.report.skip(item, CleanupSkipReason::Active, None);
+        }
+        if candidate.last_activity_at > self.cutoff {
+            return self
+                .report
+                .skip(item, CleanupSkipReason::WithinRetention, None);
+        }
+
+        if let Some(path) = &worktree {
+            if let Err((reason, detail)) = self.remove_worktree(path).await {
+                return self.report.skip(item, reason, detail);
+            }
+            if let Err(e) =
+                TaskAttempt::mark_worktree_deleted(&self.db.pool, candidate.attempt_id).await
+            {
+                return self
+                    .report
+                    .skip(item, CleanupSkipReason::Failed, Some(e.to_string()));
+            }
+            AttemptEvent::record(
+                &self.db.pool,
+                candidate.attempt_id,
+                AttemptEventType::WorktreeRemoved,
+                serde_json::json!({ "path": path }),
+            )
+            .await;
+        }
+        if delete_branch
+            && let Err(e) = self
+                .git
+                .delete_local_branch(self.repo_path, &candidate.branch)
+        {
+            return self
+                .report
+                .skip(item, CleanupSkipReason::Failed, Some(e.to_string()));
+        }
+        tracing::info!(
+            "Cleaned up worktree {:?} and branch {:?} of attempt {}",
+            item.worktree_path,
+            item.branch,
+            candidate.attempt_id
+        );
+        self.report.removed.push(item);
+    }
+
+    /// Worktrees under the vibe-kanban worktree directory that git knows
+    /// about but no attempt points at.
+    async fn orphaned_worktrees(
+        &mut self,
+        candidates: &[WorktreeCleanupCandidate],
+    ) -> Result<(), WorktreeCleanupError> {
+        let base_dir = canonical(&WorktreeManager::get_worktree_base_dir());
+        let known: HashSet<PathBuf> = candidates
+            .iter()
+            .filter_map(|c| c.container_ref.as_deref())
+            .map(|p| canonical(Path::new(p)))
+            .collect();
+        let worktrees = GitCli::new()
+            .list_worktrees(self.repo_path)
+            .map_err(GitServiceError::from)?;
+
+        for entry in worktrees {
+            let path = canonical(Path::new(&entry.path));
+            if !path.starts_with(&base_dir)
+                || known.contains(&path)
+                || TaskAttempt::container_ref_exists(&self.db.pool, &entry.path).await?
+            {
+                continue;
+            }
+
+            let item = CleanupItem {
+                task_attempt_id: None,
+                worktree_path: Some(entry.path.clone()),
+                branch: None,
+            };
+            let modified = std::fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .ok()
+                .map(DateTime::<Utc>::from);
+            if modified.is_some_and(|modified| modified > self.cutoff) {
+                self.report
+                    .skip(item, CleanupSkipReason::WithinRetention, None);
+                continue;
+            }
+            match self.remove_worktree(&path).await {
+                Ok(()) => {
+                    tracing::info!("Removed orphaned worktree {}", entry.path);
+                    self.report.removed.push(item);
+                }
+                Err((reason, detail)) => self.report.skip(item, reason, detail),
+            }
+        }
+        Ok(())
+    }
+
+    /// Branches named like attempt branches that no attempt uses. Without a
+    /// prefix they cannot be told apart from the user's own branches.
+    async fn orphaned_branches(&mut self, branch_prefix: &str) -> Result<(), WorktreeCleanupError> {
+        if branch_prefix.is_empty() {
+            return Ok(());
+        }
+        let branches = self
+            .git
+            .list_local_branches_with_prefix(self.repo_path, &format!("{branch_prefix}/"))?;
+
+        for branch in branches {
+            if TaskAttempt::branch_exists(&self.db.pool, &branch).await? {
+                continue;
+            }
+            let item = CleanupItem {
+                task_attempt_id: None,
+                worktree_path: None,
+                branch: Some(branch.clone()),
+            };
+            if !self.force {
+                match self.git.is_branch_merged_into_head(self.repo_path, &branch) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        self.report
+                            .skip(item, CleanupSkipReason::UnmergedCommits, None);
+                        continue;
+                    }
+                    Err(e) => {
+                        self.report
+                            .skip(item, CleanupSkipReason::Failed, Some(e.to_string()));
+                        continue;
+                    }
+                }
+            }
+            match self.git.delete_local_branch(self.repo_path, &branch) {
+                Ok(()) => {
+                    tracing::info!("Deleted orphaned branch {}", branch);
+                    self.report.removed.push(item);
+                }
+                Err(e) => self
+                    .report
+                    .skip(item, CleanupSkipReason::Failed, Some(e.to_string())),
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove_worktree(
+        &self,
+        path: &Path,
+    ) -> Result<(), (CleanupSkipReason, Option<String>)> {
+        if path.exists() && !self.force {
+            match self.git.get_worktree_change_counts(path) {
+                Ok((0, 0)) => {}
+                Ok(_) => return Err((CleanupSkipReason::UncommittedChanges, None)),
+                Err(e) => return Err((CleanupSkipReason::Failed, Some(e.to_string()))),
+            }
+        }
+        WorktreeManager::cleanup_worktree(&WorktreeCleanup::new(
+            path.to_path_buf(),
+            Some(self.repo_path.to_path_buf()),
+        ))
+        .await
+        .map_err(|e| (CleanupSkipReason::Failed, Some(e.to_string())))
+    }
+}
+
+/// Resolve symlinks (e.g. `/var` -> `/private/var` on macOS) so paths from
+/// git and from the database compare equal.
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::test_pool;
+use db::models::api_token::{ApiToken, ApiTokenScope, CreateApiToken};
+use services::services::api_tokens::{self, ApiTokenError};
+
+fn request(expires_at: Option<chrono::DateTime<Utc>>) -> CreateApiToken {
+    CreateApiToken {
+        name: "ci".to_string(),
+        scope: ApiTokenScope::ReadOnly,
+        expires_at,
+    }
+}
+
+#[tokio::test]
+async fn issued_token_authenticates_until_revoked() {
+    let pool = test_pool().await;
+    let (token, value) = api_tokens::issue(&pool, &request(None)).await.unwrap();
+    assert!(value.starts_with(api_tokens::API_TOKEN_PREFIX));
+    assert_ne!(token.token_hash, value);
+
+    let authenticated = api_tokens::authenticate(&pool, &value).await.unwrap();
+    assert_eq!(authenticated.id, token.id);
+    let first_use = ApiToken::find_by_id(&pool, token.id)
+        .await
+        .unwrap()
+        .unwrap()
+        .last_used_at
+        .unwrap();
+
+    // A second use within the minute leaves last_used_at alone
+    api_tokens::authenticate(&pool, &value).await.unwrap();
+    let stored = ApiToken::find_by_id(&pool, token.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.last_used_at, Some(first_use));
+
+    assert_eq!(ApiToken::revoke(&pool, token.id).await.unwrap(), 1);
+    let err = api_tokens::authenticate(&pool, &value).await.unwrap_err();
+    assert!(matches!(err, ApiTokenError::Revoked));
+}
+
+#[tokio::test]
+async fn expired_and_tampered_tokens_are_rejected() {
+    let pool = test_pool().await;
+    let (_, expired) = api_tokens::issue(&pool, &request(Some(Utc::now() - Duration::hours(1))))
+        .await
+        .unwrap();
+    let err = api_tokens::authenticate(&pool, &expired).await.unwrap_err();
+    assert!(matches!(err, ApiTokenError::Expired));
+
+    let (_, value) = api_tokens::issue(&pool, &request(Some(Utc::now() + Duration::hours(1))))
+        .await
+        .unwrap();
+    let last = if value.ends_with('0') { '1' } else { '0' };
+    let tampered = format!("{}{last}", &value[..value.len() - 1]);
+    let err = api_tokens::authenticate(&pool, &tampered)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiTokenError::Unknown));
+
+    let err = api_tokens::authenticate(&pool, "vk_not-a-token")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiTokenError::Malformed));
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use common::{insert_attempt, test_pool};
+use db::models::attempt_event::{AttemptEvent, AttemptEventType};
+use serde_json::json;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn events_are_listed_in_the_order_they_happened() {
+    let pool = test_pool().await;
+    let attempt_id = insert_attempt(&pool).await;
+    let other_attempt_id = insert_attempt(&pool).await;
+
+    AttemptEvent::record(
+        &pool,
+        attempt_id,
+        AttemptEventType::WorktreeCreated,
+        json!({ "branch": "vk/task", "path": "/tmp/worktree" }),
+    )
+    .await;
+    AttemptEvent::record(&pool, other_attempt_id, AttemptEventType::Queued, json!({})).await;
+    AttemptEvent::record(
+        &pool,
+        attempt_id,
+        AttemptEventType::ProcessStarted,
+        json!({ "execution_process_id": Uuid::new_v4() }),
+    )
+    .await;
+    AttemptEvent::record(
+        &pool,
+        attempt_id,
+        AttemptEventType::ProcessCompleted,
+        json!({ "exit_code": 0 }),
+    )
+    .await;
+    // Events of an attempt that no longer exists are dropped, not raised
+    AttemptEvent::record(&pool, Uuid::new_v4(), AttemptEventType::Queued, json!({})).await;
+
+    let events = AttemptEvent::find_by_task_attempt_id(&pool, attempt_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        events.iter().map(|e| e.event_type).collect::<Vec<_>>(),
+        [
+            AttemptEventType::WorktreeCreated,
+            AttemptEventType::ProcessStarted,
+            AttemptEventType::ProcessCompleted,
+        ]
+    );
+    assert_eq!(events[0].payload["branch"], "vk/task");
+    assert_eq!(events[2].payload["exit_code"], 0);
+}
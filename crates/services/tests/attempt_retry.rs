@@ -0,0 +1,61 @@
+mod common;
+
+use common::{insert_project, insert_task, test_pool};
+use db::models::task_attempt::{CreateTaskAttempt, TaskAttempt};
+use executors::executors::BaseCodingAgent;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+async fn create_attempt(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    retried_from_attempt_id: Option<Uuid>,
+) -> TaskAttempt {
+    let id = Uuid::new_v4();
+    TaskAttempt::create(
+        pool,
+        &CreateTaskAttempt {
+            executor: BaseCodingAgent::ClaudeCode,
+            base_branch: "main".to_string(),
+            branch: format!("vk/{id}"),
+            retried_from_attempt_id,
+        },
+        id,
+        task_id,
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn retry_links_to_the_original_attempt() {
+    let pool = test_pool().await;
+    let project_id = insert_project(&pool, "shop", None).await;
+    let task_id = insert_task(&pool, project_id, "task", None).await;
+    let original = create_attempt(&pool, task_id, None).await;
+    let retry = create_attempt(&pool, task_id, Some(original.id)).await;
+
+    assert_eq!(original.retried_from_attempt_id, None);
+    assert_eq!(retry.retried_from_attempt_id, Some(original.id));
+    let reloaded = TaskAttempt::find_by_id(&pool, retry.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(reloaded.retried_from_attempt_id, Some(original.id));
+
+    // Deleting the original keeps the retry
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM task_attempts WHERE id = $1")
+        .bind(original.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let reloaded = TaskAttempt::find_by_id(&pool, retry.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(reloaded.retried_from_attempt_id, None);
+}
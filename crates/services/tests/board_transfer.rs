@@ -0,0 +1,115 @@
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::{insert_project, test_pool};
+use db::models::{
+    project::Project,
+    task::{ImportTask, Task, TaskStatus},
+    task_dependency::TaskDependency,
+};
+use services::services::board_transfer::{
+    BoardTransferError, ImportBoardRequest, export_board, import_board, validate_board,
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// An in-progress task created at `created_at`, so exports have a stable order.
+async fn insert_started_task(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    title: &str,
+    created_at: &str,
+) -> Uuid {
+    let id = Uuid::new_v4();
+    let created_at = DateTime::parse_from_rfc3339(created_at)
+        .unwrap()
+        .with_timezone(&Utc);
+    Task::insert_imported(
+        pool,
+        &ImportTask {
+            project_id,
+            title: title.to_string(),
+            description: None,
+            status: TaskStatus::InProgress,
+            shared_task_id: None,
+            created_at,
+            updated_at: created_at,
+        },
+        id,
+    )
+    .await
+    .unwrap();
+    id
+}
+
+#[tokio::test]
+async fn boards_round_trip_with_new_ids() {
+    let pool = test_pool().await;
+    let project_id = insert_project(&pool, "shop", None).await;
+    let schema = insert_started_task(&pool, project_id, "Add schema", "2025-01-02T03:04:05Z").await;
+    let api = insert_started_task(&pool, project_id, "Build API", "2025-01-03T03:04:05Z").await;
+    TaskDependency::add(&pool, api, schema).await.unwrap();
+
+    let project = Project::find_by_id(&pool, project_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let board = export_board(&pool, &project, false).await.unwrap();
+    assert_eq!(board.tasks.len(), 2);
+
+    let imported = import_board(
+        &pool,
+        &ImportBoardRequest {
+            git_repo_path: "/tmp/shop-copy".to_string(),
+            name: Some("shop copy".to_string()),
+            board: board.clone(),
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(imported.project.name, "shop copy");
+    assert_eq!(imported.tasks, 2);
+
+    let tasks = Task::find_by_project_id_with_attempt_status(&pool, imported.project.id)
+        .await
+        .unwrap();
+    let new_api = tasks.iter().find(|t| t.title == "Build API").unwrap();
+    assert_ne!(new_api.id, api);
+    assert_eq!(new_api.status, TaskStatus::InProgress);
+    let original_api = board.tasks.iter().find(|t| t.id == api).unwrap();
+    assert_eq!(new_api.created_at, original_api.created_at);
+
+    let dependencies = TaskDependency::find_dependencies(&pool, new_api.id)
+        .await
+        .unwrap();
+    assert_eq!(dependencies.len(), 1);
+    assert_eq!(dependencies[0].title, "Add schema");
+    assert_ne!(dependencies[0].id, schema);
+}
+
+#[tokio::test]
+async fn dangling_dependencies_are_reported_by_path() {
+    let pool = test_pool().await;
+    let project_id = insert_project(&pool, "shop", None).await;
+    insert_started_task(&pool, project_id, "Add schema", "2025-01-02T03:04:05Z").await;
+    insert_started_task(&pool, project_id, "Build API", "2025-01-03T03:04:05Z").await;
+    let project = Project::find_by_id(&pool, project_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut board = export_board(&pool, &project, false).await.unwrap();
+    board.tasks[1].depends_on.push(Uuid::new_v4());
+
+    let err = validate_board(&board).unwrap_err();
+    assert!(matches!(
+        &err,
+        BoardTransferError::Invalid { path, .. } if path == "tasks[1].depends_on[0]"
+    ));
+
+    board.tasks[1].depends_on = vec![board.tasks[0].id];
+    board.tasks[0].depends_on = vec![board.tasks[1].id];
+    assert!(matches!(
+        validate_board(&board),
+        Err(BoardTransferError::Invalid { .. })
+    ));
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::{insert_project, insert_shared_task, insert_task, test_pool};
+use db::models::{
+    project::Project,
+    shared_task::SharedTask,
+    task::{Task, TaskStatus},
+};
+use services::services::bulk_tasks::{
+    BulkTaskError, BulkTaskOperation, BulkTaskOutcome, BulkTaskRequest, MAX_BULK_TASKS,
+    apply_bulk_task_operation,
+};
+use uuid::Uuid;
+
+fn request(operation: BulkTaskOperation, task_ids: Vec<Uuid>) -> BulkTaskRequest {
+    BulkTaskRequest {
+        operation,
+        task_ids,
+        status: None,
+        detach_shared: false,
+    }
+}
+
+#[tokio::test]
+async fn status_changes_of_shared_tasks_are_handed_back_for_publishing() {
+    let pool = test_pool().await;
+    let remote_project_id = Uuid::new_v4();
+    let project_id = insert_project(&pool, "shop", Some(remote_project_id)).await;
+    let project = Project::find_by_id(&pool, project_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let local = insert_task(&pool, project.id, "task", None).await;
+    let shared_task_id = insert_shared_task(&pool, remote_project_id, "task", None).await;
+    let shared = insert_task(&pool, project.id, "task", Some(shared_task_id)).await;
+    let missing = Uuid::new_v4();
+
+    let mut set_done = request(BulkTaskOperation::SetStatus, vec![shared, local, missing]);
+    set_done.status = Some(TaskStatus::Done);
+
+    let offline = apply_bulk_task_operation(&pool, &project, &set_done, false)
+        .await
+        .unwrap();
+    let outcomes: Vec<_> = offline
+        .results
+        .iter()
+        .map(|r| (r.task_id, r.outcome))
+        .collect();
+    assert_eq!(
+        outcomes,
+        [
+            (shared, BulkTaskOutcome::Skipped),
+            (local, BulkTaskOutcome::Applied),
+            (missing, BulkTaskOutcome::Skipped),
+        ]
+    );
+    assert!(offline.shared_updates.is_empty());
+
+    let online = apply_bulk_task_operation(&pool, &project, &set_done, true)
+        .await
+        .unwrap();
+    assert_eq!(online.results[0].outcome, BulkTaskOutcome::Applied);
+    assert_eq!(online.results[1].outcome, BulkTaskOutcome::Skipped);
+    assert_eq!(online.shared_updates, [shared]);
+    let shared_task = Task::find_by_id(&pool, shared).await.unwrap().unwrap();
+    assert_eq!(shared_task.status, TaskStatus::Done);
+}
+
+#[tokio::test]
+async fn deleting_shared_tasks_needs_detach_shared() {
+    let pool = test_pool().await;
+    let remote_project_id = Uuid::new_v4();
+    let project_id = insert_project(&pool, "shop", Some(remote_project_id)).await;
+    let project = Project::find_by_id(&pool, project_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let local = insert_task(&pool, project.id, "task", None).await;
+    let shared_task_id = insert_shared_task(&pool, remote_project_id, "task", None).await;
+    let shared = insert_task(&pool, project.id, "task", Some(shared_task_id)).await;
+
+    let mut delete = request(BulkTaskOperation::Delete, vec![local, shared, local]);
+    let changes = apply_bulk_task_operation(&pool, &project, &delete, true)
+        .await
+        .unwrap();
+    assert_eq!(changes.results.len(), 2);
+    assert_eq!(changes.results[0].outcome, BulkTaskOutcome::Applied);
+    assert_eq!(changes.results[1].outcome, BulkTaskOutcome::Skipped);
+    assert!(Task::find_by_id(&pool, local).await.unwrap().is_none());
+    assert!(Task::find_by_id(&pool, shared).await.unwrap().is_some());
+
+    delete.detach_shared = true;
+    delete.task_ids = vec![shared];
+    let changes = apply_bulk_task_operation(&pool, &project, &delete, false)
+        .await
+        .unwrap();
+    assert_eq!(changes.results[0].outcome, BulkTaskOutcome::Applied);
+    assert!(Task::find_by_id(&pool, shared).await.unwrap().is_none());
+    assert!(
+        SharedTask::find_by_id(&pool, shared_task_id)
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    let oversized = request(
+        BulkTaskOperation::Delete,
+        (0..=MAX_BULK_TASKS).map(|_| Uuid::new_v4()).collect(),
+    );
+    assert!(matches!(
+        apply_bulk_task_operation(&pool, &project, &oversized, true).await,
+        Err(BulkTaskError::TooManyTasks)
+    ));
+}
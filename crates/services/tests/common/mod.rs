@@ -0,0 +1,160 @@
+//! Fixtures shared by the integration tests and, as `crate::test_common`, by
+//! the unit tests. Each test binary uses only some of them.
+#![allow(dead_code)]
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    project::{CreateProject, Project},
+    shared_task::{SharedTask, SharedTaskInput},
+    task::{CreateTask, Task, TaskStatus},
+    task_attempt::{CreateTaskAttempt, TaskAttempt},
+};
+use executors::executors::BaseCodingAgent;
+use remote::db::tasks::{SharedTask as RemoteSharedTask, TaskStatus as RemoteTaskStatus};
+use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use uuid::Uuid;
+
+pub async fn test_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::migrate!("../db/migrations").run(&pool).await.unwrap();
+    pool
+}
+
+/// A project with a repo path of its own, optionally linked to a remote
+/// project.
+pub async fn insert_project(
+    pool: &SqlitePool,
+    name: &str,
+    remote_project_id: Option<Uuid>,
+) -> Uuid {
+    let id = Uuid::new_v4();
+    Project::create(
+        pool,
+        &CreateProject {
+            name: name.to_string(),
+            git_repo_path: format!("/tmp/{name}-{id}"),
+            use_existing_repo: true,
+            setup_script: None,
+            dev_script: None,
+            dev_server_url_pattern: None,
+            cleanup_script: None,
+            copy_files: None,
+            verify_script: None,
+            verify_timeout_secs: None,
+            default_executor: None,
+            max_concurrent_executions: None,
+            close_task_on_pr_merge: None,
+        },
+        id,
+    )
+    .await
+    .unwrap();
+    if remote_project_id.is_some() {
+        Project::set_remote_project_id(pool, id, remote_project_id)
+            .await
+            .unwrap();
+    }
+    id
+}
+
+pub async fn insert_task(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    title: &str,
+    shared_task_id: Option<Uuid>,
+) -> Uuid {
+    let id = Uuid::new_v4();
+    Task::create(
+        pool,
+        &CreateTask {
+            shared_task_id,
+            ..CreateTask::from_title_description(project_id, title.to_string(), None)
+        },
+        id,
+    )
+    .await
+    .unwrap();
+    id
+}
+
+pub async fn insert_shared_task(
+    pool: &SqlitePool,
+    remote_project_id: Uuid,
+    title: &str,
+    assignee_user_id: Option<Uuid>,
+) -> Uuid {
+    let id = Uuid::new_v4();
+    SharedTask::upsert(
+        pool,
+        SharedTaskInput {
+            id,
+            remote_project_id,
+            title: title.to_string(),
+            description: None,
+            status: TaskStatus::Todo,
+            assignee_user_id,
+            assignee_first_name: None,
+            assignee_last_name: None,
+            assignee_username: None,
+            version: 1,
+            last_event_seq: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        },
+    )
+    .await
+    .unwrap();
+    id
+}
+
+/// A live task as the remote server returns it, matching what
+/// [`insert_shared_task`] stores: to do, with no description or assignee.
+pub fn remote_task(
+    id: Uuid,
+    project_id: Uuid,
+    title: &str,
+    version: i64,
+    updated_at: DateTime<Utc>,
+) -> RemoteSharedTask {
+    RemoteSharedTask {
+        id,
+        organization_id: Uuid::nil(),
+        project_id,
+        creator_user_id: None,
+        assignee_user_id: None,
+        deleted_by_user_id: None,
+        title: title.to_string(),
+        description: None,
+        status: RemoteTaskStatus::Todo,
+        version,
+        deleted_at: None,
+        shared_at: Some(updated_at),
+        created_at: updated_at,
+        updated_at,
+    }
+}
+
+/// An attempt on a task in a project of its own.
+pub async fn insert_attempt(pool: &SqlitePool) -> Uuid {
+    let project_id = insert_project(pool, "shop", None).await;
+    let task_id = insert_task(pool, project_id, "task", None).await;
+    let id = Uuid::new_v4();
+    TaskAttempt::create(
+        pool,
+        &CreateTaskAttempt {
+            executor: BaseCodingAgent::ClaudeCode,
+            base_branch: "main".to_string(),
+            branch: "vk/task".to_string(),
+            retried_from_attempt_id: None,
+        },
+        id,
+        task_id,
+    )
+    .await
+    .unwrap();
+    id
+}
@@ -0,0 +1,80 @@
+use executors::executors::BaseCodingAgent;
+use serde_json::json;
+use services::services::config::{Config, ConfigLoadError, ThemeMode, load_config_from_file};
+
+/// A config from before `config_version`, `workspace_dir` and
+/// `analytics_enabled` existed, using the old `opencode` executor alias
+fn v1_config() -> String {
+    json!({
+        "theme": "purple",
+        "executor": { "type": "opencode" },
+        "disclaimer_acknowledged": true,
+        "onboarding_acknowledged": true,
+        "github_login_acknowledged": true,
+        "telemetry_acknowledged": false,
+        "sound_alerts": true,
+        "sound_file": "cow-mooing",
+        "push_notifications": false,
+        "editor": { "editor_type": "zed", "custom_command": null },
+        "github": {
+            "pat": null,
+            "token": null,
+            "username": "octocat",
+            "primary_email": null,
+            "default_pr_base": "main"
+        }
+    })
+    .to_string()
+}
+
+#[test]
+fn v1_config_upgrades_to_the_current_schema() {
+    let config = Config::load(&v1_config()).unwrap();
+
+    assert_eq!(config.config_version, "v8");
+    assert_eq!(config.executor_profile.executor, BaseCodingAgent::Opencode);
+    assert!(matches!(config.theme, ThemeMode::System));
+    assert!(config.disclaimer_acknowledged);
+    assert!(config.onboarding_acknowledged);
+    assert!(config.analytics_enabled);
+    assert_eq!(config.workspace_dir, None);
+    assert_eq!(config.github.username.as_deref(), Some("octocat"));
+
+    let again = Config::load(&v1_config()).unwrap();
+    assert_eq!(
+        serde_json::to_value(&config).unwrap(),
+        serde_json::to_value(&again).unwrap()
+    );
+}
+
+#[test]
+fn invalid_or_newer_configs_are_errors_not_defaults() {
+    let mut current = serde_json::to_value(Config::default()).unwrap();
+    current["git_branch_prefx"] = json!("typo");
+    assert!(Config::load(&current.to_string()).is_ok());
+
+    current["theme"] = json!("DARKK");
+    let err = Config::load(&current.to_string()).unwrap_err();
+    assert!(matches!(err, ConfigLoadError::InvalidSchema { .. }));
+    assert_eq!(err.step(), "v8-schema");
+
+    current["config_version"] = json!("v9");
+    let err = Config::load(&current.to_string()).unwrap_err();
+    assert_eq!(err.step(), "v9-unsupported");
+
+    assert_eq!(Config::load("{").unwrap_err().step(), "json");
+}
+
+#[tokio::test]
+async fn unusable_config_is_backed_up_before_defaults_are_used() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    let raw = r#"{"config_version": "v8", "theme": "DARKK"}"#;
+    std::fs::write(&path, raw).unwrap();
+
+    let config = load_config_from_file(&path).await;
+
+    assert_eq!(config.config_version, "v8");
+    let backup = dir.path().join("config.json.v8-schema.bak");
+    assert_eq!(std::fs::read_to_string(backup).unwrap(), raw);
+}
@@ -7,7 +7,9 @@ use std::{
 use git2::{Repository, build::CheckoutBuilder};
 use services::services::{
     git::{DiffTarget, GitCli, GitService},
+    git_forge::{self, GitForgeError},
     github::{GitHubRepoInfo, GitHubServiceError},
+    gitlab::GitLabRepoInfo,
 };
 use tempfile::TempDir;
 use utils::diff::DiffChangeKind;
@@ -217,6 +219,48 @@ fn diff_added_binary_file_has_no_content() {
     assert!(bin.new_content.is_none());
 }
 
+#[test]
+fn diff_summary_counts_lines_and_detects_renames_and_binaries() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    write_file(&repo_path, "notes.txt", "one\ntwo\nthree\nfour\nfive\n");
+    write_file(&repo_path, "edit.txt", "a\nb\n");
+    let s = GitService::new();
+    let _ = s.commit(&repo_path, "base").unwrap();
+    let base_commit = s
+        .get_base_commit(&repo_path, "main", "main")
+        .expect("base commit");
+
+    // Uncommitted: rename, edit and a new binary file
+    fs::rename(repo_path.join("notes.txt"), repo_path.join("docs.txt")).unwrap();
+    write_file(&repo_path, "edit.txt", "a\nc\nd\n");
+    fs::write(repo_path.join("bin.dat"), [0u8, 1, 2, 3]).unwrap();
+
+    let target = || DiffTarget::Worktree {
+        worktree_path: &repo_path,
+        base_commit: &base_commit,
+    };
+    let files = s.get_diff_summary(target(), Some(64 * 1024)).unwrap();
+    let find = |path: &str| files.iter().find(|f| f.path == path).unwrap();
+
+    let renamed = find("docs.txt");
+    assert!(matches!(renamed.change, DiffChangeKind::Renamed));
+    assert_eq!(renamed.old_path.as_deref(), Some("notes.txt"));
+
+    let edited = find("edit.txt");
+    assert_eq!((edited.additions, edited.deletions), (Some(2), Some(1)));
+    assert!(edited.patch.as_deref().unwrap().contains("+c\n+d\n"));
+
+    let binary = find("bin.dat");
+    assert!(binary.binary);
+    assert!(binary.additions.is_none() && binary.patch.is_none());
+
+    // Patches are dropped, not truncated, once the budget runs out
+    let files = s.get_diff_summary(target(), Some(0)).unwrap();
+    let edited = files.iter().find(|f| f.path == "edit.txt").unwrap();
+    assert!(edited.patch.is_none() && edited.patch_omitted);
+}
+
 #[test]
 fn initialize_and_default_branch_and_head_info() {
     let td = TempDir::new().unwrap();
@@ -311,6 +355,80 @@ fn branch_status_ahead_and_behind() {
     assert_eq!((ahead2, behind2), (2, 1));
 }
 
+#[test]
+fn merged_branches_can_be_found_and_deleted() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "base.txt", "base\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+
+    create_branch(&repo_path, "vk/aaaa-merged");
+    create_branch(&repo_path, "vk/bbbb-unmerged");
+    checkout_branch(&repo_path, "vk/bbbb-unmerged");
+    write_file(&repo_path, "wip.txt", "wip\n");
+    let _ = s.commit(&repo_path, "wip").unwrap();
+    checkout_branch(&repo_path, "main");
+
+    let mut branches = s
+        .list_local_branches_with_prefix(&repo_path, "vk/")
+        .unwrap();
+    branches.sort();
+    assert_eq!(branches, ["vk/aaaa-merged", "vk/bbbb-unmerged"]);
+    assert!(
+        s.is_branch_merged_into_head(&repo_path, "vk/aaaa-merged")
+            .unwrap()
+    );
+    assert!(
+        !s.is_branch_merged_into_head(&repo_path, "vk/bbbb-unmerged")
+            .unwrap()
+    );
+
+    s.delete_local_branch(&repo_path, "vk/aaaa-merged").unwrap();
+    assert!(!s.check_branch_exists(&repo_path, "vk/aaaa-merged").unwrap());
+}
+
+#[test]
+fn commits_between_lists_new_commits_and_flags_rewrites() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    let _ = s.commit(&repo_path, "add a").unwrap();
+    let before = s.get_head_info(&repo_path).unwrap().oid;
+
+    write_file(&repo_path, "b.txt", "b\n");
+    let _ = s.commit(&repo_path, "add b").unwrap();
+    write_file(&repo_path, "c.txt", "c\n");
+    let _ = s.commit(&repo_path, "add c").unwrap();
+    let after = s.get_head_info(&repo_path).unwrap().oid;
+
+    let range = s.commits_between(&repo_path, &before, &after, 10).unwrap();
+    let subjects: Vec<_> = range.commits.iter().map(|c| c.subject.as_str()).collect();
+    assert_eq!(subjects, ["add c", "add b"]);
+    assert!(!range.history_rewritten);
+
+    // Amend the last commit: the old HEAD is no longer an ancestor
+    let repo = Repository::open(&repo_path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let amended = head
+        .amend(
+            Some("HEAD"),
+            None,
+            None,
+            None,
+            Some("add c (amended)"),
+            None,
+        )
+        .unwrap();
+    let range = s
+        .commits_between(&repo_path, &after, &amended.to_string(), 10)
+        .unwrap();
+    assert!(range.history_rewritten);
+    assert_eq!(range.commits.len(), 1);
+    assert_eq!(range.commits[0].subject, "add c (amended)");
+}
+
 #[test]
 fn get_all_branches_lists_current_and_others() {
     let td = TempDir::new().unwrap();
@@ -487,6 +605,42 @@ fn github_repo_info_parses_https_and_ssh_urls() {
     }
 }
 
+#[test]
+fn forge_is_picked_from_the_remote_host() {
+    let hosts = vec!["git.example.com".to_string()];
+
+    let forge = git_forge::forge_for_remote_url("git@github.com:owner/repo.git", &hosts).unwrap();
+    assert_eq!(forge.name(), "GitHub");
+
+    let forge =
+        git_forge::forge_for_remote_url("https://gitlab.com/group/sub/repo.git", &hosts).unwrap();
+    assert_eq!((forge.name(), forge.host()), ("GitLab", "gitlab.com"));
+
+    let forge =
+        git_forge::forge_for_remote_url("ssh://git@Git.Example.com:2222/team/repo.git", &hosts)
+            .unwrap();
+    assert_eq!((forge.name(), forge.host()), ("GitLab", "git.example.com"));
+
+    let err = git_forge::forge_for_remote_url("https://bitbucket.org/team/repo.git", &hosts)
+        .err()
+        .unwrap();
+    assert!(matches!(err, GitForgeError::UnsupportedRemote(_)));
+}
+
+#[test]
+fn gitlab_repo_info_keeps_nested_groups() {
+    let info = GitLabRepoInfo::from_remote_url("git@gitlab.com:group/sub/repo.git").unwrap();
+    assert_eq!(info.host, "gitlab.com");
+    assert_eq!(info.project_path, "group/sub/repo");
+
+    let info = GitLabRepoInfo::from_remote_url("https://gitlab.com/group/repo/-/merge_requests/7")
+        .unwrap();
+    assert_eq!(info.project_path, "group/repo");
+    assert_eq!(info.web_url(), "https://gitlab.com/group/repo");
+
+    assert!(GitLabRepoInfo::from_remote_url("https://gitlab.com/repo").is_err());
+}
+
 #[test]
 fn squash_merge_libgit2_sets_author_without_user() {
     // Verify merge_changes (libgit2 path) uses fallback author when no config exists
@@ -0,0 +1,57 @@
+use services::services::log_batch::{LogBatch, LogBatchConfig};
+use tokio::time::{Duration, Instant};
+use utils::log_msg::LogMsg;
+
+#[test]
+fn chatty_output_is_written_in_few_batches() {
+    let config = LogBatchConfig::default();
+    let mut batch = LogBatch::new(config);
+    let mut writes = Vec::new();
+    let mut expected = String::new();
+
+    for i in 0..50_000 {
+        let msg = if i % 10 == 0 {
+            LogMsg::Stderr(format!("warning {i}\n"))
+        } else {
+            LogMsg::Stdout(format!("line {i}\n"))
+        };
+        let line = format!("{}\n", serde_json::to_string(&msg).unwrap());
+        expected.push_str(&line);
+        writes.extend(batch.push(&line));
+    }
+    writes.extend(batch.take());
+
+    // ~1.6MB of JSONL in 8KB batches
+    assert!(writes.len() <= 250, "{} writes", writes.len());
+    assert!(writes.iter().all(|w| w.len() < config.max_batch_bytes + 64));
+    assert_eq!(writes.concat(), expected);
+}
+
+#[test]
+fn line_limit_flushes_small_lines() {
+    let mut batch = LogBatch::new(LogBatchConfig {
+        max_batch_lines: 3,
+        ..LogBatchConfig::default()
+    });
+    assert_eq!(batch.push("a\n"), None);
+    assert_eq!(batch.push("b\n"), None);
+    assert_eq!(batch.push("c\n").as_deref(), Some("a\nb\nc\n"));
+    assert_eq!(batch.take(), None);
+}
+
+#[test]
+fn deadline_follows_first_buffered_line() {
+    let mut batch = LogBatch::new(LogBatchConfig::default());
+    assert_eq!(batch.deadline(), None);
+
+    let before = Instant::now();
+    batch.push("a\n");
+    let deadline = batch.deadline().unwrap();
+    assert!(deadline >= before + Duration::from_millis(250));
+
+    batch.push("b\n");
+    assert_eq!(batch.deadline(), Some(deadline));
+
+    assert_eq!(batch.take().as_deref(), Some("a\nb\n"));
+    assert_eq!(batch.deadline(), None);
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::test_pool;
+use db::models::oauth_handoff::{
+    HandoffProgress, HandoffRedemption, OAUTH_HANDOFF_TTL, OAuthHandoff,
+};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn handoff_is_redeemed_once() {
+    let pool = test_pool().await;
+    let id = Uuid::new_v4();
+    OAuthHandoff::create(&pool, id, "github", "verifier")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        OAuthHandoff::take(&pool, id, Utc::now()).await.unwrap(),
+        HandoffRedemption::Ready {
+            provider: "github".to_string(),
+            app_verifier: "verifier".to_string(),
+        }
+    );
+    assert_eq!(
+        OAuthHandoff::take(&pool, id, Utc::now()).await.unwrap(),
+        HandoffRedemption::AlreadyUsed
+    );
+    assert_eq!(
+        OAuthHandoff::take(&pool, Uuid::new_v4(), Utc::now())
+            .await
+            .unwrap(),
+        HandoffRedemption::Unknown
+    );
+
+    let verifier: Option<String> =
+        sqlx::query_scalar("SELECT app_verifier FROM oauth_handoffs WHERE handoff_id = $1")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(verifier, None);
+}
+
+#[tokio::test]
+async fn stale_handoff_expires() {
+    let pool = test_pool().await;
+    let id = Uuid::new_v4();
+    OAuthHandoff::create(&pool, id, "google", "verifier")
+        .await
+        .unwrap();
+    let later = Utc::now() + OAUTH_HANDOFF_TTL + Duration::seconds(1);
+
+    assert_eq!(
+        OAuthHandoff::progress(&pool, id, later).await.unwrap(),
+        HandoffProgress::Expired
+    );
+    assert_eq!(
+        OAuthHandoff::take(&pool, id, later).await.unwrap(),
+        HandoffRedemption::Expired
+    );
+
+    assert_eq!(OAuthHandoff::purge_expired(&pool, later).await.unwrap(), 1);
+    assert_eq!(
+        OAuthHandoff::progress(&pool, id, later).await.unwrap(),
+        HandoffProgress::Unknown
+    );
+}
+
+#[tokio::test]
+async fn progress_follows_the_redemption() {
+    let pool = test_pool().await;
+    let id = Uuid::new_v4();
+    OAuthHandoff::create(&pool, id, "github", "verifier")
+        .await
+        .unwrap();
+    assert_eq!(
+        OAuthHandoff::progress(&pool, id, Utc::now()).await.unwrap(),
+        HandoffProgress::Pending
+    );
+
+    OAuthHandoff::take(&pool, id, Utc::now()).await.unwrap();
+    assert_eq!(
+        OAuthHandoff::progress(&pool, id, Utc::now()).await.unwrap(),
+        HandoffProgress::Redeeming
+    );
+
+    OAuthHandoff::finish(&pool, id, Some("invalid app code"))
+        .await
+        .unwrap();
+    assert_eq!(
+        OAuthHandoff::progress(&pool, id, Utc::now()).await.unwrap(),
+        HandoffProgress::Failed {
+            error: "invalid app code".to_string()
+        }
+    );
+
+    // Fresh handoffs survive a purge
+    assert_eq!(
+        OAuthHandoff::purge_expired(&pool, Utc::now())
+            .await
+            .unwrap(),
+        0
+    );
+}
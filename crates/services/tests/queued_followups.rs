@@ -0,0 +1,87 @@
+mod common;
+
+use common::{insert_attempt, test_pool};
+use db::models::queued_followup::QueuedFollowUp;
+
+#[tokio::test]
+async fn follow_ups_are_sent_oldest_first() {
+    let pool = test_pool().await;
+    let attempt_id = insert_attempt(&pool).await;
+    let first = QueuedFollowUp::create(&pool, attempt_id, "add tests")
+        .await
+        .unwrap();
+    let second = QueuedFollowUp::create(&pool, attempt_id, "update the docs")
+        .await
+        .unwrap();
+
+    let next = QueuedFollowUp::next_pending(&pool, attempt_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(next.id, first.id);
+
+    assert!(QueuedFollowUp::claim(&pool, first.id).await.unwrap());
+    // Only one caller gets to send it
+    assert!(!QueuedFollowUp::claim(&pool, first.id).await.unwrap());
+
+    let pending = QueuedFollowUp::find_pending_by_task_attempt_id(&pool, attempt_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        pending.iter().map(|f| f.id).collect::<Vec<_>>(),
+        [second.id]
+    );
+    // Sent follow-ups can no longer be removed
+    assert!(
+        !QueuedFollowUp::delete_pending(&pool, first.id, attempt_id)
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn blocked_follow_ups_stay_queued_with_a_reason() {
+    let pool = test_pool().await;
+    let attempt_id = insert_attempt(&pool).await;
+    let other_attempt_id = insert_attempt(&pool).await;
+    let queued = QueuedFollowUp::create(&pool, attempt_id, "retry with logging")
+        .await
+        .unwrap();
+
+    QueuedFollowUp::block(&pool, queued.id, "The last run failed")
+        .await
+        .unwrap();
+    let next = QueuedFollowUp::next_pending(&pool, attempt_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(next.blocked_reason.as_deref(), Some("The last run failed"));
+
+    // Claiming it later clears the reason
+    assert!(QueuedFollowUp::claim(&pool, queued.id).await.unwrap());
+    QueuedFollowUp::block(&pool, queued.id, "Failed to start")
+        .await
+        .unwrap();
+    assert!(QueuedFollowUp::claim(&pool, queued.id).await.unwrap());
+    assert!(
+        QueuedFollowUp::next_pending(&pool, attempt_id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // Removal is scoped to the attempt
+    let other = QueuedFollowUp::create(&pool, other_attempt_id, "other")
+        .await
+        .unwrap();
+    assert!(
+        !QueuedFollowUp::delete_pending(&pool, other.id, attempt_id)
+            .await
+            .unwrap()
+    );
+    assert!(
+        QueuedFollowUp::delete_pending(&pool, other.id, other_attempt_id)
+            .await
+            .unwrap()
+    );
+}
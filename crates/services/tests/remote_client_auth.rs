@@ -0,0 +1,114 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{Duration as ChronoDuration, Utc};
+use serde_json::json;
+use services::services::{
+    auth::AuthContext,
+    oauth_credentials::{Credentials, OAuthCredentials},
+    remote_client::{RemoteClient, RemoteClientError},
+};
+use tempfile::TempDir;
+use tokio::{net::TcpListener, sync::RwLock};
+
+/// An unsigned JWT; the client only reads its expiry.
+fn jwt(label: &str) -> String {
+    let exp = (Utc::now() + ChronoDuration::hours(1)).timestamp();
+    let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256","typ":"JWT"}"#);
+    let claims = URL_SAFE_NO_PAD.encode(json!({ "exp": exp, "label": label }).to_string());
+    format!("{header}.{claims}.sig")
+}
+
+#[derive(Clone)]
+struct MockRemote {
+    valid_token: String,
+    refresh_calls: Arc<AtomicUsize>,
+    /// Answer refreshes with 401, as for a revoked session
+    refresh_rejected: bool,
+}
+
+async fn refresh(State(remote): State<MockRemote>) -> Response {
+    remote.refresh_calls.fetch_add(1, Ordering::SeqCst);
+    // Give concurrent 401s time to pile up behind this refresh
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    if remote.refresh_rejected {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    Json(json!({ "access_token": remote.valid_token, "refresh_token": "refresh-2" }))
+        .into_response()
+}
+
+async fn organizations(State(remote): State<MockRemote>, headers: HeaderMap) -> Response {
+    let expected = format!("Bearer {}", remote.valid_token);
+    match headers.get(AUTHORIZATION) {
+        Some(value) if value == expected.as_str() => {
+            Json(json!({ "organizations": [] })).into_response()
+        }
+        _ => StatusCode::UNAUTHORIZED.into_response(),
+    }
+}
+
+/// A client holding a token the mock server no longer accepts.
+async fn client_with_stale_token(refresh_rejected: bool) -> (RemoteClient, MockRemote, TempDir) {
+    let remote = MockRemote {
+        valid_token: jwt("fresh"),
+        refresh_calls: Arc::new(AtomicUsize::new(0)),
+        refresh_rejected,
+    };
+    let app = Router::new()
+        .route("/v1/tokens/refresh", post(refresh))
+        .route("/v1/organizations", get(organizations))
+        .with_state(remote.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let dir = TempDir::new().unwrap();
+    let oauth = Arc::new(OAuthCredentials::new(dir.path().join("credentials.json")));
+    let auth = AuthContext::new(oauth, Arc::new(RwLock::new(None)));
+    let stale = jwt("stale");
+    auth.save_credentials(&Credentials {
+        expires_at: Some(utils::jwt::extract_expiration(&stale).unwrap()),
+        access_token: Some(stale),
+        refresh_token: "refresh-1".to_string(),
+    })
+    .await
+    .unwrap();
+
+    let client = RemoteClient::new(&base_url, auth).unwrap();
+    (client, remote, dir)
+}
+
+#[tokio::test]
+async fn concurrent_401s_share_one_refresh() {
+    let (client, remote, _dir) = client_with_stale_token(false).await;
+
+    let results = futures::future::join_all((0..5).map(|_| client.list_organizations())).await;
+    for result in results {
+        assert!(result.unwrap().organizations.is_empty());
+    }
+    assert_eq!(remote.refresh_calls.load(Ordering::SeqCst), 1);
+    assert_eq!(client.access_token().await.unwrap(), remote.valid_token);
+}
+
+#[tokio::test]
+async fn rejected_refresh_surfaces_auth() {
+    let (client, remote, _dir) = client_with_stale_token(true).await;
+
+    let err = client.list_organizations().await.unwrap_err();
+    assert!(matches!(err, RemoteClientError::Auth));
+    assert_eq!(remote.refresh_calls.load(Ordering::SeqCst), 1);
+}
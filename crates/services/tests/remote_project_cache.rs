@@ -0,0 +1,47 @@
+mod common;
+
+use common::{insert_project, test_pool};
+use db::models::project::Project;
+use serde_json::json;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn remote_name_and_metadata_are_cached_until_unlink() {
+    let pool = test_pool().await;
+    let remote_project_id = Uuid::new_v4();
+    let id = insert_project(&pool, "shop", Some(remote_project_id)).await;
+    let metadata = json!({ "color": "blue" });
+
+    assert!(
+        Project::update_remote_project_cache(&pool, remote_project_id, "Shop", &metadata)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !Project::update_remote_project_cache(&pool, remote_project_id, "Shop", &metadata)
+            .await
+            .unwrap()
+    );
+    assert!(
+        Project::update_remote_project_cache(&pool, remote_project_id, "Storefront", &metadata)
+            .await
+            .unwrap()
+    );
+    let project = Project::find_by_id(&pool, id).await.unwrap().unwrap();
+    assert_eq!(project.remote_project_name.as_deref(), Some("Storefront"));
+    assert_eq!(project.remote_project_metadata.map(|m| m.0), Some(metadata));
+
+    Project::mark_remote_link_broken(&pool, remote_project_id)
+        .await
+        .unwrap();
+    let project = Project::find_by_id(&pool, id).await.unwrap().unwrap();
+    assert!(project.remote_link_broken_at.is_some());
+
+    Project::set_remote_project_id(&pool, id, None)
+        .await
+        .unwrap();
+    let project = Project::find_by_id(&pool, id).await.unwrap().unwrap();
+    assert_eq!(project.remote_project_name, None);
+    assert!(project.remote_project_metadata.is_none());
+    assert_eq!(project.remote_link_broken_at, None);
+}
@@ -0,0 +1,208 @@
+use std::{fs, path::Path};
+
+use services::services::repo_analysis::{
+    ProjectAnalysis, RepoAnalysisError, SuggestionConfidence, SuggestionKind,
+    VIBE_KANBAN_CONFIG_FILE, analyze_repository,
+};
+use tempfile::TempDir;
+
+fn write(base: &Path, path: &str, contents: &str) {
+    let full_path = base.join(path);
+    if let Some(parent) = full_path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    fs::write(full_path, contents).unwrap();
+}
+
+fn values(analysis: &ProjectAnalysis, kind: SuggestionKind) -> Vec<&str> {
+    analysis
+        .suggestions
+        .iter()
+        .filter(|s| s.kind == kind)
+        .map(|s| s.value.as_str())
+        .collect()
+}
+
+fn id_of(analysis: &ProjectAnalysis, kind: SuggestionKind) -> String {
+    analysis
+        .suggestions
+        .iter()
+        .find(|s| s.kind == kind)
+        .map(|s| s.id.clone())
+        .unwrap()
+}
+
+#[test]
+fn node_repo() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = temp_dir.path();
+    write(
+        repo,
+        "package.json",
+        r#"{
+  "name": "web",
+  "scripts": {
+    "dev": "vite",
+    "build": "vite build",
+    "test": "vitest run"
+  }
+}
+"#,
+    );
+    write(repo, "pnpm-lock.yaml", "lockfileVersion: '9.0'\n");
+    fs::create_dir_all(repo.join("node_modules")).unwrap();
+    fs::create_dir_all(repo.join("dist")).unwrap();
+
+    let analysis = analyze_repository(repo).unwrap();
+
+    assert_eq!(
+        values(&analysis, SuggestionKind::PackageManager),
+        vec!["pnpm"]
+    );
+    assert_eq!(
+        values(&analysis, SuggestionKind::SetupScript),
+        vec!["pnpm install"]
+    );
+    assert_eq!(
+        values(&analysis, SuggestionKind::TestCommand),
+        vec!["pnpm run test"]
+    );
+    assert_eq!(
+        values(&analysis, SuggestionKind::IgnorePattern),
+        vec!["pnpm-lock.yaml", "node_modules/", "dist/"]
+    );
+
+    let dev = analysis
+        .suggestions
+        .iter()
+        .find(|s| s.kind == SuggestionKind::DevScript)
+        .unwrap();
+    assert_eq!(dev.value, "pnpm run dev");
+    assert_eq!(dev.confidence, SuggestionConfidence::High);
+    assert_eq!(dev.evidence[0].path, "package.json");
+    assert_eq!(dev.evidence[0].line, Some(4));
+    assert_eq!(dev.evidence[0].snippet.as_deref(), Some(r#""dev": "vite""#));
+    assert!(!analysis.has_vibe_kanban_config);
+}
+
+#[test]
+fn node_repo_ignores_npm_placeholder_test() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = temp_dir.path();
+    write(
+        repo,
+        "package.json",
+        r#"{ "scripts": { "start": "node index.js", "test": "echo \"Error: no test specified\" && exit 1" } }"#,
+    );
+    write(repo, "package-lock.json", "{}");
+
+    let analysis = analyze_repository(repo).unwrap();
+
+    assert_eq!(
+        values(&analysis, SuggestionKind::SetupScript),
+        vec!["npm ci"]
+    );
+    assert_eq!(
+        values(&analysis, SuggestionKind::DevScript),
+        vec!["npm run start"]
+    );
+    assert!(values(&analysis, SuggestionKind::TestCommand).is_empty());
+}
+
+#[test]
+fn rust_repo() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = temp_dir.path();
+    write(
+        repo,
+        "Cargo.toml",
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2024\"\n",
+    );
+    write(repo, "Cargo.lock", "version = 4\n");
+    write(repo, "src/main.rs", "fn main() {}\n");
+    fs::create_dir_all(repo.join("target")).unwrap();
+
+    let analysis = analyze_repository(repo).unwrap();
+
+    assert_eq!(
+        values(&analysis, SuggestionKind::SetupScript),
+        vec!["cargo fetch"]
+    );
+    assert_eq!(
+        values(&analysis, SuggestionKind::TestCommand),
+        vec!["cargo test"]
+    );
+    assert_eq!(
+        values(&analysis, SuggestionKind::DevScript),
+        vec!["cargo run"]
+    );
+    assert_eq!(
+        values(&analysis, SuggestionKind::IgnorePattern),
+        vec!["Cargo.lock", "target/"]
+    );
+
+    let applied = analysis
+        .resolve(&[
+            id_of(&analysis, SuggestionKind::SetupScript),
+            id_of(&analysis, SuggestionKind::DevScript),
+        ])
+        .unwrap();
+    assert_eq!(applied.setup_script.as_deref(), Some("cargo fetch"));
+    assert_eq!(applied.dev_script.as_deref(), Some("cargo run"));
+}
+
+#[test]
+fn python_repo() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = temp_dir.path();
+    write(repo, "requirements.txt", "django==5.0\npytest==8.2\n");
+    write(repo, "manage.py", "#!/usr/bin/env python\n");
+    write(repo, VIBE_KANBAN_CONFIG_FILE, "[project]\n");
+
+    let analysis = analyze_repository(repo).unwrap();
+
+    assert_eq!(
+        values(&analysis, SuggestionKind::SetupScript),
+        vec!["pip install -r requirements.txt"]
+    );
+    assert_eq!(
+        values(&analysis, SuggestionKind::DevScript),
+        vec!["python manage.py runserver"]
+    );
+
+    let test = analysis
+        .suggestions
+        .iter()
+        .find(|s| s.kind == SuggestionKind::TestCommand)
+        .unwrap();
+    assert_eq!(test.value, "pytest");
+    assert_eq!(test.evidence[0].path, "requirements.txt");
+    assert_eq!(test.evidence[0].line, Some(2));
+
+    assert!(analysis.has_vibe_kanban_config);
+    assert_eq!(
+        values(&analysis, SuggestionKind::ConfigFile),
+        vec![VIBE_KANBAN_CONFIG_FILE]
+    );
+
+    // Informational suggestions cannot be written to the project
+    let config_id = id_of(&analysis, SuggestionKind::ConfigFile);
+    assert!(matches!(
+        analysis.resolve(&[config_id]),
+        Err(RepoAnalysisError::NotApplicable(_))
+    ));
+}
+
+#[test]
+fn empty_repo() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let analysis = analyze_repository(temp_dir.path()).unwrap();
+
+    assert!(analysis.suggestions.is_empty());
+    assert!(!analysis.has_vibe_kanban_config);
+    assert!(matches!(
+        analysis.resolve(&["setup_script:npm ci".to_string()]),
+        Err(RepoAnalysisError::UnknownSuggestion(_))
+    ));
+}
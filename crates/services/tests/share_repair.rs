@@ -0,0 +1,102 @@
+mod common;
+
+use common::{insert_project, insert_shared_task, insert_task, test_pool};
+use db::models::{project::Project, task::Task};
+use services::services::share::repair_shared_task_links;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// A local task pointing at a shared task that is not in the database, as
+/// left behind by restoring an older backup.
+async fn insert_dangling_task(pool: &SqlitePool, project_id: Uuid) -> Uuid {
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(pool)
+        .await
+        .unwrap();
+    let id = insert_task(pool, project_id, "dangling", Some(Uuid::new_v4())).await;
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(pool)
+        .await
+        .unwrap();
+    id
+}
+
+#[tokio::test]
+async fn dry_run_reports_without_changing_links() {
+    let pool = test_pool().await;
+    let remote_project_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    let project_id = insert_project(&pool, "shop", Some(remote_project_id)).await;
+    let project = Project::find_by_id(&pool, project_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let dangling = insert_dangling_task(&pool, project.id).await;
+    let mine = insert_shared_task(&pool, remote_project_id, "shared", Some(user_id)).await;
+    let theirs = insert_shared_task(&pool, remote_project_id, "shared", Some(Uuid::new_v4())).await;
+
+    let report = repair_shared_task_links(&pool, Some(user_id), &project, true)
+        .await
+        .unwrap();
+    assert!(report.dry_run);
+    assert_eq!(report.unlinked_tasks, vec![dangling]);
+    assert_eq!(report.created_for, vec![mine]);
+    assert_eq!(report.skipped, vec![theirs]);
+
+    let task = Task::find_by_id(&pool, dangling).await.unwrap().unwrap();
+    assert!(task.shared_task_id.is_some());
+    assert!(
+        Task::find_by_shared_task_id(&pool, mine)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn repair_unlinks_missing_and_links_existing_shared_tasks() {
+    let pool = test_pool().await;
+    let remote_project_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    let project_id = insert_project(&pool, "shop", Some(remote_project_id)).await;
+    let project = Project::find_by_id(&pool, project_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let dangling = insert_dangling_task(&pool, project.id).await;
+    let mine = insert_shared_task(&pool, remote_project_id, "shared", Some(user_id)).await;
+    insert_shared_task(&pool, remote_project_id, "shared", None).await;
+
+    let report = repair_shared_task_links(&pool, Some(user_id), &project, false)
+        .await
+        .unwrap();
+    assert_eq!(
+        (
+            report.unlinked_count,
+            report.created_count,
+            report.skipped_count
+        ),
+        (1, 1, 1)
+    );
+
+    let task = Task::find_by_id(&pool, dangling).await.unwrap().unwrap();
+    assert_eq!(task.shared_task_id, None);
+    let created = Task::find_by_shared_task_id(&pool, mine)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(created.project_id, project.id);
+
+    // Nothing is left to repair
+    let again = repair_shared_task_links(&pool, Some(user_id), &project, false)
+        .await
+        .unwrap();
+    assert_eq!(
+        (
+            again.unlinked_count,
+            again.created_count,
+            again.skipped_count
+        ),
+        (0, 0, 1)
+    );
+}
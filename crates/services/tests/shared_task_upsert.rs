@@ -0,0 +1,72 @@
+mod common;
+
+use chrono::{DateTime, Duration, Utc};
+use common::test_pool;
+use db::models::{
+    shared_task::{SharedTask, SharedTaskInput, UpsertOutcome},
+    task::TaskStatus,
+};
+use uuid::Uuid;
+
+fn input(id: Uuid, version: i64, updated_at: DateTime<Utc>) -> SharedTaskInput {
+    SharedTaskInput {
+        id,
+        remote_project_id: Uuid::nil(),
+        title: format!("v{version} at {updated_at}"),
+        description: None,
+        status: TaskStatus::Todo,
+        assignee_user_id: None,
+        assignee_first_name: None,
+        assignee_last_name: None,
+        assignee_username: None,
+        version,
+        last_event_seq: Some(version),
+        created_at: updated_at,
+        updated_at,
+    }
+}
+
+#[tokio::test]
+async fn out_of_order_events_keep_the_highest_version() {
+    let pool = test_pool().await;
+    let id = Uuid::new_v4();
+    let t0 = DateTime::parse_from_rfc3339("2025-11-01T10:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+
+    let mut outcomes = Vec::new();
+    for version in [1, 4, 2, 3] {
+        let event = input(id, version, t0 + Duration::minutes(version));
+        outcomes.push(SharedTask::upsert_if_newer(&pool, event).await.unwrap());
+    }
+    assert!(matches!(outcomes[0], UpsertOutcome::Applied(_)));
+    assert!(matches!(outcomes[1], UpsertOutcome::Applied(_)));
+    assert!(matches!(outcomes[2], UpsertOutcome::IgnoredStale));
+    assert!(matches!(outcomes[3], UpsertOutcome::IgnoredStale));
+
+    let stored = SharedTask::find_by_id(&pool, id).await.unwrap().unwrap();
+    assert_eq!(stored.version, 4);
+    assert_eq!(stored.title, input(id, 4, t0 + Duration::minutes(4)).title);
+}
+
+#[tokio::test]
+async fn same_version_prefers_the_later_update() {
+    let pool = test_pool().await;
+    let id = Uuid::new_v4();
+    let t0 = Utc::now();
+    let newer = input(id, 2, t0);
+    let older = input(id, 2, t0 - Duration::seconds(5));
+
+    SharedTask::upsert_if_newer(&pool, newer.clone())
+        .await
+        .unwrap();
+    let outcome = SharedTask::upsert_if_newer(&pool, older).await.unwrap();
+    assert!(matches!(outcome, UpsertOutcome::IgnoredStale));
+    let stored = SharedTask::find_by_id(&pool, id).await.unwrap().unwrap();
+    assert_eq!(stored.title, newer.title);
+
+    // Bulk sync is authoritative and may move the row backwards
+    SharedTask::upsert(&pool, input(id, 1, t0)).await.unwrap();
+    let stored = SharedTask::find_by_id(&pool, id).await.unwrap().unwrap();
+    assert_eq!(stored.version, 1);
+}
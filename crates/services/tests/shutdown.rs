@@ -0,0 +1,41 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use services::services::shutdown::ShutdownState;
+
+#[tokio::test]
+async fn waits_for_log_writers_to_finish() {
+    let state = ShutdownState::default();
+    let written = Arc::new(AtomicBool::new(false));
+    let flag = written.clone();
+    state.spawn_log_writer(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        flag.store(true, Ordering::SeqCst);
+    });
+
+    assert!(state.begin());
+    assert!(state.is_closing());
+    assert!(state.wait_for_log_writers(Duration::from_secs(5)).await);
+    assert!(written.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn gives_up_on_writers_after_the_timeout() {
+    let state = ShutdownState::default();
+    state.spawn_log_writer(std::future::pending());
+
+    assert!(!state.wait_for_log_writers(Duration::from_millis(50)).await);
+}
+
+#[test]
+fn begins_only_once() {
+    let state = ShutdownState::default();
+    assert!(!state.is_closing());
+    assert!(state.begin());
+    assert!(!state.begin());
+}
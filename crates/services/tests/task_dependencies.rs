@@ -0,0 +1,84 @@
+mod common;
+
+use common::{insert_project, insert_task, test_pool};
+use db::models::{
+    task::{Task, TaskStatus, TaskWithAttemptStatus},
+    task_dependency::{TaskDependency, TaskDependencyError},
+};
+
+#[tokio::test]
+async fn cycles_are_rejected_with_the_chain_named() {
+    let pool = test_pool().await;
+    let project = insert_project(&pool, "shop", None).await;
+    let schema = insert_task(&pool, project, "Add schema", None).await;
+    let api = insert_task(&pool, project, "Build API", None).await;
+    let ui = insert_task(&pool, project, "Build UI", None).await;
+
+    TaskDependency::add(&pool, api, schema).await.unwrap();
+    TaskDependency::add(&pool, ui, api).await.unwrap();
+    // Adding it twice is fine
+    TaskDependency::add(&pool, ui, api).await.unwrap();
+
+    let err = TaskDependency::add(&pool, schema, ui).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Dependency would create a cycle: Add schema → Build UI → Build API → Add schema"
+    );
+    assert!(matches!(
+        TaskDependency::add(&pool, ui, ui).await,
+        Err(TaskDependencyError::SelfDependency)
+    ));
+
+    let other_project = insert_project(&pool, "blog", None).await;
+    let post = insert_task(&pool, other_project, "Write post", None).await;
+    assert!(matches!(
+        TaskDependency::add(&pool, post, ui).await,
+        Err(TaskDependencyError::DifferentProject)
+    ));
+}
+
+#[tokio::test]
+async fn tasks_are_blocked_until_dependencies_are_done() {
+    let pool = test_pool().await;
+    let project = insert_project(&pool, "shop", None).await;
+    let schema = insert_task(&pool, project, "Add schema", None).await;
+    let api = insert_task(&pool, project, "Build API", None).await;
+    TaskDependency::add(&pool, api, schema).await.unwrap();
+
+    let blocking = TaskDependency::find_blocking(&pool, api).await.unwrap();
+    assert_eq!(
+        blocking.iter().map(|task| task.id).collect::<Vec<_>>(),
+        [schema]
+    );
+    let blocked = |tasks: Vec<TaskWithAttemptStatus>| {
+        tasks
+            .into_iter()
+            .filter(|task| task.blocked)
+            .map(|task| task.id)
+            .collect::<Vec<_>>()
+    };
+    let tasks = Task::find_by_project_id_with_attempt_status(&pool, project)
+        .await
+        .unwrap();
+    assert_eq!(blocked(tasks), [api]);
+
+    Task::update_status(&pool, schema, TaskStatus::Done)
+        .await
+        .unwrap();
+    assert!(
+        TaskDependency::find_blocking(&pool, api)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    let tasks = Task::find_by_project_id_with_attempt_status(&pool, project)
+        .await
+        .unwrap();
+    assert!(blocked(tasks).is_empty());
+    assert_eq!(
+        TaskDependency::find_dependent_ids(&pool, schema)
+            .await
+            .unwrap(),
+        [api]
+    );
+}
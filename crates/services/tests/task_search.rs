@@ -0,0 +1,109 @@
+mod common;
+
+use common::{insert_project, insert_shared_task, insert_task, test_pool};
+use db::models::{
+    project::Project,
+    task::{Task, TaskStatus},
+    task_search::TaskSearch,
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Edit a task's description the way the task form does.
+async fn set_description(pool: &SqlitePool, task_id: Uuid, description: &str) {
+    let task = Task::find_by_id(pool, task_id).await.unwrap().unwrap();
+    Task::update(
+        pool,
+        task.id,
+        task.project_id,
+        task.title,
+        Some(description.to_string()),
+        task.status,
+        task.parent_task_attempt,
+    )
+    .await
+    .unwrap();
+}
+
+async fn search(pool: &SqlitePool, q: &str, project_id: Option<Uuid>) -> Vec<String> {
+    TaskSearch::search(pool, q, project_id, None, 20, 0)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|hit| hit.title)
+        .collect()
+}
+
+#[tokio::test]
+async fn finds_tasks_across_projects_and_follows_edits() {
+    let pool = test_pool().await;
+    let shop = insert_project(&pool, "shop", None).await;
+    let blog = insert_project(&pool, "blog", None).await;
+    let webhook = insert_task(&pool, shop, "Retry failed deliveries", None).await;
+    set_description(
+        &pool,
+        webhook,
+        "The payment webhook drops events on timeout",
+    )
+    .await;
+    insert_task(&pool, blog, "Payment page copy", None).await;
+
+    assert_eq!(
+        search(&pool, "payment webh", None).await,
+        ["Retry failed deliveries"]
+    );
+    assert_eq!(
+        search(&pool, "payment", Some(blog)).await,
+        ["Payment page copy"]
+    );
+
+    let hits = TaskSearch::search(&pool, "webhook", None, None, 20, 0)
+        .await
+        .unwrap();
+    let highlight = hits[0].highlights[0];
+    let snippet: Vec<u16> = hits[0].snippet.encode_utf16().collect();
+    assert_eq!(
+        String::from_utf16(&snippet[highlight.start as usize..highlight.end as usize]).unwrap(),
+        "webhook"
+    );
+
+    set_description(&pool, webhook, "Stripe callbacks time out").await;
+    assert!(search(&pool, "webhook", None).await.is_empty());
+    assert_eq!(
+        search(&pool, "stripe", None).await,
+        ["Retry failed deliveries"]
+    );
+
+    Project::delete(&pool, shop).await.unwrap();
+    assert!(search(&pool, "stripe", None).await.is_empty());
+}
+
+#[tokio::test]
+async fn shared_tasks_are_flagged_remote_only_until_linked() {
+    let pool = test_pool().await;
+    let remote_project_id = Uuid::new_v4();
+    let project = insert_project(&pool, "shop", Some(remote_project_id)).await;
+    let shared = insert_shared_task(&pool, remote_project_id, "Rotate webhook secret", None).await;
+
+    let hits = TaskSearch::search(&pool, "webhook", None, None, 20, 0)
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].remote_only);
+    assert_eq!(hits[0].task_id, None);
+    assert_eq!(hits[0].project_id, Some(project));
+
+    let local = insert_task(&pool, project, "Rotate webhook secret", Some(shared)).await;
+    let hits = TaskSearch::search(&pool, "webhook", None, None, 20, 0)
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert!(!hits[0].remote_only);
+    assert_eq!(hits[0].task_id, Some(local));
+    assert_eq!(hits[0].shared_task_id, Some(shared));
+
+    let done = TaskSearch::search(&pool, "webhook", None, Some(TaskStatus::Done), 20, 0)
+        .await
+        .unwrap();
+    assert!(done.is_empty());
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{insert_project, insert_shared_task, insert_task, test_pool};
+use db::models::{shared_task::SharedTask, task::Task};
+use services::services::task_trash::purge_expired_tasks;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn deleted_task_moves_to_the_trash_and_back() {
+    let pool = test_pool().await;
+    let project_id = insert_project(&pool, "shop", Some(Uuid::new_v4())).await;
+    let task_id = insert_task(&pool, project_id, "task", None).await;
+
+    assert_eq!(Task::soft_delete(&pool, task_id).await.unwrap(), 1);
+    assert_eq!(Task::soft_delete(&pool, task_id).await.unwrap(), 0);
+    assert!(Task::find_by_id(&pool, task_id).await.unwrap().is_none());
+    assert!(
+        Task::find_by_project_id_with_attempt_status(&pool, project_id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    let trash = Task::find_deleted_by_project_id(&pool, project_id)
+        .await
+        .unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].task.id, task_id);
+
+    let restored = Task::restore(&pool, task_id).await.unwrap().unwrap();
+    assert_eq!(restored.id, task_id);
+    assert!(Task::restore(&pool, task_id).await.unwrap().is_none());
+    assert!(
+        Task::find_deleted_by_project_id(&pool, project_id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn shared_task_is_detached_and_relinked() {
+    let pool = test_pool().await;
+    let remote_project_id = Uuid::new_v4();
+    let project_id = insert_project(&pool, "shop", Some(remote_project_id)).await;
+    let shared_task_id = insert_shared_task(&pool, remote_project_id, "task", None).await;
+    let task_id = insert_task(&pool, project_id, "task", Some(shared_task_id)).await;
+    let gone_shared_task_id = insert_shared_task(&pool, remote_project_id, "task", None).await;
+    let orphan_id = insert_task(&pool, project_id, "task", Some(gone_shared_task_id)).await;
+
+    Task::soft_delete(&pool, task_id).await.unwrap();
+    Task::soft_delete(&pool, orphan_id).await.unwrap();
+    let trash = Task::find_deleted_by_project_id(&pool, project_id)
+        .await
+        .unwrap();
+    assert!(
+        trash
+            .iter()
+            .all(|trashed| trashed.task.shared_task_id.is_none())
+    );
+    assert!(
+        SharedTask::find_by_id(&pool, shared_task_id)
+            .await
+            .unwrap()
+            .is_some()
+    );
+    assert!(
+        Task::find_by_shared_task_id(&pool, shared_task_id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    SharedTask::remove(&pool, gone_shared_task_id)
+        .await
+        .unwrap();
+    let restored = Task::restore(&pool, task_id).await.unwrap().unwrap();
+    assert_eq!(restored.shared_task_id, Some(shared_task_id));
+    let orphan = Task::restore(&pool, orphan_id).await.unwrap().unwrap();
+    assert_eq!(orphan.shared_task_id, None);
+}
+
+#[tokio::test]
+async fn purge_only_removes_tasks_past_retention() {
+    let pool = test_pool().await;
+    let project_id = insert_project(&pool, "shop", Some(Uuid::new_v4())).await;
+    let old = insert_task(&pool, project_id, "task", None).await;
+    let recent = insert_task(&pool, project_id, "task", None).await;
+    let kept = insert_task(&pool, project_id, "task", None).await;
+    Task::soft_delete(&pool, old).await.unwrap();
+    Task::soft_delete(&pool, recent).await.unwrap();
+    sqlx::query("UPDATE tasks SET deleted_at = datetime('now', '-40 days') WHERE id = $1")
+        .bind(old)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    purge_expired_tasks(&pool, Duration::days(30), Utc::now())
+        .await
+        .unwrap();
+
+    let remaining: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM tasks ORDER BY rowid")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, vec![recent, kept]);
+}
@@ -0,0 +1,128 @@
+use std::{path::Path, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use db::models::{
+    project::{Project, SharedTaskAutoCreate},
+    task_attempt::VerifyStatus,
+};
+use executors::{
+    actions::{Executable, script::ScriptContext},
+    approvals::NoopExecutorApprovalService,
+    env::ExecutionEnv,
+    executors::ExecutorExitResult,
+};
+use services::services::verify::{
+    DEFAULT_VERIFY_TIMEOUT_SECS, VERIFY_OUTPUT_TAIL_LINES, output_tail, verify_request,
+    verify_status,
+};
+use tempfile::TempDir;
+use utils::log_msg::LogMsg;
+use uuid::Uuid;
+
+fn project(verify_script: Option<&str>, verify_timeout_secs: Option<i64>) -> Project {
+    Project {
+        id: Uuid::new_v4(),
+        name: "verify".to_string(),
+        git_repo_path: "/tmp/verify".into(),
+        setup_script: None,
+        dev_script: None,
+        dev_server_url_pattern: None,
+        cleanup_script: None,
+        copy_files: None,
+        verify_script: verify_script.map(str::to_string),
+        verify_timeout_secs,
+        default_executor: None,
+        max_concurrent_executions: None,
+        close_task_on_pr_merge: true,
+        shared_task_auto_create: SharedTaskAutoCreate::AssignedToMe,
+        remote_project_id: None,
+        remote_project_name: None,
+        remote_project_metadata: None,
+        remote_link_broken_at: None,
+        archived_at: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+/// Run the project's verify script the way the container does and classify
+/// the result.
+async fn run_verify(dir: &Path, project: &Project) -> VerifyStatus {
+    let request = verify_request(project).unwrap();
+    let mut spawned = request
+        .spawn(
+            dir,
+            Arc::new(NoopExecutorApprovalService),
+            &ExecutionEnv::default(),
+        )
+        .await
+        .unwrap();
+    let exit_signal = spawned.exit_signal.take().unwrap();
+
+    tokio::select! {
+        result = exit_signal => {
+            assert!(matches!(result, Ok(ExecutorExitResult::TimedOut)));
+            spawned.child.kill().await.unwrap();
+            verify_status(None, true)
+        }
+        status = spawned.child.wait() => {
+            let code = status.unwrap().code().map(i64::from);
+            verify_status(code, false)
+        }
+    }
+}
+
+#[test]
+fn no_verify_script_means_no_verify_phase() {
+    assert!(verify_request(&project(None, None)).is_none());
+    assert!(verify_request(&project(Some("  "), None)).is_none());
+}
+
+#[test]
+fn verify_request_uses_project_timeout() {
+    let request = verify_request(&project(Some("cargo test"), Some(30))).unwrap();
+    assert_eq!(request.context, ScriptContext::VerifyScript);
+    assert_eq!(request.timeout_secs, Some(30));
+
+    let request = verify_request(&project(Some("cargo test"), Some(0))).unwrap();
+    assert_eq!(request.timeout_secs, Some(DEFAULT_VERIFY_TIMEOUT_SECS));
+}
+
+#[tokio::test]
+async fn passing_script() {
+    let dir = TempDir::new().unwrap();
+    let status = run_verify(dir.path(), &project(Some("echo ok"), Some(30))).await;
+    assert_eq!(status, VerifyStatus::Passed);
+}
+
+#[tokio::test]
+async fn failing_script() {
+    let dir = TempDir::new().unwrap();
+    let status = run_verify(
+        dir.path(),
+        &project(Some("echo 'test result: FAILED' >&2; exit 101"), Some(30)),
+    )
+    .await;
+    assert_eq!(status, VerifyStatus::Failed);
+}
+
+#[tokio::test]
+async fn timing_out_script() {
+    let dir = TempDir::new().unwrap();
+    let started = std::time::Instant::now();
+    let status = run_verify(dir.path(), &project(Some("sleep 30"), Some(1))).await;
+    assert_eq!(status, VerifyStatus::TimedOut);
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn output_tail_keeps_last_lines_in_order() {
+    let mut history = vec![LogMsg::Stdout("compiling\n".to_string())];
+    history.extend((0..VERIFY_OUTPUT_TAIL_LINES).map(|i| LogMsg::Stdout(format!("line {i}\n"))));
+    history.push(LogMsg::Stderr("error: 1 test failed\n".to_string()));
+
+    let tail = output_tail(history);
+    assert!(!tail.contains("compiling"));
+    assert!(!tail.contains("line 0\n"));
+    assert!(tail.ends_with("line 99\nerror: 1 test failed\n"));
+}
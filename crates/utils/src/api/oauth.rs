@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 use uuid::Uuid;
@@ -77,3 +78,27 @@ pub struct StatusResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub degraded: Option<bool>,
 }
+
+/// One of the caller's sign-ins to the remote service.
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+pub struct AuthSessionSummary {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    /// Day the session was last used; `null` if only on the day it was created
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub user_agent: Option<String>,
+    /// The session the request was made with
+    pub current: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+pub struct ListSessionsResponse {
+    pub sessions: Vec<AuthSessionSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+pub struct RevokeSessionResponse {
+    /// The revoked session was the one the request was made with, i.e. the
+    /// caller is now signed out
+    pub current: bool,
+}
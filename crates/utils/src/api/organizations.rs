@@ -34,6 +34,9 @@ pub struct Organization {
     pub name: String,
     pub slug: String,
     pub is_personal: bool,
+    /// Invitee email domains allowed to join; empty allows any. Ignored for
+    /// personal organizations.
+    pub allowed_email_domains: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -80,6 +83,10 @@ pub struct CreateOrganizationResponse {
 #[ts(export)]
 pub struct UpdateOrganizationRequest {
     pub name: String,
+    /// Replaces the allowed invitee email domains when present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub allowed_email_domains: Option<Vec<String>>,
 }
 
 // Invitation types
@@ -180,3 +187,43 @@ pub struct UpdateMemberRoleResponse {
     pub user_id: Uuid,
     pub role: MemberRole,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type, TS)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[ts(export)]
+pub enum AuditAction {
+    MemberAdded,
+    MemberRemoved,
+    MemberRoleChanged,
+    InvitationCreated,
+    InvitationRevoked,
+    InvitationAccepted,
+    OrganizationRenamed,
+    OrganizationDeleted,
+}
+
+/// A change to an organization's membership or settings.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct AuditLogEntry {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    /// `None` once the acting user has been deleted
+    pub actor_user_id: Option<Uuid>,
+    pub action: AuditAction,
+    pub target_user_id: Option<Uuid>,
+    /// Action-specific context such as the previous and new role
+    #[ts(type = "Record<string, unknown>")]
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ListAuditLogResponse {
+    /// Newest first
+    pub entries: Vec<AuditLogEntry>,
+    /// Pass as `before` to fetch the next page; `None` on the last page
+    pub next_cursor: Option<DateTime<Utc>>,
+}
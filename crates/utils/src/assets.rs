@@ -36,6 +36,10 @@ pub fn credentials_path() -> std::path::PathBuf {
     asset_dir().join("credentials.json")
 }
 
+pub fn device_id_path() -> std::path::PathBuf {
+    asset_dir().join("device_id")
+}
+
 #[derive(RustEmbed)]
 #[folder = "../../assets/sounds"]
 pub struct SoundAssets;
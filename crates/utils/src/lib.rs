@@ -11,8 +11,10 @@ pub mod git;
 pub mod jwt;
 pub mod log_msg;
 pub mod msg_store;
+pub mod network;
 pub mod path;
 pub mod port_file;
+pub mod request_id;
 pub mod response;
 pub mod sentry;
 pub mod shell;
@@ -0,0 +1,292 @@
+//! Proxy and extra trusted certificates for connections to the shared task
+//! server, used by both its HTTP client and the activity websocket.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
+use rustls::{
+    ClientConfig, RootCertStore,
+    pki_types::{CertificateDer, pem::PemObject},
+};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use url::Url;
+
+/// Proxy for all connections to the shared task server. Without it the
+/// usual `https_proxy` / `http_proxy` variables apply.
+pub const PROXY_URL_ENV: &str = "VK_SHARED_PROXY_URL";
+/// PEM files of certificates to trust in addition to the built-in roots,
+/// separated like `PATH` entries
+pub const EXTRA_ROOT_CERTS_ENV: &str = "VK_SHARED_EXTRA_ROOT_CERTS";
+
+const PROXY_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_PROXY_RESPONSE_HEADER: usize = 8 * 1024;
+
+#[derive(Debug, Error)]
+pub enum NetworkConfigError {
+    #[error("invalid proxy URL `{0}`: expected http://host:port")]
+    InvalidProxyUrl(String),
+    #[error("failed to read certificate file {path}: {source}")]
+    ReadCertificate {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid PEM certificate in {path}: {message}")]
+    InvalidCertificate { path: PathBuf, message: String },
+    #[error("no PEM certificates found in {0}")]
+    NoCertificates(PathBuf),
+    #[error("failed to set up the HTTP client with the proxy and certificates: {0}")]
+    Client(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NetworkConfig {
+    pub proxy_url: Option<Url>,
+    extra_root_certs: Vec<CertificateDer<'static>>,
+}
+
+impl NetworkConfig {
+    /// Reads every certificate file up front, so a bad path fails here
+    /// rather than as a TLS error on first use. Only plain `http` proxies are
+    /// supported, since the websocket tunnels through them with `CONNECT`.
+    pub fn new(
+        proxy_url: Option<&str>,
+        extra_root_certs: &[PathBuf],
+    ) -> Result<Self, NetworkConfigError> {
+        let proxy_url = proxy_url
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(|url| {
+                Url::parse(url)
+                    .ok()
+                    .filter(is_http_proxy)
+                    .ok_or_else(|| NetworkConfigError::InvalidProxyUrl(url.to_string()))
+            })
+            .transpose()?;
+        let mut certs = Vec::new();
+        for path in extra_root_certs {
+            certs.extend(read_certificates(path)?);
+        }
+        Ok(Self {
+            proxy_url,
+            extra_root_certs: certs,
+        })
+    }
+
+    pub fn from_env() -> Result<Self, NetworkConfigError> {
+        let proxy_url = std::env::var(PROXY_URL_ENV).ok();
+        let cert_paths = std::env::var_os(EXTRA_ROOT_CERTS_ENV)
+            .map(|paths| {
+                std::env::split_paths(&paths)
+                    .filter(|path| !path.as_os_str().is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        Self::new(proxy_url.as_deref(), &cert_paths)
+    }
+
+    pub fn apply_to_reqwest(
+        &self,
+        mut builder: reqwest::ClientBuilder,
+    ) -> Result<reqwest::ClientBuilder, reqwest::Error> {
+        if let Some(proxy_url) = &self.proxy_url {
+            builder = builder.proxy(reqwest::Proxy::all(proxy_url.as_str())?);
+        }
+        for cert in &self.extra_root_certs {
+            builder = builder.add_root_certificate(reqwest::Certificate::from_der(cert.as_ref())?);
+        }
+        Ok(builder)
+    }
+
+    /// TLS settings trusting the built-in web roots and the extra
+    /// certificates.
+    pub fn rustls_config(&self) -> Arc<ClientConfig> {
+        let mut roots = RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        let (_added, ignored) = roots.add_parsable_certificates(self.extra_root_certs.clone());
+        if ignored > 0 {
+            tracing::warn!(ignored, "some extra root certificates could not be used");
+        }
+        let config =
+            ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .expect("the ring provider supports the default protocol versions")
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+        Arc::new(config)
+    }
+
+    /// The proxy to tunnel a connection to `target` through: the configured
+    /// one, else `https_proxy` / `http_proxy` by scheme, unless `no_proxy`
+    /// covers the host. Environment proxies other than `http` are ignored.
+    pub fn proxy_for(&self, target: &Url) -> Option<Url> {
+        if let Some(proxy_url) = &self.proxy_url {
+            return Some(proxy_url.clone());
+        }
+        let host = target.host_str()?;
+        if env_var_any(&["no_proxy", "NO_PROXY"])
+            .is_some_and(|no_proxy| no_proxy_matches(&no_proxy, host))
+        {
+            return None;
+        }
+        let names: &[&str] = match target.scheme() {
+            "wss" | "https" => &["https_proxy", "HTTPS_PROXY", "all_proxy", "ALL_PROXY"],
+            _ => &["http_proxy", "HTTP_PROXY", "all_proxy", "ALL_PROXY"],
+        };
+        env_var_any(names)
+            .and_then(|url| Url::parse(&url).ok())
+            .filter(is_http_proxy)
+    }
+}
+
+fn read_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>, NetworkConfigError> {
+    let pem = std::fs::read(path).map_err(|source| NetworkConfigError::ReadCertificate {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let certs = CertificateDer::pem_slice_iter(&pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| NetworkConfigError::InvalidCertificate {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })?;
+    if certs.is_empty() {
+        return Err(NetworkConfigError::NoCertificates(path.to_path_buf()));
+    }
+    Ok(certs)
+}
+
+fn is_http_proxy(url: &Url) -> bool {
+    url.scheme() == "http" && url.host_str().is_some()
+}
+
+fn env_var_any(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .find_map(|name| std::env::var(name).ok())
+        .filter(|value| !value.trim().is_empty())
+}
+
+fn no_proxy_matches(no_proxy: &str, host: &str) -> bool {
+    no_proxy
+        .split(',')
+        .map(|entry| entry.trim().trim_start_matches('.'))
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| entry == "*" || host == entry || host.ends_with(&format!(".{entry}")))
+}
+
+/// Open a tunnel to `host:port` through an HTTP proxy with `CONNECT`.
+pub async fn connect_via_proxy(proxy: &Url, host: &str, port: u16) -> std::io::Result<TcpStream> {
+    let proxy_host = proxy
+        .host_str()
+        .ok_or_else(|| std::io::Error::other(format!("proxy URL {proxy} has no host")))?;
+    let proxy_port = proxy.port_or_known_default().unwrap_or(80);
+
+    tokio::time::timeout(PROXY_CONNECT_TIMEOUT, async {
+        let mut stream = TcpStream::connect((proxy_host, proxy_port)).await?;
+        let mut request = format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n");
+        if !proxy.username().is_empty() {
+            let credentials = format!(
+                "{}:{}",
+                percent_decode(proxy.username()),
+                percent_decode(proxy.password().unwrap_or_default())
+            );
+            request.push_str(&format!(
+                "Proxy-Authorization: Basic {}\r\n",
+                BASE64_STANDARD.encode(credentials)
+            ));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await?;
+
+        // Read byte by byte so nothing past the proxy's headers is consumed
+        let mut header = Vec::new();
+        while !header.ends_with(b"\r\n\r\n") {
+            if header.len() >= MAX_PROXY_RESPONSE_HEADER {
+                return Err(std::io::Error::other("proxy response header too large"));
+            }
+            let byte = stream.read_u8().await?;
+            header.push(byte);
+        }
+        let status_line = String::from_utf8_lossy(&header);
+        let status_line = status_line.lines().next().unwrap_or_default();
+        if status_line.split_whitespace().nth(1) != Some("200") {
+            return Err(std::io::Error::other(format!(
+                "proxy refused CONNECT to {host}:{port}: {status_line}"
+            )));
+        }
+        Ok(stream)
+    })
+    .await
+    .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "proxy CONNECT timed out"))?
+}
+
+fn percent_decode(value: &str) -> String {
+    // Form decoding would turn a literal `+` into a space
+    let value = value.replace('+', "%2B");
+    url::form_urlencoded::parse(format!("v={value}").as_bytes())
+        .next()
+        .map(|(_, decoded)| decoded.into_owned())
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_certificate_file_is_reported() {
+        let err = NetworkConfig::new(None, &[PathBuf::from("/nonexistent/ca.pem")]).unwrap_err();
+        assert!(matches!(err, NetworkConfigError::ReadCertificate { .. }));
+        assert!(err.to_string().contains("/nonexistent/ca.pem"));
+    }
+
+    #[test]
+    fn file_without_certificates_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("empty.pem");
+        std::fs::write(&path, "not a certificate").unwrap();
+
+        let err = NetworkConfig::new(None, &[path]).unwrap_err();
+        assert!(matches!(err, NetworkConfigError::NoCertificates(_)));
+    }
+
+    #[test]
+    fn configured_proxy_applies_to_every_host() {
+        let config = NetworkConfig::new(Some("http://proxy.internal:3128"), &[]).unwrap();
+        let target = Url::parse("wss://tasks.example.com/v1/ws").unwrap();
+        assert_eq!(
+            config.proxy_for(&target).unwrap().as_str(),
+            "http://proxy.internal:3128/"
+        );
+    }
+
+    #[test]
+    fn proxy_schemes_other_than_http_are_rejected() {
+        for url in [
+            "https://proxy.internal:3128",
+            "socks5://proxy.internal:1080",
+        ] {
+            let err = NetworkConfig::new(Some(url), &[]).unwrap_err();
+            assert!(matches!(err, NetworkConfigError::InvalidProxyUrl(_)));
+        }
+    }
+
+    #[test]
+    fn no_proxy_matches_hosts_and_subdomains() {
+        assert!(no_proxy_matches(
+            "localhost, .example.com",
+            "tasks.example.com"
+        ));
+        assert!(no_proxy_matches("example.com", "example.com"));
+        assert!(no_proxy_matches("*", "anything"));
+        assert!(!no_proxy_matches("example.com", "notexample.com"));
+    }
+}
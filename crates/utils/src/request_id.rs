@@ -0,0 +1,23 @@
+//! Id of the API request being handled, so errors and outbound calls made
+//! while handling it can be correlated with the request's logs.
+
+use std::future::Future;
+
+/// Header carrying the request id, both on our responses and on calls to
+/// the remote server
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: String;
+}
+
+/// Run `f` as part of handling the request `id`.
+pub async fn scope<F: Future>(id: String, f: F) -> F::Output {
+    CURRENT_REQUEST_ID.scope(id, f).await
+}
+
+/// Id of the request handled by the current task, if any. Work spawned onto
+/// other tasks does not inherit it.
+pub fn current() -> Option<String> {
+    CURRENT_REQUEST_ID.try_with(Clone::clone).ok()
+}
@@ -1,12 +1,18 @@
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
+use crate::request_id;
+
 #[derive(Debug, Serialize, Deserialize, TS)]
 pub struct ApiResponse<T, E = T> {
     success: bool,
     data: Option<T>,
     error_data: Option<E>,
     message: Option<String>,
+    /// Id of the failed request, to find it in the server logs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    request_id: Option<String>,
 }
 
 impl<T, E> ApiResponse<T, E> {
@@ -17,6 +23,7 @@ impl<T, E> ApiResponse<T, E> {
             data: Some(data),
             message: None,
             error_data: None,
+            request_id: None,
         }
     }
 
@@ -27,6 +34,7 @@ impl<T, E> ApiResponse<T, E> {
             data: None,
             message: Some(message.to_string()),
             error_data: None,
+            request_id: request_id::current(),
         }
     }
     /// Creates an error response, with no `data`, no `message`, but with arbitrary `error_data`.
@@ -36,6 +44,18 @@ impl<T, E> ApiResponse<T, E> {
             data: None,
             error_data: Some(data),
             message: None,
+            request_id: request_id::current(),
+        }
+    }
+
+    /// Creates an error response with both a `message` and `error_data`.
+    pub fn error_with_message_and_data(message: &str, data: E) -> Self {
+        ApiResponse {
+            success: false,
+            data: None,
+            error_data: Some(data),
+            message: Some(message.to_string()),
+            request_id: request_id::current(),
         }
     }
 
@@ -53,4 +73,9 @@ impl<T, E> ApiResponse<T, E> {
     pub fn message(&self) -> Option<&str> {
         self.message.as_deref()
     }
+
+    /// Returns the id of the request that failed, if known.
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
 }
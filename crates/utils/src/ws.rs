@@ -1,15 +1,27 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use axum::http::{self, HeaderName, HeaderValue};
 use futures::future::BoxFuture;
 use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tokio::sync::{mpsc, watch};
+use tokio::{
+    net::TcpStream,
+    sync::{mpsc, oneshot, watch},
+};
 use tokio_tungstenite::{
-    connect_async,
+    Connector, MaybeTlsStream, WebSocketStream, client_async_tls_with_config,
+    connect_async_tls_with_config,
     tungstenite::{client::IntoClientRequest, protocol::Message},
 };
 use url::Url;
+use uuid::Uuid;
+
+use crate::network::{NetworkConfig, connect_via_proxy};
 
 /// Interval between authentication refresh probes for websocket connections.
 pub const WS_AUTH_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
@@ -19,6 +31,13 @@ pub const WS_TOKEN_EXPIRY_GRACE: Duration = Duration::from_secs(120);
 pub const WS_MAX_DELAY_BETWEEN_CATCHUP_AND_WS: Duration = WS_TOKEN_EXPIRY_GRACE;
 /// Maximum backlog accepted before forcing clients to do a full bulk sync.
 pub const WS_BULK_SYNC_THRESHOLD: u32 = 500;
+/// Websocket protocol version clients send in the `protocol` query parameter.
+/// From version 2 a client bulk syncs in place when told `resync-required`;
+/// older clients get an error and reconnect.
+pub const WS_PROTOCOL_VERSION: u32 = 2;
+/// Top-level field holding the id a server echoes back in its `ack` message
+/// for frames sent with [`WsClient::send_with_ack`].
+pub const WS_CORRELATION_ID_FIELD: &str = "correlation_id";
 
 pub type HeaderFuture = BoxFuture<'static, WsResult<Vec<(HeaderName, HeaderValue)>>>;
 pub type HeaderFactory = Arc<dyn Fn() -> HeaderFuture + Send + Sync>;
@@ -48,6 +67,12 @@ pub enum WsError {
 
     #[error("share authentication missing or expired")]
     MissingAuth,
+
+    #[error("no acknowledgement received within {0:?}")]
+    AckTimeout(Duration),
+
+    #[error("connection closed before the message was acknowledged")]
+    AckCancelled,
 }
 
 pub type WsResult<T> = std::result::Result<T, WsError>;
@@ -58,19 +83,71 @@ pub trait WsHandler: Send + Sync + 'static {
     async fn handle_message(&mut self, msg: Message) -> WsResult<()>;
 
     /// Called when the socket is closed (either remote closed or error).
+    /// With a reconnect policy, only once the client stops re-dialing.
     async fn on_close(&mut self) -> WsResult<()>;
+
+    /// Called before each re-dial when `WsConfig::reconnect` is set.
+    /// `attempt` counts from 1 and resets once a connection succeeds.
+    async fn on_reconnect(&mut self, _attempt: u32) -> WsResult<()> {
+        Ok(())
+    }
 }
 
 pub struct WsConfig {
     pub url: Url,
     pub ping_interval: Option<Duration>,
     pub header_factory: Option<HeaderFactory>,
+    /// Re-dial dropped connections instead of exiting. `None` connects once.
+    pub reconnect: Option<ReconnectPolicy>,
+    /// Proxy and extra trusted roots for the connection
+    pub network: NetworkConfig,
+}
+
+/// How `run_ws_client` retries when a connection fails or drops.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// Consecutive failed attempts before giving up; `None` retries forever
+    pub max_retries: Option<u32>,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Fraction of each delay added or removed at random, between 0 and 1
+    pub jitter: f64,
 }
 
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: Some(10),
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.2,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Exponential delay before the given 1-based attempt, capped at
+    /// `max_delay` and then jittered.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .base_delay
+            .checked_mul(1 << exponent)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let unit = (Uuid::new_v4().as_u128() % 1000) as f64 / 1000.0;
+        delay.mul_f64(1.0 - jitter + 2.0 * jitter * unit)
+    }
+}
+
+type PendingAcks = Arc<Mutex<HashMap<Uuid, oneshot::Sender<()>>>>;
+
 #[derive(Clone)]
 pub struct WsClient {
     msg_tx: mpsc::UnboundedSender<Message>,
     cancelation_token: watch::Sender<()>,
+    pending_acks: PendingAcks,
 }
 
 impl WsClient {
@@ -80,6 +157,43 @@ impl WsClient {
             .map_err(|e| WsError::Send(format!("WebSocket send error: {e}")))
     }
 
+    /// Send `msg` as a JSON object tagged with a fresh correlation id and wait
+    /// for the server's `ack` carrying the same id. Acks may arrive in any
+    /// order; they are consumed here and never reach the handler.
+    pub async fn send_with_ack<T: Serialize>(&self, msg: &T, timeout: Duration) -> WsResult<()> {
+        let mut frame =
+            serde_json::to_value(msg).map_err(|e| WsError::Send(format!("serialize: {e}")))?;
+        let Some(object) = frame.as_object_mut() else {
+            return Err(WsError::Send(
+                "only JSON objects can carry a correlation id".to_string(),
+            ));
+        };
+        let correlation_id = Uuid::new_v4();
+        object.insert(
+            WS_CORRELATION_ID_FIELD.to_string(),
+            correlation_id.to_string().into(),
+        );
+
+        let (ack_tx, ack_rx) = oneshot::channel();
+        self.pending_acks
+            .lock()
+            .unwrap()
+            .insert(correlation_id, ack_tx);
+        if let Err(err) = self.send(Message::Text(frame.to_string().into())) {
+            self.pending_acks.lock().unwrap().remove(&correlation_id);
+            return Err(err);
+        }
+
+        match tokio::time::timeout(timeout, ack_rx).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(_)) => Err(WsError::AckCancelled),
+            Err(_) => {
+                self.pending_acks.lock().unwrap().remove(&correlation_id);
+                Err(WsError::AckTimeout(timeout))
+            }
+        }
+    }
+
     pub fn close(&self) -> WsResult<()> {
         self.cancelation_token
             .send(())
@@ -93,6 +207,11 @@ impl WsClient {
 
 /// Launches a WebSocket connection with read/write tasks.
 /// Returns a `WsClient` which you can use to send messages or request shutdown.
+///
+/// Without a [`ReconnectPolicy`] the task exits when the connection ends. With
+/// one it re-dials after connect failures and dropped streams, and calls
+/// `on_close` only once it gives up or `close()` is requested. Handler errors
+/// always end the client.
 pub async fn run_ws_client<H>(mut handler: H, config: WsConfig) -> WsResult<WsClient>
 where
     H: WsHandler,
@@ -100,111 +219,215 @@ where
     let (msg_tx, mut msg_rx) = mpsc::unbounded_channel();
     let (cancel_tx, cancel_rx) = watch::channel(());
     let task_tx = msg_tx.clone();
+    let pending_acks = PendingAcks::default();
+    let task_pending_acks = pending_acks.clone();
 
     tokio::spawn(async move {
-        tracing::debug!(url = %config.url, "WebSocket connecting");
-        let request = match build_request(&config).await {
-            Ok(req) => req,
-            Err(err) => {
-                tracing::error!(?err, "failed to build websocket request");
-                return;
-            }
-        };
-
-        match connect_async(request).await {
-            Ok((ws_stream, _resp)) => {
-                tracing::info!("WebSocket connected");
-
-                let (mut ws_sink, mut ws_stream) = ws_stream.split();
-
-                let ping_task = if let Some(interval) = config.ping_interval {
-                    let mut intv = tokio::time::interval(interval);
-                    let mut cancel_rx2 = cancel_rx.clone();
-                    let ping_tx2 = task_tx.clone();
-                    Some(tokio::spawn(async move {
-                        loop {
-                            tokio::select! {
-                                _ = intv.tick() => {
-                                    if ping_tx2.send(Message::Ping(Vec::new().into())).is_err() { break; }
-                                }
-                                _ = cancel_rx2.changed() => { break; }
-                            }
-                        }
-                    }))
-                } else {
-                    None
-                };
-
-                loop {
-                    let mut cancel_rx2 = cancel_rx.clone();
-                    tokio::select! {
-                        maybe = msg_rx.recv() => {
-                            match maybe {
-                                Some(msg) => {
-                                    if let Err(err) = ws_sink.send(msg).await {
-                                        tracing::error!("WebSocket send failed: {:?}", err);
-                                        break;
-                                    }
-                                }
-                                None => {
-                                    tracing::debug!("WebSocket msg_rx closed");
-                                    break;
-                                }
-                            }
-                        }
-
-                        incoming = ws_stream.next() => {
-                            match incoming {
-                                Some(Ok(msg)) => {
-                                    if let Err(err) = handler.handle_message(msg).await {
-                                        tracing::error!("WsHandler failed: {:?}", err);
-                                        break;
-                                    }
-                                }
-                                Some(Err(err)) => {
-                                    tracing::error!("WebSocket stream error: {:?}", err);
-                                    break;
-                                }
-                                None => {
-                                    tracing::debug!("WebSocket stream ended");
-                                    break;
-                                }
-                            }
-                        }
-
-                        _ = cancel_rx2.changed() => {
-                            tracing::debug!("WebSocket shutdown requested");
-                            break;
-                        }
-                    }
-                }
-
-                if let Err(err) = handler.on_close().await {
-                    tracing::error!("WsHandler on_close failed: {:?}", err);
+        let mut attempt = 0;
+        loop {
+            let end = run_session(
+                &mut handler,
+                &config,
+                &mut msg_rx,
+                &task_tx,
+                &cancel_rx,
+                &task_pending_acks,
+            )
+            .await;
+            let Some(policy) = &config.reconnect else {
+                // Preserve the one-shot behaviour: no `on_close` if we never connected
+                if end != SessionEnd::ConnectFailed {
+                    close_handler(&mut handler).await;
                 }
+                break;
+            };
+            if end == SessionEnd::Closed {
+                close_handler(&mut handler).await;
+                break;
+            }
 
-                if let Err(err) = ws_sink.close().await {
-                    tracing::error!("WebSocket close failed: {:?}", err);
-                }
+            if end == SessionEnd::Dropped {
+                attempt = 0;
+            }
+            attempt += 1;
+            if policy.max_retries.is_some_and(|max| attempt > max) {
+                tracing::warn!(attempt, "WebSocket reconnect attempts exhausted");
+                close_handler(&mut handler).await;
+                break;
+            }
 
-                if let Some(task) = ping_task {
-                    task.abort();
+            let delay = policy.delay(attempt);
+            tracing::info!(attempt, ?delay, "WebSocket reconnecting");
+            let mut cancel_rx2 = cancel_rx.clone();
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = cancel_rx2.changed() => {
+                    tracing::debug!("WebSocket shutdown requested while reconnecting");
+                    close_handler(&mut handler).await;
+                    break;
                 }
             }
-            Err(err) => {
-                tracing::error!("WebSocket connect error: {:?}", err);
+            if let Err(err) = handler.on_reconnect(attempt).await {
+                tracing::error!("WsHandler on_reconnect failed: {:?}", err);
+                close_handler(&mut handler).await;
+                break;
             }
         }
 
+        // Fail anything still waiting for an ack
+        task_pending_acks.lock().unwrap().clear();
         tracing::info!("WebSocket client task exiting");
     });
 
     Ok(WsClient {
         msg_tx,
         cancelation_token: cancel_tx,
+        pending_acks,
     })
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionEnd {
+    ConnectFailed,
+    /// The stream ended or errored after connecting
+    Dropped,
+    /// Shutdown was requested or the handler failed
+    Closed,
+}
+
+async fn run_session<H>(
+    handler: &mut H,
+    config: &WsConfig,
+    msg_rx: &mut mpsc::UnboundedReceiver<Message>,
+    task_tx: &mpsc::UnboundedSender<Message>,
+    cancel_rx: &watch::Receiver<()>,
+    pending_acks: &PendingAcks,
+) -> SessionEnd
+where
+    H: WsHandler,
+{
+    tracing::debug!(url = %config.url, "WebSocket connecting");
+    let request = match build_request(config).await {
+        Ok(req) => req,
+        Err(err) => {
+            tracing::error!(?err, "failed to build websocket request");
+            return SessionEnd::ConnectFailed;
+        }
+    };
+
+    let ws_stream = match connect(request, config).await {
+        Ok(ws_stream) => ws_stream,
+        Err(err) => {
+            tracing::error!("WebSocket connect error: {:?}", err);
+            return SessionEnd::ConnectFailed;
+        }
+    };
+    tracing::info!("WebSocket connected");
+
+    let (mut ws_sink, mut ws_stream) = ws_stream.split();
+
+    let ping_task = if let Some(interval) = config.ping_interval {
+        let mut intv = tokio::time::interval(interval);
+        let mut cancel_rx2 = cancel_rx.clone();
+        let ping_tx2 = task_tx.clone();
+        Some(tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = intv.tick() => {
+                        if ping_tx2.send(Message::Ping(Vec::new().into())).is_err() { break; }
+                    }
+                    _ = cancel_rx2.changed() => { break; }
+                }
+            }
+        }))
+    } else {
+        None
+    };
+
+    let end = loop {
+        let mut cancel_rx2 = cancel_rx.clone();
+        tokio::select! {
+            maybe = msg_rx.recv() => {
+                match maybe {
+                    Some(msg) => {
+                        if let Err(err) = ws_sink.send(msg).await {
+                            tracing::error!("WebSocket send failed: {:?}", err);
+                            break SessionEnd::Dropped;
+                        }
+                    }
+                    None => {
+                        tracing::debug!("WebSocket msg_rx closed");
+                        break SessionEnd::Closed;
+                    }
+                }
+            }
+
+            incoming = ws_stream.next() => {
+                match incoming {
+                    Some(Ok(msg)) => {
+                        if let Some(ack_tx) = take_pending_ack(pending_acks, &msg) {
+                            let _ = ack_tx.send(());
+                        } else if let Err(err) = handler.handle_message(msg).await {
+                            tracing::error!("WsHandler failed: {:?}", err);
+                            break SessionEnd::Closed;
+                        }
+                    }
+                    Some(Err(err)) => {
+                        tracing::error!("WebSocket stream error: {:?}", err);
+                        break SessionEnd::Dropped;
+                    }
+                    None => {
+                        tracing::debug!("WebSocket stream ended");
+                        break SessionEnd::Dropped;
+                    }
+                }
+            }
+
+            _ = cancel_rx2.changed() => {
+                tracing::debug!("WebSocket shutdown requested");
+                break SessionEnd::Closed;
+            }
+        }
+    };
+
+    if let Err(err) = ws_sink.close().await {
+        tracing::debug!("WebSocket close failed: {:?}", err);
+    }
+
+    if let Some(task) = ping_task {
+        task.abort();
+    }
+
+    end
+}
+
+/// The server's `{"type": "ack", "data": {"correlation_id": ...}}` reply.
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "data")]
+enum AckFrame {
+    #[serde(rename = "ack")]
+    Ack { correlation_id: Uuid },
+}
+
+fn take_pending_ack(pending_acks: &PendingAcks, msg: &Message) -> Option<oneshot::Sender<()>> {
+    let Message::Text(text) = msg else {
+        return None;
+    };
+    if !text.contains(WS_CORRELATION_ID_FIELD) {
+        return None;
+    }
+    let Ok(AckFrame::Ack { correlation_id }) = serde_json::from_str(text) else {
+        return None;
+    };
+    pending_acks.lock().unwrap().remove(&correlation_id)
+}
+
+async fn close_handler<H: WsHandler>(handler: &mut H) {
+    if let Err(err) = handler.on_close().await {
+        tracing::error!("WsHandler on_close failed: {:?}", err);
+    }
+}
+
 async fn build_request(config: &WsConfig) -> WsResult<http::Request<()>> {
     let mut request = config.url.clone().into_client_request()?;
     if let Some(factory) = &config.header_factory {
@@ -217,6 +440,25 @@ async fn build_request(config: &WsConfig) -> WsResult<http::Request<()>> {
     Ok(request)
 }
 
+async fn connect(
+    request: http::Request<()>,
+    config: &WsConfig,
+) -> WsResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    let connector = Some(Connector::Rustls(config.network.rustls_config()));
+    let Some(proxy) = config.network.proxy_for(&config.url) else {
+        let (ws_stream, _resp) =
+            connect_async_tls_with_config(request, None, false, connector).await?;
+        return Ok(ws_stream);
+    };
+
+    let host = config.url.host_str().unwrap_or_default();
+    let port = config.url.port_or_known_default().unwrap_or(443);
+    tracing::debug!(proxy = %proxy, "WebSocket connecting through proxy");
+    let stream = connect_via_proxy(&proxy, host, port).await?;
+    let (ws_stream, _resp) = client_async_tls_with_config(request, stream, None, connector).await?;
+    Ok(ws_stream)
+}
+
 pub fn derive_ws_url(mut base: Url) -> Result<Url, url::ParseError> {
     match base.scheme() {
         "https" => base.set_scheme("wss").unwrap(),
@@ -227,3 +469,275 @@ pub fn derive_ws_url(mut base: Url) -> Result<Url, url::ParseError> {
     }
     Ok(base)
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::accept_async;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Text(String),
+        Reconnect(u32),
+        Close,
+    }
+
+    struct RecordingHandler(mpsc::UnboundedSender<Event>);
+
+    #[async_trait::async_trait]
+    impl WsHandler for RecordingHandler {
+        async fn handle_message(&mut self, msg: Message) -> WsResult<()> {
+            if let Message::Text(text) = msg {
+                let _ = self.0.send(Event::Text(text.to_string()));
+            }
+            Ok(())
+        }
+
+        async fn on_close(&mut self) -> WsResult<()> {
+            let _ = self.0.send(Event::Close);
+            Ok(())
+        }
+
+        async fn on_reconnect(&mut self, attempt: u32) -> WsResult<()> {
+            let _ = self.0.send(Event::Reconnect(attempt));
+            Ok(())
+        }
+    }
+
+    /// Greets each connection with its number, drops the first `drops`
+    /// connections straight after and echoes on the rest.
+    async fn flaky_echo_server(drops: usize) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("ws://{}", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(async move {
+            for n in 1.. {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut ws = accept_async(stream).await.unwrap();
+                ws.send(Message::Text(format!("conn {n}").into()))
+                    .await
+                    .unwrap();
+                if n <= drops {
+                    continue;
+                }
+                tokio::spawn(async move {
+                    while let Some(Ok(msg)) = ws.next().await {
+                        if msg.is_text() && ws.send(msg).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        url
+    }
+
+    /// Acks frames in batches of `batch`, newest first.
+    async fn reverse_ack_server(batch: usize) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("ws://{}", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(stream).await.unwrap();
+            let mut ids = Vec::new();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let frame: serde_json::Value = serde_json::from_str(&text).unwrap();
+                ids.push(frame[WS_CORRELATION_ID_FIELD].clone());
+                if ids.len() < batch {
+                    continue;
+                }
+                for id in ids.drain(..).rev() {
+                    let ack = serde_json::json!({"type": "ack", "data": {"correlation_id": id}});
+                    ws.send(Message::Text(ack.to_string().into()))
+                        .await
+                        .unwrap();
+                }
+            }
+        });
+        url
+    }
+
+    fn fast_policy(max_retries: u32) -> ReconnectPolicy {
+        ReconnectPolicy {
+            max_retries: Some(max_retries),
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(40),
+            jitter: 0.0,
+        }
+    }
+
+    async fn next(events: &mut mpsc::UnboundedReceiver<Event>) -> Event {
+        tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("timed out waiting for websocket event")
+            .unwrap()
+    }
+
+    async fn connect(
+        url: Url,
+        reconnect: Option<ReconnectPolicy>,
+    ) -> (WsClient, mpsc::UnboundedReceiver<Event>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let config = WsConfig {
+            url,
+            ping_interval: None,
+            header_factory: None,
+            reconnect,
+            network: NetworkConfig::default(),
+        };
+        let client = run_ws_client(RecordingHandler(tx), config).await.unwrap();
+        (client, rx)
+    }
+
+    #[tokio::test]
+    async fn redials_dropped_connections_until_closed() {
+        let url = flaky_echo_server(2).await;
+        let (client, mut events) = connect(url, Some(fast_policy(3))).await;
+
+        let mut seen = Vec::new();
+        while seen.last() != Some(&Event::Text("conn 3".into())) {
+            seen.push(next(&mut events).await);
+        }
+        // Each successful connection resets the attempt count
+        assert_eq!(
+            seen,
+            [
+                Event::Text("conn 1".into()),
+                Event::Reconnect(1),
+                Event::Text("conn 2".into()),
+                Event::Reconnect(1),
+                Event::Text("conn 3".into()),
+            ]
+        );
+
+        client.send(Message::Text("ping".into())).unwrap();
+        assert_eq!(next(&mut events).await, Event::Text("ping".into()));
+
+        client.close().unwrap();
+        assert_eq!(next(&mut events).await, Event::Close);
+    }
+
+    #[tokio::test]
+    async fn closes_once_retries_are_exhausted() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("ws://{}", listener.local_addr().unwrap())).unwrap();
+        drop(listener);
+
+        let (_client, mut events) = connect(url, Some(fast_policy(2))).await;
+        assert_eq!(next(&mut events).await, Event::Reconnect(1));
+        assert_eq!(next(&mut events).await, Event::Reconnect(2));
+        assert_eq!(next(&mut events).await, Event::Close);
+    }
+
+    #[tokio::test]
+    async fn connects_once_without_a_policy() {
+        let url = flaky_echo_server(1).await;
+        let (_client, mut events) = connect(url, None).await;
+        assert_eq!(next(&mut events).await, Event::Text("conn 1".into()));
+        assert_eq!(next(&mut events).await, Event::Close);
+        // The handler is dropped when the task exits
+        assert_eq!(events.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn acks_resolve_out_of_order() {
+        let url = reverse_ack_server(2).await;
+        let (client, mut events) = connect(url, None).await;
+
+        let timeout = Duration::from_secs(5);
+        let first = serde_json::json!({"type": "ack", "data": {"cursor": 1}});
+        let second = serde_json::json!({"type": "ack", "data": {"cursor": 2}});
+        let (first, second) = tokio::join!(
+            client.send_with_ack(&first, timeout),
+            client.send_with_ack(&second, timeout)
+        );
+        first.unwrap();
+        second.unwrap();
+        // Matched acks are not passed on to the handler
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn unacknowledged_send_times_out() {
+        let url = flaky_echo_server(0).await;
+        let (client, mut events) = connect(url, None).await;
+        assert_eq!(next(&mut events).await, Event::Text("conn 1".into()));
+
+        let err = client
+            .send_with_ack(
+                &serde_json::json!({"type": "ping"}),
+                Duration::from_millis(50),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, WsError::AckTimeout(_)));
+        assert!(client.pending_acks.lock().unwrap().is_empty());
+        // The echoed frame is not an ack, so the handler sees it
+        assert!(matches!(next(&mut events).await, Event::Text(text) if text.contains("ping")));
+    }
+
+    #[test]
+    fn delay_grows_to_the_cap() {
+        let policy = ReconnectPolicy {
+            jitter: 0.0,
+            ..ReconnectPolicy::default()
+        };
+        let delays: Vec<u64> = (1..=7).map(|n| policy.delay(n).as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 30, 30]);
+    }
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    #[tokio::test]
+    async fn trusts_extra_root_certificates() {
+        use rustls::pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject};
+
+        let certs = CertificateDer::pem_file_iter(fixture("localhost-cert.pem"))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let key = PrivateKeyDer::from_pem_file(fixture("localhost-key.pem")).unwrap();
+        let server_config = rustls::ServerConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .unwrap();
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(server_config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let Ok(tls) = acceptor.accept(stream).await else {
+                    continue;
+                };
+                let mut ws = accept_async(tls).await.unwrap();
+                ws.send(Message::Text("hello".into())).await.unwrap();
+            }
+        });
+        let url = Url::parse(&format!("wss://localhost:{port}")).unwrap();
+
+        // Rejected with only the built-in roots
+        let (_client, mut events) = connect(url.clone(), None).await;
+        assert_eq!(next(&mut events).await, Event::Close);
+
+        let (tx, mut events) = mpsc::unbounded_channel();
+        let config = WsConfig {
+            url,
+            ping_interval: None,
+            header_factory: None,
+            reconnect: None,
+            network: NetworkConfig::new(None, &[fixture("localhost-cert.pem")]).unwrap(),
+        };
+        let _client = run_ws_client(RecordingHandler(tx), config).await.unwrap();
+        assert_eq!(next(&mut events).await, Event::Text("hello".into()));
+    }
+}
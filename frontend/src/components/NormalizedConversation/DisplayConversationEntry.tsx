@@ -14,6 +14,7 @@ import FileChangeRenderer from './FileChangeRenderer';
 import { useExpandable } from '@/stores/useExpandableStore';
 import {
   AlertCircle,
+  ArrowRightLeft,
   Bot,
   Brain,
   CheckSquare,
@@ -26,6 +27,7 @@ import {
   Search,
   Settings,
   Terminal,
+  Trash2,
   User,
 } from 'lucide-react';
 import RawLogText from '../common/RawLogText';
@@ -87,6 +89,10 @@ const getEntryIcon = (entryType: NormalizedEntryType) => {
       return <Eye className={iconSize} />;
     } else if (action_type.action === 'file_edit') {
       return <Edit className={iconSize} />;
+    } else if (action_type.action === 'file_delete') {
+      return <Trash2 className={iconSize} />;
+    } else if (action_type.action === 'file_move') {
+      return <ArrowRightLeft className={iconSize} />;
     } else if (action_type.action === 'command_run') {
       return <Terminal className={iconSize} />;
     } else if (action_type.action === 'search') {
@@ -13,6 +13,7 @@ import NiceModal, { useModal } from '@ebay/nice-modal-react';
 import { useState, useRef, useEffect } from 'react';
 import { useAuthMutations } from '@/hooks/auth/useAuthMutations';
 import { useAuthStatus } from '@/hooks/auth/useAuthStatus';
+import { useHandoffStatus } from '@/hooks/auth/useHandoffStatus';
 import { useUserSystem } from '@/components/ConfigProvider';
 import type { ProfileResponse } from 'shared/types';
 import { useTranslation } from 'react-i18next';
@@ -33,6 +34,7 @@ const OAuthDialogImpl = NiceModal.create<NoProps>(() => {
   const [state, setState] = useState<OAuthState>({ type: 'select' });
   const popupRef = useRef<Window | null>(null);
   const [isPolling, setIsPolling] = useState(false);
+  const [handoffId, setHandoffId] = useState<string | null>(null);
 
   // Auth mutations hook
   const { initHandoff } = useAuthMutations({
@@ -50,6 +52,7 @@ const OAuthDialogImpl = NiceModal.create<NoProps>(() => {
       );
 
       // Start polling
+      setHandoffId(data.handoff_id);
       setIsPolling(true);
     },
     onInitError: (error) => {
@@ -79,6 +82,39 @@ const OAuthDialogImpl = NiceModal.create<NoProps>(() => {
     }
   }, [isStatusError, isPolling]);
 
+  // The handoff reports failures the popup may not be able to show, and
+  // completion when the browser refuses to let the popup close itself
+  const { data: handoffData } = useHandoffStatus(isPolling ? handoffId : null);
+
+  useEffect(() => {
+    if (!isPolling || !handoffData) return;
+
+    const fail = (message: string) => {
+      setIsPolling(false);
+      if (popupRef.current && !popupRef.current.closed) {
+        popupRef.current.close();
+      }
+      setState({ type: 'error', message });
+    };
+
+    switch (handoffData.status) {
+      case 'completed':
+        if (popupRef.current && !popupRef.current.closed) {
+          popupRef.current.close();
+        }
+        break;
+      case 'failed':
+        fail(handoffData.error || 'OAuth authorization failed');
+        break;
+      case 'expired':
+        fail('The sign-in request expired. Please try again.');
+        break;
+      case 'unknown':
+        fail('The sign-in request was not found. Please try again.');
+        break;
+    }
+  }, [handoffData, isPolling]);
+
   // Monitor status changes
   useEffect(() => {
     if (!isPolling || !statusData) return;
@@ -124,6 +160,7 @@ const OAuthDialogImpl = NiceModal.create<NoProps>(() => {
 
   const handleClose = () => {
     setIsPolling(false);
+    setHandoffId(null);
     if (popupRef.current && !popupRef.current.closed) {
       popupRef.current.close();
     }
@@ -134,6 +171,7 @@ const OAuthDialogImpl = NiceModal.create<NoProps>(() => {
 
   const handleBack = () => {
     setIsPolling(false);
+    setHandoffId(null);
     if (popupRef.current && !popupRef.current.closed) {
       popupRef.current.close();
     }
@@ -7,7 +7,11 @@ import {
   DialogHeader,
   DialogTitle,
 } from '@/components/ui/dialog';
-import { ProjectFormFields } from '@/components/projects/ProjectFormFields';
+import {
+  ProjectFormFields,
+  type RepoMode,
+} from '@/components/projects/ProjectFormFields';
+import ProcessLogsViewer from '@/components/tasks/TaskDetails/ProcessLogsViewer';
 import { CreateProject } from 'shared/types';
 import { generateProjectNameFromPath } from '@/utils/string';
 import NiceModal, { useModal } from '@ebay/nice-modal-react';
@@ -25,11 +29,15 @@ const ProjectFormDialogImpl = NiceModal.create<ProjectFormDialogProps>(() => {
   const [name, setName] = useState('');
   const [gitRepoPath, setGitRepoPath] = useState('');
   const [error, setError] = useState('');
-  const [repoMode, setRepoMode] = useState<'existing' | 'new'>('existing');
+  const [repoMode, setRepoMode] = useState<RepoMode>('existing');
   const [parentPath, setParentPath] = useState('');
   const [folderName, setFolderName] = useState('');
+  const [repoUrl, setRepoUrl] = useState('');
+  const [setupScript, setSetupScript] = useState('');
+  // Keys the clone progress log while an import runs
+  const [importId, setImportId] = useState<string | null>(null);
 
-  const { createProject } = useProjectMutations({
+  const { createProject, importProject } = useProjectMutations({
     onCreateSuccess: () => {
       modal.resolve('saved' as ProjectFormDialogResult);
       modal.hide();
@@ -59,8 +67,14 @@ const ProjectFormDialogImpl = NiceModal.create<ProjectFormDialogProps>(() => {
       use_existing_repo: true,
       setup_script: null,
       dev_script: null,
+      dev_server_url_pattern: null,
       cleanup_script: null,
       copy_files: null,
+      verify_script: null,
+      verify_timeout_secs: null,
+      default_executor: null,
+      max_concurrent_executions: null,
+      close_task_on_pr_merge: null,
     };
 
     createProject.mutate(createData);
@@ -70,6 +84,28 @@ const ProjectFormDialogImpl = NiceModal.create<ProjectFormDialogProps>(() => {
     e.preventDefault();
     setError('');
 
+    if (repoMode === 'clone') {
+      const repoName = repoUrl
+        .trim()
+        .replace(/\/+$/, '')
+        .replace(/\.git$/, '')
+        .split(/[/:]/)
+        .pop();
+      const effectiveParentPath = parentPath.trim();
+      const destination = effectiveParentPath
+        ? `${effectiveParentPath}/${repoName}`.replace(/\/+/g, '/')
+        : repoName || '';
+      const id = crypto.randomUUID();
+      setImportId(id);
+      importProject.mutate({
+        import_id: id,
+        repo_url: repoUrl.trim(),
+        destination_dir: destination,
+        setup_script: setupScript.trim() || null,
+      });
+      return;
+    }
+
     let finalGitRepoPath = gitRepoPath;
     if (repoMode === 'new') {
       const effectiveParentPath = parentPath.trim();
@@ -89,8 +125,14 @@ const ProjectFormDialogImpl = NiceModal.create<ProjectFormDialogProps>(() => {
       use_existing_repo: repoMode === 'existing',
       setup_script: null,
       dev_script: null,
+      dev_server_url_pattern: null,
       cleanup_script: null,
       copy_files: null,
+      verify_script: null,
+      verify_timeout_secs: null,
+      default_executor: null,
+      max_concurrent_executions: null,
+      close_task_on_pr_merge: null,
     };
 
     createProject.mutate(createData);
@@ -102,6 +144,9 @@ const ProjectFormDialogImpl = NiceModal.create<ProjectFormDialogProps>(() => {
     setGitRepoPath('');
     setParentPath('');
     setFolderName('');
+    setRepoUrl('');
+    setSetupScript('');
+    setImportId(null);
     setError('');
 
     modal.resolve('canceled' as ProjectFormDialogResult);
@@ -128,6 +173,8 @@ const ProjectFormDialogImpl = NiceModal.create<ProjectFormDialogProps>(() => {
               isEditing={false}
               repoMode={repoMode}
               setRepoMode={setRepoMode}
+              repoUrl={repoUrl}
+              setRepoUrl={setRepoUrl}
               gitRepoPath={gitRepoPath}
               handleGitRepoPathChange={handleGitRepoPathChange}
               parentPath={parentPath}
@@ -135,8 +182,8 @@ const ProjectFormDialogImpl = NiceModal.create<ProjectFormDialogProps>(() => {
               setFolderName={setFolderName}
               setName={setName}
               name={name}
-              setupScript=""
-              setSetupScript={() => {}}
+              setupScript={setupScript}
+              setSetupScript={setSetupScript}
               devScript=""
               setDevScript={() => {}}
               cleanupScript=""
@@ -157,6 +204,24 @@ const ProjectFormDialogImpl = NiceModal.create<ProjectFormDialogProps>(() => {
                 {createProject.isPending ? 'Creating...' : 'Create Project'}
               </Button>
             )}
+            {repoMode === 'clone' && (
+              <>
+                {importId && (
+                  <div className="h-48 border rounded-md overflow-hidden">
+                    <ProcessLogsViewer processId={importId} />
+                  </div>
+                )}
+                <Button
+                  type="submit"
+                  disabled={importProject.isPending || !repoUrl.trim()}
+                  className="w-full"
+                >
+                  {importProject.isPending
+                    ? 'Cloning...'
+                    : 'Clone and Create Project'}
+                </Button>
+              </>
+            )}
           </form>
         </div>
       </DialogContent>
@@ -9,6 +9,7 @@ import {
 import { Label } from '@radix-ui/react-label';
 import { Textarea } from '@/components/ui/textarea.tsx';
 import { Button } from '@/components/ui/button';
+import { Checkbox } from '@/components/ui/checkbox';
 import { Input } from '@/components/ui/input';
 import { Alert, AlertDescription, AlertTitle } from '@/components/ui/alert';
 import BranchSelector from '@/components/tasks/BranchSelector';
@@ -48,6 +49,8 @@ const CreatePRDialogImpl = NiceModal.create<CreatePRDialogProps>(
     const { environment } = useUserSystem();
     const [prTitle, setPrTitle] = useState('');
     const [prBody, setPrBody] = useState('');
+    const [generateBody, setGenerateBody] = useState(false);
+    const [draft, setDraft] = useState(false);
     const [prBaseBranch, setPrBaseBranch] = useState('');
     const [creatingPR, setCreatingPR] = useState(false);
     const [error, setError] = useState<string | null>(null);
@@ -136,11 +139,15 @@ const CreatePRDialogImpl = NiceModal.create<CreatePRDialogProps>(
         title: prTitle,
         body: prBody || null,
         target_branch: prBaseBranch || null,
+        generate_body: generateBody,
+        draft,
       });
 
       if (result.success) {
         setPrTitle('');
         setPrBody('');
+        setGenerateBody(false);
+        setDraft(false);
         setPrBaseBranch('');
         setCreatingPR(false);
         modal.hide();
@@ -177,6 +184,19 @@ const CreatePRDialogImpl = NiceModal.create<CreatePRDialogProps>(
             setError(ui.variant ? null : ui.message);
           }
           return;
+        } else if (
+          result.error.type === 'gitlab_cli_not_installed' ||
+          result.error.type === 'gitlab_cli_not_logged_in'
+        ) {
+          setError(
+            t(
+              result.error.type === 'gitlab_cli_not_logged_in'
+                ? 'createPrDialog.errors.gitlabCliNotLoggedIn'
+                : 'createPrDialog.errors.gitlabCliNotInstalled'
+            )
+          );
+          setGhCliHelp(null);
+          return;
         } else if (
           result.error.type === 'git_cli_not_installed' ||
           result.error.type === 'git_cli_not_logged_in'
@@ -212,6 +232,8 @@ const CreatePRDialogImpl = NiceModal.create<CreatePRDialogProps>(
       projectId,
       prBaseBranch,
       prBody,
+      generateBody,
+      draft,
       prTitle,
       modal,
       isMacEnvironment,
@@ -223,6 +245,8 @@ const CreatePRDialogImpl = NiceModal.create<CreatePRDialogProps>(
       // Reset form to empty state
       setPrTitle('');
       setPrBody('');
+      setGenerateBody(false);
+      setDraft(false);
       setPrBaseBranch('');
     }, [modal]);
 
@@ -266,7 +290,23 @@ const CreatePRDialogImpl = NiceModal.create<CreatePRDialogProps>(
                     onChange={(e) => setPrBody(e.target.value)}
                     placeholder={t('createPrDialog.descriptionPlaceholder')}
                     rows={4}
+                    disabled={generateBody}
                   />
+                  <div className="flex items-center space-x-2">
+                    <Checkbox
+                      id="pr-generate-body"
+                      checked={generateBody}
+                      onCheckedChange={(checked: boolean) =>
+                        setGenerateBody(checked)
+                      }
+                    />
+                    <Label
+                      htmlFor="pr-generate-body"
+                      className="text-sm cursor-pointer"
+                    >
+                      {t('createPrDialog.generateBodyLabel')}
+                    </Label>
+                  </div>
                 </div>
                 <div className="space-y-2">
                   <Label htmlFor="pr-base">
@@ -286,6 +326,16 @@ const CreatePRDialogImpl = NiceModal.create<CreatePRDialogProps>(
                     }
                   />
                 </div>
+                <div className="flex items-center space-x-2">
+                  <Checkbox
+                    id="pr-draft"
+                    checked={draft}
+                    onCheckedChange={(checked: boolean) => setDraft(checked)}
+                  />
+                  <Label htmlFor="pr-draft" className="text-sm cursor-pointer">
+                    {t('createPrDialog.draftLabel')}
+                  </Label>
+                </div>
                 {ghCliHelp?.variant && (
                   <Alert variant="default">
                     <AlertTitle>
@@ -61,9 +61,10 @@ const DeleteTaskConfirmationDialogImpl =
             </DialogDescription>
           </DialogHeader>
 
-          <Alert variant="destructive" className="mb-4">
-            <strong>Warning:</strong> This action will permanently delete the
-            task and cannot be undone.
+          <Alert className="mb-4">
+            The task moves to the project's trash. It can be restored from
+            there until it is removed for good, along with its attempts and
+            worktrees.
           </Alert>
 
           {error && (
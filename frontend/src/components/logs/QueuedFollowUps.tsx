@@ -0,0 +1,49 @@
+import { useTranslation } from 'react-i18next';
+import { Clock, X } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import { useQueuedFollowUps } from '@/hooks/follow-up/useQueuedFollowUps';
+
+interface QueuedFollowUpsProps {
+  attemptId: string;
+}
+
+export function QueuedFollowUps({ attemptId }: QueuedFollowUpsProps) {
+  const { t } = useTranslation('tasks');
+  const { queued, remove } = useQueuedFollowUps(attemptId);
+
+  if (queued.length === 0) return null;
+
+  return (
+    <div className="px-4 py-2 space-y-2">
+      <p className="text-xs font-medium text-muted-foreground">
+        {t('followUp.queuedFollowUps')}
+      </p>
+      {queued.map((followUp) => (
+        <div
+          key={followUp.id}
+          className="flex items-start gap-2 rounded border border-dashed px-3 py-2 text-sm"
+        >
+          <Clock className="h-4 w-4 mt-0.5 shrink-0 text-muted-foreground" />
+          <div className="flex-1 min-w-0">
+            <p className="whitespace-pre-wrap break-words">{followUp.prompt}</p>
+            {followUp.blocked_reason && (
+              <p className="text-xs text-destructive mt-1">
+                {t('followUp.queuedBlocked', {
+                  reason: followUp.blocked_reason,
+                })}
+              </p>
+            )}
+          </div>
+          <Button
+            variant="ghost"
+            size="xs"
+            aria-label={t('followUp.removeQueued')}
+            onClick={() => remove(followUp.id)}
+          >
+            <X className="h-3 w-3" />
+          </Button>
+        </div>
+      ))}
+    </div>
+  );
+}
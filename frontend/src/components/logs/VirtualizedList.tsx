@@ -18,6 +18,7 @@ import {
 import { Loader2 } from 'lucide-react';
 import { TaskAttempt, TaskWithAttemptStatus } from 'shared/types';
 import { ApprovalFormProvider } from '@/contexts/ApprovalFormContext';
+import { QueuedFollowUps } from './QueuedFollowUps';
 
 interface VirtualizedListProps {
   attempt: TaskAttempt;
@@ -70,6 +71,15 @@ const ItemContent: VirtuosoMessageListProps<
   return null;
 };
 
+const Footer: VirtuosoMessageListProps<
+  PatchTypeWithKey,
+  MessageListContext
+>['Footer'] = ({ context }) => (
+  <div className="pb-2">
+    {context?.attempt && <QueuedFollowUps attemptId={context.attempt.id} />}
+  </div>
+);
+
 const computeItemKey: VirtuosoMessageListProps<
   PatchTypeWithKey,
   MessageListContext
@@ -128,7 +138,7 @@ const VirtualizedList = ({ attempt, task }: VirtualizedListProps) => {
           computeItemKey={computeItemKey}
           ItemContent={ItemContent}
           Header={() => <div className="h-2"></div>}
-          Footer={() => <div className="h-2"></div>}
+          Footer={Footer}
         />
       </VirtuosoMessageListLicense>
       {loading && (
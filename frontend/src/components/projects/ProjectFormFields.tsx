@@ -10,6 +10,7 @@ import {
   FolderGit,
   FolderPlus,
   ArrowLeft,
+  Github,
 } from 'lucide-react';
 import { useScriptPlaceholders } from '@/hooks/useScriptPlaceholders';
 import { CopyFilesField } from './CopyFilesField';
@@ -19,10 +20,14 @@ import { FolderPickerDialog } from '@/components/dialogs/shared/FolderPickerDial
 import { DirectoryEntry } from 'shared/types';
 import { generateProjectNameFromPath } from '@/utils/string';
 
+export type RepoMode = 'existing' | 'new' | 'clone';
+
 interface ProjectFormFieldsProps {
   isEditing: boolean;
-  repoMode: 'existing' | 'new';
-  setRepoMode: (mode: 'existing' | 'new') => void;
+  repoMode: RepoMode;
+  setRepoMode: (mode: RepoMode) => void;
+  repoUrl?: string;
+  setRepoUrl?: (url: string) => void;
   gitRepoPath: string;
   handleGitRepoPathChange: (path: string) => void;
   parentPath: string;
@@ -48,6 +53,8 @@ export function ProjectFormFields({
   isEditing,
   repoMode,
   setRepoMode,
+  repoUrl = '',
+  setRepoUrl,
   gitRepoPath,
   handleGitRepoPathChange,
   parentPath,
@@ -126,6 +133,27 @@ export function ProjectFormFields({
                   </div>
                 </div>
 
+                {/* Clone from GitHub card */}
+                <div
+                  className="p-4 border cursor-pointer hover:shadow-md transition-shadow rounded-lg bg-card"
+                  onClick={() => {
+                    setRepoMode('clone');
+                    setError('');
+                  }}
+                >
+                  <div className="flex items-start gap-3">
+                    <Github className="h-5 w-5 mt-0.5 flex-shrink-0 text-muted-foreground" />
+                    <div className="min-w-0 flex-1">
+                      <div className="font-medium text-foreground">
+                        Clone from GitHub
+                      </div>
+                      <div className="text-xs text-muted-foreground mt-1">
+                        Clone a repository and create a project for it
+                      </div>
+                    </div>
+                  </div>
+                </div>
+
                 {/* Create Blank Project card */}
                 <div
                   className="p-4 border cursor-pointer hover:shadow-md transition-shadow rounded-lg bg-card"
@@ -363,6 +391,93 @@ export function ProjectFormFields({
         </div>
       )}
 
+      {/* Clone Form */}
+      {!isEditing && repoMode === 'clone' && (
+        <div className="space-y-4">
+          <Button
+            type="button"
+            variant="ghost"
+            size="sm"
+            onClick={() => {
+              setRepoMode('existing');
+              setError('');
+              setRepoUrl?.('');
+              setParentPath('');
+              setSetupScript('');
+            }}
+            className="flex items-center gap-2"
+          >
+            <ArrowLeft className="h-4 w-4" />
+            Back to options
+          </Button>
+
+          <div className="space-y-2">
+            <Label htmlFor="clone-repo-url">
+              Repository URL <span className="text-red-500">*</span>
+            </Label>
+            <Input
+              id="clone-repo-url"
+              type="text"
+              value={repoUrl}
+              onChange={(e) => setRepoUrl?.(e.target.value)}
+              placeholder="https://github.com/owner/repo"
+              className="placeholder:text-secondary-foreground placeholder:opacity-100"
+              required
+            />
+          </div>
+
+          <div className="space-y-2">
+            <Label htmlFor="clone-parent-path">Parent Directory</Label>
+            <div className="flex space-x-2">
+              <Input
+                id="clone-parent-path"
+                type="text"
+                value={parentPath}
+                onChange={(e) => setParentPath(e.target.value)}
+                placeholder="Current Directory"
+                className="flex-1 placeholder:text-secondary-foreground placeholder:opacity-100"
+              />
+              <Button
+                type="button"
+                variant="ghost"
+                size="icon"
+                onClick={async () => {
+                  const selectedPath = await FolderPickerDialog.show({
+                    title: 'Select Parent Directory',
+                    description: 'Choose where to clone the repository',
+                    value: parentPath,
+                  });
+                  if (selectedPath) {
+                    setParentPath(selectedPath);
+                  }
+                }}
+              >
+                <Folder className="h-4 w-4" />
+              </Button>
+            </div>
+            <p className="text-xs text-muted-foreground">
+              The repository is cloned into a new folder named after it.
+            </p>
+          </div>
+
+          <div className="space-y-2">
+            <Label htmlFor="clone-setup-script">Setup Script</Label>
+            <textarea
+              id="clone-setup-script"
+              value={setupScript}
+              onChange={(e) => setSetupScript(e.target.value)}
+              placeholder={placeholders.setup}
+              rows={3}
+              className="w-full px-3 py-2 text-sm border border-input bg-background text-foreground rounded-md resize-vertical focus:outline-none focus:ring-2 focus:ring-ring"
+            />
+            <p className="text-xs text-muted-foreground">
+              Optional. Runs after cloning as a &quot;Set up&quot; task, and before
+              each task attempt.
+            </p>
+          </div>
+        </div>
+      )}
+
       {isEditing && (
         <>
           <div className="space-y-2">
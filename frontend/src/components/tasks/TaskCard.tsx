@@ -1,6 +1,13 @@
 import { useCallback, useEffect, useRef, useState } from 'react';
 import { KanbanCard } from '@/components/ui/shadcn-io/kanban';
-import { CheckCircle, Link, Loader2, XCircle } from 'lucide-react';
+import {
+  CheckCircle,
+  Link,
+  Loader2,
+  ShieldAlert,
+  ShieldCheck,
+  XCircle,
+} from 'lucide-react';
 import type { TaskWithAttemptStatus } from 'shared/types';
 import { ActionsDropdown } from '@/components/ui/actions-dropdown';
 import { Button } from '@/components/ui/button';
@@ -116,6 +123,17 @@ export function TaskCard({
               {task.last_attempt_failed && !task.has_merged_attempt && (
                 <XCircle className="h-4 w-4 text-destructive" />
               )}
+              {task.last_verify_status === 'passed' && (
+                <span title={t('verifyStatus.passed')}>
+                  <ShieldCheck className="h-4 w-4 text-green-500" />
+                </span>
+              )}
+              {(task.last_verify_status === 'failed' ||
+                task.last_verify_status === 'timed_out') && (
+                <span title={t(`verifyStatus.${task.last_verify_status}`)}>
+                  <ShieldAlert className="h-4 w-4 text-amber-500" />
+                </span>
+              )}
               {task.parent_task_attempt && (
                 <Button
                   variant="icon"
@@ -92,8 +92,15 @@ export function NoServerContent({
           git_repo_path: project.git_repo_path,
           setup_script: project.setup_script ?? null,
           dev_script: script,
+          dev_server_url_pattern: project.dev_server_url_pattern ?? null,
           cleanup_script: project.cleanup_script ?? null,
           copy_files: project.copy_files ?? null,
+          verify_script: project.verify_script ?? null,
+          verify_timeout_secs: project.verify_timeout_secs ?? null,
+          default_executor: project.default_executor ?? null,
+          max_concurrent_executions: project.max_concurrent_executions ?? null,
+          close_task_on_pr_merge: project.close_task_on_pr_merge,
+          shared_task_auto_create: project.shared_task_auto_create,
         },
       },
       {
@@ -37,6 +37,7 @@ import { useRetryUi } from '@/contexts/RetryUiContext';
 import { useDraftEditor } from '@/hooks/follow-up/useDraftEditor';
 import { useDraftAutosave } from '@/hooks/follow-up/useDraftAutosave';
 import { useDraftQueue } from '@/hooks/follow-up/useDraftQueue';
+import { useQueuedFollowUps } from '@/hooks/follow-up/useQueuedFollowUps';
 import { useFollowUpSend } from '@/hooks/follow-up/useFollowUpSend';
 import { useDefaultVariant } from '@/hooks/follow-up/useDefaultVariant';
 import { buildResolveConflictsInstructions } from '@/lib/conflicts';
@@ -157,6 +158,20 @@ export function TaskFollowUpSection({
     images,
   });
 
+  // Follow-ups sent one after another as runs finish
+  const { enqueue: enqueueFollowUp } = useQueuedFollowUps(selectedAttemptId);
+  const [isAddingToQueue, setIsAddingToQueue] = useState(false);
+  const onAddToQueue = useCallback(async () => {
+    if (followUpMessage.trim().length === 0) return;
+    setIsAddingToQueue(true);
+    try {
+      await enqueueFollowUp(followUpMessage);
+      setFollowUpMessage('');
+    } finally {
+      setIsAddingToQueue(false);
+    }
+  }, [enqueueFollowUp, followUpMessage, setFollowUpMessage]);
+
   // Presentation-only queue state
   const [isQueuing, setIsQueuing] = useState(false);
   const [isUnqueuing, setIsUnqueuing] = useState(false);
@@ -674,6 +689,22 @@ export function TaskFollowUpSection({
                     t('followUp.queueForNextTurn')
                   )}
                 </Button>
+                <Button
+                  onClick={onAddToQueue}
+                  disabled={
+                    displayQueued ||
+                    isAddingToQueue ||
+                    followUpMessage.trim().length === 0 ||
+                    isRetryActive
+                  }
+                  size="sm"
+                  variant="outline"
+                >
+                  {isAddingToQueue && (
+                    <Loader2 className="animate-spin h-4 w-4 mr-2" />
+                  )}
+                  {t('followUp.addToQueue')}
+                </Button>
               </div>
             )}
           </div>
@@ -22,6 +22,9 @@ import { ReassignDialog } from '@/components/dialogs/tasks/ReassignDialog';
 import { StopShareTaskDialog } from '@/components/dialogs/tasks/StopShareTaskDialog';
 import { useProject } from '@/contexts/ProjectContext';
 import { openTaskForm } from '@/lib/openTaskForm';
+import { attemptsApi } from '@/lib/api';
+import { paths } from '@/lib/paths';
+import { useAttemptRetry } from '@/hooks/useAttemptRetry';
 
 import { useNavigate } from 'react-router-dom';
 import type { SharedTaskRecord } from '@/hooks/useProjectTasks';
@@ -43,6 +46,14 @@ export function ActionsDropdown({
   const openInEditor = useOpenInEditor(attempt?.id);
   const navigate = useNavigate();
   const { userId } = useAuth();
+  const { retryAttempt, isRetrying } = useAttemptRetry({
+    taskId: task?.id ?? attempt?.task_id ?? '',
+    onSuccess: (retry) => {
+      if (projectId) {
+        navigate(paths.attempt(projectId, retry.task_id, retry.id));
+      }
+    },
+  });
 
   const hasAttemptActions = Boolean(attempt);
   const hasTaskActions = Boolean(task);
@@ -85,6 +96,12 @@ export function ActionsDropdown({
     ViewProcessesDialog.show({ attemptId: attempt.id });
   };
 
+  const handleExportAttempt = (e: React.MouseEvent) => {
+    e.stopPropagation();
+    if (!attempt?.id) return;
+    window.location.assign(attemptsApi.getExportUrl(attempt.id));
+  };
+
   const handleViewRelatedTasks = (e: React.MouseEvent) => {
     e.stopPropagation();
     if (!attempt?.id || !projectId) return;
@@ -108,6 +125,12 @@ export function ActionsDropdown({
     });
   };
 
+  const handleRetryAttempt = (e: React.MouseEvent) => {
+    e.stopPropagation();
+    if (!attempt?.id) return;
+    retryAttempt({ attemptId: attempt.id });
+  };
+
   const handleCreateSubtask = (e: React.MouseEvent) => {
     e.stopPropagation();
     if (!projectId || !attempt) return;
@@ -194,6 +217,12 @@ export function ActionsDropdown({
               >
                 {t('actionsMenu.viewProcesses')}
               </DropdownMenuItem>
+              <DropdownMenuItem
+                disabled={!attempt?.id}
+                onClick={handleExportAttempt}
+              >
+                {t('actionsMenu.exportAttempt')}
+              </DropdownMenuItem>
               <DropdownMenuItem
                 disabled={!attempt?.id}
                 onClick={handleViewRelatedTasks}
@@ -203,6 +232,12 @@ export function ActionsDropdown({
               <DropdownMenuItem onClick={handleCreateNewAttempt}>
                 {t('actionsMenu.createNewAttempt')}
               </DropdownMenuItem>
+              <DropdownMenuItem
+                disabled={!attempt?.id || isRetrying}
+                onClick={handleRetryAttempt}
+              >
+                {t('actionsMenu.retryAttempt')}
+              </DropdownMenuItem>
               <DropdownMenuItem
                 disabled={!projectId || !attempt}
                 onClick={handleCreateSubtask}
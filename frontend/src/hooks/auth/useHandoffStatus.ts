@@ -0,0 +1,12 @@
+import { useQuery } from '@tanstack/react-query';
+import { oauthApi } from '@/lib/api';
+
+export function useHandoffStatus(handoffId: string | null) {
+  return useQuery({
+    queryKey: ['auth', 'handoff', handoffId],
+    queryFn: () => oauthApi.handoffStatus(handoffId!),
+    enabled: !!handoffId,
+    refetchInterval: handoffId ? 1000 : false,
+    staleTime: 0,
+  });
+}
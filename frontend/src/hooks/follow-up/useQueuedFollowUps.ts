@@ -0,0 +1,35 @@
+import { useMutation, useQuery, useQueryClient } from '@tanstack/react-query';
+import { attemptsApi } from '@/lib/api';
+
+// The server sends queued follow-ups as runs finish, so keep the list fresh
+const REFETCH_INTERVAL_MS = 5000;
+
+export function useQueuedFollowUps(attemptId?: string) {
+  const queryClient = useQueryClient();
+  const queryKey = ['queuedFollowUps', attemptId];
+
+  const query = useQuery({
+    queryKey,
+    queryFn: () => attemptsApi.listQueuedFollowUps(attemptId!),
+    enabled: !!attemptId,
+    refetchInterval: REFETCH_INTERVAL_MS,
+  });
+
+  const enqueue = useMutation({
+    mutationFn: (prompt: string) =>
+      attemptsApi.enqueueFollowUp(attemptId!, prompt),
+    onSettled: () => queryClient.invalidateQueries({ queryKey }),
+  });
+
+  const remove = useMutation({
+    mutationFn: (followUpId: string) =>
+      attemptsApi.deleteQueuedFollowUp(attemptId!, followUpId),
+    onSettled: () => queryClient.invalidateQueries({ queryKey }),
+  });
+
+  return {
+    queued: query.data ?? [],
+    enqueue: enqueue.mutateAsync,
+    remove: remove.mutate,
+  };
+}
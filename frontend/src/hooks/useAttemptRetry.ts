@@ -0,0 +1,41 @@
+import { useMutation, useQueryClient } from '@tanstack/react-query';
+import { attemptsApi } from '@/lib/api';
+import type { TaskAttempt } from 'shared/types';
+
+type UseAttemptRetryArgs = {
+  taskId: string;
+  onSuccess?: (attempt: TaskAttempt) => void;
+};
+
+export function useAttemptRetry({ taskId, onSuccess }: UseAttemptRetryArgs) {
+  const queryClient = useQueryClient();
+
+  const mutation = useMutation({
+    mutationFn: ({
+      attemptId,
+      allowParallel = false,
+    }: {
+      attemptId: string;
+      allowParallel?: boolean;
+    }) =>
+      attemptsApi.retry(attemptId, {
+        allow_parallel: allowParallel,
+      }),
+    onSuccess: (newAttempt: TaskAttempt) => {
+      queryClient.setQueryData(
+        ['taskAttempts', taskId],
+        (old: TaskAttempt[] = []) => [newAttempt, ...old]
+      );
+      onSuccess?.(newAttempt);
+    },
+    onError: (err) => {
+      console.error('Failed to retry attempt:', err);
+    },
+  });
+
+  return {
+    retryAttempt: mutation.mutate,
+    isRetrying: mutation.isPending,
+    error: mutation.error,
+  };
+}
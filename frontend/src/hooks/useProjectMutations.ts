@@ -2,6 +2,7 @@ import { useMutation, useQueryClient } from '@tanstack/react-query';
 import { projectsApi } from '@/lib/api';
 import type {
   CreateProject,
+  ImportProjectRequest,
   UpdateProject,
   Project,
   LinkToExistingRequest,
@@ -36,6 +37,20 @@ export function useProjectMutations(options?: UseProjectMutationsOptions) {
     },
   });
 
+  const importProject = useMutation({
+    mutationKey: ['importProject'],
+    mutationFn: (data: ImportProjectRequest) => projectsApi.import(data),
+    onSuccess: (project: Project) => {
+      queryClient.setQueryData(['project', project.id], project);
+      queryClient.invalidateQueries({ queryKey: ['projects'] });
+      options?.onCreateSuccess?.(project);
+    },
+    onError: (err) => {
+      console.error('Failed to import project:', err);
+      options?.onCreateError?.(err);
+    },
+  });
+
   const updateProject = useMutation({
     mutationKey: ['updateProject'],
     mutationFn: ({
@@ -181,6 +196,7 @@ export function useProjectMutations(options?: UseProjectMutationsOptions) {
 
   return {
     createProject,
+    importProject,
     updateProject,
     linkToExisting,
     createAndLink,
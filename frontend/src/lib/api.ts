@@ -3,6 +3,8 @@
 import {
   ApprovalStatus,
   ApiResponse,
+  AssignedSharedTasksResponse,
+  BoardExport,
   BranchStatus,
   Config,
   CommitInfo,
@@ -12,14 +14,23 @@ import {
   CreateTask,
   CreateAndStartTaskRequest,
   CreateTaskAttemptBody,
+  RetryTaskAttemptRequest,
   CreateTag,
   DirectoryListResponse,
   DirectoryEntry,
+  DevServerStatus,
   ExecutionProcess,
   GitBranch,
+  ImportBoardRequest,
+  ImportBoardResponse,
+  ListSessionsResponse,
   Project,
   CreateProject,
+  ImportProjectRequest,
   SearchResult,
+  ShareLinkRepairReport,
+  ExecutorInfo,
+  ExecutorSessionEntry,
   ShareTaskResponse,
   Task,
   TaskAttempt,
@@ -27,6 +38,7 @@ import {
   Tag,
   TagSearchParams,
   TaskWithAttemptStatus,
+  TrashedTask,
   AssignSharedTaskResponse,
   UpdateProject,
   UpdateTask,
@@ -35,10 +47,15 @@ import {
   UpdateRetryFollowUpDraftRequest,
   McpServerQuery,
   UpdateMcpServersBody,
+  UpdateMcpServersResponse,
+  McpServerProblems,
+  McpConfigBackup,
+  RestoreMcpBackupBody,
   GetMcpServerResponse,
   ImageResponse,
   DraftResponse,
   UpdateFollowUpDraftRequest,
+  QueuedFollowUp,
   GitOperationError,
   ApprovalResponse,
   RebaseTaskAttemptRequest,
@@ -47,11 +64,14 @@ import {
   RenameBranchRequest,
   RenameBranchResponse,
   CheckEditorAvailabilityResponse,
+  OpenFileInEditorRequest,
   AvailabilityInfo,
   BaseCodingAgent,
   RunAgentSetupRequest,
   RunAgentSetupResponse,
   GhCliSetupError,
+  HandoffProgress,
+  RevokeSessionResponse,
   StatusResponse,
   ListOrganizationsResponse,
   OrganizationMemberWithProfile,
@@ -74,6 +94,8 @@ import {
   OpenEditorRequest,
   CreatePrError,
   PushError,
+  PullRequestInfo,
+  TaskStatus,
 } from 'shared/types';
 
 // Re-export types for convenience
@@ -223,8 +245,12 @@ const handleApiResponse = async <T, E = T>(response: Response): Promise<T> => {
 
 // Project Management APIs
 export const projectsApi = {
-  getAll: async (): Promise<Project[]> => {
-    const response = await makeRequest('/api/projects');
+  getAll: async (includeArchived = false): Promise<Project[]> => {
+    const response = await makeRequest(
+      includeArchived
+        ? '/api/projects?include_archived=true'
+        : '/api/projects'
+    );
     return handleApiResponse<Project[]>(response);
   },
 
@@ -241,6 +267,14 @@ export const projectsApi = {
     return handleApiResponse<Project>(response);
   },
 
+  import: async (data: ImportProjectRequest): Promise<Project> => {
+    const response = await makeRequest('/api/projects/import', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Project>(response);
+  },
+
   update: async (id: string, data: UpdateProject): Promise<Project> => {
     const response = await makeRequest(`/api/projects/${id}`, {
       method: 'PUT',
@@ -249,6 +283,99 @@ export const projectsApi = {
     return handleApiResponse<Project>(response);
   },
 
+  getDevServer: async (id: string): Promise<DevServerStatus> => {
+    const response = await makeRequest(`/api/projects/${id}/dev-server`);
+    return handleApiResponse<DevServerStatus>(response);
+  },
+
+  restartDevServer: async (id: string): Promise<DevServerStatus> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/dev-server/restart`,
+      { method: 'POST' }
+    );
+    return handleApiResponse<DevServerStatus>(response);
+  },
+
+  stopDevServer: async (id: string): Promise<DevServerStatus> => {
+    const response = await makeRequest(`/api/projects/${id}/dev-server/stop`, {
+      method: 'POST',
+    });
+    return handleApiResponse<DevServerStatus>(response);
+  },
+
+  archive: async (id: string): Promise<Project> => {
+    const response = await makeRequest(`/api/projects/${id}/archive`, {
+      method: 'POST',
+    });
+    return handleApiResponse<Project>(response);
+  },
+
+  exportBoard: async (
+    id: string,
+    includeLogs = false
+  ): Promise<BoardExport> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/export${includeLogs ? '?include_logs=true' : ''}`
+    );
+    if (!response.ok) {
+      throw new ApiError(
+        `Failed to export board: ${response.statusText}`,
+        response.status,
+        response
+      );
+    }
+    return response.json();
+  },
+
+  importBoard: async (
+    data: ImportBoardRequest
+  ): Promise<ImportBoardResponse> => {
+    const response = await makeRequest('/api/projects/import-board', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<ImportBoardResponse>(response);
+  },
+
+  repairShareLinks: async (
+    id: string,
+    dryRun = false
+  ): Promise<ShareLinkRepairReport> => {
+    const response = await makeRequest(`/api/projects/${id}/share/repair`, {
+      method: 'POST',
+      body: JSON.stringify({ dry_run: dryRun }),
+    });
+    return handleApiResponse<ShareLinkRepairReport>(response);
+  },
+
+  getSessionEntries: async (
+    id: string,
+    options: {
+      kind?: string;
+      action?: string;
+      limit?: number;
+      offset?: number;
+    } = {}
+  ): Promise<ExecutorSessionEntry[]> => {
+    const params = new URLSearchParams();
+    if (options.kind) params.set('kind', options.kind);
+    if (options.action) params.set('action', options.action);
+    if (options.limit !== undefined) params.set('limit', String(options.limit));
+    if (options.offset !== undefined)
+      params.set('offset', String(options.offset));
+    const response = await makeRequest(
+      `/api/projects/${id}/session-entries?${params}`
+    );
+    return handleApiResponse<ExecutorSessionEntry[]>(response);
+  },
+
+  unarchive: async (id: string): Promise<Project> => {
+    const response = await makeRequest(`/api/projects/${id}/unarchive`, {
+      method: 'POST',
+    });
+    return handleApiResponse<Project>(response);
+  },
+
   getRemoteMembers: async (
     projectId: string
   ): Promise<RemoteProjectMembersResponse> => {
@@ -368,6 +495,20 @@ export const tasksApi = {
     return handleApiResponse<void>(response);
   },
 
+  restore: async (taskId: string): Promise<Task> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/restore`, {
+      method: 'POST',
+    });
+    return handleApiResponse<Task>(response);
+  },
+
+  getTrash: async (projectId: string): Promise<TrashedTask[]> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/trash`
+    );
+    return handleApiResponse<TrashedTask[]>(response);
+  },
+
   share: async (taskId: string): Promise<ShareTaskResponse> => {
     const response = await makeRequest(`/api/tasks/${taskId}/share`, {
       method: 'POST',
@@ -401,10 +542,26 @@ export const tasksApi = {
     });
     return handleApiResponse<void>(response);
   },
+
+  listAssignedToMe: async (
+    options: { status?: TaskStatus; limit?: number; offset?: number } = {}
+  ): Promise<AssignedSharedTasksResponse> => {
+    const params = new URLSearchParams({ assignee: 'me' });
+    if (options.status) params.set('status', options.status);
+    if (options.limit !== undefined) params.set('limit', String(options.limit));
+    if (options.offset !== undefined)
+      params.set('offset', String(options.offset));
+    const response = await makeRequest(`/api/shared-tasks?${params}`);
+    return handleApiResponse<AssignedSharedTasksResponse>(response);
+  },
 };
 
 // Task Attempts APIs
 export const attemptsApi = {
+  getExportUrl: (attemptId: string): string => {
+    return `/api/task-attempts/${attemptId}/export`;
+  },
+
   getChildren: async (attemptId: string): Promise<TaskRelationships> => {
     const response = await makeRequest(
       `/api/task-attempts/${attemptId}/children`
@@ -430,6 +587,20 @@ export const attemptsApi = {
     return handleApiResponse<TaskAttempt>(response);
   },
 
+  retry: async (
+    attemptId: string,
+    data: RetryTaskAttemptRequest
+  ): Promise<TaskAttempt> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/retry`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<TaskAttempt>(response);
+  },
+
   stop: async (attemptId: string): Promise<void> => {
     const response = await makeRequest(`/api/task-attempts/${attemptId}/stop`, {
       method: 'POST',
@@ -501,6 +672,38 @@ export const attemptsApi = {
     return handleApiResponse<void>(response);
   },
 
+  listQueuedFollowUps: async (attemptId: string): Promise<QueuedFollowUp[]> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/queued-follow-ups`
+    );
+    return handleApiResponse<QueuedFollowUp[]>(response);
+  },
+
+  enqueueFollowUp: async (
+    attemptId: string,
+    prompt: string
+  ): Promise<QueuedFollowUp> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/queued-follow-ups`,
+      {
+        method: 'POST',
+        body: JSON.stringify({ prompt }),
+      }
+    );
+    return handleApiResponse<QueuedFollowUp>(response);
+  },
+
+  deleteQueuedFollowUp: async (
+    attemptId: string,
+    followUpId: string
+  ): Promise<void> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/queued-follow-ups/${followUpId}`,
+      { method: 'DELETE' }
+    );
+    return handleApiResponse<void>(response);
+  },
+
   setDraftQueue: async (
     attemptId: string,
     queued: boolean,
@@ -636,6 +839,16 @@ export const attemptsApi = {
     return handleApiResponseAsResult<string, CreatePrError>(response);
   },
 
+  refreshPR: async (attemptId: string): Promise<PullRequestInfo> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/refresh-pr`,
+      {
+        method: 'POST',
+      }
+    );
+    return handleApiResponse<PullRequestInfo>(response);
+  },
+
   startDevServer: async (attemptId: string): Promise<void> => {
     const response = await makeRequest(
       `/api/task-attempts/${attemptId}/start-dev-server`,
@@ -738,6 +951,15 @@ export const configApi = {
     );
     return handleApiResponse<CheckEditorAvailabilityResponse>(response);
   },
+  openFileInEditor: async (
+    data: OpenFileInEditorRequest
+  ): Promise<OpenEditorResponse> => {
+    const response = await makeRequest('/api/editor/open', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<OpenEditorResponse>(response);
+  },
   checkAgentAvailability: async (
     agent: BaseCodingAgent
   ): Promise<AvailabilityInfo> => {
@@ -746,6 +968,10 @@ export const configApi = {
     );
     return handleApiResponse<AvailabilityInfo>(response);
   },
+  listExecutors: async (): Promise<ExecutorInfo[]> => {
+    const response = await makeRequest('/api/executors');
+    return handleApiResponse<ExecutorInfo[]>(response);
+  },
 };
 
 // Task Tags APIs (all tags are global)
@@ -785,34 +1011,62 @@ export const tagsApi = {
 // MCP Servers APIs
 export const mcpServersApi = {
   load: async (query: McpServerQuery): Promise<GetMcpServerResponse> => {
-    const params = new URLSearchParams(query);
+    const params = new URLSearchParams({ executor: query.executor });
     const response = await makeRequest(`/api/mcp-config?${params.toString()}`);
     return handleApiResponse<GetMcpServerResponse>(response);
   },
   save: async (
     query: McpServerQuery,
     data: UpdateMcpServersBody
-  ): Promise<void> => {
-    const params = new URLSearchParams(query);
-    // params.set('profile', profile);
+  ): Promise<Result<UpdateMcpServersResponse, McpServerProblems>> => {
+    const params = new URLSearchParams({ executor: query.executor });
     const response = await makeRequest(`/api/mcp-config?${params.toString()}`, {
       method: 'POST',
       body: JSON.stringify(data),
     });
-    if (!response.ok) {
-      const errorData = await response.json();
-      console.error('[API Error] Failed to save MCP servers', {
-        message: errorData.message,
-        status: response.status,
-        response,
-        timestamp: new Date().toISOString(),
-      });
-      throw new ApiError(
-        errorData.message || 'Failed to save MCP servers',
-        response.status,
-        response
-      );
-    }
+    return handleApiResponseAsResult<
+      UpdateMcpServersResponse,
+      McpServerProblems
+    >(response);
+  },
+  // Launches each server with --help instead of saving
+  check: async (
+    query: McpServerQuery,
+    data: UpdateMcpServersBody
+  ): Promise<Result<UpdateMcpServersResponse, McpServerProblems>> => {
+    const params = new URLSearchParams({
+      executor: query.executor,
+      validate: 'spawn',
+    });
+    const response = await makeRequest(`/api/mcp-config?${params.toString()}`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponseAsResult<
+      UpdateMcpServersResponse,
+      McpServerProblems
+    >(response);
+  },
+  listBackups: async (query: McpServerQuery): Promise<McpConfigBackup[]> => {
+    const params = new URLSearchParams({ executor: query.executor });
+    const response = await makeRequest(
+      `/api/mcp-config/backups?${params.toString()}`
+    );
+    return handleApiResponse<McpConfigBackup[]>(response);
+  },
+  restoreBackup: async (
+    query: McpServerQuery,
+    data: RestoreMcpBackupBody
+  ): Promise<string> => {
+    const params = new URLSearchParams({ executor: query.executor });
+    const response = await makeRequest(
+      `/api/mcp-config/restore?${params.toString()}`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<string>(response);
   },
 };
 
@@ -930,6 +1184,13 @@ export const oauthApi = {
     );
   },
 
+  handoffStatus: async (handoffId: string): Promise<HandoffProgress> => {
+    const response = await makeRequest(
+      `/api/auth/handoff/${handoffId}/status`
+    );
+    return handleApiResponse<HandoffProgress>(response);
+  },
+
   status: async (): Promise<StatusResponse> => {
     const response = await makeRequest('/api/auth/status');
     return handleApiResponse<StatusResponse>(response);
@@ -947,6 +1208,18 @@ export const oauthApi = {
       );
     }
   },
+
+  listSessions: async (): Promise<ListSessionsResponse> => {
+    const response = await makeRequest('/api/auth/sessions');
+    return handleApiResponse<ListSessionsResponse>(response);
+  },
+
+  revokeSession: async (sessionId: string): Promise<RevokeSessionResponse> => {
+    const response = await makeRequest(`/api/auth/sessions/${sessionId}`, {
+      method: 'DELETE',
+    });
+    return handleApiResponse<RevokeSessionResponse>(response);
+  },
 };
 
 // Organizations API
@@ -604,6 +604,31 @@ export function GeneralSettings() {
               )}
             </p>
           </div>
+          <div className="space-y-2">
+            <Label htmlFor="gitlab-hosts">
+              {t('settings.general.git.gitlabHosts.label')}
+            </Label>
+            <Input
+              // Parsed on blur so commas can be typed; remounts when the
+              // saved list changes
+              key={draft?.gitlab_hosts.join(',')}
+              id="gitlab-hosts"
+              type="text"
+              placeholder={t('settings.general.git.gitlabHosts.placeholder')}
+              defaultValue={draft?.gitlab_hosts.join(', ') ?? ''}
+              onBlur={(e) =>
+                updateDraft({
+                  gitlab_hosts: e.target.value
+                    .split(',')
+                    .map((host) => host.trim())
+                    .filter(Boolean),
+                })
+              }
+            />
+            <p className="text-sm text-muted-foreground">
+              {t('settings.general.git.gitlabHosts.helper')}
+            </p>
+          </div>
         </CardContent>
       </Card>
 
@@ -26,7 +26,12 @@ import { Label } from '@/components/ui/label';
 import { Alert, AlertDescription } from '@/components/ui/alert';
 import { JSONEditor } from '@/components/ui/json-editor';
 import { Loader2 } from 'lucide-react';
-import type { BaseCodingAgent, ExecutorConfig } from 'shared/types';
+import type {
+  BaseCodingAgent,
+  ExecutorConfig,
+  McpConfigBackup,
+  McpSpawnCheck,
+} from 'shared/types';
 import { McpConfig } from 'shared/types';
 import { useUserSystem } from '@/components/ConfigProvider';
 import { mcpServersApi } from '@/lib/api';
@@ -45,6 +50,17 @@ export function McpSettings() {
   const [mcpApplying, setMcpApplying] = useState(false);
   const [mcpConfigPath, setMcpConfigPath] = useState<string>('');
   const [success, setSuccess] = useState(false);
+  const [serverProblems, setServerProblems] = useState<
+    Record<string, string[]>
+  >({});
+  const [spawnChecks, setSpawnChecks] = useState<Record<
+    string,
+    McpSpawnCheck
+  > | null>(null);
+  const [mcpChecking, setMcpChecking] = useState(false);
+  const [backups, setBackups] = useState<McpConfigBackup[]>([]);
+  // Bumped to re-read the config file, e.g. after restoring a backup
+  const [reloadToken, setReloadToken] = useState(0);
 
   // Initialize selected profile when config loads
   useEffect(() => {
@@ -91,6 +107,10 @@ export function McpSettings() {
         const configJson = JSON.stringify(fullConfig, null, 2);
         setMcpServers(configJson);
         setMcpConfigPath(result.config_path);
+        mcpServersApi
+          .listBackups({ executor: profileKey as BaseCodingAgent })
+          .then(setBackups)
+          .catch(() => setBackups([]));
       } catch (err: unknown) {
         if (
           err instanceof Error &&
@@ -109,11 +129,28 @@ export function McpSettings() {
     if (selectedProfile) {
       loadMcpServersForProfile(selectedProfile);
     }
+  }, [selectedProfile, profiles, reloadToken]);
+
+  // Re-read the config when it is edited outside the app
+  useEffect(() => {
+    if (!selectedProfile || !profiles) return;
+    const profileKey = Object.keys(profiles).find(
+      (key) => profiles[key] === selectedProfile
+    );
+    const events = new EventSource('/api/events');
+    events.addEventListener('mcp-config-changed', (event) => {
+      if ((event as MessageEvent).data === profileKey) {
+        setReloadToken((token) => token + 1);
+      }
+    });
+    return () => events.close();
   }, [selectedProfile, profiles]);
 
   const handleMcpServersChange = (value: string) => {
     setMcpServers(value);
     setMcpError(null);
+    setServerProblems({});
+    setSpawnChecks(null);
 
     // Validate JSON on change
     if (value.trim() && mcpConfig) {
@@ -153,22 +190,20 @@ export function McpSettings() {
               fullConfig
             );
 
-          // Find the key for the selected profile
-          const selectedProfileKey = profiles
-            ? Object.keys(profiles).find(
-                (key) => profiles[key] === selectedProfile
-              )
-            : null;
-          if (!selectedProfileKey) {
-            throw new Error('Selected profile key not found');
-          }
-
-          await mcpServersApi.save(
-            {
-              executor: selectedProfileKey as BaseCodingAgent,
-            },
+          const result = await mcpServersApi.save(
+            { executor: selectedProfileKey() },
             { servers: mcpServersConfig }
           );
+          if (!result.success) {
+            setServerProblems(result.error?.servers ?? {});
+            setMcpError(result.message ?? t('settings.mcp.errors.saveFailed'));
+            return;
+          }
+
+          mcpServersApi
+            .listBackups({ executor: selectedProfileKey() })
+            .then(setBackups)
+            .catch(() => setBackups([]));
 
           // Show success feedback
           setSuccess(true);
@@ -193,6 +228,70 @@ export function McpSettings() {
     }
   };
 
+  const selectedProfileKey = (): BaseCodingAgent => {
+    const key = profiles
+      ? Object.keys(profiles).find((key) => profiles[key] === selectedProfile)
+      : null;
+    if (!key) {
+      throw new Error('Selected profile key not found');
+    }
+    return key as BaseCodingAgent;
+  };
+
+  const handleCheckMcpServers = async () => {
+    if (!selectedProfile || !mcpConfig || !mcpServers.trim()) return;
+
+    setMcpChecking(true);
+    setMcpError(null);
+    setServerProblems({});
+    setSpawnChecks(null);
+    try {
+      const fullConfig = JSON.parse(mcpServers);
+      const servers = McpConfigStrategyGeneral.extractServersForApi(
+        mcpConfig,
+        fullConfig
+      );
+      const result = await mcpServersApi.check(
+        { executor: selectedProfileKey() },
+        { servers }
+      );
+      if (result.success) {
+        setSpawnChecks(
+          result.data.spawn_checks as Record<string, McpSpawnCheck>
+        );
+      } else {
+        setServerProblems(result.error?.servers ?? {});
+        setMcpError(result.message ?? t('settings.mcp.errors.validationError'));
+      }
+    } catch (err) {
+      setMcpError(
+        err instanceof SyntaxError
+          ? t('settings.mcp.errors.invalidJson')
+          : err instanceof Error
+            ? err.message
+            : t('settings.mcp.errors.validationError')
+      );
+    } finally {
+      setMcpChecking(false);
+    }
+  };
+
+  const handleRestoreBackup = async (id: string) => {
+    try {
+      await mcpServersApi.restoreBackup(
+        { executor: selectedProfileKey() },
+        { id }
+      );
+      setReloadToken((token) => token + 1);
+    } catch (err) {
+      setMcpError(
+        err instanceof Error
+          ? err.message
+          : t('settings.mcp.errors.restoreFailed')
+      );
+    }
+  };
+
   const addServer = (key: string) => {
     try {
       const existing = mcpServers.trim() ? JSON.parse(mcpServers) : {};
@@ -343,6 +442,34 @@ export function McpSettings() {
                   {mcpError}
                 </p>
               )}
+              {Object.keys(serverProblems).length > 0 && (
+                <ul className="space-y-1 text-sm text-destructive dark:text-red-400">
+                  {Object.entries(serverProblems).map(([name, problems]) => (
+                    <li key={name}>
+                      <span className="font-mono">{name}</span>:{' '}
+                      {problems.join('; ')}
+                    </li>
+                  ))}
+                </ul>
+              )}
+              {spawnChecks && (
+                <ul className="space-y-1 text-sm">
+                  {Object.entries(spawnChecks).map(([name, check]) => (
+                    <li
+                      key={name}
+                      className={
+                        check.status === 'failed'
+                          ? 'text-destructive dark:text-red-400'
+                          : 'text-muted-foreground'
+                      }
+                    >
+                      <span className="font-mono">{name}</span>:{' '}
+                      {t(`settings.mcp.check.status.${check.status}`)}
+                      {check.detail && ` (${check.detail})`}
+                    </li>
+                  ))}
+                </ul>
+              )}
               <div className="text-sm text-muted-foreground">
                 {mcpLoading ? (
                   t('settings.mcp.loading.configuration')
@@ -357,6 +484,22 @@ export function McpSettings() {
                   </span>
                 )}
               </div>
+              {!mcpLoading && backups.length > 0 && (
+                <Select value="" onValueChange={handleRestoreBackup}>
+                  <SelectTrigger className="w-64">
+                    <SelectValue
+                      placeholder={t('settings.mcp.backups.placeholder')}
+                    />
+                  </SelectTrigger>
+                  <SelectContent>
+                    {backups.map((backup) => (
+                      <SelectItem key={backup.id} value={backup.id}>
+                        {new Date(backup.created_at).toLocaleString()}
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+              )}
 
               {mcpConfig?.preconfigured &&
                 typeof mcpConfig.preconfigured === 'object' && (
@@ -441,7 +584,15 @@ export function McpSettings() {
 
       {/* Sticky Save Button */}
       <div className="sticky bottom-0 z-10 bg-background/80 backdrop-blur-sm border-t py-4">
-        <div className="flex justify-end">
+        <div className="flex justify-end gap-2">
+          <Button
+            variant="outline"
+            onClick={handleCheckMcpServers}
+            disabled={mcpChecking || mcpApplying || mcpLoading || !!mcpError}
+          >
+            {mcpChecking && <Loader2 className="mr-2 h-4 w-4 animate-spin" />}
+            {t('settings.mcp.check.button')}
+          </Button>
           <Button
             onClick={handleApplyMcpServers}
             disabled={mcpApplying || mcpLoading || !!mcpError || success}
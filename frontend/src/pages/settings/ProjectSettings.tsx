@@ -19,6 +19,7 @@ import {
 } from '@/components/ui/select';
 import { Label } from '@/components/ui/label';
 import { Input } from '@/components/ui/input';
+import { Checkbox } from '@/components/ui/checkbox';
 import { Alert, AlertDescription } from '@/components/ui/alert';
 import { Loader2, Folder } from 'lucide-react';
 import { useProjects } from '@/hooks/useProjects';
@@ -27,25 +28,41 @@ import { useScriptPlaceholders } from '@/hooks/useScriptPlaceholders';
 import { CopyFilesField } from '@/components/projects/CopyFilesField';
 import { AutoExpandingTextarea } from '@/components/ui/auto-expanding-textarea';
 import { FolderPickerDialog } from '@/components/dialogs/shared/FolderPickerDialog';
-import type { Project, UpdateProject } from 'shared/types';
+import type {
+  Project,
+  SharedTaskAutoCreate,
+  UpdateProject,
+} from 'shared/types';
 
 interface ProjectFormState {
   name: string;
   git_repo_path: string;
   setup_script: string;
   dev_script: string;
+  dev_server_url_pattern: string;
   cleanup_script: string;
   copy_files: string;
+  close_task_on_pr_merge: boolean;
+  shared_task_auto_create: SharedTaskAutoCreate;
 }
 
+const SHARED_TASK_AUTO_CREATE_OPTIONS: SharedTaskAutoCreate[] = [
+  'assigned_to_me',
+  'all',
+  'none',
+];
+
 function projectToFormState(project: Project): ProjectFormState {
   return {
     name: project.name,
     git_repo_path: project.git_repo_path,
     setup_script: project.setup_script ?? '',
     dev_script: project.dev_script ?? '',
+    dev_server_url_pattern: project.dev_server_url_pattern ?? '',
     cleanup_script: project.cleanup_script ?? '',
     copy_files: project.copy_files ?? '',
+    close_task_on_pr_merge: project.close_task_on_pr_merge,
+    shared_task_auto_create: project.shared_task_auto_create,
   };
 }
 
@@ -212,8 +229,15 @@ export function ProjectSettings() {
         git_repo_path: draft.git_repo_path.trim(),
         setup_script: draft.setup_script.trim() || null,
         dev_script: draft.dev_script.trim() || null,
+        dev_server_url_pattern: draft.dev_server_url_pattern.trim() || null,
         cleanup_script: draft.cleanup_script.trim() || null,
         copy_files: draft.copy_files.trim() || null,
+        verify_script: selectedProject.verify_script,
+        verify_timeout_secs: selectedProject.verify_timeout_secs,
+        default_executor: selectedProject.default_executor,
+        max_concurrent_executions: selectedProject.max_concurrent_executions,
+        close_task_on_pr_merge: draft.close_task_on_pr_merge,
+        shared_task_auto_create: draft.shared_task_auto_create,
       };
 
       updateProject.mutate({
@@ -433,6 +457,24 @@ export function ProjectSettings() {
                 </p>
               </div>
 
+              <div className="space-y-2">
+                <Label htmlFor="dev-server-url-pattern">
+                  {t('settings.projects.scripts.devUrlPattern.label')}
+                </Label>
+                <Input
+                  id="dev-server-url-pattern"
+                  value={draft.dev_server_url_pattern}
+                  onChange={(e) =>
+                    updateDraft({ dev_server_url_pattern: e.target.value })
+                  }
+                  placeholder="App running at (?P<url>https?://\S+)"
+                  className="font-mono"
+                />
+                <p className="text-sm text-muted-foreground">
+                  {t('settings.projects.scripts.devUrlPattern.helper')}
+                </p>
+              </div>
+
               <div className="space-y-2">
                 <Label htmlFor="cleanup-script">
                   {t('settings.projects.scripts.cleanup.label')}
@@ -463,6 +505,57 @@ export function ProjectSettings() {
                   {t('settings.projects.scripts.copyFiles.helper')}
                 </p>
               </div>
+
+              <div className="flex items-center space-x-2">
+                <Checkbox
+                  id="close-task-on-pr-merge"
+                  checked={draft.close_task_on_pr_merge}
+                  onCheckedChange={(checked: boolean) =>
+                    updateDraft({ close_task_on_pr_merge: checked })
+                  }
+                />
+                <div className="space-y-0.5">
+                  <Label
+                    htmlFor="close-task-on-pr-merge"
+                    className="cursor-pointer"
+                  >
+                    {t('settings.projects.closeTaskOnPrMerge.label')}
+                  </Label>
+                  <p className="text-sm text-muted-foreground">
+                    {t('settings.projects.closeTaskOnPrMerge.helper')}
+                  </p>
+                </div>
+              </div>
+
+              {selectedProject.remote_project_id && (
+                <div className="space-y-2">
+                  <Label htmlFor="shared-task-auto-create">
+                    {t('settings.projects.sharedTaskAutoCreate.label')}
+                  </Label>
+                  <Select
+                    value={draft.shared_task_auto_create}
+                    onValueChange={(value: SharedTaskAutoCreate) =>
+                      updateDraft({ shared_task_auto_create: value })
+                    }
+                  >
+                    <SelectTrigger id="shared-task-auto-create">
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      {SHARED_TASK_AUTO_CREATE_OPTIONS.map((option) => (
+                        <SelectItem key={option} value={option}>
+                          {t(
+                            `settings.projects.sharedTaskAutoCreate.options.${option}`
+                          )}
+                        </SelectItem>
+                      ))}
+                    </SelectContent>
+                  </Select>
+                  <p className="text-sm text-muted-foreground">
+                    {t('settings.projects.sharedTaskAutoCreate.helper')}
+                  </p>
+                </div>
+              )}
             </CardContent>
           </Card>
 
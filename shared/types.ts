@@ -8,11 +8,84 @@ export type DirectoryEntry = { name: string, path: string, is_directory: boolean
 
 export type DirectoryListResponse = { entries: Array<DirectoryEntry>, current_path: string, };
 
-export type Project = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, remote_project_id: string | null, created_at: Date, updated_at: Date, };
+/**
+ * One entry of a coding agent run's normalized conversation, as persisted
+ * while the run's output was normalized.
+ */
+export type ExecutorSessionEntry = { execution_process_id: string, 
+/**
+ * Position in the run's conversation
+ */
+ordinal: bigint, entry: NormalizedEntry, };
 
-export type CreateProject = { name: string, git_repo_path: string, use_existing_repo: boolean, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, };
+export type Project = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, 
+/**
+ * Regex for the URL in the dev server's output, tried before the
+ * built-in patterns. A named `url` group selects part of the match.
+ */
+dev_server_url_pattern: string | null, cleanup_script: string | null, copy_files: string | null, 
+/**
+ * Command run after the coding agent to check the attempt, e.g. `cargo test`
+ */
+verify_script: string | null, verify_timeout_secs: bigint | null, 
+/**
+ * Executor profile for attempts started without one, as `EXECUTOR[:VARIANT]`
+ */
+default_executor: string | null, 
+/**
+ * Coding agent runs allowed at once in this project; further attempts
+ * wait in the queue. `None` leaves only the global limit.
+ */
+max_concurrent_executions: bigint | null, 
+/**
+ * Move tasks to done when the PR opened from one of their attempts is
+ * merged
+ */
+close_task_on_pr_merge: boolean, 
+/**
+ * Which shared tasks synced from the linked remote project get a local
+ * task
+ */
+shared_task_auto_create: SharedTaskAutoCreate, remote_project_id: string | null, 
+/**
+ * Name of the linked remote project as last fetched from the remote
+ */
+remote_project_name: string | null, 
+/**
+ * Metadata of the linked remote project as last fetched
+ */
+remote_project_metadata: Record<string, unknown> | null, 
+/**
+ * Set once the linked remote project was found to be deleted. The
+ * project stops syncing until it is unlinked.
+ */
+remote_link_broken_at: Date | null, 
+/**
+ * Set while the project is archived
+ */
+archived_at: Date | null, created_at: Date, updated_at: Date, };
+
+/**
+ * When syncing a shared task creates a local task for it in the linked
+ * project. Tasks that already have a local task are updated regardless.
+ */
+export type SharedTaskAutoCreate = "assigned_to_me" | "all" | "none";
+
+export type CreateProject = { name: string, git_repo_path: string, use_existing_repo: boolean, setup_script: string | null, dev_script: string | null, dev_server_url_pattern: string | null, cleanup_script: string | null, copy_files: string | null, verify_script: string | null, verify_timeout_secs: bigint | null, default_executor: string | null, max_concurrent_executions: bigint | null, 
+/**
+ * Defaults to true
+ */
+close_task_on_pr_merge: boolean | null, };
 
-export type UpdateProject = { name: string | null, git_repo_path: string | null, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, };
+export type UpdateProject = { name: string | null, git_repo_path: string | null, setup_script: string | null, dev_script: string | null, dev_server_url_pattern: string | null, cleanup_script: string | null, copy_files: string | null, verify_script: string | null, verify_timeout_secs: bigint | null, default_executor: string | null, max_concurrent_executions: bigint | null, 
+/**
+ * Leaves the current setting when omitted
+ */
+close_task_on_pr_merge: boolean | null, 
+/**
+ * Leaves the current setting when omitted
+ */
+shared_task_auto_create: SharedTaskAutoCreate | null, };
 
 export type SearchResult = { path: string, is_file: boolean, match_type: SearchMatchType, };
 
@@ -22,15 +95,272 @@ export type CreateRemoteProjectRequest = { organization_id: string, name: string
 
 export type LinkToExistingRequest = { remote_project_id: string, };
 
+export type ApplyProjectSuggestionsRequest = { suggestion_ids: Array<string>, };
+
+export type ProjectUsage = { 
+/**
+ * `None` when no run in the project has reported usage
+ */
+total: UsageSummary | null, 
+/**
+ * Heaviest tasks first
+ */
+tasks: Array<TaskUsage>, 
+/**
+ * Disk space taken by the project's attempt worktrees
+ */
+worktree_disk: ProjectDiskUsage, };
+
+/**
+ * Combined size of a project's worktrees as of their last scans.
+ */
+export type ProjectDiskUsage = { total_bytes: bigint, 
+/**
+ * When the least recently scanned worktree was scanned; `None` before
+ * the first scan
+ */
+computed_at: Date | null, 
+/**
+ * Largest first
+ */
+worktrees: Array<WorktreeDiskUsage>, };
+
+/**
+ * Size of an attempt's worktree as of the last scan
+ */
+export type WorktreeDiskUsage = { task_attempt_id: string, bytes: bigint, 
+/**
+ * The scan ran out of time, so `bytes` is a lower bound
+ */
+truncated: boolean, computed_at: Date, };
+
+export type SessionEntriesQuery = { 
+/**
+ * Entry type, e.g. `tool_use`
+ */
+kind: string | null, 
+/**
+ * Tool action, e.g. `file_edit`
+ */
+action: string | null, limit: bigint | null, offset: bigint | null, };
+
+export type ProjectEnvVars = { 
+/**
+ * Values may reference the server's environment as `${VAR}`
+ */
+vars: { [key in string]?: string }, };
+
+export type UpdateProjectEnvVars = { vars: { [key in string]?: string }, 
+/**
+ * Permit overriding variables such as `PATH` that executors rely on
+ */
+allow_dangerous: boolean, };
+
+export type ImportProjectRequest = { 
+/**
+ * Chosen by the client so it can follow clone progress on
+ * `/api/execution-processes/{import_id}/raw-logs/ws` while this runs;
+ * rejected if a log with this id already exists
+ */
+import_id: string, repo_url: string, 
+/**
+ * Directory to clone into; must be new or empty
+ */
+destination_dir: string, setup_script: string | null, };
+
+export type CleanupWorktreesRequest = { 
+/**
+ * Also remove worktrees with uncommitted changes and unmerged branches
+ * left by deleted attempts
+ */
+force: boolean, };
+
+export type RepairShareLinksRequest = { 
+/**
+ * Report the planned changes without applying them
+ */
+dry_run: boolean, };
+
+export type ShareLinkRepairReport = { 
+/**
+ * Nothing was changed; the lists are what a real run would do
+ */
+dry_run: boolean, 
+/**
+ * Local tasks linked to a shared task that no longer exists; the link
+ * is cleared
+ */
+unlinked_tasks: Array<string>, 
+/**
+ * Shared tasks that get a new local task
+ */
+created_for: Array<string>, 
+/**
+ * Shared tasks left without a local task by the project's
+ * `shared_task_auto_create` setting
+ */
+skipped: Array<string>, unlinked_count: number, created_count: number, skipped_count: number, };
+
+export type DevServerState = "running" | "restarting" | "crash_loop" | "stopped";
+
+export type DevServerStatus = { state: DevServerState, task_attempt_id: string | null, execution_process_id: string | null, pid: number | null, url: string | null, port_conflict: boolean, uptime_secs: bigint | null, 
+/**
+ * Restarts since the dev server was last started by hand or ran
+ * steadily
+ */
+restart_count: number, last_exit_code: bigint | null, };
+
+export type CleanupSkipReason = "active" | "within_retention" | "uncommitted_changes" | "unmerged_commits" | "failed";
+
+export type CleanupItem = { 
+/**
+ * `None` for leftovers of attempts that were deleted
+ */
+task_attempt_id: string | null, worktree_path: string | null, branch: string | null, };
+
+export type SkippedCleanupItem = { item: CleanupItem, reason: CleanupSkipReason, detail: string | null, };
+
+export type WorktreeCleanupReport = { removed: Array<CleanupItem>, skipped: Array<SkippedCleanupItem>, };
+
+export type BulkTaskOperation = "set_status" | "delete";
+
+export type BulkTaskRequest = { operation: BulkTaskOperation, task_ids: Array<string>, 
+/**
+ * Required for `set_status`
+ */
+status: TaskStatus | null, 
+/**
+ * Allow deleting tasks linked to a shared task. The local task is
+ * detached and moved to the trash; the shared task stays on the remote
+ * server.
+ */
+detach_shared: boolean, };
+
+export type BulkTaskOutcome = "applied" | "skipped";
+
+export type BulkTaskResult = { task_id: string, outcome: BulkTaskOutcome, 
+/**
+ * Why the task was skipped, or why an applied change did not reach the
+ * task's remote copy
+ */
+reason: string | null, };
+
+export type BulkTaskResponse = { 
+/**
+ * One entry per distinct task id, in request order
+ */
+results: Array<BulkTaskResult>, };
+
+export type BoardExport = { version: number, exported_at: Date, project: BoardProject, tasks: Array<BoardTask>, };
+
+export type BoardProject = { name: string, setup_script: string | null, dev_script: string | null, dev_server_url_pattern: string | null, cleanup_script: string | null, copy_files: string | null, verify_script: string | null, verify_timeout_secs: bigint | null, default_executor: string | null, max_concurrent_executions: bigint | null, close_task_on_pr_merge: boolean, 
+/**
+ * Absent from boards exported before the setting existed
+ */
+shared_task_auto_create: SharedTaskAutoCreate, 
+/**
+ * Remote project the board was linked to
+ */
+remote_project_id: string | null, };
+
+export type BoardTask = { 
+/**
+ * Id on the exporting machine; only used to resolve references within
+ * the document
+ */
+id: string, title: string, description: string | null, status: TaskStatus, 
+/**
+ * Attempt this task was created from, if it is part of the document
+ */
+parent_task_attempt: string | null, 
+/**
+ * Remote shared task this task was linked to
+ */
+shared_task_id: string | null, 
+/**
+ * Tasks of the document this one waits for
+ */
+depends_on: Array<string>, created_at: Date, updated_at: Date, attempts: Array<BoardAttempt>, };
+
+export type BoardAttempt = { id: string, branch: string, target_branch: string, executor: string, verify_status: VerifyStatus | null, created_at: Date, updated_at: Date, processes: Array<BoardProcess>, };
+
+export type BoardProcess = { run_reason: ExecutionProcessRunReason, 
+/**
+ * What was run, including the prompt sent to the agent
+ */
+executor_action: ExecutorAction, status: ExecutionProcessStatus, exit_code: bigint | null, before_head_commit: string | null, after_head_commit: string | null, started_at: Date, completed_at: Date | null, 
+/**
+ * Raw output as JSONL; only present when the export asked for logs
+ */
+logs?: string, };
+
+export type ImportBoardRequest = { 
+/**
+ * Existing git repository the board is attached to
+ */
+git_repo_path: string, 
+/**
+ * Defaults to the exported project's name
+ */
+name: string | null, board: BoardExport, };
+
+export type ImportBoardResponse = { project: Project, tasks: number, attempts: number, 
+/**
+ * Tasks whose shared task link was dropped because the board's remote
+ * project is not available to this project
+ */
+unlinked_shared_tasks: number, };
+
+export type ProjectAnalysis = { suggestions: Array<ProjectSuggestion>, has_vibe_kanban_config: boolean, };
+
+export type ProjectSuggestion = { 
+/**
+ * Stable across analyses of the same repository contents
+ */
+id: string, kind: SuggestionKind, value: string, confidence: SuggestionConfidence, evidence: Array<SuggestionEvidence>, 
+/**
+ * Whether this can be written to the project settings
+ */
+applicable: boolean, };
+
+export type SuggestionKind = "package_manager" | "setup_script" | "dev_script" | "test_command" | "default_branch" | "ignore_pattern" | "config_file";
+
+export type SuggestionConfidence = "low" | "medium" | "high";
+
+/**
+ * Where a suggestion came from, e.g. `package.json` line 7: `"dev": "vite"`
+ */
+export type SuggestionEvidence = { path: string, 
+/**
+ * 1-based line number, when the evidence is a specific line
+ */
+line: number | null, snippet: string | null, };
+
 export type ExecutorAction = { typ: ExecutorActionType, next_action: ExecutorAction | null, };
 
 export type McpConfig = { servers: { [key in string]?: JsonValue }, servers_path: Array<string>, template: JsonValue, preconfigured: JsonValue, is_toml_config: boolean, };
 
 export type ExecutorActionType = { "type": "CodingAgentInitialRequest" } & CodingAgentInitialRequest | { "type": "CodingAgentFollowUpRequest" } & CodingAgentFollowUpRequest | { "type": "ScriptRequest" } & ScriptRequest;
 
-export type ScriptContext = "SetupScript" | "CleanupScript" | "DevServer" | "ToolInstallScript";
+export type ScriptContext = "SetupScript" | "CleanupScript" | "DevServer" | "ToolInstallScript" | "VerifyScript";
 
-export type ScriptRequest = { script: string, language: ScriptRequestLanguage, context: ScriptContext, };
+export type SetupStep = { name: string, command: string, 
+/**
+ * Run the following steps even if this one fails
+ */
+continue_on_error: boolean, };
+
+export type ScriptRequest = { script: string, language: ScriptRequestLanguage, context: ScriptContext, 
+/**
+ * Kill the script and report [`ExecutorExitResult::TimedOut`] after this
+ * many seconds
+ */
+timeout_secs: bigint | null, 
+/**
+ * Seconds without output before the run is reported as stalled,
+ * overriding the user's setting. `Some(0)` turns stall detection off.
+ */
+stall_timeout_secs: bigint | null, };
 
 export type ScriptRequestLanguage = "Bash";
 
@@ -46,11 +376,128 @@ export type UpdateTag = { tag_name: string | null, content: string | null, };
 
 export type TagSearchParams = { search: string | null, };
 
+/**
+ * A reusable follow-up prompt. `{name}` placeholders in `body` are filled in
+ * when the template is sent.
+ */
+export type PromptTemplate = { id: string, 
+/**
+ * `None` for global templates, available in every project
+ */
+project_id: string | null, name: string, body: string, created_at: string, updated_at: string, };
+
+export type CreatePromptTemplate = { project_id: string | null, name: string, body: string, };
+
+export type UpdatePromptTemplate = { name: string | null, body: string | null, };
+
+export type PromptTemplateQuery = { 
+/**
+ * Limit to global templates and this project's own
+ */
+project_id: string | null, };
+
+/**
+ * A matched term within [`TaskSearchHit::snippet`], as `[start, end)` in
+ * UTF-16 code units so it can be applied to JavaScript strings directly
+ */
+export type SearchHighlight = { start: number, end: number, };
+
+export type TaskSearchHit = { 
+/**
+ * `None` for shared tasks that only exist on the remote server
+ */
+task_id: string | null, shared_task_id: string | null, 
+/**
+ * `None` if a remote-only task's project is not linked locally
+ */
+project_id: string | null, title: string, status: TaskStatus, 
+/**
+ * There is no local task to open; only the shared task exists
+ */
+remote_only: boolean, 
+/**
+ * Excerpt around the best match in the title, description or summary
+ */
+snippet: string, highlights: Array<SearchHighlight>, };
+
+export type TaskSearchQuery = { q: string, project_id: string | null, status: TaskStatus | null, 
+/**
+ * Defaults to 20, at most 100
+ */
+limit: number | null, offset: number | null, };
+
+export type TaskSearchResponse = { hits: Array<TaskSearchHit>, 
+/**
+ * Offset of the next page, if there is one
+ */
+next_offset: number | null, };
+
+export type ApiTokenScope = "read_only" | "task_execution";
+
+/**
+ * A bearer token for the local API. The secret itself is never stored.
+ */
+export type ApiToken = { id: string, name: string, scope: ApiTokenScope, expires_at: string | null, revoked_at: string | null, 
+/**
+ * Updated at most once a minute
+ */
+last_used_at: string | null, created_at: string, };
+
+export type CreateApiToken = { name: string, scope: ApiTokenScope, 
+/**
+ * Never expires when omitted
+ */
+expires_at: string | null, };
+
+export type CreateApiTokenResponse = { token: ApiToken, 
+/**
+ * The bearer value; it cannot be retrieved again
+ */
+value: string, };
+
 export type TaskStatus = "todo" | "inprogress" | "inreview" | "done" | "cancelled";
 
 export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, shared_task_id: string | null, created_at: string, updated_at: string, };
 
-export type TaskWithAttemptStatus = { has_in_progress_attempt: boolean, has_merged_attempt: boolean, last_attempt_failed: boolean, executor: string, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, shared_task_id: string | null, created_at: string, updated_at: string, };
+export type TaskWithAttemptStatus = { has_in_progress_attempt: boolean, has_merged_attempt: boolean, last_attempt_failed: boolean, 
+/**
+ * An attempt is waiting for a free execution slot
+ */
+has_queued_attempt: boolean, 
+/**
+ * Some task this one depends on is not done yet
+ */
+blocked: boolean, executor: string, last_verify_status: VerifyStatus | null, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, shared_task_id: string | null, created_at: string, updated_at: string, };
+
+export type TrashedTask = { deleted_at: string, 
+/**
+ * The shared task this task was detached from when it was deleted
+ */
+detached_shared_task_id: string | null, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, shared_task_id: string | null, created_at: string, updated_at: string, };
+
+export type QueuedAttempt = { task_attempt_id: string, task_id: string, task_title: string, project_id: string, executor_profile_id: ExecutorProfileId, 
+/**
+ * Started with `override`, so unfinished dependencies do not hold it
+ * back
+ */
+ignore_dependencies: boolean, 
+/**
+ * Zero-based place in the queue
+ */
+position: bigint, created_at: string, };
+
+export type QueuedFollowUp = { id: string, task_attempt_id: string, prompt: string, 
+/**
+ * Why it could not be sent when the last run finished; it is tried
+ * again after the next one
+ */
+blocked_reason: string | null, created_at: string, dispatched_at: string | null, };
+
+export type MoveQueuedAttemptRequest = { 
+/**
+ * Zero-based place to move the attempt to; past the end moves it last
+ */
+position: number, };
 
 export type TaskRelationships = { parent_task: Task | null, current_attempt: TaskAttempt, children: Array<Task>, };
 
@@ -60,11 +507,45 @@ export type UpdateTask = { title: string | null, description: string | null, sta
 
 export type SharedTask = { id: string, remote_project_id: string, title: string, description: string | null, status: TaskStatus, assignee_user_id: string | null, assignee_first_name: string | null, assignee_last_name: string | null, assignee_username: string | null, version: bigint, last_event_seq: bigint | null, created_at: Date, updated_at: Date, };
 
+/**
+ * Latest result of the periodic local/remote consistency check for a project.
+ */
+export type SharedTaskDivergenceReport = { remote_project_id: string, 
+/**
+ * Local rows that disagreed with the remote and were re-fetched
+ */
+mismatched_task_ids: string[], 
+/**
+ * Remote tasks that had no local row
+ */
+missing_task_ids: string[], 
+/**
+ * Local rows for tasks that are gone remotely and were removed
+ */
+stray_task_ids: string[], 
+/**
+ * When the divergence above was found, `None` if no check found any
+ */
+detected_at: Date | null, checked_at: Date, };
+
+/**
+ * How many changes are waiting to be synced and why the last replay failed.
+ */
+export type SharedTaskOutboxStatus = { pending: bigint, 
+/**
+ * Why replaying the oldest failing change last failed
+ */
+last_error: string | null, oldest_created_at: string | null, };
+
 export type Image = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
 
 export type CreateImage = { file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, };
 
-export type ApiResponse<T, E = T> = { success: boolean, data: T | null, error_data: E | null, message: string | null, };
+export type ApiResponse<T, E = T> = { success: boolean, data: T | null, error_data: E | null, message: string | null, 
+/**
+ * Id of the failed request, to find it in the server logs
+ */
+request_id?: string, };
 
 export type LoginStatus = { "status": "loggedout" } | { "status": "loggedin", profile: ProfileResponse, };
 
@@ -74,11 +555,37 @@ export type ProviderProfile = { provider: string, username: string | null, displ
 
 export type StatusResponse = { logged_in: boolean, profile: ProfileResponse | null, degraded: boolean | null, };
 
+export type AuthSessionSummary = { id: string, created_at: Date, 
+/**
+ * Day the session was last used; `null` if only on the day it was created
+ */
+last_used_at: Date | null, user_agent: string | null, 
+/**
+ * The session the request was made with
+ */
+current: boolean, };
+
+export type ListSessionsResponse = { sessions: Array<AuthSessionSummary>, };
+
+export type RevokeSessionResponse = { 
+/**
+ * The revoked session was the one the request was made with, i.e. the
+ * caller is now signed out
+ */
+current: boolean, };
+
+export type HandoffProgress = { "status": "pending" } | { "status": "redeeming" } | { "status": "completed", provider: string, } | { "status": "failed", error: string, } | { "status": "expired" } | { "status": "unknown" };
+
 export enum MemberRole { ADMIN = "ADMIN", MEMBER = "MEMBER" }
 
 export enum InvitationStatus { PENDING = "PENDING", ACCEPTED = "ACCEPTED", DECLINED = "DECLINED", EXPIRED = "EXPIRED" }
 
-export type Organization = { id: string, name: string, slug: string, is_personal: boolean, created_at: string, updated_at: string, };
+export type Organization = { id: string, name: string, slug: string, is_personal: boolean, 
+/**
+ * Invitee email domains allowed to join; empty allows any. Ignored for
+ * personal organizations.
+ */
+allowed_email_domains: Array<string>, created_at: string, updated_at: string, };
 
 export type OrganizationWithRole = { id: string, name: string, slug: string, is_personal: boolean, created_at: string, updated_at: string, user_role: MemberRole, };
 
@@ -90,7 +597,11 @@ export type CreateOrganizationRequest = { name: string, slug: string, };
 
 export type CreateOrganizationResponse = { organization: OrganizationWithRole, };
 
-export type UpdateOrganizationRequest = { name: string, };
+export type UpdateOrganizationRequest = { name: string, 
+/**
+ * Replaces the allowed invitee email domains when present
+ */
+allowed_email_domains?: Array<string>, };
 
 export type Invitation = { id: string, organization_id: string, invited_by_user_id: string | null, email: string, role: MemberRole, status: InvitationStatus, token: string, created_at: string, expires_at: string, };
 
@@ -116,6 +627,28 @@ export type UpdateMemberRoleRequest = { role: MemberRole, };
 
 export type UpdateMemberRoleResponse = { user_id: string, role: MemberRole, };
 
+export type AuditAction = "member_added" | "member_removed" | "member_role_changed" | "invitation_created" | "invitation_revoked" | "invitation_accepted" | "organization_renamed" | "organization_deleted";
+
+export type AuditLogEntry = { id: string, organization_id: string, 
+/**
+ * `None` once the acting user has been deleted
+ */
+actor_user_id: string | null, action: AuditAction, target_user_id: string | null, 
+/**
+ * Action-specific context such as the previous and new role
+ */
+details: Record<string, unknown>, created_at: string, };
+
+export type ListAuditLogResponse = { 
+/**
+ * Newest first
+ */
+entries: Array<AuditLogEntry>, 
+/**
+ * Pass as `before` to fetch the next page; `None` on the last page
+ */
+next_cursor: string | null, };
+
 export type RemoteProject = { id: string, organization_id: string, name: string, metadata: Record<string, unknown>, created_at: string, };
 
 export type ListProjectsResponse = { projects: Array<RemoteProject>, };
@@ -126,25 +659,115 @@ export type UserSystemInfo = { config: Config, analytics_user_id: string, login_
 /**
  * Capabilities supported per executor (e.g., { "CLAUDE_CODE": ["SESSION_FORK"] })
  */
-capabilities: { [key in string]?: Array<BaseAgentCapability> }, executors: { [key in BaseCodingAgent]?: ExecutorConfig }, };
+capabilities: { [key in string]?: Array<BaseAgentCapability> }, 
+/**
+ * Outcome of picking up edits made to the config file on disk
+ */
+config_reload: ConfigReloadStatus, executors: { [key in BaseCodingAgent]?: ExecutorConfig }, };
+
+export type ConfigReloadStatus = { 
+/**
+ * When edits made on disk were last applied
+ */
+last_reload_at: Date | null, 
+/**
+ * Why the file on disk was last rejected; cleared by the next good reload
+ */
+last_error: string | null, };
 
 export type Environment = { os_type: string, os_version: string, os_architecture: string, bitness: string, };
 
-export type McpServerQuery = { executor: BaseCodingAgent, };
+export type McpServerQuery = { executor: BaseCodingAgent, 
+/**
+ * Only used when updating servers
+ */
+validate?: McpValidateMode, };
 
 export type UpdateMcpServersBody = { servers: { [key in string]?: JsonValue }, };
 
+export type UpdateMcpServersResponse = { message: string, 
+/**
+ * Per-server results for `validate=spawn`, in which case nothing was saved
+ */
+spawn_checks: { [key in string]?: McpSpawnCheck }, };
+
+/**
+ * Returned as `error_data` when server entries are malformed
+ */
+export type McpServerProblems = { 
+/**
+ * Problems keyed by server name
+ */
+servers: { [key in string]?: Array<string> }, };
+
+export type McpValidateMode = "spawn";
+
+export type RestoreMcpBackupBody = { id: string, };
+
+export type McpConfigBackup = { 
+/**
+ * File name within the executor's backup directory
+ */
+id: string, created_at: Date, size_bytes: bigint, };
+
+export type McpSpawnCheck = { status: McpSpawnStatus, detail: string | null, };
+
+export type McpSpawnStatus = "passed" | "failed" | "skipped";
+
 export type GetMcpServerResponse = { mcp_config: McpConfig, config_path: string, };
 
 export type CheckEditorAvailabilityQuery = { editor_type: EditorType, };
 
 export type CheckEditorAvailabilityResponse = { available: boolean, };
 
+export type OpenFileInEditorRequest = { project_id: string, 
+/**
+ * Resolve `path` against this attempt's worktree rather than the
+ * project's repository
+ */
+attempt_id: string | null, 
+/**
+ * Relative to the project or worktree root
+ */
+path: string, line: number | null, editor_type: string | null, };
+
 export type CheckAgentAvailabilityQuery = { executor: BaseCodingAgent, };
 
 export type AvailabilityInfo = { "type": "LOGIN_DETECTED", last_auth_timestamp: bigint, } | { "type": "INSTALLATION_FOUND" } | { "type": "NOT_FOUND" };
 
-export type CreateFollowUpAttempt = { prompt: string, variant: string | null, image_ids: Array<string> | null, retry_process_id: string | null, force_when_dirty: boolean | null, perform_git_reset: boolean | null, };
+export type ExecutorHealth = { available: boolean, 
+/**
+ * Program the executor launches, e.g. `npx` or `droid`
+ */
+program: string, 
+/**
+ * Where `program` resolves on the server's PATH
+ */
+resolved_path: string | null, 
+/**
+ * Output of `--version`, or the pinned package for package runners
+ */
+version: string | null, 
+/**
+ * Why the executor cannot be started
+ */
+error: string | null, };
+
+export type ExecutorProfileHealth = { profile: ExecutorProfileId, health: ExecutorHealth, };
+
+export type CreateFollowUpAttempt = { 
+/**
+ * Ignored when `template_id` is set
+ */
+prompt: string, 
+/**
+ * Send this prompt template instead of `prompt`
+ */
+template_id?: string, 
+/**
+ * Values for the template's `{placeholders}`
+ */
+variables?: { [key in string]?: string }, variant: string | null, image_ids: Array<string> | null, retry_process_id: string | null, force_when_dirty: boolean | null, perform_git_reset: boolean | null, };
 
 export type DraftResponse = { task_attempt_id: string, draft_type: DraftType, retry_process_id: string | null, prompt: string, queued: boolean, variant: string | null, image_ids: Array<string> | null, version: bigint, };
 
@@ -152,6 +775,8 @@ export type UpdateFollowUpDraftRequest = { prompt: string | null, variant: strin
 
 export type UpdateRetryFollowUpDraftRequest = { retry_process_id: string, prompt: string | null, variant: string | null | null, image_ids: Array<string> | null, version: bigint | null, };
 
+export type EnqueueFollowUpRequest = { prompt: string, };
+
 export type ChangeTargetBranchRequest = { new_target_branch: string, };
 
 export type ChangeTargetBranchResponse = { new_target_branch: string, status: [number, number], };
@@ -170,17 +795,127 @@ export type AssignSharedTaskRequest = { new_assignee_user_id: string | null, ver
 
 export type AssignSharedTaskResponse = { shared_task: SharedTask, };
 
+export type SharedSyncStatus = { remote_project_id: string, last_seq: bigint | null, 
+/**
+ * Outcome of the most recent consistency check, if one has run
+ */
+divergence: SharedTaskDivergenceReport | null, };
+
+export type LocalTaskLink = { task_id: string, project_id: string, status: TaskStatus, attempt_count: number, 
+/**
+ * Most recently created attempt
+ */
+latest_attempt_id: string | null, has_in_progress_attempt: boolean, };
+
+export type AssignedSharedTask = { shared_task: SharedTask, 
+/**
+ * `None` if no task on this machine is linked to it yet
+ */
+local_task: LocalTaskLink | null, };
+
+export type AssignedSharedTasksResponse = { 
+/**
+ * Most recently updated first
+ */
+tasks: Array<AssignedSharedTask>, 
+/**
+ * Offset of the next page, if there is one
+ */
+next_offset: number | null, };
+
+export type ProjectSyncState = "waiting_auth" | "catching_up" | "connected" | "backoff" | "link_broken";
+
+export type ProjectSyncStatus = { remote_project_id: string, state: ProjectSyncState, 
+/**
+ * Last activity sequence number applied locally
+ */
+last_seq: bigint | null, 
+/**
+ * When the last activity event arrived over the websocket
+ */
+last_event_at: string | null, 
+/**
+ * When the watcher tries again, while waiting or backing off
+ */
+retry_at: string | null, 
+/**
+ * Most recent failure, kept after the watcher recovers
+ */
+last_error: string | null, };
+
 export type ShareTaskResponse = { shared_task_id: string, };
 
-export type CreateAndStartTaskRequest = { task: CreateTask, executor_profile_id: ExecutorProfileId, base_branch: string, };
+export type AddTaskDependencyRequest = { depends_on_task_id: string, };
 
-export type CreateGitHubPrRequest = { title: string, body: string | null, target_branch: string | null, };
+export type CreateAndStartTaskRequest = { task: CreateTask, 
+/**
+ * Falls back to the project's default executor, then the global one
+ */
+executor_profile_id: ExecutorProfileId | null, base_branch: string, };
+
+export type CreateGitHubPrRequest = { title: string, body: string | null, target_branch: string | null, 
+/**
+ * Replace `body` with a draft written from the task and the attempt's
+ * conversation
+ */
+generate_body: boolean, 
+/**
+ * Open the PR as a draft
+ */
+draft: boolean, };
 
 export type ImageResponse = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
 
-export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, };
+export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, 
+/**
+ * Minutes a coding agent or script may go without output before it is
+ * reported as stalled. 0 disables stall detection.
+ */
+executor_stall_timeout_minutes: number, 
+/**
+ * Attempts allowed to run a coding agent at once across all projects;
+ * further attempts are queued. 0 means no limit.
+ */
+max_concurrent_executions: number, 
+/**
+ * Redact every supported response regardless of the request, for kiosk
+ * and demo machines
+ */
+privacy_mode: PrivacyMode | null, 
+/**
+ * How process output is batched before being written to the database
+ */
+log_batching: LogBatchConfig, 
+/**
+ * Hours an attempt must sit idle before its worktree is cleaned up
+ */
+worktree_retention_hours: number, 
+/**
+ * Combined size of a project's worktrees, in GB, above which a
+ * notification is sent. 0 disables the warning.
+ */
+worktree_disk_warning_gb: number, 
+/**
+ * Days a deleted task stays in the trash before it is removed for good,
+ * with its attempts and worktrees. 0 keeps deleted tasks until restored.
+ */
+task_trash_retention_days: number, 
+/**
+ * Extra CLI arguments appended to every command of an executor, keyed by
+ * executor type (e.g. `CLAUDE_CODE`)
+ */
+executor_args: { [key in string]?: Array<string> }, 
+/**
+ * Hosts of self-hosted GitLab instances, e.g. `gitlab.example.com`.
+ * gitlab.com is always recognised.
+ */
+gitlab_hosts: Array<string>, };
 
-export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, };
+export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, 
+/**
+ * Notify when someone else assigns a shared task to you
+ */
+shared_task_assigned: boolean, };
 
 export enum ThemeMode { LIGHT = "LIGHT", DARK = "DARK", SYSTEM = "SYSTEM" }
 
@@ -188,7 +923,7 @@ export type EditorConfig = { editor_type: EditorType, custom_command: string | n
 
 export enum EditorType { VS_CODE = "VS_CODE", CURSOR = "CURSOR", WINDSURF = "WINDSURF", INTELLI_J = "INTELLI_J", ZED = "ZED", XCODE = "XCODE", CUSTOM = "CUSTOM" }
 
-export type EditorOpenError = { "type": "executable_not_found", executable: string, editor_type: EditorType, } | { "type": "invalid_command", details: string, editor_type: EditorType, } | { "type": "launch_failed", executable: string, details: string, editor_type: EditorType, };
+export type EditorOpenError = { "type": "executable_not_found", executable: string, editor_type: EditorType, } | { "type": "invalid_command", details: string, editor_type: EditorType, } | { "type": "launch_failed", executable: string, details: string, editor_type: EditorType, } | { "type": "invalid_path", path: string, details: string, };
 
 export type GitHubConfig = { pat: string | null, oauth_token: string | null, username: string | null, primary_email: string | null, default_pr_base: string | null, };
 
@@ -198,6 +933,18 @@ export type UiLanguage = "BROWSER" | "EN" | "JA" | "ES" | "KO";
 
 export type ShowcaseState = { seen_features: Array<string>, };
 
+/**
+ * Ordered from least to most redaction, so the stricter of a requested and
+ * a forced mode is `max(a, b)`.
+ */
+export type PrivacyMode = "paths_only" | "redact_content";
+
+export type LogBatchConfig = { 
+/**
+ * Longest time output may sit in the buffer before it is written
+ */
+flush_interval_ms: number, max_batch_bytes: number, max_batch_lines: number, };
+
 export type GitBranch = { name: string, is_current: boolean, is_remote: boolean, last_commit_date: Date, };
 
 export type Diff = { change: DiffChangeKind, oldPath: string | null, newPath: string | null, oldContent: string | null, newContent: string | null, 
@@ -222,6 +969,25 @@ base: string,
  */
 params: Array<string> | null, };
 
+export type CommandPreview = { program: string, 
+/**
+ * Where `program` resolves on the server's PATH, if it does
+ */
+resolved_program: string | null, args: Array<string>, 
+/**
+ * Variables the executor sets on top of the server's environment
+ */
+env: { [key in string]?: string }, 
+/**
+ * Secret-looking variables the process would inherit from the server.
+ * Only names are listed.
+ */
+inherited_secrets: Array<string>, working_dir: string, 
+/**
+ * Shell line that reproduces the invocation
+ */
+command_line: string, };
+
 export type ExecutorProfileId = { 
 /**
  * The executor type (e.g., "CLAUDE_CODE", "AMP")
@@ -238,6 +1004,36 @@ export type ExecutorConfigs = { executors: { [key in BaseCodingAgent]?: Executor
 
 export enum BaseAgentCapability { SESSION_FORK = "SESSION_FORK", SETUP_HELPER = "SETUP_HELPER" }
 
+/**
+ * What an executor supports. Every executor declares these once, in
+ * [`BaseCodingAgent::capabilities`].
+ */
+export type ExecutorCapabilities = { supports_mcp: boolean, supports_followup: boolean, supports_plan_mode: boolean, 
+/**
+ * Follow-ups continue a fork of the session rather than the session
+ * itself
+ */
+supports_session_fork: boolean, 
+/**
+ * Needs a setup script (login, installation) before its first run
+ */
+requires_setup_helper: boolean, 
+/**
+ * Reports a session id that follow-ups can resume
+ */
+produces_session_id: boolean, 
+/**
+ * `None` when the executor has no MCP configuration
+ */
+config_file_format: ConfigFileFormat | null, };
+
+/**
+ * Format of the file an executor reads its MCP servers from.
+ */
+export type ConfigFileFormat = "json" | "toml";
+
+export type ExecutorInfo = { executor: BaseCodingAgent, display_name: string, capabilities: ExecutorCapabilities, };
+
 export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, approvals?: boolean | null, model?: string | null, dangerously_skip_permissions?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
 
 export type Gemini = { append_prompt: AppendPrompt, model?: string | null, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
@@ -279,6 +1075,11 @@ export type CodingAgentInitialRequest = { prompt: string,
 executor_profile_id: ExecutorProfileId, };
 
 export type CodingAgentFollowUpRequest = { prompt: string, session_id: string, 
+/**
+ * The task's own prompt. Used to brief a fresh session when `session_id`
+ * can no longer be resumed.
+ */
+task_context: string | null, 
 /**
  * Executor profile specification
  */
@@ -286,9 +1087,24 @@ executor_profile_id: ExecutorProfileId, };
 
 export type CreateTaskAttemptBody = { task_id: string, 
 /**
- * Executor profile specification
+ * Executor profile specification. Falls back to the project's default
+ * executor, then the global one.
  */
-executor_profile_id: ExecutorProfileId, base_branch: string, };
+executor_profile_id: ExecutorProfileId | null, base_branch: string, 
+/**
+ * Start even though tasks this one depends on are not done yet
+ */
+override: boolean, };
+
+export type RetryTaskAttemptRequest = { 
+/**
+ * Defaults to the profile the original attempt started with
+ */
+executor_profile_id?: ExecutorProfileId, 
+/**
+ * Retry even though another attempt of the task is running
+ */
+allow_parallel: boolean, };
 
 export type RunAgentSetupRequest = { executor_profile_id: ExecutorProfileId, };
 
@@ -302,7 +1118,7 @@ export type GitOperationError = { "type": "merge_conflicts", message: string, op
 
 export type PushError = { "type": "force_push_required" };
 
-export type CreatePrError = { "type": "github_cli_not_installed" } | { "type": "github_cli_not_logged_in" } | { "type": "git_cli_not_logged_in" } | { "type": "git_cli_not_installed" } | { "type": "target_branch_not_found", branch: string, };
+export type CreatePrError = { "type": "github_cli_not_installed" } | { "type": "github_cli_not_logged_in" } | { "type": "gitlab_cli_not_installed" } | { "type": "gitlab_cli_not_logged_in" } | { "type": "git_cli_not_logged_in" } | { "type": "git_cli_not_installed" } | { "type": "target_branch_not_found", branch: string, };
 
 export type CommitInfo = { sha: string, subject: string, };
 
@@ -322,7 +1138,65 @@ conflicted_files: Array<string>, };
 
 export type ConflictOp = "rebase" | "merge" | "cherry_pick" | "revert";
 
-export type TaskAttempt = { id: string, task_id: string, container_ref: string | null, branch: string, target_branch: string, executor: string, worktree_deleted: boolean, setup_completed_at: string | null, created_at: string, updated_at: string, };
+export type DiffFileSummary = { change: DiffChangeKind, path: string, 
+/**
+ * Previous path for renames and copies
+ */
+old_path: string | null, 
+/**
+ * Line counts; `None` for binary files
+ */
+additions: number | null, deletions: number | null, binary: boolean, 
+/**
+ * Unified diff for this file, when patches were requested
+ */
+patch: string | null, 
+/**
+ * True when the patch was dropped to stay within the size cap
+ */
+patch_omitted: boolean, };
+
+export type CommitSummary = { sha: string, subject: string, };
+
+export type ExecutionProcessCommits = { before_head_commit: string | null, 
+/**
+ * `None` while the process is still running
+ */
+after_head_commit: string | null, commits: Array<CommitSummary>, history_rewritten: boolean, };
+
+/**
+ * Outcome of the latest run of the project's verify command for an attempt
+ */
+export type VerifyStatus = "running" | "passed" | "failed" | "timed_out";
+
+export type TaskAttempt = { id: string, task_id: string, container_ref: string | null, branch: string, target_branch: string, executor: string, worktree_deleted: boolean, setup_completed_at: string | null, verify_status: VerifyStatus | null, verify_output: string | null, retried_from_attempt_id: string | null, created_at: string, updated_at: string, };
+
+export type AttemptEventType = "worktree_created" | "queued" | "process_started" | "process_completed" | "process_failed" | "process_killed" | "follow_up_requested" | "pr_opened" | "pr_merged" | "branch_merged" | "rebased" | "worktree_removed";
+
+export type AttemptEvent = { id: string, task_attempt_id: string, event_type: AttemptEventType, payload: JsonValue, created_at: string, };
+
+/**
+ * The latest plan a plan-mode run presented for an attempt.
+ */
+export type TaskAttemptPlan = { task_attempt_id: string, 
+/**
+ * The run that presented the plan
+ */
+execution_process_id: string, 
+/**
+ * The plan as the agent wrote it
+ */
+plan: string, steps: Array<PlanStep>, created_at: string, updated_at: string, };
+
+export type PlanStep = { 
+/**
+ * 1-based position in the plan
+ */
+number: number, 
+/**
+ * The step's own line followed by any nested lines, without its marker
+ */
+text: string, };
 
 export type ExecutionProcess = { id: string, task_attempt_id: string, run_reason: ExecutionProcessRunReason, executor_action: ExecutorAction, 
 /**
@@ -338,11 +1212,43 @@ after_head_commit: string | null, status: ExecutionProcessStatus, exit_code: big
  * history view (due to restore/trimming). Hidden from logs/timeline;
  * still listed in the Processes tab.
  */
-dropped: boolean, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
+dropped: boolean, 
+/**
+ * URL the dev server reported it is listening on, detected from its output
+ */
+dev_server_url: string | null, 
+/**
+ * true if the dev server output reported its port was already in use
+ */
+dev_server_port_conflict: boolean, 
+/**
+ * When the process went quiet for longer than the stall timeout; cleared
+ * if its output resumes
+ */
+stalled_at: string | null, 
+/**
+ * CPU time the process used, sampled while it ran; `None` if it could
+ * not be measured
+ */
+cpu_time_ms: bigint | null, 
+/**
+ * Largest resident memory seen while sampling
+ */
+peak_rss_bytes: bigint | null, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
+
+/**
+ * CPU time and peak memory of one of an attempt's processes
+ */
+export type ProcessResourceUsage = { execution_process_id: string, run_reason: ExecutionProcessRunReason, cpu_time_ms: bigint | null, peak_rss_bytes: bigint | null, };
 
 export enum ExecutionProcessStatus { running = "running", completed = "completed", failed = "failed", killed = "killed" }
 
-export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "codingagent" | "devserver";
+export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "codingagent" | "devserver" | "verifyscript";
+
+/**
+ * Token totals for one task, across all of its attempts.
+ */
+export type TaskUsage = { task_id: string, task_title: string, usage: UsageSummary, };
 
 export type Merge = { "type": "direct" } & DirectMerge | { "type": "pr" } & PrMerge;
 
@@ -362,6 +1268,32 @@ export type CommandExitStatus = { "type": "exit_code", code: number, } | { "type
 
 export type CommandRunResult = { exit_status: CommandExitStatus | null, output: string | null, };
 
+export type NormalizedConversation = { entries: Array<NormalizedEntry>, session_id: string | null, executor_type: string, prompt: string | null, summary: string | null, 
+/**
+ * Tokens used by the conversation's agent runs, if any reported usage
+ */
+usage: UsageSummary | null, 
+/**
+ * Some run started without MCP servers that failed to launch
+ */
+mcp_degraded: boolean, };
+
+/**
+ * Token counts for one or more agent runs. Counts are disjoint, so the
+ * tokens billed as input are `input_tokens + cache_read_input_tokens +
+ * cache_creation_input_tokens`.
+ */
+export type UsageSummary = { 
+/**
+ * Input tokens not read from or written to the prompt cache
+ */
+input_tokens: bigint, output_tokens: bigint, cache_read_input_tokens: bigint, cache_creation_input_tokens: bigint, 
+/**
+ * Cost reported by the agent. `None` when any of the summed runs did not
+ * report one.
+ */
+cost_usd: number | null, };
+
 export type NormalizedEntry = { timestamp: string | null, entry_type: NormalizedEntryType, content: string, };
 
 export type NormalizedEntryType = { "type": "user_message" } | { "type": "user_feedback", denied_tool: string, } | { "type": "assistant_message" } | { "type": "tool_use", tool_name: string, action_type: ActionType, status: ToolStatus, } | { "type": "system_message" } | { "type": "error_message", error_type: NormalizedEntryError, } | { "type": "thinking" } | { "type": "loading" } | { "type": "next_action", failed: boolean, execution_processes: number, needs_setup: boolean, };
@@ -376,7 +1308,7 @@ unified_diff: string,
  */
 has_line_numbers: boolean, };
 
-export type ActionType = { "action": "file_read", path: string, } | { "action": "file_edit", path: string, changes: Array<FileChange>, } | { "action": "command_run", command: string, result: CommandRunResult | null, } | { "action": "search", query: string, } | { "action": "web_fetch", url: string, } | { "action": "tool", tool_name: string, arguments: JsonValue | null, result: ToolResult | null, } | { "action": "task_create", description: string, } | { "action": "plan_presentation", plan: string, } | { "action": "todo_management", todos: Array<TodoItem>, operation: string, } | { "action": "other", description: string, };
+export type ActionType = { "action": "file_read", path: string, } | { "action": "file_edit", path: string, changes: Array<FileChange>, } | { "action": "file_delete", path: string, } | { "action": "file_move", from: string, to: string, } | { "action": "command_run", command: string, result: CommandRunResult | null, } | { "action": "search", query: string, } | { "action": "web_fetch", url: string, } | { "action": "tool", tool_name: string, arguments: JsonValue | null, result: ToolResult | null, } | { "action": "task_create", description: string, } | { "action": "plan_presentation", plan: string, } | { "action": "todo_management", todos: Array<TodoItem>, operation: string, } | { "action": "other", description: string, };
 
 export type TodoItem = { content: string, status: string, priority: string | null, };
 
@@ -394,6 +1326,26 @@ export type ToolStatus = { "status": "created" } | { "status": "success" } | { "
 
 export type PatchType = { "type": "NORMALIZED_ENTRY", "content": NormalizedEntry } | { "type": "STDOUT", "content": string } | { "type": "STDERR", "content": string } | { "type": "DIFF", "content": Diff };
 
+export type EntryUpdate = { "op": "upsert", index: number, entry: NormalizedEntry, } | { "op": "remove", index: number, };
+
+export type ConversationComparison = { total: number, offset: number, has_more: boolean, pairs: Array<AlignedEntryPair>, };
+
+/**
+ * One row of the side-by-side view. `left_index`/`right_index` point into the
+ * respective conversations; either is missing for added/removed rows.
+ */
+export type AlignedEntryPair = { index: number, kind: AlignmentKind, left_index: number | null, right_index: number | null, left: NormalizedEntry | null, right: NormalizedEntry | null, 
+/**
+ * Word-level diff, only for changed assistant message pairs
+ */
+text_diff: Array<WordDiffSegment> | null, };
+
+export type AlignmentKind = "unchanged" | "changed" | "added" | "removed";
+
+export type WordDiffSegment = { kind: WordDiffKind, text: string, };
+
+export type WordDiffKind = "equal" | "insert" | "delete";
+
 export type ApprovalStatus = { "status": "pending" } | { "status": "approved" } | { "status": "denied", reason?: string, } | { "status": "timed_out" };
 
 export type CreateApprovalRequest = { tool_name: string, tool_input: JsonValue, tool_call_id: string, };